@@ -30,7 +30,6 @@ const MASK: usize = CAPACITY - 1;
 
 /// This benchmark differs from `put.rs` benchmark in a way that it performs an `.await` operation on the result of `put` operation.
 /// As a part of this benchmark we leverage `to_async` function of `criterion` and use `tokio` as the async runtime.
-
 #[cfg(feature = "bench_testable")]
 #[cfg(not(tarpaulin_include))]
 pub fn async_put_single_task(criterion: &mut Criterion) {
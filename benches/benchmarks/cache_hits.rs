@@ -1,9 +1,9 @@
-use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 
 use criterion::{Criterion, criterion_group, criterion_main};
 use tokio::runtime::Builder;
 
+use tinylfu_cached::cache::bench_util::HitRatioRecorder;
 use tinylfu_cached::cache::cached::CacheD;
 use tinylfu_cached::cache::config::ConfigBuilder;
 use tinylfu_cached::cache::types::{TotalCounters, Weight};
@@ -27,32 +27,6 @@ const ITEMS: usize = CAPACITY * 16;
 
 /// This benchmark uses 0.9, and 1.001 as the Zipf distribution exponent.
 /// For now, this benchmark prints the cache-hit ratio on console and the cache-hits.json under results/ is manually prepared.
-
-#[derive(Debug)]
-struct HitsMissRecorder {
-    hits: AtomicU64,
-    miss: AtomicU64,
-}
-
-impl HitsMissRecorder {
-    #[cfg(not(tarpaulin_include))]
-    fn new() -> Self {
-        HitsMissRecorder {
-            hits: AtomicU64::new(0),
-            miss: AtomicU64::new(0),
-        }
-    }
-
-    #[cfg(not(tarpaulin_include))]
-    fn record_hit(&self) { self.hits.fetch_add(1, Ordering::SeqCst); }
-    #[cfg(not(tarpaulin_include))]
-    fn record_miss(&self) { self.miss.fetch_add(1, Ordering::SeqCst); }
-    #[cfg(not(tarpaulin_include))]
-    fn ratio(&self) -> f64 {
-        (self.hits.load(Ordering::SeqCst) as f64 / (self.hits.load(Ordering::SeqCst) + self.miss.load(Ordering::SeqCst)) as f64) * 100.0
-    }
-}
-
 #[cfg(feature = "bench_testable")]
 #[cfg(not(tarpaulin_include))]
 pub fn cache_hits_single_threaded_exponent_1_001(criterion: &mut Criterion) {
@@ -63,12 +37,12 @@ pub fn cache_hits_single_threaded_exponent_1_001(criterion: &mut Criterion) {
             .build()
             .unwrap();
 
-        bencher.to_async(runtime).iter_custom(|iterations| {
+        bencher.to_async(runtime).iter_custom(|_iterations| {
             async move {
                 let cached = CacheD::new(ConfigBuilder::new(COUNTERS, CAPACITY, WEIGHT).build());
                 let distribution = distribution_with_exponent(ITEMS as u64, ITEMS, 1.001);
 
-                let hit_miss_recorder = HitsMissRecorder::new();
+                let hit_miss_recorder = HitRatioRecorder::new();
                 let mut index = 0;
 
                 let start = Instant::now();
@@ -83,7 +57,7 @@ pub fn cache_hits_single_threaded_exponent_1_001(criterion: &mut Criterion) {
                     index += 1;
                 }
                 cached.shutdown();
-                println!("{:?} %", hit_miss_recorder.ratio());
+                println!("{:?} %", hit_miss_recorder.hit_ratio_as_percentage());
                 start.elapsed()
             }
         });
@@ -100,12 +74,12 @@ pub fn cache_hits_single_threaded_exponent_0_9(criterion: &mut Criterion) {
             .build()
             .unwrap();
 
-        bencher.to_async(runtime).iter_custom(|iterations| {
+        bencher.to_async(runtime).iter_custom(|_iterations| {
             async move {
                 let cached = CacheD::new(ConfigBuilder::new(COUNTERS, CAPACITY, WEIGHT).build());
                 let distribution = distribution_with_exponent(ITEMS as u64, ITEMS, 0.9);
 
-                let hit_miss_recorder = HitsMissRecorder::new();
+                let hit_miss_recorder = HitRatioRecorder::new();
                 let mut index = 0;
 
                 let start = Instant::now();
@@ -120,7 +94,7 @@ pub fn cache_hits_single_threaded_exponent_0_9(criterion: &mut Criterion) {
                     index += 1;
                 }
                 cached.shutdown();
-                println!("{:?} %", hit_miss_recorder.ratio());
+                println!("{:?} %", hit_miss_recorder.hit_ratio_as_percentage());
                 start.elapsed()
             }
         });
@@ -6,10 +6,9 @@ use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
 use criterion::Criterion;
-use rand::{Rng, thread_rng};
-use rand_distr::Zipf;
 use tokio::runtime::Builder;
 
+use tinylfu_cached::cache::bench_util::{preload, zipf_distribution};
 use tinylfu_cached::cache::cached::CacheD;
 
 #[cfg(feature = "bench_testable")]
@@ -92,12 +91,12 @@ pub fn distribution(items: u64, capacity: usize) -> Vec<u64> {
 #[cfg(feature = "bench_testable")]
 #[cfg(not(tarpaulin_include))]
 pub fn distribution_with_exponent(items: u64, capacity: usize, exponent: f64) -> Vec<u64> {
-    thread_rng().sample_iter(Zipf::new(items, exponent).unwrap()).take(capacity).map(|value| value as u64).collect::<Vec<_>>()
+    zipf_distribution(items, capacity, exponent)
 }
 
 #[cfg(feature = "bench_testable")]
 #[cfg(not(tarpaulin_include))]
-pub fn preload_cache<Value, F>(cached: &CacheD<u64, Value>, distribution: &Vec<u64>, value_generation: F)
+pub fn preload_cache<Value, F>(cached: &CacheD<u64, Value>, distribution: &[u64], value_generation: F)
     where Value: Send + Sync + 'static,
           F: Fn(u64) -> Value {
     Builder::new_current_thread()
@@ -105,20 +104,10 @@ pub fn preload_cache<Value, F>(cached: &CacheD<u64, Value>, distribution: &Vec<u
         .build()
         .unwrap()
         .block_on(async {
-            setup(cached, distribution, value_generation).await;
+            preload(cached, distribution, value_generation).await;
         });
 }
 
-#[cfg(not(tarpaulin_include))]
-async fn setup<Value, F>(cached: &CacheD<u64, Value>, distribution: &Vec<u64>, value_generation: F)
-    where Value: Send + Sync + 'static,
-          F: Fn(u64) -> Value {
-    for key in distribution {
-        let value = value_generation(*key);
-        cached.put(*key, value).unwrap().handle().await;
-    }
-}
-
 #[cfg(not(tarpaulin_include))]
 fn spawn_threads<F>(block: Arc<F>, thread_count: u8, iterations: u64) -> Vec<JoinHandle<Duration>> where F: Fn(u64) + Send + Sync + 'static {
     let per_thread_iterations = iterations / thread_count as u64;
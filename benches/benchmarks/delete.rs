@@ -28,7 +28,6 @@ const ITEMS: usize = CAPACITY / 3;
 const MASK: usize = CAPACITY - 1;
 
 /// This benchmark preloads the cache with the total number of elements = CAPACITY.
-
 #[cfg(feature = "bench_testable")]
 #[cfg(not(tarpaulin_include))]
 pub fn delete_single_threaded(criterion: &mut Criterion) {
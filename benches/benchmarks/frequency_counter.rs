@@ -17,9 +17,8 @@ const MASK: usize = CAPACITY - 1;
 /// treating each element of the distribution as a KeyHash.
 /// In order to benchmark the `estimate` method, we perform `setup` which loads the distribution in the `FrequencyCounter`
 /// and then we invoke the `estimate` method a total of CAPACITY times by treating each element of the distribution as a KeyHash.
-
+///
 /// This benchmark also varies the `total_counters` which is CAPACITY, CAPACITY*2, CAPACITY*10
-
 #[cfg(feature = "bench_testable")]
 #[cfg(not(tarpaulin_include))]
 pub fn increase_frequency(criterion: &mut Criterion) {
@@ -28,6 +28,7 @@ const MASK: usize = CAPACITY - 1;
 
 /// Non-cloneable value type that is loaded in the Cache
 struct ValueRef {
+    #[allow(dead_code)]
     value: u64,
 }
 
@@ -66,9 +66,20 @@ pub fn put_32_threads(criterion: &mut Criterion) {
     execute_parallel(criterion, "Cached.put() | 32 threads", prepare_execution_block(), 32);
 }
 
+#[cfg(feature = "bench_testable")]
+#[cfg(not(tarpaulin_include))]
+pub fn put_16_threads_4_command_executor_threads(criterion: &mut Criterion) {
+    execute_parallel(criterion, "Cached.put() | 16 threads | 4 command executor threads", prepare_execution_block_with(4), 16);
+}
+
 #[cfg(not(tarpaulin_include))]
 fn prepare_execution_block() -> Arc<impl Fn(u64) + Send + Sync + 'static> {
-    let cached = CacheD::new(ConfigBuilder::new(COUNTERS, CAPACITY, WEIGHT).build());
+    prepare_execution_block_with(1)
+}
+
+#[cfg(not(tarpaulin_include))]
+fn prepare_execution_block_with(command_executor_threads: usize) -> Arc<impl Fn(u64) + Send + Sync + 'static> {
+    let cached = CacheD::new(ConfigBuilder::new(COUNTERS, CAPACITY, WEIGHT).command_executor_threads(command_executor_threads).build());
     let distribution = distribution(ITEMS as u64, CAPACITY);
 
     Arc::new(move |index| {
@@ -77,5 +88,5 @@ fn prepare_execution_block() -> Arc<impl Fn(u64) + Send + Sync + 'static> {
     })
 }
 
-criterion_group!(benches, put_single_threaded, put_8_threads, put_16_threads, put_32_threads);
+criterion_group!(benches, put_single_threaded, put_8_threads, put_16_threads, put_32_threads, put_16_threads_4_command_executor_threads);
 criterion_main!(benches);
\ No newline at end of file
@@ -30,7 +30,6 @@ const MASK: usize = CAPACITY - 1;
 
 /// This benchmark preloads the cache with the total number of elements = CAPACITY.
 /// PutOrUpdate changes the value corresponding to each key.
-
 #[cfg(feature = "bench_testable")]
 #[cfg(not(tarpaulin_include))]
 pub fn put_or_update_single_threaded(criterion: &mut Criterion) {
@@ -25,7 +25,6 @@ const CACHE_WEIGHT: Weight = CAPACITY as Weight;
 /// This benchmark measures the `pool.add` method.
 /// Pool represents a ring-buffer that is used to buffer the gets for various keys.
 /// We simulate the contention by running it with 8/16/32 threads. `ProxyAdmissionPolicy` is used as a `BufferConsumer`.
-
 #[cfg(feature = "bench_testable")]
 #[cfg(not(tarpaulin_include))]
 pub fn read_buffer_one_thread(criterion: &mut Criterion) {
@@ -0,0 +1,210 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rand::{Rng, thread_rng};
+use rand_distr::Zipf;
+
+use crate::cache::cached::CacheD;
+use crate::cache::types::KeyHash;
+
+/// HitRatioRecorder records the hits and the misses observed by the clients while evaluating the cache on a workload.
+///
+/// The recorder is independent of the stats maintained by the cache (`crate::cache::stats::StatsSummary`), so that the clients can decide
+/// which lookups to count (for example, only the lookups after the cache is warmed up).
+///
+/// Measuring the hit ratio for a given `counters`/`capacity` pair:
+/// ```
+/// use tinylfu_cached::cache::bench_util::{HitRatioRecorder, preload, zipf_distribution};
+/// use tinylfu_cached::cache::cached::CacheD;
+/// use tinylfu_cached::cache::config::ConfigBuilder;
+/// #[tokio::main]
+///  async fn main() {
+///     let capacity = 100;
+///     let cached = CacheD::new(ConfigBuilder::new((capacity * 10) as u64, capacity, (capacity * 40) as i64).build());
+///     let distribution = zipf_distribution((capacity * 16) as u64, capacity * 16, 1.001);
+///     preload(&cached, &distribution[0..capacity], |key| key).await;
+///
+///     let recorder = HitRatioRecorder::new();
+///     for key in &distribution {
+///         recorder.record(cached.get(key).is_some());
+///         let _ = cached.put_with_weight(*key, *key, 40).unwrap().handle().await;
+///     }
+///     assert!(recorder.hit_ratio() > 0.0);
+///     assert_eq!((capacity * 16) as u64, recorder.hits() + recorder.misses());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct HitRatioRecorder {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl HitRatioRecorder {
+    pub fn new() -> Self {
+        HitRatioRecorder {
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record_hit(&self) { self.hits.fetch_add(1, Ordering::AcqRel); }
+
+    pub fn record_miss(&self) { self.misses.fetch_add(1, Ordering::AcqRel); }
+
+    /// Records a hit if `is_hit` is true, else records a miss.
+    pub fn record(&self, is_hit: bool) {
+        if is_hit { self.record_hit(); } else { self.record_miss(); }
+    }
+
+    pub fn hits(&self) -> u64 { self.hits.load(Ordering::Acquire) }
+
+    pub fn misses(&self) -> u64 { self.misses.load(Ordering::Acquire) }
+
+    /// Returns the hit ratio between 0.0 and 1.0, or 0.0 if nothing is recorded.
+    pub fn hit_ratio(&self) -> f64 {
+        let hits = self.hits();
+        let total = hits + self.misses();
+        if total == 0 {
+            return 0.0;
+        }
+        hits as f64 / total as f64
+    }
+
+    /// Returns the hit ratio as %.
+    pub fn hit_ratio_as_percentage(&self) -> f64 {
+        self.hit_ratio() * 100.0
+    }
+}
+
+impl Default for HitRatioRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns `sample_size` keys drawn from a [Zipf distribution](https://en.wikipedia.org/wiki/Zipf%27s_law) over `1..=items` with the given `exponent`.
+///
+/// A larger exponent results in a more skewed distribution, where a few keys are accessed a lot more than the others.
+pub fn zipf_distribution(items: u64, sample_size: usize, exponent: f64) -> Vec<u64> {
+    thread_rng().sample_iter(Zipf::new(items, exponent).unwrap()).take(sample_size).map(|value| value as u64).collect::<Vec<_>>()
+}
+
+/// Puts each key in the `cached` instance with the value generated by `value_generation`, and awaits each `put`.
+pub async fn preload<Value, F>(cached: &CacheD<u64, Value>, keys: &[u64], value_generation: F)
+    where Value: Send + Sync + 'static,
+          F: Fn(u64) -> Value {
+    for key in keys {
+        let value = value_generation(*key);
+        if let Ok(acknowledgement) = cached.put(*key, value) {
+            acknowledgement.handle().await;
+        }
+    }
+}
+
+/// Returns the theoretical optimal hit ratio between 0.0 and 1.0 of a cache that holds `capacity_entries` entries, over the access `trace`.
+///
+/// The hit ratio is computed using [Belady's MIN algorithm](https://en.wikipedia.org/wiki/Cache_replacement_policies#B%C3%A9l%C3%A1dy's_algorithm),
+/// which knows the future: on a miss in a full cache, it evicts the entry whose next access is the farthest in the future.
+/// The incoming entry is not admitted if its own next access is the farthest, the way `CacheD` may reject an incoming key.
+///
+/// Comparing the hit ratio measured by [`HitRatioRecorder`] against this ceiling tells how close TinyLFU gets on a workload.
+/// The trace can be recorded using the `trace` feature, or generated using [`zipf_distribution`].
+/// ```
+/// use tinylfu_cached::cache::bench_util::belady_optimal_hit_ratio;
+/// let hit_ratio = belady_optimal_hit_ratio(&[1, 2, 1, 3, 1, 2], 2);
+/// assert_eq!(0.5, hit_ratio);
+/// ```
+pub fn belady_optimal_hit_ratio(trace: &[KeyHash], capacity_entries: usize) -> f64 {
+    if trace.is_empty() || capacity_entries == 0 {
+        return 0.0;
+    }
+    let never_accessed_again = trace.len();
+    let mut next_access_positions = vec![never_accessed_again; trace.len()];
+    let mut next_position_by_key: HashMap<KeyHash, usize> = HashMap::new();
+    for (position, key) in trace.iter().enumerate().rev() {
+        if let Some(next_position) = next_position_by_key.insert(*key, position) {
+            next_access_positions[position] = next_position;
+        }
+    }
+
+    let mut next_access_by_key: HashMap<KeyHash, usize> = HashMap::with_capacity(capacity_entries);
+    let mut keys_by_next_access: BTreeSet<(usize, KeyHash)> = BTreeSet::new();
+    let mut hits = 0;
+
+    for (position, key) in trace.iter().enumerate() {
+        let next_access = next_access_positions[position];
+        if let Some(current_next_access) = next_access_by_key.get_mut(key) {
+            hits += 1;
+            keys_by_next_access.remove(&(*current_next_access, *key));
+            keys_by_next_access.insert((next_access, *key));
+            *current_next_access = next_access;
+            continue;
+        }
+        if next_access_by_key.len() >= capacity_entries {
+            let farthest = *keys_by_next_access.iter().next_back().unwrap();
+            if next_access >= farthest.0 {
+                continue;
+            }
+            keys_by_next_access.remove(&farthest);
+            next_access_by_key.remove(&farthest.1);
+        }
+        next_access_by_key.insert(*key, next_access);
+        keys_by_next_access.insert((next_access, *key));
+    }
+    hits as f64 / trace.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::bench_util::{belady_optimal_hit_ratio, HitRatioRecorder, zipf_distribution};
+
+    #[test]
+    fn hit_ratio() {
+        let recorder = HitRatioRecorder::new();
+        recorder.record_hit();
+        recorder.record(false);
+        recorder.record(false);
+        recorder.record(false);
+
+        assert_eq!(1, recorder.hits());
+        assert_eq!(3, recorder.misses());
+        assert_eq!(0.25, recorder.hit_ratio());
+        assert_eq!(25.0, recorder.hit_ratio_as_percentage());
+    }
+
+    #[test]
+    fn hit_ratio_without_records() {
+        let recorder = HitRatioRecorder::new();
+        assert_eq!(0.0, recorder.hit_ratio());
+    }
+
+    #[test]
+    fn belady_optimal_hit_ratio_of_a_trace() {
+        let trace = [1, 2, 3, 4, 1, 2, 5, 1, 2, 3, 4, 5];
+        assert_eq!(5.0 / 12.0, belady_optimal_hit_ratio(&trace, 3));
+    }
+
+    #[test]
+    fn belady_optimal_hit_ratio_with_capacity_for_all_the_keys() {
+        let trace = [1, 2, 1, 3, 2, 1];
+        assert_eq!(0.5, belady_optimal_hit_ratio(&trace, 3));
+    }
+
+    #[test]
+    fn belady_optimal_hit_ratio_without_capacity() {
+        assert_eq!(0.0, belady_optimal_hit_ratio(&[1, 1, 1], 0));
+    }
+
+    #[test]
+    fn belady_optimal_hit_ratio_of_an_empty_trace() {
+        assert_eq!(0.0, belady_optimal_hit_ratio(&[], 2));
+    }
+
+    #[test]
+    fn zipf_distribution_within_items() {
+        let distribution = zipf_distribution(100, 1000, 1.001);
+
+        assert_eq!(1000, distribution.len());
+        assert!(distribution.iter().all(|key| *key >= 1 && *key <= 100));
+    }
+}
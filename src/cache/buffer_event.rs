@@ -1,28 +1,112 @@
-use crate::cache::types::KeyHash;
-
-#[cfg(not(feature = "bench_testable"))]
-pub(crate) enum BufferEvent {
-    Full(Vec<KeyHash>),
-    Shutdown,
-}
+use std::sync::Arc;
 
-#[cfg(not(feature = "bench_testable"))]
-pub(crate) trait BufferConsumer {
-    fn accept(&self, event: BufferEvent);
-}
+use crate::cache::types::KeyHash;
 
-/// BufferEvent::Full signifies that a buffer in the [`crate::cache::pool::Pool`] is full
+/// BufferEvent::Full signifies that a buffer in the `crate::cache::pool::Pool` is full
 /// and the consumer should accepts the buffer, called draining.
 /// During the event of cache shutdown, the consumer of the buffer needs to be shutdown.
 /// Buffer::Shutdown signals the consumer of the buffer to shutdown.
 /// Currently, `crate::cache::policy::admission_policy::AdmissionPolicy` is the consumer of the buffer.
-#[cfg(feature = "bench_testable")]
 pub enum BufferEvent {
     Full(Vec<KeyHash>),
     Shutdown,
 }
 
-#[cfg(feature = "bench_testable")]
+/// BufferConsumer accepts the [`BufferEvent`]s, the drained access batches of the access pool.
+///
+/// Clients can implement `BufferConsumer` to observe the accesses (for example, to feed them to an external analytics system),
+/// and register it using [`crate::cache::config::ConfigBuilder::additional_access_consumer`].
 pub trait BufferConsumer {
     fn accept(&self, event: BufferEvent);
-}
\ No newline at end of file
+}
+
+/// Defines the consumer of the drained access batches that is registered in addition to the admission policy.
+pub type AdditionalBufferConsumer = dyn BufferConsumer + Send + Sync;
+
+/// ChainedBufferConsumer passes every `BufferEvent` to the `primary` consumer, and then to the `additional` consumer (if any).
+/// The `primary` consumer gets the event first, so the `additional` consumer can not delay it.
+pub(crate) struct ChainedBufferConsumer<Primary: BufferConsumer> {
+    primary: Arc<Primary>,
+    additional: Option<Arc<AdditionalBufferConsumer>>,
+}
+
+impl<Primary> ChainedBufferConsumer<Primary>
+    where Primary: BufferConsumer {
+    pub(crate) fn new(primary: Arc<Primary>, additional: Option<Arc<AdditionalBufferConsumer>>) -> Self {
+        ChainedBufferConsumer { primary, additional }
+    }
+}
+
+impl<Primary> BufferConsumer for ChainedBufferConsumer<Primary>
+    where Primary: BufferConsumer {
+    fn accept(&self, event: BufferEvent) {
+        match &self.additional {
+            Some(additional) => {
+                let additional_event = match &event {
+                    BufferEvent::Full(key_hashes) => BufferEvent::Full(key_hashes.clone()),
+                    BufferEvent::Shutdown => BufferEvent::Shutdown,
+                };
+                self.primary.accept(event);
+                additional.accept(additional_event);
+            }
+            None => self.primary.accept(event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+
+    use crate::cache::buffer_event::{BufferConsumer, BufferEvent, ChainedBufferConsumer};
+    use crate::cache::buffer_event::tests::setup::RecordingBufferConsumer;
+    use crate::cache::types::KeyHash;
+
+    mod setup {
+        use parking_lot::Mutex;
+
+        use crate::cache::buffer_event::{BufferConsumer, BufferEvent};
+        use crate::cache::types::KeyHash;
+
+        pub(crate) struct RecordingBufferConsumer {
+            pub(crate) key_hashes: Mutex<Vec<KeyHash>>,
+        }
+
+        impl BufferConsumer for RecordingBufferConsumer {
+            fn accept(&self, event: BufferEvent) {
+                if let BufferEvent::Full(key_hashes) = event {
+                    self.key_hashes.lock().extend(key_hashes);
+                }
+            }
+        }
+    }
+
+    fn recording_consumer() -> Arc<RecordingBufferConsumer> {
+        Arc::new(RecordingBufferConsumer { key_hashes: Mutex::new(Vec::new()) })
+    }
+
+    #[test]
+    fn chained_consumers_accept_the_same_event() {
+        let primary = recording_consumer();
+        let additional = recording_consumer();
+        let consumer = ChainedBufferConsumer::new(primary.clone(), Some(additional.clone()));
+
+        consumer.accept(BufferEvent::Full(vec![10, 20, 10]));
+
+        assert_eq!(vec![10, 20, 10], *primary.key_hashes.lock());
+        assert_eq!(vec![10, 20, 10], *additional.key_hashes.lock());
+    }
+
+    #[test]
+    fn primary_consumer_accepts_the_event_without_an_additional_consumer() {
+        let primary = recording_consumer();
+        let consumer = ChainedBufferConsumer::new(primary.clone(), None);
+
+        consumer.accept(BufferEvent::Full(vec![10, 20]));
+
+        let expected: Vec<KeyHash> = vec![10, 20];
+        assert_eq!(expected, *primary.key_hashes.lock());
+    }
+}
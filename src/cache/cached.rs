@@ -1,30 +1,68 @@
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::hash::Hash;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "serde")]
+use std::io::{Read, Write};
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use std::sync::atomic::Ordering::Acquire;
-use std::time::Duration;
-
-use log::info;
-
+#[cfg(feature = "stream")]
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "stream")]
+use futures::Stream;
+use log::{info, warn};
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::cache::buffer_event::{BufferEvent, ChainedBufferConsumer};
+use crate::cache::clock::ClockType;
 use crate::cache::command::acknowledgement::CommandAcknowledgement;
-use crate::cache::command::command_executor::{CommandExecutor, CommandSendResult, shutdown_result};
-use crate::cache::command::{CommandType, RejectionReason};
-use crate::cache::config::Config;
+use crate::cache::command::error::{CommandSendError, TryPutError};
+use crate::cache::command::adaptive_capacity::AdaptiveCapacity;
+use crate::cache::command::command_executor::{CommandExecutor, CommandSendResult, ExecutionContext, shutdown_result, ttl_disabled_result, TryPutResult};
+use crate::cache::command::{CommandStatus, CommandType, RejectionReason};
+use crate::cache::compare_and_swap::CompareAndSwap;
+use crate::cache::config::{AccessDrain, AdmissionMode, Config, EvictionReason, ExpiredReadBehavior, PutMode};
 use crate::cache::config::weight_calculation::Calculation;
+use crate::cache::config::error::WeightError;
 use crate::cache::errors::Errors;
 use crate::cache::expiration::TTLTicker;
+#[cfg(feature = "serde")]
+use crate::cache::export;
+#[cfg(feature = "serde")]
+use crate::cache::export::ExportError;
+#[cfg(feature = "metrics")]
+use crate::cache::metrics;
 use crate::cache::key_description::KeyDescription;
+use crate::cache::lfu::frequency_counter::FrequencyCounter;
 use crate::cache::policy::admission_policy::AdmissionPolicy;
+use crate::cache::policy::cache_weight::MinResidency;
 use crate::cache::pool::Pool;
 use crate::cache::put_or_update::PutOrUpdateRequest;
+use crate::cache::single_flight::SingleFlight;
+use crate::cache::sketch::SketchState;
 use crate::cache::stats::{ConcurrentStatsCounter, StatsSummary};
-use crate::cache::store::{Store, TypeOfExpiryUpdate};
+use crate::cache::store::{BatchSwapResponse, IncrementResponse, Store, TypeOfExpiryUpdate, UpdateResponse};
 use crate::cache::store::key_value_ref::KeyValueRef;
 use crate::cache::store::stored_value::StoredValue;
-use crate::cache::types::{KeyId, Weight};
+use crate::cache::store::tombstones::Tombstones;
+#[cfg(feature = "trace")]
+use crate::cache::trace::TraceOperation;
+use crate::cache::types::{ExpireAfter, FrequencyEstimate, KeyHash, KeyId, TotalCapacity, Weight};
 use crate::cache::unique_id::increasing_id_generator::IncreasingIdGenerator;
 
+/// The number of keys that `multi_get` (and `multi_get_fresh`) looks up before yielding the thread.
+const MULTI_GET_BATCH_SIZE: usize = 1024;
+
 /// `CacheD` is a high performance, LFU based in-memory cache. Cached provides various behaviors including:
 /// `put`, `put_with_weight`, `put_with_ttl`, `get`, `get_ref`, `map_get_ref`, `multi_get`, `delete`, `put_or_update`.
 ///
@@ -74,10 +112,14 @@ pub struct CacheD<Key, Value>
     store: Arc<Store<Key, Value>>,
     command_executor: CommandExecutor<Key, Value>,
     admission_policy: Arc<AdmissionPolicy<Key>>,
-    pool: Pool<AdmissionPolicy<Key>>,
+    pool: Arc<Pool<ChainedBufferConsumer<AdmissionPolicy<Key>>>>,
     ttl_ticker: Arc<TTLTicker>,
+    tombstones: Arc<Tombstones<Key>>,
+    single_flight: SingleFlight<Key, Value>,
     id_generator: IncreasingIdGenerator,
     is_shutting_down: AtomicBool,
+    shutdown_report: Mutex<Option<ShutdownReport>>,
+    clear_gate: RwLock<()>,
 }
 
 impl<Key, Value> CacheD<Key, Value>
@@ -88,33 +130,94 @@ impl<Key, Value> CacheD<Key, Value>
         assert!(config.counters > 0);
 
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
-        let store = Store::new(config.clock.clone_box(), stats_counter.clone(), config.capacity, config.shards);
-        let admission_policy = Arc::new(AdmissionPolicy::new(config.counters, config.cache_weight_config(), stats_counter.clone()));
-        let pool = Pool::new(config.access_pool_size, config.access_buffer_size, admission_policy.clone());
-        let ttl_ticker = Self::ttl_ticker(&config, store.clone(), admission_policy.clone());
-        let command_buffer_size = config.command_buffer_size;
+        let store = Store::new(config.clock.clone_box(), stats_counter.clone(), config.capacity, config.shards, config.shard_hash_builder());
+        let admission_policy = Arc::new(Self::admission_policy(&config, store.clone(), stats_counter.clone()));
+        let access_consumer = Arc::new(ChainedBufferConsumer::new(admission_policy.clone(), config.additional_access_consumer.clone()));
+        let pool = Arc::new(Pool::new(config.access_pool_size, config.access_buffer_size, access_consumer));
+        if let AccessDrain::Periodic(period) = config.access_drain {
+            pool.clone().drain_periodically(period);
+        }
+        let tombstones = Arc::new(Tombstones::new());
+        let ttl_ticker = Self::ttl_ticker(&config, store.clone(), admission_policy.clone(), tombstones.clone());
+        let command_executor = if config.inline_execution {
+            CommandExecutor::inline(store.clone(), admission_policy.clone(), stats_counter, ttl_ticker.clone(), config.put_mode, config.eviction_listener.clone())
+        } else {
+            CommandExecutor::with_threads(
+                ExecutionContext::new(store.clone(), admission_policy.clone(), stats_counter, ttl_ticker.clone(), config.put_mode, config.eviction_listener.clone()),
+                config.command_buffer_size,
+                config.max_command_buffer_size.map(|max_command_buffer_size| AdaptiveCapacity::new(config.command_buffer_size, max_command_buffer_size)),
+                config.command_executor_threads,
+            )
+        };
 
         CacheD {
             config,
-            store: store.clone(),
-            command_executor: CommandExecutor::new(store, admission_policy.clone(), stats_counter, ttl_ticker.clone(), command_buffer_size),
+            store,
+            command_executor,
             admission_policy,
             pool,
             ttl_ticker,
+            tombstones,
+            single_flight: SingleFlight::new(),
             id_generator: IncreasingIdGenerator::new(),
             is_shutting_down: AtomicBool::new(false),
+            shutdown_report: Mutex::new(None),
+            clear_gate: RwLock::new(()),
         }
     }
 
+    /// Creates a new instance of `Cached` with the provided [`crate::cache::config::Config`], and the access frequency of the keys warmed
+    /// with the [`crate::cache::sketch::SketchState`] exported from another instance, read [`CacheD::export_sketch`].
+    ///
+    /// The hot keys in the sketch are admitted from their first put, instead of getting rejected (under pressure) during a warm-up period.
+    /// Both the instances must use the same `key_hash_fn`, and the sketch must have been exported from an instance with the same `counters`.
+    ///
+    /// Panics if the total counters of the sketch do not match the total counters derived from `counters` in the config.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use tinylfu_cached::cache::sketch::SketchState;
+    ///
+    /// let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// cached.prime_frequency(&"topic", 5);
+    /// let bytes = cached.export_sketch().to_bytes();
+    ///
+    /// let sketch = SketchState::from_bytes(&bytes).unwrap();
+    /// let warmed_cached: CacheD<&str, &str> = CacheD::new_with_sketch(ConfigBuilder::new(100, 10, 100).build(), sketch);
+    /// ```
+    pub fn new_with_sketch(config: Config<Key, Value>, sketch: SketchState) -> Self {
+        assert!(
+            FrequencyCounter::total_counters_for(config.counters) == sketch.total_counters(), "{}", Errors::SketchDimensionsMismatch
+        );
+        let cached = Self::new(config);
+        cached.admission_policy.import_sketch(sketch);
+        cached
+    }
+
+    /// Exports the count-min sketch that maintains the access frequency of the keys, which can be used to construct
+    /// a warmed instance using [`CacheD::new_with_sketch`].
+    ///
+    /// The accesses that are still in the access buffers (not yet drained) are not a part of the sketch.
+    pub fn export_sketch(&self) -> SketchState {
+        self.admission_policy.export_sketch()
+    }
+
     /// Puts the key/value pair in the cacheD instance and returns an instance of [` crate::cache::command::command_executor::CommandSendResult`] to the clients.
     ///
     /// Weight is calculated by the weight calculation function provided as a part of `Config`.
+    /// If the weight calculation function returns a [`crate::cache::config::error::WeightError`], `put` returns a
+    /// [`crate::cache::command::error::CommandSendError`] that wraps the `WeightError`, and the key/value pair is not put.
     ///
-    ///  [`crate::cache::command::CommandStatus::Rejected`] is returned to the clients if the key already exists, since v0.0.3.
+    ///  [`crate::cache::command::CommandStatus::Rejected`] is returned to the clients if the key already exists, since v0.0.3. This behavior can be changed using [`crate::cache::config::PutMode`].
     ///
     /// `put` is not an immediate operation. Every invocation of `put` results in `crate::cache::command::CommandType::Put` to the `CommandExecutor`.
     /// `CommandExecutor` in turn delegates to the `AdmissionPolicy` to perform the put operation.
     /// `AdmissionPolicy` may accept or reject the key/value pair depending on the available cache weight.
+    /// A key/value pair that gets rejected is never stored, so once the acknowledgement resolves to [`crate::cache::command::CommandStatus::Rejected`],
+    /// `get` (and its variants) does not return the value of the rejected put. Use [`CacheD::put_and_confirm`] to know if the value is retrievable after the put.
+    ///
+    /// If `delete_on_value` is configured in [`crate::cache::config::ConfigBuilder`], a `put` (and its variants) whose value matches the predicate
+    /// deletes the key instead, without calculating the weight of the value.
     ///
     /// Since, `put` is not an immediate operation, clients can `await` on the response to get the [`crate::cache::command::CommandStatus`]
     /// ```
@@ -129,16 +232,71 @@ impl<Key, Value> CacheD<Key, Value>
     /// }
     /// ```
     pub fn put(&self, key: Key, value: Value) -> CommandSendResult {
-        let weight = (self.config.weight_calculation_fn)(&key, &value, false);
+        self.put_with_calculated_weight(key, value, self.config.default_time_to_live)
+    }
+
+    /// Puts the key/value pair like [`CacheD::put`], but never blocks the calling thread on a full command channel.
+    ///
+    /// `put` blocks once the command channel of `crate::cache::command::command_executor::CommandExecutor` is full,
+    /// whereas `try_put` returns [`crate::cache::command::error::TryPutError::ChannelFull`] without sending the put,
+    /// which lets the clients apply their own backpressure, or drop the put. Any other failure (for example, a shutdown) is returned as `TryPutError::Send`.
+    ///
+    /// A value that matches `delete_on_value` of [`crate::cache::config::ConfigBuilder`] deletes the key using [`CacheD::delete`], which may block.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.try_put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    /// }
+    /// ```
+    pub fn try_put(&self, key: Key, value: Value) -> TryPutResult {
+        if self.is_shutting_down() { return Err(TryPutError::Send(CommandSendError::shutdown())); }
+        if self.is_delete_value(&value) { return self.delete(key).map_err(TryPutError::from); }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Put, &key);
+
+        let time_to_live = self.config.default_time_to_live;
+        let value = self.transform_on_put(value);
+        let weight = self.config.weight_of(&key, &value, time_to_live.is_some())?;
         assert!(weight > 0, "{}", Errors::WeightCalculationGtZero);
-        self.put_with_weight(key, value, weight)
+
+        if let Some(rejection) = self.put_rejection(&key) {
+            return rejection.map_err(TryPutError::from);
+        }
+        let _gate = self.pass_clear_gate();
+        self.command_executor.try_send(self.put_command(key, value, weight, time_to_live))
+    }
+
+    /// Puts the key/value pair in the cacheD instance without a `time_to_live`, and returns an instance of [` crate::cache::command::command_executor::CommandSendResult`] to the clients.
+    ///
+    /// `put_without_ttl` behaves like [`CacheD::put`], except that it does not apply the default time to live configured using
+    /// [`crate::cache::config::ConfigBuilder::default_time_to_live`], so the key never expires.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use std::time::Duration;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).default_time_to_live(Duration::from_secs(300)).build());
+    ///     let status = cached.put_without_ttl("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    ///     assert_eq!(None, cached.get_ref(&"topic").unwrap().value().expire_after());
+    /// }
+    /// ```
+    pub fn put_without_ttl(&self, key: Key, value: Value) -> CommandSendResult {
+        self.put_with_calculated_weight(key, value, None)
     }
 
     /// Puts the key/value pair in the cacheD instance and returns an instance of [` crate::cache::command::command_executor::CommandSendResult`] to the clients.
     ///
     /// Weight is provided by the clients.
     ///
-    ///  [`crate::cache::command::CommandStatus::Rejected`] is returned to the clients if the key already exists, since v0.0.3.
+    ///  [`crate::cache::command::CommandStatus::Rejected`] is returned to the clients if the key already exists, since v0.0.3. This behavior can be changed using [`crate::cache::config::PutMode`].
     ///
     /// `put_with_weight` is not an immediate operation. Every invocation of `put_with_weight` results in `crate::cache::command::CommandType::Put` to the `CommandExecutor`.
     /// `CommandExecutor` in turn delegates to the `AdmissionPolicy` to perform the put operation.
@@ -159,27 +317,36 @@ impl<Key, Value> CacheD<Key, Value>
     /// ```
     pub fn put_with_weight(&self, key: Key, value: Value, weight: Weight) -> CommandSendResult {
         if self.is_shutting_down() { return shutdown_result(); }
+        if self.is_delete_value(&value) { return self.delete(key); }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Put, &key);
 
         assert!(weight > 0, "{}", Errors::KeyWeightGtZero("put_with_weight"));
-        if self.store.is_present(&key) {
-            return Ok(CommandAcknowledgement::rejected(RejectionReason::KeyAlreadyExists))
+        match self.config.default_time_to_live {
+            Some(time_to_live) => {
+                let weight = weight + Calculation::time_to_live_weight(true);
+                self.put_transformed(key, self.transform_on_put(value), weight, Some(time_to_live))
+            }
+            None =>
+                self.put_transformed(key, self.transform_on_put(value), weight, None),
         }
-        self.command_executor.send(CommandType::Put(
-            self.key_description(key, weight),
-            value,
-        ))
     }
 
     /// Puts the key/value pair with `time_to_live` in the cacheD instance and returns an instance of [` crate::cache::command::command_executor::CommandSendResult`] to the clients.
     ///
     /// Weight is calculated by the weight calculation function provided as a part of `Config`.
+    /// If the weight calculation function returns a [`crate::cache::config::error::WeightError`], `put_with_ttl` returns a
+    /// [`crate::cache::command::error::CommandSendError`] that wraps the `WeightError`, and the key/value pair is not put.
     ///
-    /// [`crate::cache::command::CommandStatus::Rejected`] is returned to the clients if the key already exists, since v0.0.3.
+    /// [`crate::cache::command::CommandStatus::Rejected`] is returned to the clients if the key already exists, since v0.0.3. This behavior can be changed using [`crate::cache::config::PutMode`].
     ///
     /// `put_with_ttl` is not an immediate operation. Every invocation of `put_with_ttl` results in `crate::cache::command::CommandType::PutWithTTL` to the `CommandExecutor`.
     /// `CommandExecutor` in turn delegates to the `AdmissionPolicy` to perform the put operation.
     /// `AdmissionPolicy` may accept or reject the key/value pair depending on the available cache weight.
     ///
+    /// The expired key is not returned by `get` (and its variants), but its weight is reclaimed only when `crate::cache::expiration::TTLTicker` removes it,
+    /// read [`CacheD::ttl_resolution`]. A warning is logged if `time_to_live` is less than the `ttl_resolution`.
+    ///
     /// Since, `put_with_ttl` is not an immediate operation, clients can `await` on the response to get the [`crate::cache::command::CommandStatus`]
     /// ```
     /// use tinylfu_cached::cache::cached::CacheD;
@@ -195,22 +362,24 @@ impl<Key, Value> CacheD<Key, Value>
     /// ```
     pub fn put_with_ttl(&self, key: Key, value: Value, time_to_live: Duration) -> CommandSendResult {
         if self.is_shutting_down() { return shutdown_result(); }
-
-        let weight = (self.config.weight_calculation_fn)(&key, &value, true);
+        if self.is_ttl_disabled() { return ttl_disabled_result("PutWithTTL"); }
+        if self.is_delete_value(&value) { return self.delete(key); }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Put, &key);
+        self.warn_if_below_ttl_resolution(time_to_live);
+
+        let value = self.transform_on_put(value);
+        let weight = self.config.weight_of(&key, &value, true)?;
         assert!(weight > 0, "{}", Errors::WeightCalculationGtZero);
-        if self.store.is_present(&key) {
-            return Ok(CommandAcknowledgement::rejected(RejectionReason::KeyAlreadyExists))
-        }
-        self.command_executor.send(CommandType::PutWithTTL(
-            self.key_description(key, weight), value, time_to_live)
-        )
+        self.put_transformed(key, value, weight, Some(time_to_live))
     }
 
     /// Puts the key/value pair with `time_to_live` in the cacheD instance and returns an instance of [` crate::cache::command::command_executor::CommandSendResult`] to the clients.
     ///
-    /// Weight is provided by the clients.
+    /// Weight is provided by the clients. The weight of the key/value pair in the cache is the provided weight plus
+    /// the weight of the entry that `crate::cache::expiration::TTLTicker` keeps for the key.
     ///
-    /// [`crate::cache::command::CommandStatus::Rejected`] is returned to the clients if the key already exists, since v0.0.3.
+    /// [`crate::cache::command::CommandStatus::Rejected`] is returned to the clients if the key already exists, since v0.0.3. This behavior can be changed using [`crate::cache::config::PutMode`].
     ///
     /// `put_with_weight_and_ttl` is not an immediate operation. Every invocation of `put_with_weight_and_ttl` results in `crate::cache::command::CommandType::PutWithTTL` to the `CommandExecutor`.
     /// `CommandExecutor` in turn delegates to the `AdmissionPolicy` to perform the put operation.
@@ -226,26 +395,106 @@ impl<Key, Value> CacheD<Key, Value>
     ///  async fn main() {
     ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
     ///     let status = cached.put_with_weight_and_ttl("topic", "microservices", 50, Duration::from_secs(120)).unwrap().handle().await;
-    ///     assert_eq!(50, cached.total_weight_used());
+    ///     assert_eq!(74, cached.total_weight_used());
     ///     assert_eq!(CommandStatus::Accepted, status);
     /// }
     /// ```
     pub fn put_with_weight_and_ttl(&self, key: Key, value: Value, weight: Weight, time_to_live: Duration) -> CommandSendResult {
         if self.is_shutting_down() { return shutdown_result(); }
+        if self.is_ttl_disabled() { return ttl_disabled_result("PutWithTTL"); }
+        if self.is_delete_value(&value) { return self.delete(key); }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Put, &key);
+        self.warn_if_below_ttl_resolution(time_to_live);
 
         assert!(weight > 0, "{}", Errors::KeyWeightGtZero("put_with_weight_and_ttl"));
-        if self.store.is_present(&key) {
-            return Ok(CommandAcknowledgement::rejected(RejectionReason::KeyAlreadyExists))
+        let weight = weight + Calculation::time_to_live_weight(true);
+        self.put_transformed(key, self.transform_on_put(value), weight, Some(time_to_live))
+    }
+
+    /// Puts the key/value pair with a `version` in the cacheD instance and returns an instance of [` crate::cache::command::command_executor::CommandSendResult`] to the clients.
+    ///
+    /// `put_versioned` implements last-writer-wins with monotonically increasing versions, which is useful when the updates arrive out of order (for example, from a replicated source),
+    /// or when the values are expensive to compare. The key/value pair replaces the existing key only if the version of the existing key is lower than `version`,
+    /// else it is rejected with [`crate::cache::command::RejectionReason::StaleVersion`]. An existing key that was put without a version is always replaced.
+    ///
+    /// The versions are compared on the thread of `crate::cache::command::command_executor::CommandExecutor`, which makes the comparison and the put atomic.
+    /// [`crate::cache::config::PutMode`] does not apply to `put_versioned`. The version of a key is available via [`crate::cache::store::stored_value::StoredValue::version`].
+    ///
+    /// Weight is calculated by the weight calculation function provided as a part of `Config`.
+    /// Like [`CacheD::put`], the key/value pair expires after the time to live configured using [`crate::cache::config::ConfigBuilder::default_time_to_live`] (if any),
+    /// and a value that matches `delete_on_value` of [`crate::cache::config::ConfigBuilder`] deletes the key using [`CacheD::delete`], irrespective of the version.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::{CommandStatus, RejectionReason};
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    ///     let status = cached.put_versioned("topic", "cached", 2).unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    ///
+    ///     let status = cached.put_versioned("topic", "microservices", 1).unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Rejected(RejectionReason::StaleVersion), status);
+    ///     assert_eq!(Some("cached"), cached.get(&"topic"));
+    /// }
+    /// ```
+    pub fn put_versioned(&self, key: Key, value: Value, version: u64) -> CommandSendResult {
+        if self.is_shutting_down() { return shutdown_result(); }
+        if self.is_delete_value(&value) { return self.delete(key); }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Put, &key);
+
+        if let Some(rejection) = self.tombstone_rejection(&key) {
+            return rejection;
         }
-        self.command_executor.send(CommandType::PutWithTTL(
-            self.key_description(key, weight), value, time_to_live,
-        ))
+        let time_to_live = self.config.default_time_to_live;
+        let value = self.transform_on_put(value);
+        let weight = self.config.weight_of(&key, &value, time_to_live.is_some())?;
+        assert!(weight > 0, "{}", Errors::WeightCalculationGtZero);
+        let _gate = self.pass_clear_gate();
+        self.command_executor.send(CommandType::PutVersioned(self.key_description(key, weight), value, version, time_to_live))
+    }
+
+    /// Puts a non-essential key/value pair in the cacheD instance and returns an instance of [` crate::cache::command::command_executor::CommandSendResult`] to the clients.
+    ///
+    /// If the soft capacity is configured using [`crate::cache::config::ConfigBuilder::soft_capacity`] and the weight used by the cache exceeds it,
+    /// `put_optional` is rejected immediately with [`crate::cache::command::RejectionReason::SoftCapacityExceeded`], without attempting the admission of the key/value pair.
+    /// Otherwise, `put_optional` behaves like [`CacheD::put`].
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::{CommandStatus, RejectionReason};
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).soft_capacity(40).build());
+    ///     let status = cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    ///
+    ///     let status = cached.put_optional("cache", "cached").unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Rejected(RejectionReason::SoftCapacityExceeded), status);
+    /// }
+    /// ```
+    pub fn put_optional(&self, key: Key, value: Value) -> CommandSendResult {
+        if self.is_shutting_down() { return shutdown_result(); }
+        if let Some(soft_capacity) = self.config.soft_capacity {
+            if self.total_weight_used() > soft_capacity {
+                return Ok(CommandAcknowledgement::rejected(RejectionReason::SoftCapacityExceeded));
+            }
+        }
+        self.put(key, value)
     }
 
     /// Performs a `put` if the key does not exist or an `update` operation, if the key exists. [`PutOrUpdateRequest`] is a convenient way to perform put or update operation.
     /// `put_or_update` attempts to perform the update operation on `crate::cache::store::Store` first.
     /// If the update operation is successful then the changes are made to `TTLTicker` and `AdmissionPolicy`, if applicable.
     /// If the update is not successful then a `put` operation is performed.
+    ///
+    /// The weight of a key with `time_to_live` includes the weight of its entry in `TTLTicker`. This weight is added when the `time_to_live` is added,
+    /// and removed when the `time_to_live` is removed, irrespective of whether the weight was provided by the client or calculated by the weight calculation function.
+    ///
+    /// If the weight calculation function returns a [`crate::cache::config::error::WeightError`], `put_or_update` returns a
+    /// [`crate::cache::command::error::CommandSendError`] that wraps the `WeightError`.
     /// ```
     /// use tinylfu_cached::cache::cached::CacheD;
     /// use tinylfu_cached::cache::command::CommandStatus;
@@ -261,61 +510,128 @@ impl<Key, Value> CacheD<Key, Value>
     ///     assert_eq!(Some("Cached"), value);
     /// }
     /// ```
-    pub fn put_or_update(&self, request: PutOrUpdateRequest<Key, Value>) -> CommandSendResult {
+    pub fn put_or_update(&self, mut request: PutOrUpdateRequest<Key, Value>) -> CommandSendResult {
         if self.is_shutting_down() { return shutdown_result(); }
+        if self.is_ttl_disabled() && request.time_to_live.is_some() { return ttl_disabled_result("PutOrUpdate"); }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Put, &request.key);
 
-        let updated_weight = request.updated_weight(&self.config.weight_calculation_fn);
-        let (key, value, time_to_live)
-            = (request.key, request.value, request.time_to_live);
+        request.value = request.value.map(|value| self.transform_on_put(value));
+        let put_weight = request.updated_weight(|key, value, is_time_to_live_specified| self.config.weight_of(key, value, is_time_to_live_specified))?;
+        let (key, value, weight, time_to_live)
+            = (request.key, request.value, request.weight, request.time_to_live);
+        let is_value_present = value.is_some();
 
+        let _gate = self.pass_clear_gate();
+        if self.is_shutting_down() { return shutdown_result(); }
         let update_response
             = self.store.update(&key, value, time_to_live, request.remove_time_to_live);
 
         if !update_response.did_update_happen() {
             let value = update_response.value();
             assert!(value.is_some(), "{}", Errors::PutOrUpdateValueMissing);
-            assert!(updated_weight.is_some());
+
+            assert!(put_weight.is_some());
 
             let value = value.unwrap();
-            let weight = updated_weight.unwrap();
+            let weight = put_weight.unwrap();
             assert!(weight > 0, "{}", Errors::KeyWeightGtZero("PutOrUpdate"));
+            if self.config.delete_tombstone_ttl.is_some() && self.tombstones.is_present(&key, &self.config.clock) {
+                return Ok(CommandAcknowledgement::rejected(RejectionReason::KeyRecentlyDeleted));
+            }
 
-            return if let Some(time_to_live) = time_to_live {
-                self.command_executor.send(CommandType::PutWithTTL(
-                    self.key_description(key, weight), value, time_to_live,
-                ))
-            } else {
-                self.command_executor.send(CommandType::Put(
-                    self.key_description(key, weight),
-                    value,
-                ))
-            };
+            return self.command_executor.send(CommandType::PutOrUpdate(
+                self.key_description(key, weight), value, time_to_live,
+            ));
         }
 
-        let key_id = update_response.key_id_or_panic();
-        let existing_weight = self.admission_policy.weight_of(&key_id).unwrap_or(0);
+        self.reconcile_update(&key, update_response, weight, is_value_present)
+    }
 
-        let updated_weight = match update_response.type_of_expiry_update() {
-            TypeOfExpiryUpdate::Added(key_id, expiry) => {
-                self.ttl_ticker.put(key_id, expiry);
-                updated_weight.or_else(|| Some(existing_weight + Calculation::ttl_ticker_entry_size() as i64))
-            }
-            TypeOfExpiryUpdate::Deleted(key_id, expiry) => {
-                self.ttl_ticker.delete(&key_id, &expiry);
-                updated_weight.or_else(|| Some(existing_weight - Calculation::ttl_ticker_entry_size() as i64))
-            }
-            TypeOfExpiryUpdate::Updated(key_id, old_expiry, new_expiry) => {
-                self.ttl_ticker.update(key_id, &old_expiry, new_expiry);
-                updated_weight
-            }
-            _ => updated_weight,
+    /// Puts the key/value pair, awaits the acknowledgement and returns true if the key is retrievable after the put.
+    ///
+    /// `put_and_confirm` returns false if the put could not be sent (for example, if the weight calculation function returns an error or the cache is being shutdown),
+    /// if the put is rejected (for example, by the `AdmissionPolicy` or by the [`crate::cache::config::PutMode`]), or if the key is not present (or has expired)
+    /// at the time of the confirming read, which does not record a hit or a miss.
+    ///
+    /// The confirming read checks the presence of the key, a concurrent put of the same key may have replaced the value by the time `put_and_confirm` returns.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     assert!(cached.put_and_confirm("topic", "microservices").await);
+    ///     assert!(!cached.put_and_confirm("topic", "cached").await);
+    /// }
+    /// ```
+    pub async fn put_and_confirm(&self, key: Key, value: Value) -> bool {
+        let confirming_key = key.clone();
+        let acknowledgement = match self.put(key, value) {
+            Ok(acknowledgement) => acknowledgement,
+            Err(_) => return false,
         };
+        if acknowledgement.handle().await != CommandStatus::Accepted {
+            return false;
+        }
+        self.store
+            .map_stored_value(&confirming_key, |stored_value| stored_value.is_alive(&self.config.clock))
+            .unwrap_or(false)
+    }
 
-        if let Some(weight) = updated_weight {
-            assert!(weight > 0, "{}", Errors::KeyWeightGtZero("PutOrUpdate"));
-            return self.command_executor.send(CommandType::UpdateWeight(key_id, weight));
+    /// Atomically applies all the [`crate::cache::compare_and_swap::CompareAndSwap`] operations if the current value of each key is equal to its expected value,
+    /// else applies none of them.
+    ///
+    /// The preconditions are checked and the values are swapped while holding the lock on the shard of the keys, so the batch is only accepted if all the keys
+    /// belong to the same shard. Keys can be colocated in a shard by providing a shard hasher using `dual_hash` of [`crate::cache::config::ConfigBuilder`].
+    ///
+    /// The batch is rejected with [`crate::cache::command::RejectionReason::KeysInDifferentShards`] if the keys belong to different shards,
+    /// with [`crate::cache::command::RejectionReason::DuplicateKeys`] if a key appears more than once in the batch,
+    /// and with [`crate::cache::command::RejectionReason::PreconditionFailed`] if any key does not exist or its value is not the expected value.
+    /// The time_to_live of the keys is unchanged, and the weights of the swapped keys are updated using the weight calculation function,
+    /// by a single `crate::cache::command::CommandType::UpdateWeightBatch` to the `CommandExecutor`, whose acknowledgement is returned.
+    ///
+    /// If the weight calculation function returns a [`crate::cache::config::error::WeightError`], none of the swaps are applied and `compare_and_swap_batch` returns a
+    /// [`crate::cache::command::error::CommandSendError`] that wraps the `WeightError`.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::{CommandStatus, RejectionReason};
+    /// use tinylfu_cached::cache::compare_and_swap::CompareAndSwap;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///
+    ///     let status = cached.compare_and_swap_batch(vec![CompareAndSwap::new("topic", "storage", "cached")]).unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Rejected(RejectionReason::PreconditionFailed), status);
+    ///
+    ///     let status = cached.compare_and_swap_batch(vec![CompareAndSwap::new("topic", "microservices", "cached")]).unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    ///     assert_eq!(Some("cached"), cached.get(&"topic"));
+    /// }
+    /// ```
+    pub fn compare_and_swap_batch(&self, swaps: Vec<CompareAndSwap<Key, Value>>) -> CommandSendResult
+        where Value: PartialEq {
+        if self.is_shutting_down() { return shutdown_result(); }
+
+        let swaps = swaps.into_iter().map(|swap| CompareAndSwap {
+            key: swap.key,
+            expected: self.transform_on_put(swap.expected),
+            new_value: self.transform_on_put(swap.new_value),
+        }).collect();
+        let _gate = self.pass_clear_gate();
+        if self.is_shutting_down() { return shutdown_result(); }
+        let weight_calculation_fn = |key: &Key, value: &Value, is_time_to_live_specified| {
+            let weight = self.config.weight_of(key, value, is_time_to_live_specified)?;
+            assert!(weight > 0, "{}", Errors::KeyWeightGtZero("CompareAndSwap"));
+            Ok(weight)
+        };
+        match self.store.compare_and_swap_batch(swaps, weight_calculation_fn)? {
+            BatchSwapResponse::Rejected(reason) => Ok(CommandAcknowledgement::rejected(reason)),
+            BatchSwapResponse::Swapped(key_id_weights) if key_id_weights.is_empty() => Ok(CommandAcknowledgement::accepted()),
+            BatchSwapResponse::Swapped(key_id_weights) => self.command_executor.send(CommandType::UpdateWeightBatch(key_id_weights)),
         }
-        Ok(CommandAcknowledgement::accepted())
     }
 
     /// Deletes the key/value pair from the instance of `CacheD`. Delete is a 2 step process:
@@ -326,6 +642,9 @@ impl<Key, Value> CacheD<Key, Value>
     /// 2) Sends a `crate::cache::command::CommandType::Delete` to the `CommandExecutor` which causes the key weight to be removed from `AdmissionPolicy`.
     ///    This step may happen at a later point in time.
     ///
+    /// If `delete_tombstone_ttl` is configured in [`crate::cache::config::ConfigBuilder`], `delete` also leaves a tombstone for the key,
+    /// and the `put` operations for the key are rejected until the tombstone expires.
+    ///
     /// Since, `delete` is not an immediate operation, clients can `await` on the response to get the [`crate::cache::command::CommandStatus`]
     /// ```
     /// use tinylfu_cached::cache::cached::CacheD;
@@ -341,10 +660,220 @@ impl<Key, Value> CacheD<Key, Value>
     /// }
     /// ```
     pub fn delete(&self, key: Key) -> CommandSendResult {
-        if self.is_shutting_down() { return shutdown_result(); }
+        self.mark_deleted_and_send(key).1
+    }
+
+    /// Removes the key like [`CacheD::delete`], and returns true if the key was present (and alive) in the `crate::cache::store::Store`.
+    ///
+    /// The presence is determined when the key is marked deleted, so there is no need to `get` the key before deleting it.
+    /// The `crate::cache::command::CommandType::Delete` is still sent to the `CommandExecutor`, which removes the key weight from `AdmissionPolicy` later.
+    /// Returns false after the cache is shutdown.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// let _ = cached.put_sync("topic", "microservices");
+    /// assert!(cached.remove(&"topic"));
+    /// assert!(!cached.remove(&"topic"));
+    /// assert_eq!(None, cached.get(&"topic"));
+    /// ```
+    pub fn remove(&self, key: &Key) -> bool {
+        self.mark_deleted_and_send(key.clone()).0
+    }
+
+    /// Deletes all the keys like [`CacheD::delete`], and returns the result of the delete of each key, in the order of the `keys`.
+    ///
+    /// Each key is marked deleted in the `crate::cache::store::Store` immediately, and a single `crate::cache::command::CommandType::DeleteBatch`
+    /// is sent to the `CommandExecutor`, which removes all the keys from the `Store`, `AdmissionPolicy` and `TTLTicker` in one pass.
+    /// So, all the keys share the same [`crate::cache::command::acknowledgement::CommandAcknowledgement`], which is `Accepted` once all the keys are removed.
+    /// The keys that are not present are skipped.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    ///     let _ = cached.put_all(vec![("topic", "microservices"), ("disk", "SSD")]);
+    ///     let results = cached.delete_all(vec!["topic", "disk"]);
+    ///     for result in results {
+    ///         assert_eq!(CommandStatus::Accepted, result.unwrap().handle().await);
+    ///     }
+    ///     assert_eq!(None, cached.get(&"topic"));
+    ///     assert_eq!(0, cached.total_weight_used());
+    /// }
+    /// ```
+    pub fn delete_all(&self, keys: Vec<Key>) -> Vec<CommandSendResult> {
+        if self.is_shutting_down() { return keys.iter().map(|_| shutdown_result()).collect(); }
+        if keys.is_empty() { return Vec::new(); }
+
+        for key in keys.iter() {
+            #[cfg(feature = "trace")]
+            self.record_trace(TraceOperation::Delete, key);
+
+            self.store.mark_deleted(key);
+            if let Some(delete_tombstone_ttl) = self.config.delete_tombstone_ttl {
+                self.add_tombstone(key.clone(), delete_tombstone_ttl);
+            }
+        }
+        let total_keys = keys.len();
+        vec![self.command_executor.send(CommandType::DeleteBatch(keys)); total_keys]
+    }
+
+    fn mark_deleted_and_send(&self, key: Key) -> (bool, CommandSendResult) {
+        if self.is_shutting_down() { return (false, shutdown_result()); }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Delete, &key);
+
+        let was_present = self.store.mark_deleted(&key);
+        if let Some(delete_tombstone_ttl) = self.config.delete_tombstone_ttl {
+            self.add_tombstone(key.clone(), delete_tombstone_ttl);
+        }
+        (was_present, self.command_executor.send(CommandType::Delete(key)))
+    }
+
+    /// Puts all the key/value pairs like [`CacheD::put`], and returns the result of the put of each pair, in the order of the `entries`.
+    ///
+    /// The commands of all the pairs are created before any of them is sent, and are then sent to the `CommandExecutor` one after the other,
+    /// so the acknowledgements can be awaited together. Like [`CacheD::put`], it panics if the weight of any of the pairs is not greater than zero.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    ///     let results = cached.put_all(vec![("topic", "microservices"), ("disk", "SSD")]);
+    ///     for result in results {
+    ///         assert_eq!(CommandStatus::Accepted, result.unwrap().handle().await);
+    ///     }
+    ///     assert_eq!(Some("SSD"), cached.get(&"disk"));
+    /// }
+    /// ```
+    pub fn put_all(&self, entries: Vec<(Key, Value)>) -> Vec<CommandSendResult> {
+        let mut results = Vec::with_capacity(entries.len());
+        let mut commands = Vec::with_capacity(entries.len());
+        let mut positions = Vec::with_capacity(entries.len());
+        for (key, value) in entries {
+            match self.put_command_with_calculated_weight(key, value, self.config.default_time_to_live) {
+                Ok(command) => {
+                    positions.push(results.len());
+                    results.push(None);
+                    commands.push(command);
+                }
+                Err(result) => results.push(Some(result)),
+            }
+        }
+
+        let _gate = self.pass_clear_gate();
+        for (position, result) in positions.into_iter().zip(self.command_executor.send_batch(commands)) {
+            results[position] = Some(result);
+        }
+        results.into_iter().flatten().collect()
+    }
+
+    /// Puts the key/value pair like [`CacheD::put`], and blocks the calling thread till the put is executed by the `CommandExecutor`,
+    /// so that the clients do not need an async runtime to get the [`crate::cache::command::CommandStatus`].
+    ///
+    /// Returns a [`crate::cache::command::error::CommandSendError`] if the put can not be sent, for example after the cache is shutdown.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// assert_eq!(CommandStatus::Accepted, cached.put_sync("topic", "microservices").unwrap());
+    /// assert_eq!(Some("microservices"), cached.get(&"topic"));
+    ///
+    /// cached.shutdown();
+    /// assert!(cached.put_sync("disk", "SSD").is_err());
+    /// ```
+    pub fn put_sync(&self, key: Key, value: Value) -> Result<CommandStatus, CommandSendError> {
+        self.put(key, value).map(|acknowledgement| acknowledgement.block_on_done())
+    }
+
+    /// Puts the key/value pair with the weight like [`CacheD::put_with_weight`], and blocks the calling thread till the put is executed by the `CommandExecutor`.
+    ///
+    /// Returns a [`crate::cache::command::error::CommandSendError`] if the put can not be sent, for example after the cache is shutdown.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// assert_eq!(CommandStatus::Accepted, cached.put_with_weight_sync("topic", "microservices", 50).unwrap());
+    /// assert_eq!(50, cached.total_weight_used());
+    /// ```
+    pub fn put_with_weight_sync(&self, key: Key, value: Value, weight: Weight) -> Result<CommandStatus, CommandSendError> {
+        self.put_with_weight(key, value, weight).map(|acknowledgement| acknowledgement.block_on_done())
+    }
+
+    /// Deletes the key like [`CacheD::delete`], and blocks the calling thread till the delete is executed by the `CommandExecutor`.
+    ///
+    /// Returns a [`crate::cache::command::error::CommandSendError`] if the delete can not be sent, for example after the cache is shutdown.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// let _ = cached.put_sync("topic", "microservices");
+    /// assert_eq!(CommandStatus::Accepted, cached.delete_sync("topic").unwrap());
+    /// assert_eq!(None, cached.get(&"topic"));
+    /// assert_eq!(0, cached.total_weight_used());
+    /// ```
+    pub fn delete_sync(&self, key: Key) -> Result<CommandStatus, CommandSendError> {
+        self.delete(key).map(|acknowledgement| acknowledgement.block_on_done())
+    }
+
+    /// Removes the keys from the instance of `CacheD` and returns a `HashMap` of the removed keys against their values.
+    /// The keys that are not present (or have expired) are omitted from the returned `HashMap`.
+    ///
+    /// Each key is removed from the `crate::cache::store::Store` immediately, so a key is taken by only one of the concurrent `take_all` operations.
+    /// The weights of all the removed keys are then removed from `AdmissionPolicy` using a single `crate::cache::command::CommandType::DeleteTaken`,
+    /// and `take_all` returns after the command is executed. So, `total_weight_used` reflects the removal once `take_all` returns.
+    ///
+    /// If `delete_tombstone_ttl` is configured in [`crate::cache::config::ConfigBuilder`], `take_all` also leaves a tombstone for each removed key.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put_with_weight("topic", "microservices", 10).unwrap().handle().await;
+    ///     let _ = cached.put_with_weight("disk", "SSD", 10).unwrap().handle().await;
+    ///
+    ///     let taken = cached.take_all(vec!["topic", "non-existing"]).await;
+    ///     assert_eq!(1, taken.len());
+    ///     assert_eq!(Some(&"microservices"), taken.get(&"topic"));
+    ///     assert_eq!(None, cached.get(&"topic"));
+    ///     assert_eq!(10, cached.total_weight_used());
+    /// }
+    /// ```
+    pub async fn take_all(&self, keys: Vec<Key>) -> HashMap<Key, Value> {
+        if self.is_shutting_down() { return HashMap::new(); }
+
+        let mut taken = HashMap::with_capacity(keys.len());
+        let mut key_id_expiries = Vec::with_capacity(keys.len());
+        for key in keys {
+            #[cfg(feature = "trace")]
+            self.record_trace(TraceOperation::Delete, &key);
 
-        self.store.mark_deleted(&key);
-        self.command_executor.send(CommandType::Delete(key))
+            if let Some((value, key_id_expiry)) = self.store.take(&key) {
+                if let Some(delete_tombstone_ttl) = self.config.delete_tombstone_ttl {
+                    self.add_tombstone(key.clone(), delete_tombstone_ttl);
+                }
+                key_id_expiries.push((key.clone(), key_id_expiry));
+                taken.insert(key, self.transform_on_get(value));
+            }
+        }
+        if !key_id_expiries.is_empty() {
+            if let Ok(acknowledgement) = self.command_executor.send(CommandType::DeleteTaken(key_id_expiries)) {
+                acknowledgement.handle().await;
+            }
+        }
+        taken
     }
 
     /// Returns an optional reference to the key/value present in the instance of `Cached`.
@@ -372,19 +901,25 @@ impl<Key, Value> CacheD<Key, Value>
     /// ```
     pub fn get_ref(&self, key: &Key) -> Option<KeyValueRef<'_, Key, StoredValue<Value>>> {
         if self.is_shutting_down() { return None; }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Get, key);
 
+        self.slide_time_to_live(key);
         if let Some(value_ref) = self.store.get_ref(key) {
             self.mark_key_accessed(key);
             return Some(value_ref);
         }
+        if self.config.expired_read_behavior == ExpiredReadBehavior::ServeStaleOnce {
+            return self.store.take_expired_ref(key);
+        }
         None
     }
 
-    /// Returns an optional MappedValue for key present in the instance of `Cached`.
+    /// Returns an optional reference to the key/value present in the instance of `Cached`, without marking the key accessed.
     ///
-    /// The parameter `map_fn` is an instance of `Fn` that takes a reference to [`crate::cache::store::stored_value::StoredValue`] and returns any MappedValue.
-    /// This is an extension to `get_ref` method.
-    /// If the key is present in `Cached`, it returns `Some(MappedValue)`, else returns `None`.
+    /// Unlike `get_ref`, `peek_ref` does not influence the access frequency of the key (and hence, the admission and the eviction decisions of the `AdmissionPolicy`),
+    /// and does not record a hit or a miss. It is meant for inspecting the cache, for example, from dashboards and debugging tools.
+    /// Like `get_ref`, the returned reference holds a lock against the shard that contains the key (within the scope of its usage).
     /// ```
     /// use tinylfu_cached::cache::cached::CacheD;
     /// use tinylfu_cached::cache::command::CommandStatus;
@@ -394,139 +929,187 @@ impl<Key, Value> CacheD<Key, Value>
     ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
     ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
     ///     assert_eq!(CommandStatus::Accepted, status);
-    ///     let value = cached.map_get_ref(&"topic", |stored_value| stored_value.value_ref().to_uppercase());
-    ///     assert_eq!("MICROSERVICES", value.unwrap());
+    ///     let value = cached.peek_ref(&"topic");
+    ///     assert_eq!("microservices", value.unwrap().value().value());
     /// }
     /// ```
-    pub fn map_get_ref<MapFn, MappedValue>(&self, key: &Key, map_fn: MapFn) -> Option<MappedValue>
-        where MapFn: Fn(&StoredValue<Value>) -> MappedValue {
+    pub fn peek_ref(&self, key: &Key) -> Option<KeyValueRef<'_, Key, StoredValue<Value>>> {
         if self.is_shutting_down() { return None; }
-
-        if let Some(value_ref) = self.get_ref(key) {
-            return Some(map_fn(value_ref.value()));
-        }
-        None
-    }
-
-    /// Returns the total weight used in the cache.
-    pub fn total_weight_used(&self) -> Weight {
-        self.admission_policy.weight_used()
+        self.store.peek_ref(key)
     }
 
-    /// Returns an instance of [`crate::cache::stats::StatsSummary`].
+    /// Returns true if the key is present in the instance of `Cached` and has not expired.
+    ///
+    /// Unlike `get`, `contains_key` does not mark the key accessed, so it does not influence the access frequency of the key
+    /// (and hence, the admission and the eviction decisions of the `AdmissionPolicy`). It does not record a hit or a miss either.
+    /// An expired key is not contained, even if `crate::cache::expiration::TTLTicker` is yet to remove it.
     /// ```
     /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
     /// use tinylfu_cached::cache::config::ConfigBuilder;
-    /// use tinylfu_cached::cache::stats::StatsType;
     /// #[tokio::main]
     ///  async fn main() {
-    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 200).build());
-    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
-    ///     let _ = cached.put("cache", "cached").unwrap().handle().await;
-    ///     let _ = cached.get(&"topic");
-    ///     let _ = cached.get(&"cache");
-    ///     let stats_summary = cached.stats_summary();
-    ///     assert_eq!(2, stats_summary.get(&StatsType::CacheHits).unwrap());
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    ///     assert!(cached.contains_key(&"topic"));
+    ///     assert!(!cached.contains_key(&"non-existing"));
     /// }
     /// ```
-    pub fn stats_summary(&self) -> StatsSummary {
-        self.store.stats_counter().summary()
+    pub fn contains_key(&self, key: &Key) -> bool {
+        if self.is_shutting_down() { return false; }
+        self.store.is_alive(key)
     }
 
-    /// Shuts down the cache.
-    ///
-    /// Shutdown involves the following:
-    /// 1) Marking `is_shutting_down` to true
-    /// 2) Sending a `crate::cache::command::CommandType::Shutdown` to the `crate::cache::command::command_executor::CommandExecutor`
-    /// 3) Shutting down `crate::cache::expiration::TTLTicker`
-    /// 4) Clearing the data inside `crate::cache::store::Store`
-    /// 5) Clearing the data inside `crate::cache::policy::admission_policy::AdmissionPolicy`
-    /// 6) Clearing the data inside `crate::cache::expiration::TTLTicker`
+    /// Records `count` synthetic accesses of the key in the access frequency of the keys, without touching the key/value pairs in the cache.
     ///
-    /// Any attempt to perform an operation after the `CacheD` instance is shutdown, will result in an error.
+    /// `prime_frequency` allows warming the admission policy with a known access distribution (for example, from the logs of the previous day),
+    /// so that the hot keys are admitted from their first put, even if the cache is full.
     ///
-    /// However, there is race condition sort of a scenario here.
-    /// Consider that `shutdown()` and `put()` on an instance of `Cached` are invoked at the same time.
-    /// Both these operations result in sending different commands to the `CommandExecutor`.
-    /// Somehow, the `Shutdown` command goes in before the `put` command.
-    /// This also means that the client could have performed `await` operation on response from `put`.
-    /// It becomes important to finish the future of the `put` command that has come in at the same time `shutdown` was invoked.
+    /// The synthetic accesses are additive to the real accesses of the key, and are subject to the usual decay (reset) of the access frequency.
+    /// They are not recorded as accesses in the stats.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     cached.prime_frequency(&"topic", 5);
     ///
-    /// This is how `shutdown` in `CommandExecutor` is handled, it finishes all the futures in the pipeline that are placed after the `Shutdown` command.
-    /// All such futures ultimately get [`crate::cache::command::CommandStatus::ShuttingDown`].
-    pub fn shutdown(&self) {
-        if self.is_shutting_down.compare_exchange(false, true, Ordering::Release, Ordering::Relaxed).is_ok() {
-            info!("Starting to shutdown cached");
-            let _ = self.command_executor.shutdown();
-            self.admission_policy.shutdown();
-            self.ttl_ticker.shutdown();
-
-            self.store.clear();
-            self.admission_policy.clear();
-            self.ttl_ticker.clear();
-        }
-    }
-
-    fn mark_key_accessed(&self, key: &Key) {
-        self.pool.add((self.config.key_hash_fn)(key));
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    /// }
+    /// ```
+    pub fn prime_frequency(&self, key: &Key, count: usize) {
+        if self.is_shutting_down() { return; }
+        self.admission_policy.prime((self.config.key_hash_fn)(key), count);
     }
 
-    fn key_description(&self, key: Key, weight: Weight) -> KeyDescription<Key> {
-        let hash = (self.config.key_hash_fn)(&key);
-        KeyDescription::new(key, self.id_generator.next(), hash, weight)
+    /// Invokes `f` with a reference to every alive key and its [`crate::cache::store::stored_value::StoredValue`], without cloning the keys or the values.
+    ///
+    /// `for_each` walks the shards of the `Store` one at a time, and holds the read lock on a shard while `f` is invoked for the entries of that shard.
+    /// Hence:
+    /// - `f` must not call back into the `Cached` instance, a write operation (or a `get_ref`) on a key in the locked shard may deadlock.
+    /// - `f` should be quick, the writes to the locked shard wait till `f` finishes for all its entries.
+    ///
+    /// `for_each` neither records a hit or a miss, nor marks the keys as accessed. Like `get_ref`, the values are passed as they are stored,
+    /// so `value_transform` (if configured) is not applied.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    ///     let _ = cached.put("topic", 10).unwrap().handle().await;
+    ///     let _ = cached.put("cache", 20).unwrap().handle().await;
+    ///
+    ///     let mut sum = 0;
+    ///     cached.for_each(|_key, stored_value| sum += stored_value.value_ref());
+    ///     assert_eq!(30, sum);
+    /// }
+    /// ```
+    pub fn for_each<F>(&self, f: F)
+        where F: FnMut(&Key, &StoredValue<Value>) {
+        if self.is_shutting_down() { return; }
+        self.store.for_each(f);
     }
 
-    fn ttl_ticker(config: &Config<Key, Value>, store: Arc<Store<Key, Value>>, admission_policy: Arc<AdmissionPolicy<Key>>) -> Arc<TTLTicker> {
-        let store_evict_hook = move |key| {
-            store.delete(&key);
-        };
-        let cache_weight_evict_hook = move |key_id: &KeyId| {
-            admission_policy.delete_with_hook(key_id, &store_evict_hook);
-        };
-
-        TTLTicker::new(config.ttl_config(), cache_weight_evict_hook)
+    /// Returns an iterator over the alive keys along with their estimated access frequency, the most frequently accessed keys first.
+    ///
+    /// The iterator walks a snapshot: the keys are collected from all the shards of the `Store`, and sorted by their frequency estimate in
+    /// descending order before the iterator is returned. Hence, the keys put (or removed) after `iter_by_frequency` returns are not reflected.
+    /// Collecting and sorting the keys costs O(n log n) for n keys in the cache, and the snapshot holds a clone of every key.
+    ///
+    /// The frequencies are estimates from the count-min sketch, so two keys may collide and report a higher frequency than their accesses.
+    /// The keys with the same estimate are in no particular order. The accesses that are still buffered (not yet drained to the sketch) are not counted.
+    ///
+    /// `iter_by_frequency` neither records a hit or a miss, nor marks the keys as accessed. Returns an empty iterator after shutdown.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::{AccessDrain, ConfigBuilder};
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).access_drain(AccessDrain::Sync).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let _ = cached.put("disk", "SSD").unwrap().handle().await;
+    ///     let _ = cached.get_ref(&"disk");
+    ///
+    ///     let keys = cached.iter_by_frequency().collect::<Vec<_>>();
+    ///     assert_eq!(vec![("disk", 1), ("topic", 0)], keys);
+    /// }
+    /// ```
+    pub fn iter_by_frequency(&self) -> impl Iterator<Item=(Key, FrequencyEstimate)> {
+        let mut keys = Vec::new();
+        if !self.is_shutting_down() {
+            self.store.for_each(|key, _| keys.push(key.clone()));
+        }
+        let mut keys_by_frequency = keys
+            .into_iter()
+            .map(|key| {
+                let frequency = self.admission_policy.estimate((self.config.key_hash_fn)(&key));
+                (key, frequency)
+            })
+            .collect::<Vec<_>>();
+        keys_by_frequency.sort_unstable_by(|(_, frequency), (_, other_frequency)| other_frequency.cmp(frequency));
+        keys_by_frequency.into_iter()
     }
 
-    fn is_shutting_down(&self) -> bool {
-        self.is_shutting_down.load(Acquire)
+    /// Returns the estimated access frequency of the key, as maintained by the count-min sketch of the `AdmissionPolicy`.
+    ///
+    /// The estimate is what the `AdmissionPolicy` compares when it decides to admit a key or to evict the others for it, which makes
+    /// `estimated_frequency` useful to understand the evictions and to tune the `counters` of [`crate::cache::config::ConfigBuilder`].
+    /// The key does not need to be present in the cache: the sketch retains the accesses of the keys that are deleted, expired or evicted.
+    ///
+    /// The estimate saturates at 16 (the counters of the sketch are 4 bits wide, and the doorkeeper adds one for a key it has seen),
+    /// and two keys may collide and report a higher frequency than their accesses.
+    /// The accesses that are still buffered (not yet drained to the sketch) are not counted.
+    /// `estimated_frequency` neither records a hit or a miss, nor marks the key as accessed.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::{AccessDrain, ConfigBuilder};
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).access_drain(AccessDrain::Sync).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let _ = cached.get(&"topic");
+    ///
+    ///     assert_eq!(1, cached.estimated_frequency(&"topic"));
+    ///     assert_eq!(0, cached.estimated_frequency(&"disk"));
+    /// }
+    /// ```
+    pub fn estimated_frequency(&self, key: &Key) -> FrequencyEstimate {
+        self.admission_policy.estimate((self.config.key_hash_fn)(key))
     }
-}
 
-impl<Key, Value> CacheD<Key, Value>
-    where Key: Hash + Eq + Send + Sync + Clone + 'static,
-          Value: Send + Sync + Clone + 'static {
-    /// Returns an optional reference to the Value in the instance of `Cached`.
+    /// Halves the estimated access frequency of all the keys, and clears the doorkeeper, like the admission policy does periodically
+    /// (read [`crate::cache::config::ConfigBuilder::frequency_reset_after`]).
     ///
-    /// This method is only available if the Value type is Cloneable. This method clones the value and returns it to the client.
+    /// `reset_frequencies` ages the access frequencies on demand, for example, when the access pattern is known to shift.
+    /// The accesses that are still buffered (not yet drained) are applied after the reset, so they are not halved.
+    /// The count of the accesses towards the next periodic reset starts afresh.
     /// ```
     /// use tinylfu_cached::cache::cached::CacheD;
-    /// use tinylfu_cached::cache::command::CommandStatus;
-    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use tinylfu_cached::cache::config::{AccessDrain, ConfigBuilder};
     /// #[tokio::main]
     ///  async fn main() {
-    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
-    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
-    ///     assert_eq!(CommandStatus::Accepted, status);
-    ///     let value = cached.get(&"topic");
-    ///     assert_eq!(Some("microservices"), value);
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).access_drain(AccessDrain::Sync).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     (0..8).for_each(|_| { let _ = cached.get(&"topic"); });
+    ///     assert_eq!(8, cached.estimated_frequency(&"topic"));
+    ///
+    ///     cached.reset_frequencies();
+    ///     assert_eq!(3, cached.estimated_frequency(&"topic"));
     /// }
     /// ```
-    pub fn get(&self, key: &Key) -> Option<Value> {
-        if self.is_shutting_down() { return None; }
-
-        if let Some(value) = self.store.get(key) {
-            self.mark_key_accessed(key);
-            return Some(value);
-        }
-        None
+    pub fn reset_frequencies(&self) {
+        self.admission_policy.reset_frequencies();
     }
 
     /// Returns an optional MappedValue for key present in the instance of `Cached`.
     ///
-    /// The parameter `map_fn` is an instance of `Fn` that takes the cloned Value and returns any MappedValue
-    /// This is an extension to the `get` method.
-    ///
-    /// This method is only available if the Value type is Cloneable.
+    /// The parameter `map_fn` is an instance of `Fn` that takes a reference to [`crate::cache::store::stored_value::StoredValue`] and returns any MappedValue.
+    /// This is an extension to `get_ref` method.
     /// If the key is present in `Cached`, it returns `Some(MappedValue)`, else returns `None`.
     /// ```
     /// use tinylfu_cached::cache::cached::CacheD;
@@ -537,140 +1120,1938 @@ impl<Key, Value> CacheD<Key, Value>
     ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
     ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
     ///     assert_eq!(CommandStatus::Accepted, status);
-    ///     let value = cached.map_get(&"topic", |value| value.to_uppercase());
+    ///     let value = cached.map_get_ref(&"topic", |stored_value| stored_value.value_ref().to_uppercase());
     ///     assert_eq!("MICROSERVICES", value.unwrap());
     /// }
     /// ```
-    pub fn map_get<MapFn, MappedValue>(&self, key: &Key, map_fn: MapFn) -> Option<MappedValue>
-        where MapFn: Fn(Value) -> MappedValue {
+    pub fn map_get_ref<MapFn, MappedValue>(&self, key: &Key, map_fn: MapFn) -> Option<MappedValue>
+        where MapFn: Fn(&StoredValue<Value>) -> MappedValue {
         if self.is_shutting_down() { return None; }
 
-        if let Some(value) = self.get(key) {
-            return Some(map_fn(value));
+        if let Some(value_ref) = self.get_ref(key) {
+            return Some(map_fn(value_ref.value()));
         }
         None
     }
 
-    /// Returns values corresponding to multiple keys.
+    /// Returns the oldest key in the instance of `Cached`, the live key that was inserted before all the other live keys.
     ///
-    /// It takes a vector of reference of keys and returns a `HashMap` containing the key reference and the optional Value.
-    /// If the value is present for a key, the returned `HashMap` will contain the key reference and `Some(Value)`.
-    /// If the value is not present for a key, the returned `HashMap` will contain the key reference and `None` as the value.
+    /// The order of insertion is derived from the monotonically increasing `key_id` that `Cached` assigns to each key
+    /// (exposed via [`crate::cache::store::stored_value::StoredValue::key_id`]), so the difference between the key_ids of the newest and the oldest keys
+    /// gives an idea of the residency window of the cache.
     ///
-    /// This method is only available if the Value type is Cloneable.
+    /// The result is an approximation under concurrent churn. `oldest_entry` scans all the live keys without taking a snapshot,
+    /// so the keys put, deleted, evicted or expired during the scan may or may not be considered. The key returned may also be removed by the time it is used.
+    ///
+    /// This is a diagnostic method, it is `O(n)` in the number of keys and does not record a hit or a miss.
     /// ```
     /// use tinylfu_cached::cache::cached::CacheD;
     /// use tinylfu_cached::cache::config::ConfigBuilder;
     /// #[tokio::main]
     ///  async fn main() {
-    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
-    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
-    ///     let values = cached.multi_get(vec![&"topic", &"non-existing"]);
-    ///     assert_eq!(&Some("microservices"), values.get(&"topic").unwrap());
-    ///     assert_eq!(&None, values.get(&"non-existing").unwrap());
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 200).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let _ = cached.put("cache", "cached").unwrap().handle().await;
+    ///     assert_eq!(Some("topic"), cached.oldest_entry());
     /// }
     /// ```
-    pub fn multi_get<'a>(&self, keys: Vec<&'a Key>) -> HashMap<&'a Key, Option<Value>> {
-        if self.is_shutting_down() { return HashMap::new(); }
-
-        keys.into_iter().map(|key| (key, self.get(key))).collect::<HashMap<_, _>>()
+    pub fn oldest_entry(&self) -> Option<Key> {
+        if self.is_shutting_down() { return None; }
+        self.store.oldest_key()
     }
 
-    /// Returns an instance of [`MultiGetIterator`] that allows iterating over multiple keys and getting the value corresponding to each key.
+    /// Returns the newest key in the instance of `Cached`, the live key that was inserted after all the other live keys.
     ///
-    /// It takes a vector of reference of keys and an instance of `MultiGetIterator`
-    ///
-    /// This method is only available if the Value type is Cloneable.
+    /// Like [`CacheD::oldest_entry`], the result is an approximation under concurrent churn.
     /// ```
     /// use tinylfu_cached::cache::cached::CacheD;
     /// use tinylfu_cached::cache::config::ConfigBuilder;
     /// #[tokio::main]
     ///  async fn main() {
-    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
-    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
-    ///     let mut iterator = cached.multi_get_iterator(vec![&"topic", &"non-existing"]);
-    ///     assert_eq!(Some("microservices"), iterator.next().unwrap());
-    ///     assert_eq!(None, iterator.next().unwrap());
-    ///     assert_eq!(None, iterator.next());
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 200).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let _ = cached.put("cache", "cached").unwrap().handle().await;
+    ///     assert_eq!(Some("cache"), cached.newest_entry());
     /// }
     /// ```
-    pub fn multi_get_iterator<'a>(&'a self, keys: Vec<&'a Key>) -> MultiGetIterator<'a, Key, Value> {
-        MultiGetIterator {
-            cache: self,
-            keys,
-        }
+    pub fn newest_entry(&self) -> Option<Key> {
+        if self.is_shutting_down() { return None; }
+        self.store.newest_key()
     }
 
-    /// Returns an instance of [`MultiGetMapIterator`] that allows iterating over multiple keys, performing a map operation over each key and then getting the value corresponding to each key.
+    /// Returns the number of key/value pairs in the cache, including the expired pairs that are not yet removed by `crate::cache::expiration::TTLTicker`.
     ///
-    /// It takes a vector of reference of keys and an instance of `MultiGetIterator`.
+    /// `len` sums the lengths of the shards of `crate::cache::store::Store`, without walking the key/value pairs, so it is cheap enough to be invoked frequently,
+    /// for example, to emit a metric. Use [`CacheD::live_len`] to exclude the expired pairs.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
     ///
-    /// This method is only available if the Value type is Cloneable.
+    /// let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// assert!(cached.is_empty());
+    ///
+    /// cached.put_sync("topic", "microservices").unwrap();
+    /// assert_eq!(1, cached.len());
+    /// assert!(!cached.is_empty());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    /// Returns true if there are no key/value pairs in the cache, including the expired pairs that are not yet removed. Read [`CacheD::len`].
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of the alive key/value pairs in the cache, excluding the expired pairs that are not yet removed by `crate::cache::expiration::TTLTicker`.
+    ///
+    /// Unlike [`CacheD::len`], `live_len` walks all the key/value pairs (one shard at a time, under the read lock of the shard)
+    /// to check their expiry against the clock of the cache.
+    pub fn live_len(&self) -> usize {
+        self.store.live_len()
+    }
+
+    /// Returns the [`crate::cache::types::KeyId`] of the key, if the key is alive. The `KeyId` is assigned on every put of the key,
+    /// so the `KeyId` of a key changes when it is put again. `key_id_of` does not record a hit or a miss.
+    ///
+    /// The `KeyId`s identify the keys in the low-level structures, for example, in [`crate::cache::store::stored_value::StoredValue::key_id`].
+    /// Use [`CacheD::key_of`] to get the key back from a `KeyId`.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// cached.put_sync("topic", "microservices").unwrap();
+    ///
+    /// let key_id = cached.key_id_of(&"topic").unwrap();
+    /// assert_eq!(Some("topic"), cached.key_of(key_id));
+    /// ```
+    pub fn key_id_of(&self, key: &Key) -> Option<KeyId> {
+        self.store.key_id_of(key)
+    }
+
+    /// Returns the key identified by the [`crate::cache::types::KeyId`], if the key is admitted by the `AdmissionPolicy` and is not removed yet.
+    ///
+    /// The reverse lookup is served by the `AdmissionPolicy`, which already keeps each admitted key by its `KeyId` (to evict the keys),
+    /// so `key_of` does not need an additional index, and costs no additional memory.
+    /// A key that is put is resolved only after its put is executed by the `CommandExecutor`, and an expired key is resolved till `TTLTicker` removes it.
+    pub fn key_of(&self, key_id: KeyId) -> Option<Key> {
+        self.admission_policy.key_of(&key_id)
+    }
+
+    /// Returns the time left before the key expires, as per the [`crate::cache::config::ConfigBuilder::clock`].
+    ///
+    /// It returns:
+        /// None: if the key does not exist (or is deleted), or if the key never expires
+        /// Some(Duration::ZERO): if the key has expired, but is not yet removed by `crate::cache::expiration::TTLTicker`
+        /// Some: the remaining time to live, otherwise
+    ///
+    /// `time_to_live_remaining` neither clones the value, nor records a hit or a miss, which makes it suitable for refreshing the keys ahead of their expiry.
     /// ```
+    /// use std::time::Duration;
     /// use tinylfu_cached::cache::cached::CacheD;
     /// use tinylfu_cached::cache::config::ConfigBuilder;
     /// #[tokio::main]
     ///  async fn main() {
     ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
-    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
-    ///     let mut iterator = cached.multi_get_map_iterator(vec![&"topic", &"non-existing"], |value| value.to_uppercase());
-    ///     assert_eq!(Some("MICROSERVICES".to_string()), iterator.next().unwrap());
-    ///     assert_eq!(None, iterator.next().unwrap());
-    ///     assert_eq!(None, iterator.next());
+    ///     let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(300)).unwrap().handle().await;
+    ///     assert!(cached.time_to_live_remaining(&"topic").unwrap() <= Duration::from_secs(300));
+    ///     assert_eq!(None, cached.time_to_live_remaining(&"non-existing"));
     /// }
     /// ```
-    pub fn multi_get_map_iterator<'a, MapFn, MappedValue>(&'a self, keys: Vec<&'a Key>, map_fn: MapFn) -> MultiGetMapIterator<'a, Key, Value, MapFn, MappedValue>
-        where MapFn: Fn(Value) -> MappedValue {
-        MultiGetMapIterator {
-            iterator: MultiGetIterator {
-                cache: self,
-                keys,
-            },
-            map_fn,
-        }
+    pub fn time_to_live_remaining(&self, key: &Key) -> Option<Duration> {
+        self.store.map_stored_value(key, |stored_value| {
+            if stored_value.is_soft_deleted() {
+                return None;
+            }
+            stored_value.remaining_time_to_live(&self.config.clock)
+        }).flatten()
     }
-}
 
-/// `MultiGetIterator` allows iterating over multiple keys and getting the value corresponding to each key.
-/// ```
-/// use tinylfu_cached::cache::cached::CacheD;
-/// use tinylfu_cached::cache::config::ConfigBuilder;
-/// #[tokio::main]
-///  async fn main() {
-///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
-///     let status = cached.put("topic", "microservices").unwrap().handle().await;
-///     let mut iterator = cached.multi_get_iterator(vec![&"topic", &"non-existing"]);
-///     assert_eq!(Some("microservices"), iterator.next().unwrap());
-///     assert_eq!(None, iterator.next().unwrap());
-///     assert_eq!(None, iterator.next());
-/// }
-/// ```
-pub struct MultiGetIterator<'a, Key, Value>
-    where Key: Hash + Eq + Send + Sync + Clone + 'static,
-          Value: Send + Sync + Clone + 'static {
-    cache: &'a CacheD<Key, Value>,
-    keys: Vec<&'a Key>,
-}
+    /// Returns the total weight used in the cache.
+    pub fn total_weight_used(&self) -> Weight {
+        self.admission_policy.weight_used()
+    }
 
-impl<'a, Key, Value> Iterator for MultiGetIterator<'a, Key, Value>
-    where Key: Hash + Eq + Send + Sync + Clone + 'static,
-          Value: Send + Sync + Clone + 'static {
-    type Item = Option<Value>;
+    /// Returns the total cache weight, which is the `cache_weight` of [`crate::cache::config::ConfigBuilder`].
+    pub fn max_weight(&self) -> Weight {
+        self.config.total_cache_weight
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.keys.is_empty() || self.cache.is_shutting_down() {
-            return None;
-        }
-        let key = self.keys.get(0).unwrap();
-        let value = self.cache.get(key);
+    /// Returns the capacity of the cache, which is the `capacity` of [`crate::cache::config::ConfigBuilder`].
+    pub fn capacity(&self) -> TotalCapacity {
+        self.config.capacity
+    }
 
-        self.keys.remove(0);
-        Some(value)
+    /// Returns the ratio of the total weight used to the total cache weight, between 0 and 1.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 200).build());
+    ///     let _ = cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
+    ///
+    ///     assert_eq!(200, cached.max_weight());
+    ///     assert_eq!(10, cached.capacity());
+    ///     assert_eq!(0.25, cached.weight_utilization());
+    /// }
+    /// ```
+    pub fn weight_utilization(&self) -> f64 {
+        self.total_weight_used() as f64 / self.max_weight() as f64
     }
-}
 
-/// `MultiGetMapIterator` allows iterating over multiple keys, performing a map operation over each key and then getting the value corresponding to each key.
-/// ```
+    /// Returns the number of commands that can be buffered for the `crate::cache::command::command_executor::CommandExecutor` before a write blocks.
+    ///
+    /// The capacity is the `command_buffer_size` of [`crate::cache::config::ConfigBuilder`], unless the `adaptive_command_buffer` is enabled,
+    /// in which case the capacity changes with the pressure of writes. The capacity is `0` with the `inline_execution`, since the commands are not buffered.
+    /// With more than one of the `command_executor_threads`, each thread has a command buffer of this capacity.
+    pub fn command_buffer_capacity(&self) -> usize {
+        self.command_executor.capacity()
+    }
+
+    /// Returns the ratio of the admissions that readmit a key evicted earlier (to create space) to all the admissions, between 0 and 1.
+    ///
+    /// A high thrash rate indicates that the keys oscillate in and out of the cache, which means the cache is undersized for the working set.
+    /// The readmissions are only tracked if [`crate::cache::config::ConfigBuilder::on_readmission`] is set, else the thrash rate is 0.
+    pub fn thrash_rate(&self) -> f64 {
+        self.admission_policy.thrash_rate()
+    }
+
+    /// Returns the resolution of the expiry of the keys, which is the `ttl_tick_duration` of [`crate::cache::config::ConfigBuilder`].
+    ///
+    /// `crate::cache::expiration::TTLTicker` removes the expired keys once every `ttl_tick_duration`, so an expired key (and its weight) may
+    /// stay in the cache for up to `ttl_tick_duration` after its expiry. `get` (and its variants) never return an expired key.
+    /// ```
+    /// use std::time::Duration;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 100).ttl_tick_duration(Duration::from_millis(100)).build());
+    /// assert_eq!(Duration::from_millis(100), cached.ttl_resolution());
+    /// ```
+    pub fn ttl_resolution(&self) -> Duration {
+        self.config.ttl_tick_duration
+    }
+
+    /// Returns a reference to the [`crate::cache::clock::ClockType`] that `Cached` uses to compute the expiry of the keys.
+    ///
+    /// This allows the clients to compute expected expiry times from the same clock that `Cached` uses, instead of creating another clock instance.
+    ///
+    /// `Cached` holds clones (`clone_box`) of this clock, so a controllable clock that shares its state between clones affects the expiry math of `Cached` when it is changed through this reference.
+    /// ```
+    /// use std::time::Duration;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let now = cached.clock().now();
+    ///     let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).unwrap().handle().await;
+    ///     let expire_after = cached.get_ref(&"topic").unwrap().value().expire_after().unwrap();
+    ///     assert!(expire_after >= now + Duration::from_secs(5));
+    /// }
+    /// ```
+    pub fn clock(&self) -> &ClockType {
+        &self.config.clock
+    }
+
+    /// Returns an instance of [`crate::cache::stats::StatsSummary`].
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use tinylfu_cached::cache::stats::StatsType;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 200).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let _ = cached.put("cache", "cached").unwrap().handle().await;
+    ///     let _ = cached.get(&"topic");
+    ///     let _ = cached.get(&"cache");
+    ///     let stats_summary = cached.stats_summary();
+    ///     assert_eq!(2, stats_summary.get(&StatsType::CacheHits).unwrap());
+    /// }
+    /// ```
+    pub fn stats_summary(&self) -> StatsSummary {
+        self.store.stats_counter().summary()
+    }
+
+    /// Renders the stats of the cache in the Prometheus text exposition format, with each metric named `<prefix>_<name>`.
+    ///
+    /// Each [`crate::cache::stats::StatsType`] is rendered as a counter with a stable name, for example, `CacheHits` as `<prefix>_hits_total`
+    /// and `CacheMisses` as `<prefix>_misses_total`. [`CacheD::total_weight_used`] is rendered as the gauge `<prefix>_weight_used`,
+    /// and [`CacheD::len`] as the gauge `<prefix>_keys`.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 200).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let _ = cached.get(&"topic");
+    ///
+    ///     let metrics = cached.prometheus_metrics("cached");
+    ///     assert!(metrics.contains("cached_hits_total 1"));
+    ///     assert!(metrics.contains("cached_keys 1"));
+    /// }
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub fn prometheus_metrics(&self, prefix: &str) -> String {
+        metrics::render(prefix, &self.stats_summary(), self.total_weight_used(), self.len())
+    }
+
+    /// Zeroes all the stats counters, for example, to measure the stats of an interval. Read [`CacheD::stats_summary_and_reset`].
+    pub fn reset_stats(&self) {
+        self.store.stats_counter().reset()
+    }
+
+    /// Returns an instance of [`crate::cache::stats::StatsSummary`] and zeroes the stats counters in one call.
+    ///
+    /// Each counter is read and zeroed atomically, so an increment that happens concurrently is either a part of the returned summary or
+    /// a part of the next summary, and is never lost, unlike invoking [`CacheD::stats_summary`] followed by [`CacheD::reset_stats`].
+    /// The counters are read one at a time, so the summary is not a point-in-time snapshot across all the counters.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use tinylfu_cached::cache::stats::StatsType;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 200).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let _ = cached.get(&"topic");
+    ///
+    ///     let stats_summary = cached.stats_summary_and_reset();
+    ///     assert_eq!(1, stats_summary.get(&StatsType::CacheHits).unwrap());
+    ///     assert_eq!(0, cached.stats_summary().get(&StatsType::CacheHits).unwrap());
+    /// }
+    /// ```
+    pub fn stats_summary_and_reset(&self) -> StatsSummary {
+        self.store.stats_counter().summary_and_reset()
+    }
+
+    /// Suspends the eviction of keys until the returned [`EvictionGuard`] is dropped.
+    ///
+    /// While the eviction is suspended, a `put` that does not fit in the remaining cache weight is admitted without evicting any existing key,
+    /// so the keys read during a multi-step operation are not evicted in the middle of it.
+    /// Keys may still be removed by an explicit `delete` or on expiry.
+    ///
+    /// **The total weight used may exceed the cache weight while the eviction is suspended**, so the guard should be held only for a short duration.
+    /// Once the last guard is dropped, the eviction resumes and the keys with the smallest access frequency are evicted until the total weight used
+    /// is within the cache weight again.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     {
+    ///         let _guard = cached.suspend_eviction();
+    ///         let _ = cached.put_with_weight("topic", "microservices", 60).unwrap().handle().await;
+    ///         let _ = cached.put_with_weight("disk", "SSD", 60).unwrap().handle().await;
+    ///         assert_eq!(120, cached.total_weight_used());
+    ///     }
+    ///     let _ = cached.delete("non-existing").unwrap().handle().await;
+    ///     assert_eq!(60, cached.total_weight_used());
+    /// }
+    /// ```
+    pub fn suspend_eviction(&self) -> EvictionGuard<'_, Key, Value> {
+        self.admission_policy.suspend_eviction();
+        EvictionGuard { cached: self }
+    }
+
+    /// Reserves the `weight` in the cache for a burst of related puts, and returns a [`Reservation`].
+    ///
+    /// The puts made through the `Reservation` are admitted using the reserved weight, so they do not compete against each other
+    /// (or against any other key) for the cache weight, and none of them evicts another key.
+    /// The reserved weight is not available to the other puts, they either find the space in the unreserved weight or evict the existing keys.
+    /// Dropping the `Reservation` releases the reserved weight that is not used.
+    ///
+    /// Returns [`ReservationError::NotEnoughWeightAvailable`] if the `weight` is more than the available weight, that is the weight that is neither used
+    /// nor reserved. Returns [`ReservationError::ShuttingDown`] if the cache is being shutdown.
+    ///
+    /// `weight` must be greater than `0`.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     {
+    ///         let reservation = cached.reserve(60).unwrap();
+    ///         let status = reservation.put_with_weight("topic", "microservices", 30).unwrap().handle().await;
+    ///         assert_eq!(CommandStatus::Accepted, status);
+    ///         let status = reservation.put_with_weight("disk", "SSD", 30).unwrap().handle().await;
+    ///         assert_eq!(CommandStatus::Accepted, status);
+    ///     }
+    ///     assert_eq!(60, cached.total_weight_used());
+    ///     assert!(cached.reserve(50).is_err());
+    /// }
+    /// ```
+    pub fn reserve(&self, weight: Weight) -> Result<Reservation<'_, Key, Value>, ReservationError> {
+        if self.is_shutting_down() { return Err(ReservationError::ShuttingDown); }
+
+        assert!(weight > 0, "{}", Errors::ReservedWeightGtZero);
+        if !self.admission_policy.reserve(weight) {
+            return Err(ReservationError::NotEnoughWeightAvailable(weight));
+        }
+        Ok(Reservation { cached: self, remaining: AtomicI64::new(weight) })
+    }
+
+    /// Removes all the key/value pairs from the cache, and returns an instance of [` crate::cache::command::command_executor::CommandSendResult`] to the clients.
+    /// Unlike [`CacheD::shutdown`], the cache can be used after `clear`.
+    ///
+    /// `clear` is not an immediate operation. It results in `crate::cache::command::CommandType::Clear` to the `crate::cache::command::command_executor::CommandExecutor`,
+    /// so the commands sent before `clear` are executed before the key/value pairs are removed, and the commands sent after `clear` are executed after.
+    ///
+    /// `clear` removes the key/value pairs from `crate::cache::store::Store`, the keys and their access frequencies from `crate::cache::policy::admission_policy::AdmissionPolicy`,
+    /// and the keys from `crate::cache::expiration::TTLTicker`. The removed keys and their weight are counted in
+    /// [`crate::cache::stats::StatsType::KeysDeleted`] and [`crate::cache::stats::StatsType::WeightRemoved`], the other stats are retained.
+    /// If `eviction_listener` is configured in [`crate::cache::config::ConfigBuilder`], it is invoked with each removed key.
+    /// The weight reserved by the outstanding [`Reservation`]s is retained, and remains available to the puts made through them.
+    ///
+    /// `clear` is best-effort with respect to the concurrent puts: a put sent while the clear is in progress is executed after the clear, so the key survives the clear,
+    /// and the cache may never be observed empty. Use [`CacheD::clear_barrier`] to get an empty cache at a point in time.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///
+    ///     let status = cached.clear().unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    ///     assert_eq!(None, cached.get(&"topic"));
+    ///     assert_eq!(0, cached.total_weight_used());
+    /// }
+    /// ```
+    pub fn clear(&self) -> CommandSendResult {
+        if self.is_shutting_down() { return shutdown_result(); }
+        self.command_executor.send(CommandType::Clear)
+    }
+
+    /// Removes all the key/value pairs from the cache like [`CacheD::clear`], and blocks the calling thread till the clear is executed by the `CommandExecutor`.
+    ///
+    /// Unlike `clear`, `clear_barrier` blocks the puts (and the other operations that add or change the keys, like `put_or_update`, `compare_and_swap_batch`
+    /// and `increment_with_ttl`) till the clear is complete, so the cache is empty when the clear completes.
+    /// The puts that are in progress when `clear_barrier` is invoked complete before the clear starts, and are cleared.
+    /// The blocked puts proceed once `clear_barrier` returns, so the cache may not be empty by the time the caller observes it.
+    /// The puts from the listeners that run on the thread of `CommandExecutor` (like the `eviction_listener` or `on_full`) are not blocked,
+    /// since the clear waits for the listeners to return, so the keys put by the listeners may be present after the clear.
+    ///
+    /// Returns a [`crate::cache::command::error::CommandSendError`] if the clear can not be sent, for example after the cache is shutdown,
+    /// or if `clear_barrier` is invoked from a listener that runs on the thread of `CommandExecutor`, because the clear would wait for the listener.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// cached.put_sync("topic", "microservices").unwrap();
+    ///
+    /// assert_eq!(CommandStatus::Accepted, cached.clear_barrier().unwrap());
+    /// assert_eq!(None, cached.get(&"topic"));
+    /// ```
+    pub fn clear_barrier(&self) -> Result<CommandStatus, CommandSendError> {
+        if self.command_executor.is_executor_thread() {
+            return Err(CommandSendError::on_executor_thread("ClearBarrier"));
+        }
+        let _gate = self.clear_gate.write();
+        self.clear().map(|acknowledgement| acknowledgement.block_on_done())
+    }
+
+    /// Shuts down the cache.
+    ///
+    /// Shutdown involves the following:
+    /// 1) Marking `is_shutting_down` to true
+    /// 2) Sending a `crate::cache::command::CommandType::Shutdown` to the `crate::cache::command::command_executor::CommandExecutor`,
+    ///    and waiting till the `CommandExecutor` stops executing the commands, so that no command writes into the `Store` after it is cleared
+    /// 3) Shutting down `crate::cache::expiration::TTLTicker`
+    /// 4) Clearing the data inside `crate::cache::store::Store`
+    /// 5) Clearing the data inside `crate::cache::policy::admission_policy::AdmissionPolicy`
+    /// 6) Clearing the data inside `crate::cache::expiration::TTLTicker`
+    ///
+    /// Any attempt to perform an operation after the `CacheD` instance is shutdown, will result in an error.
+    ///
+    /// However, there is race condition sort of a scenario here.
+    /// Consider that `shutdown()` and `put()` on an instance of `Cached` are invoked at the same time.
+    /// Both these operations result in sending different commands to the `CommandExecutor`.
+    /// Somehow, the `Shutdown` command goes in before the `put` command.
+    /// This also means that the client could have performed `await` operation on response from `put`.
+    /// It becomes important to finish the future of the `put` command that has come in at the same time `shutdown` was invoked.
+    ///
+    /// This is how `shutdown` in `CommandExecutor` is handled, it finishes all the futures in the pipeline that are placed after the `Shutdown` command.
+    /// All such futures ultimately get [`crate::cache::command::CommandStatus::ShuttingDown`].
+    ///
+    /// The operations that change the `Store` directly (like [`CacheD::put_or_update`] and [`CacheD::increment_with_ttl`]) check the shutdown
+    /// after passing the `clear_gate`, and `shutdown` waits for the operations that have passed the `clear_gate` before clearing the `Store`.
+    /// So, an operation either completes before the `Store` is cleared, or is rejected.
+    ///
+    /// `shutdown` can be invoked from a listener that runs on the thread of `CommandExecutor` (like an eviction listener or `on_full`),
+    /// in which case it does not wait for the `CommandExecutor` to stop or for the operations that have passed the `clear_gate`, because they may be waiting
+    /// for the very command that invoked the listener. The `executor_stopped` of its report is false.
+    ///
+    /// Returns a [`ShutdownReport`] that describes how cleanly the cache was shutdown, the same report is also available from `shutdown_report`.
+    /// Invoking `shutdown` on a cache that is already shutdown returns the report of the earlier shutdown.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///
+    ///     let report = cached.shutdown();
+    ///     assert_eq!(1, report.cleared_entries());
+    ///     assert_eq!(0, report.dropped_commands());
+    ///     assert!(report.executor_stopped());
+    /// }
+    /// ```
+    pub fn shutdown(&self) -> ShutdownReport {
+        if self.is_shutting_down.compare_exchange(false, true, Ordering::Release, Ordering::Relaxed).is_ok() {
+            info!("Starting to shutdown cached");
+            if !self.command_executor.is_executor_thread() {
+                drop(self.clear_gate.write());
+            }
+            let _ = self.command_executor.shutdown();
+            let dropped_commands = self.command_executor.dropped_commands();
+            self.admission_policy.shutdown();
+            self.ttl_ticker.shutdown();
+            self.pool.shutdown();
+            if let Some(additional_access_consumer) = &self.config.additional_access_consumer {
+                additional_access_consumer.accept(BufferEvent::Shutdown);
+            }
+
+            let cleared_entries = self.store.clear();
+            self.admission_policy.clear();
+            self.ttl_ticker.clear();
+            self.tombstones.clear();
+
+            #[cfg(feature = "trace")]
+            if let Some(trace_recorder) = &self.config.trace_recorder {
+                trace_recorder.flush();
+            }
+
+            *self.shutdown_report.lock() = Some(ShutdownReport {
+                dropped_commands: dropped_commands.unwrap_or(0),
+                cleared_entries,
+                executor_stopped: dropped_commands.is_some(),
+                ttl_ticker_stopped: false,
+            });
+        }
+        self.shutdown_report().unwrap_or_default()
+    }
+
+    /// Returns the [`ShutdownReport`] of the cache, or `None` if the cache is not shutdown (or is in the middle of the shutdown).
+    ///
+    /// `ttl_ticker_stopped` of the report is evaluated on every invocation, because `crate::cache::expiration::TTLTicker` stops on its next tick after the shutdown.
+    pub fn shutdown_report(&self) -> Option<ShutdownReport> {
+        self.shutdown_report.lock().map(|report| ShutdownReport {
+            ttl_ticker_stopped: self.ttl_ticker.has_stopped(),
+            ..report
+        })
+    }
+
+    /// Shuts down the cache like [`CacheD::shutdown`], and then waits till the threads of `crate::cache::command::command_executor::CommandExecutor`
+    /// and `crate::cache::expiration::TTLTicker` exit, so that no background thread mutates the `crate::cache::store::Store` after it returns.
+    ///
+    /// `shutdown_and_join` consumes the cache. The thread of `TTLTicker` exits on its next tick after the shutdown,
+    /// so `shutdown_and_join` may block for up to `ttl_tick_duration` of [`crate::cache::config::ConfigBuilder`].
+    ///
+    /// Returns the [`ShutdownReport`] of the shutdown, where `ttl_ticker_stopped` is true.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use std::time::Duration;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).ttl_tick_duration(Duration::from_millis(5)).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///
+    ///     let report = cached.shutdown_and_join();
+    ///     assert_eq!(1, report.cleared_entries());
+    ///     assert!(report.ttl_ticker_stopped());
+    /// }
+    /// ```
+    pub fn shutdown_and_join(self) -> ShutdownReport {
+        self.shutdown();
+        self.ttl_ticker.join();
+        let report = self.shutdown_report().unwrap_or_default();
+        self.command_executor.join();
+        report
+    }
+
+    /// Puts the key/value pair, where the value is already transformed by `value_transform` (if configured),
+    /// and the weight includes the weight of the entry in `crate::cache::expiration::TTLTicker` if `time_to_live` is specified.
+    fn put_with_calculated_weight(&self, key: Key, value: Value, time_to_live: Option<Duration>) -> CommandSendResult {
+        match self.put_command_with_calculated_weight(key, value, time_to_live) {
+            Ok(command) => {
+                let _gate = self.pass_clear_gate();
+                self.command_executor.send(command)
+            }
+            Err(result) => result,
+        }
+    }
+
+    /// Returns the put command for the key/value pair with the calculated weight, or the result of the put if the put does not need a command,
+    /// for example, if the put is rejected by the [`crate::cache::config::PutMode`], or if the value is the `delete_on_value`.
+    fn put_command_with_calculated_weight(&self, key: Key, value: Value, time_to_live: Option<Duration>) -> Result<CommandType<Key, Value>, CommandSendResult> {
+        if self.is_shutting_down() { return Err(shutdown_result()); }
+        if self.is_delete_value(&value) { return Err(self.delete(key)); }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Put, &key);
+
+        let value = self.transform_on_put(value);
+        let weight = self.config.weight_of(&key, &value, time_to_live.is_some()).map_err(|error| Err(error.into()))?;
+        assert!(weight > 0, "{}", Errors::WeightCalculationGtZero);
+        if let Some(rejection) = self.put_rejection(&key) {
+            return Err(rejection);
+        }
+        Ok(self.put_command(key, value, weight, time_to_live))
+    }
+
+    fn put_transformed(&self, key: Key, value: Value, weight: Weight, time_to_live: Option<Duration>) -> CommandSendResult {
+        if let Some(rejection) = self.put_rejection(&key) {
+            return rejection;
+        }
+        let _gate = self.pass_clear_gate();
+        self.command_executor.send(self.put_command(key, value, weight, time_to_live))
+    }
+
+    /// Puts the transformed value after the checks of `put_with_ttl` that do not depend on the value: shutdown, and the time to live of a cache built with `no_ttl`.
+    fn put_transformed_checked(&self, key: Key, value: Value, weight: Weight, time_to_live: Option<Duration>) -> CommandSendResult {
+        if self.is_shutting_down() { return shutdown_result(); }
+        if time_to_live.is_some() && self.is_ttl_disabled() { return ttl_disabled_result("PutWithTTL"); }
+        self.put_transformed(key, value, weight, time_to_live)
+    }
+
+    fn put_command(&self, key: Key, value: Value, weight: Weight, time_to_live: Option<Duration>) -> CommandType<Key, Value> {
+        match time_to_live {
+            Some(time_to_live) => CommandType::PutWithTTL(self.key_description(key, weight), value, time_to_live),
+            None => CommandType::Put(self.key_description(key, weight), value),
+        }
+    }
+
+    /// Returns the guard that a put (or any other operation that adds or changes the keys) holds while it changes the `Store` or sends its command.
+    /// [`CacheD::clear_barrier`] holds the `clear_gate` exclusively, so the puts wait till the clear is complete.
+    /// The guard must not be acquired again by the same thread while it is held, else the thread may deadlock with a waiting `clear_barrier`.
+    ///
+    /// The operations invoked from a listener on the thread of `CommandExecutor` do not pass the gate (`None` is returned),
+    /// because `clear_barrier` holds the gate while it waits for the `CommandExecutor`, which waits for the listener.
+    fn pass_clear_gate(&self) -> Option<RwLockReadGuard<'_, ()>> {
+        if self.command_executor.is_executor_thread() {
+            return None;
+        }
+        Some(self.clear_gate.read())
+    }
+
+    fn warn_if_below_ttl_resolution(&self, time_to_live: Duration) {
+        if time_to_live < self.config.ttl_tick_duration {
+            warn!("time_to_live {:?} is less than the ttl resolution {:?}, the expired key will be removed only on the next tick of the TTLTicker",
+                time_to_live, self.config.ttl_tick_duration);
+        }
+    }
+
+    fn is_delete_value(&self, value: &Value) -> bool {
+        self.config.delete_on_value.as_ref().map(|delete_on_value| delete_on_value(value)).unwrap_or(false)
+    }
+
+    fn transform_on_put(&self, value: Value) -> Value {
+        match &self.config.value_transform {
+            Some(value_transform) => (value_transform.on_put)(value),
+            None => value,
+        }
+    }
+
+    fn transform_on_get(&self, value: Value) -> Value {
+        match &self.config.value_transform {
+            Some(value_transform) => (value_transform.on_get)(value),
+            None => value,
+        }
+    }
+
+    fn put_rejection(&self, key: &Key) -> Option<CommandSendResult> {
+        if let Some(rejection) = self.tombstone_rejection(key) {
+            return Some(rejection);
+        }
+        if self.config.put_mode == PutMode::InsertOnly && self.store.is_present(key) {
+            return Some(Ok(CommandAcknowledgement::rejected(RejectionReason::KeyAlreadyExists)));
+        }
+        None
+    }
+
+    fn tombstone_rejection(&self, key: &Key) -> Option<CommandSendResult> {
+        if self.config.delete_tombstone_ttl.is_some() && self.tombstones.is_present(key, &self.config.clock) {
+            return Some(Ok(CommandAcknowledgement::rejected(RejectionReason::KeyRecentlyDeleted)));
+        }
+        None
+    }
+
+    /// Moves the expiry of the alive key to `sliding_ttl` from now, if the [`crate::cache::config::ConfigBuilder::sliding_ttl`] is set,
+    /// the key has a time to live, and the remaining time to live of the key is below half of `sliding_ttl`.
+    /// It must not be invoked while holding a reference to the key, since the update locks the shard of the key.
+    fn slide_time_to_live(&self, key: &Key) {
+        if let Some(sliding_ttl) = self.config.sliding_ttl {
+            let remaining_time_to_live = self.store.map_stored_value(key, |stored_value| {
+                if stored_value.is_alive(&self.config.clock) { stored_value.remaining_time_to_live(&self.config.clock) } else { None }
+            }).flatten();
+
+            if matches!(remaining_time_to_live, Some(remaining) if remaining < sliding_ttl / 2) {
+                let update_response = self.store.update(key, None, Some(sliding_ttl), false);
+                if update_response.did_update_happen() {
+                    let _ = self.reconcile_update(key, update_response, None, false);
+                }
+            }
+        }
+    }
+
+    /// Reconciles the `crate::cache::expiration::TTLTicker` and the weight of the key after the key is updated in the `crate::cache::store::Store`.
+    fn reconcile_update(&self, key: &Key, update_response: UpdateResponse<Value>, weight: Option<Weight>, is_value_present: bool) -> CommandSendResult {
+        let key_id = update_response.key_id_or_panic();
+        let is_time_to_live_specified = update_response.new_expiry().is_some();
+        let type_of_expiry_update = update_response.type_of_expiry_update();
+
+        match &type_of_expiry_update {
+            TypeOfExpiryUpdate::Added(key_id, expiry) => self.ttl_ticker.put(*key_id, *expiry),
+            TypeOfExpiryUpdate::Deleted(key_id, expiry) => self.ttl_ticker.delete(key_id, expiry),
+            TypeOfExpiryUpdate::Updated(key_id, old_expiry, new_expiry) => self.ttl_ticker.update(*key_id, old_expiry, *new_expiry),
+            TypeOfExpiryUpdate::Nothing => {}
+        };
+
+        let updated_weight = if let Some(weight) = weight {
+            Some(weight + Calculation::time_to_live_weight(is_time_to_live_specified))
+        } else if is_value_present {
+            self.store.map_stored_value(key, |stored_value| {
+                self.config.weight_of(key, stored_value.value_ref(), is_time_to_live_specified)
+            }).transpose()?
+        } else {
+            let existing_weight = self.admission_policy.weight_of(&key_id).unwrap_or(0);
+            match type_of_expiry_update {
+                TypeOfExpiryUpdate::Added(_, _) => Some(existing_weight + Calculation::time_to_live_weight(true)),
+                TypeOfExpiryUpdate::Deleted(_, _) => Some(existing_weight - Calculation::time_to_live_weight(true)),
+                _ => None
+            }
+        };
+
+        if let Some(weight) = updated_weight {
+            assert!(weight > 0, "{}", Errors::KeyWeightGtZero("PutOrUpdate"));
+            return self.command_executor.send(CommandType::UpdateWeight(key.clone(), key_id, weight));
+        }
+        Ok(CommandAcknowledgement::accepted())
+    }
+
+    /// Adds a tombstone for the key that expires after `delete_tombstone_ttl`, the tombstone is removed by `crate::cache::expiration::TTLTicker`.
+    fn add_tombstone(&self, key: Key, delete_tombstone_ttl: Duration) {
+        let key_id = self.id_generator.next();
+        let expire_after = StoredValue::<Value>::calculate_expiry(delete_tombstone_ttl, &self.config.clock);
+        if let Some((replaced_key_id, replaced_expire_after)) = self.tombstones.add(key, key_id, expire_after) {
+            self.ttl_ticker.delete(&replaced_key_id, &replaced_expire_after);
+        }
+        self.ttl_ticker.put(key_id, expire_after);
+    }
+
+    #[cfg(feature = "trace")]
+    fn record_trace(&self, operation: TraceOperation, key: &Key) {
+        if let Some(trace_recorder) = &self.config.trace_recorder {
+            trace_recorder.record(operation, (self.config.key_hash_fn)(key), self.config.clock.now());
+        }
+    }
+
+    fn mark_key_accessed(&self, key: &Key) {
+        self.mark_key_hash_accessed((self.config.key_hash_fn)(key));
+    }
+
+    fn mark_key_hash_accessed(&self, key_hash: KeyHash) {
+        match self.config.access_drain {
+            AccessDrain::Sync => {
+                self.admission_policy.record_access(key_hash);
+                if let Some(additional_access_consumer) = &self.config.additional_access_consumer {
+                    additional_access_consumer.accept(BufferEvent::Full(vec![key_hash]));
+                }
+            }
+            _ => self.pool.add(key_hash),
+        }
+    }
+
+    fn key_description(&self, key: Key, weight: Weight) -> KeyDescription<Key> {
+        let hash = (self.config.key_hash_fn)(&key);
+        KeyDescription::new(key, self.id_generator.next(), hash, weight)
+    }
+
+    fn admission_policy(config: &Config<Key, Value>, store: Arc<Store<Key, Value>>, stats_counter: Arc<ConcurrentStatsCounter>) -> AdmissionPolicy<Key> {
+        let mut admission_policy = AdmissionPolicy::with_sketch_depth(config.counters, config.sketch_depth, config.cache_weight_config(), stats_counter);
+        if let Some(audit_key_fn) = &config.audit_key_fn {
+            admission_policy = admission_policy.audit_removals(audit_key_fn.clone());
+        }
+        if let Some(admission_observer) = &config.admission_observer {
+            admission_policy = admission_policy.observe_admissions(admission_observer.clone());
+        }
+        if let Some(on_readmission) = &config.on_readmission {
+            admission_policy = admission_policy.track_readmissions(on_readmission.clone());
+        }
+        if let Some(accesses) = config.frequency_reset_after {
+            admission_policy = admission_policy.reset_frequencies_after(accesses);
+        }
+        if config.admission_mode == AdmissionMode::Always {
+            admission_policy = admission_policy.admit_always();
+        }
+        if let Some(min_residency) = config.min_residency {
+            admission_policy = admission_policy.protect_young_keys(MinResidency::new(min_residency, config.clock.clone_box()));
+        }
+        if config.prefer_evicting_ttl_entries {
+            let expiry_lookup = move |key: &Key| {
+                store.map_stored_value(key, |stored_value| stored_value.expire_after()).flatten()
+            };
+            return admission_policy.prefer_evicting_expiring_keys(Box::new(expiry_lookup));
+        }
+        admission_policy
+    }
+
+    fn ttl_ticker(config: &Config<Key, Value>, store: Arc<Store<Key, Value>>, admission_policy: Arc<AdmissionPolicy<Key>>, tombstones: Arc<Tombstones<Key>>) -> Arc<TTLTicker> {
+        if !config.ttl_enabled {
+            return TTLTicker::idle(config.ttl_config());
+        }
+        let batch_eviction_listener = config.batch_eviction_listener.clone();
+        let is_batching_evictions = batch_eviction_listener.is_some();
+        let eviction_listener = config.eviction_listener.clone();
+        let expired_keys = Arc::new(Mutex::new(Vec::new()));
+
+        let store_evict_hook = {
+            let expired_keys = expired_keys.clone();
+            move |key| {
+                if let (Some((value, _)), Some(eviction_listener)) = (store.remove_expired(&key), &eviction_listener) {
+                    eviction_listener(&key, &value, EvictionReason::Expired);
+                }
+                if is_batching_evictions {
+                    expired_keys.lock().push((key, EvictionReason::Expired));
+                }
+            }
+        };
+        let cache_weight_evict_hook = move |key_id: &KeyId| {
+            if tombstones.remove(key_id) {
+                return;
+            }
+            admission_policy.delete_with_hook(key_id, EvictionReason::Expired, &store_evict_hook);
+        };
+
+        let reaped_hook = move || {
+            if let Some(batch_eviction_listener) = &batch_eviction_listener {
+                let expired_keys = std::mem::take(&mut *expired_keys.lock());
+                if !expired_keys.is_empty() {
+                    batch_eviction_listener(expired_keys);
+                }
+            }
+        };
+
+        TTLTicker::with_reaped_hook(config.ttl_config(), cache_weight_evict_hook, reaped_hook)
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        self.is_shutting_down.load(Acquire)
+    }
+
+    fn is_ttl_disabled(&self) -> bool {
+        !self.config.ttl_enabled
+    }
+}
+
+impl<Key, Value> CacheD<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static {
+    /// Returns an optional reference to the Value in the instance of `Cached`.
+    ///
+    /// This method is only available if the Value type is Cloneable. This method clones the value and returns it to the client.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    ///     let value = cached.get(&"topic");
+    ///     assert_eq!(Some("microservices"), value);
+    /// }
+    /// ```
+    pub fn get(&self, key: &Key) -> Option<Value> {
+        self.get_result(key).ok().flatten()
+    }
+
+    /// Returns the Value corresponding to the key, or an [`ExpiredError`] if the key has expired and the
+    /// [`crate::cache::config::ConfigBuilder::expired_read_behavior`] is [`crate::cache::config::ExpiredReadBehavior::Error`].
+    ///
+    /// It returns:
+        /// Ok(Some): if the key is alive, or if the key has expired and the stale value is served by `ExpiredReadBehavior::ServeStaleOnce`
+        /// Ok(None): if the key does not exist, or if the key has expired and the `expired_read_behavior` is `ExpiredReadBehavior::Miss`
+        /// Err(ExpiredError): if the key has expired and the `expired_read_behavior` is `ExpiredReadBehavior::Error`
+    ///
+    /// An expired key is recorded as a miss with every `expired_read_behavior`. [`CacheD::get`] is `get_result` that treats `ExpiredError` as `None`.
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use std::time::Duration;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::{ConfigBuilder, ExpiredReadBehavior};
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).expired_read_behavior(ExpiredReadBehavior::Error).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(Ok(Some("microservices")), cached.get_result(&"topic"));
+    ///     assert_eq!(Ok(None), cached.get_result(&"non-existing"));
+    /// }
+    /// ```
+    pub fn get_result(&self, key: &Key) -> Result<Option<Value>, ExpiredError> {
+        if self.is_shutting_down() { return Ok(None); }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Get, key);
+
+        if let Some(value) = self.store.get(key) {
+            self.mark_key_accessed(key);
+            self.slide_time_to_live(key);
+            return Ok(Some(self.transform_on_get(value)));
+        }
+        match self.config.expired_read_behavior {
+            ExpiredReadBehavior::Miss => Ok(None),
+            ExpiredReadBehavior::ServeStaleOnce => Ok(self.store.take_expired(key).map(|value| self.transform_on_get(value))),
+            ExpiredReadBehavior::Error if self.store.is_expired(key) => Err(ExpiredError),
+            ExpiredReadBehavior::Error => Ok(None),
+        }
+    }
+
+    /// Returns an optional Value corresponding to the key, only if the value is at most `max_age` old.
+    ///
+    /// The age of a value is the time elapsed (as per the [`crate::cache::config::ConfigBuilder::clock`]) since it was put, or since it was
+    /// last replaced by `put_or_update`, read [`crate::cache::store::stored_value::StoredValue::age`].
+    /// A value older than `max_age` is treated as a miss, even though it is alive, which lets the clients enforce freshness independent of `time_to_live`.
+    /// The older value is not removed from the cache.
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use std::time::Duration;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(Some("microservices"), cached.get_if_fresher_than(&"topic", Duration::from_secs(60)));
+    /// }
+    /// ```
+    pub fn get_if_fresher_than(&self, key: &Key, max_age: Duration) -> Option<Value> {
+        if self.is_shutting_down() { return None; }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Get, key);
+
+        if let Some(value) = self.store.get_if_fresher_than(key, max_age) {
+            self.mark_key_accessed(key);
+            return Some(self.transform_on_get(value));
+        }
+        None
+    }
+
+    /// Returns an optional Value corresponding to the key, without marking the key accessed.
+    ///
+    /// Unlike `get`, `peek` does not influence the access frequency of the key (and hence, the admission and the eviction decisions of the `AdmissionPolicy`),
+    /// and does not record a hit or a miss. It is meant for inspecting the cache, for example, from dashboards and debugging tools.
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    ///     assert_eq!(Some("microservices"), cached.peek(&"topic"));
+    /// }
+    /// ```
+    pub fn peek(&self, key: &Key) -> Option<Value> {
+        if self.is_shutting_down() { return None; }
+        self.store.peek(key).map(|value| self.transform_on_get(value))
+    }
+
+    /// Returns the Value corresponding to the key along with its current weight in the `AdmissionPolicy`.
+    ///
+    /// The key_id of the key is resolved once, so the returned weight belongs to the returned value even if the key is concurrently replaced.
+    /// The key is marked accessed once. Returns None if the key is not present, or if it is not yet admitted by the `AdmissionPolicy`.
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
+    ///     assert_eq!(Some(("microservices", 50)), cached.get_weighted(&"topic"));
+    ///     assert_eq!(None, cached.get_weighted(&"non-existing"));
+    /// }
+    /// ```
+    pub fn get_weighted(&self, key: &Key) -> Option<(Value, Weight)> {
+        if self.is_shutting_down() { return None; }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Get, key);
+
+        let (value, key_id) = self.store.get_ref(key).map(|key_value_ref| {
+            let stored_value = key_value_ref.value();
+            (stored_value.value(), stored_value.key_id())
+        })?;
+        self.mark_key_accessed(key);
+        self.admission_policy.weight_of(&key_id).map(|weight| (self.transform_on_get(value), weight))
+    }
+
+    /// Returns the Value corresponding to the key along with its metadata: its key_id, its current weight in the `AdmissionPolicy`,
+    /// its expiry and the estimate of its access frequency, read [`EntryMetadata`].
+    ///
+    /// The key is looked up once and the lock on its shard is released before the `AdmissionPolicy` is queried, so the metadata belongs to the returned value
+    /// even if the key is concurrently replaced. Like [`CacheD::get_with_frequency`], the frequency is estimated **before** this access is recorded.
+    /// The key is marked accessed once. Returns None if the key is not present, or if it is not yet admitted by the `AdmissionPolicy`.
+    /// Use [`CacheD::peek_with_metadata`] to get the metadata without marking the key accessed.
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use std::time::Duration;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put_with_weight_and_ttl("topic", "microservices", 50, Duration::from_secs(300)).unwrap().handle().await;
+    ///
+    ///     let metadata = cached.get_with_metadata(&"topic").unwrap();
+    ///     assert_eq!(&"microservices", metadata.value());
+    ///     assert!(metadata.expire_after().is_some());
+    ///     assert_eq!(None, cached.get_with_metadata(&"non-existing").map(|metadata| metadata.into_value()));
+    /// }
+    /// ```
+    pub fn get_with_metadata(&self, key: &Key) -> Option<EntryMetadata<Value>> {
+        if self.is_shutting_down() { return None; }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Get, key);
+
+        let entry = self.store.get_ref(key).map(|key_value_ref| Self::entry_of(key_value_ref.value()))?;
+        let key_hash = (self.config.key_hash_fn)(key);
+        let metadata = self.metadata_of(entry, key_hash);
+        self.mark_key_hash_accessed(key_hash);
+        metadata
+    }
+
+    /// Returns the Value corresponding to the key along with its metadata like [`CacheD::get_with_metadata`], without marking the key accessed,
+    /// and without recording a hit or a miss, like [`CacheD::peek`].
+    /// This method is only available if the Value type is Cloneable.
+    pub fn peek_with_metadata(&self, key: &Key) -> Option<EntryMetadata<Value>> {
+        if self.is_shutting_down() { return None; }
+        let entry = self.store.peek_ref(key).map(|key_value_ref| Self::entry_of(key_value_ref.value()))?;
+        self.metadata_of(entry, (self.config.key_hash_fn)(key))
+    }
+
+    fn entry_of(stored_value: &StoredValue<Value>) -> (Value, KeyId, Option<ExpireAfter>) {
+        (stored_value.value(), stored_value.key_id(), stored_value.expire_after())
+    }
+
+    fn metadata_of(&self, (value, key_id, expire_after): (Value, KeyId, Option<ExpireAfter>), key_hash: KeyHash) -> Option<EntryMetadata<Value>> {
+        let weight = self.admission_policy.weight_of(&key_id)?;
+        Some(EntryMetadata {
+            value: self.transform_on_get(value),
+            key_id,
+            weight,
+            expire_after,
+            estimated_frequency: self.admission_policy.estimate(key_hash),
+        })
+    }
+
+    /// Returns the Value corresponding to the key along with the estimate of its access frequency, hashing the key only once.
+    ///
+    /// The frequency is estimated **before** this access is recorded, so the returned estimate reflects only the prior accesses of the key.
+    /// The key is marked accessed once, and the access is reflected in the estimate only after it is drained to the `AdmissionPolicy`,
+    /// read [`crate::cache::config::AccessDrain`].
+    /// Returns None if the key is not present. This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::{AccessDrain, ConfigBuilder};
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).access_drain(AccessDrain::Sync).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(Some(("microservices", 0)), cached.get_with_frequency(&"topic"));
+    ///     assert_eq!(Some(("microservices", 1)), cached.get_with_frequency(&"topic"));
+    ///     assert_eq!(None, cached.get_with_frequency(&"non-existing"));
+    /// }
+    /// ```
+    pub fn get_with_frequency(&self, key: &Key) -> Option<(Value, FrequencyEstimate)> {
+        if self.is_shutting_down() { return None; }
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Get, key);
+
+        let value = self.store.get(key)?;
+        let key_hash = (self.config.key_hash_fn)(key);
+        let frequency = self.admission_policy.estimate(key_hash);
+        self.mark_key_hash_accessed(key_hash);
+        Some((self.transform_on_get(value), frequency))
+    }
+
+    /// Returns the Value corresponding to the key if it is present in the instance of `Cached`, else returns the supplied `default`.
+    ///
+    /// The `default` is not stored in `Cached`, the cache remains unchanged after a miss.
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!("microservices", cached.get_or(&"topic", "cache"));
+    ///     assert_eq!("cache", cached.get_or(&"non-existing", "cache"));
+    ///     assert_eq!(None, cached.get(&"non-existing"));
+    /// }
+    /// ```
+    pub fn get_or(&self, key: &Key, default: Value) -> Value {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Returns the Value corresponding to the key if it is present in the instance of `Cached`, else returns the value computed by `default_fn`.
+    ///
+    /// `default_fn` is invoked only on a miss, and the computed value is not stored in `Cached`.
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!("microservices", cached.get_or_else(&"topic", || "cache"));
+    ///     assert_eq!("cache", cached.get_or_else(&"non-existing", || "cache"));
+    ///     assert_eq!(None, cached.get(&"non-existing"));
+    /// }
+    /// ```
+    pub fn get_or_else<DefaultFn>(&self, key: &Key, default_fn: DefaultFn) -> Value
+        where DefaultFn: FnOnce() -> Value {
+        self.get(key).unwrap_or_else(default_fn)
+    }
+
+    /// Returns the Value corresponding to the key if it is present in the instance of `Cached`, else computes the value using `compute_fn`,
+    /// puts it in `Cached` and returns it.
+    ///
+    /// The computed value is put using `put`, which is not an immediate operation, and `get_or_insert_with` does not wait for the put to be executed.
+    /// The put may be rejected (for example, by the `AdmissionPolicy`), the computed value is returned regardless.
+    ///
+    /// `compute_fn` may run more than once for the same key if `get_or_insert_with` is invoked concurrently for the key,
+    /// since all the concurrent invocations may miss the key before any of the computed values is put. Use [`CacheD::get_or_insert_with_single_flight`]
+    /// to compute the value of a key only once amongst the concurrent invocations.
+    ///
+    /// The computed value is returned without being put, if the cache is being shutdown.
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    ///     assert_eq!("microservices", cached.get_or_insert_with("topic", || "microservices"));
+    ///
+    ///     let _ = cached.put("disk", "SSD").unwrap().handle().await;
+    ///     assert_eq!("microservices", cached.get_or_insert_with("topic", || "cache"));
+    /// }
+    /// ```
+    pub fn get_or_insert_with<ComputeFn>(&self, key: Key, compute_fn: ComputeFn) -> Value
+        where ComputeFn: FnOnce() -> Value {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = compute_fn();
+        let _ = self.put(key, value.clone());
+        value
+    }
+
+    /// Returns the Value corresponding to the key if it is present in the instance of `Cached`, else computes the value using `compute_fn`,
+    /// puts it in `Cached` and returns it, like [`CacheD::get_or_insert_with`].
+    ///
+    /// Unlike `get_or_insert_with`, the concurrent invocations for the same key are coordinated so that `compute_fn` runs only once:
+    /// the first invocation computes the value, and the others wait for it and return the computed value.
+    /// The invocation that computes the value blocks (after computing it) till the put is executed by the `CommandExecutor`,
+    /// so that the invocations that come after the computation find the key in `Cached`.
+    /// `compute_fn` may still run again if the put is rejected, or if the key is removed after the put.
+    ///
+    /// The computed value is returned without being put, if the cache is being shutdown.
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     assert_eq!("microservices", cached.get_or_insert_with_single_flight("topic", || "microservices"));
+    ///     assert_eq!(Some("microservices"), cached.get(&"topic"));
+    /// }
+    /// ```
+    pub fn get_or_insert_with_single_flight<ComputeFn>(&self, key: Key, compute_fn: ComputeFn) -> Value
+        where ComputeFn: FnOnce() -> Value {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        self.single_flight.get_or_compute(&key.clone(), || {
+            if let Some(value) = self.peek(&key) {
+                return value;
+            }
+            let value = compute_fn();
+            let _ = self.put(key, value.clone()).map(|acknowledgement| acknowledgement.block_on_done());
+            value
+        })
+    }
+
+    /// Returns the Value corresponding to the key if it is present in the instance of `Cached`, and extends the expiry of the key to `now + new_ttl`.
+    ///
+    /// Each invocation can specify a different `new_ttl`, which allows sliding expiration with a per-access `time_to_live`.
+    /// A key that was put without `time_to_live` becomes an expiring key, and its weight is reconciled the same way `put_or_update` does.
+    ///
+    /// The change in weight (if any) is not an immediate operation and results in `crate::cache::command::CommandType::UpdateWeight`,
+    /// the expiry of the key is changed before `get_and_extend` returns.
+    /// Panics if the cache is built with [`crate::cache::config::ConfigBuilder::no_ttl`].
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use std::time::Duration;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).unwrap().handle().await;
+    ///     assert_eq!(Some("microservices"), cached.get_and_extend(&"topic", Duration::from_secs(300)));
+    ///     assert_eq!(None, cached.get_and_extend(&"non-existing", Duration::from_secs(300)));
+    /// }
+    /// ```
+    pub fn get_and_extend(&self, key: &Key, new_ttl: Duration) -> Option<Value> {
+        assert!(!self.is_ttl_disabled(), "{}", Errors::TimeToLiveDisabled("get_and_extend"));
+        let value = self.get(key)?;
+        let update_response = self.store.update(key, None, Some(new_ttl), false);
+        if update_response.did_update_happen() {
+            let _ = self.reconcile_update(key, update_response, None, false);
+        }
+        Some(value)
+    }
+
+    /// Returns an optional MappedValue for key present in the instance of `Cached`.
+    ///
+    /// The parameter `map_fn` is an instance of `Fn` that takes the cloned Value and returns any MappedValue
+    /// This is an extension to the `get` method.
+    ///
+    /// This method is only available if the Value type is Cloneable.
+    /// If the key is present in `Cached`, it returns `Some(MappedValue)`, else returns `None`.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    ///     let value = cached.map_get(&"topic", |value| value.to_uppercase());
+    ///     assert_eq!("MICROSERVICES", value.unwrap());
+    /// }
+    /// ```
+    pub fn map_get<MapFn, MappedValue>(&self, key: &Key, map_fn: MapFn) -> Option<MappedValue>
+        where MapFn: Fn(Value) -> MappedValue {
+        if self.is_shutting_down() { return None; }
+
+        if let Some(value) = self.get(key) {
+            return Some(map_fn(value));
+        }
+        None
+    }
+
+    /// Returns the MappedValue for key present in the instance of `Cached`, else returns the supplied `default_mapped`.
+    ///
+    /// The parameter `map_fn` is an instance of `Fn` that takes the cloned Value and returns any MappedValue.
+    /// This is an extension to the `map_get` method, `default_mapped` is also returned if the cache is being shutdown.
+    ///
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     assert_eq!(13, cached.map_get_or(&"topic", 0, |value| value.len()));
+    ///     assert_eq!(0, cached.map_get_or(&"non-existing", 0, |value| value.len()));
+    /// }
+    /// ```
+    pub fn map_get_or<MapFn, MappedValue>(&self, key: &Key, default_mapped: MappedValue, map_fn: MapFn) -> MappedValue
+        where MapFn: Fn(Value) -> MappedValue {
+        self.map_get(key, map_fn).unwrap_or(default_mapped)
+    }
+
+    /// Returns values corresponding to multiple keys.
+    ///
+    /// It takes a vector of reference of keys and returns a `HashMap` containing the key reference and the optional Value.
+    /// If the value is present for a key, the returned `HashMap` will contain the key reference and `Some(Value)`.
+    /// If the value is not present for a key, the returned `HashMap` will contain the key reference and `None` as the value.
+    ///
+    /// The keys are looked up in batches of `MULTI_GET_BATCH_SIZE` (1024) keys, and the thread yields between the batches.
+    /// The lock on a shard is held only while a single key is looked up, so a large `multi_get` does not block the writes.
+    /// However, the returned `HashMap` holds all the keys, so it is recommended to keep a `multi_get` within 10_000 keys,
+    /// and use `multi_get_iterator` (that does not collect the values) for larger inputs.
+    ///
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let values = cached.multi_get(vec![&"topic", &"non-existing"]);
+    ///     assert_eq!(&Some("microservices"), values.get(&"topic").unwrap());
+    ///     assert_eq!(&None, values.get(&"non-existing").unwrap());
+    /// }
+    /// ```
+    pub fn multi_get<'a>(&self, keys: Vec<&'a Key>) -> HashMap<&'a Key, Option<Value>> {
+        if self.is_shutting_down() { return HashMap::new(); }
+
+        self.in_batches(keys, |key| self.get(key))
+    }
+
+    /// Returns values corresponding to multiple keys, excluding the keys that have expired, irrespective of the `expired_read_behavior`.
+    ///
+    /// It takes a vector of reference of keys and returns a `HashMap` containing the key reference and the optional Value, like `multi_get`.
+    /// Unlike `multi_get`, which follows [`crate::cache::config::ConfigBuilder::expired_read_behavior`] and may serve the stale value
+    /// of an expired key with `ExpiredReadBehavior::ServeStaleOnce`, `multi_get_fresh` always reports an expired key as `None`,
+    /// even if `crate::cache::expiration::TTLTicker` has not removed it yet. It does not consume the one stale read of such a key.
+    ///
+    /// Like `multi_get`, the keys are looked up in batches, and it is recommended to keep a `multi_get_fresh` within 10_000 keys.
+    ///
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use std::time::Duration;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 200).build());
+    ///     let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(300)).unwrap().handle().await;
+    ///     let values = cached.multi_get_fresh(vec![&"topic", &"non-existing"]);
+    ///     assert_eq!(&Some("microservices"), values.get(&"topic").unwrap());
+    ///     assert_eq!(&None, values.get(&"non-existing").unwrap());
+    /// }
+    /// ```
+    pub fn multi_get_fresh<'a>(&self, keys: Vec<&'a Key>) -> HashMap<&'a Key, Option<Value>> {
+        if self.is_shutting_down() { return HashMap::new(); }
+
+        self.in_batches(keys, |key| {
+            #[cfg(feature = "trace")]
+            self.record_trace(TraceOperation::Get, key);
+
+            self.store.get(key).map(|value| {
+                self.mark_key_accessed(key);
+                self.slide_time_to_live(key);
+                self.transform_on_get(value)
+            })
+        })
+    }
+
+    /// Looks up the keys in batches of `MULTI_GET_BATCH_SIZE`, yielding the thread between the batches,
+    /// so that a very large input does not starve the other threads (including the writers).
+    fn in_batches<'a, GetFn>(&self, keys: Vec<&'a Key>, get: GetFn) -> HashMap<&'a Key, Option<Value>>
+        where GetFn: Fn(&Key) -> Option<Value> {
+        let mut values = HashMap::with_capacity(keys.len().min(MULTI_GET_BATCH_SIZE));
+        for (index, batch) in keys.chunks(MULTI_GET_BATCH_SIZE).enumerate() {
+            if index > 0 {
+                thread::yield_now();
+            }
+            values.extend(batch.iter().map(|key| (*key, get(key))));
+        }
+        values
+    }
+
+    /// Returns an instance of [`MultiGetIterator`] that allows iterating over multiple keys and getting the value corresponding to each key.
+    ///
+    /// It takes a vector of reference of keys and an instance of `MultiGetIterator`
+    ///
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let mut iterator = cached.multi_get_iterator(vec![&"topic", &"non-existing"]);
+    ///     assert_eq!(Some("microservices"), iterator.next().unwrap());
+    ///     assert_eq!(None, iterator.next().unwrap());
+    ///     assert_eq!(None, iterator.next());
+    /// }
+    /// ```
+    pub fn multi_get_iterator<'a>(&'a self, keys: Vec<&'a Key>) -> MultiGetIterator<'a, Key, Value> {
+        MultiGetIterator {
+            cache: self,
+            keys: keys.into_iter(),
+            was_interrupted: false,
+        }
+    }
+
+    /// Returns an instance of [`MultiGetMapIterator`] that allows iterating over multiple keys, performing a map operation over each key and then getting the value corresponding to each key.
+    ///
+    /// It takes a vector of reference of keys and an instance of `MultiGetIterator`.
+    ///
+    /// This method is only available if the Value type is Cloneable.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let mut iterator = cached.multi_get_map_iterator(vec![&"topic", &"non-existing"], |value| value.to_uppercase());
+    ///     assert_eq!(Some("MICROSERVICES".to_string()), iterator.next().unwrap());
+    ///     assert_eq!(None, iterator.next().unwrap());
+    ///     assert_eq!(None, iterator.next());
+    /// }
+    /// ```
+    pub fn multi_get_map_iterator<'a, MapFn, MappedValue>(&'a self, keys: Vec<&'a Key>, map_fn: MapFn) -> MultiGetMapIterator<'a, Key, Value, MapFn, MappedValue>
+        where MapFn: Fn(Value) -> MappedValue {
+        MultiGetMapIterator {
+            iterator: self.multi_get_iterator(keys),
+            map_fn,
+        }
+    }
+
+    /// Returns an iterator over a copy of the alive key/value pairs, in no particular order.
+    ///
+    /// The iterator is weakly consistent: it walks the shards of the `Store` one at a time, copying the alive pairs of a shard under its read lock
+    /// when the iterator reaches the shard, so the whole cache is never locked. A pair put (or removed) while iterating may or may not be yielded.
+    /// The pairs that have expired as per the configured clock are skipped, even if they are not yet removed.
+    ///
+    /// `iter` neither records a hit or a miss, nor marks the keys as accessed. Like `get`, `value_transform` (if configured) is applied to the values.
+    /// Returns an empty iterator after shutdown.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let _ = cached.put("disk", "SSD").unwrap().handle().await;
+    ///
+    ///     let mut entries = cached.iter().collect::<Vec<_>>();
+    ///     entries.sort();
+    ///     assert_eq!(vec![("disk", "SSD"), ("topic", "microservices")], entries);
+    /// }
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item=(Key, Value)> + '_ {
+        let total_shards = if self.is_shutting_down() { 0 } else { self.store.total_shards() };
+        (0..total_shards)
+            .flat_map(move |shard_index| self.store.shard_entries(shard_index, |_| true))
+            .map(move |entry| (entry.key, self.transform_on_get(entry.value)))
+    }
+
+    /// Returns a [`MultiGetStream`] that yields the value (or `None`) of each of the `keys`, in the order of the `keys`.
+    ///
+    /// The stream is a thin wrapper over [`CacheD::multi_get_iterator`], every value is ready when it is polled, because `get` is synchronous.
+    /// It allows the cache to be used in the stream based code, for example, with the combinators of `futures::StreamExt`.
+    /// ```
+    /// use futures::StreamExt;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///
+    ///     let values: Vec<Option<&str>> = cached.multi_get_stream(vec![&"topic", &"non-existing"]).collect().await;
+    ///     assert_eq!(vec![Some("microservices"), None], values);
+    /// }
+    /// ```
+    #[cfg(feature = "stream")]
+    pub fn multi_get_stream<'a>(&'a self, keys: Vec<&'a Key>) -> MultiGetStream<'a, Key, Value> {
+        MultiGetStream {
+            iterator: self.multi_get_iterator(keys),
+        }
+    }
+
+    /// Returns a [`GetStream`] that yields each of the `keys` along with its value, where the hits are yielded before the misses.
+    ///
+    /// The hits are looked up when `get_stream` is invoked, so a slow `load_fn` never holds up the hits.
+    /// The value of a miss is loaded (and put in the cache) using `load_fn` through [`CacheD::get_or_insert_with_single_flight`] when the miss is polled,
+    /// so the concurrent loads of the same key run `load_fn` once. The load runs on the task that polls the stream.
+    /// The value of a miss is `None` if the cache is shutdown before the miss is polled, in which case `load_fn` is not invoked.
+    /// ```
+    /// use futures::StreamExt;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///
+    ///     let items: Vec<(&str, Option<&str>)> = cached.get_stream(vec!["disk", "topic"], |_key| "SSD").collect().await;
+    ///     assert_eq!(vec![("topic", Some("microservices")), ("disk", Some("SSD"))], items);
+    ///     assert_eq!(Some("SSD"), cached.get(&"disk"));
+    /// }
+    /// ```
+    #[cfg(feature = "stream")]
+    pub fn get_stream<LoadFn>(&self, keys: Vec<Key>, load_fn: LoadFn) -> GetStream<'_, Key, Value, LoadFn>
+        where LoadFn: Fn(&Key) -> Value {
+        let (hits, misses): (Vec<_>, Vec<_>) = keys.into_iter()
+            .map(|key| {
+                let value = self.get(&key);
+                (key, value)
+            })
+            .partition(|(_, value)| value.is_some());
+
+        GetStream {
+            cached: self,
+            hits: hits.into_iter(),
+            misses: misses.into_iter().map(|(key, _)| key).collect::<Vec<_>>().into_iter(),
+            load_fn,
+        }
+    }
+
+    /// Moves the key/value pairs whose keys match the `predicate` from this instance of `CacheD` to `other`, and returns the number of pairs moved.
+    ///
+    /// Each pair is put in `other` with the weight it has in this instance and with its remaining `time_to_live`, if any.
+    /// The pair is deleted from this instance only if `other` accepts it. If `other` rejects the pair (for example, by the `AdmissionPolicy` of `other`,
+    /// or by its [`crate::cache::config::PutMode`] because the key already exists in `other`), the pair remains in this instance.
+    /// A pair with `time_to_live` is not transferred to `other` that is built with [`crate::cache::config::ConfigBuilder::no_ttl`], and remains in this instance.
+    ///
+    /// The pairs are collected one shard at a time, holding the read lock on a shard only while its matching pairs are copied.
+    /// A pair that is put in this instance while `transfer_to` is in progress may not be transferred.
+    /// A transferred pair is deleted only if it is still stored with the key_id it was transferred with, so a pair that is put again
+    /// in this instance after its earlier version is transferred, is retained.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 200).build());
+    ///     let tenant_cached = CacheD::new(ConfigBuilder::new(100, 10, 200).build());
+    ///     let _ = cached.put("tenant-1:topic", "microservices").unwrap().handle().await;
+    ///     let _ = cached.put("tenant-2:topic", "cached").unwrap().handle().await;
+    ///
+    ///     let transferred = cached.transfer_to(&tenant_cached, |key| key.starts_with("tenant-1")).await;
+    ///     assert_eq!(1, transferred);
+    ///     assert_eq!(None, cached.get(&"tenant-1:topic"));
+    ///     assert_eq!(Some("microservices"), tenant_cached.get(&"tenant-1:topic"));
+    /// }
+    /// ```
+    pub async fn transfer_to<Predicate>(&self, other: &CacheD<Key, Value>, predicate: Predicate) -> usize
+        where Predicate: Fn(&Key) -> bool {
+        if self.is_shutting_down() { return 0; }
+
+        let mut transferred = 0;
+        for shard_index in 0..self.store.total_shards() {
+            for entry in self.store.shard_entries(shard_index, &predicate) {
+                let weight = match self.admission_policy.weight_of(&entry.key_id) {
+                    Some(weight) => weight,
+                    None => continue,
+                };
+                let time_to_live = match entry.expire_after {
+                    Some(expire_after) => match self.remaining_time_to_live(expire_after) {
+                        Some(time_to_live) => Some(time_to_live),
+                        None => continue,
+                    },
+                    None => None,
+                };
+                let (key, key_id) = (entry.key.clone(), entry.key_id);
+                let value = other.transform_on_put(self.transform_on_get(entry.value));
+                let status = match other.put_transformed_checked(entry.key, value, weight, time_to_live) {
+                    Ok(acknowledgement) => acknowledgement.handle().await,
+                    Err(_) => continue,
+                };
+                if status == CommandStatus::Accepted {
+                    if let Some(key_id_expiry) = self.store.delete_if_key_id(&key, key_id) {
+                        if let Ok(acknowledgement) = self.command_executor.send(CommandType::DeleteTaken(vec![(key, key_id_expiry)])) {
+                            acknowledgement.handle().await;
+                        }
+                    }
+                    transferred += 1;
+                }
+            }
+        }
+        transferred
+    }
+
+    /// Moves the value of `old_key` to `new_key`, keeping its weight and its remaining `time_to_live`, if any.
+    /// The calling thread is blocked till the rename is executed by the `CommandExecutor`.
+    ///
+    /// Returns true if the value is moved, false if `old_key` does not exist (or has expired), or if the rename is rejected.
+    ///
+    /// The value is put under `new_key` before `old_key` is deleted, so the concurrent readers always find the value under one of the keys.
+    /// If `new_key` exists, [`crate::cache::config::PutMode::InsertOnly`] (the default) rejects the rename, the other [`crate::cache::config::PutMode`]s replace `new_key`.
+    /// ```
+    /// use std::time::Duration;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(300)).unwrap().handle().await;
+    ///
+    ///     assert!(cached.rename(&"topic", "subject"));
+    ///     assert_eq!(None, cached.get(&"topic"));
+    ///     assert_eq!(Some("microservices"), cached.get(&"subject"));
+    /// }
+    /// ```
+    pub fn rename(&self, old_key: &Key, new_key: Key) -> bool {
+        if self.is_shutting_down() { return false; }
+
+        let send_result = {
+            let _gate = self.pass_clear_gate();
+            let key_description = self.key_description(new_key, 0);
+            self.command_executor.send(CommandType::Rename(old_key.clone(), key_description, Value::clone))
+        };
+        send_result.map(|acknowledgement| acknowledgement.block_on_done() == CommandStatus::Accepted).unwrap_or(false)
+    }
+
+    /// Returns the time remaining till `expire_after`, or `None` if `expire_after` has passed.
+    fn remaining_time_to_live(&self, expire_after: ExpireAfter) -> Option<Duration> {
+        match expire_after.duration_since(self.config.clock.now()) {
+            Ok(time_to_live) if !time_to_live.is_zero() => Some(time_to_live),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Key, Value> CacheD<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + Serialize + DeserializeOwned + 'static,
+          Value: Send + Sync + Clone + Serialize + DeserializeOwned + 'static {
+    /// Writes the key/value pairs of the cache to the `writer`, along with their remaining `time_to_live`, and returns the number of pairs written.
+    ///
+    /// The pairs are collected one shard at a time, holding the read lock on a shard only while its pairs are copied,
+    /// so only the pairs of one shard are held in memory, which makes `export_to` suitable for the caches that are too large to be copied at once.
+    /// A pair that is put, updated or deleted while `export_to` is in progress may or may not be written.
+    ///
+    /// Each pair is written as a frame: the length of the serialized pair (as a little-endian u64), followed by the pair serialized using `bincode`.
+    /// The value is written as returned by `get` (after `on_get` of `value_transform`), and the weight is not written.
+    /// The expired pairs are not written. Use [`CacheD::import_from`] to read the pairs back.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// cached.put_sync("topic".to_string(), "microservices".to_string()).unwrap();
+    ///
+    /// let mut bytes = Vec::new();
+    /// assert_eq!(1, cached.export_to(&mut bytes).unwrap());
+    ///
+    /// let imported: CacheD<String, String> = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// assert_eq!(1, imported.import_from(&mut bytes.as_slice()).unwrap());
+    /// assert_eq!(Some("microservices".to_string()), imported.get(&"topic".to_string()));
+    /// ```
+    pub fn export_to<W: Write>(&self, writer: &mut W) -> Result<usize, ExportError> {
+        let mut exported = 0;
+        for shard_index in 0..self.store.total_shards() {
+            for entry in self.store.shard_entries(shard_index, |_| true) {
+                let time_to_live = match entry.expire_after {
+                    Some(expire_after) => match self.remaining_time_to_live(expire_after) {
+                        Some(time_to_live) => Some(time_to_live),
+                        None => continue,
+                    },
+                    None => None,
+                };
+                export::write_entry(writer, &entry.key, &self.transform_on_get(entry.value), time_to_live)?;
+                exported += 1;
+            }
+        }
+        Ok(exported)
+    }
+
+    /// Reads the key/value pairs written by [`CacheD::export_to`] from the `reader`, puts them in the cache, and returns the number of pairs accepted.
+    ///
+    /// The pairs are read and put one at a time, so only one pair is held in memory.
+    /// A pair with a remaining `time_to_live` is put using [`CacheD::put_with_ttl`], and a pair without it is put using [`CacheD::put_without_ttl`].
+    /// The weight of each pair is calculated by the weight calculation function provided as a part of `Config`.
+    /// Each put blocks the calling thread till it is executed by the `CommandExecutor`. The pairs that can not be put, or are rejected, are not counted.
+    ///
+    /// Returns an [`ExportError`] if the reader does not hold the pairs written by `export_to`, the pairs read before the error remain in the cache.
+    pub fn import_from<R: Read>(&self, reader: &mut R) -> Result<usize, ExportError> {
+        let mut imported = 0;
+        while let Some(entry) = export::read_entry::<Key, Value, R>(reader)? {
+            let result = match entry.time_to_live {
+                Some(time_to_live) => self.put_with_ttl(entry.key, entry.value, time_to_live),
+                None => self.put_without_ttl(entry.key, entry.value),
+            };
+            if let Ok(CommandStatus::Accepted) = result.map(|acknowledgement| acknowledgement.block_on_done()) {
+                imported += 1;
+            }
+        }
+        Ok(imported)
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl<Key, Value> CacheD<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + Serialize + DeserializeOwned + 'static,
+          Value: Send + Sync + Clone + Serialize + DeserializeOwned + 'static {
+    /// Saves the live key/value pairs of the cache to the `writer`, along with their remaining `time_to_live` and weight, and returns the number of pairs saved.
+    ///
+    /// `save_to_writer` is meant to persist a warm cache across restarts, use [`CacheD::load_from_reader`] to load the pairs back.
+    /// The saved cache begins with a header that carries the version of the format and the time of save (as per the `Clock` of the cache),
+    /// followed by a frame for each pair, like [`CacheD::export_to`]. Unlike `export_to`, the weight of each pair is saved.
+    ///
+    /// The pairs are collected one shard at a time. A pair that is put, updated or deleted while `save_to_writer` is in progress may or may not be saved,
+    /// and a pair that is not yet admitted by the `AdmissionPolicy` is not saved. The expired pairs are not saved.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// cached.put_with_weight("topic".to_string(), "microservices".to_string(), 10).unwrap().block_on_done();
+    ///
+    /// let mut bytes = Vec::new();
+    /// assert_eq!(1, cached.save_to_writer(&mut bytes).unwrap());
+    ///
+    /// let loaded: CacheD<String, String> = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// assert_eq!(1, loaded.load_from_reader(&mut bytes.as_slice()).unwrap());
+    /// assert_eq!(Some("microservices".to_string()), loaded.get(&"topic".to_string()));
+    /// assert_eq!(10, loaded.total_weight_used());
+    /// ```
+    pub fn save_to_writer<W: Write>(&self, writer: &mut W) -> Result<usize, ExportError> {
+        export::write_header(writer, self.config.clock.now())?;
+
+        let mut saved = 0;
+        for shard_index in 0..self.store.total_shards() {
+            for entry in self.store.shard_entries(shard_index, |_| true) {
+                let weight = match self.admission_policy.weight_of(&entry.key_id) {
+                    Some(weight) => weight,
+                    None => continue,
+                };
+                let time_to_live = match entry.expire_after {
+                    Some(expire_after) => match self.remaining_time_to_live(expire_after) {
+                        Some(time_to_live) => Some(time_to_live),
+                        None => continue,
+                    },
+                    None => None,
+                };
+                export::write_persisted_entry(writer, &entry.key, &self.transform_on_get(entry.value), time_to_live, weight)?;
+                saved += 1;
+            }
+        }
+        Ok(saved)
+    }
+
+    /// Loads the key/value pairs saved by [`CacheD::save_to_writer`] from the `reader`, puts them in the cache, and returns the number of pairs accepted.
+    ///
+    /// Each pair is replayed through the put path with its saved weight, so the `AdmissionPolicy` and the `crate::cache::expiration::TTLTicker` track the pair
+    /// like any other put. The time elapsed since the save (as per the `Clock` of the cache) is deducted from the remaining `time_to_live` of each pair,
+    /// and the pairs whose `time_to_live` has elapsed are skipped. A pair with a `time_to_live` is rejected if the cache is built with [`crate::cache::config::ConfigBuilder::no_ttl`].
+    /// Each put blocks the calling thread till it is executed by the `CommandExecutor`. The pairs that can not be put, or are rejected, are not counted.
+    ///
+    /// Returns an [`ExportError`] if the reader does not begin with the header of a saved cache of a supported version,
+    /// or does not hold the pairs written by `save_to_writer`. The pairs loaded before the error remain in the cache.
+    pub fn load_from_reader<R: Read>(&self, reader: &mut R) -> Result<usize, ExportError> {
+        let saved_at = export::read_header(reader)?;
+        let elapsed = self.config.clock.now().duration_since(saved_at).unwrap_or(Duration::ZERO);
+
+        let mut loaded = 0;
+        while let Some(entry) = export::read_persisted_entry::<Key, Value, R>(reader)? {
+            let time_to_live = match entry.time_to_live {
+                Some(time_to_live) => match time_to_live.checked_sub(elapsed) {
+                    Some(time_to_live) if !time_to_live.is_zero() => Some(time_to_live),
+                    _ => continue,
+                },
+                None => None,
+            };
+            let result = self.put_persisted(entry.key, entry.value, entry.weight, time_to_live);
+            if let Ok(CommandStatus::Accepted) = result.map(|acknowledgement| acknowledgement.block_on_done()) {
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    fn put_persisted(&self, key: Key, value: Value, weight: Weight, time_to_live: Option<Duration>) -> CommandSendResult {
+        if self.is_shutting_down() { return shutdown_result(); }
+        if time_to_live.is_some() && self.is_ttl_disabled() { return ttl_disabled_result("PutWithTTL"); }
+        if self.is_delete_value(&value) { return self.delete(key); }
+        self.put_transformed(key, self.transform_on_put(value), weight, time_to_live)
+    }
+}
+
+impl<Key> CacheD<Key, i64>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static {
+    /// Increments the count of the key by `delta` and returns the new count, atomically with respect to the other increments of the key.
+    ///
+    /// If the key is absent (or has expired), the count starts at `delta` and expires after `time_to_live`.
+    /// Increments of a live key retain its expiry, so the count resets only once the `time_to_live` (window) passes.
+    /// This makes `increment_with_ttl` suitable for fixed window rate limiting.
+    ///
+    /// The count is changed before `increment_with_ttl` returns.
+    /// Admitting a new count is not an immediate operation and results in `crate::cache::command::CommandType::Admit` to the `CommandExecutor`.
+    /// If `AdmissionPolicy` rejects the key, the count gets removed, and the next increment starts the count again.
+    ///
+    /// Returns `Ok(0)` if the cache is being shutdown.
+    /// Returns the [`crate::cache::config::error::WeightError`] if the fallible weight calculation function could not calculate the weight, and the count is not changed.
+    /// Panics if the cache is built with [`crate::cache::config::ConfigBuilder::no_ttl`].
+    /// This method is only available if the Value type is i64.
+    /// ```
+    /// use std::time::Duration;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached: CacheD<&str, i64> = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    ///     assert_eq!(Ok(1), cached.increment_with_ttl("requests", 1, Duration::from_secs(60)));
+    ///     assert_eq!(Ok(3), cached.increment_with_ttl("requests", 2, Duration::from_secs(60)));
+    ///     assert_eq!(Some(3), cached.get(&"requests"));
+    /// }
+    /// ```
+    pub fn increment_with_ttl(&self, key: Key, delta: i64, time_to_live: Duration) -> Result<i64, WeightError> {
+        if self.is_shutting_down() { return Ok(0); }
+        assert!(!self.is_ttl_disabled(), "{}", Errors::TimeToLiveDisabled("increment_with_ttl"));
+        #[cfg(feature = "trace")]
+        self.record_trace(TraceOperation::Put, &key);
+
+        let weight = self.config.weight_of(&key, &delta, true)?;
+        assert!(weight > 0, "{}", Errors::WeightCalculationGtZero);
+
+        let key_description = self.key_description(key.clone(), weight);
+        let _gate = self.pass_clear_gate();
+        if self.is_shutting_down() { return Ok(0); }
+        match self.store.increment_with_ttl(key, delta, key_description.id, time_to_live) {
+            IncrementResponse::Incremented(count) => {
+                self.mark_key_accessed(key_description.key());
+                Ok(count)
+            }
+            IncrementResponse::Started(count, expire_after, replaced_key_id_expiry) => {
+                let _ = self.command_executor.send(CommandType::Admit(key_description, expire_after, replaced_key_id_expiry));
+                Ok(count)
+            }
+        }
+    }
+}
+
+impl<Key, Value> CacheD<Key, Weak<Value>>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    /// Puts the key with a weak reference to the `value`, so that the cache does not keep the value alive once the clients drop their strong references to it.
+    ///
+    /// `put_weak` behaves like [`CacheD::put`] with `Arc::downgrade(value)`. Once the value is dropped, the key is dead:
+    /// [`CacheD::get_weak`] returns `None` for it and reclaims it, and [`CacheD::reclaim_weak`] reclaims all the dead keys.
+    /// Until a dead key is reclaimed, it holds its weight in the cache.
+    /// ```
+    /// use std::sync::Arc;
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let value = Arc::new("microservices");
+    ///     let status = cached.put_weak("topic", &value).unwrap().handle().await;
+    ///     assert_eq!(CommandStatus::Accepted, status);
+    ///     assert_eq!(Some(value.clone()), cached.get_weak(&"topic"));
+    ///
+    ///     drop(value);
+    ///     assert_eq!(None, cached.get_weak(&"topic"));
+    /// }
+    /// ```
+    pub fn put_weak(&self, key: Key, value: &Arc<Value>) -> CommandSendResult {
+        self.put(key, Arc::downgrade(value))
+    }
+
+    /// Returns the value of the key put by [`CacheD::put_weak`], if the key exists and its value is alive.
+    ///
+    /// If the value is dropped, the key is reclaimed: it is removed from the cache and counted in [`crate::cache::stats::StatsType::WeakValuesReclaimed`].
+    /// The removal of the key from `crate::cache::policy::admission_policy::AdmissionPolicy` is not an immediate operation,
+    /// so its weight is released after `get_weak` returns. Like `get`, `get_weak` records a hit for a key that exists, even if its value is dropped.
+    pub fn get_weak(&self, key: &Key) -> Option<Arc<Value>> {
+        let (value, key_id) = self.get_ref(key).map(|key_value_ref| {
+            let stored_value = key_value_ref.value();
+            (self.transform_on_get(stored_value.value()).upgrade(), stored_value.key_id())
+        })?;
+        if value.is_none() {
+            self.reclaim_weak_key(key, key_id);
+        }
+        value
+    }
+
+    /// Reclaims all the keys whose values are dropped (read [`CacheD::put_weak`]) and returns the number of keys reclaimed.
+    ///
+    /// The dead keys are collected one shard at a time, holding the read lock on a shard only while its keys are checked, and are removed after.
+    pub fn reclaim_weak(&self) -> usize {
+        if self.is_shutting_down() { return 0; }
+
+        let mut dead_keys = Vec::new();
+        self.store.for_each(|key, stored_value| {
+            if stored_value.value_ref().strong_count() == 0 {
+                dead_keys.push((key.clone(), stored_value.key_id()));
+            }
+        });
+        dead_keys.iter().filter(|(key, key_id)| self.reclaim_weak_key(key, *key_id)).count()
+    }
+
+    /// Removes the dead key only if it is stored with the `key_id`, so that a key put again is retained, and a key reclaimed concurrently is counted once.
+    fn reclaim_weak_key(&self, key: &Key, key_id: KeyId) -> bool {
+        match self.store.delete_if_key_id(key, key_id) {
+            Some(key_id_expiry) => {
+                self.store.stats_counter().reclaim_weak_value();
+                let _ = self.command_executor.send(CommandType::DeleteTaken(vec![(key.clone(), key_id_expiry)]));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// `MultiGetIterator` allows iterating over multiple keys and getting the value corresponding to each key.
+/// ```
+/// use tinylfu_cached::cache::cached::CacheD;
+/// use tinylfu_cached::cache::config::ConfigBuilder;
+/// #[tokio::main]
+///  async fn main() {
+///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+///     let status = cached.put("topic", "microservices").unwrap().handle().await;
+///     let mut iterator = cached.multi_get_iterator(vec![&"topic", &"non-existing"]);
+///     assert_eq!(Some("microservices"), iterator.next().unwrap());
+///     assert_eq!(None, iterator.next().unwrap());
+///     assert_eq!(None, iterator.next());
+///     assert!(!iterator.was_interrupted());
+/// }
+/// ```
+///
+/// The iterator ends (returns `None`) if the cache is shutdown while iterating, even if some keys are left.
+/// [`MultiGetIterator::was_interrupted`] distinguishes the iterator that ended because of shutdown from the iterator that ran out of keys.
+pub struct MultiGetIterator<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static {
+    cache: &'a CacheD<Key, Value>,
+    keys: std::vec::IntoIter<&'a Key>,
+    was_interrupted: bool,
+}
+
+impl<'a, Key, Value> MultiGetIterator<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static {
+    /// Returns true if the iterator ended because the cache was shutdown before all the keys were iterated,
+    /// and false if the iterator ran out of keys (or has not ended yet).
+    pub fn was_interrupted(&self) -> bool {
+        self.was_interrupted
+    }
+}
+
+impl<'a, Key, Value> Iterator for MultiGetIterator<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static {
+    type Item = Option<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.keys.as_slice().is_empty() {
+            return None;
+        }
+        if self.cache.is_shutting_down() {
+            self.was_interrupted = true;
+            return None;
+        }
+        self.keys.next().map(|key| self.cache.get(key))
+    }
+}
+
+/// `MultiGetMapIterator` allows iterating over multiple keys, performing a map operation over each key and then getting the value corresponding to each key.
+/// ```
 /// use tinylfu_cached::cache::cached::CacheD;
 /// use tinylfu_cached::cache::config::ConfigBuilder;
 /// #[tokio::main]
@@ -681,6 +3062,7 @@ impl<'a, Key, Value> Iterator for MultiGetIterator<'a, Key, Value>
 ///     assert_eq!(Some("MICROSERVICES".to_string()), iterator.next().unwrap());
 ///     assert_eq!(None, iterator.next().unwrap());
 ///     assert_eq!(None, iterator.next());
+///     assert!(!iterator.was_interrupted());
 /// }
 /// ```
 pub struct MultiGetMapIterator<'a, Key, Value, MapFn, MappedValue>
@@ -691,1178 +3073,6205 @@ pub struct MultiGetMapIterator<'a, Key, Value, MapFn, MappedValue>
     map_fn: MapFn,
 }
 
-impl<'a, Key, Value, MapFn, MappedValue> Iterator for MultiGetMapIterator<'a, Key, Value, MapFn, MappedValue>
-    where Key: Hash + Eq + Send + Sync + Clone + 'static,
-          Value: Send + Sync + Clone + 'static,
-          MapFn: Fn(Value) -> MappedValue, {
-    type Item = Option<MappedValue>;
+impl<'a, Key, Value, MapFn, MappedValue> MultiGetMapIterator<'a, Key, Value, MapFn, MappedValue>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static,
+          MapFn: Fn(Value) -> MappedValue, {
+    /// Returns true if the iterator ended because the cache was shutdown before all the keys were iterated,
+    /// and false if the iterator ran out of keys (or has not ended yet). Read [`MultiGetIterator::was_interrupted`].
+    pub fn was_interrupted(&self) -> bool {
+        self.iterator.was_interrupted()
+    }
+}
+
+impl<'a, Key, Value, MapFn, MappedValue> Iterator for MultiGetMapIterator<'a, Key, Value, MapFn, MappedValue>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static,
+          MapFn: Fn(Value) -> MappedValue, {
+    type Item = Option<MappedValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next().map(|optional_value| {
+            match optional_value {
+                None => None,
+                Some(value) => Some((self.map_fn)(value))
+            }
+        })
+    }
+}
+
+/// `MultiGetStream` is a [`futures::Stream`] over multiple keys, that yields the value (or `None`) of each key. Read [`CacheD::multi_get_stream`].
+///
+/// The stream ends if the cache is shutdown while streaming, like [`MultiGetIterator`].
+#[cfg(feature = "stream")]
+pub struct MultiGetStream<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static {
+    iterator: MultiGetIterator<'a, Key, Value>,
+}
+
+#[cfg(feature = "stream")]
+impl<'a, Key, Value> MultiGetStream<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static {
+    /// Returns true if the stream ended because the cache was shutdown before all the keys were streamed. Read [`MultiGetIterator::was_interrupted`].
+    pub fn was_interrupted(&self) -> bool {
+        self.iterator.was_interrupted()
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<'a, Key, Value> Stream for MultiGetStream<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static {
+    type Item = Option<Value>;
+
+    fn poll_next(self: Pin<&mut Self>, _context: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().iterator.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.iterator.keys.len()))
+    }
+}
+
+/// `GetStream` is a [`futures::Stream`] over multiple keys, that yields the hits before the misses, and loads the value of each miss when it is polled.
+/// Read [`CacheD::get_stream`].
+#[cfg(feature = "stream")]
+pub struct GetStream<'a, Key, Value, LoadFn>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static,
+          LoadFn: Fn(&Key) -> Value {
+    cached: &'a CacheD<Key, Value>,
+    hits: std::vec::IntoIter<(Key, Option<Value>)>,
+    misses: std::vec::IntoIter<Key>,
+    load_fn: LoadFn,
+}
+
+/// `GetStream` is never pinned structurally, it loads the misses synchronously.
+#[cfg(feature = "stream")]
+impl<'a, Key, Value, LoadFn> Unpin for GetStream<'a, Key, Value, LoadFn>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static,
+          LoadFn: Fn(&Key) -> Value {}
+
+#[cfg(feature = "stream")]
+impl<'a, Key, Value, LoadFn> Stream for GetStream<'a, Key, Value, LoadFn>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + Clone + 'static,
+          LoadFn: Fn(&Key) -> Value {
+    type Item = (Key, Option<Value>);
+
+    fn poll_next(self: Pin<&mut Self>, _context: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let stream = self.get_mut();
+        if let Some(hit) = stream.hits.next() {
+            return Poll::Ready(Some(hit));
+        }
+        Poll::Ready(stream.misses.next().map(|key| {
+            if stream.cached.is_shutting_down() {
+                return (key, None);
+            }
+            let value = stream.cached.get_or_insert_with_single_flight(key.clone(), || (stream.load_fn)(&key));
+            (key, Some(value))
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.hits.len() + self.misses.len();
+        (remaining, Some(remaining))
+    }
+}
+
+/// `EvictionGuard` is returned by [`CacheD::suspend_eviction`], the eviction of keys stays suspended until the guard is dropped.
+///
+/// Dropping the last guard sends a `crate::cache::command::CommandType::ResumeEviction` to the `CommandExecutor`,
+/// which evicts the keys that were admitted over the cache weight while the eviction was suspended.
+pub struct EvictionGuard<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    cached: &'a CacheD<Key, Value>,
+}
+
+impl<'a, Key, Value> Drop for EvictionGuard<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    fn drop(&mut self) {
+        if self.cached.admission_policy.resume_eviction() && !self.cached.is_shutting_down() {
+            let _ = self.cached.command_executor.send(CommandType::ResumeEviction);
+        }
+    }
+}
+
+/// `Reservation` is returned by [`CacheD::reserve`], it holds the weight reserved in the cache until it is dropped.
+///
+/// Every put made through the `Reservation` takes its weight from the remaining reserved weight, and is admitted without competing against the other keys.
+/// A put whose weight is more than the remaining reserved weight is performed as a regular put, which may be rejected or may evict other keys.
+///
+/// Dropping the `Reservation` releases the remaining reserved weight.
+pub struct Reservation<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    cached: &'a CacheD<Key, Value>,
+    remaining: AtomicI64,
+}
+
+impl<'a, Key, Value> Reservation<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    /// Puts the key/value pair using the reserved weight, the weight is calculated by the weight calculation function provided as a part of `Config`.
+    /// Read [`CacheD::put`].
+    pub fn put(&self, key: Key, value: Value) -> CommandSendResult {
+        if self.cached.is_shutting_down() { return shutdown_result(); }
+
+        let value = self.cached.transform_on_put(value);
+        let weight = self.cached.config.weight_of(&key, &value, false)?;
+        assert!(weight > 0, "{}", Errors::WeightCalculationGtZero);
+        self.put_reserved(key, value, weight, None)
+    }
+
+    /// Puts the key/value pair with the provided weight using the reserved weight. Read [`CacheD::put_with_weight`].
+    pub fn put_with_weight(&self, key: Key, value: Value, weight: Weight) -> CommandSendResult {
+        if self.cached.is_shutting_down() { return shutdown_result(); }
+
+        assert!(weight > 0, "{}", Errors::KeyWeightGtZero("put_with_weight"));
+        self.put_reserved(key, self.cached.transform_on_put(value), weight, None)
+    }
+
+    /// Puts the key/value pair with `time_to_live` using the reserved weight, the weight is calculated by the weight calculation function provided as a part of `Config`.
+    /// Read [`CacheD::put_with_ttl`].
+    pub fn put_with_ttl(&self, key: Key, value: Value, time_to_live: Duration) -> CommandSendResult {
+        if self.cached.is_shutting_down() { return shutdown_result(); }
+        if self.cached.is_ttl_disabled() { return ttl_disabled_result("PutWithTTL"); }
+
+        let value = self.cached.transform_on_put(value);
+        let weight = self.cached.config.weight_of(&key, &value, true)?;
+        assert!(weight > 0, "{}", Errors::WeightCalculationGtZero);
+        self.put_reserved(key, value, weight, Some(time_to_live))
+    }
+
+    /// Returns the reserved weight that is not yet used.
+    pub fn remaining(&self) -> Weight {
+        self.remaining.load(Ordering::Acquire)
+    }
+
+    /// Puts the key/value pair (with the value already transformed) using the reserved weight, or as a regular put if the remaining reserved weight is less than the `weight`.
+    fn put_reserved(&self, key: Key, value: Value, weight: Weight, time_to_live: Option<Duration>) -> CommandSendResult {
+        if !self.take(weight) {
+            return self.cached.put_transformed(key, value, weight, time_to_live);
+        }
+        if let Some(rejection) = self.cached.put_rejection(&key) {
+            self.remaining.fetch_add(weight, Ordering::AcqRel);
+            return rejection;
+        }
+        let key_description = self.cached.key_description(key, weight);
+        let _gate = self.cached.pass_clear_gate();
+        if self.cached.is_shutting_down() {
+            self.remaining.fetch_add(weight, Ordering::AcqRel);
+            return shutdown_result();
+        }
+        match time_to_live {
+            Some(time_to_live) =>
+                self.cached.command_executor.send(CommandType::PutReservedWithTTL(key_description, value, time_to_live)),
+            None =>
+                self.cached.command_executor.send(CommandType::PutReserved(key_description, value)),
+        }
+    }
+
+    /// Takes the `weight` from the remaining reserved weight, returns false if the remaining reserved weight is less than the `weight`.
+    fn take(&self, weight: Weight) -> bool {
+        self.remaining
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+                if remaining >= weight { Some(remaining - weight) } else { None }
+            })
+            .is_ok()
+    }
+}
+
+impl<'a, Key, Value> Drop for Reservation<'a, Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    fn drop(&mut self) {
+        self.cached.admission_policy.release_reservation(self.remaining());
+    }
+}
+
+/// `ReservationError` is returned by [`CacheD::reserve`] if the weight can not be reserved.
+///
+/// `NotEnoughWeightAvailable`: the weight to reserve is more than the weight that is neither used nor reserved.
+///
+/// `ShuttingDown`: the cache is being shutdown.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ReservationError {
+    NotEnoughWeightAvailable(Weight),
+    ShuttingDown,
+}
+
+/// Display implementation for `ReservationError`.
+impl Display for ReservationError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReservationError::NotEnoughWeightAvailable(weight) =>
+                write!(formatter, "could not reserve weight {}, not enough weight is available", weight),
+            ReservationError::ShuttingDown =>
+                write!(formatter, "could not reserve weight, cache is being shutdown"),
+        }
+    }
+}
+
+/// Error implementation for `ReservationError`.
+impl Error for ReservationError {}
+
+/// `ExpiredError` is returned by [`CacheD::get_result`] for a key that has expired, but is not yet removed by `crate::cache::expiration::TTLTicker`,
+/// if the [`crate::cache::config::ConfigBuilder::expired_read_behavior`] is [`crate::cache::config::ExpiredReadBehavior::Error`].
+#[derive(Debug, Eq, PartialEq)]
+pub struct ExpiredError;
+
+/// Display implementation for `ExpiredError`.
+impl Display for ExpiredError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "the key has expired")
+    }
+}
+
+/// Error implementation for `ExpiredError`.
+impl Error for ExpiredError {}
+
+/// EntryMetadata is the value of a key along with its metadata, it is returned from [`CacheD::get_with_metadata`] and [`CacheD::peek_with_metadata`].
+///
+/// `key_id`: the id assigned to the key when it was put.
+///
+/// `weight`: the current weight of the key in `crate::cache::policy::admission_policy::AdmissionPolicy`, including the weight of its expiry (if any).
+///
+/// `expire_after`: the time at which the key expires, or `None` if the key never expires.
+///
+/// `estimated_frequency`: the estimate of the access frequency of the key, read [`CacheD::estimated_frequency`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntryMetadata<Value> {
+    value: Value,
+    key_id: KeyId,
+    weight: Weight,
+    expire_after: Option<SystemTime>,
+    estimated_frequency: FrequencyEstimate,
+}
+
+impl<Value> EntryMetadata<Value> {
+    pub fn value(&self) -> &Value { &self.value }
+
+    pub fn into_value(self) -> Value { self.value }
+
+    pub fn key_id(&self) -> KeyId { self.key_id }
+
+    pub fn weight(&self) -> Weight { self.weight }
+
+    pub fn expire_after(&self) -> Option<SystemTime> { self.expire_after }
+
+    pub fn estimated_frequency(&self) -> FrequencyEstimate { self.estimated_frequency }
+}
+
+/// ShutdownReport describes how cleanly an instance of `CacheD` was shutdown, it is returned from `shutdown` and `shutdown_report` of [`CacheD`].
+///
+/// `dropped_commands`: the number of commands that were queued behind the `Shutdown` command in `crate::cache::command::command_executor::CommandExecutor`,
+/// and were dropped without being executed. Each of these commands receives [`crate::cache::command::CommandStatus::ShuttingDown`].
+///
+/// `cleared_entries`: the number of key/value pairs that were present in the cache when it was cleared.
+///
+/// `executor_stopped`: true if the thread of `CommandExecutor` confirmed that it stopped executing the commands before the cache was cleared.
+///
+/// `ttl_ticker_stopped`: true if the thread of `crate::cache::expiration::TTLTicker` has stopped. The thread stops on its next tick after the shutdown,
+/// so it may be false immediately after the shutdown, and becomes true within `ttl_tick_duration` of [`crate::cache::config::ConfigBuilder`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ShutdownReport {
+    dropped_commands: usize,
+    cleared_entries: usize,
+    executor_stopped: bool,
+    ttl_ticker_stopped: bool,
+}
+
+impl ShutdownReport {
+    pub fn dropped_commands(&self) -> usize { self.dropped_commands }
+
+    pub fn cleared_entries(&self) -> usize { self.cleared_entries }
+
+    pub fn executor_stopped(&self) -> bool { self.executor_stopped }
+
+    pub fn ttl_ticker_stopped(&self) -> bool { self.ttl_ticker_stopped }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Add;
+    use std::sync::{Arc, OnceLock, Weak};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::cached::{CacheD, ReservationError};
+    use crate::cache::clock::{Clock, ClockType, ManualClock};
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::command::acknowledgement::CommandAcknowledgement;
+    use crate::cache::config::{AccessDrain, AdmissionMode, ConfigBuilder, ExpiredReadBehavior, FallibleWeightCalculationFn, WeightCalculationFn};
+    use crate::cache::config::error::WeightError;
+    #[cfg(any(feature = "serde", feature = "persistence"))]
+    use crate::cache::export::ExportError;
+    use crate::cache::put_or_update::{PutOrUpdateRequest, PutOrUpdateRequestBuilder};
+    use crate::cache::stats::StatsType;
+
+    #[derive(Eq, PartialEq, Debug)]
+    struct Name {
+        first: String,
+        last: String,
+    }
+
+    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str> {
+        ConfigBuilder::new(100, 10, 100)
+    }
+
+    #[test]
+    #[should_panic]
+    fn shards_mut_be_power_of_2_and_greater_than_1() {
+        let _: CacheD<&str, &str> = CacheD::new(test_config_builder().shards(1).build());
+    }
+
+    #[test]
+    #[should_panic]
+    fn weight_must_be_greater_than_zero_1() {
+        let cached = CacheD::new(test_config_builder().build());
+        let _ =
+            cached.put_with_weight("topic", "microservices", 0).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn weight_must_be_greater_than_zero_2() {
+        let cached = CacheD::new(test_config_builder().build());
+        let _ =
+            cached.put_with_weight_and_ttl("topic", "microservices", 0, Duration::from_secs(5)).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn weight_calculation_fn_must_return_weight_greater_than_zero_1() {
+        let weight_calculation: Box<WeightCalculationFn<&str, &str>> = Box::new(|_key, _value, _is_time_to_live_specified| 0);
+        let cached = CacheD::new(test_config_builder().weight_calculation_fn(weight_calculation).build());
+        let _ =
+            cached.put("topic", "microservices").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn weight_calculation_fn_must_return_weight_greater_than_zero_2() {
+        let weight_calculation: Box<WeightCalculationFn<&str, &str>> = Box::new(|_key, _value, _is_time_to_live_specified| 0);
+        let cached = CacheD::new(test_config_builder().weight_calculation_fn(weight_calculation).build());
+        let _ =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).unwrap();
+    }
+
+    #[test]
+    fn put_returns_the_error_of_the_fallible_weight_calculation_fn() {
+        let weight_calculation: Box<FallibleWeightCalculationFn<&str, &str>> = Box::new(|_key, value, _is_time_to_live_specified| {
+            if value.len() > 5 { Err(WeightError::new("value is too large")) } else { Ok(10) }
+        });
+        let cached = CacheD::new(test_config_builder().fallible_weight_calculation_fn(weight_calculation).inline_execution().build());
+
+        let result = cached.put("topic", "microservices");
+        assert_eq!(Some(&WeightError::new("value is too large")), result.err().unwrap().weight_error());
+
+        let result = cached.put_with_ttl("topic", "microservices", Duration::from_secs(5));
+        assert_eq!(Some(&WeightError::new("value is too large")), result.err().unwrap().weight_error());
+
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(0, cached.total_weight_used());
+
+        let _ = cached.put("disk", "SSD").unwrap();
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
+        assert_eq!(10, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn put_or_update_returns_the_error_of_the_fallible_weight_calculation_fn() {
+        let weight_calculation: Box<FallibleWeightCalculationFn<&str, &str>> = Box::new(|_key, value, _is_time_to_live_specified| {
+            if value.len() > 5 { Err(WeightError::new("value is too large")) } else { Ok(10) }
+        });
+        let cached = CacheD::new(test_config_builder().fallible_weight_calculation_fn(weight_calculation).build());
+
+        let result = cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").build());
+        assert_eq!(Some(&WeightError::new("value is too large")), result.err().unwrap().weight_error());
+
+        cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("cache").build()).unwrap().handle().await;
+        let result = cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").build());
+
+        assert_eq!(Some(&WeightError::new("value is too large")), result.err().unwrap().weight_error());
+        assert_eq!(Some("cache"), cached.get(&"topic"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn put_or_update_results_in_put_value_must_be_present() {
+        let cached = CacheD::new(test_config_builder().build());
+        let put_or_update: PutOrUpdateRequest<&str, &str> = PutOrUpdateRequestBuilder::new("store").build();
+        let _ = cached.put_or_update(put_or_update);
+    }
+
+    #[test]
+    #[should_panic]
+    fn put_or_update_results_in_put_with_weight_calculation_fn_must_return_weight_greater_than_zero() {
+        let weight_calculation: Box<WeightCalculationFn<&str, &str>> = Box::new(|_key, _value, _is_time_to_live_specified| 0);
+        let cached = CacheD::new(test_config_builder().weight_calculation_fn(weight_calculation).build());
+
+        let put_or_update = PutOrUpdateRequestBuilder::new("store").value("cached").build();
+        let _ = cached.put_or_update(put_or_update);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn put_or_update_results_in_update_with_weight_calculation_fn_must_return_weight_greater_than_zero() {
+        let weight_calculation: Box<WeightCalculationFn<&str, &str>> = Box::new(|_key, _value, _is_time_to_live_specified| 0);
+        let cached = CacheD::new(test_config_builder().weight_calculation_fn(weight_calculation).build());
+        cached.put("topic", "microservices").unwrap().handle().await;
+
+        let put_or_update = PutOrUpdateRequestBuilder::new("topic").value("cached").build();
+        let _ = cached.put_or_update(put_or_update);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn put_or_update_results_in_update_with_weight_must_be_greater_than_zero() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("topic", "microservices").unwrap().handle().await;
+
+        let put_or_update = PutOrUpdateRequestBuilder::new("topic").value("cached").weight(0).build();
+        let _ = cached.put_or_update(put_or_update);
+    }
+
+    #[tokio::test]
+    async fn put_a_key_value_without_weight_and_ttl() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+
+        let key: u64 = 100;
+        let value: u64 = 1000;
+
+        let acknowledgement =
+            cached.put(key, value).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&100);
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!(1000, stored_value.value());
+        assert_eq!(Some(40), cached.admission_policy.weight_of(&key_id));
+    }
+
+    #[tokio::test]
+    async fn put_a_key_value_without_weight_with_ttl() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+
+        let key: u64 = 100;
+        let value: u64 = 1000;
+
+        let acknowledgement =
+            cached.put_with_ttl(key, value, Duration::from_secs(300)).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&100);
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!(1000, stored_value.value());
+        assert_eq!(Some(64), cached.admission_policy.weight_of(&key_id));
+        assert!(stored_value.expire_after().is_some());
+    }
+
+    #[tokio::test]
+    async fn put_a_key_value_with_ttl_and_assert_expiry_using_the_clock_of_cached() {
+        let cached = CacheD::new(test_config_builder().clock(Box::new(ManualClock::new(SystemTime::UNIX_EPOCH))).build());
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(300)).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+
+        assert_eq!(Some(cached.clock().now().add(Duration::from_secs(300))), stored_value.expire_after());
+    }
+
+    #[tokio::test]
+    async fn put_the_same_key_value_again() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+
+        let key: u64 = 100;
+        let value: u64 = 1000;
+
+        let acknowledgement = cached.put(key, value).unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement = cached.put(key, value).unwrap();
+        let status = acknowledgement.handle().await;
+
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), status);
+
+        let value = cached.get_ref(&100);
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+
+        assert_eq!(1000, stored_value.value());
+        assert_eq!(40, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn put_a_key_value_with_weight() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_with_weight("topic", "microservices", 50).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!("microservices", stored_value.value());
+        assert_eq!(Some(50), cached.admission_policy.weight_of(&key_id));
+    }
+
+    #[tokio::test]
+    async fn put_a_key_value_with_weight_again() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_with_weight("topic", "microservices", 50).unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put_with_weight("topic", "microservices", 50).unwrap();
+        let status = acknowledgement.handle().await;
+
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), status);
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!("microservices", stored_value.value());
+        assert_eq!(Some(50), cached.admission_policy.weight_of(&key_id));
+        assert_eq!(50, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn put_a_key_value_with_ttl() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(120)).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get(&"topic");
+        assert_eq!(Some("microservices"), value);
+    }
+
+    #[tokio::test]
+    async fn put_a_key_value_with_ttl_again() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(120)).unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(120)).unwrap();
+        let status = acknowledgement.handle().await;
+
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), status);
+
+        let value = cached.get(&"topic");
+        assert_eq!(Some("microservices"), value);
+    }
+
+    #[tokio::test]
+    async fn put_a_key_value_with_weight_and_ttl() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_with_weight_and_ttl("topic", "microservices", 10, Duration::from_secs(120)).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get(&"topic");
+        assert_eq!(Some("microservices"), value);
+    }
+
+    #[tokio::test]
+    async fn put_a_key_value_with_weight_and_ttl_again() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_with_weight_and_ttl("topic", "microservices", 10, Duration::from_secs(120)).unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put_with_weight_and_ttl("topic", "microservices", 10, Duration::from_secs(120)).unwrap();
+        let status = acknowledgement.handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), status);
+
+        let value = cached.get(&"topic");
+        assert_eq!(Some("microservices"), value);
+        assert_eq!(34, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn put_a_key_value_with_ttl_and_ttl_ticker_evicts_it() {
+        let cached = CacheD::new(test_config_builder().shards(2).ttl_tick_duration(Duration::from_millis(10)).build());
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_millis(20)).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get(&"topic");
+        assert_eq!(Some("microservices"), value);
+
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn round_trip_a_key_through_its_key_id() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let key_id = cached.key_id_of(&"topic").unwrap();
+
+        assert_eq!(Some("topic"), cached.key_of(key_id));
+        assert_eq!(Some(key_id), cached.get_ref(&"topic").map(|key_value_ref| key_value_ref.value().key_id()));
+    }
+
+    #[tokio::test]
+    async fn key_id_and_key_of_a_deleted_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let key_id = cached.key_id_of(&"topic").unwrap();
+        let _ = cached.delete("topic").unwrap().handle().await;
+
+        assert_eq!(None, cached.key_id_of(&"topic"));
+        assert_eq!(None, cached.key_of(key_id));
+    }
+
+    #[test]
+    fn get_value_ref_for_a_non_existing_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+
+        let value = cached.get_ref(&"non-existing");
+        assert!(value.is_none());
+    }
+
+    #[test]
+    fn get_value_ref_for_a_non_existing_key_and_attempt_to_map_it() {
+        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+
+        let value = cached.map_get_ref(&"non_existing", |stored_value| stored_value.value_ref().to_uppercase());
+        assert!(value.is_none());
+    }
+
+    #[tokio::test]
+    async fn get_value_ref_for_an_existing_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        assert_eq!(&"microservices", value.unwrap().value().value_ref());
+    }
+
+    #[tokio::test]
+    async fn get_value_ref_and_access_the_stored_value() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(300)).unwrap();
+        acknowledgement.handle().await;
+
+        let key_value_ref = cached.get_ref(&"topic").unwrap();
+        let stored_value = key_value_ref.value();
+
+        assert_eq!(&"microservices", stored_value.value_ref());
+        assert_eq!("microservices", stored_value.value());
+        assert!(stored_value.key_id() > 0);
+        assert_eq!(Some(SystemTime::UNIX_EPOCH.add(Duration::from_secs(300))), stored_value.expire_after());
+        assert_eq!(Some(Duration::from_secs(300)), stored_value.time_to_live());
+        assert_eq!(Some(Duration::from_secs(300)), stored_value.remaining_time_to_live(&clock));
+        assert!(stored_value.is_alive(&clock));
+        assert!(!stored_value.is_soft_deleted());
+    }
+
+    #[tokio::test]
+    async fn get_value_ref_for_an_existing_key_and_map_it() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.map_get_ref(&"topic", |stored_value| stored_value.value_ref().to_uppercase());
+        assert_eq!("MICROSERVICES", value.unwrap());
+    }
+
+    #[tokio::test]
+    async fn get_value_for_an_existing_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get(&"topic");
+        assert_eq!(Some("microservices"), value);
+    }
+
+    #[tokio::test]
+    async fn get_value_for_an_existing_key_and_map_it() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.map_get(&"topic", |value| value.to_uppercase());
+        assert_eq!("MICROSERVICES", value.unwrap());
+    }
+
+    #[tokio::test]
+    async fn get_value_or_default_for_an_existing_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        assert_eq!("microservices", cached.get_or(&"topic", "cache"));
+        assert_eq!("microservices", cached.get_or_else(&"topic", || "cache"));
+    }
+
+    #[tokio::test]
+    async fn get_value_or_default_for_a_non_existing_key_does_not_store_the_default() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        assert_eq!("cache", cached.get_or(&"non-existing", "cache"));
+        assert_eq!("cache", cached.get_or_else(&"non-existing", || "cache"));
+
+        assert_eq!(None, cached.get(&"non-existing"));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(64, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn get_weighted_value_for_an_existing_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_with_weight("topic", "microservices", 30).unwrap();
+        acknowledgement.handle().await;
+
+        assert_eq!(Some(("microservices", 30)), cached.get_weighted(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn get_weighted_value_for_an_existing_key_with_ttl() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_with_weight_and_ttl("topic", "microservices", 30, Duration::from_secs(300)).unwrap();
+        acknowledgement.handle().await;
+
+        let (value, weight) = cached.get_weighted(&"topic").unwrap();
+        assert_eq!("microservices", value);
+        assert_eq!(cached.total_weight_used(), weight);
+    }
+
+    #[tokio::test]
+    async fn get_weighted_value_records_a_single_hit() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_with_weight("topic", "microservices", 30).unwrap();
+        acknowledgement.handle().await;
+
+        let _ = cached.get_weighted(&"topic");
+        assert_eq!(1, cached.stats_summary().get(&StatsType::CacheHits).unwrap());
+    }
+
+    #[tokio::test]
+    async fn get_value_with_metadata_for_a_key_with_weight_and_ttl() {
+        let clock = ManualClock::new(SystemTime::now());
+        let cached = CacheD::new(test_config_builder().clock(clock.boxed()).access_drain(AccessDrain::Sync).build());
+
+        let acknowledgement =
+            cached.put_with_weight_and_ttl("topic", "microservices", 30, Duration::from_secs(300)).unwrap();
+        acknowledgement.handle().await;
+
+        let key_id = cached.get_ref(&"topic").unwrap().value().key_id();
+        let metadata = cached.get_with_metadata(&"topic").unwrap();
+        assert_eq!(&"microservices", metadata.value());
+        assert_eq!(key_id, metadata.key_id());
+        assert_eq!(cached.total_weight_used(), metadata.weight());
+        assert_eq!(Some(clock.now().add(Duration::from_secs(300))), metadata.expire_after());
+        assert_eq!(1, metadata.estimated_frequency());
+
+        assert_eq!(2, cached.get_with_metadata(&"topic").unwrap().estimated_frequency());
+        assert_eq!(3, cached.stats_summary().get(&StatsType::CacheHits).unwrap());
+    }
+
+    #[tokio::test]
+    async fn peek_value_with_metadata() {
+        let cached = CacheD::new(test_config_builder().access_drain(AccessDrain::Sync).build());
+
+        let acknowledgement =
+            cached.put_with_weight("topic", "microservices", 30).unwrap();
+        acknowledgement.handle().await;
+
+        let metadata = cached.peek_with_metadata(&"topic").unwrap();
+        assert_eq!(("microservices", 30, None, 0), (metadata.clone().into_value(), metadata.weight(), metadata.expire_after(), metadata.estimated_frequency()));
+
+        assert_eq!(0, cached.peek_with_metadata(&"topic").unwrap().estimated_frequency());
+        assert_eq!(0, cached.stats_summary().get(&StatsType::CacheHits).unwrap());
+    }
+
+    #[test]
+    fn get_value_with_metadata_for_a_non_existing_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+
+        assert_eq!(None, cached.get_with_metadata(&"non-existing"));
+        assert_eq!(None, cached.peek_with_metadata(&"non-existing"));
+    }
+
+    #[test]
+    fn get_weighted_value_for_a_non_existing_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+
+        assert_eq!(None, cached.get_weighted(&"non-existing"));
+    }
+
+    #[test]
+    fn get_value_for_a_non_existing_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+
+        let value = cached.get(&"non-existing");
+        assert_eq!(None, value);
+    }
+
+    #[test]
+    fn get_value_for_a_non_existing_key_and_attempt_to_map_it() {
+        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+
+        let value = cached.map_get(&"topic", |value| value.to_uppercase());
+        assert_eq!(None, value);
+    }
+
+    #[tokio::test]
+    async fn map_get_or_for_an_existing_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put("topic", "microservices").unwrap().handle().await;
+
+        let value = cached.map_get_or(&"topic", "NONE".to_string(), |value| value.to_uppercase());
+        assert_eq!("MICROSERVICES", value);
+    }
+
+    #[test]
+    fn map_get_or_for_a_non_existing_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+
+        let value = cached.map_get_or(&"topic", "NONE".to_string(), |value| value.to_uppercase());
+        assert_eq!("NONE", value);
+    }
+
+    #[tokio::test]
+    async fn get_value_ref_for_an_existing_key_if_value_is_not_cloneable() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let acknowledgement =
+            cached.put("name", Name { first: "John".to_string(), last: "Mcnamara".to_string() }).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"name");
+        assert_eq!(&Name { first: "John".to_string(), last: "Mcnamara".to_string() }, value.unwrap().value().value_ref());
+    }
+
+    #[tokio::test]
+    async fn get_value_for_an_existing_key_if_value_is_not_cloneable_by_passing_an_arc() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let acknowledgement =
+            cached.put("name", Arc::new(Name { first: "John".to_string(), last: "Mcnamara".to_string() })).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get(&"name").unwrap();
+        assert_eq!("John".to_string(), value.first);
+        assert_eq!("Mcnamara".to_string(), value.last);
+    }
+
+    #[tokio::test]
+    async fn delete_a_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        let key_id = {
+            let key_value_ref = cached.get_ref(&"topic").unwrap();
+            key_value_ref.value().key_id()
+        };
+
+        let acknowledgement =
+            cached.delete("topic").unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get(&"topic");
+        assert_eq!(None, value);
+        assert!(!cached.admission_policy.contains(&key_id));
+    }
+
+    #[tokio::test]
+    async fn delete_a_key_and_get_its_previous_value() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put("topic", "microservices").unwrap().handle().await;
+
+        let (status, previous) = cached.delete("topic").unwrap().handle_with_previous::<&str>().await;
+        assert_eq!((CommandStatus::Accepted, Some("microservices")), (status, previous));
+
+        let (status, previous) = cached.delete("topic").unwrap().handle_with_previous::<&str>().await;
+        assert_eq!((CommandStatus::Rejected(RejectionReason::KeyDoesNotExist), None), (status, previous));
+    }
+
+    #[tokio::test]
+    async fn get_access_frequency() {
+        let cached = CacheD::new(ConfigBuilder::new(10, 10, 1000).access_pool_size(1).access_buffer_size(3).build());
+
+        let acknowledgement_topic =
+            cached.put("topic", "microservices").unwrap();
+        let acknowledgement_disk =
+            cached.put("disk", "SSD").unwrap();
+
+        acknowledgement_topic.handle().await;
+        acknowledgement_disk.handle().await;
+
+        cached.get(&"topic");
+        cached.get(&"disk");
+        cached.get(&"topic");
+        cached.get(&"disk"); //will cause the drain of the buffer which will have 2 accesses of topic and one for disk
+
+        thread::sleep(Duration::from_secs(2));
+
+        let hasher = &(cached.config.key_hash_fn);
+        let policy = cached.admission_policy;
+
+        assert_eq!(2, policy.estimate(hasher(&"topic")));
+        assert_eq!(1, policy.estimate(hasher(&"disk")));
+    }
+
+    #[tokio::test]
+    async fn get_estimated_frequency() {
+        let cached = CacheD::new(ConfigBuilder::new(10, 10, 1000).access_pool_size(1).access_buffer_size(3).build());
+
+        let acknowledgement_topic =
+            cached.put("topic", "microservices").unwrap();
+        let acknowledgement_disk =
+            cached.put("disk", "SSD").unwrap();
+
+        acknowledgement_topic.handle().await;
+        acknowledgement_disk.handle().await;
+
+        cached.get(&"topic");
+        cached.get(&"disk");
+        cached.get(&"topic");
+        cached.get(&"disk"); //will cause the drain of the buffer which will have 2 accesses of topic and one for disk
+
+        thread::sleep(Duration::from_secs(2));
+
+        assert_eq!(2, cached.estimated_frequency(&"topic"));
+        assert_eq!(1, cached.estimated_frequency(&"disk"));
+    }
+
+    #[tokio::test]
+    async fn get_estimated_frequency_of_a_deleted_key() {
+        let cached = CacheD::new(ConfigBuilder::new(10, 10, 1000).access_drain(AccessDrain::Sync).build());
+
+        cached.put("topic", "microservices").unwrap().handle().await;
+        cached.get(&"topic");
+        cached.get(&"topic");
+        cached.delete("topic").unwrap().handle().await;
+
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(2, cached.estimated_frequency(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn reset_the_frequency_of_a_saturated_key() {
+        let cached = CacheD::new(ConfigBuilder::new(1024, 10, 1000).access_drain(AccessDrain::Sync).build());
+
+        cached.put("topic", "microservices").unwrap().handle().await;
+        (0..50).for_each(|_| { let _ = cached.get(&"topic"); });
+        assert_eq!(16, cached.estimated_frequency(&"topic"));
+
+        cached.reset_frequencies();
+        assert_eq!(7, cached.estimated_frequency(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn reset_the_frequency_after_the_configured_accesses() {
+        let cached = CacheD::new(ConfigBuilder::new(1024, 10, 1000).access_drain(AccessDrain::Sync).frequency_reset_after(20).build());
+
+        cached.put("topic", "microservices").unwrap().handle().await;
+        (0..19).for_each(|_| { let _ = cached.get(&"topic"); });
+        assert_eq!(16, cached.estimated_frequency(&"topic"));
+
+        let _ = cached.get(&"topic");
+        assert_eq!(7, cached.estimated_frequency(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn get_estimated_frequency_with_a_deeper_sketch() {
+        let cached = CacheD::new(ConfigBuilder::new(10, 10, 1000).sketch_depth(8).access_drain(AccessDrain::Sync).build());
+
+        cached.put("topic", "microservices").unwrap().handle().await;
+        cached.put("disk", "SSD").unwrap().handle().await;
+        cached.get(&"topic");
+        cached.get(&"disk");
+        cached.get(&"topic");
+
+        assert_eq!(2, cached.estimated_frequency(&"topic"));
+        assert_eq!(1, cached.estimated_frequency(&"disk"));
+        assert_eq!(8, cached.export_sketch().depth());
+    }
+
+    #[tokio::test]
+    async fn get_access_frequency_with_sync_access_drain() {
+        let cached = CacheD::new(ConfigBuilder::new(10, 10, 1000).access_drain(AccessDrain::Sync).build());
+
+        let acknowledgement_topic =
+            cached.put("topic", "microservices").unwrap();
+        let acknowledgement_disk =
+            cached.put("disk", "SSD").unwrap();
+
+        acknowledgement_topic.handle().await;
+        acknowledgement_disk.handle().await;
+
+        cached.get(&"topic");
+        cached.get(&"disk");
+        cached.get(&"topic");
+
+        let hasher = &(cached.config.key_hash_fn);
+        let policy = &cached.admission_policy;
+
+        assert_eq!(2, policy.estimate(hasher(&"topic")));
+        assert_eq!(1, policy.estimate(hasher(&"disk")));
+    }
+
+    #[tokio::test]
+    async fn get_access_frequency_with_periodic_access_drain() {
+        let cached = CacheD::new(ConfigBuilder::new(10, 10, 1000).access_drain(AccessDrain::Periodic(Duration::from_millis(5))).build());
+
+        let acknowledgement_topic =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement_topic.handle().await;
+
+        cached.get(&"topic");
+        cached.get(&"topic");
+
+        thread::sleep(Duration::from_millis(500));
+
+        let hasher = &(cached.config.key_hash_fn);
+        assert_eq!(2, cached.admission_policy.estimate(hasher(&"topic")));
+    }
+
+    #[tokio::test]
+    async fn get_with_frequency() {
+        let cached = CacheD::new(ConfigBuilder::new(10, 10, 1000).access_drain(AccessDrain::Sync).build());
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let _ = cached.put("disk", "SSD").unwrap().handle().await;
+
+        cached.get(&"topic");
+        cached.get(&"topic");
+        cached.get(&"disk");
+
+        assert_eq!(Some(("microservices", 2)), cached.get_with_frequency(&"topic"));
+        assert_eq!(Some(("SSD", 1)), cached.get_with_frequency(&"disk"));
+
+        let hasher = &(cached.config.key_hash_fn);
+        assert_eq!(3, cached.admission_policy.estimate(hasher(&"topic")));
+    }
+
+    #[tokio::test]
+    async fn get_with_frequency_of_a_non_existing_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(10, 10, 1000).access_drain(AccessDrain::Sync).build());
+        cached.prime_frequency(&"topic", 2);
+
+        assert_eq!(None, cached.get_with_frequency(&"topic"));
+        let hasher = &(cached.config.key_hash_fn);
+        assert_eq!(2, cached.admission_policy.estimate(hasher(&"topic")));
+    }
+
+    #[tokio::test]
+    async fn prime_frequency_of_a_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(10, 10, 1000).build());
+        cached.prime_frequency(&"topic", 3);
+
+        let hasher = &(cached.config.key_hash_fn);
+        assert_eq!(3, cached.admission_policy.estimate(hasher(&"topic")));
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn primed_key_evicts_a_cold_key_which_an_unprimed_key_fails_to() {
+        let cached = CacheD::new(ConfigBuilder::new(10, 10, 64).access_drain(AccessDrain::Sync).build());
+
+        let status = cached.put("disk", "SSD").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        cached.get(&"disk");
+
+        let status = cached.put("topic", "microservices").unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers), status);
+
+        cached.prime_frequency(&"cache", 3);
+        let status = cached.put("cache", "cached").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        assert_eq!(Some("cached"), cached.get(&"cache"));
+        assert_eq!(None, cached.get(&"disk"));
+    }
+
+    #[tokio::test]
+    async fn get_multiple_keys() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put("disk", "SSD").unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put("cache", "in-memory").unwrap();
+        acknowledgement.handle().await;
+
+        let values = cached.multi_get(vec![&"topic", &"non-existing", &"cache", &"disk"]);
+
+        assert_eq!(&Some("microservices"), values.get(&"topic").unwrap());
+        assert_eq!(&None, values.get(&"non-existing").unwrap());
+        assert_eq!(&Some("in-memory"), values.get(&"cache").unwrap());
+        assert_eq!(&Some("SSD"), values.get(&"disk").unwrap());
+    }
+
+    #[test]
+    fn get_a_very_large_number_of_keys_while_the_writes_make_progress() {
+        let cached: Arc<CacheD<u64, u64>> = Arc::new(CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).build()));
+        cached.put_sync(1, 100).unwrap();
+
+        let writer = thread::spawn({
+            let cached = cached.clone();
+            move || (2..=200).map(|key| cached.put_sync(key, key * 100).unwrap()).collect::<Vec<_>>()
+        });
+
+        let keys = (0..500_000).map(|key| key % 1_000).collect::<Vec<u64>>();
+        let values = cached.multi_get(keys.iter().collect());
+
+        let statuses = writer.join().unwrap();
+        assert!(statuses.iter().all(|status| *status == CommandStatus::Accepted));
+        assert_eq!(1_000, values.len());
+        assert_eq!(&Some(100), values.get(&1).unwrap());
+        assert_eq!(&None, values.get(&999).unwrap());
+        assert_eq!(Some(20_000), cached.get(&200));
+    }
+
+    #[tokio::test]
+    async fn get_multiple_fresh_keys_excluding_an_expired_but_not_removed_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .expired_read_behavior(ExpiredReadBehavior::ServeStaleOnce)
+                .ttl_tick_duration(Duration::from_secs(300))
+                .build()
+        );
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put("disk", "SSD").unwrap();
+        acknowledgement.handle().await;
+
+        clock.advance(Duration::from_secs(10));
+
+        let values = cached.multi_get_fresh(vec![&"topic", &"non-existing", &"disk"]);
+
+        assert_eq!(&None, values.get(&"topic").unwrap());
+        assert_eq!(&None, values.get(&"non-existing").unwrap());
+        assert_eq!(&Some("SSD"), values.get(&"disk").unwrap());
+        assert!(cached.store.is_present(&"topic"));
+
+        let values = cached.multi_get(vec![&"topic"]);
+        assert_eq!(&Some("microservices"), values.get(&"topic").unwrap());
+    }
+
+    #[tokio::test]
+    async fn contains_a_live_key_without_marking_it_accessed() {
+        let cached = CacheD::new(test_config_builder().access_drain(AccessDrain::Sync).build());
+        cached.put("topic", "microservices").unwrap().handle().await;
+
+        assert!(cached.contains_key(&"topic"));
+        assert!(!cached.contains_key(&"non-existing"));
+        assert_eq!(0, cached.stats_summary().get(&StatsType::CacheHits).unwrap());
+        assert_eq!(0, cached.stats_summary().get(&StatsType::CacheMisses).unwrap());
+
+        let hasher = &(cached.config.key_hash_fn);
+        assert_eq!(0, cached.admission_policy.estimate(hasher(&"topic")));
+    }
+
+    #[tokio::test]
+    async fn peek_a_key_without_marking_it_accessed() {
+        let cached = CacheD::new(test_config_builder().access_drain(AccessDrain::Sync).build());
+        cached.put("topic", "microservices").unwrap().handle().await;
+        cached.get(&"topic");
+
+        let hasher = &(cached.config.key_hash_fn);
+        assert_eq!(1, cached.admission_policy.estimate(hasher(&"topic")));
+
+        assert_eq!(Some("microservices"), cached.peek(&"topic"));
+        assert_eq!(Some("microservices"), cached.peek_ref(&"topic").map(|value_ref| *value_ref.value().value_ref()));
+        assert_eq!(None, cached.peek(&"non-existing"));
+
+        assert_eq!(1, cached.admission_policy.estimate(hasher(&"topic")));
+        assert_eq!(1, cached.stats_summary().get(&StatsType::CacheHits).unwrap());
+        assert_eq!(0, cached.stats_summary().get(&StatsType::CacheMisses).unwrap());
+    }
+
+    #[tokio::test]
+    async fn does_not_peek_an_expired_but_not_removed_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .build()
+        );
+
+        cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).unwrap().handle().await;
+        clock.advance(Duration::from_secs(10));
+
+        assert_eq!(None, cached.peek(&"topic"));
+        assert!(cached.peek_ref(&"topic").is_none());
+    }
+
+    #[tokio::test]
+    async fn does_not_contain_an_expired_but_not_removed_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .build()
+        );
+
+        cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).unwrap().handle().await;
+        clock.advance(Duration::from_secs(10));
+
+        assert!(!cached.contains_key(&"topic"));
+        assert!(cached.store.is_present(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn does_not_contain_a_deleted_key() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("topic", "microservices").unwrap().handle().await;
+        cached.delete("topic").unwrap().handle().await;
+
+        assert!(!cached.contains_key(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn does_not_contain_a_key_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("topic", "microservices").unwrap().handle().await;
+        cached.shutdown();
+
+        assert!(!cached.contains_key(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn get_and_extend_keeps_a_key_alive_while_a_key_that_is_not_extended_expires() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .build()
+        );
+
+        cached.put_with_ttl("topic", "microservices", Duration::from_secs(10)).unwrap().handle().await;
+        cached.put_with_ttl("disk", "SSD", Duration::from_secs(10)).unwrap().handle().await;
+
+        for _ in 0..3 {
+            clock.advance(Duration::from_secs(5));
+            assert_eq!(Some("microservices"), cached.get_and_extend(&"topic", Duration::from_secs(10)));
+        }
+
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(None, cached.get(&"disk"));
+    }
+
+    #[tokio::test]
+    async fn get_and_extend_a_non_existing_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+
+        assert_eq!(None, cached.get_and_extend(&"topic", Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn get_and_extend_a_key_without_time_to_live_and_reconcile_its_weight() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .inline_execution()
+                .build()
+        );
+
+        let _ = cached.put("topic", "microservices").unwrap();
+        assert_eq!(64, cached.total_weight_used());
+
+        assert_eq!(Some("microservices"), cached.get_and_extend(&"topic", Duration::from_secs(10)));
+        assert_eq!(88, cached.total_weight_used());
+
+        assert_eq!(Some("microservices"), cached.get_and_extend(&"topic", Duration::from_secs(20)));
+        assert_eq!(88, cached.total_weight_used());
+
+        clock.advance(Duration::from_secs(25));
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn get_multiple_keys_via_an_iterator() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put("disk", "SSD").unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put("cache", "in-memory").unwrap();
+        acknowledgement.handle().await;
+
+        let mut iterator = cached.multi_get_iterator(vec![&"topic", &"non-existing", &"cache", &"disk"]);
+        assert_eq!(Some("microservices"), iterator.next().unwrap());
+        assert_eq!(None, iterator.next().unwrap());
+        assert_eq!(Some("in-memory"), iterator.next().unwrap());
+        assert_eq!(Some("SSD"), iterator.next().unwrap());
+        assert_eq!(None, iterator.next());
+    }
+
+    #[tokio::test]
+    async fn get_multiple_keys_via_an_iterator_given_value_is_not_cloneable() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let acknowledgement =
+            cached.put("captain", Arc::new(Name { first: "John".to_string(), last: "Mcnamara".to_string() })).unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put("vice-captain", Arc::new(Name { first: "Martin".to_string(), last: "Trolley".to_string() })).unwrap();
+        acknowledgement.handle().await;
+
+        let mut iterator = cached.multi_get_iterator(vec![&"captain", &"vice-captain", &"disk"]);
+        assert_eq!("John", iterator.next().unwrap().unwrap().first);
+        assert_eq!("Martin", iterator.next().unwrap().unwrap().first);
+        assert_eq!(None, iterator.next().unwrap());
+    }
+
+    #[tokio::test]
+    async fn get_thousands_of_keys_via_an_iterator_in_order() {
+        let cached = CacheD::new(ConfigBuilder::new(10_000, 5_000, 1_000_000).build());
+        let acknowledgements = cached.put_all((0..5_000).map(|key| (key, key * 2)).collect())
+            .into_iter()
+            .map(|result| result.unwrap())
+            .collect();
+        CommandAcknowledgement::handle_all(acknowledgements).await;
+
+        let keys = (0..5_000).rev().collect::<Vec<_>>();
+        let values = cached.multi_get_iterator(keys.iter().collect()).collect::<Vec<_>>();
+
+        assert_eq!(keys.iter().map(|key| Some(key * 2)).collect::<Vec<_>>(), values);
+    }
+
+    #[tokio::test]
+    async fn map_multiple_keys_via_an_iterator() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put("disk", "ssd").unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put("cache", "in-memory").unwrap();
+        acknowledgement.handle().await;
+
+        let mut iterator = cached.multi_get_map_iterator(vec![&"topic", &"non-existing", &"cache", &"disk"], |value| value.to_uppercase());
+        assert_eq!(Some("MICROSERVICES".to_string()), iterator.next().unwrap());
+        assert_eq!(None, iterator.next().unwrap());
+        assert_eq!(Some("IN-MEMORY".to_string()), iterator.next().unwrap());
+        assert_eq!(Some("SSD".to_string()), iterator.next().unwrap());
+        assert_eq!(None, iterator.next());
+    }
+
+    #[tokio::test]
+    async fn total_weight_used() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_with_weight("topic", "microservices", 50).unwrap();
+        acknowledgement.handle().await;
+
+        assert_eq!(50, cached.total_weight_used());
+    }
+
+    #[test]
+    fn max_weight_and_capacity() {
+        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+
+        assert_eq!(100, cached.max_weight());
+        assert_eq!(10, cached.capacity());
+    }
+
+    #[tokio::test]
+    async fn weight_utilization() {
+        let cached = CacheD::new(test_config_builder().build());
+        assert_eq!(0.0, cached.weight_utilization());
+
+        cached.put_with_weight("topic", "microservices", 40).unwrap().handle().await;
+        assert_eq!(0.4, cached.weight_utilization());
+    }
+
+    #[tokio::test]
+    async fn stats_summary() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
+        cached.put_with_weight("cache", "cached", 10).unwrap().handle().await;
+        cached.delete("cache").unwrap().handle().await;
+
+        let _ = cached.get(&"topic");
+        let _ = cached.get(&"cache");
+
+        let summary = cached.stats_summary();
+        assert_eq!(1, summary.get(&StatsType::CacheMisses).unwrap());
+        assert_eq!(1, summary.get(&StatsType::CacheHits).unwrap());
+        assert_eq!(60, summary.get(&StatsType::WeightAdded).unwrap());
+        assert_eq!(10, summary.get(&StatsType::WeightRemoved).unwrap());
+        assert_eq!(2, summary.get(&StatsType::KeysAdded).unwrap());
+        assert_eq!(1, summary.get(&StatsType::KeysDeleted).unwrap());
+
+        assert_eq!(0, summary.get(&StatsType::KeysRejected).unwrap());
+        assert_eq!(0, summary.get(&StatsType::AccessAdded).unwrap());
+        assert_eq!(0, summary.get(&StatsType::AccessDropped).unwrap());
+    }
+
+    #[tokio::test]
+    async fn stats_summary_with_an_expired_key() {
+        let cached = CacheD::new(test_config_builder().ttl_tick_duration(Duration::from_millis(5)).build());
+
+        cached.put_with_ttl("topic", "microservices", Duration::from_millis(5)).unwrap().handle().await;
+        thread::sleep(Duration::from_millis(100));
+
+        let summary = cached.stats_summary();
+        assert_eq!(Some(1), summary.get(&StatsType::KeysExpired));
+        assert_eq!(Some(0), summary.get(&StatsType::KeysDeleted));
+        assert_eq!(Some(0), summary.get(&StatsType::KeysEvicted));
+    }
+
+    #[tokio::test]
+    async fn stats_summary_with_an_evicted_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put_with_weight("topic", "microservices", 60).unwrap().handle().await;
+        let status = cached.put_with_weight("disk", "SSD", 60).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let summary = cached.stats_summary();
+        assert_eq!(Some(1), summary.get(&StatsType::KeysEvicted));
+        assert_eq!(Some(0), summary.get(&StatsType::KeysDeleted));
+        assert_eq!(Some(0), summary.get(&StatsType::KeysExpired));
+    }
+
+    #[tokio::test]
+    async fn reset_stats() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
+        let _ = cached.get(&"topic");
+        cached.reset_stats();
+
+        let summary = cached.stats_summary();
+        assert_eq!(0, summary.get(&StatsType::CacheHits).unwrap());
+        assert_eq!(0, summary.get(&StatsType::KeysAdded).unwrap());
+        assert_eq!(0, summary.get(&StatsType::WeightAdded).unwrap());
+        assert_eq!(50, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn stats_summary_and_reset() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
+        let _ = cached.get(&"topic");
+        let _ = cached.get(&"cache");
+
+        let summary = cached.stats_summary_and_reset();
+        assert_eq!(1, summary.get(&StatsType::CacheHits).unwrap());
+        assert_eq!(1, summary.get(&StatsType::CacheMisses).unwrap());
+        assert_eq!(50, summary.get(&StatsType::WeightAdded).unwrap());
+
+        let _ = cached.get(&"topic");
+        let summary = cached.stats_summary_and_reset();
+        assert_eq!(1, summary.get(&StatsType::CacheHits).unwrap());
+        assert_eq!(0, summary.get(&StatsType::CacheMisses).unwrap());
+        assert_eq!(0, summary.get(&StatsType::WeightAdded).unwrap());
+    }
+
+    #[test]
+    fn increment_with_ttl_counts_concurrent_increments_in_a_window() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached: Arc<CacheD<&str, i64>> = Arc::new(CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .build()
+        ));
+
+        let handles = (1..=8).map(|_| {
+            let cached = cached.clone();
+            thread::spawn(move || {
+                (1..=100).map(|_| cached.increment_with_ttl("requests", 1, Duration::from_secs(60)).unwrap()).collect::<Vec<_>>()
+            })
+        }).collect::<Vec<_>>();
+
+        let mut counts = handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect::<Vec<_>>();
+        counts.sort();
+
+        assert_eq!((1..=800).collect::<Vec<i64>>(), counts);
+        assert_eq!(Some(800), cached.get(&"requests"));
+
+        clock.advance(Duration::from_secs(61));
+        assert_eq!(None, cached.get(&"requests"));
+        assert_eq!(Ok(1), cached.increment_with_ttl("requests", 1, Duration::from_secs(60)));
+        assert_eq!(Ok(2), cached.increment_with_ttl("requests", 1, Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn increment_with_ttl_retains_the_expiry_of_a_live_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached: CacheD<&str, i64> = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .inline_execution()
+                .build()
+        );
+
+        assert_eq!(Ok(5), cached.increment_with_ttl("requests", 5, Duration::from_secs(60)));
+        clock.advance(Duration::from_secs(50));
+        assert_eq!(Ok(3), cached.increment_with_ttl("requests", -2, Duration::from_secs(60)));
+
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(None, cached.get(&"requests"));
+    }
+
+    #[test]
+    fn increment_with_ttl_admits_the_key_and_replaces_the_weight_of_the_expired_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached: CacheD<&str, i64> = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .inline_execution()
+                .build()
+        );
+
+        cached.increment_with_ttl("requests", 1, Duration::from_secs(60)).unwrap();
+        let weight = cached.total_weight_used();
+        assert!(weight > 0);
+
+        clock.advance(Duration::from_secs(61));
+        cached.increment_with_ttl("requests", 1, Duration::from_secs(60)).unwrap();
+
+        assert_eq!(weight, cached.total_weight_used());
+        assert_eq!(Some(1), cached.get(&"requests"));
+    }
+
+    #[test]
+    fn get_does_not_return_expired_keys_deferred_by_max_reaps_per_tick() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached: CacheD<&str, &str> = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .max_reaps_per_tick(1)
+                .inline_execution()
+                .build()
+        );
+
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).unwrap();
+        let _ = cached.put_with_ttl("disk", "SSD", Duration::from_secs(5)).unwrap();
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+
+        clock.advance(Duration::from_secs(10));
+
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(None, cached.get(&"disk"));
+    }
+
+    #[tokio::test]
+    async fn get_does_not_return_a_key_rejected_by_admission() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 10).build());
+
+        let status = cached.put_with_weight("topic", "microservices", 20).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyWeightIsGreaterThanCacheWeight), status);
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn put_and_confirm_an_admitted_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        assert!(cached.put_and_confirm("topic", "microservices").await);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn put_and_confirm_a_key_rejected_by_admission() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 10).build());
+
+        assert!(!cached.put_and_confirm("topic", "microservices").await);
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn put_and_confirm_an_existing_key() {
+        let cached = CacheD::new(test_config_builder().build());
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+
+        assert!(!cached.put_and_confirm("topic", "cached").await);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn put_and_confirm_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.shutdown();
+
+        assert!(!cached.put_and_confirm("topic", "microservices").await);
+    }
+
+    #[test]
+    fn oldest_and_newest_entries_after_removals() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached: CacheD<&str, &str> = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .inline_execution()
+                .build()
+        );
+
+        let _ = cached.put("topic", "microservices").unwrap();
+        let _ = cached.put_with_ttl("disk", "SSD", Duration::from_secs(5)).unwrap();
+        let _ = cached.put("cache", "cached").unwrap();
+        let _ = cached.put("memory", "RAM").unwrap();
+        assert_eq!(Some("topic"), cached.oldest_entry());
+        assert_eq!(Some("memory"), cached.newest_entry());
+
+        let _ = cached.delete("topic").unwrap();
+        assert_eq!(Some("disk"), cached.oldest_entry());
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(Some("cache"), cached.oldest_entry());
+
+        let _ = cached.delete("memory").unwrap();
+        assert_eq!(Some("cache"), cached.newest_entry());
+    }
+
+    #[test]
+    fn oldest_and_newest_entries_after_shutdown() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 1000).inline_execution().build());
+        let _ = cached.put("topic", "microservices").unwrap();
+        cached.shutdown();
+
+        assert_eq!(None, cached.oldest_entry());
+        assert_eq!(None, cached.newest_entry());
+    }
+
+    #[test]
+    fn increment_with_ttl_after_shutdown() {
+        let cached: CacheD<&str, i64> = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.shutdown();
+
+        assert_eq!(Ok(0), cached.increment_with_ttl("requests", 1, Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn increment_with_ttl_returns_the_error_of_the_fallible_weight_calculation_fn() {
+        let cached: CacheD<&str, i64> = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .fallible_weight_calculation_fn(Box::new(|_key, delta, _is_time_to_live_specified| {
+                    if *delta > 10 { Err(WeightError::new("delta is too large")) } else { Ok(10) }
+                }))
+                .inline_execution()
+                .build()
+        );
+
+        assert_eq!(Err(WeightError::new("delta is too large")), cached.increment_with_ttl("requests", 20, Duration::from_secs(60)));
+        assert_eq!(None, cached.get(&"requests"));
+        assert_eq!(Ok(5), cached.increment_with_ttl("requests", 5, Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn filling_past_capacity_invokes_on_full_once_until_headroom_returns() {
+        let full_count = Arc::new(AtomicUsize::new(0));
+        let headroom_count = Arc::new(AtomicUsize::new(0));
+        let (on_full_count, on_headroom_count) = (full_count.clone(), headroom_count.clone());
+        let cached = CacheD::new(
+            test_config_builder()
+                .inline_execution()
+                .on_full(Box::new(move || { on_full_count.fetch_add(1, Ordering::SeqCst); }))
+                .on_has_headroom(Box::new(move || { on_headroom_count.fetch_add(1, Ordering::SeqCst); }))
+                .build()
+        );
+
+        let _ = cached.put_with_weight("topic", "microservices", 40).unwrap();
+        let _ = cached.put_with_weight("disk", "SSD", 40).unwrap();
+        assert_eq!(0, full_count.load(Ordering::SeqCst));
+
+        let _ = cached.put_with_weight("cache", "cached", 40).unwrap();
+        let _ = cached.put_with_weight("memory", "RAM", 40).unwrap();
+        let _ = cached.put_with_weight("storage", "HDD", 40).unwrap();
+        assert_eq!(1, full_count.load(Ordering::SeqCst));
+        assert_eq!(0, headroom_count.load(Ordering::SeqCst));
+
+        for key in ["topic", "disk", "cache", "memory", "storage"] {
+            let _ = cached.delete(key).unwrap();
+        }
+        assert_eq!(1, headroom_count.load(Ordering::SeqCst));
+
+        let _ = cached.put_with_weight("topic", "microservices", 100).unwrap();
+        assert_eq!(2, full_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn does_not_invoke_on_has_headroom_if_the_cache_never_became_full() {
+        let full_count = Arc::new(AtomicUsize::new(0));
+        let headroom_count = Arc::new(AtomicUsize::new(0));
+        let (on_full_count, on_headroom_count) = (full_count.clone(), headroom_count.clone());
+        let cached = CacheD::new(
+            test_config_builder()
+                .inline_execution()
+                .on_full(Box::new(move || { on_full_count.fetch_add(1, Ordering::SeqCst); }))
+                .on_has_headroom(Box::new(move || { on_headroom_count.fetch_add(1, Ordering::SeqCst); }))
+                .build()
+        );
+
+        let _ = cached.put_with_weight("topic", "microservices", 40).unwrap();
+        let _ = cached.delete("topic").unwrap();
+
+        assert_eq!(0, full_count.load(Ordering::SeqCst));
+        assert_eq!(0, headroom_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn does_not_evict_keys_while_the_guard_is_held() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let _ = cached.put_with_weight("topic", "microservices", 50).unwrap();
+        let _ = cached.put_with_weight("disk", "SSD", 40).unwrap();
+
+        let _guard = cached.suspend_eviction();
+        let _ = cached.put_with_weight("cache", "cached", 30).unwrap();
+        let _ = cached.put_with_weight("memory", "RAM", 20).unwrap();
+
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
+        assert_eq!(Some("cached"), cached.get(&"cache"));
+        assert_eq!(Some("RAM"), cached.get(&"memory"));
+        assert_eq!(140, cached.total_weight_used());
+    }
+
+    #[test]
+    fn resumes_the_eviction_when_the_guard_is_dropped() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let _ = cached.put_with_weight("topic", "microservices", 50).unwrap();
+        let _ = cached.put_with_weight("disk", "SSD", 40).unwrap();
+
+        let guard = cached.suspend_eviction();
+        let _ = cached.put_with_weight("cache", "cached", 30).unwrap();
+        assert_eq!(120, cached.total_weight_used());
+
+        drop(guard);
+        assert!(cached.total_weight_used() <= 100);
+
+        let present_keys = ["topic", "disk", "cache"].iter().filter(|key| cached.get(key).is_some()).count();
+        assert_eq!(2, present_keys);
+    }
+
+    #[test]
+    fn resumes_the_eviction_when_the_last_guard_is_dropped() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let _ = cached.put_with_weight("topic", "microservices", 60).unwrap();
+
+        let guard = cached.suspend_eviction();
+        let other_guard = cached.suspend_eviction();
+        let _ = cached.put_with_weight("disk", "SSD", 60).unwrap();
+
+        drop(guard);
+        assert_eq!(120, cached.total_weight_used());
+
+        drop(other_guard);
+        assert_eq!(60, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn reserved_puts_do_not_evict_each_other() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let _ = cached.put_with_weight("topic", "microservices", 40).unwrap();
+
+        {
+            let reservation = cached.reserve(60).unwrap();
+            for (key, value) in [("disk", "SSD"), ("cache", "cached"), ("memory", "RAM")] {
+                let status = reservation.put_with_weight(key, value, 20).unwrap().handle().await;
+                assert_eq!(CommandStatus::Accepted, status);
+            }
+            assert_eq!(0, reservation.remaining());
+        }
+
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
+        assert_eq!(Some("cached"), cached.get(&"cache"));
+        assert_eq!(Some("RAM"), cached.get(&"memory"));
+        assert_eq!(100, cached.total_weight_used());
+    }
+
+    #[test]
+    fn reserving_more_than_the_available_weight_fails() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let _ = cached.put_with_weight("topic", "microservices", 40).unwrap();
+
+        let reservation = cached.reserve(70);
+        assert_eq!(Some(ReservationError::NotEnoughWeightAvailable(70)), reservation.err());
+    }
+
+    #[test]
+    fn reserving_more_than_the_weight_left_by_another_reservation_fails() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let _reservation = cached.reserve(60).unwrap();
+
+        let reservation = cached.reserve(50);
+        assert_eq!(Some(ReservationError::NotEnoughWeightAvailable(50)), reservation.err());
+    }
+
+    #[test]
+    fn dropping_the_reservation_releases_the_unused_weight() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        {
+            let reservation = cached.reserve(60).unwrap();
+            let _ = reservation.put_with_weight("topic", "microservices", 20).unwrap();
+            assert_eq!(40, reservation.remaining());
+        }
+
+        assert!(cached.reserve(80).is_ok());
+    }
+
+    #[tokio::test]
+    async fn reserved_weight_is_not_available_to_other_puts() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let _reservation = cached.reserve(100).unwrap();
+
+        let status = cached.put_with_weight("topic", "microservices", 10).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers), status);
+    }
+
+    #[tokio::test]
+    async fn put_beyond_the_reserved_weight_is_a_regular_put() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let reservation = cached.reserve(30).unwrap();
+
+        let status = reservation.put_with_weight("topic", "microservices", 40).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(30, reservation.remaining());
+        assert_eq!(40, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn rejected_reserved_put_returns_the_weight_to_the_reservation() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let _ = cached.put_with_weight("topic", "microservices", 10).unwrap();
+        let reservation = cached.reserve(30).unwrap();
+
+        let status = reservation.put_with_weight("topic", "cache", 20).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), status);
+        assert_eq!(30, reservation.remaining());
+    }
+
+    #[tokio::test]
+    async fn reserved_put_with_ttl() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let reservation = cached.reserve(100).unwrap();
+
+        let status = reservation.put_with_ttl("topic", "microservices", Duration::from_secs(300)).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(100 - cached.total_weight_used(), reservation.remaining());
+    }
+
+    #[tokio::test]
+    async fn reservation_is_retained_across_clear() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let reservation = cached.reserve(30).unwrap();
+        let _ = reservation.put_with_weight("topic", "microservices", 10).unwrap().handle().await;
+
+        cached.clear().unwrap().handle().await;
+        assert_eq!(0, cached.total_weight_used());
+        assert_eq!(Some(ReservationError::NotEnoughWeightAvailable(90)), cached.reserve(90).err());
+
+        let status = reservation.put_with_weight("disk", "SSD", 20).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(0, reservation.remaining());
+
+        drop(reservation);
+        assert!(cached.reserve(80).is_ok());
+    }
+
+    #[test]
+    fn reserve_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        cached.shutdown();
+
+        assert_eq!(Some(ReservationError::ShuttingDown), cached.reserve(10).err());
+    }
+
+    #[test]
+    fn reserved_put_racing_with_shutdown_returns_the_weight_to_the_reservation() {
+        let cached_slot: Arc<OnceLock<Weak<CacheD<&str, &str>>>> = Arc::new(OnceLock::new());
+        let shutdown_slot = cached_slot.clone();
+        let cached = Arc::new(CacheD::new(test_config_builder().inline_execution().value_transform(
+            Box::new(move |value| {
+                if let Some(cached) = shutdown_slot.get().and_then(|cached| cached.upgrade()) {
+                    cached.shutdown();
+                }
+                value
+            }),
+            Box::new(|value| value),
+        ).build()));
+        let _ = cached_slot.set(Arc::downgrade(&cached));
+
+        let reservation = cached.reserve(30).unwrap();
+        let result = reservation.put_with_weight("topic", "microservices", 20);
+
+        assert!(result.is_err());
+        assert_eq!(30, reservation.remaining());
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[test]
+    fn reservation_error_display() {
+        assert_eq!("could not reserve weight 70, not enough weight is available", ReservationError::NotEnoughWeightAvailable(70).to_string());
+        assert_eq!("could not reserve weight, cache is being shutdown", ReservationError::ShuttingDown.to_string());
+    }
+
+    #[test]
+    fn put_sync() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        assert_eq!(CommandStatus::Accepted, cached.put_sync("topic", "microservices").unwrap());
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[test]
+    fn put_sync_of_an_existing_key() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let _ = cached.put_sync("topic", "microservices");
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), cached.put_sync("topic", "cached").unwrap());
+    }
+
+    #[test]
+    fn put_with_weight_sync() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        assert_eq!(CommandStatus::Accepted, cached.put_with_weight_sync("topic", "microservices", 40).unwrap());
+        assert_eq!(40, cached.total_weight_used());
+    }
+
+    #[test]
+    fn delete_sync() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let _ = cached.put_sync("topic", "microservices");
+        assert_eq!(CommandStatus::Accepted, cached.delete_sync("topic").unwrap());
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(0, cached.total_weight_used());
+    }
+
+    #[test]
+    fn delete_sync_of_a_non_existing_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyDoesNotExist), cached.delete_sync("topic").unwrap());
+    }
+
+    #[test]
+    fn remove_a_present_key() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let _ = cached.put_with_weight_sync("topic", "microservices", 40);
+        assert!(cached.remove(&"topic"));
+        assert_eq!(None, cached.get(&"topic"));
+
+        let _ = cached.put_with_weight_sync("disk", "SSD", 10);
+        assert_eq!(10, cached.total_weight_used());
+    }
+
+    #[test]
+    fn remove_an_absent_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        assert!(!cached.remove(&"topic"));
+    }
+
+    #[test]
+    fn remove_after_shutdown() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        let _ = cached.put_sync("topic", "microservices");
+        cached.shutdown();
+
+        assert!(!cached.remove(&"topic"));
+    }
+
+    #[test]
+    fn sync_operations_after_shutdown() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.shutdown();
+
+        assert!(cached.put_sync("topic", "microservices").is_err());
+        assert!(cached.put_with_weight_sync("topic", "microservices", 40).is_err());
+        assert!(cached.delete_sync("topic").is_err());
+    }
+
+    #[tokio::test]
+    async fn put_with_the_default_time_to_live_and_ttl_ticker_evicts_it() {
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .ttl_tick_duration(Duration::from_millis(10))
+                .default_time_to_live(Duration::from_millis(20))
+                .build()
+        );
+
+        let status = cached.put("topic", "microservices").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+
+        thread::sleep(Duration::from_millis(50));
+        assert!(!cached.store.is_present(&"topic"));
+        assert_eq!(0, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn put_with_weight_and_the_default_time_to_live_and_ttl_ticker_evicts_it() {
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .ttl_tick_duration(Duration::from_millis(10))
+                .default_time_to_live(Duration::from_millis(20))
+                .build()
+        );
+
+        let status = cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert!(cached.get_ref(&"topic").unwrap().value().expire_after().is_some());
+
+        thread::sleep(Duration::from_millis(50));
+        assert!(!cached.store.is_present(&"topic"));
+        assert_eq!(0, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn explicit_time_to_live_overrides_the_default_time_to_live() {
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .ttl_tick_duration(Duration::from_millis(10))
+                .default_time_to_live(Duration::from_millis(20))
+                .build()
+        );
+
+        let status = cached.put_with_ttl("topic", "microservices", Duration::from_secs(300)).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn put_without_ttl_opts_out_of_the_default_time_to_live() {
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .ttl_tick_duration(Duration::from_millis(10))
+                .default_time_to_live(Duration::from_millis(20))
+                .build()
+        );
+
+        let status = cached.put_without_ttl("topic", "microservices").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(None, cached.get_ref(&"topic").unwrap().value().expire_after());
+
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[cfg(feature = "serde")]
+    fn expire_after(cached: &CacheD<u64, String>, key: u64) -> Option<SystemTime> {
+        cached.map_get_ref(&key, |stored_value| stored_value.expire_after()).flatten()
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn export_and_import_a_cache() {
+        let cached: CacheD<u64, String> = CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).clock(Box::new(ManualClock::new(SystemTime::UNIX_EPOCH))).build());
+        for key in 0..1_000 {
+            let value = format!("value-{}", key);
+            let _ = match key % 2 {
+                0 => cached.put_with_ttl(key, value, Duration::from_secs(300 + key)).unwrap().block_on_done(),
+                _ => cached.put(key, value).unwrap().block_on_done(),
+            };
+        }
+
+        let mut bytes = Vec::new();
+        assert_eq!(1_000, cached.export_to(&mut bytes).unwrap());
+
+        let imported: CacheD<u64, String> = CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).clock(Box::new(ManualClock::new(SystemTime::UNIX_EPOCH))).build());
+        assert_eq!(1_000, imported.import_from(&mut bytes.as_slice()).unwrap());
+
+        for key in 0..1_000 {
+            assert_eq!(Some(format!("value-{}", key)), imported.get(&key));
+            assert_eq!(expire_after(&cached, key), expire_after(&imported, key));
+        }
+        assert_eq!(Some(SystemTime::UNIX_EPOCH + Duration::from_secs(300)), expire_after(&imported, 0));
+        assert_eq!(None, expire_after(&imported, 1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn import_from_a_truncated_export() {
+        let cached: CacheD<u64, String> = CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).clock(Box::new(ManualClock::new(SystemTime::UNIX_EPOCH))).build());
+        let _ = cached.put(1, "microservices".to_string()).unwrap().block_on_done();
+        let _ = cached.put(2, "SSD".to_string()).unwrap().block_on_done();
+
+        let mut bytes = Vec::new();
+        cached.export_to(&mut bytes).unwrap();
+        bytes.pop();
+
+        let imported: CacheD<u64, String> = CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).clock(Box::new(ManualClock::new(SystemTime::UNIX_EPOCH))).build());
+        let error = imported.import_from(&mut bytes.as_slice()).unwrap_err();
+        assert!(matches!(error, ExportError::Io(_)));
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn save_and_load_a_cache() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached: CacheD<u64, String> = CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).clock(Box::new(clock.clone())).ttl_tick_duration(Duration::from_secs(300)).build());
+        for key in 0..500 {
+            let value = format!("value-{}", key);
+            let _ = match key % 2 {
+                0 => cached.put_with_weight_and_ttl(key, value, 10 + key as i64, Duration::from_secs(100 + key)).unwrap().block_on_done(),
+                _ => cached.put_with_weight(key, value, 10 + key as i64).unwrap().block_on_done(),
+            };
+        }
+
+        let mut bytes = Vec::new();
+        assert_eq!(500, cached.save_to_writer(&mut bytes).unwrap());
+
+        let loaded: CacheD<u64, String> = CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).clock(Box::new(clock.clone())).ttl_tick_duration(Duration::from_secs(300)).build());
+        assert_eq!(500, loaded.load_from_reader(&mut bytes.as_slice()).unwrap());
+
+        for key in 0..500 {
+            assert_eq!(Some(format!("value-{}", key)), loaded.get(&key));
+            assert_eq!(cached.get_weighted(&key).map(|(_, weight)| weight), loaded.get_weighted(&key).map(|(_, weight)| weight));
+        }
+        assert_eq!(cached.total_weight_used(), loaded.total_weight_used());
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn load_deducts_the_time_elapsed_since_the_save() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached: CacheD<u64, String> = CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).clock(Box::new(clock.clone())).ttl_tick_duration(Duration::from_secs(300)).build());
+        let _ = cached.put_with_weight_and_ttl(1, "microservices".to_string(), 10, Duration::from_secs(100)).unwrap().block_on_done();
+        let _ = cached.put_with_weight_and_ttl(2, "SSD".to_string(), 10, Duration::from_secs(300)).unwrap().block_on_done();
+        let _ = cached.put_with_weight(3, "cached".to_string(), 10).unwrap().block_on_done();
+
+        let mut bytes = Vec::new();
+        assert_eq!(3, cached.save_to_writer(&mut bytes).unwrap());
+
+        clock.advance(Duration::from_secs(200));
+        let loaded: CacheD<u64, String> = CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).clock(Box::new(clock.clone())).ttl_tick_duration(Duration::from_secs(300)).build());
+        assert_eq!(2, loaded.load_from_reader(&mut bytes.as_slice()).unwrap());
+
+        assert_eq!(None, loaded.get(&1));
+        assert_eq!(Some("SSD".to_string()), loaded.get(&2));
+        assert_eq!(Some("cached".to_string()), loaded.get(&3));
+
+        clock.advance(Duration::from_secs(101));
+        assert_eq!(None, loaded.get(&2));
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn does_not_load_from_a_reader_without_a_header() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached: CacheD<u64, String> = CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).clock(Box::new(clock.clone())).ttl_tick_duration(Duration::from_secs(300)).build());
+        let _ = cached.put(1, "microservices".to_string()).unwrap().block_on_done();
+
+        let mut bytes = Vec::new();
+        cached.export_to(&mut bytes).unwrap();
+
+        let loaded: CacheD<u64, String> = CacheD::new(ConfigBuilder::new(10_000, 1_000, 1_000_000).clock(Box::new(clock.clone())).ttl_tick_duration(Duration::from_secs(300)).build());
+        let error = loaded.load_from_reader(&mut bytes.as_slice()).unwrap_err();
+        assert!(matches!(error, ExportError::InvalidHeader));
+    }
+
+    #[tokio::test]
+    async fn always_admits_a_fresh_key_over_a_frequently_accessed_key() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 10).access_drain(AccessDrain::Sync).admission(AdmissionMode::Always).build());
+        cached.put_with_weight("topic", "microservices", 10).unwrap().handle().await;
+        (0..10).for_each(|_| { let _ = cached.get(&"topic"); });
+
+        let status = cached.put_with_weight("disk", "SSD", 10).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn tiny_lfu_rejects_a_fresh_key_over_a_frequently_accessed_key() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 10).access_drain(AccessDrain::Sync).admission(AdmissionMode::TinyLfu).build());
+        cached.put_with_weight("topic", "microservices", 10).unwrap().handle().await;
+        (0..10).for_each(|_| { let _ = cached.get(&"topic"); });
+
+        let status = cached.put_with_weight("disk", "SSD", 10).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers), status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn always_admits_every_fresh_key_that_fits_in_the_cache_weight() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 10).access_drain(AccessDrain::Sync).admission(AdmissionMode::Always).build());
+        for (index, key) in ["topic", "disk", "cache", "policy", "ssd"].into_iter().enumerate() {
+            let status = cached.put_with_weight(key, "value", 3 + index as i64).unwrap().handle().await;
+            assert_eq!(CommandStatus::Accepted, status);
+            assert_eq!(Some("value"), cached.get(&key));
+            (0..5).for_each(|_| { let _ = cached.get(&key); });
+        }
+        assert!(cached.total_weight_used() <= 10);
+    }
+
+    #[tokio::test]
+    async fn put_of_a_delete_value_deletes_the_key() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).delete_on_value(Box::new(|value| *value == "<deleted>")).build());
+
+        let status = cached.put("topic", "microservices").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+
+        let status = cached.put("topic", "<deleted>").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(0, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn put_of_a_delete_value_for_a_non_existing_key() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).delete_on_value(Box::new(|value| *value == "<deleted>")).build());
+
+        let status = cached.put("topic", "<deleted>").unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyDoesNotExist), status);
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn put_variants_of_a_delete_value_delete_the_key() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).delete_on_value(Box::new(|value| *value == "<deleted>")).build());
+
+        let _ = cached.put_with_weight("topic", "microservices", 10).unwrap().handle().await;
+        let _ = cached.put_with_ttl("disk", "SSD", Duration::from_secs(300)).unwrap().handle().await;
+
+        let _ = cached.put_with_weight("topic", "<deleted>", 10).unwrap().handle().await;
+        let _ = cached.put_with_weight_and_ttl("disk", "<deleted>", 10, Duration::from_secs(300)).unwrap().handle().await;
+
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(None, cached.get(&"disk"));
+        assert_eq!(0, cached.total_weight_used());
+    }
+}
+
+#[cfg(test)]
+mod shutdown_tests {
+    use std::sync::{Arc, OnceLock, Weak};
+    use std::sync::atomic::Ordering;
+    use std::thread;
+    use std::time::Duration;
+
+    use async_std::future::timeout;
+    use tokio::time::sleep;
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::{CommandStatus, CommandType};
+    use crate::cache::config::ConfigBuilder;
+    use crate::cache::put_or_update::PutOrUpdateRequestBuilder;
+
+    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str> {
+        ConfigBuilder::new(100, 10, 100)
+    }
+
+    #[test]
+    fn put_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.shutdown();
+
+        let put_result = cached.put("storage", "cached");
+        assert!(put_result.is_err());
+    }
+
+    #[test]
+    fn put_with_weight_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.shutdown();
+
+        let put_result = cached.put_with_weight("storage", "cached", 10);
+        assert!(put_result.is_err());
+    }
+
+    #[test]
+    fn put_with_ttl_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.shutdown();
+
+        let put_result = cached.put_with_ttl("storage", "cached", Duration::from_secs(5));
+        assert!(put_result.is_err());
+    }
+
+    #[test]
+    fn put_with_weight_and_ttl_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.shutdown();
+
+        let put_result = cached.put_with_weight_and_ttl("storage", "cached", 10, Duration::from_secs(5));
+        assert!(put_result.is_err());
+    }
+
+    #[test]
+    fn delete_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.shutdown();
+
+        let delete_result = cached.delete("storage");
+        assert!(delete_result.is_err());
+    }
+
+    #[test]
+    fn put_or_update_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.shutdown();
+
+        let put_or_update_result = cached.put_or_update(PutOrUpdateRequestBuilder::new("storage").weight(10).build());
+        assert!(put_or_update_result.is_err());
+    }
+
+    #[tokio::test]
+    async fn get_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("storage", "cached").unwrap().handle().await;
+        cached.shutdown();
+
+        let get_result = cached.get(&"storage");
+        assert_eq!(None, get_result);
+    }
+
+    #[tokio::test]
+    async fn get_ref_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("storage", "cached").unwrap().handle().await;
+        cached.shutdown();
+
+        let get_result = cached.get_ref(&"storage");
+        assert!(get_result.is_none());
+    }
+
+    #[tokio::test]
+    async fn map_get_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("storage", "cached").unwrap().handle().await;
+        cached.shutdown();
+
+        let get_result = cached.map_get(&"storage", |value| value.to_uppercase());
+        assert!(get_result.is_none());
+    }
+
+    #[tokio::test]
+    async fn map_get_or_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("storage", "cached").unwrap().handle().await;
+        cached.shutdown();
+
+        let get_result = cached.map_get_or(&"storage", "NONE".to_string(), |value| value.to_uppercase());
+        assert_eq!("NONE", get_result);
+    }
+
+    #[tokio::test]
+    async fn map_get_ref_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("storage", "cached").unwrap().handle().await;
+        cached.shutdown();
+
+        let get_result = cached.map_get_ref(&"storage", |stored_value| stored_value.value_ref().to_uppercase());
+        assert!(get_result.is_none());
+    }
+
+    #[tokio::test]
+    async fn multi_get_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("storage", "cached").unwrap().handle().await;
+        cached.put("topic", "microservices").unwrap().handle().await;
+
+        cached.shutdown();
+
+        let multi_get_result = cached.multi_get(vec![&"storage", &"topic"]);
+        assert!(multi_get_result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn multi_get_iterator_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("storage", "cached").unwrap().handle().await;
+        cached.put("topic", "microservices").unwrap().handle().await;
+
+        cached.shutdown();
+
+        let mut iterator = cached.multi_get_iterator(vec![&"storage", &"topic"]);
+        assert!(iterator.next().is_none());
+    }
+
+    #[tokio::test]
+    async fn multi_get_iterator_interrupted_by_shutdown_midway() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put_with_weight("storage", "cached", 10).unwrap().handle().await;
+        cached.put_with_weight("topic", "microservices", 10).unwrap().handle().await;
+
+        let mut iterator = cached.multi_get_iterator(vec![&"storage", &"topic"]);
+        assert_eq!(Some(Some("cached")), iterator.next());
+        assert!(!iterator.was_interrupted());
+
+        cached.shutdown();
+
+        assert_eq!(None, iterator.next());
+        assert!(iterator.was_interrupted());
+    }
+
+    #[tokio::test]
+    async fn multi_get_iterator_runs_out_of_keys_without_interruption() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put_with_weight("storage", "cached", 10).unwrap().handle().await;
+
+        let mut iterator = cached.multi_get_iterator(vec![&"storage", &"topic"]);
+        assert_eq!(Some(Some("cached")), iterator.next());
+        assert_eq!(Some(None), iterator.next());
+        assert_eq!(None, iterator.next());
+        assert!(!iterator.was_interrupted());
+
+        cached.shutdown();
+        assert_eq!(None, iterator.next());
+        assert!(!iterator.was_interrupted());
+    }
+
+    #[tokio::test]
+    async fn multi_get_map_iterator_interrupted_by_shutdown_midway() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put_with_weight("storage", "cached", 10).unwrap().handle().await;
+        cached.put_with_weight("topic", "microservices", 10).unwrap().handle().await;
+
+        let mut iterator = cached.multi_get_map_iterator(vec![&"storage", &"topic"], |value| { value.to_uppercase() });
+        assert_eq!(Some(Some("CACHED".to_string())), iterator.next());
+
+        cached.shutdown();
+
+        assert_eq!(None, iterator.next());
+        assert!(iterator.was_interrupted());
+    }
+
+    #[tokio::test]
+    async fn multi_get_map_iterator_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.put("storage", "cached").unwrap().handle().await;
+        cached.put("topic", "microservices").unwrap().handle().await;
+
+        cached.shutdown();
+
+        let mut iterator = cached.multi_get_map_iterator(vec![&"storage", &"topic"], |value| { value.to_uppercase() });
+        assert!(iterator.next().is_none());
+    }
+
+    #[tokio::test]
+    async fn shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
+        cached.put("cache", "cached").unwrap().handle().await;
+
+        cached.shutdown();
+        assert!(cached.is_shutting_down.load(Ordering::Acquire));
+
+        let put_result = cached.put("storage", "cached");
+        assert!(put_result.is_err());
+
+        assert_eq!(0, cached.total_weight_used());
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(None, cached.get(&"cache"));
+    }
+
+    #[tokio::test]
+    async fn concurrent_shutdown() {
+        let cached = Arc::new(CacheD::new(test_config_builder().build()));
+        cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
+        cached.put("cache", "cached").unwrap().handle().await;
+
+        let thread_handles = (1..=10).map(|_| {
+            thread::spawn({
+                let cached = cached.clone();
+                move || {
+                    cached.shutdown();
+                }
+            })
+        }).collect::<Vec<_>>();
+        for handle in thread_handles {
+            handle.join().unwrap();
+        }
+
+        assert!(cached.is_shutting_down.load(Ordering::Acquire));
+
+        let put_result = cached.put("storage", "cached");
+        assert!(put_result.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn should_not_block_on_shutdown() {
+        let config_builder = ConfigBuilder::new(1000, 100, 1_000_000);
+        let cached = Arc::new(CacheD::new(config_builder.build()));
+
+        let task_handles = (1..=50).map(|index| {
+            let cached_clone = cached.clone();
+            tokio::spawn(
+                async move {
+                    let start_index = index * 10;
+                    let end_index = start_index + 10;
+
+                    for count in start_index..end_index {
+                        let put_result = cached_clone.put(count, count * 10);
+                        if let Ok(result) = put_result {
+                            timeout(Duration::from_secs(1), result.handle()).await.unwrap();
+                        }
+                        sleep(Duration::from_millis(2)).await;
+                    }
+                }
+            )
+        }).collect::<Vec<_>>();
+
+        let cached_clone = cached.clone();
+        let shutdown_handle = tokio::spawn(
+            async move {
+                sleep(Duration::from_millis(8)).await;
+                cached_clone.shutdown();
+            }
+        );
+        for handle in task_handles {
+            handle.await.unwrap()
+        }
+        shutdown_handle.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn should_not_block_on_shutdown_with_limited_space() {
+        let config_builder = ConfigBuilder::new(1000, 100, 1000);
+        let cached = Arc::new(CacheD::new(config_builder.build()));
+
+        let task_handles = (1..=50).map(|index| {
+            let cached_clone = cached.clone();
+            tokio::spawn(
+                async move {
+                    let start_index = index * 10;
+                    let end_index = start_index + 10;
+
+                    for count in start_index..end_index {
+                        let put_result = cached_clone.put(count, count * 10);
+                        if let Ok(result) = put_result {
+                            timeout(Duration::from_secs(1), result.handle()).await.unwrap();
+                        }
+                        sleep(Duration::from_millis(2)).await;
+                    }
+                }
+            )
+        }).collect::<Vec<_>>();
+
+        let cached_clone = cached.clone();
+        let shutdown_handle = tokio::spawn(
+            async move {
+                sleep(Duration::from_millis(8)).await;
+                cached_clone.shutdown();
+            }
+        );
+        for handle in task_handles {
+            handle.await.unwrap()
+        }
+        shutdown_handle.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn should_not_retain_entries_after_shutdown_with_puts_in_flight() {
+        let config_builder = ConfigBuilder::new(10_000, 1000, 1_000_000);
+        let cached = Arc::new(CacheD::new(config_builder.build()));
+
+        let task_handles = (0..100).map(|index| {
+            let cached_clone = cached.clone();
+            tokio::spawn(
+                async move {
+                    let start_index = index * 50;
+                    let end_index = start_index + 50;
+
+                    for count in start_index..end_index {
+                        let _ = cached_clone.put(count, count * 10);
+                    }
+                }
+            )
+        }).collect::<Vec<_>>();
+
+        let cached_clone = cached.clone();
+        let shutdown_handle = tokio::spawn(
+            async move {
+                sleep(Duration::from_millis(1)).await;
+                cached_clone.shutdown();
+            }
+        );
+        for handle in task_handles {
+            handle.await.unwrap()
+        }
+        shutdown_handle.await.unwrap();
+
+        for count in 0..5000 {
+            assert!(!cached.store.is_present(&count));
+        }
+        assert_eq!(0, cached.total_weight_used());
+    }
+
+    #[test]
+    fn shutdown_report_with_commands_queued_behind_shutdown() {
+        let (entered_sender, entered_receiver) = crossbeam_channel::bounded(1);
+        let (release_sender, release_receiver) = crossbeam_channel::bounded::<()>(0);
+        let cached = Arc::new(CacheD::new(
+            test_config_builder()
+                .on_full(Box::new(move || {
+                    let _ = entered_sender.send(());
+                    let _ = release_receiver.recv();
+                }))
+                .build()
+        ));
+
+        let _ = cached.put_with_weight("topic", "microservices", 100).unwrap();
+        entered_receiver.recv().unwrap();
+
+        let shutdown = thread::spawn({
+            let cached = cached.clone();
+            move || cached.shutdown()
+        });
+        while cached.command_executor.queued_commands() == 0 {
+            thread::yield_now();
+        }
+
+        let _ = cached.command_executor.send(CommandType::Put(cached.key_description("disk", 10), "SSD")).unwrap();
+        let _ = cached.command_executor.send(CommandType::Put(cached.key_description("cache", 10), "cached")).unwrap();
+        release_sender.send(()).unwrap();
+
+        let report = shutdown.join().unwrap();
+        assert_eq!(2, report.dropped_commands());
+        assert_eq!(1, report.cleared_entries());
+        assert!(report.executor_stopped());
+        assert_eq!(Some(report), cached.shutdown_report());
+
+        assert!(!cached.store.is_present(&"topic"));
+        assert!(!cached.store.is_present(&"disk"));
+        assert_eq!(0, cached.total_weight_used());
+    }
+
+    #[test]
+    fn shutdown_waits_for_the_operations_that_passed_the_clear_gate() {
+        let cached = Arc::new(CacheD::new(test_config_builder().build()));
+        let gate = cached.pass_clear_gate();
+
+        let shutdown = thread::spawn({
+            let cached = cached.clone();
+            move || cached.shutdown()
+        });
+        while !cached.is_shutting_down() {
+            thread::yield_now();
+        }
+        cached.store.put("topic", "microservices", 1);
+        drop(gate);
+
+        let report = shutdown.join().unwrap();
+        assert_eq!(1, report.cleared_entries());
+        assert!(!cached.store.is_present(&"topic"));
+    }
+
+    #[test]
+    fn shutdown_from_a_listener_on_the_thread_of_command_executor() {
+        let cached_slot: Arc<OnceLock<Weak<CacheD<&str, &str>>>> = Arc::new(OnceLock::new());
+        let cached = Arc::new(CacheD::new(
+            test_config_builder()
+                .on_full(Box::new({
+                    let cached_slot = cached_slot.clone();
+                    move || {
+                        if let Some(cached) = cached_slot.get().and_then(Weak::upgrade) {
+                            cached.shutdown();
+                        }
+                    }
+                }))
+                .build()
+        ));
+        cached_slot.set(Arc::downgrade(&cached)).unwrap();
+
+        let status = cached.put_with_weight("topic", "microservices", 100).unwrap().block_on_done();
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let report = cached.shutdown_report().unwrap();
+        assert!(!report.executor_stopped());
+        assert!(cached.put("disk", "SSD").is_err());
+    }
+
+    #[test]
+    fn shutdown_from_a_listener_of_an_inline_command_executor() {
+        let cached_slot: Arc<OnceLock<Weak<CacheD<&str, &str>>>> = Arc::new(OnceLock::new());
+        let cached = Arc::new(CacheD::new(
+            test_config_builder()
+                .inline_execution()
+                .on_full(Box::new({
+                    let cached_slot = cached_slot.clone();
+                    move || {
+                        if let Some(cached) = cached_slot.get().and_then(Weak::upgrade) {
+                            cached.shutdown();
+                        }
+                    }
+                }))
+                .build()
+        ));
+        cached_slot.set(Arc::downgrade(&cached)).unwrap();
+
+        let status = cached.put_with_weight("topic", "microservices", 100).unwrap().block_on_done();
+        assert_eq!(CommandStatus::Accepted, status);
+
+        assert!(!cached.shutdown_report().unwrap().executor_stopped());
+        assert!(cached.put("disk", "SSD").is_err());
+    }
+
+    fn cache_shutdown_by_eviction_listener(config_builder: ConfigBuilder<&'static str, &'static str>) -> Arc<CacheD<&'static str, &'static str>> {
+        let cached_slot: Arc<OnceLock<Weak<CacheD<&str, &str>>>> = Arc::new(OnceLock::new());
+        let cached = Arc::new(CacheD::new(
+            config_builder
+                .eviction_listener(Box::new({
+                    let cached_slot = cached_slot.clone();
+                    move |_key, _value, _reason| {
+                        if let Some(cached) = cached_slot.get().and_then(Weak::upgrade) {
+                            cached.shutdown();
+                        }
+                    }
+                }))
+                .build()
+        ));
+        cached_slot.set(Arc::downgrade(&cached)).unwrap();
+        cached
+    }
+
+    #[test]
+    fn shutdown_from_an_eviction_listener_on_the_thread_of_command_executor() {
+        let cached = cache_shutdown_by_eviction_listener(test_config_builder());
+
+        let _ = cached.put_with_weight("topic", "microservices", 60).unwrap().block_on_done();
+        let status = cached.put_with_weight("disk", "SSD", 60).unwrap().block_on_done();
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let report = cached.shutdown_report().unwrap();
+        assert!(!report.executor_stopped());
+        assert!(cached.put("cache", "in-memory").is_err());
+    }
+
+    #[test]
+    fn shutdown_from_an_eviction_listener_of_an_inline_command_executor() {
+        let cached = cache_shutdown_by_eviction_listener(test_config_builder().inline_execution());
+
+        let _ = cached.put_with_weight("topic", "microservices", 60).unwrap().block_on_done();
+        let status = cached.put_with_weight("disk", "SSD", 60).unwrap().block_on_done();
+        assert_eq!(CommandStatus::Accepted, status);
+
+        assert!(!cached.shutdown_report().unwrap().executor_stopped());
+        assert!(cached.put("cache", "in-memory").is_err());
+    }
+
+    #[test]
+    fn shutdown_report_before_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        assert_eq!(None, cached.shutdown_report());
+    }
+
+    #[test]
+    fn shutdown_report_of_the_ttl_ticker() {
+        let cached = CacheD::new(test_config_builder().ttl_tick_duration(Duration::from_millis(5)).build());
+        cached.shutdown();
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(cached.shutdown_report().unwrap().ttl_ticker_stopped());
+    }
+
+    #[test]
+    fn shutdown_and_join_stops_the_background_threads() {
+        let cached = CacheD::new(test_config_builder().ttl_tick_duration(Duration::from_millis(5)).build());
+        let store = cached.store.clone();
+        for key in ["topic", "disk", "cache", "engine", "database"] {
+            let _ = cached.put_with_ttl(key, "value", Duration::from_millis(1));
+        }
+
+        let report = cached.shutdown_and_join();
+        assert!(report.executor_stopped());
+        assert!(report.ttl_ticker_stopped());
+
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(0, store.len());
+        assert_eq!(1, Arc::strong_count(&store));
+    }
+
+    #[test]
+    fn shutdown_again_returns_the_report_of_the_earlier_shutdown() {
+        let cached = CacheD::new(test_config_builder().inline_execution().build());
+        let _ = cached.put("topic", "microservices").unwrap();
+
+        let report = cached.shutdown();
+        assert_eq!(1, report.cleared_entries());
+        assert_eq!(report.cleared_entries(), cached.shutdown().cleared_entries());
+    }
+}
+
+#[cfg(test)]
+mod for_each_tests {
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::CommandStatus;
+    use crate::cache::config::{AccessDrain, ConfigBuilder};
+    use crate::cache::stats::StatsType;
+
+    #[derive(Eq, PartialEq, Debug)]
+    struct Order {
+        item: String,
+        amount: u64,
+    }
+
+    #[tokio::test]
+    async fn sum_a_field_across_values_without_cloning() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let status = cached.put("order-1", Order { item: "disk".to_string(), amount: 100 }).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        let status = cached.put("order-2", Order { item: "memory".to_string(), amount: 250 }).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        let status = cached.put("order-3", Order { item: "cpu".to_string(), amount: 400 }).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let mut total_amount = 0;
+        let mut total_orders = 0;
+        cached.for_each(|_key, stored_value| {
+            total_amount += stored_value.value_ref().amount;
+            total_orders += 1;
+        });
+
+        assert_eq!(750, total_amount);
+        assert_eq!(3, total_orders);
+    }
+
+    #[tokio::test]
+    async fn for_each_does_not_record_hits() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let _ = cached.put("order-1", Order { item: "disk".to_string(), amount: 100 }).unwrap().handle().await;
+        cached.for_each(|_key, stored_value| assert_eq!("disk", stored_value.value_ref().item));
+
+        assert_eq!(0, cached.stats_summary().get(&StatsType::CacheHits).unwrap());
+    }
+
+    #[tokio::test]
+    async fn iterate_the_keys_by_frequency() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).access_drain(AccessDrain::Sync).build());
+
+        for key in ["topic", "disk", "cache", "memory"] {
+            let status = cached.put(key, Order { item: key.to_string(), amount: 100 }).unwrap().handle().await;
+            assert_eq!(CommandStatus::Accepted, status);
+        }
+        for _ in 0..5 {
+            let _ = cached.get_ref(&"memory");
+        }
+        for _ in 0..3 {
+            let _ = cached.get_ref(&"disk");
+        }
+
+        let keys = cached.iter_by_frequency().collect::<Vec<_>>();
+        assert_eq!(4, keys.len());
+        assert_eq!(("memory", 5), keys[0]);
+        assert_eq!(("disk", 3), keys[1]);
+        assert!(keys[2..].iter().all(|(key, frequency)| (*key == "topic" || *key == "cache") && *frequency == 0));
+        assert_eq!(8, cached.stats_summary().get(&StatsType::CacheHits).unwrap());
+    }
+
+    #[tokio::test]
+    async fn for_each_after_shutdown() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let _ = cached.put("order-1", Order { item: "disk".to_string(), amount: 100 }).unwrap().handle().await;
+        cached.shutdown();
+
+        let mut total_orders = 0;
+        cached.for_each(|_key, _stored_value| total_orders += 1);
+        assert_eq!(0, total_orders);
+    }
+}
+
+#[cfg(test)]
+mod put_or_update_tests {
+    use std::ops::Add;
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::{ClockType, ManualClock};
+    use crate::cache::config::ConfigBuilder;
+    use crate::cache::config::weight_calculation::Calculation;
+    use crate::cache::put_or_update::PutOrUpdateRequestBuilder;
+    use crate::cache::types::Weight;
+
+    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str> {
+        ConfigBuilder::new(100, 10, 100)
+    }
+
+    #[tokio::test]
+    async fn put_or_update_a_non_existing_key_value() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+
+        assert_eq!("microservices", stored_value.value());
+    }
+
+    #[tokio::test]
+    async fn put_or_update_a_non_existing_key_value_with_weight() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").weight(33).build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!("microservices", stored_value.value());
+        assert_eq!(Some(33), cached.admission_policy.weight_of(&key_id));
+    }
+
+    #[tokio::test]
+    async fn put_or_update_a_non_existing_key_value_with_time_to_live() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").weight(10).time_to_live(Duration::from_secs(10)).build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!(Some(clock.now().add(Duration::from_secs(10))), stored_value.expire_after());
+        assert_eq!("microservices", stored_value.value());
+        assert_eq!(Some(34), cached.admission_policy.weight_of(&key_id));
+    }
+
+    #[tokio::test]
+    async fn update_the_value_of_an_existing_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("storage engine").build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+
+        assert_eq!("storage engine", stored_value.value());
+    }
+
+    #[tokio::test]
+    async fn update_the_weight_of_an_existing_key() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").weight(29).build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!("microservices", stored_value.value());
+        assert_eq!(Some(29), cached.admission_policy.weight_of(&key_id));
+    }
+
+    #[tokio::test]
+    async fn update_the_time_to_live_of_an_existing_key_with_original_key_not_having_time_to_live() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        assert_eq!(Some(64), weight_of(&cached, "topic"));
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").time_to_live(Duration::from_secs(100)).build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!("microservices", stored_value.value());
+        assert_eq!(Some(88), cached.admission_policy.weight_of(&key_id));
+
+        assert_eq!(Some(clock.now().add(Duration::from_secs(100))), stored_value.expire_after());
+        assert_eq!(stored_value.expire_after(), cached.ttl_ticker.get(&key_id, &stored_value.expire_after().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn remove_the_time_to_live_of_an_existing_key() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(100)).unwrap();
+        acknowledgement.handle().await;
+
+        assert_eq!(Some(88), weight_of(&cached, "topic"));
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").remove_time_to_live().build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!("microservices", stored_value.value());
+        assert_eq!(Some(64), cached.admission_policy.weight_of(&key_id));
+
+        assert_eq!(None, stored_value.expire_after());
+    }
+
+    #[tokio::test]
+    async fn add_the_time_to_live_of_an_existing_key() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        assert_eq!(Some(64), weight_of(&cached, "topic"));
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").time_to_live(Duration::from_secs(120)).build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!("microservices", stored_value.value());
+        assert_eq!(Some(88), cached.admission_policy.weight_of(&key_id));
+
+        assert_eq!(Some(clock.now().add(Duration::from_secs(120))), stored_value.expire_after());
+        assert_eq!(stored_value.expire_after(), cached.ttl_ticker.get(&key_id, &stored_value.expire_after().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn update_the_value_and_time_to_live_of_an_existing_key() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+
+        let acknowledgement =
+            cached.put("topic", "microservices").unwrap();
+        acknowledgement.handle().await;
+
+        assert_eq!(Some(64), weight_of(&cached, "topic"));
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("storage engine").time_to_live(Duration::from_secs(100)).build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        assert_eq!("storage engine", stored_value.value());
+        assert_eq!(Some(88), cached.admission_policy.weight_of(&key_id));
+
+        assert_eq!(Some(clock.now().add(Duration::from_secs(100))), stored_value.expire_after());
+        assert_eq!(stored_value.expire_after(), cached.ttl_ticker.get(&key_id, &stored_value.expire_after().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn update_the_value_and_remove_time_to_live_of_an_existing_key() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(100)).unwrap();
+        acknowledgement.handle().await;
+
+        let original_weight = weight_of(&cached, "topic");
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("storage engine").remove_time_to_live().build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        let new_weight = cached.admission_policy.weight_of(&key_id);
+        assert_eq!("storage engine", stored_value.value());
+        assert_ne!(original_weight, new_weight);
+        assert!(new_weight < original_weight);
+
+        assert_eq!(None, stored_value.expire_after());
+    }
+
+    #[tokio::test]
+    async fn update_the_value_weight_and_remove_time_to_live_of_an_existing_key() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(100)).unwrap();
+        acknowledgement.handle().await;
+
+        let original_weight = weight_of(&cached, "topic");
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("storage engine").weight(300).remove_time_to_live().build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        let new_weight = cached.admission_policy.weight_of(&key_id);
+        assert_eq!("storage engine", stored_value.value());
+        assert_ne!(original_weight, new_weight);
+        assert_eq!(Some(300), new_weight);
+
+        assert_eq!(None, stored_value.expire_after());
+    }
+
+    #[tokio::test]
+    async fn update_the_time_to_live_of_an_existing_key() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+
+        let acknowledgement =
+            cached.put_with_ttl("topic", "microservices", Duration::from_secs(100)).unwrap();
+        acknowledgement.handle().await;
+
+        let original_weight = weight_of(&cached, "topic");
+
+        let acknowledgement =
+            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").time_to_live(Duration::from_secs(500)).build()).unwrap();
+        acknowledgement.handle().await;
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        let new_weight = cached.admission_policy.weight_of(&key_id);
+        assert_eq!("microservices", stored_value.value());
+        assert_eq!(original_weight, new_weight);
+    }
+
+    #[tokio::test]
+    async fn add_and_remove_the_time_to_live_of_an_existing_key_restores_the_weight() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put("topic", "microservices").unwrap().handle().await;
+        let original_weight = weight_of(&cached, "topic");
+
+        cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").time_to_live(Duration::from_secs(100)).build()).unwrap().handle().await;
+        assert_eq!(Some(88), weight_of(&cached, "topic"));
+
+        cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").remove_time_to_live().build()).unwrap().handle().await;
+        assert_eq!(original_weight, weight_of(&cached, "topic"));
+    }
+
+    #[tokio::test]
+    async fn add_and_remove_the_time_to_live_of_an_existing_key_with_client_provided_weight_restores_the_weight() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put_with_weight("topic", "microservices", 10).unwrap().handle().await;
+        assert_eq!(Some(10), weight_of(&cached, "topic"));
+
+        cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").time_to_live(Duration::from_secs(100)).build()).unwrap().handle().await;
+        assert_eq!(Some(34), weight_of(&cached, "topic"));
+
+        cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").remove_time_to_live().build()).unwrap().handle().await;
+        assert_eq!(Some(10), weight_of(&cached, "topic"));
+    }
+
+    #[tokio::test]
+    async fn remove_the_time_to_live_of_an_existing_key_put_with_client_provided_weight_and_time_to_live() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put_with_weight_and_ttl("topic", "microservices", 10, Duration::from_secs(100)).unwrap().handle().await;
+        assert_eq!(Some(34), weight_of(&cached, "topic"));
+
+        cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").remove_time_to_live().build()).unwrap().handle().await;
+        assert_eq!(Some(10), weight_of(&cached, "topic"));
+    }
+
+    #[tokio::test]
+    async fn update_the_value_of_an_existing_key_with_time_to_live_retains_the_time_to_live_weight() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        cached.put_with_ttl("topic", "microservices", Duration::from_secs(100)).unwrap().handle().await;
+        assert_eq!(Some(88), weight_of(&cached, "topic"));
+
+        cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("cache").build()).unwrap().handle().await;
+        assert_eq!(Some(88), weight_of(&cached, "topic"));
+
+        cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").weight(10).build()).unwrap().handle().await;
+        assert_eq!(Some(34), weight_of(&cached, "topic"));
+    }
+
+    #[tokio::test]
+    async fn put_or_update_a_non_existing_key_with_time_to_live_weighs_like_put_with_ttl() {
+        let cached = CacheD::new(value_length_weigher_config_builder().build());
+        let put_or_updated = CacheD::new(value_length_weigher_config_builder().build());
+
+        cached.put_with_ttl("topic", "microservices", Duration::from_secs(100)).unwrap().handle().await;
+        put_or_updated.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").time_to_live(Duration::from_secs(100)).build()).unwrap().handle().await;
+
+        assert_eq!(Some(13 + Calculation::time_to_live_weight(true)), weight_of(&cached, "topic"));
+        assert_eq!(weight_of(&cached, "topic"), weight_of(&put_or_updated, "topic"));
+    }
+
+    #[tokio::test]
+    async fn update_the_value_and_add_the_time_to_live_of_an_existing_key_weighs_like_put_with_ttl() {
+        let cached = CacheD::new(value_length_weigher_config_builder().build());
+        let put_or_updated = CacheD::new(value_length_weigher_config_builder().build());
+
+        cached.put_with_ttl("topic", "cached", Duration::from_secs(100)).unwrap().handle().await;
+        put_or_updated.put("topic", "microservices").unwrap().handle().await;
+        assert_eq!(Some(13), weight_of(&put_or_updated, "topic"));
+
+        put_or_updated.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("cached").time_to_live(Duration::from_secs(100)).build()).unwrap().handle().await;
+        assert_eq!(Some(6 + Calculation::time_to_live_weight(true)), weight_of(&put_or_updated, "topic"));
+        assert_eq!(weight_of(&cached, "topic"), weight_of(&put_or_updated, "topic"));
+    }
+
+    fn value_length_weigher_config_builder() -> ConfigBuilder<&'static str, &'static str> {
+        ConfigBuilder::new(100, 10, 1000).weight_calculation_fn(Box::new(|_key, value: &&str, is_time_to_live_specified| {
+            value.len() as Weight + Calculation::time_to_live_weight(is_time_to_live_specified)
+        }))
+    }
+
+    fn weight_of(cached: &CacheD<&str, &str>, key: &'static str) -> Option<Weight> {
+        let value = cached.get_ref(&key);
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        let key_id = stored_value.key_id();
+
+        cached.admission_policy.weight_of(&key_id)
+    }
+}
+#[cfg(test)]
+mod inline_execution_tests {
+    use std::time::Duration;
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::config::ConfigBuilder;
+    use crate::cache::put_or_update::PutOrUpdateRequestBuilder;
+
+    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str> {
+        ConfigBuilder::new(100, 10, 100).inline_execution()
+    }
+
+    #[test]
+    fn put_a_key_value_and_get_it_without_awaiting() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put("topic", "microservices").unwrap();
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[test]
+    fn put_a_key_value_with_ttl_and_get_it_without_awaiting() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(300)).unwrap();
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[test]
+    fn put_or_update_a_key_value_and_get_it_without_awaiting() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put("topic", "microservices").unwrap();
+        let _ = cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("cache").weight(20).build()).unwrap();
+
+        assert_eq!(Some("cache"), cached.get(&"topic"));
+        assert_eq!(20, cached.total_weight_used());
+    }
+
+    #[test]
+    fn delete_a_key_and_get_it_without_awaiting() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put("topic", "microservices").unwrap();
+        let _ = cached.delete("topic").unwrap();
+
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(0, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn acknowledgement_is_already_done() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let status = cached.put_with_weight("topic", "microservices", 200).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyWeightIsGreaterThanCacheWeight), status);
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[test]
+    fn put_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.shutdown();
+
+        let put_result = cached.put("topic", "microservices");
+        assert!(put_result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod put_mode_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use parking_lot::Mutex;
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::config::{ConfigBuilder, EvictionReason, PutMode};
+    use crate::cache::put_or_update::PutOrUpdateRequestBuilder;
+
+    fn test_config_builder(put_mode: PutMode) -> ConfigBuilder<&'static str, &'static str> {
+        ConfigBuilder::new(100, 10, 100).put_mode(put_mode)
+    }
+
+    #[tokio::test]
+    async fn insert_only_puts_an_absent_key() {
+        let cached = CacheD::new(test_config_builder(PutMode::InsertOnly).build());
+
+        let status = cached.put("topic", "microservices").unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn insert_only_rejects_a_present_key() {
+        let cached = CacheD::new(test_config_builder(PutMode::InsertOnly).build());
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let status = cached.put_with_weight("topic", "cache", 10).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn overwrite_a_key_in_a_full_cache_without_evicting_the_other_keys() {
+        let removals = Arc::new(Mutex::new(Vec::new()));
+        let cached = CacheD::new(
+            test_config_builder(PutMode::Overwrite)
+                .eviction_listener(Box::new({
+                    let removals = removals.clone();
+                    move |key, _value, reason| removals.lock().push((*key, reason))
+                }))
+                .build()
+        );
+        let _ = cached.put_with_weight("topic", "microservices", 40).unwrap().handle().await;
+        let _ = cached.put_with_weight("disk", "SSD", 30).unwrap().handle().await;
+        let _ = cached.put_with_weight("cpu", "8 cores", 30).unwrap().handle().await;
+        cached.prime_frequency(&"topic", 5);
+        cached.prime_frequency(&"disk", 5);
+
+        let status = cached.put_with_weight("topic", "cache", 40).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(vec![("topic", EvictionReason::Replaced)], *removals.lock());
+        assert_eq!(Some("cache"), cached.get(&"topic"));
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
+        assert_eq!(Some("8 cores"), cached.get(&"cpu"));
+        assert_eq!(100, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn overwrite_puts_an_absent_key() {
+        let cached = CacheD::new(test_config_builder(PutMode::Overwrite).build());
+
+        let status = cached.put("topic", "microservices").unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn overwrite_replaces_a_present_key() {
+        let cached = CacheD::new(test_config_builder(PutMode::Overwrite).build());
+
+        let _ = cached.put_with_weight("topic", "microservices", 20).unwrap().handle().await;
+        let status = cached.put_with_weight("topic", "cache", 10).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("cache"), cached.get(&"topic"));
+        assert_eq!(10, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn overwrite_replaces_a_present_key_with_ttl() {
+        let cached = CacheD::new(test_config_builder(PutMode::Overwrite).build());
+
+        let _ = cached.put_with_weight_and_ttl("topic", "microservices", 20, Duration::from_secs(300)).unwrap().handle().await;
+        let status = cached.put_with_weight("topic", "cache", 10).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let value = cached.get_ref(&"topic");
+        let value_ref = value.unwrap();
+        let stored_value = value_ref.value();
+        assert_eq!(&"cache", stored_value.value_ref());
+        assert_eq!(None, stored_value.expire_after());
+    }
+
+    #[tokio::test]
+    async fn overwrite_returns_the_previous_value() {
+        let cached = CacheD::new(test_config_builder(PutMode::Overwrite).build());
+
+        let (status, previous) = cached.put_with_weight("topic", "microservices", 20).unwrap().handle_with_previous::<&str>().await;
+        assert_eq!((CommandStatus::Accepted, None), (status, previous));
+
+        let (status, previous) = cached.put_with_weight("topic", "cache", 10).unwrap().handle_with_previous::<&str>().await;
+        assert_eq!((CommandStatus::Accepted, Some("microservices")), (status, previous));
+        assert_eq!(Some("cache"), cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn insert_only_does_not_return_the_previous_value_of_a_rejected_put() {
+        let cached = CacheD::new(test_config_builder(PutMode::InsertOnly).build());
+
+        let _ = cached.put_with_weight("topic", "microservices", 20).unwrap().handle().await;
+        let (status, previous) = cached.put_with_weight("topic", "cache", 10).unwrap().handle_with_previous::<&str>().await;
+
+        assert_eq!((CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), None), (status, previous));
+    }
+
+    #[tokio::test]
+    async fn update_only_rejects_an_absent_key() {
+        let cached = CacheD::new(test_config_builder(PutMode::UpdateOnly).build());
+
+        let status = cached.put("topic", "microservices").unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyDoesNotExist), status);
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn update_only_replaces_a_present_key() {
+        let cached = CacheD::new(test_config_builder(PutMode::UpdateOnly).build());
+
+        let _ = cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").weight(20).build()).unwrap().handle().await;
+        let status = cached.put_with_weight("topic", "cache", 10).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("cache"), cached.get(&"topic"));
+        assert_eq!(10, cached.total_weight_used());
+    }
+}
+
+#[cfg(test)]
+mod prefer_evicting_ttl_entries_tests {
+    use std::time::Duration;
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::config::ConfigBuilder;
+
+    #[test]
+    fn evicts_the_key_with_the_soonest_expiry_amongst_the_keys_with_the_same_frequency() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 264).prefer_evicting_ttl_entries(true).inline_execution().build());
+
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(300)).unwrap();
+        let _ = cached.put_with_ttl("disk", "SSD", Duration::from_secs(10)).unwrap();
+        let _ = cached.put_with_ttl("cache", "cached", Duration::from_secs(100)).unwrap();
+        assert_eq!(264, cached.total_weight_used());
+
+        let _ = cached.put_with_ttl("memory", "RAM", Duration::from_secs(500)).unwrap();
+
+        assert_eq!(None, cached.get(&"disk"));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(Some("cached"), cached.get(&"cache"));
+        assert_eq!(Some("RAM"), cached.get(&"memory"));
+    }
+
+    #[test]
+    fn evicts_the_key_with_expiry_before_the_key_without_expiry() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 152).prefer_evicting_ttl_entries(true).inline_execution().build());
+
+        let _ = cached.put("topic", "microservices").unwrap();
+        let _ = cached.put_with_ttl("disk", "SSD", Duration::from_secs(300)).unwrap();
+        assert_eq!(152, cached.total_weight_used());
+
+        let _ = cached.put("cache", "cached").unwrap();
+
+        assert_eq!(None, cached.get(&"disk"));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(Some("cached"), cached.get(&"cache"));
+    }
+}
+
+#[cfg(test)]
+mod compare_and_swap_tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::cached::compare_and_swap_tests::setup::KeyPrefixHasher;
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::compare_and_swap::CompareAndSwap;
+    use crate::cache::config::ConfigBuilder;
+    use crate::cache::config::error::WeightError;
+
+    mod setup {
+        use std::hash::Hasher;
+
+        /// Places all the keys starting with "order" in the first shard, and all the other keys in the last shard.
+        pub(crate) struct KeyPrefixHasher {
+            first_byte: Option<u8>,
+        }
+
+        impl KeyPrefixHasher {
+            pub(crate) fn new() -> Self {
+                KeyPrefixHasher { first_byte: None }
+            }
+        }
+
+        impl Hasher for KeyPrefixHasher {
+            fn finish(&self) -> u64 {
+                if self.first_byte == Some(b'o') { 0 } else { u64::MAX }
+            }
+
+            fn write(&mut self, bytes: &[u8]) {
+                if self.first_byte.is_none() {
+                    self.first_byte = bytes.first().copied();
+                }
+            }
+        }
+    }
+
+    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str> {
+        let sketch_hash = |key: &&str| {
+            let mut hasher = DefaultHasher::new();
+            key.hash(&mut hasher);
+            hasher.finish()
+        };
+        ConfigBuilder::new(100, 10, 200).dual_hash(Box::new(|| Box::new(KeyPrefixHasher::new())), Box::new(sketch_hash))
+    }
+
+    #[tokio::test]
+    async fn two_key_swap_fully_applies() {
+        let cached = CacheD::new(test_config_builder().build());
+        let _ = cached.put("order:1:primary", "pending").unwrap().handle().await;
+        let _ = cached.put("order:1:replica", "shipped").unwrap().handle().await;
+
+        let status = cached.compare_and_swap_batch(vec![
+            CompareAndSwap::new("order:1:primary", "pending", "shipped"),
+            CompareAndSwap::new("order:1:replica", "shipped", "pending"),
+        ]).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("shipped"), cached.get(&"order:1:primary"));
+        assert_eq!(Some("pending"), cached.get(&"order:1:replica"));
+    }
+
+    #[tokio::test]
+    async fn two_key_swap_fully_fails() {
+        let cached = CacheD::new(test_config_builder().build());
+        let _ = cached.put("order:1:primary", "pending").unwrap().handle().await;
+        let _ = cached.put("order:1:replica", "delivered").unwrap().handle().await;
+
+        let status = cached.compare_and_swap_batch(vec![
+            CompareAndSwap::new("order:1:primary", "pending", "shipped"),
+            CompareAndSwap::new("order:1:replica", "shipped", "pending"),
+        ]).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Rejected(RejectionReason::PreconditionFailed), status);
+        assert_eq!(Some("pending"), cached.get(&"order:1:primary"));
+        assert_eq!(Some("delivered"), cached.get(&"order:1:replica"));
+    }
+
+    #[tokio::test]
+    async fn swap_fails_for_a_non_existing_key() {
+        let cached = CacheD::new(test_config_builder().build());
+        let _ = cached.put("order:1:primary", "pending").unwrap().handle().await;
+
+        let status = cached.compare_and_swap_batch(vec![
+            CompareAndSwap::new("order:1:primary", "pending", "shipped"),
+            CompareAndSwap::new("order:1:replica", "shipped", "pending"),
+        ]).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Rejected(RejectionReason::PreconditionFailed), status);
+        assert_eq!(Some("pending"), cached.get(&"order:1:primary"));
+        assert_eq!(None, cached.get(&"order:1:replica"));
+    }
+
+    #[tokio::test]
+    async fn swap_rejects_keys_in_different_shards() {
+        let cached = CacheD::new(test_config_builder().build());
+        let _ = cached.put("order:1", "pending").unwrap().handle().await;
+        let _ = cached.put("payment:1", "initiated").unwrap().handle().await;
+
+        let status = cached.compare_and_swap_batch(vec![
+            CompareAndSwap::new("order:1", "pending", "shipped"),
+            CompareAndSwap::new("payment:1", "initiated", "completed"),
+        ]).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeysInDifferentShards), status);
+        assert_eq!(Some("pending"), cached.get(&"order:1"));
+        assert_eq!(Some("initiated"), cached.get(&"payment:1"));
+    }
+
+    #[tokio::test]
+    async fn swap_updates_the_weight() {
+        let cached = CacheD::new(test_config_builder().weight_calculation_fn(Box::new(|_key, value, _| value.len() as i64)).build());
+        let _ = cached.put("order:1", "pending").unwrap().handle().await;
+
+        let status = cached.compare_and_swap_batch(vec![
+            CompareAndSwap::new("order:1", "pending", "delivered"),
+        ]).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(9, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn swap_updates_the_weights_of_all_the_keys_on_a_single_acknowledgement() {
+        let cached = CacheD::new(test_config_builder().weight_calculation_fn(Box::new(|_key, value, _| value.len() as i64)).build());
+        let _ = cached.put("order:1:primary", "pending").unwrap().handle().await;
+        let _ = cached.put("order:1:replica", "shipped").unwrap().handle().await;
+
+        let status = cached.compare_and_swap_batch(vec![
+            CompareAndSwap::new("order:1:primary", "pending", "delivered"),
+            CompareAndSwap::new("order:1:replica", "shipped", "returned-to-sender"),
+        ]).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(9 + 18, cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn swap_rejects_duplicate_keys() {
+        let cached = CacheD::new(test_config_builder().build());
+        let _ = cached.put("order:1", "pending").unwrap().handle().await;
+
+        let status = cached.compare_and_swap_batch(vec![
+            CompareAndSwap::new("order:1", "pending", "shipped"),
+            CompareAndSwap::new("order:1", "shipped", "delivered"),
+        ]).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Rejected(RejectionReason::DuplicateKeys), status);
+        assert_eq!(Some("pending"), cached.get(&"order:1"));
+    }
+
+    #[tokio::test]
+    async fn swap_does_not_apply_if_the_weight_can_not_be_calculated() {
+        let cached = CacheD::new(test_config_builder().fallible_weight_calculation_fn(Box::new(|_key, value, _| {
+            if value.len() > 8 { Err(WeightError::new("value is too large")) } else { Ok(value.len() as i64) }
+        })).build());
+        let _ = cached.put("order:1:primary", "pending").unwrap().handle().await;
+        let _ = cached.put("order:1:replica", "shipped").unwrap().handle().await;
+
+        let result = cached.compare_and_swap_batch(vec![
+            CompareAndSwap::new("order:1:primary", "pending", "shipped"),
+            CompareAndSwap::new("order:1:replica", "shipped", "delivered"),
+        ]);
+
+        assert!(result.is_err());
+        assert_eq!(Some("pending"), cached.get(&"order:1:primary"));
+        assert_eq!(Some("shipped"), cached.get(&"order:1:replica"));
+    }
+
+    #[test]
+    fn swap_after_shutdown() {
+        let cached = CacheD::new(test_config_builder().build());
+        cached.shutdown();
+
+        let result = cached.compare_and_swap_batch(vec![
+            CompareAndSwap::new("order:1", "pending", "shipped"),
+        ]);
+
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod log_capture {
+    use std::sync::{Mutex, Once};
+
+    use log::{LevelFilter, Log, Metadata, Record};
+
+    /// Captures the log records along with their targets, the logger is installed once for all the tests.
+    struct CapturingLogger {
+        records: Mutex<Vec<(String, String)>>,
+    }
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                self.records.lock().unwrap().push((record.target().to_string(), record.args().to_string()));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger { records: Mutex::new(Vec::new()) };
+    static INSTALL: Once = Once::new();
+
+    pub(crate) fn install_logger() {
+        INSTALL.call_once(|| {
+            log::set_logger(&LOGGER).unwrap();
+            log::set_max_level(LevelFilter::Info);
+        });
+    }
+
+    pub(crate) fn records(target: &str) -> Vec<String> {
+        LOGGER.records.lock().unwrap().iter().filter(|(record_target, _)| record_target == target).map(|(_, record)| record.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod audit_tests {
+    use crate::cache::cached::CacheD;
+    use crate::cache::cached::log_capture::{install_logger as install_audit_logger, records};
+    use crate::cache::command::CommandStatus;
+    use crate::cache::config::{AUDIT_LOG_TARGET, ConfigBuilder, PutMode};
+
+    fn audit_records(key: &str) -> Vec<String> {
+        let prefix = format!("key {} with id", key);
+        records(AUDIT_LOG_TARGET).into_iter().filter(|record| record.starts_with(&prefix)).collect()
+    }
+
+    #[tokio::test]
+    async fn audit_a_forced_eviction() {
+        install_audit_logger();
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).audit_evictions(true).build());
+
+        let status = cached.put_with_weight("audit:evicted", "microservices", 60).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let status = cached.put_with_weight("audit:incoming", "cached", 60).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(None, cached.get(&"audit:evicted"));
+
+        let records = audit_records("audit:evicted");
+        assert_eq!(1, records.len());
+        assert!(records[0].ends_with("evicted, weight reclaimed 60"));
+    }
+
+    #[tokio::test]
+    async fn audit_a_delete() {
+        install_audit_logger();
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).audit_evictions(true).build());
+
+        let _ = cached.put_with_weight("audit:deleted", "microservices", 40).unwrap().handle().await;
+        let status = cached.delete("audit:deleted").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let records = audit_records("audit:deleted");
+        assert_eq!(1, records.len());
+        assert!(records[0].ends_with("deleted, weight reclaimed 40"));
+    }
+
+    #[tokio::test]
+    async fn audit_a_replacement() {
+        install_audit_logger();
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).put_mode(PutMode::Overwrite).audit_evictions(true).build());
+
+        let _ = cached.put_with_weight("audit:replaced", "microservices", 40).unwrap().handle().await;
+        let status = cached.put_with_weight("audit:replaced", "cached", 30).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let records = audit_records("audit:replaced");
+        assert_eq!(1, records.len());
+        assert!(records[0].ends_with("replaced, weight reclaimed 40"));
+    }
+
+    #[tokio::test]
+    async fn do_not_audit_by_default() {
+        install_audit_logger();
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+
+        let _ = cached.put_with_weight("audit:disabled", "microservices", 60).unwrap().handle().await;
+        let _ = cached.put_with_weight("audit:disabled:incoming", "cached", 60).unwrap().handle().await;
+        assert_eq!(None, cached.get(&"audit:disabled"));
+
+        assert!(audit_records("audit:disabled").is_empty());
+    }
+}
+
+#[cfg(test)]
+mod ttl_resolution_tests {
+    use std::time::Duration;
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::cached::log_capture::{install_logger, records};
+    use crate::cache::config::ConfigBuilder;
+
+    #[tokio::test]
+    async fn ttl_resolution() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 100).ttl_tick_duration(Duration::from_millis(50)).build());
+        assert_eq!(Duration::from_millis(50), cached.ttl_resolution());
+    }
+
+    #[tokio::test]
+    async fn warn_on_a_put_with_time_to_live_below_the_ttl_resolution() {
+        install_logger();
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).ttl_tick_duration(Duration::from_millis(50)).build());
+
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_millis(7)).unwrap().handle().await;
+        let _ = cached.put_with_weight_and_ttl("disk", "SSD", 10, Duration::from_millis(9)).unwrap().handle().await;
+
+        let warnings = records(module_path!().trim_end_matches("::ttl_resolution_tests"));
+        assert!(warnings.iter().any(|record| record.starts_with("time_to_live 7ms is less than the ttl resolution 50ms")));
+        assert!(warnings.iter().any(|record| record.starts_with("time_to_live 9ms is less than the ttl resolution 50ms")));
+    }
+
+    #[tokio::test]
+    async fn does_not_warn_on_a_put_with_time_to_live_above_the_ttl_resolution() {
+        install_logger();
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).ttl_tick_duration(Duration::from_millis(50)).build());
+
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_millis(1013)).unwrap().handle().await;
+
+        let warnings = records(module_path!().trim_end_matches("::ttl_resolution_tests"));
+        assert!(!warnings.iter().any(|record| record.starts_with("time_to_live 1.013s")));
+    }
+}
+
+#[cfg(test)]
+mod transfer_tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::ManualClock;
+    use crate::cache::config::{ConfigBuilder, PutMode};
+
+    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str> {
+        ConfigBuilder::new(100, 10, 500).clock(Box::new(ManualClock::new(SystemTime::UNIX_EPOCH)))
+    }
+
+    fn expire_after(cached: &CacheD<&'static str, &'static str>, key: &'static str) -> Option<SystemTime> {
+        cached.map_get_ref(&key, |stored_value| stored_value.expire_after()).flatten()
+    }
+
+    #[tokio::test]
+    async fn transfer_a_subset() {
+        let cached = CacheD::new(test_config_builder().build());
+        let tenant_cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put_with_weight_and_ttl("tenant-1:topic", "microservices", 30, Duration::from_secs(300)).unwrap().handle().await;
+        let _ = cached.put_with_weight("tenant-1:disk", "SSD", 20).unwrap().handle().await;
+        let _ = cached.put_with_weight("tenant-2:topic", "cached", 40).unwrap().handle().await;
+        let expire_after_before_transfer = expire_after(&cached, "tenant-1:topic");
+        let weight_before_transfer = cached.total_weight_used();
+
+        let transferred = cached.transfer_to(&tenant_cached, |key| key.starts_with("tenant-1")).await;
+        assert_eq!(2, transferred);
+
+        assert_eq!(None, cached.get(&"tenant-1:topic"));
+        assert_eq!(None, cached.get(&"tenant-1:disk"));
+        assert_eq!(Some("cached"), cached.get(&"tenant-2:topic"));
+
+        assert_eq!(Some("microservices"), tenant_cached.get(&"tenant-1:topic"));
+        assert_eq!(Some("SSD"), tenant_cached.get(&"tenant-1:disk"));
+        assert_eq!(None, tenant_cached.get(&"tenant-2:topic"));
+
+        assert!(expire_after_before_transfer.is_some());
+        assert_eq!(expire_after_before_transfer, expire_after(&tenant_cached, "tenant-1:topic"));
+        assert_eq!(None, expire_after(&tenant_cached, "tenant-1:disk"));
+
+        assert_eq!(40, cached.total_weight_used());
+        assert_eq!(weight_before_transfer - 40, tenant_cached.total_weight_used());
+    }
+
+    #[tokio::test]
+    async fn leave_a_rejected_entry_in_the_source() {
+        let cached = CacheD::new(test_config_builder().build());
+        let tenant_cached = CacheD::new(ConfigBuilder::new(100, 10, 50).clock(Box::new(ManualClock::new(SystemTime::UNIX_EPOCH))).build());
+
+        let _ = cached.put_with_weight("tenant-1:topic", "microservices", 30).unwrap().handle().await;
+        let _ = cached.put_with_weight("tenant-1:disk", "SSD", 60).unwrap().handle().await;
+
+        let transferred = cached.transfer_to(&tenant_cached, |key| key.starts_with("tenant-1")).await;
+        assert_eq!(1, transferred);
+
+        assert_eq!(None, cached.get(&"tenant-1:topic"));
+        assert_eq!(Some("microservices"), tenant_cached.get(&"tenant-1:topic"));
+
+        assert_eq!(Some("SSD"), cached.get(&"tenant-1:disk"));
+        assert_eq!(None, tenant_cached.get(&"tenant-1:disk"));
+    }
+
+    #[tokio::test]
+    async fn leave_an_entry_with_time_to_live_in_the_source_if_the_target_has_no_ttl() {
+        let cached = CacheD::new(test_config_builder().build());
+        let tenant_cached = CacheD::new(test_config_builder().no_ttl().build());
+
+        let _ = cached.put_with_ttl("tenant-1:topic", "microservices", Duration::from_secs(300)).unwrap().handle().await;
+        let _ = cached.put("tenant-1:disk", "SSD").unwrap().handle().await;
+
+        let transferred = cached.transfer_to(&tenant_cached, |key| key.starts_with("tenant-1")).await;
+        assert_eq!(1, transferred);
+
+        assert_eq!(Some("microservices"), cached.get(&"tenant-1:topic"));
+        assert_eq!(None, tenant_cached.get(&"tenant-1:topic"));
+
+        assert_eq!(None, cached.get(&"tenant-1:disk"));
+        assert_eq!(Some("SSD"), tenant_cached.get(&"tenant-1:disk"));
+    }
+
+    #[tokio::test]
+    async fn leave_an_entry_in_the_source_if_the_key_exists_in_the_target() {
+        let cached = CacheD::new(test_config_builder().build());
+        let tenant_cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put("tenant-1:topic", "microservices").unwrap().handle().await;
+        let _ = tenant_cached.put("tenant-1:topic", "cached").unwrap().handle().await;
+
+        let transferred = cached.transfer_to(&tenant_cached, |key| key.starts_with("tenant-1")).await;
+        assert_eq!(0, transferred);
+
+        assert_eq!(Some("microservices"), cached.get(&"tenant-1:topic"));
+        assert_eq!(Some("cached"), tenant_cached.get(&"tenant-1:topic"));
+    }
+
+    #[tokio::test]
+    async fn retain_an_entry_put_again_in_the_source_during_the_transfer() {
+        let cached = Arc::new(CacheD::new(test_config_builder().put_mode(PutMode::Overwrite).inline_execution().build()));
+        let _ = cached.put("tenant-1:topic", "microservices").unwrap();
+
+        let source = cached.clone();
+        let is_put_again = Arc::new(AtomicBool::new(false));
+        let tenant_cached = CacheD::new(test_config_builder().value_transform(
+            Box::new(move |value| {
+                if !is_put_again.swap(true, Ordering::AcqRel) {
+                    let _ = source.put("tenant-1:topic", "cached").unwrap();
+                }
+                value
+            }),
+            Box::new(|value| value),
+        ).build());
+
+        let transferred = cached.transfer_to(&tenant_cached, |key| key.starts_with("tenant-1")).await;
+        assert_eq!(1, transferred);
+
+        assert_eq!(Some("cached"), cached.get(&"tenant-1:topic"));
+        assert_eq!(Some("microservices"), tenant_cached.get(&"tenant-1:topic"));
+    }
+}
+
+#[cfg(test)]
+mod tombstone_tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::ManualClock;
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::config::{ConfigBuilder, PutMode};
+    use crate::cache::put_or_update::PutOrUpdateRequestBuilder;
+
+    fn test_config_builder(clock: &ManualClock) -> ConfigBuilder<&'static str, &'static str> {
+        ConfigBuilder::new(100, 10, 100)
+            .clock(Box::new(clock.clone()))
+            .delete_tombstone_ttl(Duration::from_secs(5))
+    }
+
+    #[tokio::test]
+    async fn reject_a_put_within_the_tombstone_window_and_accept_it_after_the_window_lapses() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(test_config_builder(&clock).build());
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let _ = cached.delete("topic").unwrap().handle().await;
+
+        let status = cached.put("topic", "stale").unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyRecentlyDeleted), status);
+        assert_eq!(None, cached.get(&"topic"));
+
+        clock.advance(Duration::from_secs(6));
+
+        let status = cached.put("topic", "cached").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("cached"), cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn reject_a_put_with_ttl_within_the_tombstone_window() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(test_config_builder(&clock).build());
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let _ = cached.delete("topic").unwrap().handle().await;
+
+        let status = cached.put_with_ttl("topic", "stale", Duration::from_secs(300)).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyRecentlyDeleted), status);
+    }
+
+    #[tokio::test]
+    async fn reject_a_put_or_update_within_the_tombstone_window() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(test_config_builder(&clock).build());
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let _ = cached.delete("topic").unwrap().handle().await;
+
+        let status = cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("stale").build()).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyRecentlyDeleted), status);
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn delete_of_a_non_existing_key_leaves_a_tombstone() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(test_config_builder(&clock).build());
+
+        let _ = cached.delete("topic").unwrap().handle().await;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.iterator.next().map(|optional_value| {
-            match optional_value {
-                None => None,
-                Some(value) => Some((self.map_fn)(value))
-            }
-        })
+        let status = cached.put("topic", "stale").unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyRecentlyDeleted), status);
     }
-}
 
+    #[tokio::test]
+    async fn tombstone_does_not_reject_other_keys() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(test_config_builder(&clock).put_mode(PutMode::Overwrite).build());
+
+        let _ = cached.delete("topic").unwrap().handle().await;
+
+        let status = cached.put("disk", "SSD").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+    }
+
+    #[tokio::test]
+    async fn accept_a_put_after_delete_without_tombstone() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let _ = cached.delete("topic").unwrap().handle().await;
+
+        let status = cached.put("topic", "cached").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+    }
+}
 
 #[cfg(test)]
-mod tests {
-    use std::sync::Arc;
-    use std::thread;
+mod value_transform_tests {
     use std::time::Duration;
 
     use crate::cache::cached::CacheD;
-    use crate::cache::command::{CommandStatus, RejectionReason};
-    use crate::cache::config::{ConfigBuilder, WeightCalculationFn};
-    use crate::cache::put_or_update::{PutOrUpdateRequest, PutOrUpdateRequestBuilder};
-    use crate::cache::stats::StatsType;
+    use crate::cache::command::CommandStatus;
+    use crate::cache::compare_and_swap::CompareAndSwap;
+    use crate::cache::config::ConfigBuilder;
+    use crate::cache::put_or_update::PutOrUpdateRequestBuilder;
 
-    #[derive(Eq, PartialEq, Debug)]
-    struct Name {
-        first: String,
-        last: String,
+    const MASK: u8 = 0b1010_1010;
+    const ENVELOPE: [u8; 2] = [0xEE, 0xEE];
+
+    fn xor(value: Vec<u8>) -> Vec<u8> {
+        value.into_iter().map(|byte| byte ^ MASK).collect()
     }
 
-    mod setup {
-        use std::time::SystemTime;
+    fn seal(value: Vec<u8>) -> Vec<u8> {
+        let mut sealed = ENVELOPE.to_vec();
+        sealed.extend(xor(value));
+        sealed
+    }
 
-        use crate::cache::clock::Clock;
+    fn open(value: Vec<u8>) -> Vec<u8> {
+        xor(value[ENVELOPE.len()..].to_vec())
+    }
 
-        #[derive(Clone)]
-        pub(crate) struct UnixEpochClock;
+    fn test_config_builder() -> ConfigBuilder<&'static str, Vec<u8>> {
+        ConfigBuilder::new(100, 10, 1000)
+            .weight_calculation_fn(Box::new(|_key, value: &Vec<u8>, _| value.len() as i64))
+            .value_transform(Box::new(seal), Box::new(open))
+    }
 
-        impl Clock for UnixEpochClock {
-            fn now(&self) -> SystemTime {
-                SystemTime::UNIX_EPOCH
-            }
-        }
+    #[tokio::test]
+    async fn round_trip_a_value() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let status = cached.put("topic", b"microservices".to_vec()).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        assert_eq!(Some(b"microservices".to_vec()), cached.get(&"topic"));
+        assert_eq!(Some((b"microservices".to_vec(), 15)), cached.get_weighted(&"topic"));
+        assert_eq!(&Some(b"microservices".to_vec()), cached.multi_get(vec![&"topic"]).get(&"topic").unwrap());
     }
 
-    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str> {
-        ConfigBuilder::new(100, 10, 100)
+    #[tokio::test]
+    async fn store_the_transformed_value() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put_with_ttl("topic", b"microservices".to_vec(), Duration::from_secs(300)).unwrap().handle().await;
+
+        let stored = cached.map_get_ref(&"topic", |stored_value| stored_value.value()).unwrap();
+        assert_eq!(seal(b"microservices".to_vec()), stored);
+        assert_ne!(b"microservices".to_vec(), stored);
     }
 
-    #[test]
-    #[should_panic]
-    fn shards_mut_be_power_of_2_and_greater_than_1() {
-        let _: CacheD<&str, &str> = CacheD::new(test_config_builder().shards(1).build());
+    #[tokio::test]
+    async fn calculate_the_weight_on_the_transformed_value() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put("topic", b"microservices".to_vec()).unwrap().handle().await;
+
+        assert_eq!(15, cached.total_weight_used());
     }
 
-    #[test]
-    #[should_panic]
-    fn weight_must_be_greater_than_zero_1() {
+    #[tokio::test]
+    async fn round_trip_a_value_put_with_weight() {
         let cached = CacheD::new(test_config_builder().build());
-        let _ =
-            cached.put_with_weight("topic", "microservices", 0).unwrap();
+
+        let _ = cached.put_with_weight("topic", b"microservices".to_vec(), 50).unwrap().handle().await;
+
+        assert_eq!(Some(b"microservices".to_vec()), cached.get(&"topic"));
     }
 
-    #[test]
-    #[should_panic]
-    fn weight_must_be_greater_than_zero_2() {
+    #[tokio::test]
+    async fn round_trip_an_updated_value() {
         let cached = CacheD::new(test_config_builder().build());
-        let _ =
-            cached.put_with_weight_and_ttl("topic", "microservices", 0, Duration::from_secs(5)).unwrap();
+
+        let _ = cached.put("topic", b"microservices".to_vec()).unwrap().handle().await;
+        let _ = cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value(b"cached".to_vec()).build()).unwrap().handle().await;
+
+        assert_eq!(Some(b"cached".to_vec()), cached.get(&"topic"));
     }
 
-    #[test]
-    #[should_panic]
-    fn weight_calculation_fn_must_return_weight_greater_than_zero_1() {
-        let weight_calculation: Box<WeightCalculationFn<&str, &str>> = Box::new(|_key, _value, _is_time_to_live_specified| 0);
-        let cached = CacheD::new(test_config_builder().weight_calculation_fn(weight_calculation).build());
-        let _ =
-            cached.put("topic", "microservices").unwrap();
+    #[tokio::test]
+    async fn compare_and_swap_a_transformed_value() {
+        let cached = CacheD::new(test_config_builder().build());
+
+        let _ = cached.put("topic", b"microservices".to_vec()).unwrap().handle().await;
+        let status = cached.compare_and_swap_batch(vec![
+            CompareAndSwap::new("topic", b"microservices".to_vec(), b"cached".to_vec())
+        ]).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some(b"cached".to_vec()), cached.get(&"topic"));
     }
+}
 
-    #[test]
-    #[should_panic]
-    fn weight_calculation_fn_must_return_weight_greater_than_zero_2() {
-        let weight_calculation: Box<WeightCalculationFn<&str, &str>> = Box::new(|_key, _value, _is_time_to_live_specified| 0);
-        let cached = CacheD::new(test_config_builder().weight_calculation_fn(weight_calculation).build());
-        let _ =
-            cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).unwrap();
+#[cfg(test)]
+mod soft_capacity_tests {
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::config::ConfigBuilder;
+
+    #[tokio::test]
+    async fn put_optional_below_the_soft_capacity() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).soft_capacity(40).build());
+
+        let status = cached.put_optional("topic", "microservices").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
     }
 
-    #[test]
-    #[should_panic]
-    fn put_or_update_results_in_put_value_must_be_present() {
-        let cached = CacheD::new(test_config_builder().build());
-        let put_or_update: PutOrUpdateRequest<&str, &str> = PutOrUpdateRequestBuilder::new("store").build();
-        let _ = cached.put_or_update(put_or_update);
+    #[tokio::test]
+    async fn put_optional_without_soft_capacity() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+
+        let status = cached.put_with_weight("topic", "microservices", 30).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let status = cached.put_optional("cache", "cached").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("cached"), cached.get(&"cache"));
     }
 
-    #[test]
-    #[should_panic]
-    fn put_or_update_results_in_put_with_weight_calculation_fn_must_return_weight_greater_than_zero() {
-        let weight_calculation: Box<WeightCalculationFn<&str, &str>> = Box::new(|_key, _value, _is_time_to_live_specified| 0);
-        let cached = CacheD::new(test_config_builder().weight_calculation_fn(weight_calculation).build());
+    #[tokio::test]
+    async fn put_optional_is_rejected_above_the_soft_capacity_while_put_is_admitted_up_to_the_hard_capacity() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).soft_capacity(40).build());
 
-        let put_or_update = PutOrUpdateRequestBuilder::new("store").value("cached").build();
-        let _ = cached.put_or_update(put_or_update);
+        let status = cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let status = cached.put_optional("cache", "cached").unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::SoftCapacityExceeded), status);
+        assert_eq!(None, cached.get(&"cache"));
+
+        let status = cached.put_with_weight("disk", "SSD", 40).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
+        assert_eq!(90, cached.total_weight_used());
+    }
+}
+
+#[cfg(test)]
+mod put_versioned_tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::ManualClock;
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::config::ConfigBuilder;
+
+    #[tokio::test]
+    async fn put_a_versioned_key_value() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let status = cached.put_versioned("topic", "microservices", 1).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(Some(1), cached.get_ref(&"topic").unwrap().value().version());
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn put_or_update_results_in_update_with_weight_calculation_fn_must_return_weight_greater_than_zero() {
-        let weight_calculation: Box<WeightCalculationFn<&str, &str>> = Box::new(|_key, _value, _is_time_to_live_specified| 0);
-        let cached = CacheD::new(test_config_builder().weight_calculation_fn(weight_calculation).build());
-        cached.put("topic", "microservices").unwrap().handle().await;
+    async fn put_a_higher_version() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
 
-        let put_or_update = PutOrUpdateRequestBuilder::new("topic").value("cached").build();
-        let _ = cached.put_or_update(put_or_update);
+        let _ = cached.put_versioned("topic", "microservices", 1).unwrap().handle().await;
+        let status = cached.put_versioned("topic", "cached", 2).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        assert_eq!(Some("cached"), cached.get(&"topic"));
+        assert_eq!(Some(2), cached.get_ref(&"topic").unwrap().value().version());
+        assert_eq!(64, cached.total_weight_used());
     }
 
+    #[tokio::test]
+    async fn reject_an_out_of_order_lower_version() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let _ = cached.put_versioned("topic", "cached", 5).unwrap().handle().await;
+        let status = cached.put_versioned("topic", "microservices", 3).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::StaleVersion), status);
+
+        assert_eq!(Some("cached"), cached.get(&"topic"));
+        assert_eq!(Some(5), cached.get_ref(&"topic").unwrap().value().version());
+    }
 
     #[tokio::test]
-    #[should_panic]
-    async fn put_or_update_results_in_update_with_weight_must_be_greater_than_zero() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.put("topic", "microservices").unwrap().handle().await;
+    async fn reject_the_same_version() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
 
-        let put_or_update = PutOrUpdateRequestBuilder::new("topic").value("cached").weight(0).build();
-        let _ = cached.put_or_update(put_or_update);
+        let _ = cached.put_versioned("topic", "cached", 5).unwrap().handle().await;
+        let status = cached.put_versioned("topic", "microservices", 5).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::StaleVersion), status);
+
+        assert_eq!(Some("cached"), cached.get(&"topic"));
     }
 
     #[tokio::test]
-    async fn put_a_key_value_without_weight_and_ttl() {
-        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    async fn replace_a_key_without_version() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
 
-        let key: u64 = 100;
-        let value: u64 = 1000;
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let status = cached.put_versioned("topic", "cached", 1).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
 
-        let acknowledgement =
-            cached.put(key, value).unwrap();
-        acknowledgement.handle().await;
+        assert_eq!(Some("cached"), cached.get(&"topic"));
+    }
 
-        let value = cached.get_ref(&100);
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+    #[tokio::test]
+    async fn put_versioned_with_the_default_time_to_live() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .default_time_to_live(Duration::from_secs(300))
+                .build()
+        );
 
-        assert_eq!(1000, stored_value.value());
-        assert_eq!(Some(40), cached.admission_policy.weight_of(&key_id));
+        let status = cached.put_versioned("topic", "microservices", 1).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some(SystemTime::UNIX_EPOCH + Duration::from_secs(300)), cached.get_ref(&"topic").unwrap().value().expire_after());
+        assert_eq!(Some(1), cached.get_ref(&"topic").unwrap().value().version());
+
+        clock.advance(Duration::from_secs(301));
+        assert_eq!(None, cached.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn put_versioned_of_a_delete_value_deletes_the_key() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).delete_on_value(Box::new(|value| *value == "<deleted>")).build());
+
+        let _ = cached.put_versioned("topic", "microservices", 2).unwrap().handle().await;
+        let status = cached.put_versioned("topic", "<deleted>", 1).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(0, cached.total_weight_used());
+    }
+}
+
+#[cfg(test)]
+mod additional_access_consumer_tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use parking_lot::Mutex;
+
+    use crate::cache::buffer_event::{BufferConsumer, BufferEvent};
+    use crate::cache::cached::CacheD;
+    use crate::cache::config::{AccessDrain, ConfigBuilder};
+    use crate::cache::types::KeyHash;
+
+    #[derive(Clone)]
+    struct RecordingConsumer {
+        key_hashes: Arc<Mutex<Vec<KeyHash>>>,
+        is_shutdown: Arc<Mutex<bool>>,
+    }
+
+    impl RecordingConsumer {
+        fn new() -> Self {
+            RecordingConsumer { key_hashes: Arc::new(Mutex::new(Vec::new())), is_shutdown: Arc::new(Mutex::new(false)) }
+        }
+    }
+
+    impl BufferConsumer for RecordingConsumer {
+        fn accept(&self, event: BufferEvent) {
+            match event {
+                BufferEvent::Full(key_hashes) => self.key_hashes.lock().extend(key_hashes),
+                BufferEvent::Shutdown => *self.is_shutdown.lock() = true,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn additional_consumer_observes_the_accesses_of_the_admission_policy() {
+        let consumer = RecordingConsumer::new();
+        let cached = CacheD::new(
+            ConfigBuilder::new(10, 10, 1000)
+                .access_pool_size(1)
+                .access_buffer_size(3)
+                .additional_access_consumer(Box::new(consumer.clone()))
+                .build()
+        );
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let _ = cached.put("disk", "SSD").unwrap().handle().await;
+
+        cached.get(&"topic");
+        cached.get(&"disk");
+        cached.get(&"topic");
+        cached.get(&"disk"); //will cause the drain of the buffer which will have 2 accesses of topic and one for disk
+
+        thread::sleep(Duration::from_secs(2));
+
+        let hasher = &(cached.config.key_hash_fn);
+        let key_hashes = consumer.key_hashes.lock().clone();
+        assert_eq!(vec![hasher(&"topic"), hasher(&"disk"), hasher(&"topic")], key_hashes);
+
+        assert_eq!(2, cached.admission_policy.estimate(hasher(&"topic")));
+        assert_eq!(1, cached.admission_policy.estimate(hasher(&"disk")));
+    }
+
+    #[tokio::test]
+    async fn additional_consumer_observes_the_accesses_with_sync_access_drain() {
+        let consumer = RecordingConsumer::new();
+        let cached = CacheD::new(
+            ConfigBuilder::new(10, 10, 1000)
+                .access_drain(AccessDrain::Sync)
+                .additional_access_consumer(Box::new(consumer.clone()))
+                .build()
+        );
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        cached.get(&"topic");
+
+        let hasher = &(cached.config.key_hash_fn);
+        assert_eq!(vec![hasher(&"topic")], *consumer.key_hashes.lock());
+    }
+
+    #[tokio::test]
+    async fn additional_consumer_is_shutdown() {
+        let consumer = RecordingConsumer::new();
+        let cached: CacheD<&str, &str> = CacheD::new(
+            ConfigBuilder::new(10, 10, 1000)
+                .additional_access_consumer(Box::new(consumer.clone()))
+                .build()
+        );
+
+        cached.shutdown();
+        assert!(*consumer.is_shutdown.lock());
+    }
+}
+
+#[cfg(test)]
+mod batch_eviction_tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, SystemTime};
+
+    use parking_lot::Mutex;
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::ManualClock;
+    use crate::cache::config::{ConfigBuilder, EvictionReason};
+
+    type Batches = Arc<Mutex<Vec<Vec<(u64, EvictionReason)>>>>;
+
+    #[tokio::test]
+    async fn mass_expiry_invokes_the_batch_eviction_listener_once_with_all_the_keys() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let batches: Batches = Arc::new(Mutex::new(Vec::new()));
+        let listener_batches = batches.clone();
+
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 10_000)
+                .clock(Box::new(clock.clone()))
+                .shards(4)
+                .ttl_tick_duration(Duration::from_millis(10))
+                .batch_eviction_listener(Box::new(move |batch| listener_batches.lock().push(batch)))
+                .build()
+        );
+
+        for key in 1..=20 {
+            let _ = cached.put_with_ttl(key, key * 10, Duration::from_secs(5)).unwrap().handle().await;
+        }
+        clock.advance(Duration::from_secs(9));
+        thread::sleep(Duration::from_millis(200));
+
+        let batches = batches.lock();
+        assert_eq!(1, batches.len());
+
+        let mut keys = batches[0].iter().map(|(key, reason)| {
+            assert_eq!(EvictionReason::Expired, *reason);
+            *key
+        }).collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!((1..=20).collect::<Vec<u64>>(), keys);
+        assert_eq!(0, cached.total_weight_used());
     }
+}
+
+#[cfg(test)]
+mod take_all_tests {
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::CommandStatus;
+    use crate::cache::config::ConfigBuilder;
 
     #[tokio::test]
-    async fn put_a_key_value_without_weight_with_ttl() {
+    async fn take_all_returns_the_values_of_the_present_keys() {
         let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
 
-        let key: u64 = 100;
-        let value: u64 = 1000;
-
-        let acknowledgement =
-            cached.put_with_ttl(key, value, Duration::from_secs(300)).unwrap();
-        acknowledgement.handle().await;
+        let status = cached.put_with_weight("job-1", "resize", 10).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        let status = cached.put_with_weight("job-2", "compress", 20).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        let status = cached.put_with_weight("job-3", "upload", 30).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(60, cached.total_weight_used());
+
+        let taken = cached.take_all(vec!["job-1", "job-3", "job-4"]).await;
+
+        assert_eq!(2, taken.len());
+        assert_eq!(Some(&"resize"), taken.get(&"job-1"));
+        assert_eq!(Some(&"upload"), taken.get(&"job-3"));
+        assert_eq!(None, taken.get(&"job-4"));
+
+        assert_eq!(None, cached.get(&"job-1"));
+        assert_eq!(None, cached.get(&"job-3"));
+        assert_eq!(Some("compress"), cached.get(&"job-2"));
+        assert_eq!(20, cached.total_weight_used());
+    }
 
-        let value = cached.get_ref(&100);
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+    #[tokio::test]
+    async fn take_all_of_the_non_existing_keys() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
 
-        assert_eq!(1000, stored_value.value());
-        assert_eq!(Some(64), cached.admission_policy.weight_of(&key_id));
-        assert!(stored_value.expire_after().is_some());
+        let taken = cached.take_all(vec!["job-1", "job-2"]).await;
+        assert!(taken.is_empty());
     }
 
     #[tokio::test]
-    async fn put_the_same_key_value_again() {
+    async fn take_all_after_shutdown() {
         let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
 
-        let key: u64 = 100;
-        let value: u64 = 1000;
-
-        let acknowledgement = cached.put(key, value).unwrap();
-        acknowledgement.handle().await;
+        let _ = cached.put_with_weight("job-1", "resize", 10).unwrap().handle().await;
+        cached.shutdown();
 
-        let acknowledgement = cached.put(key, value).unwrap();
-        let status = acknowledgement.handle().await;
+        let taken = cached.take_all(vec!["job-1"]).await;
+        assert!(taken.is_empty());
+    }
+}
 
-        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), status);
+#[cfg(test)]
+mod adaptive_command_buffer_tests {
+    use crate::cache::cached::CacheD;
+    use crate::cache::config::ConfigBuilder;
 
-        let value = cached.get_ref(&100);
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
+    #[test]
+    fn command_buffer_capacity() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 100).command_buffer_size(16).build());
+        assert_eq!(16, cached.command_buffer_capacity());
+    }
 
-        assert_eq!(1000, stored_value.value());
-        assert_eq!(40, cached.total_weight_used());
+    #[test]
+    fn command_buffer_capacity_with_inline_execution() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 100).inline_execution().build());
+        assert_eq!(0, cached.command_buffer_capacity());
     }
 
     #[tokio::test]
-    async fn put_a_key_value_with_weight() {
-        let cached = CacheD::new(test_config_builder().build());
-
-        let acknowledgement =
-            cached.put_with_weight("topic", "microservices", 50).unwrap();
-        acknowledgement.handle().await;
+    async fn burst_of_writes_with_adaptive_command_buffer() {
+        let cached = CacheD::new(ConfigBuilder::new(1000, 100, 10_000).command_buffer_size(2).adaptive_command_buffer(64).build());
+        assert_eq!(2, cached.command_buffer_capacity());
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+        let acknowledgements = (0..50).map(|key| cached.put(key, key * 10).unwrap()).collect::<Vec<_>>();
+        assert!(cached.command_buffer_capacity() <= 64);
 
-        assert_eq!("microservices", stored_value.value());
-        assert_eq!(Some(50), cached.admission_policy.weight_of(&key_id));
+        for acknowledgement in acknowledgements {
+            acknowledgement.handle().await;
+        }
+        for key in 0..50 {
+            assert_eq!(Some(key * 10), cached.get(&key));
+        }
+        assert_eq!(2, cached.command_buffer_capacity());
     }
+}
 
-    #[tokio::test]
-    async fn put_a_key_value_with_weight_again() {
-        let cached = CacheD::new(test_config_builder().build());
+#[cfg(test)]
+mod admission_observer_tests {
+    use std::sync::Arc;
 
-        let acknowledgement =
-            cached.put_with_weight("topic", "microservices", 50).unwrap();
-        acknowledgement.handle().await;
+    use parking_lot::Mutex;
 
-        let acknowledgement =
-            cached.put_with_weight("topic", "microservices", 50).unwrap();
-        let status = acknowledgement.handle().await;
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::config::ConfigBuilder;
 
-        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), status);
+    #[tokio::test]
+    async fn observe_one_admission_decision_per_put() {
+        let decisions = Arc::new(Mutex::new(Vec::new()));
+        let observed_decisions = decisions.clone();
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 100)
+                .admission_observer(Box::new(move |decision| observed_decisions.lock().push(decision)))
+                .build()
+        );
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+        let status = cached.put_with_weight("topic", "microservices", 60).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        let status = cached.put_with_weight("disk", "SSD", 30).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        let status = cached.put_with_weight("cache", "in-memory", 200).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyWeightIsGreaterThanCacheWeight), status);
 
-        assert_eq!("microservices", stored_value.value());
-        assert_eq!(Some(50), cached.admission_policy.weight_of(&key_id));
-        assert_eq!(50, cached.total_weight_used());
-    }
+        let decisions = decisions.lock();
+        assert_eq!(3, decisions.len());
 
-    #[tokio::test]
-    async fn put_a_key_value_with_ttl() {
-        let cached = CacheD::new(test_config_builder().build());
+        assert_eq!(60, decisions[0].weight());
+        assert_eq!(None, decisions[0].victim());
+        assert_eq!(CommandStatus::Accepted, decisions[0].status());
 
-        let acknowledgement =
-            cached.put_with_ttl("topic", "microservices", Duration::from_secs(120)).unwrap();
-        acknowledgement.handle().await;
+        assert_eq!(30, decisions[1].weight());
+        assert_eq!(None, decisions[1].victim());
+        assert_eq!(CommandStatus::Accepted, decisions[1].status());
+        assert_ne!(decisions[0].key_id(), decisions[1].key_id());
 
-        let value = cached.get(&"topic");
-        assert_eq!(Some("microservices"), value);
+        assert_eq!(200, decisions[2].weight());
+        assert_eq!(0, decisions[2].frequency());
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyWeightIsGreaterThanCacheWeight), decisions[2].status());
     }
 
     #[tokio::test]
-    async fn put_a_key_value_with_ttl_again() {
-        let cached = CacheD::new(test_config_builder().build());
+    async fn observe_an_admission_decision_with_a_victim() {
+        let decisions = Arc::new(Mutex::new(Vec::new()));
+        let observed_decisions = decisions.clone();
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 100)
+                .admission_observer(Box::new(move |decision| observed_decisions.lock().push(decision)))
+                .build()
+        );
 
-        let acknowledgement =
-            cached.put_with_ttl("topic", "microservices", Duration::from_secs(120)).unwrap();
-        acknowledgement.handle().await;
+        let _ = cached.put_with_weight("topic", "microservices", 60).unwrap().handle().await;
+        let status = cached.put_with_weight("disk", "SSD", 60).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(None, cached.get(&"topic"));
 
-        let acknowledgement =
-            cached.put_with_ttl("topic", "microservices", Duration::from_secs(120)).unwrap();
-        let status = acknowledgement.handle().await;
+        let decisions = decisions.lock();
+        assert_eq!(2, decisions.len());
+        assert_eq!(Some((decisions[0].key_id(), 0)), decisions[1].victim());
+        assert_eq!(CommandStatus::Accepted, decisions[1].status());
+    }
+}
 
-        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), status);
+#[cfg(test)]
+mod sketch_tests {
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::config::{AccessDrain, ConfigBuilder};
+    use crate::cache::sketch::SketchState;
 
-        let value = cached.get(&"topic");
-        assert_eq!(Some("microservices"), value);
+    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str> {
+        ConfigBuilder::new(100, 10, 100).access_drain(AccessDrain::Sync)
     }
 
     #[tokio::test]
-    async fn put_a_key_value_with_weight_and_ttl() {
+    async fn cache_with_an_empty_sketch_rejects_a_hot_key_under_pressure() {
         let cached = CacheD::new(test_config_builder().build());
 
-        let acknowledgement =
-            cached.put_with_weight_and_ttl("topic", "microservices", 10, Duration::from_secs(120)).unwrap();
-        acknowledgement.handle().await;
+        let status = cached.put_with_weight("disk", "SSD", 100).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        cached.get(&"disk");
 
-        let value = cached.get(&"topic");
-        assert_eq!(Some("microservices"), value);
+        let status = cached.put_with_weight("topic", "microservices", 100).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers), status);
     }
 
     #[tokio::test]
-    async fn put_a_key_value_with_weight_and_ttl_again() {
-        let cached = CacheD::new(test_config_builder().build());
+    async fn cache_with_a_primed_sketch_admits_a_hot_key_under_pressure() {
+        let previous_cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+        previous_cached.prime_frequency(&"topic", 5);
+        let sketch = SketchState::from_bytes(&previous_cached.export_sketch().to_bytes()).unwrap();
 
-        let acknowledgement =
-            cached.put_with_weight_and_ttl("topic", "microservices", 10, Duration::from_secs(120)).unwrap();
-        acknowledgement.handle().await;
+        let cached = CacheD::new_with_sketch(test_config_builder().build(), sketch);
 
-        let acknowledgement =
-            cached.put_with_weight_and_ttl("topic", "microservices", 10, Duration::from_secs(120)).unwrap();
-        let status = acknowledgement.handle().await;
-        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), status);
+        let status = cached.put_with_weight("disk", "SSD", 100).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        cached.get(&"disk");
 
-        let value = cached.get(&"topic");
-        assert_eq!(Some("microservices"), value);
-        assert_eq!(10, cached.total_weight_used());
+        let status = cached.put_with_weight("topic", "microservices", 100).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(None, cached.get(&"disk"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn sketch_dimensions_must_match_the_config() {
+        let previous_cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(1000, 10, 100).build());
+
+        let _: CacheD<&str, &str> = CacheD::new_with_sketch(test_config_builder().build(), previous_cached.export_sketch());
     }
+}
+
+#[cfg(test)]
+mod get_or_insert_tests {
+    use std::sync::{Arc, Barrier};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::CommandStatus;
+    use crate::cache::config::ConfigBuilder;
 
     #[tokio::test]
-    async fn put_a_key_value_with_ttl_and_ttl_ticker_evicts_it() {
-        let cached = CacheD::new(test_config_builder().shards(2).ttl_tick_duration(Duration::from_millis(10)).build());
+    async fn get_an_existing_value_without_computing() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+        cached.put("topic", "microservices").unwrap().handle().await;
 
-        let acknowledgement =
-            cached.put_with_ttl("topic", "microservices", Duration::from_millis(20)).unwrap();
-        acknowledgement.handle().await;
+        let value = cached.get_or_insert_with("topic", || panic!("must not compute the value of an existing key"));
+        assert_eq!("microservices", value);
+    }
 
-        let value = cached.get(&"topic");
-        assert_eq!(Some("microservices"), value);
+    #[tokio::test]
+    async fn compute_and_insert_a_missing_value() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
 
-        thread::sleep(Duration::from_millis(20));
-        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!("microservices", cached.get_or_insert_with("topic", || "microservices"));
+
+        let status = cached.put("disk", "SSD").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
     }
 
     #[test]
-    fn get_value_ref_for_a_non_existing_key() {
-        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+    fn compute_the_value_once_for_concurrent_single_flight_callers() {
+        let cached: Arc<CacheD<&str, usize>> = Arc::new(CacheD::new(ConfigBuilder::new(100, 10, 100).build()));
+        let computations = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(8));
+
+        let handles = (0..8).map(|_| {
+            let cached = cached.clone();
+            let computations = computations.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                cached.get_or_insert_with_single_flight("topic", || {
+                    thread::sleep(Duration::from_millis(50));
+                    computations.fetch_add(1, Ordering::SeqCst) + 100
+                })
+            })
+        }).collect::<Vec<_>>();
 
-        let value = cached.get_ref(&"non-existing");
-        assert!(value.is_none());
+        let values = handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>();
+        assert!(values.iter().all(|value| *value == 100));
+        assert_eq!(1, computations.load(Ordering::SeqCst));
+        assert_eq!(Some(100), cached.get(&"topic"));
     }
 
     #[test]
-    fn get_value_ref_for_a_non_existing_key_and_attempt_to_map_it() {
-        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+    fn single_flight_returns_the_computed_value_after_shutdown() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+        cached.shutdown();
 
-        let value = cached.map_get_ref(&"non_existing", |stored_value| stored_value.value_ref().to_uppercase());
-        assert!(value.is_none());
+        assert_eq!("microservices", cached.get_or_insert_with_single_flight("topic", || "microservices"));
+        assert_eq!("microservices", cached.get_or_insert_with("topic", || "microservices"));
     }
+}
+
+#[cfg(test)]
+mod readmission_tests {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::CommandStatus;
+    use crate::cache::config::ConfigBuilder;
 
     #[tokio::test]
-    async fn get_value_ref_for_an_existing_key() {
-        let cached = CacheD::new(test_config_builder().build());
+    async fn readmission_of_keys_evicted_under_pressure() {
+        let readmitted = Arc::new(Mutex::new(Vec::new()));
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 100)
+                .on_readmission(Box::new({
+                    let readmitted = readmitted.clone();
+                    move |key: &&str| readmitted.lock().push(*key)
+                }))
+                .build()
+        );
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+        for _ in 0..3 {
+            let status = cached.put_with_weight("topic", "microservices", 60).unwrap().handle().await;
+            assert_eq!(CommandStatus::Accepted, status);
+            let status = cached.put_with_weight("disk", "SSD", 60).unwrap().handle().await;
+            assert_eq!(CommandStatus::Accepted, status);
+        }
 
-        let value = cached.get_ref(&"topic");
-        assert_eq!(&"microservices", value.unwrap().value().value_ref());
+        assert_eq!(vec!["topic", "disk", "topic", "disk"], *readmitted.lock());
+        assert!(cached.thrash_rate() > 0.6);
     }
 
     #[tokio::test]
-    async fn get_value_ref_for_an_existing_key_and_map_it() {
-        let cached = CacheD::new(test_config_builder().build());
+    async fn thrash_rate_without_evictions() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).on_readmission(Box::new(|_key: &&str| {})).build());
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+        let _ = cached.put_with_weight("topic", "microservices", 30).unwrap().handle().await;
+        let _ = cached.put_with_weight("disk", "SSD", 30).unwrap().handle().await;
 
-        let value = cached.map_get_ref(&"topic", |stored_value| stored_value.value_ref().to_uppercase());
-        assert_eq!("MICROSERVICES", value.unwrap());
+        assert_eq!(0.0, cached.thrash_rate());
     }
+}
 
-    #[tokio::test]
-    async fn get_value_for_an_existing_key() {
-        let cached = CacheD::new(test_config_builder().build());
+#[cfg(test)]
+mod eviction_listener_tests {
+    use std::sync::{Arc, OnceLock, Weak};
+    use std::thread;
+    use std::thread::ThreadId;
+    use std::time::Duration;
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+    use parking_lot::Mutex;
 
-        let value = cached.get(&"topic");
-        assert_eq!(Some("microservices"), value);
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::CommandStatus;
+    use crate::cache::config::{ConfigBuilder, EvictionReason, PutMode};
+
+    type Removals = Arc<Mutex<Vec<(&'static str, &'static str, EvictionReason, ThreadId)>>>;
+
+    fn test_config_builder(removals: &Removals) -> ConfigBuilder<&'static str, &'static str> {
+        let removals = removals.clone();
+        ConfigBuilder::new(100, 10, 100)
+            .eviction_listener(Box::new(move |key, value, reason| removals.lock().push((*key, *value, reason, thread::current().id()))))
     }
 
     #[tokio::test]
-    async fn get_value_for_an_existing_key_and_map_it() {
-        let cached = CacheD::new(test_config_builder().build());
+    async fn evicted_key() {
+        let removals: Removals = Arc::new(Mutex::new(Vec::new()));
+        let cached = CacheD::new(test_config_builder(&removals).build());
+
+        let _ = cached.put_with_weight("topic", "microservices", 60).unwrap().handle().await;
+        let status = cached.put_with_weight("disk", "SSD", 60).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let removals = removals.lock();
+        assert_eq!(1, removals.len());
+        let (key, value, reason, thread_id) = removals[0];
+        assert_eq!(("topic", "microservices", EvictionReason::Evicted), (key, value, reason));
+        assert_ne!(thread::current().id(), thread_id);
+    }
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+    #[tokio::test]
+    async fn deleted_key() {
+        let removals: Removals = Arc::new(Mutex::new(Vec::new()));
+        let cached = CacheD::new(test_config_builder(&removals).build());
 
-        let value = cached.map_get(&"topic", |value| value.to_uppercase());
-        assert_eq!("MICROSERVICES", value.unwrap());
-    }
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let _ = cached.delete("topic").unwrap().handle().await;
 
-    #[test]
-    fn get_value_for_a_non_existing_key() {
-        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+        let removals = removals.lock();
+        assert_eq!(1, removals.len());
+        let (key, value, reason, _) = removals[0];
+        assert_eq!(("topic", "microservices", EvictionReason::Explicit), (key, value, reason));
+    }
 
-        let value = cached.get(&"non-existing");
-        assert_eq!(None, value);
+    #[tokio::test]
+    async fn replaced_key() {
+        let removals: Removals = Arc::new(Mutex::new(Vec::new()));
+        let cached = CacheD::new(test_config_builder(&removals).put_mode(PutMode::Overwrite).build());
+
+        let _ = cached.put_with_weight("topic", "microservices", 20).unwrap().handle().await;
+        let _ = cached.put_with_weight("topic", "cache", 20).unwrap().handle().await;
+
+        let removals = removals.lock();
+        assert_eq!(1, removals.len());
+        let (key, value, reason, _) = removals[0];
+        assert_eq!(("topic", "microservices", EvictionReason::Replaced), (key, value, reason));
+        assert_eq!(Some("cache"), cached.get(&"topic"));
     }
 
-    #[test]
-    fn get_value_for_a_non_existing_key_and_attempt_to_map_it() {
-        let cached: CacheD<&str, &str> = CacheD::new(test_config_builder().build());
+    #[tokio::test]
+    async fn expired_key() {
+        let removals: Removals = Arc::new(Mutex::new(Vec::new()));
+        let cached = CacheD::new(test_config_builder(&removals).ttl_tick_duration(Duration::from_millis(5)).build());
+
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_millis(5)).unwrap().handle().await;
+        thread::sleep(Duration::from_millis(100));
+
+        let removals = removals.lock();
+        assert_eq!(1, removals.len());
+        let (key, value, reason, thread_id) = removals[0];
+        assert_eq!(("topic", "microservices", EvictionReason::Expired), (key, value, reason));
+        assert_ne!(thread::current().id(), thread_id);
+    }
 
-        let value = cached.map_get(&"topic", |value| value.to_uppercase());
-        assert_eq!(None, value);
+    fn cache_with_weight_reading_listener(
+        config_builder: ConfigBuilder<&'static str, &'static str>,
+        weights_used: &Arc<Mutex<Vec<(EvictionReason, i64)>>>) -> Arc<CacheD<&'static str, &'static str>> {
+        let cached_slot: Arc<OnceLock<Weak<CacheD<&str, &str>>>> = Arc::new(OnceLock::new());
+        let cached = Arc::new(CacheD::new(
+            config_builder
+                .eviction_listener(Box::new({
+                    let cached_slot = cached_slot.clone();
+                    let weights_used = weights_used.clone();
+                    move |_key, _value, reason| {
+                        if let Some(cached) = cached_slot.get().and_then(Weak::upgrade) {
+                            weights_used.lock().push((reason, cached.total_weight_used()));
+                        }
+                    }
+                }))
+                .build()
+        ));
+        cached_slot.set(Arc::downgrade(&cached)).unwrap();
+        cached
     }
 
     #[tokio::test]
-    async fn get_value_ref_for_an_existing_key_if_value_is_not_cloneable() {
-        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    async fn evicted_key_with_a_listener_reading_the_weight_used() {
+        let weights_used = Arc::new(Mutex::new(Vec::new()));
+        let cached = cache_with_weight_reading_listener(ConfigBuilder::new(100, 10, 100), &weights_used);
 
-        let acknowledgement =
-            cached.put("name", Name { first: "John".to_string(), last: "Mcnamara".to_string() }).unwrap();
-        acknowledgement.handle().await;
+        let _ = cached.put_with_weight("topic", "microservices", 60).unwrap().handle().await;
+        let status = cached.put_with_weight("disk", "SSD", 60).unwrap().handle().await;
 
-        let value = cached.get_ref(&"name");
-        assert_eq!(&Name { first: "John".to_string(), last: "Mcnamara".to_string() }, value.unwrap().value().value_ref());
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(vec![(EvictionReason::Evicted, 60)], *weights_used.lock());
     }
 
     #[tokio::test]
-    async fn get_value_for_an_existing_key_if_value_is_not_cloneable_by_passing_an_arc() {
-        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    async fn expired_key_with_a_listener_reading_the_weight_used() {
+        let weights_used = Arc::new(Mutex::new(Vec::new()));
+        let cached = cache_with_weight_reading_listener(
+            ConfigBuilder::new(100, 10, 100).ttl_tick_duration(Duration::from_millis(5)), &weights_used,
+        );
 
-        let acknowledgement =
-            cached.put("name", Arc::new(Name { first: "John".to_string(), last: "Mcnamara".to_string() })).unwrap();
-        acknowledgement.handle().await;
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_millis(5)).unwrap().handle().await;
+        thread::sleep(Duration::from_millis(100));
 
-        let value = cached.get(&"name").unwrap();
-        assert_eq!("John".to_string(), value.first);
-        assert_eq!("Mcnamara".to_string(), value.last);
+        assert_eq!(vec![(EvictionReason::Expired, 0)], *weights_used.lock());
     }
+}
 
-    #[tokio::test]
-    async fn delete_a_key() {
-        let cached = CacheD::new(test_config_builder().build());
+#[cfg(test)]
+mod min_residency_tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::ManualClock;
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::config::ConfigBuilder;
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+    #[tokio::test]
+    async fn a_new_key_survives_the_eviction_pressure_until_the_min_residency_elapses() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 100)
+                .clock(Box::new(clock.clone()))
+                .min_residency(Duration::from_secs(10))
+                .build()
+        );
 
-        let key_id = {
-            let key_value_ref = cached.get_ref(&"topic").unwrap();
-            key_value_ref.value().key_id()
-        };
+        let status = cached.put_with_weight("topic", "microservices", 60).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
 
-        let acknowledgement =
-            cached.delete("topic").unwrap();
-        acknowledgement.handle().await;
+        clock.advance(Duration::from_secs(5));
+        let status = cached.put_with_weight("disk", "SSD", 60).unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(RejectionReason::EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers), status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
 
-        let value = cached.get(&"topic");
-        assert_eq!(None, value);
-        assert!(!cached.admission_policy.contains(&key_id));
+        clock.advance(Duration::from_secs(6));
+        let status = cached.put_with_weight("disk", "SSD", 60).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
     }
 
     #[tokio::test]
-    async fn get_access_frequency() {
-        let cached = CacheD::new(ConfigBuilder::new(10, 10, 1000).access_pool_size(1).access_buffer_size(3).build());
+    async fn an_older_key_is_evicted_instead_of_a_new_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 100)
+                .clock(Box::new(clock.clone()))
+                .min_residency(Duration::from_secs(10))
+                .build()
+        );
 
-        let acknowledgement_topic =
-            cached.put("topic", "microservices").unwrap();
-        let acknowledgement_disk =
-            cached.put("disk", "SSD").unwrap();
+        let _ = cached.put_with_weight("topic", "microservices", 40).unwrap().handle().await;
+        clock.advance(Duration::from_secs(20));
+        let _ = cached.put_with_weight("disk", "SSD", 40).unwrap().handle().await;
 
-        acknowledgement_topic.handle().await;
-        acknowledgement_disk.handle().await;
+        let status = cached.put_with_weight("cache", "in-memory", 40).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
+        assert_eq!(Some("in-memory"), cached.get(&"cache"));
+    }
+}
 
-        cached.get(&"topic");
-        cached.get(&"disk");
-        cached.get(&"topic");
-        cached.get(&"disk"); //will cause the drain of the buffer which will have 2 accesses of topic and one for disk
+#[cfg(test)]
+mod clear_tests {
+    use std::sync::{Arc, OnceLock, Weak};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::Duration;
 
-        thread::sleep(Duration::from_secs(2));
+    use parking_lot::Mutex;
 
-        let hasher = &(cached.config.key_hash_fn);
-        let policy = cached.admission_policy;
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::CommandStatus;
+    use crate::cache::config::{ConfigBuilder, EvictionReason};
+    use crate::cache::stats::StatsType;
 
-        assert_eq!(2, policy.estimate(hasher(&"topic")));
-        assert_eq!(1, policy.estimate(hasher(&"disk")));
+    #[tokio::test]
+    async fn put_clear_and_put_again() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let _ = cached.put_with_ttl("disk", "SSD", Duration::from_secs(300)).unwrap().handle().await;
+
+        let status = cached.clear().unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(None, cached.get(&"disk"));
+        assert_eq!(0, cached.total_weight_used());
+
+        let status = cached.put("topic", "cached").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("cached"), cached.get(&"topic"));
     }
 
     #[tokio::test]
-    async fn get_multiple_keys() {
+    async fn clear_counts_the_deleted_keys_and_the_removed_weight() {
         let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+        let _ = cached.put_with_weight("topic", "microservices", 40).unwrap().handle().await;
+        let _ = cached.put_with_weight("disk", "SSD", 30).unwrap().handle().await;
+        let _ = cached.clear().unwrap().handle().await;
 
-        let acknowledgement =
-            cached.put("disk", "SSD").unwrap();
-        acknowledgement.handle().await;
+        let summary = cached.stats_summary();
+        assert_eq!(Some(2), summary.get(&StatsType::KeysAdded));
+        assert_eq!(Some(2), summary.get(&StatsType::KeysDeleted));
+        assert_eq!(Some(70), summary.get(&StatsType::WeightRemoved));
+    }
 
-        let acknowledgement =
-            cached.put("cache", "in-memory").unwrap();
-        acknowledgement.handle().await;
+    #[tokio::test]
+    async fn clear_executes_the_commands_sent_before_it() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
 
-        let values = cached.multi_get(vec![&"topic", &"non-existing", &"cache", &"disk"]);
+        let put = cached.put("topic", "microservices").unwrap();
+        let clear = cached.clear().unwrap();
 
-        assert_eq!(&Some("microservices"), values.get(&"topic").unwrap());
-        assert_eq!(&None, values.get(&"non-existing").unwrap());
-        assert_eq!(&Some("in-memory"), values.get(&"cache").unwrap());
-        assert_eq!(&Some("SSD"), values.get(&"disk").unwrap());
+        assert_eq!(CommandStatus::Accepted, put.handle().await);
+        assert_eq!(CommandStatus::Accepted, clear.handle().await);
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(0, cached.total_weight_used());
     }
 
     #[tokio::test]
-    async fn get_multiple_keys_via_an_iterator() {
-        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    async fn clear_notifies_the_eviction_listener() {
+        let removals = Arc::new(Mutex::new(Vec::new()));
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .eviction_listener(Box::new({
+                    let removals = removals.clone();
+                    move |key: &&str, value: &&str, reason| removals.lock().push((*key, *value, reason))
+                }))
+                .build()
+        );
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        let _ = cached.clear().unwrap().handle().await;
 
-        let acknowledgement =
-            cached.put("disk", "SSD").unwrap();
-        acknowledgement.handle().await;
+        assert_eq!(vec![("topic", "microservices", EvictionReason::Explicit)], *removals.lock());
+    }
 
-        let acknowledgement =
-            cached.put("cache", "in-memory").unwrap();
-        acknowledgement.handle().await;
+    fn cache_with_slow_eviction_listener(listener_done: Arc<AtomicBool>) -> Arc<CacheD<&'static str, &'static str>> {
+        Arc::new(CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .eviction_listener(Box::new(move |_key: &&str, _value: &&str, _cause| {
+                    thread::sleep(Duration::from_millis(100));
+                    listener_done.store(true, Ordering::SeqCst);
+                }))
+                .build()
+        ))
+    }
 
-        let mut iterator = cached.multi_get_iterator(vec![&"topic", &"non-existing", &"cache", &"disk"]);
-        assert_eq!(Some("microservices"), iterator.next().unwrap());
-        assert_eq!(None, iterator.next().unwrap());
-        assert_eq!(Some("in-memory"), iterator.next().unwrap());
-        assert_eq!(Some("SSD"), iterator.next().unwrap());
-        assert_eq!(None, iterator.next());
+    #[test]
+    fn clear_may_leave_a_racing_put() {
+        let listener_done = Arc::new(AtomicBool::new(false));
+        let cached = cache_with_slow_eviction_listener(listener_done.clone());
+        cached.put_sync("topic", "microservices").unwrap();
+
+        let clear = cached.clear().unwrap();
+        let put = thread::spawn({
+            let cached = cached.clone();
+            let listener_done = listener_done.clone();
+            move || {
+                thread::sleep(Duration::from_millis(20));
+                let acknowledgement = cached.put("disk", "SSD").unwrap();
+                (listener_done.load(Ordering::SeqCst), acknowledgement.block_on_done())
+            }
+        });
+
+        let (was_clear_done_before_put, status) = put.join().unwrap();
+        assert_eq!(CommandStatus::Accepted, clear.block_on_done());
+        assert!(!was_clear_done_before_put);
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
     }
 
-    #[tokio::test]
-    async fn get_multiple_keys_via_an_iterator_given_value_is_not_cloneable() {
-        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    #[test]
+    fn clear_barrier_blocks_a_racing_put_till_the_clear_is_complete() {
+        let listener_done = Arc::new(AtomicBool::new(false));
+        let cached = cache_with_slow_eviction_listener(listener_done.clone());
+        cached.put_sync("topic", "microservices").unwrap();
+
+        let clear = thread::spawn({
+            let cached = cached.clone();
+            move || cached.clear_barrier().unwrap()
+        });
+        thread::sleep(Duration::from_millis(20));
+        let acknowledgement = cached.put("disk", "SSD").unwrap();
+        let was_clear_done_before_put = listener_done.load(Ordering::SeqCst);
 
-        let acknowledgement =
-            cached.put("captain", Arc::new(Name { first: "John".to_string(), last: "Mcnamara".to_string() })).unwrap();
-        acknowledgement.handle().await;
+        assert_eq!(CommandStatus::Accepted, clear.join().unwrap());
+        assert!(was_clear_done_before_put);
+        assert_eq!(CommandStatus::Accepted, acknowledgement.block_on_done());
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
+    }
 
-        let acknowledgement =
-            cached.put("vice-captain", Arc::new(Name { first: "Martin".to_string(), last: "Trolley".to_string() })).unwrap();
-        acknowledgement.handle().await;
+    #[test]
+    fn clear_barrier_after_shutdown() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.shutdown();
 
-        let mut iterator = cached.multi_get_iterator(vec![&"captain", &"vice-captain", &"disk"]);
-        assert_eq!("John", iterator.next().unwrap().unwrap().first);
-        assert_eq!("Martin", iterator.next().unwrap().unwrap().first);
-        assert_eq!(None, iterator.next().unwrap());
+        assert!(cached.clear_barrier().is_err());
     }
 
-    #[tokio::test]
-    async fn map_multiple_keys_via_an_iterator() {
-        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    #[test]
+    fn clear_barrier_from_a_listener_on_the_thread_of_command_executor() {
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let cached_slot: Arc<OnceLock<Weak<CacheD<&str, &str>>>> = Arc::new(OnceLock::new());
+        let cached = Arc::new(CacheD::new(
+            ConfigBuilder::new(100, 10, 100)
+                .on_full(Box::new({
+                    let cached_slot = cached_slot.clone();
+                    let results = results.clone();
+                    move || {
+                        if let Some(cached) = cached_slot.get().and_then(Weak::upgrade) {
+                            results.lock().push(cached.clear_barrier().map_err(|error| error.is_on_executor_thread()));
+                        }
+                    }
+                }))
+                .build()
+        ));
+        cached_slot.set(Arc::downgrade(&cached)).unwrap();
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+        let status = cached.put_with_weight("topic", "microservices", 100).unwrap().block_on_done();
 
-        let acknowledgement =
-            cached.put("disk", "ssd").unwrap();
-        acknowledgement.handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(vec![Err(true)], *results.lock());
+    }
 
-        let acknowledgement =
-            cached.put("cache", "in-memory").unwrap();
-        acknowledgement.handle().await;
+    #[test]
+    fn put_from_a_listener_on_the_thread_of_command_executor_while_clear_barrier_waits() {
+        let (entered_sender, entered_receiver) = crossbeam_channel::bounded(1);
+        let (release_sender, release_receiver) = crossbeam_channel::bounded::<()>(0);
+        let put_results = Arc::new(Mutex::new(Vec::new()));
+        let cached_slot: Arc<OnceLock<Weak<CacheD<&str, &str>>>> = Arc::new(OnceLock::new());
+        let cached = Arc::new(CacheD::new(
+            ConfigBuilder::new(100, 10, 100)
+                .on_full(Box::new({
+                    let cached_slot = cached_slot.clone();
+                    let put_results = put_results.clone();
+                    move || {
+                        entered_sender.send(()).unwrap();
+                        release_receiver.recv().unwrap();
+                        if let Some(cached) = cached_slot.get().and_then(Weak::upgrade) {
+                            put_results.lock().push(cached.put("disk", "SSD").is_ok());
+                        }
+                    }
+                }))
+                .build()
+        ));
+        cached_slot.set(Arc::downgrade(&cached)).unwrap();
+
+        let acknowledgement = cached.put_with_weight("topic", "microservices", 100).unwrap();
+        entered_receiver.recv().unwrap();
+        let clear = thread::spawn({
+            let cached = cached.clone();
+            move || cached.clear_barrier().unwrap()
+        });
+        while !cached.clear_gate.is_locked_exclusive() {
+            thread::yield_now();
+        }
+        release_sender.send(()).unwrap();
 
-        let mut iterator = cached.multi_get_map_iterator(vec![&"topic", &"non-existing", &"cache", &"disk"], |value| value.to_uppercase());
-        assert_eq!(Some("MICROSERVICES".to_string()), iterator.next().unwrap());
-        assert_eq!(None, iterator.next().unwrap());
-        assert_eq!(Some("IN-MEMORY".to_string()), iterator.next().unwrap());
-        assert_eq!(Some("SSD".to_string()), iterator.next().unwrap());
-        assert_eq!(None, iterator.next());
+        assert_eq!(CommandStatus::Accepted, acknowledgement.block_on_done());
+        assert_eq!(CommandStatus::Accepted, clear.join().unwrap());
+        assert_eq!(vec![true], *put_results.lock());
     }
 
     #[tokio::test]
-    async fn total_weight_used() {
-        let cached = CacheD::new(test_config_builder().build());
-
-        let acknowledgement =
-            cached.put_with_weight("topic", "microservices", 50).unwrap();
-        acknowledgement.handle().await;
+    async fn clear_after_shutdown() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.shutdown();
 
-        assert_eq!(50, cached.total_weight_used());
+        assert!(cached.clear().is_err());
     }
+}
+
+#[cfg(test)]
+mod len_tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::ManualClock;
+    use crate::cache::config::ConfigBuilder;
 
     #[tokio::test]
-    async fn stats_summary() {
-        let cached = CacheD::new(test_config_builder().build());
+    async fn len_of_an_empty_cache() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
 
-        cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
-        cached.put_with_weight("cache", "cached", 10).unwrap().handle().await;
-        cached.delete("cache").unwrap().handle().await;
+        assert_eq!(0, cached.len());
+        assert_eq!(0, cached.live_len());
+        assert!(cached.is_empty());
+    }
 
-        let _ = cached.get(&"topic");
-        let _ = cached.get(&"cache");
+    #[tokio::test]
+    async fn len_includes_an_expired_but_not_removed_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .build()
+        );
 
-        let summary = cached.stats_summary();
-        assert_eq!(1, summary.get(&StatsType::CacheMisses).unwrap());
-        assert_eq!(1, summary.get(&StatsType::CacheHits).unwrap());
-        assert_eq!(60, summary.get(&StatsType::WeightAdded).unwrap());
-        assert_eq!(10, summary.get(&StatsType::WeightRemoved).unwrap());
-        assert_eq!(2, summary.get(&StatsType::KeysAdded).unwrap());
-        assert_eq!(1, summary.get(&StatsType::KeysDeleted).unwrap());
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).unwrap().handle().await;
+        let _ = cached.put("disk", "SSD").unwrap().handle().await;
+        assert_eq!(2, cached.len());
+        assert_eq!(2, cached.live_len());
 
-        assert_eq!(0, summary.get(&StatsType::KeysRejected).unwrap());
-        assert_eq!(0, summary.get(&StatsType::AccessAdded).unwrap());
-        assert_eq!(0, summary.get(&StatsType::AccessDropped).unwrap());
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(2, cached.len());
+        assert_eq!(1, cached.live_len());
+        assert!(!cached.is_empty());
     }
 }
 
 #[cfg(test)]
-mod shutdown_tests {
+mod weak_value_tests {
     use std::sync::Arc;
-    use std::sync::atomic::Ordering;
-    use std::thread;
-    use std::time::Duration;
-
-    use async_std::future::timeout;
-    use tokio::time::sleep;
 
     use crate::cache::cached::CacheD;
+    use crate::cache::command::CommandStatus;
     use crate::cache::config::ConfigBuilder;
-    use crate::cache::put_or_update::PutOrUpdateRequestBuilder;
-
-    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str> {
-        ConfigBuilder::new(100, 10, 100)
-    }
+    use crate::cache::stats::StatsType;
 
-    #[test]
-    fn put_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.shutdown();
+    #[tokio::test]
+    async fn get_weak_of_an_alive_value() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        let value = Arc::new("microservices".to_string());
 
-        let put_result = cached.put("storage", "cached");
-        assert!(put_result.is_err());
+        let status = cached.put_weak("topic", &value).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some(value.clone()), cached.get_weak(&"topic"));
+        assert_eq!(1, Arc::strong_count(&value));
     }
 
-    #[test]
-    fn put_with_weight_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.shutdown();
+    #[tokio::test]
+    async fn get_weak_after_the_last_strong_reference_is_dropped() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        let value = Arc::new("microservices".to_string());
+        let _ = cached.put_weak("topic", &value).unwrap().handle().await;
 
-        let put_result = cached.put_with_weight("storage", "cached", 10);
-        assert!(put_result.is_err());
-    }
+        drop(value);
+        assert_eq!(None, cached.get_weak(&"topic"));
+        assert!(!cached.contains_key(&"topic"));
+        assert_eq!(Some(1), cached.stats_summary().get(&StatsType::WeakValuesReclaimed));
 
-    #[test]
-    fn put_with_ttl_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.shutdown();
+        assert_eq!(None, cached.get_weak(&"topic"));
+        assert_eq!(Some(1), cached.stats_summary().get(&StatsType::WeakValuesReclaimed));
+    }
 
-        let put_result = cached.put_with_ttl("storage", "cached", Duration::from_secs(5));
-        assert!(put_result.is_err());
+    #[tokio::test]
+    async fn reclaim_the_keys_with_dropped_values() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        let topic = Arc::new("microservices".to_string());
+        let disk = Arc::new("SSD".to_string());
+        let _ = cached.put_weak("topic", &topic).unwrap().handle().await;
+        let _ = cached.put_weak("disk", &disk).unwrap().handle().await;
+
+        drop(topic);
+        assert_eq!(1, cached.reclaim_weak());
+        assert_eq!(1, cached.len());
+        assert_eq!(Some(disk), cached.get_weak(&"disk"));
+        assert_eq!(Some(1), cached.stats_summary().get(&StatsType::WeakValuesReclaimed));
     }
 
-    #[test]
-    fn put_with_weight_and_ttl_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.shutdown();
+    #[tokio::test]
+    async fn reclaiming_a_key_releases_its_weight() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        let value = Arc::new("microservices".to_string());
+        let _ = cached.put_weak("topic", &value).unwrap().handle().await;
+        assert!(cached.total_weight_used() > 0);
 
-        let put_result = cached.put_with_weight_and_ttl("storage", "cached", 10, Duration::from_secs(5));
-        assert!(put_result.is_err());
+        drop(value);
+        assert_eq!(1, cached.reclaim_weak());
+
+        let _ = cached.delete("non-existing").unwrap().handle().await;
+        assert_eq!(0, cached.total_weight_used());
     }
+}
 
-    #[test]
-    fn delete_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.shutdown();
+#[cfg(all(test, feature = "stream"))]
+mod multi_get_stream_tests {
+    use std::thread;
+    use std::time::Duration;
 
-        let delete_result = cached.delete("storage");
-        assert!(delete_result.is_err());
-    }
+    use futures::StreamExt;
 
-    #[test]
-    fn put_or_update_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.shutdown();
+    use crate::cache::cached::CacheD;
+    use crate::cache::config::ConfigBuilder;
 
-        let put_or_update_result = cached.put_or_update(PutOrUpdateRequestBuilder::new("storage").weight(10).build());
-        assert!(put_or_update_result.is_err());
+    #[tokio::test]
+    async fn collect_the_multi_get_stream() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.put("topic", "microservices").unwrap().handle().await;
+        cached.put("disk", "SSD").unwrap().handle().await;
+
+        let values: Vec<Option<&str>> = cached.multi_get_stream(vec![&"topic", &"non-existing", &"disk"]).collect().await;
+        assert_eq!(vec![Some("microservices"), None, Some("SSD")], values);
     }
 
     #[tokio::test]
-    async fn get_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.put("storage", "cached").unwrap().handle().await;
-        cached.shutdown();
+    async fn multi_get_stream_ends_on_shutdown() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.put("topic", "microservices").unwrap().handle().await;
 
-        let get_result = cached.get(&"storage");
-        assert_eq!(None, get_result);
+        let mut stream = cached.multi_get_stream(vec![&"topic", &"disk"]);
+        assert_eq!(Some(Some("microservices")), stream.next().await);
+
+        cached.shutdown();
+        assert_eq!(None, stream.next().await);
+        assert!(stream.was_interrupted());
     }
 
     #[tokio::test]
-    async fn get_ref_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.put("storage", "cached").unwrap().handle().await;
-        cached.shutdown();
+    async fn get_stream_yields_the_hits_before_the_slow_misses() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.put("topic", "microservices").unwrap().handle().await;
+        cached.put("disk", "SSD").unwrap().handle().await;
 
-        let get_result = cached.get_ref(&"storage");
-        assert!(get_result.is_none());
+        let load_fn = |key: &&str| {
+            thread::sleep(Duration::from_millis(10));
+            if *key == "cache" { "in-memory" } else { "NVMe" }
+        };
+        let items: Vec<(&str, Option<&str>)> = cached.get_stream(vec!["cache", "topic", "storage", "disk"], load_fn).collect().await;
+
+        assert_eq!(vec![
+            ("topic", Some("microservices")),
+            ("disk", Some("SSD")),
+            ("cache", Some("in-memory")),
+            ("storage", Some("NVMe")),
+        ], items);
+        assert_eq!(Some("in-memory"), cached.get(&"cache"));
+        assert_eq!(Some("NVMe"), cached.get(&"storage"));
     }
 
     #[tokio::test]
-    async fn map_get_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.put("storage", "cached").unwrap().handle().await;
-        cached.shutdown();
+    async fn get_stream_does_not_load_the_misses_after_shutdown() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.put("topic", "microservices").unwrap().handle().await;
 
-        let get_result = cached.map_get(&"storage", |value| value.to_uppercase());
-        assert!(get_result.is_none());
+        let mut stream = cached.get_stream(vec!["disk", "topic"], |_key| unreachable!());
+        assert_eq!(Some(("topic", Some("microservices"))), stream.next().await);
+
+        cached.shutdown();
+        assert_eq!(Some(("disk", None)), stream.next().await);
+        assert_eq!(None, stream.next().await);
     }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod metrics_tests {
+    use crate::cache::cached::CacheD;
+    use crate::cache::config::ConfigBuilder;
 
     #[tokio::test]
-    async fn map_get_ref_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.put("storage", "cached").unwrap().handle().await;
-        cached.shutdown();
+    async fn prometheus_metrics_after_puts_and_gets() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        let _ = cached.put_with_weight("topic", "microservices", 30).unwrap().handle().await;
+        let _ = cached.put_with_weight("disk", "SSD", 20).unwrap().handle().await;
+        let _ = cached.get(&"topic");
+        let _ = cached.get(&"topic");
+        let _ = cached.get(&"cache");
 
-        let get_result = cached.map_get_ref(&"storage", |stored_value| stored_value.value_ref().to_uppercase());
-        assert!(get_result.is_none());
+        let metrics = cached.prometheus_metrics("cached");
+        let lines = metrics.lines().collect::<Vec<_>>();
+
+        assert!(lines.contains(&"cached_hits_total 2"));
+        assert!(lines.contains(&"cached_misses_total 1"));
+        assert!(lines.contains(&"cached_keys_added_total 2"));
+        assert!(lines.contains(&"cached_weight_added_total 50"));
+        assert!(lines.contains(&"# TYPE cached_weight_used gauge"));
+        assert!(lines.contains(&"cached_weight_used 50"));
+        assert!(lines.contains(&"cached_keys 2"));
     }
+}
+
+#[cfg(test)]
+mod no_ttl_tests {
+    use std::time::Duration;
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::CommandStatus;
+    use crate::cache::config::ConfigBuilder;
+    use crate::cache::put_or_update::PutOrUpdateRequestBuilder;
 
     #[tokio::test]
-    async fn multi_get_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.put("storage", "cached").unwrap().handle().await;
-        cached.put("topic", "microservices").unwrap().handle().await;
+    async fn no_ttl_cache_does_not_spawn_the_ttl_ticker() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).no_ttl().build());
+        assert!(cached.ttl_ticker.has_stopped());
 
-        cached.shutdown();
+        let status = cached.put("topic", "microservices").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
 
-        let multi_get_result = cached.multi_get(vec![&"storage", &"topic"]);
-        assert!(multi_get_result.is_empty());
+        let report = cached.shutdown();
+        assert!(report.ttl_ticker_stopped());
     }
 
     #[tokio::test]
-    async fn multi_get_iterator_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.put("storage", "cached").unwrap().handle().await;
-        cached.put("topic", "microservices").unwrap().handle().await;
+    async fn put_with_ttl_in_a_no_ttl_cache() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).no_ttl().build());
 
-        cached.shutdown();
+        let error = cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).err().unwrap();
+        assert!(error.is_ttl_disabled());
+        assert!(error.to_string().contains("no_ttl"));
 
-        let mut iterator = cached.multi_get_iterator(vec![&"storage", &"topic"]);
-        assert!(iterator.next().is_none());
+        let error = cached.put_with_weight_and_ttl("topic", "microservices", 10, Duration::from_secs(5)).err().unwrap();
+        assert!(error.is_ttl_disabled());
+        assert_eq!(None, cached.get(&"topic"));
     }
 
     #[tokio::test]
-    async fn multi_get_map_iterator_after_shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
-        cached.put("storage", "cached").unwrap().handle().await;
-        cached.put("topic", "microservices").unwrap().handle().await;
+    async fn put_or_update_with_ttl_in_a_no_ttl_cache() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).no_ttl().build());
 
-        cached.shutdown();
+        let error = cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").time_to_live(Duration::from_secs(5)).build()).err().unwrap();
+        assert!(error.is_ttl_disabled());
 
-        let mut iterator = cached.multi_get_map_iterator(vec![&"storage", &"topic"], |value| { value.to_uppercase() });
-        assert!(iterator.next().is_none());
+        let status = cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").build()).unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
     }
 
-    #[tokio::test]
-    async fn shutdown() {
-        let cached = CacheD::new(test_config_builder().build());
+    #[test]
+    #[should_panic]
+    fn increment_with_ttl_in_a_no_ttl_cache() {
+        let cached: CacheD<&str, i64> = CacheD::new(ConfigBuilder::new(100, 10, 1000).no_ttl().build());
+        let _ = cached.increment_with_ttl("requests", 1, Duration::from_secs(5));
+    }
+}
 
-        cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
-        cached.put("cache", "cached").unwrap().handle().await;
+#[cfg(test)]
+mod fresh_get_tests {
+    use std::time::{Duration, SystemTime};
 
-        cached.shutdown();
-        assert!(cached.is_shutting_down.load(Ordering::Acquire));
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::ManualClock;
+    use crate::cache::config::ConfigBuilder;
+    use crate::cache::put_or_update::PutOrUpdateRequestBuilder;
+    use crate::cache::stats::StatsType;
 
-        let put_result = cached.put("storage", "cached");
-        assert!(put_result.is_err());
+    #[tokio::test]
+    async fn get_if_fresher_than_treats_an_older_value_as_a_miss() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).clock(Box::new(clock.clone())).build());
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        clock.advance(Duration::from_secs(10));
+        let _ = cached.put("disk", "SSD").unwrap().handle().await;
+
+        assert_eq!(None, cached.get_if_fresher_than(&"topic", Duration::from_secs(5)));
+        assert_eq!(Some("SSD"), cached.get_if_fresher_than(&"disk", Duration::from_secs(5)));
+        assert_eq!(Some("microservices"), cached.get_if_fresher_than(&"topic", Duration::from_secs(10)));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+
+        let stats_summary = cached.stats_summary();
+        assert_eq!(Some(3), stats_summary.get(&StatsType::CacheHits));
+        assert_eq!(Some(1), stats_summary.get(&StatsType::CacheMisses));
+    }
 
-        assert_eq!(0, cached.total_weight_used());
-        assert_eq!(None, cached.get(&"topic"));
-        assert_eq!(None, cached.get(&"cache"));
+    #[tokio::test]
+    async fn get_if_fresher_than_after_the_value_is_updated() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).clock(Box::new(clock.clone())).build());
+
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(None, cached.get_if_fresher_than(&"topic", Duration::from_secs(5)));
+
+        let _ = cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("cache").build()).unwrap().handle().await;
+        assert_eq!(Some("cache"), cached.get_if_fresher_than(&"topic", Duration::from_secs(5)));
     }
 
     #[tokio::test]
-    async fn concurrent_shutdown() {
-        let cached = Arc::new(CacheD::new(test_config_builder().build()));
-        cached.put_with_weight("topic", "microservices", 50).unwrap().handle().await;
-        cached.put("cache", "cached").unwrap().handle().await;
+    async fn get_if_fresher_than_for_a_non_existing_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        assert_eq!(None, cached.get_if_fresher_than(&"topic", Duration::from_secs(5)));
+    }
+}
 
-        let thread_handles = (1..=10).map(|_| {
-            thread::spawn({
-                let cached = cached.clone();
-                move || {
-                    cached.shutdown();
-                }
-            })
-        }).collect::<Vec<_>>();
-        for handle in thread_handles {
-            handle.join().unwrap();
-        }
+#[cfg(test)]
+mod time_to_live_remaining_tests {
+    use std::time::{Duration, SystemTime};
 
-        assert!(cached.is_shutting_down.load(Ordering::Acquire));
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::ManualClock;
+    use crate::cache::config::ConfigBuilder;
 
-        let put_result = cached.put("storage", "cached");
-        assert!(put_result.is_err());
+    fn cached(clock: &ManualClock) -> CacheD<&'static str, &'static str> {
+        CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .build()
+        )
     }
 
-    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn should_not_block_on_shutdown() {
-        let config_builder = ConfigBuilder::new(1000, 100, 1_000_000);
-        let cached = Arc::new(CacheD::new(config_builder.build()));
+    #[tokio::test]
+    async fn time_to_live_remaining_of_an_expiring_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = cached(&clock);
 
-        let task_handles = (1..=50).map(|index| {
-            let cached_clone = cached.clone();
-            tokio::spawn(
-                async move {
-                    let start_index = index * 10;
-                    let end_index = start_index + 10;
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(10)).unwrap().handle().await;
+        assert_eq!(Some(Duration::from_secs(10)), cached.time_to_live_remaining(&"topic"));
 
-                    for count in start_index..end_index {
-                        let put_result = cached_clone.put(count, count * 10);
-                        if let Ok(result) = put_result {
-                            timeout(Duration::from_secs(1), result.handle()).await.unwrap();
-                        }
-                        sleep(Duration::from_millis(2)).await;
-                    }
-                }
-            )
-        }).collect::<Vec<_>>();
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(Some(Duration::from_secs(6)), cached.time_to_live_remaining(&"topic"));
+    }
 
-        let cached_clone = cached.clone();
-        let shutdown_handle = tokio::spawn(
-            async move {
-                sleep(Duration::from_millis(8)).await;
-                cached_clone.shutdown();
-            }
-        );
-        for handle in task_handles {
-            handle.await.unwrap()
-        }
-        shutdown_handle.await.unwrap();
+    #[tokio::test]
+    async fn time_to_live_remaining_of_an_expired_but_resident_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = cached(&clock);
+
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(10)).unwrap().handle().await;
+        clock.advance(Duration::from_secs(15));
+
+        assert_eq!(Some(Duration::ZERO), cached.time_to_live_remaining(&"topic"));
     }
 
-    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn should_not_block_on_shutdown_with_limited_space() {
-        let config_builder = ConfigBuilder::new(1000, 100, 1000);
-        let cached = Arc::new(CacheD::new(config_builder.build()));
+    #[tokio::test]
+    async fn time_to_live_remaining_of_a_never_expiring_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = cached(&clock);
 
-        let task_handles = (1..=50).map(|index| {
-            let cached_clone = cached.clone();
-            tokio::spawn(
-                async move {
-                    let start_index = index * 10;
-                    let end_index = start_index + 10;
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
+        assert_eq!(None, cached.time_to_live_remaining(&"topic"));
+    }
 
-                    for count in start_index..end_index {
-                        let put_result = cached_clone.put(count, count * 10);
-                        if let Ok(result) = put_result {
-                            timeout(Duration::from_secs(1), result.handle()).await.unwrap();
-                        }
-                        sleep(Duration::from_millis(2)).await;
-                    }
-                }
-            )
-        }).collect::<Vec<_>>();
+    #[tokio::test]
+    async fn time_to_live_remaining_of_a_missing_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = cached(&clock);
 
-        let cached_clone = cached.clone();
-        let shutdown_handle = tokio::spawn(
-            async move {
-                sleep(Duration::from_millis(8)).await;
-                cached_clone.shutdown();
-            }
-        );
-        for handle in task_handles {
-            handle.await.unwrap()
-        }
-        shutdown_handle.await.unwrap();
+        assert_eq!(None, cached.time_to_live_remaining(&"topic"));
     }
 }
 
 #[cfg(test)]
-mod put_or_update_tests {
-    use std::ops::Add;
-    use std::time::Duration;
+mod sliding_ttl_tests {
+    use std::time::{Duration, SystemTime};
 
     use crate::cache::cached::CacheD;
-    use crate::cache::cached::put_or_update_tests::setup::UnixEpochClock;
-    use crate::cache::clock::ClockType;
+    use crate::cache::clock::ManualClock;
     use crate::cache::config::ConfigBuilder;
-    use crate::cache::put_or_update::PutOrUpdateRequestBuilder;
-    use crate::cache::types::Weight;
-
-    mod setup {
-        use std::time::SystemTime;
-
-        use crate::cache::clock::Clock;
 
-        #[derive(Clone)]
-        pub(crate) struct UnixEpochClock;
+    fn cached(clock: &ManualClock) -> CacheD<&'static str, &'static str> {
+        CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .sliding_ttl(Duration::from_secs(10))
+                .build()
+        )
+    }
 
-        impl Clock for UnixEpochClock {
-            fn now(&self) -> SystemTime {
-                SystemTime::UNIX_EPOCH
-            }
+    #[tokio::test]
+    async fn repeated_gets_keep_the_key_alive_past_its_original_expiry() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = cached(&clock);
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(10)).unwrap().handle().await;
+
+        for _ in 0..3 {
+            clock.advance(Duration::from_secs(6));
+            assert_eq!(Some("microservices"), cached.get(&"topic"));
         }
+        assert_eq!(Some(Duration::from_secs(10)), cached.time_to_live_remaining(&"topic"));
     }
 
-    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str> {
-        ConfigBuilder::new(100, 10, 100)
+    #[tokio::test]
+    async fn a_gap_longer_than_the_window_lets_the_key_expire() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = cached(&clock);
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(10)).unwrap().handle().await;
+
+        clock.advance(Duration::from_secs(6));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+
+        clock.advance(Duration::from_secs(11));
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(Some(Duration::ZERO), cached.time_to_live_remaining(&"topic"));
     }
 
     #[tokio::test]
-    async fn put_or_update_a_non_existing_key_value() {
-        let cached = CacheD::new(test_config_builder().build());
+    async fn get_does_not_refresh_the_expiry_above_half_of_the_window() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = cached(&clock);
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(10)).unwrap().handle().await;
+
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(Some(Duration::from_secs(6)), cached.time_to_live_remaining(&"topic"));
+    }
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").build()).unwrap();
-        acknowledgement.handle().await;
+    #[tokio::test]
+    async fn get_ref_refreshes_the_expiry_in_the_ttl_ticker() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = cached(&clock);
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(10)).unwrap().handle().await;
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
+        clock.advance(Duration::from_secs(6));
+        let key_id = {
+            let key_value_ref = cached.get_ref(&"topic").unwrap();
+            key_value_ref.value().key_id()
+        };
 
-        assert_eq!("microservices", stored_value.value());
+        let expire_after = cached.peek_ref(&"topic").unwrap().value().expire_after().unwrap();
+        assert_eq!(Some(Duration::from_secs(10)), cached.time_to_live_remaining(&"topic"));
+        assert_eq!(Some(expire_after), cached.ttl_ticker.get(&key_id, &expire_after));
     }
 
     #[tokio::test]
-    async fn put_or_update_a_non_existing_key_value_with_weight() {
-        let cached = CacheD::new(test_config_builder().build());
+    async fn get_does_not_add_an_expiry_to_a_never_expiring_key() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = cached(&clock);
+        let _ = cached.put("topic", "microservices").unwrap().handle().await;
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").weight(33).build()).unwrap();
-        acknowledgement.handle().await;
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(None, cached.time_to_live_remaining(&"topic"));
+    }
+}
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+#[cfg(test)]
+mod expired_read_behavior_tests {
+    use std::time::{Duration, SystemTime};
 
-        assert_eq!("microservices", stored_value.value());
-        assert_eq!(Some(33), cached.admission_policy.weight_of(&key_id));
+    use crate::cache::cached::{CacheD, ExpiredError};
+    use crate::cache::clock::ManualClock;
+    use crate::cache::config::{ConfigBuilder, ExpiredReadBehavior};
+    use crate::cache::stats::StatsType;
+
+    async fn cached_with_an_expired_key(expired_read_behavior: ExpiredReadBehavior) -> CacheD<&'static str, &'static str> {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .expired_read_behavior(expired_read_behavior)
+                .build()
+        );
+        let _ = cached.put_with_ttl("topic", "microservices", Duration::from_secs(5)).unwrap().handle().await;
+        clock.advance(Duration::from_secs(10));
+        cached
     }
 
     #[tokio::test]
-    async fn put_or_update_a_non_existing_key_value_with_time_to_live() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
-        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+    async fn miss_on_an_expired_key() {
+        let cached = cached_with_an_expired_key(ExpiredReadBehavior::Miss).await;
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("microservices").weight(10).time_to_live(Duration::from_secs(10)).build()).unwrap();
-        acknowledgement.handle().await;
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(Ok(None), cached.get_result(&"topic"));
+        assert!(cached.get_ref(&"topic").is_none());
+        assert_eq!(Some(3), cached.stats_summary().get(&StatsType::CacheMisses));
+    }
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+    #[tokio::test]
+    async fn serve_the_stale_value_of_an_expired_key_once() {
+        let cached = cached_with_an_expired_key(ExpiredReadBehavior::ServeStaleOnce).await;
 
-        assert_eq!(Some(clock.now().add(Duration::from_secs(10))), stored_value.expire_after());
-        assert_eq!("microservices", stored_value.value());
-        assert_eq!(Some(10), cached.admission_policy.weight_of(&key_id));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(Ok(None), cached.get_result(&"topic"));
+        assert!(!cached.contains_key(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn serve_the_stale_value_of_an_expired_key_once_by_reference() {
+        let cached = cached_with_an_expired_key(ExpiredReadBehavior::ServeStaleOnce).await;
+
+        {
+            let key_value_ref = cached.get_ref(&"topic").unwrap();
+            assert_eq!(&"microservices", key_value_ref.value().value_ref());
+        }
+        assert!(cached.get_ref(&"topic").is_none());
+        assert_eq!(None, cached.get(&"topic"));
     }
 
     #[tokio::test]
-    async fn update_the_value_of_an_existing_key() {
-        let cached = CacheD::new(test_config_builder().build());
+    async fn error_on_an_expired_key() {
+        let cached = cached_with_an_expired_key(ExpiredReadBehavior::Error).await;
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+        assert_eq!(Err(ExpiredError), cached.get_result(&"topic"));
+        assert_eq!(Err(ExpiredError), cached.get_result(&"topic"));
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(Ok(None), cached.get_result(&"non-existing"));
+    }
+}
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("storage engine").build()).unwrap();
-        acknowledgement.handle().await;
+#[cfg(test)]
+mod try_put_tests {
+    use std::sync::Arc;
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
+    use crossbeam_channel::Receiver;
+    use parking_lot::Mutex;
 
-        assert_eq!("storage engine", stored_value.value());
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::CommandStatus;
+    use crate::cache::command::error::TryPutError;
+    use crate::cache::config::ConfigBuilder;
+
+    /// Returns a config builder whose `CommandExecutor` signals on the returned receiver, and waits for the `executor_lock`, on every admission decision.
+    fn blocking_executor(executor_lock: Arc<Mutex<()>>) -> (ConfigBuilder<&'static str, &'static str>, Receiver<()>) {
+        let (entered_sender, entered_receiver) = crossbeam_channel::unbounded();
+        let builder = ConfigBuilder::new(100, 10, 1000)
+            .command_buffer_size(1)
+            .admission_observer(Box::new(move |_decision| {
+                let _ = entered_sender.send(());
+                drop(executor_lock.lock());
+            }));
+        (builder, entered_receiver)
     }
 
     #[tokio::test]
-    async fn update_the_weight_of_an_existing_key() {
-        let cached = CacheD::new(test_config_builder().build());
-
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+    async fn try_put_a_key_value() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").weight(29).build()).unwrap();
-        acknowledgement.handle().await;
+        let status = cached.try_put("topic", "microservices").unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+    }
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+    #[tokio::test]
+    async fn try_put_on_a_full_command_channel() {
+        let executor_lock = Arc::new(Mutex::new(()));
+        let (builder, entered) = blocking_executor(executor_lock.clone());
+        let cached = CacheD::new(builder.build());
+
+        let held = executor_lock.lock();
+        let executing = cached.try_put("topic", "microservices").unwrap();
+        entered.recv().unwrap();
+        let queued = cached.try_put("disk", "SSD").unwrap();
+
+        let error = cached.try_put("cache", "in-memory").err().unwrap();
+        assert!(matches!(error, TryPutError::ChannelFull));
+
+        drop(held);
+        assert_eq!(CommandStatus::Accepted, executing.handle().await);
+        assert_eq!(CommandStatus::Accepted, queued.handle().await);
+        assert_eq!(None, cached.get(&"cache"));
+    }
 
-        assert_eq!("microservices", stored_value.value());
-        assert_eq!(Some(29), cached.admission_policy.weight_of(&key_id));
+    #[tokio::test]
+    async fn try_put_on_a_full_adaptive_command_channel() {
+        let executor_lock = Arc::new(Mutex::new(()));
+        let (builder, entered) = blocking_executor(executor_lock.clone());
+        let cached = CacheD::new(builder.adaptive_command_buffer(2).build());
+
+        let held = executor_lock.lock();
+        let _ = cached.try_put("topic", "microservices").unwrap();
+        entered.recv().unwrap();
+        let _ = cached.try_put("disk", "SSD").unwrap();
+        let _ = cached.try_put("storage", "NVMe").unwrap();
+
+        let error = cached.try_put("cache", "in-memory").err().unwrap();
+        assert!(matches!(error, TryPutError::ChannelFull));
+        drop(held);
     }
 
     #[tokio::test]
-    async fn update_the_time_to_live_of_an_existing_key_with_original_key_not_having_time_to_live() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
-        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+    async fn try_put_after_shutdown() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.shutdown();
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+        let error = cached.try_put("topic", "microservices").err().unwrap();
+        assert!(matches!(error, TryPutError::Send(_)));
+    }
+}
 
-        let original_weight = weight_of(&cached, "topic");
+#[cfg(test)]
+mod rename_tests {
+    use std::time::{Duration, SystemTime};
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").time_to_live(Duration::from_secs(100)).build()).unwrap();
-        acknowledgement.handle().await;
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::ManualClock;
+    use crate::cache::config::{ConfigBuilder, PutMode};
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+    #[tokio::test]
+    async fn rename_a_key_with_time_to_live() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 100)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .build()
+        );
+        cached.put_with_weight_and_ttl("topic", "microservices", 5, Duration::from_secs(300)).unwrap().handle().await;
+        clock.advance(Duration::from_secs(100));
+        let weight_used = cached.total_weight_used();
 
-        assert_eq!("microservices", stored_value.value());
-        assert_ne!(original_weight, cached.admission_policy.weight_of(&key_id));
+        assert!(cached.rename(&"topic", "subject"));
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(Some("microservices"), cached.get(&"subject"));
+        assert_eq!(Some(Duration::from_secs(200)), cached.time_to_live_remaining(&"subject"));
+        assert_eq!(weight_used, cached.total_weight_used());
+    }
 
-        assert_eq!(Some(clock.now().add(Duration::from_secs(100))), stored_value.expire_after());
-        assert_eq!(stored_value.expire_after(), cached.ttl_ticker.get(&key_id, &stored_value.expire_after().unwrap()));
+    #[tokio::test]
+    async fn rename_a_non_existing_key() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+
+        assert!(!cached.rename(&"topic", "subject"));
+        assert_eq!(None, cached.get(&"subject"));
     }
 
     #[tokio::test]
-    async fn remove_the_time_to_live_of_an_existing_key() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
-        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+    async fn rename_replaces_the_existing_new_key() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).put_mode(PutMode::Overwrite).build());
+        cached.put_with_weight("topic", "microservices", 5).unwrap().handle().await;
+        cached.put_with_weight("subject", "cached", 3).unwrap().handle().await;
 
-        let acknowledgement =
-            cached.put_with_ttl("topic", "microservices", Duration::from_secs(100)).unwrap();
-        acknowledgement.handle().await;
+        assert!(cached.rename(&"topic", "subject"));
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(Some("microservices"), cached.get(&"subject"));
+        assert_eq!(5, cached.total_weight_used());
+    }
 
-        let original_weight = weight_of(&cached, "topic");
+    #[tokio::test]
+    async fn rename_rejects_the_existing_new_key_with_insert_only() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).put_mode(PutMode::InsertOnly).build());
+        cached.put_with_weight("topic", "microservices", 5).unwrap().handle().await;
+        cached.put_with_weight("subject", "cached", 3).unwrap().handle().await;
+
+        assert!(!cached.rename(&"topic", "subject"));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(Some("cached"), cached.get(&"subject"));
+    }
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").remove_time_to_live().build()).unwrap();
-        acknowledgement.handle().await;
+    #[tokio::test]
+    async fn rename_rejected_by_the_admission_retains_the_old_key() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+        cached.admission_policy.suspend_eviction();
+        cached.put_with_weight("topic", "microservices", 60).unwrap().handle().await;
+        cached.put_with_weight("disk", "SSD", 60).unwrap().handle().await;
+        cached.admission_policy.resume_eviction();
+        cached.prime_frequency(&"disk", 5);
+
+        assert!(!cached.rename(&"topic", "subject"));
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(None, cached.get(&"subject"));
+        assert_eq!(120, cached.total_weight_used());
+    }
+}
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+#[cfg(test)]
+mod command_executor_threads_tests {
+    use std::sync::Arc;
+    use std::thread;
 
-        assert_eq!("microservices", stored_value.value());
-        assert_ne!(original_weight, cached.admission_policy.weight_of(&key_id));
+    use crate::cache::cached::CacheD;
+    use crate::cache::config::ConfigBuilder;
 
-        assert_eq!(None, stored_value.expire_after());
+    #[test]
+    fn concurrent_puts_and_deletes_of_overlapping_keys_with_many_threads() {
+        let cached: Arc<CacheD<u64, u64>> = Arc::new(CacheD::new(
+            ConfigBuilder::new(100, 10, 20).command_executor_threads(4).build()
+        ));
+
+        let handles = (0..8).map(|thread_index| {
+            let cached = cached.clone();
+            thread::spawn(move || {
+                let acknowledgements = (0..500).map(|index| {
+                    let key = (thread_index * 7 + index) % 40;
+                    if index % 3 == 0 {
+                        cached.delete(key).unwrap()
+                    } else {
+                        cached.put_with_weight(key, index, 1).unwrap()
+                    }
+                }).collect::<Vec<_>>();
+                acknowledgements.iter().for_each(|acknowledgement| { acknowledgement.block_on_done(); });
+            })
+        }).collect::<Vec<_>>();
+        handles.into_iter().for_each(|handle| handle.join().unwrap());
+
+        assert!(cached.total_weight_used() <= 20);
+        assert_eq!(cached.store.len() as i64, cached.total_weight_used());
+        cached.store.for_each(|_, stored_value| assert!(cached.admission_policy.contains(&stored_value.key_id())));
     }
 
     #[tokio::test]
-    async fn add_the_time_to_live_of_an_existing_key() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
-        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+    async fn put_followed_by_delete_without_awaiting_the_put_with_many_threads() {
+        let cached = CacheD::new(ConfigBuilder::new(1000, 100, 1000).command_executor_threads(4).build());
 
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
-
-        let original_weight = weight_of(&cached, "topic");
+        let acknowledgements = (0..200).map(|key| {
+            cached.put_with_weight(key, key * 10, 1).unwrap();
+            cached.delete(key).unwrap()
+        }).collect::<Vec<_>>();
+        for acknowledgement in acknowledgements {
+            acknowledgement.handle().await;
+        }
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").time_to_live(Duration::from_secs(120)).build()).unwrap();
-        acknowledgement.handle().await;
+        (0..200).for_each(|key| assert_eq!(None, cached.get(&key)));
+        assert_eq!(0, cached.store.len());
+        assert_eq!(0, cached.total_weight_used());
+    }
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+    #[tokio::test]
+    async fn clear_after_puts_to_all_the_threads() {
+        let cached = CacheD::new(ConfigBuilder::new(1000, 100, 1000).command_executor_threads(4).build());
 
-        assert_eq!("microservices", stored_value.value());
-        assert_ne!(original_weight, cached.admission_policy.weight_of(&key_id));
+        (0..200).for_each(|key| { cached.put_with_weight(key, key * 10, 1).unwrap(); });
+        cached.clear().unwrap().handle().await;
 
-        assert_eq!(Some(clock.now().add(Duration::from_secs(120))), stored_value.expire_after());
-        assert_eq!(stored_value.expire_after(), cached.ttl_ticker.get(&key_id, &stored_value.expire_after().unwrap()));
+        (0..200).for_each(|key| assert_eq!(None, cached.get(&key)));
+        assert_eq!(0, cached.total_weight_used());
     }
 
     #[tokio::test]
-    async fn update_the_value_and_time_to_live_of_an_existing_key() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
-        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
-
-        let acknowledgement =
-            cached.put("topic", "microservices").unwrap();
-        acknowledgement.handle().await;
+    async fn shutdown_with_many_threads() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).command_executor_threads(4).build());
+        cached.put_with_weight("topic", "microservices", 5).unwrap().handle().await;
 
-        let original_weight = weight_of(&cached, "topic");
+        let report = cached.shutdown();
+        assert!(report.executor_stopped());
+        assert!(cached.put("disk", "SSD").is_err());
+        assert_eq!(None, cached.get(&"topic"));
+    }
+}
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("storage engine").time_to_live(Duration::from_secs(100)).build()).unwrap();
-        acknowledgement.handle().await;
+#[cfg(test)]
+mod delete_all_tests {
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::acknowledgement::CommandAcknowledgement;
+    use crate::cache::command::CommandStatus;
+    use crate::cache::config::ConfigBuilder;
+    use crate::cache::stats::StatsType;
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+    #[tokio::test]
+    async fn delete_all_the_keys() {
+        let cached = CacheD::new(ConfigBuilder::new(1000, 100, 100_000).build());
+
+        let acknowledgements = cached.put_all((0..50).map(|key| (key, key * 10)).collect())
+            .into_iter()
+            .map(|result| result.unwrap())
+            .collect();
+        let statuses = CommandAcknowledgement::handle_all(acknowledgements).await;
+        assert!(statuses.iter().all(|status| *status == CommandStatus::Accepted));
+        let weight_used = cached.total_weight_used();
+        assert!(weight_used > 0);
+
+        let results = cached.delete_all((0..50).collect());
+        assert_eq!(50, results.len());
+        for result in results {
+            assert_eq!(CommandStatus::Accepted, result.unwrap().handle().await);
+        }
 
-        assert_eq!("storage engine", stored_value.value());
-        assert_ne!(original_weight, cached.admission_policy.weight_of(&key_id));
+        assert!((0..50).all(|key| cached.get(&key).is_none()));
+        assert_eq!(0, cached.total_weight_used());
 
-        assert_eq!(Some(clock.now().add(Duration::from_secs(100))), stored_value.expire_after());
-        assert_eq!(stored_value.expire_after(), cached.ttl_ticker.get(&key_id, &stored_value.expire_after().unwrap()));
+        let summary = cached.stats_summary();
+        assert_eq!(Some(50), summary.get(&StatsType::KeysDeleted));
+        assert_eq!(Some(weight_used as u64), summary.get(&StatsType::WeightRemoved));
     }
 
     #[tokio::test]
-    async fn update_the_value_and_remove_time_to_live_of_an_existing_key() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
-        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+    async fn delete_all_with_absent_keys() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.put("topic", "microservices").unwrap().handle().await;
 
-        let acknowledgement =
-            cached.put_with_ttl("topic", "microservices", Duration::from_secs(100)).unwrap();
-        acknowledgement.handle().await;
+        for result in cached.delete_all(vec!["topic", "non-existing"]) {
+            assert_eq!(CommandStatus::Accepted, result.unwrap().handle().await);
+        }
+        assert_eq!(None, cached.get(&"topic"));
+        assert_eq!(0, cached.total_weight_used());
+    }
 
-        let original_weight = weight_of(&cached, "topic");
+    #[test]
+    fn delete_all_after_shutdown() {
+        let cached: CacheD<&str, &str> = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.shutdown();
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("storage engine").remove_time_to_live().build()).unwrap();
-        acknowledgement.handle().await;
+        let results = cached.delete_all(vec!["topic", "disk"]);
+        assert_eq!(2, results.len());
+        assert!(results.iter().all(|result| result.is_err()));
+    }
+}
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+#[cfg(test)]
+mod put_all_tests {
+    use crate::cache::cached::CacheD;
+    use crate::cache::command::{CommandStatus, RejectionReason};
+    use crate::cache::command::acknowledgement::CommandAcknowledgement;
+    use crate::cache::config::ConfigBuilder;
 
-        let new_weight = cached.admission_policy.weight_of(&key_id);
-        assert_eq!("storage engine", stored_value.value());
-        assert_ne!(original_weight, new_weight);
-        assert!(new_weight < original_weight);
+    #[tokio::test]
+    async fn put_all_the_entries() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
 
-        assert_eq!(None, stored_value.expire_after());
+        let results = cached.put_all(vec![("topic", "microservices"), ("disk", "SSD"), ("cache", "cached")]);
+        assert_eq!(3, results.len());
+        for result in results {
+            assert_eq!(CommandStatus::Accepted, result.unwrap().handle().await);
+        }
+
+        assert_eq!(Some("microservices"), cached.get(&"topic"));
+        assert_eq!(Some("SSD"), cached.get(&"disk"));
+        assert_eq!(Some("cached"), cached.get(&"cache"));
     }
 
     #[tokio::test]
-    async fn update_the_value_weight_and_remove_time_to_live_of_an_existing_key() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
-        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+    async fn put_all_and_handle_all() {
+        let cached = CacheD::new(ConfigBuilder::new(1000, 100, 100_000).build());
 
-        let acknowledgement =
-            cached.put_with_ttl("topic", "microservices", Duration::from_secs(100)).unwrap();
-        acknowledgement.handle().await;
+        let acknowledgements = cached.put_all((0..100).map(|key| (key, key * 10)).collect())
+            .into_iter()
+            .map(|result| result.unwrap())
+            .collect();
+        let statuses = CommandAcknowledgement::handle_all(acknowledgements).await;
 
-        let original_weight = weight_of(&cached, "topic");
+        assert!(statuses.iter().all(|status| *status == CommandStatus::Accepted));
+        assert!((0..100).all(|key| cached.get(&key) == Some(key * 10)));
+    }
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").value("storage engine").weight(300).remove_time_to_live().build()).unwrap();
-        acknowledgement.handle().await;
+    #[tokio::test]
+    async fn put_all_returns_the_results_in_the_order_of_the_entries() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.put("disk", "HDD").unwrap().handle().await;
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+        let results = cached.put_all(vec![("topic", "microservices"), ("disk", "SSD")]);
+        let mut statuses = Vec::new();
+        for result in results {
+            statuses.push(result.unwrap().handle().await);
+        }
 
-        let new_weight = cached.admission_policy.weight_of(&key_id);
-        assert_eq!("storage engine", stored_value.value());
-        assert_ne!(original_weight, new_weight);
-        assert_eq!(Some(300), new_weight);
+        assert_eq!(vec![CommandStatus::Accepted, CommandStatus::Rejected(RejectionReason::KeyAlreadyExists)], statuses);
+        assert_eq!(Some("HDD"), cached.get(&"disk"));
+    }
 
-        assert_eq!(None, stored_value.expire_after());
+    #[test]
+    fn put_all_after_shutdown() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.shutdown();
+
+        let results = cached.put_all(vec![("topic", "microservices")]);
+        assert!(results[0].is_err());
     }
 
-    #[tokio::test]
-    async fn update_the_time_to_live_of_an_existing_key() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
-        let cached = CacheD::new(test_config_builder().clock(clock.clone_box()).build());
+    #[test]
+    #[should_panic]
+    fn put_all_with_a_weight_that_is_not_greater_than_zero() {
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .weight_calculation_fn(Box::new(|key: &&str, _value: &&str, _is_time_to_live_specified| if *key == "disk" { 0 } else { 10 }))
+                .build()
+        );
+        let _ = cached.put_all(vec![("topic", "microservices"), ("disk", "SSD")]);
+    }
+}
 
-        let acknowledgement =
-            cached.put_with_ttl("topic", "microservices", Duration::from_secs(100)).unwrap();
-        acknowledgement.handle().await;
+#[cfg(test)]
+mod iter_tests {
+    use std::time::{Duration, SystemTime};
 
-        let original_weight = weight_of(&cached, "topic");
+    use crate::cache::cached::CacheD;
+    use crate::cache::clock::ManualClock;
+    use crate::cache::config::ConfigBuilder;
+    use crate::cache::stats::StatsType;
 
-        let acknowledgement =
-            cached.put_or_update(PutOrUpdateRequestBuilder::new("topic").time_to_live(Duration::from_secs(500)).build()).unwrap();
-        acknowledgement.handle().await;
+    #[tokio::test]
+    async fn iter_the_live_entries() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .clock(Box::new(clock.clone()))
+                .ttl_tick_duration(Duration::from_secs(300))
+                .build()
+        );
+        cached.put("topic", "microservices").unwrap().handle().await;
+        cached.put_with_ttl("disk", "SSD", Duration::from_secs(5)).unwrap().handle().await;
+        cached.put_with_ttl("cache", "cached", Duration::from_secs(60)).unwrap().handle().await;
+        cached.put_with_ttl("memory", "RAM", Duration::from_secs(5)).unwrap().handle().await;
+        clock.advance(Duration::from_secs(10));
 
-        let value = cached.get_ref(&"topic");
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+        let mut entries = cached.iter().collect::<Vec<_>>();
+        entries.sort();
 
-        let new_weight = cached.admission_policy.weight_of(&key_id);
-        assert_eq!("microservices", stored_value.value());
-        assert_eq!(original_weight, new_weight);
+        assert_eq!(vec![("cache", "cached"), ("topic", "microservices")], entries);
+        assert_eq!(0, cached.stats_summary().get(&StatsType::CacheHits).unwrap());
     }
 
-    fn weight_of(cached: &CacheD<&str, &str>, key: &'static str) -> Option<Weight> {
-        let value = cached.get_ref(&key);
-        let value_ref = value.unwrap();
-        let stored_value = value_ref.value();
-        let key_id = stored_value.key_id();
+    #[tokio::test]
+    async fn iter_after_shutdown() {
+        let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+        cached.put("topic", "microservices").unwrap().handle().await;
+        cached.shutdown();
 
-        cached.admission_policy.weight_of(&key_id)
+        assert_eq!(0, cached.iter().count());
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,7 @@
-use std::time::SystemTime;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use parking_lot::Mutex;
 
 /// Defines a boxed pointer to [`Clock`].
 pub type ClockType = Box<dyn Clock + Send + Sync>;
@@ -62,4 +65,93 @@ impl Default for SystemClock {
     fn default() -> Self {
         SystemClock::new()
     }
-}
\ No newline at end of file
+}
+
+/// ManualClock is an implementation of the [`Clock`] trait whose time only moves when it is advanced or set,
+/// which makes it useful for the deterministic tests of the keys with `time_to_live`.
+///
+/// The clones of a `ManualClock` share the time, so the instance passed to [`crate::cache::config::ConfigBuilder::clock`]
+/// can be advanced by retaining a clone:
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use tinylfu_cached::cache::clock::ManualClock;
+///
+/// let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+/// let cloned = clock.clone();
+/// clock.advance(Duration::from_secs(5));
+///
+/// assert_eq!(SystemTime::UNIX_EPOCH + Duration::from_secs(5), cloned.current());
+/// ```
+#[derive(Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<SystemTime>>,
+}
+
+impl ManualClock {
+    /// Creates a new instance of ManualClock, starting at `now`.
+    pub fn new(now: SystemTime) -> ManualClock {
+        ManualClock { now: Arc::new(Mutex::new(now)) }
+    }
+
+    /// Moves the time of the clock (and all its clones) forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock();
+        *now += duration;
+    }
+
+    /// Sets the time of the clock (and all its clones) to `now`.
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock() = now;
+    }
+
+    /// Returns the current time of the clock.
+    pub fn current(&self) -> SystemTime {
+        *self.now.lock()
+    }
+
+    /// Creates a boxed pointer to [`Clock`] that shares the time with this clock.
+    pub fn boxed(&self) -> ClockType {
+        Box::new(self.clone())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::clock::{Clock, ManualClock};
+
+    #[test]
+    fn advance_a_manual_clock() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        clock.advance(Duration::from_secs(10));
+
+        assert_eq!(SystemTime::UNIX_EPOCH + Duration::from_secs(10), clock.now());
+    }
+
+    #[test]
+    fn set_a_manual_clock() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        clock.set(SystemTime::UNIX_EPOCH + Duration::from_secs(30));
+
+        assert_eq!(SystemTime::UNIX_EPOCH + Duration::from_secs(30), clock.now());
+    }
+
+    #[test]
+    fn boxed_manual_clock_shares_the_time() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let boxed = clock.boxed();
+        let cloned = boxed.clone_box();
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(SystemTime::UNIX_EPOCH + Duration::from_secs(5), boxed.now());
+        assert_eq!(SystemTime::UNIX_EPOCH + Duration::from_secs(5), cloned.now());
+    }
+}
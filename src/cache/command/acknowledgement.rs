@@ -1,9 +1,10 @@
+use std::any::Any;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::{Arc};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::task::{Context, Poll, Waker};
-use parking_lot::Mutex;
+use parking_lot::{Condvar, Mutex};
 use crate::cache::command::{CommandStatus, RejectionReason};
 
 /// The execution of every write operation is returned a `CommandAcknowledgement` wrapped inside [`crate::cache::command::command_executor::CommandSendResult`].
@@ -24,6 +25,7 @@ use crate::cache::command::{CommandStatus, RejectionReason};
 /// ```
 pub struct CommandAcknowledgement {
     handle: CommandAcknowledgementHandle,
+    previous_value: Mutex<Option<Box<dyn Any + Send + Sync>>>,
 }
 
 /// CommandAcknowledgementHandle implements [`std::future::Future`] and returns a [`crate::cache::command::CommandStatus`]
@@ -31,14 +33,18 @@ pub struct CommandAcknowledgement {
 /// The initial status in the `CommandAcknowledgementHandle` is `CommandStatus::Pending`
 ///
 /// The status gets updated when the command is executed by the `crate::cache::command::command_executor::CommandExecutor`.
+///
+/// The handle can be awaited by more than one task (for example, by the tasks sharing the `Arc<CommandAcknowledgement>`).
+/// Every awaiting task is woken up when the command is executed, and all of them receive the same `CommandStatus`.
 pub struct CommandAcknowledgementHandle {
     done: AtomicBool,
     status: Arc<Mutex<CommandStatus>>,
     waker_state: Arc<Mutex<WakerState>>,
+    done_signal: Condvar,
 }
 
 pub(crate) struct WakerState {
-    waker: Option<Waker>,
+    wakers: Vec<Waker>,
 }
 
 /// CommandAcknowledgement provides a `handle()` method  that returns a reference to the `CommandAcknowledgementHandle`
@@ -50,9 +56,11 @@ impl CommandAcknowledgement {
                     done: AtomicBool::new(false),
                     status: Arc::new(Mutex::new(CommandStatus::Pending)),
                     waker_state: Arc::new(Mutex::new(WakerState {
-                        waker: None
+                        wakers: Vec::new()
                     })),
+                    done_signal: Condvar::new(),
                 },
+                previous_value: Mutex::new(None),
             }
         )
     }
@@ -63,9 +71,11 @@ impl CommandAcknowledgement {
                     done: AtomicBool::new(true),
                     status: Arc::new(Mutex::new(CommandStatus::Accepted)),
                     waker_state: Arc::new(Mutex::new(WakerState {
-                        waker: None
+                        wakers: Vec::new()
                     })),
+                    done_signal: Condvar::new(),
                 },
+                previous_value: Mutex::new(None),
             }
         )
     }
@@ -76,9 +86,11 @@ impl CommandAcknowledgement {
                     done: AtomicBool::new(true),
                     status: Arc::new(Mutex::new(CommandStatus::Rejected(reason))),
                     waker_state: Arc::new(Mutex::new(WakerState {
-                        waker: None
+                        wakers: Vec::new()
                     })),
+                    done_signal: Condvar::new(),
                 },
+                previous_value: Mutex::new(None),
             }
         )
     }
@@ -88,39 +100,131 @@ impl CommandAcknowledgement {
         self.handle.done(status);
     }
 
+    /// Holds the value removed by the command before invoking `done()`, so that the value is available once the command status is.
+    pub(crate) fn done_with_previous<Value>(&self, status: CommandStatus, previous_value: Option<Value>)
+        where Value: Send + Sync + 'static {
+        if let Some(value) = previous_value {
+            *self.previous_value.lock() = Some(Box::new(value));
+        }
+        self.done(status);
+    }
+
     pub fn handle(&self) -> &CommandAcknowledgementHandle {
         &self.handle
     }
+
+    /// Awaits the command like `handle()` and returns the [`crate::cache::command::CommandStatus`] along with the value the command removed.
+    ///
+    /// The value is `Some` if a put replaced an existing key (the key was overwritten), or a delete removed an existing key.
+    /// The value is `None` if there was no existing key, if the existing key had expired, or if the command was rejected.
+    /// The type parameter `Value` must be the value type of the cache that the command was sent to, any other type results in `None`.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    ///     let (status, previous) = cached.put("topic", "microservices").unwrap().handle_with_previous::<&str>().await;
+    ///     assert_eq!((CommandStatus::Accepted, None), (status, previous));
+    ///
+    ///     let (status, previous) = cached.delete("topic").unwrap().handle_with_previous::<&str>().await;
+    ///     assert_eq!((CommandStatus::Accepted, Some("microservices")), (status, previous));
+    /// }
+    /// ```
+    pub async fn handle_with_previous<Value>(&self) -> (CommandStatus, Option<Value>)
+        where Value: Clone + 'static {
+        let status = self.handle().await;
+        let previous_value = self.previous_value
+            .lock()
+            .as_ref()
+            .and_then(|value| value.downcast_ref::<Value>())
+            .cloned();
+        (status, previous_value)
+    }
+
+    /// Blocks the calling thread till the command is executed and returns the [`crate::cache::command::CommandStatus`],
+    /// this allows the clients to get the command status without an async runtime.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    /// let status = cached.put("topic", "microservices").unwrap().block_on_done();
+    /// assert_eq!(CommandStatus::Accepted, status);
+    /// assert_eq!(Some("microservices"), cached.get(&"topic"));
+    /// ```
+    pub fn block_on_done(&self) -> CommandStatus {
+        self.handle.block_on_done()
+    }
+
+    /// Awaits all the `acknowledgements` and returns their [`crate::cache::command::CommandStatus`]es, in the order of the `acknowledgements`.
+    ///
+    /// The commands are executed independent of the order in which their acknowledgements are awaited, so awaiting them one after the other
+    /// takes as long as awaiting the last executed command. The returned future does not depend on any async runtime.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::command::acknowledgement::CommandAcknowledgement;
+    /// use tinylfu_cached::cache::command::CommandStatus;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 1000).build());
+    ///     let acknowledgements = cached.put_all(vec![("topic", "microservices"), ("disk", "SSD")]).into_iter().map(|result| result.unwrap()).collect();
+    ///
+    ///     let statuses = CommandAcknowledgement::handle_all(acknowledgements).await;
+    ///     assert_eq!(vec![CommandStatus::Accepted, CommandStatus::Accepted], statuses);
+    /// }
+    /// ```
+    pub async fn handle_all(acknowledgements: Vec<Arc<CommandAcknowledgement>>) -> Vec<CommandStatus> {
+        let mut statuses = Vec::with_capacity(acknowledgements.len());
+        for acknowledgement in acknowledgements {
+            statuses.push(acknowledgement.handle().await);
+        }
+        statuses
+    }
 }
 
 impl CommandAcknowledgementHandle {
-    /// Marks the flag to indicate that the command execution is done and changes the `CommandStatus`
+    /// Changes the `CommandStatus`, marks the flag to indicate that the command execution is done and wakes all the awaiting tasks.
+    /// The status is changed before the flag is marked, so that an awaiting task never observes the `CommandStatus::Pending` status after the command is done.
     pub(crate) fn done(&self, status: CommandStatus) {
-        self.done.store(true, Ordering::Release);
         *self.status.lock() = status;
-        if let Some(waker) = &self.waker_state.lock().waker {
-            waker.wake_by_ref();
+        self.done.store(true, Ordering::Release);
+        let wakers = {
+            let mut guard = self.waker_state.lock();
+            self.done_signal.notify_all();
+            std::mem::take(&mut guard.wakers)
+        };
+        for waker in wakers {
+            waker.wake();
         }
     }
+
+    /// Parks the calling thread on `done_signal` till the command execution is done.
+    /// The flag is checked while holding the lock on the `waker_state`, which `done` also acquires before signalling, so the signal is never missed.
+    fn block_on_done(&self) -> CommandStatus {
+        let mut guard = self.waker_state.lock();
+        while !self.done.load(Ordering::Acquire) {
+            self.done_signal.wait(&mut guard);
+        }
+        *self.status.lock()
+    }
 }
 
 /// Future implementation for CommandAcknowledgementHandle.
 /// The future is complete when the the command is executed by the `crate::cache::command::command_executor::CommandExecutor`.
 /// The completion of future returns [`CommandStatus`].
+///
+/// Each awaiting task registers its waker, so the completion wakes every task awaiting on the handle, not just the last one to poll.
 impl Future for &CommandAcknowledgementHandle {
     type Output = CommandStatus;
 
     fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
         let mut guard = self.waker_state.lock();
-        match guard.waker.as_ref() {
-            Some(waker) => {
-                if !waker.will_wake(context.waker()) {
-                    guard.waker = Some(context.waker().clone());
-                }
-            }
-            None => {
-                guard.waker = Some(context.waker().clone());
-            }
+        if !guard.wakers.iter().any(|waker| waker.will_wake(context.waker())) {
+            guard.wakers.push(context.waker().clone());
         }
         if self.done.load(Ordering::Acquire) {
             return Poll::Ready(*self.status.lock());
@@ -131,6 +235,8 @@ impl Future for &CommandAcknowledgementHandle {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use crate::cache::command::acknowledgement::CommandAcknowledgement;
     use crate::cache::command::{CommandStatus, RejectionReason};
 
@@ -148,6 +254,76 @@ mod tests {
         assert_eq!(CommandStatus::Accepted, response);
     }
 
+    #[tokio::test]
+    async fn acknowledge_all_the_awaiting_tasks() {
+        let acknowledgement = CommandAcknowledgement::new();
+        let awaiters = (0..2).map(|_| tokio::spawn({
+            let acknowledgement = acknowledgement.clone();
+            async move {
+                acknowledgement.handle().await
+            }
+        })).collect::<Vec<_>>();
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        acknowledgement.done(CommandStatus::Rejected(RejectionReason::KeyDoesNotExist));
+
+        for awaiter in awaiters {
+            let response = tokio::time::timeout(Duration::from_secs(5), awaiter).await.unwrap().unwrap();
+            assert_eq!(CommandStatus::Rejected(RejectionReason::KeyDoesNotExist), response);
+        }
+    }
+
+    #[test]
+    fn handle_all_without_tokio() {
+        let acknowledgements = vec![CommandAcknowledgement::new(), CommandAcknowledgement::rejected(RejectionReason::KeyAlreadyExists)];
+        let handle = std::thread::spawn({
+            let acknowledgement = acknowledgements[0].clone();
+            move || acknowledgement.done(CommandStatus::Accepted)
+        });
+
+        let statuses = async_std::task::block_on(CommandAcknowledgement::handle_all(acknowledgements));
+        handle.join().unwrap();
+        assert_eq!(vec![CommandStatus::Accepted, CommandStatus::Rejected(RejectionReason::KeyAlreadyExists)], statuses);
+    }
+
+    #[test]
+    fn block_on_done() {
+        let acknowledgement = CommandAcknowledgement::new();
+        let handle = std::thread::spawn({
+            let acknowledgement = acknowledgement.clone();
+            move || acknowledgement.block_on_done()
+        });
+
+        std::thread::sleep(Duration::from_millis(10));
+        acknowledgement.done(CommandStatus::Accepted);
+        assert_eq!(CommandStatus::Accepted, handle.join().unwrap());
+    }
+
+    #[test]
+    fn block_on_done_of_a_rejected_acknowledgement() {
+        let acknowledgement = CommandAcknowledgement::rejected(RejectionReason::KeyAlreadyExists);
+        assert_eq!(CommandStatus::Rejected(RejectionReason::KeyAlreadyExists), acknowledgement.block_on_done());
+    }
+
+    #[tokio::test]
+    async fn acknowledge_with_the_previous_value() {
+        let acknowledgement = CommandAcknowledgement::new();
+        acknowledgement.done_with_previous(CommandStatus::Accepted, Some("microservices"));
+
+        let response = acknowledgement.handle_with_previous::<&str>().await;
+        assert_eq!((CommandStatus::Accepted, Some("microservices")), response);
+        assert_eq!(CommandStatus::Accepted, acknowledgement.handle().await);
+    }
+
+    #[tokio::test]
+    async fn acknowledge_without_the_previous_value() {
+        let acknowledgement = CommandAcknowledgement::new();
+        acknowledgement.done_with_previous::<&str>(CommandStatus::Rejected(RejectionReason::KeyDoesNotExist), None);
+
+        let response = acknowledgement.handle_with_previous::<&str>().await;
+        assert_eq!((CommandStatus::Rejected(RejectionReason::KeyDoesNotExist), None), response);
+    }
+
     #[tokio::test]
     async fn accepted() {
         let acknowledgement = CommandAcknowledgement::accepted();
@@ -0,0 +1,146 @@
+use parking_lot::{Condvar, Mutex};
+
+/// AdaptiveCapacity bounds the number of commands queued for `crate::cache::command::command_executor::CommandExecutor`,
+/// when the commands are sent over an unbounded channel.
+///
+/// The capacity starts at `initial` and doubles (up to `max`) every time a command finds the queue full, so a transient burst of writes
+/// does not block the senders. A sender blocks only if the queue is full at the `max` capacity, the way it blocks on a full bounded channel.
+/// The capacity halves (down to `initial`) once the queued commands fall to a quarter of the capacity, so the queue does not stay over-allocated
+/// after the burst subsides.
+pub(crate) struct AdaptiveCapacity {
+    initial: usize,
+    max: usize,
+    state: Mutex<CapacityState>,
+    has_room: Condvar,
+}
+
+struct CapacityState {
+    capacity: usize,
+    queued: usize,
+}
+
+impl AdaptiveCapacity {
+    pub(crate) fn new(initial: usize, max: usize) -> Self {
+        AdaptiveCapacity {
+            initial,
+            max,
+            state: Mutex::new(CapacityState { capacity: initial, queued: 0 }),
+            has_room: Condvar::new(),
+        }
+    }
+
+    /// Reserves a place for a command in the queue, growing the capacity if the queue is full.
+    /// Blocks till a place is released if the queue is full at the `max` capacity.
+    pub(crate) fn acquire(&self) {
+        let mut state = self.state.lock();
+        while state.queued >= state.capacity {
+            if state.capacity < self.max {
+                state.capacity = (state.capacity * 2).min(self.max);
+            } else {
+                self.has_room.wait(&mut state);
+            }
+        }
+        state.queued += 1;
+    }
+
+    /// Reserves a place for a command in the queue like `acquire`, but returns false instead of blocking if the queue is full at the `max` capacity.
+    pub(crate) fn try_acquire(&self) -> bool {
+        let mut state = self.state.lock();
+        if state.queued >= state.capacity {
+            if state.capacity == self.max {
+                return false;
+            }
+            state.capacity = (state.capacity * 2).min(self.max);
+        }
+        state.queued += 1;
+        true
+    }
+
+    /// Releases the place of a command that is taken off the queue, shrinking the capacity if the pressure has subsided.
+    pub(crate) fn release(&self) {
+        {
+            let mut state = self.state.lock();
+            state.queued -= 1;
+            if state.capacity > self.initial && state.queued <= state.capacity / 4 {
+                state.capacity = (state.capacity / 2).max(self.initial);
+            }
+        }
+        self.has_room.notify_one();
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.state.lock().capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::cache::command::adaptive_capacity::AdaptiveCapacity;
+
+    #[test]
+    fn grows_the_capacity_when_the_queue_is_full() {
+        let adaptive_capacity = AdaptiveCapacity::new(2, 8);
+        (0..3).for_each(|_| adaptive_capacity.acquire());
+
+        assert_eq!(4, adaptive_capacity.capacity());
+    }
+
+    #[test]
+    fn grows_the_capacity_up_to_the_max() {
+        let adaptive_capacity = AdaptiveCapacity::new(2, 6);
+        (0..6).for_each(|_| adaptive_capacity.acquire());
+
+        assert_eq!(6, adaptive_capacity.capacity());
+    }
+
+    #[test]
+    fn shrinks_the_capacity_when_the_pressure_subsides() {
+        let adaptive_capacity = AdaptiveCapacity::new(2, 8);
+        (0..8).for_each(|_| adaptive_capacity.acquire());
+        assert_eq!(8, adaptive_capacity.capacity());
+
+        (0..8).for_each(|_| adaptive_capacity.release());
+        assert_eq!(2, adaptive_capacity.capacity());
+    }
+
+    #[test]
+    fn try_acquire_grows_the_capacity_up_to_the_max_without_blocking() {
+        let adaptive_capacity = AdaptiveCapacity::new(1, 2);
+
+        assert!(adaptive_capacity.try_acquire());
+        assert!(adaptive_capacity.try_acquire());
+        assert!(!adaptive_capacity.try_acquire());
+        assert_eq!(2, adaptive_capacity.capacity());
+
+        adaptive_capacity.release();
+        assert!(adaptive_capacity.try_acquire());
+    }
+
+    #[test]
+    fn blocks_at_the_max_capacity_till_a_place_is_released() {
+        let adaptive_capacity = Arc::new(AdaptiveCapacity::new(1, 2));
+        (0..2).for_each(|_| adaptive_capacity.acquire());
+
+        let acquired = Arc::new(AtomicBool::new(false));
+        let handle = thread::spawn({
+            let adaptive_capacity = adaptive_capacity.clone();
+            let acquired = acquired.clone();
+            move || {
+                adaptive_capacity.acquire();
+                acquired.store(true, Ordering::SeqCst);
+            }
+        });
+
+        thread::sleep(Duration::from_millis(50));
+        assert!(!acquired.load(Ordering::SeqCst));
+
+        adaptive_capacity.release();
+        handle.join().unwrap();
+        assert!(acquired.load(Ordering::SeqCst));
+    }
+}
@@ -1,38 +1,78 @@
 use std::hash::Hash;
-use std::sync::Arc;
+use std::sync::{Arc, Barrier};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
+use std::thread::JoinHandle;
 use std::time::Duration;
 
-use crossbeam_channel::Receiver;
+use crossbeam_channel::{Receiver, TrySendError};
 use log::{error, info};
+use parking_lot::{Mutex, ReentrantMutex};
 
 use crate::cache::command::{CommandStatus, CommandType};
 use crate::cache::command::acknowledgement::CommandAcknowledgement;
-use crate::cache::command::error::CommandSendError;
-use crate::cache::command::RejectionReason::KeyDoesNotExist;
+use crate::cache::command::adaptive_capacity::AdaptiveCapacity;
+use crate::cache::command::error::{CommandSendError, TryPutError};
+use crate::cache::command::RejectionReason::{KeyAlreadyExists, KeyDoesNotExist, StaleVersion};
+use crate::cache::config::{EvictionListenerFn, EvictionReason, PutMode};
 use crate::cache::expiration::TTLTicker;
 use crate::cache::key_description::KeyDescription;
 use crate::cache::policy::admission_policy::AdmissionPolicy;
 use crate::cache::stats::ConcurrentStatsCounter;
 use crate::cache::store::Store;
+use crate::cache::types::{ExpireAfter, KeyId};
 
 /// Every write operation like `put`, `put_or_update` and `delete` is returned a [`crate::cache::command::command_executor::CommandSendResult`] that
 /// wraps an instance of [`crate::cache::command::acknowledgement::CommandAcknowledgement`] and a [`crate::cache::command::error::CommandSendError`]
 pub type CommandSendResult = Result<Arc<CommandAcknowledgement>, CommandSendError>;
 
+/// `try_put` of [`crate::cache::cached::CacheD`] is returned a `TryPutResult`, which is a `CommandSendResult` that also reports a full command channel,
+/// read [`crate::cache::command::error::TryPutError`].
+pub type TryPutResult = Result<Arc<CommandAcknowledgement>, TryPutError>;
+
 pub(crate) fn shutdown_result() -> CommandSendResult {
     Err(CommandSendError::shutdown())
 }
 
+pub(crate) fn ttl_disabled_result(command_description: &str) -> CommandSendResult {
+    Err(CommandSendError::ttl_disabled(command_description))
+}
+
 /// CommandExecutor executes various commands of type `crate::cache::command::CommandType`.
 /// CommandExecutor spins a thread when it is instantiated and starts receiving commands from the `crossbeam_channel::Receiver`.
 /// The command is wrapped in an abstraction `CommandAcknowledgementPair` that combines `CommandType` and `CommandAcknowledgement`
 /// Execution of a command typically involves interacting with `crate::cache::policy::admission_policy::AdmissionPolicy`,
 /// `crate::cache::store::Store` and `crate::cache::expiration::TTLTicker`
+///
+/// An inline `CommandExecutor` (created via `CommandExecutor::inline`) does not spin a thread,
+/// it executes every command on the caller's thread, serializing the commands using a lock.
+///
+/// An adaptive `CommandExecutor` (created via `CommandExecutor::adaptive`) receives the commands over an unbounded channel,
+/// and bounds the queued commands using `crate::cache::command::adaptive_capacity::AdaptiveCapacity`, which grows under the pressure of writes.
+///
+/// A `CommandExecutor` with more than one thread (created via `CommandExecutor::with_threads`) partitions the commands between its threads (the workers)
+/// by the shards of their keys in the `Store`. Each worker receives the commands of its shards from its own channel in `senders`,
+/// so the commands of the same key are executed in the order they are sent. A command with the keys of more than one worker is fenced, read `Work`.
+/// `fence_lock` is held while a command is sent to more than one worker, so that all the workers receive such commands in the same order.
+///
+/// `stopped` receives a signal from each worker once it has stopped executing the commands, after it receives the `Shutdown` command.
+/// The signal carries the number of commands that were queued behind the `Shutdown` command, and were dropped without being executed,
+/// the sum of the signals of all the workers is held in `dropped_commands`.
+///
+/// `handles` holds the `JoinHandle` of each thread, the threads exit once `senders` are dropped, read `join`.
+/// `workers` is shared with the threads, so that `shutdown` can stop them without a `Shutdown` command when it is invoked from one of them.
 pub(crate) struct CommandExecutor<Key, Value>
     where Key: Hash + Eq + Send + Sync + Clone + 'static,
           Value: Send + Sync + 'static {
-    sender: crossbeam_channel::Sender<CommandAcknowledgementPair<Key, Value>>,
+    senders: Vec<crossbeam_channel::Sender<Work<Key, Value>>>,
+    fence_lock: Mutex<()>,
+    stopped: Option<Receiver<usize>>,
+    dropped_commands: Mutex<Option<usize>>,
+    adaptive_capacity: Option<Arc<AdaptiveCapacity>>,
+    fixed_capacity: usize,
+    inline_executor: Option<InlineExecutor<Key, Value>>,
+    handles: Vec<JoinHandle<()>>,
+    workers: Option<Arc<Workers<Key, Value>>>,
 }
 
 struct CommandAcknowledgementPair<Key, Value>
@@ -41,6 +81,61 @@ struct CommandAcknowledgementPair<Key, Value>
     acknowledgement: Arc<CommandAcknowledgement>,
 }
 
+/// Work is received by a worker of `CommandExecutor` from its channel.
+///
+/// `Execute` carries a command with the keys of the worker only.
+/// `Fenced` carries a command with the keys of more than one worker (like `CommandType::Clear`), the worker executes the command once all the workers
+/// with its keys have received their `Fence`, that is, once they have executed all the commands that were sent to them before the fenced command.
+/// `Fence` holds the other workers till the fenced command is executed, so that they do not execute the commands sent after it, before it.
+enum Work<Key, Value>
+    where Key: Hash + Eq + Clone {
+    Execute(CommandAcknowledgementPair<Key, Value>),
+    Fenced(CommandAcknowledgementPair<Key, Value>, Arc<Fence>),
+    Fence(Arc<Fence>),
+}
+
+/// Fence is shared by the workers with the keys of a fenced command, read `Work`.
+/// The workers wait on `arrived` before the command is executed, and on `executed` after it is executed.
+struct Fence {
+    arrived: Barrier,
+    executed: Barrier,
+}
+
+/// ExecutionContext holds the abstractions that are needed to execute a command.
+/// `eviction_listener` (if any) is invoked with every key that a command removes from the `Store`.
+pub(crate) struct ExecutionContext<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    store: Arc<Store<Key, Value>>,
+    admission_policy: Arc<AdmissionPolicy<Key>>,
+    stats_counter: Arc<ConcurrentStatsCounter>,
+    ttl_ticker: Arc<TTLTicker>,
+    put_mode: PutMode,
+    eviction_listener: Option<Arc<EvictionListenerFn<Key, Value>>>,
+}
+
+/// Workers is shared by the threads of a spawned `CommandExecutor`.
+/// `total` is the number of the workers, a key belongs to the worker at the index of its shard modulo `total`.
+/// `is_shutdown` is set if `shutdown` is invoked from a thread of the `CommandExecutor`, read `CommandExecutor::stop_without_waiting`.
+struct Workers<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    execution_context: ExecutionContext<Key, Value>,
+    total: usize,
+    is_shutdown: AtomicBool,
+}
+
+/// InlineExecutor executes the commands on the caller's thread.
+/// `lock` ensures that the commands are executed one at a time, the way they would be executed by the single thread of `CommandExecutor`.
+/// `lock` is reentrant, so that a command sent from a listener that is invoked during the execution of a command (for example, a `Shutdown`) does not deadlock.
+struct InlineExecutor<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    execution_context: ExecutionContext<Key, Value>,
+    lock: ReentrantMutex<()>,
+    is_shutdown: AtomicBool,
+}
+
 struct PutParameter<'a, Key, Value, DeleteHook>
     where Key: Hash + Eq + Send + Sync + Clone + 'static,
           Value: Send + Sync + 'static,
@@ -51,6 +146,8 @@ struct PutParameter<'a, Key, Value, DeleteHook>
     value: Value,
     admission_policy: &'a Arc<AdmissionPolicy<Key>>,
     stats_counter: &'a Arc<ConcurrentStatsCounter>,
+    is_reserved: bool,
+    replaced: Option<(KeyId, EvictionReason)>,
 }
 
 struct PutWithTTLParameter<'a, Key, Value, DeleteHook>
@@ -68,26 +165,108 @@ struct DeleteParameter<'a, Key, Value>
     key: &'a Key,
     admission_policy: &'a Arc<AdmissionPolicy<Key>>,
     ttl_ticker: &'a Arc<TTLTicker>,
+    eviction_listener: &'a Option<Arc<EvictionListenerFn<Key, Value>>>,
+    reason: EvictionReason,
 }
 
 impl<Key, Value> CommandExecutor<Key, Value>
     where Key: Hash + Eq + Send + Sync + Clone + 'static,
           Value: Send + Sync + 'static {
+    #[cfg(test)]
     pub(crate) fn new(
         store: Arc<Store<Key, Value>>,
         admission_policy: Arc<AdmissionPolicy<Key>>,
         stats_counter: Arc<ConcurrentStatsCounter>,
         ttl_ticker: Arc<TTLTicker>,
-        command_channel_size: usize) -> Self {
-        let (sender, receiver) = crossbeam_channel::bounded(command_channel_size);
-        let command_executor = CommandExecutor { sender };
+        command_channel_size: usize,
+        put_mode: PutMode,
+        eviction_listener: Option<Arc<EvictionListenerFn<Key, Value>>>) -> Self {
+        let execution_context = ExecutionContext::new(store, admission_policy, stats_counter, ttl_ticker, put_mode, eviction_listener);
+        Self::with_threads(execution_context, command_channel_size, None, 1)
+    }
+
+    /// Creates an adaptive `CommandExecutor` that queues the initial capacity of `adaptive_capacity` commands, and grows the capacity of the queue
+    /// up to its max capacity under the pressure of writes. The capacity shrinks back once the pressure subsides.
+    #[cfg(test)]
+    pub(crate) fn adaptive(
+        store: Arc<Store<Key, Value>>,
+        admission_policy: Arc<AdmissionPolicy<Key>>,
+        stats_counter: Arc<ConcurrentStatsCounter>,
+        ttl_ticker: Arc<TTLTicker>,
+        adaptive_capacity: AdaptiveCapacity,
+        put_mode: PutMode,
+        eviction_listener: Option<Arc<EvictionListenerFn<Key, Value>>>) -> Self {
+        let execution_context = ExecutionContext::new(store, admission_policy, stats_counter, ttl_ticker, put_mode, eviction_listener);
+        Self::with_threads(execution_context, adaptive_capacity.capacity(), Some(adaptive_capacity), 1)
+    }
 
-        command_executor.spin(receiver, store, admission_policy, stats_counter, ttl_ticker);
+    /// Creates a `CommandExecutor` that spins `threads` workers to execute the commands.
+    /// Each worker queues its commands in a channel of `command_channel_size`, or in an adaptive channel if `adaptive_capacity` is provided.
+    /// The queued commands of all the workers are bounded by the same `adaptive_capacity`.
+    pub(crate) fn with_threads(
+        execution_context: ExecutionContext<Key, Value>,
+        command_channel_size: usize,
+        adaptive_capacity: Option<AdaptiveCapacity>,
+        threads: usize) -> Self {
+        let (stopped_sender, stopped_receiver) = crossbeam_channel::bounded(threads);
+        let mut command_executor = CommandExecutor {
+            senders: Vec::with_capacity(threads),
+            fence_lock: Mutex::new(()),
+            stopped: Some(stopped_receiver),
+            dropped_commands: Mutex::new(None),
+            adaptive_capacity: adaptive_capacity.map(Arc::new),
+            fixed_capacity: command_channel_size,
+            inline_executor: None,
+            handles: Vec::with_capacity(threads),
+            workers: None,
+        };
+
+        let workers = Arc::new(Workers {
+            execution_context,
+            total: threads,
+            is_shutdown: AtomicBool::new(false),
+        });
+        for _ in 0..threads {
+            let (sender, receiver) = match command_executor.adaptive_capacity {
+                Some(_) => crossbeam_channel::unbounded(),
+                None => crossbeam_channel::bounded(command_channel_size),
+            };
+            let handle = command_executor.spin(receiver, stopped_sender.clone(), workers.clone());
+            command_executor.senders.push(sender);
+            command_executor.handles.push(handle);
+        }
+        command_executor.workers = Some(workers);
         command_executor
     }
 
-    /// Spins a thread when `CommandExecutor` is instantiated.
-    /// The thread receives a command wrapped in `CommandAcknowledgementPair` from the [`crossbeam_channel::Receiver<T>`].
+    /// Creates an inline `CommandExecutor` that executes every command synchronously on the caller's thread.
+    /// The `CommandAcknowledgement` returned from `send` is already done.
+    pub(crate) fn inline(
+        store: Arc<Store<Key, Value>>,
+        admission_policy: Arc<AdmissionPolicy<Key>>,
+        stats_counter: Arc<ConcurrentStatsCounter>,
+        ttl_ticker: Arc<TTLTicker>,
+        put_mode: PutMode,
+        eviction_listener: Option<Arc<EvictionListenerFn<Key, Value>>>) -> Self {
+        CommandExecutor {
+            senders: Vec::new(),
+            fence_lock: Mutex::new(()),
+            stopped: None,
+            dropped_commands: Mutex::new(None),
+            adaptive_capacity: None,
+            fixed_capacity: 0,
+            inline_executor: Some(InlineExecutor {
+                execution_context: ExecutionContext::new(store, admission_policy, stats_counter, ttl_ticker, put_mode, eviction_listener),
+                lock: ReentrantMutex::new(()),
+                is_shutdown: AtomicBool::new(false),
+            }),
+            handles: Vec::new(),
+            workers: None,
+        }
+    }
+
+    /// Spins the thread of a worker when `CommandExecutor` is instantiated.
+    /// The thread receives `Work` from the [`crossbeam_channel::Receiver<T>`] of the worker.
     /// It identifies the command and performs an appropriate action.
     /// Execution of a command typically involves interacting with `crate::cache::policy::admission_policy::AdmissionPolicy`,
     /// `crate::cache::store::Store` and `crate::cache::expiration::TTLTicker`.
@@ -99,115 +278,687 @@ impl<Key, Value> CommandExecutor<Key, Value>
     /// It is essential to complete the future that the client is awaiting on. That is what the `Shutdown` command does.
     /// It drains the `receiver` and marks the status of the CommandAcknowledgement as `CommandStatus::ShuttingDown`.
     /// The client(s) awaiting on the future will receive `CommandStatus::ShuttingDown`.
+    ///
+    /// The thread signals `stopped_sender` with the number of the commands drained from the `receiver` on receiving the `Shutdown` command,
+    /// none of the commands are executed after the signal. The commands that sneak in after the signal also receive `CommandStatus::ShuttingDown`.
+    ///
+    /// Every worker receives the `Shutdown` command. The fenced commands are sent to all their workers in the same order as the `Shutdown` command,
+    /// so a fenced command that is drained by one of its workers is drained by all of them, and no worker waits on its `Fence` forever.
+    ///
+    /// The thread exits once all the senders of the `receiver` are dropped, and returns its `JoinHandle`.
     fn spin(&self,
-            receiver: Receiver<CommandAcknowledgementPair<Key, Value>>,
-            store: Arc<Store<Key, Value>>,
-            admission_policy: Arc<AdmissionPolicy<Key>>,
-            stats_counter: Arc<ConcurrentStatsCounter>,
-            ttl_ticker: Arc<TTLTicker>) {
-        let store_clone = store.clone();
-        let delete_hook = move |key| { store_clone.delete(&key); };
-
+            receiver: Receiver<Work<Key, Value>>,
+            stopped_sender: crossbeam_channel::Sender<usize>,
+            workers: Arc<Workers<Key, Value>>) -> JoinHandle<()> {
+        let adaptive_capacity = self.adaptive_capacity.clone();
+        let release = move || {
+            if let Some(adaptive_capacity) = &adaptive_capacity {
+                adaptive_capacity.release();
+            }
+        };
         thread::spawn(move || {
-            while let Ok(pair) = receiver.recv() {
-                let command = pair.command;
-                let status = match command {
-                    CommandType::Put(key_description, value) =>
-                        Self::put(PutParameter {
-                            store: &store,
-                            key_description: &key_description,
-                            delete_hook: &delete_hook,
-                            value,
-                            admission_policy: &admission_policy,
-                            stats_counter: &stats_counter,
-                        }),
-                    CommandType::PutWithTTL(key_description, value, ttl) =>
-                        Self::put_with_ttl(PutWithTTLParameter {
-                            put_parameter: PutParameter {
-                                store: &store,
-                                key_description: &key_description,
-                                delete_hook: &delete_hook,
-                                value,
-                                admission_policy: &admission_policy,
-                                stats_counter: &stats_counter,
-                            },
-                            ttl,
-                            ttl_ticker: &ttl_ticker,
-                        }),
-                    CommandType::UpdateWeight(key_id, weight) => {
-                        admission_policy.update(&key_id, weight);
-                        CommandStatus::Accepted
-                    }
-                    CommandType::Delete(key) =>
-                        Self::delete(DeleteParameter {
-                            store: &store,
-                            key: &key,
-                            admission_policy: &admission_policy,
-                            ttl_ticker: &ttl_ticker,
-                        }),
-                    CommandType::Shutdown => {
+            let mut has_stopped = false;
+            for work in receiver.iter() {
+                release();
+                if has_stopped {
+                    work.drop_as_shutting_down();
+                    continue;
+                }
+                match work {
+                    Work::Execute(pair) if matches!(pair.command, CommandType::Shutdown) => {
                         info!("Received Shutdown command");
+                        has_stopped = true;
                         pair.acknowledgement.done(CommandStatus::Accepted);
-                        for command_acknowledgement_pair in receiver.iter() {
-                            command_acknowledgement_pair.acknowledgement.done(CommandStatus::ShuttingDown);
+                        let mut dropped_commands = 0;
+                        for work in receiver.try_iter() {
+                            release();
+                            dropped_commands += work.drop_as_shutting_down();
                         }
-                        drop(receiver);
-                        break;
+                        let _ = stopped_sender.send(dropped_commands);
                     }
-                };
-                pair.acknowledgement.done(status);
+                    Work::Execute(pair) => workers.execute(pair),
+                    Work::Fenced(pair, fence) => {
+                        fence.arrived.wait();
+                        workers.execute(pair);
+                        fence.executed.wait();
+                    }
+                    Work::Fence(fence) => {
+                        fence.arrived.wait();
+                        fence.executed.wait();
+                    }
+                }
             }
-        });
+        })
     }
 
     /// Sends a command to the `CommandExecutor`. Every Command is wrapped in a `CommandAcknowledgementPair`
     /// that allows 2 things:
     /// 1) It allows returning an instance of `CommandAcknowledgement` to the clients, so that they can perform `await`
     /// 2) It allows `CommandExecutor` to change the status of the command inside `CommandAcknowledgement`. This would then finish the `await` at the client's end.
+    ///
+    /// The command is sent to the worker with its keys, a command with the keys of more than one worker is fenced, and the `Shutdown` command is sent to every worker.
+    /// An inline `CommandExecutor` executes the command before returning.
     pub(crate) fn send(&self, command: CommandType<Key, Value>) -> CommandSendResult {
+        if let Some(inline_executor) = &self.inline_executor {
+            return inline_executor.execute(command);
+        }
+        let acknowledgement = CommandAcknowledgement::new();
+        let workers = self.workers_of(&command);
+        let is_shutdown = matches!(command, CommandType::Shutdown);
+        let pair = CommandAcknowledgementPair { command, acknowledgement: acknowledgement.clone() };
+        match workers.as_slice() {
+            [worker] => self.send_to(*worker, Work::Execute(pair))?,
+            _ if is_shutdown => self.send_to_all(pair)?,
+            _ => self.send_fenced(&workers, pair)?,
+        }
+        Ok(acknowledgement)
+    }
+
+    /// Sends the commands to the `CommandExecutor` in the order they are provided, and returns the result of sending each command, in the same order.
+    /// Each command blocks like `send` if the command channel of its worker is full.
+    ///
+    /// An inline `CommandExecutor` executes each command before sending the next one.
+    pub(crate) fn send_batch(&self, commands: Vec<CommandType<Key, Value>>) -> Vec<CommandSendResult> {
+        commands.into_iter().map(|command| self.send(command)).collect()
+    }
+
+    /// Returns the workers with the keys of the command, in the increasing order.
+    /// A command without keys (`CommandType::ResumeEviction`) goes to the first worker, `CommandType::Clear` and `CommandType::Shutdown` go to all the workers.
+    fn workers_of(&self, command: &CommandType<Key, Value>) -> Vec<usize> {
+        self.workers.as_ref().map_or_else(|| vec![0], |workers| workers.workers_of(command))
+    }
+
+    fn send_to(&self, worker: usize, work: Work<Key, Value>) -> Result<(), CommandSendError> {
+        if let Some(adaptive_capacity) = &self.adaptive_capacity {
+            adaptive_capacity.acquire();
+        }
+        self.senders[worker].send(work).map_err(|err| {
+            if let Some(adaptive_capacity) = &self.adaptive_capacity {
+                adaptive_capacity.release();
+            }
+            let description = err.0.description();
+            error!("received a SendError while sending command type {}", description);
+            CommandSendError::new(description)
+        })
+    }
+
+    /// Sends the `Shutdown` command to every worker, all the workers share the `CommandAcknowledgement` of the `pair`.
+    fn send_to_all(&self, pair: CommandAcknowledgementPair<Key, Value>) -> Result<(), CommandSendError> {
+        let _fence = self.fence_lock.lock();
+        for worker in 1..self.senders.len() {
+            self.send_to(worker, Work::Execute(CommandAcknowledgementPair {
+                command: CommandType::Shutdown,
+                acknowledgement: pair.acknowledgement.clone(),
+            }))?;
+        }
+        self.send_to(0, Work::Execute(pair))
+    }
+
+    /// Sends the command to the first of the `workers`, and a `Fence` to the others, read `Work`.
+    fn send_fenced(&self, workers: &[usize], pair: CommandAcknowledgementPair<Key, Value>) -> Result<(), CommandSendError> {
+        let fence = Arc::new(Fence::new(workers.len()));
+        let _fence = self.fence_lock.lock();
+        self.send_to(workers[0], Work::Fenced(pair, fence.clone()))?;
+        for worker in &workers[1..] {
+            self.send_to(*worker, Work::Fence(fence.clone()))?;
+        }
+        Ok(())
+    }
+
+    /// Sends a command to the `CommandExecutor` like `send`, but returns `TryPutError::ChannelFull` instead of blocking if the command channel is full.
+    ///
+    /// An inline `CommandExecutor` executes the command before returning, it has no channel to be full.
+    /// A command with the keys of more than one worker is sent like `send`.
+    pub(crate) fn try_send(&self, command: CommandType<Key, Value>) -> TryPutResult {
+        if let Some(inline_executor) = &self.inline_executor {
+            return inline_executor.execute(command).map_err(TryPutError::from);
+        }
+        let worker = match self.workers_of(&command).as_slice() {
+            [worker] => *worker,
+            _ => return self.send(command).map_err(TryPutError::from),
+        };
+        if let Some(adaptive_capacity) = &self.adaptive_capacity {
+            if !adaptive_capacity.try_acquire() {
+                return Err(TryPutError::ChannelFull);
+            }
+        }
         let acknowledgement = CommandAcknowledgement::new();
-        let send_result = self.sender.send(CommandAcknowledgementPair {
+        let send_result = self.senders[worker].try_send(Work::Execute(CommandAcknowledgementPair {
             command,
             acknowledgement: acknowledgement.clone(),
-        });
+        }));
 
         match send_result {
             Ok(_) => Ok(acknowledgement),
             Err(err) => {
-                error!("received a SendError while sending command type {}", err.0.command.description());
-                Err(CommandSendError::new(err.0.command.description()))
+                if let Some(adaptive_capacity) = &self.adaptive_capacity {
+                    adaptive_capacity.release();
+                }
+                match err {
+                    TrySendError::Full(_) => Err(TryPutError::ChannelFull),
+                    TrySendError::Disconnected(work) => {
+                        error!("received a SendError while sending command type {}", work.description());
+                        Err(TryPutError::Send(CommandSendError::new(work.description())))
+                    }
+                }
             }
         }
     }
 
-    /// Sends a Shutdown command to the `CommandExecutor`.
+    /// Sends a Shutdown command to the `CommandExecutor` and waits till all its workers stop executing the commands.
+    ///
+    /// The commands of a worker are executed in the order they are sent, so all the commands sent before the `Shutdown` command are executed before `shutdown` returns.
+    /// This allows the clients of `CommandExecutor` to clear the `crate::cache::store::Store` (and others) without racing against a command in execution.
+    /// The wait also finishes if the threads of `CommandExecutor` have already stopped, because `recv` returns an error once the `stopped_sender` is dropped.
+    ///
+    /// An inline `CommandExecutor` stops when the `Shutdown` command is executed, because it executes one command at a time.
+    ///
+    /// If `shutdown` is invoked from a thread of the `CommandExecutor` (for example, from an eviction listener), it does not wait,
+    /// because the thread can not stop while it is executing the command that invoked `shutdown`. Read `stop_without_waiting`.
     pub(crate) fn shutdown(&self) -> CommandSendResult {
-        self.send(CommandType::Shutdown)
+        if self.is_executor_thread() {
+            return self.stop_without_waiting();
+        }
+        let result = self.send(CommandType::Shutdown);
+        if result.is_ok() {
+            let dropped_commands = match &self.stopped {
+                Some(stopped) => self.senders.iter().map(|_| stopped.recv().ok()).sum(),
+                None => Some(0),
+            };
+            *self.dropped_commands.lock() = dropped_commands;
+        }
+        result
     }
 
-    fn put<DeleteHook>(put_parameters: PutParameter<Key, Value, DeleteHook>) -> CommandStatus where DeleteHook: Fn(Key) {
-        let status = put_parameters.admission_policy.maybe_add(
-            put_parameters.key_description,
-            put_parameters.delete_hook,
-        );
+    /// Returns true if the current thread is executing a command of the `CommandExecutor`,
+    /// which is the case for the listeners and the hooks that are invoked during the execution of a command.
+    pub(crate) fn is_executor_thread(&self) -> bool {
+        if let Some(inline_executor) = &self.inline_executor {
+            return inline_executor.lock.is_owned_by_current_thread();
+        }
+        let current_thread = thread::current().id();
+        self.handles.iter().any(|handle| handle.thread().id() == current_thread)
+    }
+
+    /// Marks the `CommandExecutor` as shutdown, so that the commands received after are marked as `CommandStatus::ShuttingDown` instead of being executed.
+    /// `dropped_commands` remains `None`, because the `CommandExecutor` is not confirmed to have stopped:
+    /// the command that invoked `shutdown` (and the commands being executed by the other threads) complete after `shutdown` returns.
+    fn stop_without_waiting(&self) -> CommandSendResult {
+        match &self.workers {
+            Some(workers) => {
+                info!("Received Shutdown on a thread of CommandExecutor");
+                workers.is_shutdown.store(true, Ordering::Release);
+                Ok(CommandAcknowledgement::accepted())
+            }
+            None => self.send(CommandType::Shutdown),
+        }
+    }
+
+    /// Returns the number of commands that can be queued for a worker of the `CommandExecutor` before a send blocks.
+    /// The capacity of an adaptive `CommandExecutor` changes with the pressure of writes, an inline `CommandExecutor` does not queue the commands.
+    pub(crate) fn capacity(&self) -> usize {
+        match &self.adaptive_capacity {
+            Some(adaptive_capacity) => adaptive_capacity.capacity(),
+            None => self.fixed_capacity,
+        }
+    }
+
+    /// Returns the number of commands in the channels that are yet to be received by the workers of the `CommandExecutor`.
+    #[cfg(test)]
+    pub(crate) fn queued_commands(&self) -> usize {
+        self.senders.iter().map(|sender| sender.len()).sum()
+    }
+
+    /// Returns the number of commands that were queued behind the `Shutdown` command, and were dropped without being executed.
+    ///
+    /// Returns `None` if the `CommandExecutor` is not shutdown, or if its thread did not confirm stopping.
+    /// An inline `CommandExecutor` does not queue the commands, so it never drops a command.
+    pub(crate) fn dropped_commands(&self) -> Option<usize> {
+        *self.dropped_commands.lock()
+    }
+
+    /// Drops the senders of the commands and waits till the threads of the `CommandExecutor` exit.
+    ///
+    /// `join` consumes the `CommandExecutor`, and is expected to be invoked after `shutdown`, so that the commands still in the channel
+    /// are marked as `CommandStatus::ShuttingDown` instead of being executed. An inline `CommandExecutor` has no threads to join.
+    pub(crate) fn join(self) {
+        let CommandExecutor { senders, handles, .. } = self;
+        drop(senders);
+        for handle in handles {
+            if handle.join().is_err() {
+                error!("a thread of CommandExecutor panicked");
+            }
+        }
+    }
+}
+
+impl<Key, Value> Work<Key, Value>
+    where Key: Hash + Eq + Clone {
+    /// Marks the command of the work as `CommandStatus::ShuttingDown` without executing it, and returns the number of the commands marked.
+    fn drop_as_shutting_down(self) -> usize {
+        match self {
+            Work::Execute(pair) | Work::Fenced(pair, _) => {
+                pair.acknowledgement.done(CommandStatus::ShuttingDown);
+                1
+            }
+            Work::Fence(_) => 0,
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            Work::Execute(pair) | Work::Fenced(pair, _) => pair.command.description(),
+            Work::Fence(_) => "Fence".to_string(),
+        }
+    }
+}
+
+impl Fence {
+    fn new(workers: usize) -> Self {
+        Fence {
+            arrived: Barrier::new(workers),
+            executed: Barrier::new(workers),
+        }
+    }
+}
+
+impl<Key, Value> Workers<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    fn worker_of(&self, key: &Key) -> usize {
+        self.execution_context.store.shard_index(key) % self.total
+    }
+
+    /// Returns the workers with the keys of the command, in the increasing order.
+    /// The commands that identify the keys by their ids carry the keys as well, so that they are executed in order with the other commands of the keys.
+    fn workers_of(&self, command: &CommandType<Key, Value>) -> Vec<usize> {
+        let mut workers = match command {
+            CommandType::Put(key_description, _) |
+            CommandType::PutWithTTL(key_description, _, _) |
+            CommandType::PutReserved(key_description, _) |
+            CommandType::PutReservedWithTTL(key_description, _, _) |
+            CommandType::PutVersioned(key_description, _, _, _) |
+            CommandType::PutOrUpdate(key_description, _, _) |
+            CommandType::Admit(key_description, _, _) => vec![self.worker_of(key_description.key())],
+            CommandType::Delete(key) |
+            CommandType::UpdateWeight(key, _, _) => vec![self.worker_of(key)],
+            CommandType::DeleteBatch(keys) => keys.iter().map(|key| self.worker_of(key)).collect(),
+            CommandType::DeleteTaken(key_id_expiries) => key_id_expiries.iter().map(|(key, _)| self.worker_of(key)).collect(),
+            CommandType::UpdateWeightBatch(key_id_weights) => key_id_weights.iter().map(|(key, _, _)| self.worker_of(key)).collect(),
+            CommandType::Rename(old_key, key_description, _) =>
+                vec![self.worker_of(old_key), self.worker_of(key_description.key())],
+            CommandType::Clear |
+            CommandType::Shutdown => (0..self.total).collect(),
+            CommandType::ResumeEviction => Vec::new(),
+        };
+        if workers.is_empty() {
+            return vec![0];
+        }
+        workers.sort_unstable();
+        workers.dedup();
+        workers
+    }
+
+    /// Executes the command, unless `shutdown` was invoked from a thread of the `CommandExecutor`.
+    fn execute(&self, pair: CommandAcknowledgementPair<Key, Value>) {
+        if self.is_shutdown.load(Ordering::Acquire) {
+            pair.acknowledgement.done(CommandStatus::ShuttingDown);
+            return;
+        }
+        let (status, previous_value) = self.execution_context.execute(pair.command);
+        pair.acknowledgement.done_with_previous(status, previous_value);
+    }
+}
+
+impl<Key, Value> InlineExecutor<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    fn execute(&self, command: CommandType<Key, Value>) -> CommandSendResult {
+        let _guard = self.lock.lock();
+        let acknowledgement = CommandAcknowledgement::new();
+        if self.is_shutdown.load(Ordering::Acquire) {
+            acknowledgement.done(CommandStatus::ShuttingDown);
+            return Ok(acknowledgement);
+        }
+        if let CommandType::Shutdown = command {
+            info!("Received Shutdown command");
+            self.is_shutdown.store(true, Ordering::Release);
+            acknowledgement.done(CommandStatus::Accepted);
+            return Ok(acknowledgement);
+        }
+        let (status, previous_value) = self.execution_context.execute(command);
+        acknowledgement.done_with_previous(status, previous_value);
+        Ok(acknowledgement)
+    }
+}
+
+impl<Key, Value> ExecutionContext<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone + 'static,
+          Value: Send + Sync + 'static {
+    pub(crate) fn new(
+        store: Arc<Store<Key, Value>>,
+        admission_policy: Arc<AdmissionPolicy<Key>>,
+        stats_counter: Arc<ConcurrentStatsCounter>,
+        ttl_ticker: Arc<TTLTicker>,
+        put_mode: PutMode,
+        eviction_listener: Option<Arc<EvictionListenerFn<Key, Value>>>) -> Self {
+        ExecutionContext { store, admission_policy, stats_counter, ttl_ticker, put_mode, eviction_listener }
+    }
+
+    /// Executes all the commands other than `CommandType::Shutdown`, which is handled by the executors.
+    ///
+    /// `CommandType::PutReserved` and `CommandType::PutReservedWithTTL` are executed like `CommandType::Put` and `CommandType::PutWithTTL`,
+    /// except that the key is admitted using the weight reserved for it. The reserved weight is released if the key is rejected.
+    ///
+    /// Returns the status of the command along with the value that the command removed, if a put replaced an existing key or a delete removed one.
+    fn execute(&self, command: CommandType<Key, Value>) -> (CommandStatus, Option<Value>) {
+        let delete_hook = |key| {
+            if let Some((value, _)) = self.store.remove_evicted(&key, |key_id| self.admission_policy.contains(key_id)) {
+                self.notify_removal(&key, &value, EvictionReason::Evicted);
+            }
+        };
+        let is_reserved = matches!(command, CommandType::PutReserved(_, _) | CommandType::PutReservedWithTTL(_, _, _));
+        match command {
+            CommandType::Put(key_description, value) | CommandType::PutReserved(key_description, value) => {
+                let replaced = match self.apply_put_mode(key_description.key(), self.put_mode) {
+                    Ok(replaced) => replaced,
+                    Err(status) => {
+                        if is_reserved { self.admission_policy.release_reservation(key_description.weight); }
+                        return (status, None);
+                    }
+                };
+                Self::put(PutParameter {
+                    store: &self.store,
+                    key_description: &key_description,
+                    delete_hook: &delete_hook,
+                    value,
+                    admission_policy: &self.admission_policy,
+                    stats_counter: &self.stats_counter,
+                    is_reserved,
+                    replaced,
+                }, || self.remove_replaced(key_description.key(), replaced))
+            }
+            CommandType::PutWithTTL(key_description, value, ttl) | CommandType::PutReservedWithTTL(key_description, value, ttl) => {
+                let replaced = match self.apply_put_mode(key_description.key(), self.put_mode) {
+                    Ok(replaced) => replaced,
+                    Err(status) => {
+                        if is_reserved { self.admission_policy.release_reservation(key_description.weight); }
+                        return (status, None);
+                    }
+                };
+                Self::put_with_ttl(PutWithTTLParameter {
+                    put_parameter: PutParameter {
+                        store: &self.store,
+                        key_description: &key_description,
+                        delete_hook: &delete_hook,
+                        value,
+                        admission_policy: &self.admission_policy,
+                        stats_counter: &self.stats_counter,
+                        is_reserved,
+                        replaced,
+                    },
+                    ttl,
+                    ttl_ticker: &self.ttl_ticker,
+                }, || self.remove_replaced(key_description.key(), replaced))
+            }
+            CommandType::PutOrUpdate(key_description, value, time_to_live) => {
+                let replaced = match self.apply_put_mode(key_description.key(), self.put_mode.ignoring_update_only()) {
+                    Ok(replaced) => replaced,
+                    Err(status) => return (status, None),
+                };
+                let put_parameter = PutParameter {
+                    store: &self.store,
+                    key_description: &key_description,
+                    delete_hook: &delete_hook,
+                    value,
+                    admission_policy: &self.admission_policy,
+                    stats_counter: &self.stats_counter,
+                    is_reserved: false,
+                    replaced,
+                };
+                let replace = || self.remove_replaced(key_description.key(), replaced);
+                match time_to_live {
+                    Some(ttl) => Self::put_with_ttl(PutWithTTLParameter { put_parameter, ttl, ttl_ticker: &self.ttl_ticker }, replace),
+                    None => Self::put(put_parameter, replace),
+                }
+            }
+            CommandType::PutVersioned(key_description, value, version, time_to_live) => {
+                let replaced = match self.apply_version(key_description.key(), version) {
+                    Ok(replaced) => replaced,
+                    Err(status) => return (status, None),
+                };
+                let status = self.admission_policy.maybe_replace(&key_description, replaced.as_slice(), &delete_hook);
+                if let CommandStatus::Accepted = status {
+                    let previous_value = self.remove_replaced(key_description.key(), replaced);
+                    match time_to_live {
+                        Some(ttl) => {
+                            let expiry = self.store.put_versioned_with_ttl(key_description.clone_key(), value, key_description.id, version, ttl);
+                            self.ttl_ticker.put(key_description.id, expiry);
+                        }
+                        None => self.store.put_versioned(key_description.clone_key(), value, key_description.id, version),
+                    }
+                    return (status, previous_value);
+                }
+                self.stats_counter.reject_key();
+                (status, None)
+            }
+            CommandType::Admit(key_description, expire_after, replaced_key_id_expiry) => {
+                if let Some(key_id_expiry) = replaced_key_id_expiry {
+                    self.admission_policy.delete(&key_id_expiry.0, EvictionReason::Replaced);
+                    if let Some(expiry) = key_id_expiry.1 {
+                        self.ttl_ticker.delete(&key_id_expiry.0, &expiry);
+                    }
+                }
+                (self.admit(&key_description, expire_after, &delete_hook), None)
+            }
+            CommandType::UpdateWeight(_, key_id, weight) => {
+                self.admission_policy.update(&key_id, weight);
+                (CommandStatus::Accepted, None)
+            }
+            CommandType::UpdateWeightBatch(key_id_weights) => {
+                for (_, key_id, weight) in key_id_weights {
+                    self.admission_policy.update(&key_id, weight);
+                }
+                (CommandStatus::Accepted, None)
+            }
+            CommandType::ResumeEviction => {
+                self.admission_policy.evict_excess(&delete_hook);
+                (CommandStatus::Accepted, None)
+            }
+            CommandType::Delete(key) =>
+                Self::delete(DeleteParameter {
+                    store: &self.store,
+                    key: &key,
+                    admission_policy: &self.admission_policy,
+                    ttl_ticker: &self.ttl_ticker,
+                    eviction_listener: &self.eviction_listener,
+                    reason: EvictionReason::Explicit,
+                }),
+            CommandType::Rename(old_key, key_description, clone_value) =>
+                self.rename(&old_key, key_description, clone_value, &delete_hook),
+            CommandType::DeleteTaken(key_id_expiries) => {
+                for (_, key_id_expiry) in key_id_expiries {
+                    self.admission_policy.delete(&key_id_expiry.0, EvictionReason::Explicit);
+                    if let Some(expiry) = key_id_expiry.1 {
+                        self.ttl_ticker.delete(&key_id_expiry.0, &expiry);
+                    }
+                }
+                (CommandStatus::Accepted, None)
+            }
+            CommandType::DeleteBatch(keys) => {
+                for key in keys.iter() {
+                    Self::delete(DeleteParameter {
+                        store: &self.store,
+                        key,
+                        admission_policy: &self.admission_policy,
+                        ttl_ticker: &self.ttl_ticker,
+                        eviction_listener: &self.eviction_listener,
+                        reason: EvictionReason::Explicit,
+                    });
+                }
+                (CommandStatus::Accepted, None)
+            }
+            CommandType::Clear => {
+                self.clear();
+                (CommandStatus::Accepted, None)
+            }
+            CommandType::Shutdown => (CommandStatus::Accepted, None),
+        }
+    }
+
+    /// Removes all the keys from the `Store`, the `AdmissionPolicy` and the `TTLTicker`, notifying the `eviction_listener` (if any) of each removed key.
+    fn clear(&self) {
+        self.store.drain(|key, stored_value| {
+            let reason = if self.store.has_expired(stored_value.expire_after()) { EvictionReason::Expired } else { EvictionReason::Explicit };
+            self.notify_removal(key, stored_value.value_ref(), reason);
+        });
+        self.admission_policy.remove_all();
+        self.ttl_ticker.clear();
+    }
+
+    /// Applies the `put_mode` before putting the key.
+    /// Returns the rejected status if the key is present and the `put_mode` is `InsertOnly`, or if the key is absent and the `put_mode` is `UpdateOnly`,
+    /// else returns the key_id of the present key (that the put replaces) along with the reason of its removal, read `replaced`.
+    ///
+    /// `CommandType::PutOrUpdate` is applied with `PutMode::UpdateOnly` treated as `PutMode::Overwrite`, because `put_or_update` puts an absent key.
+    fn apply_put_mode(&self, key: &Key, put_mode: PutMode) -> Result<Option<(KeyId, EvictionReason)>, CommandStatus> {
+        let replaced = self.replaced(key);
+        match put_mode {
+            PutMode::InsertOnly if replaced.is_some() => Err(CommandStatus::Rejected(KeyAlreadyExists)),
+            PutMode::UpdateOnly if replaced.is_none() => Err(CommandStatus::Rejected(KeyDoesNotExist)),
+            _ => Ok(replaced),
+        }
+    }
+
+    /// Returns the key_id of the present key along with the reason of its removal if a put replaces it,
+    /// which is `EvictionReason::Expired` if the key has expired, else `EvictionReason::Replaced`.
+    fn replaced(&self, key: &Key) -> Option<(KeyId, EvictionReason)> {
+        self.store.map_stored_value(key, |stored_value| {
+            let reason = if self.store.has_expired(stored_value.expire_after()) { EvictionReason::Expired } else { EvictionReason::Replaced };
+            (stored_value.key_id(), reason)
+        })
+    }
+
+    /// Removes the existing key (if `replaced`) that is being replaced by an admitted put, and returns its value.
+    /// It is invoked only after the put is admitted, so that a rejected put leaves the existing key in the cache.
+    fn remove_replaced(&self, key: &Key, replaced: Option<(KeyId, EvictionReason)>) -> Option<Value> {
+        replaced.and_then(|_| {
+            let (_, previous_value) = Self::delete(DeleteParameter {
+                store: &self.store,
+                key,
+                admission_policy: &self.admission_policy,
+                ttl_ticker: &self.ttl_ticker,
+                eviction_listener: &self.eviction_listener,
+                reason: EvictionReason::Replaced,
+            });
+            previous_value
+        })
+    }
+
+    /// Moves the value of the alive `old_key` to the key of the `key_description`, keeping its weight and its expiry.
+    /// Returns the rejected status if the `old_key` is not alive, or if the new key is present and the `PutMode` is `InsertOnly`.
+    /// With the other `PutMode`s, the new key is replaced, and its value is returned.
+    ///
+    /// The new key is admitted as a replacement of the `old_key` (and of the existing new key, if any), so that the weight of the replaced keys
+    /// is available to it. The `Store` is changed only after the new key is admitted, so a rejected rename leaves both the keys as they were.
+    fn rename<DeleteHook>(&self,
+                          old_key: &Key,
+                          mut key_description: KeyDescription<Key>,
+                          clone_value: fn(&Value) -> Value,
+                          delete_hook: &DeleteHook) -> (CommandStatus, Option<Value>) where DeleteHook: Fn(Key) {
+        let old_key_id = match self.store.key_id_of(old_key) {
+            Some(key_id) => key_id,
+            None => return (CommandStatus::Rejected(KeyDoesNotExist), None),
+        };
+        if old_key == key_description.key() {
+            return (CommandStatus::Accepted, None);
+        }
+        let replaced = match self.apply_put_mode(key_description.key(), self.put_mode.ignoring_update_only()) {
+            Ok(replaced) => replaced,
+            Err(status) => return (status, None),
+        };
+        let replaced_keys: Vec<(KeyId, EvictionReason)> = std::iter::once((old_key_id, EvictionReason::Explicit)).chain(replaced).collect();
+
+        key_description.weight = self.admission_policy.weight_of(&old_key_id).unwrap_or(key_description.weight);
+        let status = self.admission_policy.maybe_replace(&key_description, &replaced_keys, delete_hook);
+        if status != CommandStatus::Accepted {
+            self.stats_counter.reject_key();
+            return (status, None);
+        }
+        let previous_value = self.remove_replaced(key_description.key(), replaced);
+        match self.store.rename(old_key, key_description.clone_key(), key_description.id, clone_value) {
+            Some(key_id_expiry) => {
+                if let Some(expiry) = key_id_expiry.1 {
+                    self.ttl_ticker.delete(&key_id_expiry.0, &expiry);
+                    self.ttl_ticker.put(key_description.id, expiry);
+                }
+                (status, previous_value)
+            }
+            None => {
+                self.admission_policy.delete(&key_description.id, EvictionReason::Explicit);
+                (CommandStatus::Rejected(KeyDoesNotExist), previous_value)
+            }
+        }
+    }
+
+    /// Compares the `version` against the version of the existing key, before putting the key with the `version`.
+    /// Returns the rejected status if the existing key has a version greater than or equal to the `version`,
+    /// else returns the key_id of the present key (read `replaced`), so that the existing key is replaced only after the versioned put is admitted.
+    /// An existing key without a version is always replaced.
+    ///
+    /// `PutMode` does not apply to the versioned puts, the versions decide whether the existing key is replaced.
+    fn apply_version(&self, key: &Key, version: u64) -> Result<Option<(KeyId, EvictionReason)>, CommandStatus> {
+        if let Some(existing_version) = self.store.version_of(key) {
+            if existing_version >= version {
+                return Err(CommandStatus::Rejected(StaleVersion));
+            }
+        }
+        Ok(self.replaced(key))
+    }
+
+    /// Admits the key that is already present in the `Store` with the id of the `key_description`.
+    /// The key is deleted from the `Store` if `AdmissionPolicy` rejects it.
+    /// Returns the rejected status if the key is no longer present with the same id, for example if it got deleted before getting admitted.
+    fn admit<DeleteHook>(&self, key_description: &KeyDescription<Key>, expire_after: ExpireAfter, delete_hook: &DeleteHook) -> CommandStatus where DeleteHook: Fn(Key) {
+        let key_id = self.store.map_stored_value(key_description.key(), |stored_value| stored_value.key_id());
+        if key_id != Some(key_description.id) {
+            return CommandStatus::Rejected(KeyDoesNotExist);
+        }
+        let status = self.admission_policy.maybe_add(key_description, delete_hook);
+        if let CommandStatus::Accepted = status {
+            self.ttl_ticker.put(key_description.id, expire_after);
+        } else {
+            self.store.delete_if_key_id(key_description.key(), key_description.id);
+            self.stats_counter.reject_key();
+        }
+        status
+    }
+
+    /// Puts the key if it is admitted, `replace` is invoked after the key is admitted (and before it is put in the `Store`) to remove the key it replaces.
+    /// Returns the status along with the value returned by `replace`.
+    fn put<DeleteHook, ReplaceFn>(put_parameters: PutParameter<Key, Value, DeleteHook>, replace: ReplaceFn) -> (CommandStatus, Option<Value>)
+        where DeleteHook: Fn(Key),
+              ReplaceFn: FnOnce() -> Option<Value> {
+        let status = Self::admission_status(&put_parameters);
         if let CommandStatus::Accepted = status {
+            let previous_value = replace();
             put_parameters.store.put(
                 put_parameters.key_description.clone_key(),
                 put_parameters.value,
                 put_parameters.key_description.id,
             );
-        } else {
-            put_parameters.stats_counter.reject_key();
+            return (status, previous_value);
         }
-        status
+        put_parameters.stats_counter.reject_key();
+        (status, None)
     }
 
-    fn put_with_ttl<DeleteHook>(put_with_ttl_parameter: PutWithTTLParameter<Key, Value, DeleteHook>) -> CommandStatus where DeleteHook: Fn(Key) {
-        let status = put_with_ttl_parameter.put_parameter.admission_policy.maybe_add(
-            put_with_ttl_parameter.put_parameter.key_description,
-            put_with_ttl_parameter.put_parameter.delete_hook,
-        );
+    fn put_with_ttl<DeleteHook, ReplaceFn>(put_with_ttl_parameter: PutWithTTLParameter<Key, Value, DeleteHook>, replace: ReplaceFn) -> (CommandStatus, Option<Value>)
+        where DeleteHook: Fn(Key),
+              ReplaceFn: FnOnce() -> Option<Value> {
+        let status = Self::admission_status(&put_with_ttl_parameter.put_parameter);
         if let CommandStatus::Accepted = status {
+            let previous_value = replace();
             let expiry = put_with_ttl_parameter.put_parameter.store.put_with_ttl(
                 put_with_ttl_parameter.put_parameter.key_description.clone_key(),
                 put_with_ttl_parameter.put_parameter.value,
@@ -218,22 +969,51 @@ impl<Key, Value> CommandExecutor<Key, Value>
                 put_with_ttl_parameter.put_parameter.key_description.id,
                 expiry,
             );
-        } else {
-            put_with_ttl_parameter.put_parameter.stats_counter.reject_key();
+            return (status, previous_value);
         }
-        status
+        put_with_ttl_parameter.put_parameter.stats_counter.reject_key();
+        (status, None)
+    }
+
+    /// Admits the key using its reserved weight if the put is reserved, else delegates to `AdmissionPolicy` which may accept or reject the key.
+    /// The weight of the key that the put replaces (if any) is available to the key, so an overwrite needs only the difference of the weights.
+    fn admission_status<DeleteHook>(put_parameters: &PutParameter<Key, Value, DeleteHook>) -> CommandStatus where DeleteHook: Fn(Key) {
+        if put_parameters.is_reserved {
+            return put_parameters.admission_policy.add_reserved(put_parameters.key_description);
+        }
+        put_parameters.admission_policy.maybe_replace(
+            put_parameters.key_description,
+            put_parameters.replaced.as_slice(),
+            put_parameters.delete_hook,
+        )
     }
 
-    fn delete(delete_parameter: DeleteParameter<Key, Value>) -> CommandStatus {
-        let may_be_key_id_expiry = delete_parameter.store.delete(delete_parameter.key);
-        if let Some(key_id_expiry) = may_be_key_id_expiry {
-            delete_parameter.admission_policy.delete(&key_id_expiry.0);
+    /// Removes the key and returns its value, unless the value has expired.
+    /// The eviction listener (and the audit log) receives the `reason` of the `DeleteParameter`, or `EvictionReason::Expired` if the value has expired.
+    fn delete(delete_parameter: DeleteParameter<Key, Value>) -> (CommandStatus, Option<Value>) {
+        let may_be_removed = delete_parameter.store.remove(delete_parameter.key);
+        if let Some((value, key_id_expiry)) = may_be_removed {
+            let has_expired = delete_parameter.store.has_expired(key_id_expiry.1);
+            let reason = if has_expired { EvictionReason::Expired } else { delete_parameter.reason };
+            delete_parameter.admission_policy.delete(&key_id_expiry.0, reason);
             if let Some(expiry) = key_id_expiry.1 {
                 delete_parameter.ttl_ticker.delete(&key_id_expiry.0, &expiry);
             }
-            return CommandStatus::Accepted;
+            if let Some(eviction_listener) = delete_parameter.eviction_listener {
+                eviction_listener(delete_parameter.key, &value, reason);
+            }
+            if has_expired {
+                return (CommandStatus::Accepted, None);
+            }
+            return (CommandStatus::Accepted, Some(value));
+        }
+        (CommandStatus::Rejected(KeyDoesNotExist), None)
+    }
+
+    fn notify_removal(&self, key: &Key, value: &Value, reason: EvictionReason) {
+        if let Some(eviction_listener) = &self.eviction_listener {
+            eviction_listener(key, value, reason);
         }
-        CommandStatus::Rejected(KeyDoesNotExist)
     }
 }
 
@@ -245,8 +1025,10 @@ mod tests {
 
     use crate::cache::clock::{ClockType, SystemClock};
     use crate::cache::command::{CommandStatus, CommandType};
+    use crate::cache::command::adaptive_capacity::AdaptiveCapacity;
     use crate::cache::command::command_executor::{CommandExecutor, shutdown_result};
-    use crate::cache::command::RejectionReason::{KeyDoesNotExist, KeyWeightIsGreaterThanCacheWeight};
+    use crate::cache::config::{CapacityListenerFn, PutMode};
+    use crate::cache::command::RejectionReason::{KeyAlreadyExists, KeyDoesNotExist, KeyWeightIsGreaterThanCacheWeight};
     use crate::cache::expiration::config::TTLConfig;
     use crate::cache::expiration::TTLTicker;
     use crate::cache::key_description::KeyDescription;
@@ -254,91 +1036,695 @@ mod tests {
     use crate::cache::policy::config::CacheWeightConfig;
     use crate::cache::stats::ConcurrentStatsCounter;
     use crate::cache::store::Store;
+    use crate::cache::store::shard_hash::ShardHashBuilder;
 
     fn no_action_ttl_ticker() -> Arc<TTLTicker> {
         TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), SystemClock::boxed()), |_key_id| {})
     }
 
     fn test_store(clock: ClockType, stats_counter: Arc<ConcurrentStatsCounter>) -> Arc<Store<&'static str, &'static str>> {
-        Store::new(clock, stats_counter, 16, 4)
+        Store::new(clock, stats_counter, 16, 4, ShardHashBuilder::default())
     }
 
     fn test_cache_weight_config() -> CacheWeightConfig {
         CacheWeightConfig::new(100, 4, 100)
     }
 
-    mod setup {
-        use std::time::SystemTime;
+    #[test]
+    fn result_on_shutdown() {
+        let result = shutdown_result();
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn puts_a_key_value_after_shutdown_with_delay() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
 
-        use crate::cache::clock::Clock;
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+        command_executor.shutdown().unwrap().handle().await;
 
-        #[derive(Clone)]
-        pub(crate) struct UnixEpochClock;
+        thread::sleep(Duration::from_secs(1));
 
-        impl Clock for UnixEpochClock {
-            fn now(&self) -> SystemTime {
-                SystemTime::UNIX_EPOCH
-            }
-        }
+        let send_result = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+        ));
+
+        assert!(send_result.is_err() || send_result.unwrap().handle().await == CommandStatus::ShuttingDown);
+    }
+
+    #[tokio::test]
+    async fn puts_a_key_value_after_shutdown() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+        command_executor.shutdown().unwrap().handle().await;
+
+        let send_result = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+        ));
+        assert!(send_result.is_err() || send_result.unwrap().handle().await == CommandStatus::ShuttingDown);
     }
 
     #[test]
-    fn result_on_shutdown() {
-        let result = shutdown_result();
-        assert!(result.is_err());
+    fn executes_the_commands_sent_before_shutdown() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+        let _ = command_executor.send(CommandType::Put(KeyDescription::new("topic", 1, 1029, 10), "microservices"));
+        let _ = command_executor.send(CommandType::Put(KeyDescription::new("disk", 2, 2076, 10), "SSD"));
+        let _ = command_executor.shutdown();
+
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert_eq!(Some("SSD"), store.get(&"disk"));
+    }
+
+    #[test]
+    fn join_the_threads_after_shutdown() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+        let _ = command_executor.send(CommandType::Put(KeyDescription::new("topic", 1, 1029, 10), "microservices"));
+        let _ = command_executor.shutdown();
+        command_executor.join();
+
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert_eq!(1, Arc::strong_count(&store));
+    }
+
+    #[test]
+    fn counts_the_commands_dropped_after_shutdown() {
+        let (entered_sender, entered_receiver) = crossbeam_channel::bounded(1);
+        let (release_sender, release_receiver) = crossbeam_channel::bounded::<()>(0);
+        let on_full: Arc<CapacityListenerFn> = Arc::new(move || {
+            let _ = entered_sender.send(());
+            let _ = release_receiver.recv();
+        });
+
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let cache_weight_config = test_cache_weight_config().with_capacity_listeners(Some(on_full), None);
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, cache_weight_config, stats_counter.clone()));
+
+        let command_executor = Arc::new(CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        ));
+        let _ = command_executor.send(CommandType::Put(KeyDescription::new("topic", 1, 1029, 100), "microservices"));
+        entered_receiver.recv().unwrap();
+
+        let shutdown = thread::spawn({
+            let command_executor = command_executor.clone();
+            move || {
+                let _ = command_executor.shutdown();
+                command_executor.dropped_commands()
+            }
+        });
+        thread::sleep(Duration::from_millis(100));
+
+        let _ = command_executor.send(CommandType::Put(KeyDescription::new("disk", 2, 2076, 10), "SSD"));
+        let _ = command_executor.send(CommandType::Put(KeyDescription::new("cache", 3, 3012, 10), "cached"));
+        release_sender.send(()).unwrap();
+
+        assert_eq!(Some(2), shutdown.join().unwrap());
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert_eq!(None, store.get(&"disk"));
+    }
+
+    #[test]
+    fn acknowledges_every_command_queued_around_shutdown() {
+        let (entered_sender, entered_receiver) = crossbeam_channel::bounded(1);
+        let (release_sender, release_receiver) = crossbeam_channel::bounded::<()>(0);
+        let on_full: Arc<CapacityListenerFn> = Arc::new(move || {
+            let _ = entered_sender.send(());
+            let _ = release_receiver.recv();
+        });
+
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let cache_weight_config = test_cache_weight_config().with_capacity_listeners(Some(on_full), None);
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, cache_weight_config, stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+        let _ = command_executor.send(CommandType::Put(KeyDescription::new("topic", 1, 1029, 100), "microservices"));
+        entered_receiver.recv().unwrap();
+
+        let queued_before_shutdown = command_executor.send(CommandType::Put(KeyDescription::new("disk", 2, 2076, 10), "SSD")).unwrap();
+        let shutdown = command_executor.send(CommandType::Shutdown).unwrap();
+
+        let queued_after_shutdown = ["cache", "memory", "storage"].iter().enumerate().map(|(index, key)| {
+            command_executor.send(CommandType::Put(KeyDescription::new(*key, index as u64 + 3, 3000 + index as u64, 10), "value")).unwrap()
+        }).collect::<Vec<_>>();
+        release_sender.send(()).unwrap();
+
+        assert_eq!(CommandStatus::Accepted, shutdown.block_on_done());
+        assert_ne!(CommandStatus::ShuttingDown, queued_before_shutdown.block_on_done());
+        for acknowledgement in queued_after_shutdown {
+            assert_eq!(CommandStatus::ShuttingDown, acknowledgement.block_on_done());
+        }
+    }
+
+    #[tokio::test]
+    async fn adaptive_executor_grows_the_capacity_under_a_burst_of_writes() {
+        let (entered_sender, entered_receiver) = crossbeam_channel::bounded(1);
+        let (release_sender, release_receiver) = crossbeam_channel::bounded::<()>(0);
+        let on_full: Arc<CapacityListenerFn> = Arc::new(move || {
+            let _ = entered_sender.send(());
+            let _ = release_receiver.recv();
+        });
+
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let cache_weight_config = CacheWeightConfig::new(100, 4, 200).with_capacity_listeners(Some(on_full), None);
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, cache_weight_config, stats_counter.clone()));
+
+        let command_executor = CommandExecutor::adaptive(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            no_action_ttl_ticker(),
+            AdaptiveCapacity::new(2, 8),
+            PutMode::InsertOnly,
+            None,
+        );
+        assert_eq!(2, command_executor.capacity());
+
+        let _ = command_executor.send(CommandType::Put(KeyDescription::new("topic", 1, 1029, 200), "microservices"));
+        entered_receiver.recv().unwrap();
+
+        let keys = ["disk", "cache", "memory", "storage", "network", "cpu"];
+        let acknowledgements = keys.iter().enumerate().map(|(index, key)| {
+            command_executor.send(CommandType::Put(KeyDescription::new(*key, index as u64 + 2, 2000 + index as u64, 10), "value")).unwrap()
+        }).collect::<Vec<_>>();
+        assert_eq!(8, command_executor.capacity());
+
+        release_sender.send(()).unwrap();
+        for acknowledgement in acknowledgements {
+            assert_ne!(CommandStatus::ShuttingDown, acknowledgement.handle().await);
+        }
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(2, command_executor.capacity());
+    }
+
+    #[test]
+    fn inline_executor_does_not_drop_commands_on_shutdown() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::inline(store, admission_policy, stats_counter, no_action_ttl_ticker(), PutMode::InsertOnly, None);
+        assert_eq!(None, command_executor.dropped_commands());
+
+        let _ = command_executor.shutdown();
+        assert_eq!(Some(0), command_executor.dropped_commands());
+    }
+
+    #[tokio::test]
+    async fn puts_a_key_value() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        let command_acknowledgement = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+        )).unwrap();
+        command_acknowledgement.handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+    }
+
+    #[tokio::test]
+    async fn key_value_gets_rejected_given_its_weight_is_more_than_the_cache_weight() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter.clone(),
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        let command_acknowledgement = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 200),
+            "microservices",
+        )).unwrap();
+        let status = command_acknowledgement.handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(None, store.get(&"topic"));
+        assert_eq!(CommandStatus::Rejected(KeyWeightIsGreaterThanCacheWeight), status);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_key_value_and_increase_stats() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter.clone(),
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        let command_acknowledgement = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 200),
+            "microservices",
+        )).unwrap();
+        let status = command_acknowledgement.handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(KeyWeightIsGreaterThanCacheWeight), status);
+        assert_eq!(1, stats_counter.keys_rejected());
+    }
+
+    #[tokio::test]
+    async fn puts_a_couple_of_key_values() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        let acknowledgement = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+        )).unwrap();
+        let other_acknowledgment = command_executor.send(CommandType::Put(
+            KeyDescription::new("disk", 2, 2076, 3),
+            "SSD",
+        )).unwrap();
+        acknowledgement.handle().await;
+        other_acknowledgment.handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert_eq!(Some("SSD"), store.get(&"disk"));
+    }
+
+    #[tokio::test]
+    async fn puts_a_key_value_with_ttl() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let ttl_ticker = no_action_ttl_ticker();
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            ttl_ticker.clone(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        let acknowledgement = command_executor.send(CommandType::PutWithTTL(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+            Duration::from_secs(10),
+        )).unwrap();
+        acknowledgement.handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+
+        let expiry = store.get_ref(&"topic").unwrap().value().expire_after().unwrap();
+        let expiry_in_ttl_ticker = ttl_ticker.get(&1, &expiry).unwrap();
+
+        assert_eq!(expiry, expiry_in_ttl_ticker);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_key_value_with_ttl_and_increase_stats() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter.clone(),
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        let acknowledgement = command_executor.send(CommandType::PutWithTTL(
+            KeyDescription::new("topic", 1, 1029, 4000),
+            "microservices",
+            Duration::from_secs(10),
+        )).unwrap();
+        acknowledgement.handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(1, stats_counter.keys_rejected());
+    }
+
+    #[tokio::test]
+    async fn puts_a_reserved_key_value() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy.clone(),
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        assert!(admission_policy.reserve(60));
+        let status = command_executor.send(CommandType::PutReserved(
+            KeyDescription::new("topic", 1, 1029, 30),
+            "microservices",
+        )).unwrap().handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert_eq!(30, admission_policy.weight_used());
+        assert!(!admission_policy.reserve(41));
+    }
+
+    #[tokio::test]
+    async fn puts_a_reserved_key_value_with_ttl() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let ttl_ticker = no_action_ttl_ticker();
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy.clone(),
+            stats_counter,
+            ttl_ticker.clone(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        assert!(admission_policy.reserve(60));
+        let status = command_executor.send(CommandType::PutReservedWithTTL(
+            KeyDescription::new("topic", 1, 1029, 30),
+            "microservices",
+            Duration::from_secs(10),
+        )).unwrap().handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let expiry = store.get_ref(&"topic").unwrap().value().expire_after().unwrap();
+        assert_eq!(Some(expiry), ttl_ticker.get(&1, &expiry));
+        assert_eq!(30, admission_policy.weight_used());
+    }
+
+    #[tokio::test]
+    async fn rejected_reserved_put_releases_the_reserved_weight() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy.clone(),
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        let _ = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+        )).unwrap().handle().await;
+
+        assert!(admission_policy.reserve(20));
+        let status = command_executor.send(CommandType::PutReserved(
+            KeyDescription::new("topic", 2, 1029, 20),
+            "cache",
+        )).unwrap().handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(KeyAlreadyExists), status);
+        assert!(admission_policy.reserve(90));
+    }
+
+    #[tokio::test]
+    async fn admits_a_key_present_in_the_store() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store: Arc<Store<&str, i64>> = Store::new(SystemClock::boxed(), stats_counter.clone(), 16, 4, ShardHashBuilder::default());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+        let ttl_ticker = no_action_ttl_ticker();
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy.clone(),
+            stats_counter,
+            ttl_ticker.clone(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        store.increment_with_ttl("requests", 1, 1, Duration::from_secs(10));
+        let expiry = store.get_ref(&"requests").unwrap().value().expire_after().unwrap();
+        let status = command_executor.send(CommandType::Admit(
+            KeyDescription::new("requests", 1, 1029, 10),
+            expiry,
+            None,
+        )).unwrap().handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some(1), store.get(&"requests"));
+        assert_eq!(Some(10), admission_policy.weight_of(&1));
+        assert_eq!(Some(expiry), ttl_ticker.get(&1, &expiry));
+    }
+
+    #[tokio::test]
+    async fn rejects_admitting_a_key_and_deletes_it_from_the_store() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store: Arc<Store<&str, i64>> = Store::new(SystemClock::boxed(), stats_counter.clone(), 16, 4, ShardHashBuilder::default());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy,
+            stats_counter.clone(),
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        store.increment_with_ttl("requests", 1, 1, Duration::from_secs(10));
+        let expiry = store.get_ref(&"requests").unwrap().value().expire_after().unwrap();
+        let status = command_executor.send(CommandType::Admit(
+            KeyDescription::new("requests", 1, 1029, 4000),
+            expiry,
+            None,
+        )).unwrap().handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(KeyWeightIsGreaterThanCacheWeight), status);
+        assert_eq!(None, store.get(&"requests"));
+        assert_eq!(1, stats_counter.keys_rejected());
+    }
+
+    #[tokio::test]
+    async fn rejects_admitting_a_key_not_present_in_the_store() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store: Arc<Store<&str, i64>> = Store::new(SystemClock::boxed(), stats_counter.clone(), 16, 4, ShardHashBuilder::default());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::new(
+            store.clone(),
+            admission_policy.clone(),
+            stats_counter,
+            no_action_ttl_ticker(),
+            10,
+            PutMode::InsertOnly,
+            None,
+        );
+
+        let status = command_executor.send(CommandType::Admit(
+            KeyDescription::new("requests", 1, 1029, 10),
+            SystemClock::boxed().now(),
+            None,
+        )).unwrap().handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(KeyDoesNotExist), status);
+        assert_eq!(None, admission_policy.weight_of(&1));
     }
 
     #[tokio::test]
-    async fn puts_a_key_value_after_shutdown_with_delay() {
+    async fn deletes_a_key() {
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
         let store = test_store(SystemClock::boxed(), stats_counter.clone());
         let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+        let ttl_ticker = no_action_ttl_ticker();
 
         let command_executor = CommandExecutor::new(
             store.clone(),
             admission_policy,
             stats_counter,
-            no_action_ttl_ticker(),
+            ttl_ticker.clone(),
             10,
+            PutMode::InsertOnly,
+            None,
         );
-        command_executor.shutdown().unwrap().handle().await;
-
-        thread::sleep(Duration::from_secs(1));
 
-        let send_result = command_executor.send(CommandType::Put(
-            KeyDescription::new("topic", 1, 1029, 10),
+        let acknowledgement = command_executor.send(CommandType::PutWithTTL(
+            KeyDescription::new("topic", 10, 1029, 10),
             "microservices",
-        ));
+            Duration::from_secs(10),
+        )).unwrap();
+        acknowledgement.handle().await;
 
-        assert!(send_result.is_err() || send_result.unwrap().handle().await == CommandStatus::ShuttingDown);
+        let expiry = store.get_ref(&"topic").unwrap().value().expire_after().unwrap();
+        let expiry_in_ttl_ticker = ttl_ticker.get(&10, &expiry).unwrap();
+
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert_eq!(expiry, expiry_in_ttl_ticker);
+
+        let acknowledgement =
+            command_executor.send(CommandType::Delete("topic")).unwrap();
+        acknowledgement.handle().await;
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(None, store.get(&"topic"));
+        assert_eq!(None, ttl_ticker.get(&10, &expiry));
     }
 
     #[tokio::test]
-    async fn puts_a_key_value_after_shutdown() {
+    async fn deletes_the_weight_and_the_expiry_of_the_taken_keys() {
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
         let store = test_store(SystemClock::boxed(), stats_counter.clone());
         let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+        let ttl_ticker = no_action_ttl_ticker();
 
         let command_executor = CommandExecutor::new(
             store.clone(),
-            admission_policy,
+            admission_policy.clone(),
             stats_counter,
-            no_action_ttl_ticker(),
+            ttl_ticker.clone(),
             10,
+            PutMode::InsertOnly,
+            None,
         );
-        command_executor.shutdown().unwrap().handle().await;
 
-        let send_result = command_executor.send(CommandType::Put(
-            KeyDescription::new("topic", 1, 1029, 10),
+        let acknowledgement = command_executor.send(CommandType::PutWithTTL(
+            KeyDescription::new("topic", 10, 1029, 10),
             "microservices",
-        ));
-        assert!(send_result.is_err() || send_result.unwrap().handle().await == CommandStatus::ShuttingDown);
+            Duration::from_secs(10),
+        )).unwrap();
+        acknowledgement.handle().await;
+
+        let (_, key_id_expiry) = store.take(&"topic").unwrap();
+        let expiry = key_id_expiry.1.unwrap();
+
+        let acknowledgement = command_executor.send(CommandType::DeleteTaken(vec![("topic", key_id_expiry)])).unwrap();
+        assert_eq!(CommandStatus::Accepted, acknowledgement.handle().await);
+
+        command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(0, admission_policy.weight_used());
+        assert_eq!(None, ttl_ticker.get(&10, &expiry));
     }
 
     #[tokio::test]
-    async fn puts_a_key_value() {
+    async fn deletion_of_a_non_existing_key_value_gets_rejected() {
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
-        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let store= test_store(SystemClock::boxed(), stats_counter.clone());
         let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
 
         let command_executor = CommandExecutor::new(
@@ -347,211 +1733,236 @@ mod tests {
             stats_counter,
             no_action_ttl_ticker(),
             10,
+            PutMode::InsertOnly,
+            None,
         );
 
-        let command_acknowledgement = command_executor.send(CommandType::Put(
-            KeyDescription::new("topic", 1, 1029, 10),
-            "microservices",
-        )).unwrap();
-        command_acknowledgement.handle().await;
+        let acknowledgement =
+            command_executor.send(CommandType::Delete("non-existing")).unwrap();
+        let status = acknowledgement.handle().await;
 
         command_executor.shutdown().unwrap().handle().await;
-        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert_eq!(CommandStatus::Rejected(KeyDoesNotExist), status);
     }
 
     #[tokio::test]
-    async fn key_value_gets_rejected_given_its_weight_is_more_than_the_cache_weight() {
+    async fn put_of_a_present_key_gets_rejected_given_insert_only_put_mode() {
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
         let store = test_store(SystemClock::boxed(), stats_counter.clone());
         let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
 
         let command_executor = CommandExecutor::new(
             store.clone(),
-            admission_policy,
-            stats_counter.clone(),
+            admission_policy.clone(),
+            stats_counter,
             no_action_ttl_ticker(),
             10,
+            PutMode::InsertOnly,
+            None,
         );
 
-        let command_acknowledgement = command_executor.send(CommandType::Put(
-            KeyDescription::new("topic", 1, 1029, 200),
+        let _ = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
             "microservices",
         )).unwrap();
-        let status = command_acknowledgement.handle().await;
+        let status = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 2, 1029, 10),
+            "cache",
+        )).unwrap().handle().await;
 
         command_executor.shutdown().unwrap().handle().await;
-        assert_eq!(None, store.get(&"topic"));
-        assert_eq!(CommandStatus::Rejected(KeyWeightIsGreaterThanCacheWeight), status);
+        assert_eq!(CommandStatus::Rejected(KeyAlreadyExists), status);
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert!(!admission_policy.contains(&2));
     }
 
     #[tokio::test]
-    async fn rejects_a_key_value_and_increase_stats() {
+    async fn put_of_a_present_key_replaces_it_given_overwrite_put_mode() {
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
         let store = test_store(SystemClock::boxed(), stats_counter.clone());
         let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
 
         let command_executor = CommandExecutor::new(
             store.clone(),
-            admission_policy,
-            stats_counter.clone(),
+            admission_policy.clone(),
+            stats_counter,
             no_action_ttl_ticker(),
             10,
+            PutMode::Overwrite,
+            None,
         );
 
-        let command_acknowledgement = command_executor.send(CommandType::Put(
-            KeyDescription::new("topic", 1, 1029, 200),
+        let _ = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
             "microservices",
         )).unwrap();
-        let status = command_acknowledgement.handle().await;
+        let status = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 2, 1029, 20),
+            "cache",
+        )).unwrap().handle().await;
 
         command_executor.shutdown().unwrap().handle().await;
-        assert_eq!(CommandStatus::Rejected(KeyWeightIsGreaterThanCacheWeight), status);
-        assert_eq!(1, stats_counter.keys_rejected());
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("cache"), store.get(&"topic"));
+        assert!(!admission_policy.contains(&1));
+        assert_eq!(20, admission_policy.weight_used());
     }
 
     #[tokio::test]
-    async fn puts_a_couple_of_key_values() {
+    async fn put_of_a_present_key_replaces_it_given_update_only_put_mode() {
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
         let store = test_store(SystemClock::boxed(), stats_counter.clone());
         let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
 
         let command_executor = CommandExecutor::new(
             store.clone(),
-            admission_policy,
+            admission_policy.clone(),
             stats_counter,
             no_action_ttl_ticker(),
             10,
+            PutMode::UpdateOnly,
+            None,
         );
 
-        let acknowledgement = command_executor.send(CommandType::Put(
+        let _ = command_executor.send(CommandType::PutOrUpdate(
             KeyDescription::new("topic", 1, 1029, 10),
             "microservices",
+            None,
         )).unwrap();
-        let other_acknowledgment = command_executor.send(CommandType::Put(
-            KeyDescription::new("disk", 2, 2076, 3),
-            "SSD",
-        )).unwrap();
-        acknowledgement.handle().await;
-        other_acknowledgment.handle().await;
+        let status = command_executor.send(CommandType::PutWithTTL(
+            KeyDescription::new("topic", 2, 1029, 20),
+            "cache",
+            Duration::from_secs(300),
+        )).unwrap().handle().await;
 
         command_executor.shutdown().unwrap().handle().await;
-        assert_eq!(Some("microservices"), store.get(&"topic"));
-        assert_eq!(Some("SSD"), store.get(&"disk"));
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("cache"), store.get(&"topic"));
+        assert!(!admission_policy.contains(&1));
+        assert_eq!(20, admission_policy.weight_used());
     }
 
     #[tokio::test]
-    async fn puts_a_key_value_with_ttl() {
+    async fn rejected_put_of_a_present_key_retains_it_given_overwrite_put_mode() {
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
         let store = test_store(SystemClock::boxed(), stats_counter.clone());
         let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
 
-        let ttl_ticker = no_action_ttl_ticker();
         let command_executor = CommandExecutor::new(
             store.clone(),
-            admission_policy,
+            admission_policy.clone(),
             stats_counter,
-            ttl_ticker.clone(),
+            no_action_ttl_ticker(),
             10,
+            PutMode::Overwrite,
+            None,
         );
 
-        let acknowledgement = command_executor.send(CommandType::PutWithTTL(
+        let _ = command_executor.send(CommandType::Put(
             KeyDescription::new("topic", 1, 1029, 10),
             "microservices",
-            Duration::from_secs(10),
         )).unwrap();
-        acknowledgement.handle().await;
+        let status = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 2, 1029, 200),
+            "cache",
+        )).unwrap().handle().await;
 
         command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(KeyWeightIsGreaterThanCacheWeight), status);
         assert_eq!(Some("microservices"), store.get(&"topic"));
-
-        let expiry = store.get_ref(&"topic").unwrap().value().expire_after().unwrap();
-        let expiry_in_ttl_ticker = ttl_ticker.get(&1, &expiry).unwrap();
-
-        assert_eq!(expiry, expiry_in_ttl_ticker);
+        assert!(admission_policy.contains(&1));
+        assert_eq!(10, admission_policy.weight_used());
     }
 
     #[tokio::test]
-    async fn rejects_a_key_value_with_ttl_and_increase_stats() {
+    async fn rejected_versioned_put_of_a_present_key_retains_it() {
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
         let store = test_store(SystemClock::boxed(), stats_counter.clone());
         let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
 
         let command_executor = CommandExecutor::new(
             store.clone(),
-            admission_policy,
-            stats_counter.clone(),
+            admission_policy.clone(),
+            stats_counter,
             no_action_ttl_ticker(),
             10,
+            PutMode::Overwrite,
+            None,
         );
 
-        let acknowledgement = command_executor.send(CommandType::PutWithTTL(
-            KeyDescription::new("topic", 1, 1029, 4000),
+        let _ = command_executor.send(CommandType::PutVersioned(
+            KeyDescription::new("topic", 1, 1029, 10),
             "microservices",
-            Duration::from_secs(10),
+            1,
+            None,
         )).unwrap();
-        acknowledgement.handle().await;
+        let status = command_executor.send(CommandType::PutVersioned(
+            KeyDescription::new("topic", 2, 1029, 200),
+            "cache",
+            2,
+            None,
+        )).unwrap().handle().await;
 
         command_executor.shutdown().unwrap().handle().await;
-        assert_eq!(1, stats_counter.keys_rejected());
+        assert_eq!(CommandStatus::Rejected(KeyWeightIsGreaterThanCacheWeight), status);
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert_eq!(Some(1), store.version_of(&"topic"));
+        assert!(admission_policy.contains(&1));
+        assert_eq!(10, admission_policy.weight_used());
     }
 
     #[tokio::test]
-    async fn deletes_a_key() {
+    async fn put_of_an_absent_key_gets_rejected_given_update_only_put_mode() {
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
         let store = test_store(SystemClock::boxed(), stats_counter.clone());
         let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
-        let ttl_ticker = no_action_ttl_ticker();
 
         let command_executor = CommandExecutor::new(
             store.clone(),
-            admission_policy,
+            admission_policy.clone(),
             stats_counter,
-            ttl_ticker.clone(),
+            no_action_ttl_ticker(),
             10,
+            PutMode::UpdateOnly,
+            None,
         );
 
-        let acknowledgement = command_executor.send(CommandType::PutWithTTL(
-            KeyDescription::new("topic", 10, 1029, 10),
+        let status = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
             "microservices",
-            Duration::from_secs(10),
-        )).unwrap();
-        acknowledgement.handle().await;
-
-        let expiry = store.get_ref(&"topic").unwrap().value().expire_after().unwrap();
-        let expiry_in_ttl_ticker = ttl_ticker.get(&10, &expiry).unwrap();
-
-        assert_eq!(Some("microservices"), store.get(&"topic"));
-        assert_eq!(expiry, expiry_in_ttl_ticker);
-
-        let acknowledgement =
-            command_executor.send(CommandType::Delete("topic")).unwrap();
-        acknowledgement.handle().await;
+        )).unwrap().handle().await;
 
         command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(CommandStatus::Rejected(KeyDoesNotExist), status);
         assert_eq!(None, store.get(&"topic"));
-        assert_eq!(None, ttl_ticker.get(&10, &expiry));
+        assert_eq!(0, admission_policy.weight_used());
     }
 
     #[tokio::test]
-    async fn deletion_of_a_non_existing_key_value_gets_rejected() {
+    async fn put_or_update_of_an_absent_key_given_update_only_put_mode() {
         let stats_counter = Arc::new(ConcurrentStatsCounter::new());
-        let store= test_store(SystemClock::boxed(), stats_counter.clone());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
         let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
 
         let command_executor = CommandExecutor::new(
             store.clone(),
-            admission_policy,
+            admission_policy.clone(),
             stats_counter,
             no_action_ttl_ticker(),
             10,
+            PutMode::UpdateOnly,
+            None,
         );
 
-        let acknowledgement =
-            command_executor.send(CommandType::Delete("non-existing")).unwrap();
-        let status = acknowledgement.handle().await;
+        let status = command_executor.send(CommandType::PutOrUpdate(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+            None,
+        )).unwrap().handle().await;
 
         command_executor.shutdown().unwrap().handle().await;
-        assert_eq!(CommandStatus::Rejected(KeyDoesNotExist), status);
+        assert_eq!(CommandStatus::Accepted, status);
+        assert_eq!(Some("microservices"), store.get(&"topic"));
     }
 }
 
@@ -566,19 +1977,21 @@ mod sociable_tests {
     use crate::cache::command::{CommandStatus, CommandType};
     use crate::cache::command::command_executor::CommandExecutor;
     use crate::cache::command::command_executor::Store;
+    use crate::cache::config::PutMode;
     use crate::cache::expiration::config::TTLConfig;
     use crate::cache::expiration::TTLTicker;
     use crate::cache::key_description::KeyDescription;
     use crate::cache::policy::admission_policy::AdmissionPolicy;
     use crate::cache::policy::config::CacheWeightConfig;
     use crate::cache::stats::ConcurrentStatsCounter;
+    use crate::cache::store::shard_hash::ShardHashBuilder;
 
     fn no_action_ttl_ticker() -> Arc<TTLTicker> {
         TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), SystemClock::boxed()), |_key_id| {})
     }
 
     fn test_store(clock: ClockType, stats_counter: Arc<ConcurrentStatsCounter>) -> Arc<Store<&'static str, &'static str>> {
-        Store::new(clock, stats_counter, 16, 4)
+        Store::new(clock, stats_counter, 16, 4, ShardHashBuilder::default())
     }
 
     fn test_cache_weight_config() -> CacheWeightConfig {
@@ -597,6 +2010,8 @@ mod sociable_tests {
             stats_counter,
             no_action_ttl_ticker(),
             10,
+            PutMode::InsertOnly,
+            None,
         );
 
         let key_description = KeyDescription::new("topic", 1, 1029, 10);
@@ -629,6 +2044,8 @@ mod sociable_tests {
             stats_counter,
             no_action_ttl_ticker(),
             10,
+            PutMode::InsertOnly,
+            None,
         );
 
         let command_acknowledgement = command_executor.send(CommandType::Put(
@@ -665,6 +2082,8 @@ mod sociable_tests {
             stats_counter,
             no_action_ttl_ticker(),
             10,
+            PutMode::InsertOnly,
+            None,
         );
 
         let acknowledgement = command_executor.send(CommandType::Put(
@@ -694,6 +2113,8 @@ mod sociable_tests {
             stats_counter,
             no_action_ttl_ticker(),
             10,
+            PutMode::InsertOnly,
+            None,
         );
 
         let key_description = KeyDescription::new("topic", 1, 1029, 10);
@@ -705,11 +2126,116 @@ mod sociable_tests {
         command_acknowledgement.handle().await;
 
         let command_acknowledgement = command_executor.send(CommandType::UpdateWeight(
-            1, 20)).unwrap();
+            "topic", 1, 20)).unwrap();
         command_acknowledgement.handle().await;
 
         command_executor.shutdown().unwrap().handle().await;
         assert_eq!(Some("microservices"), store.get(&"topic"));
         assert_eq!(Some(20), admission_policy.weight_of(&key_id));
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod inline_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::cache::clock::{ClockType, SystemClock};
+    use crate::cache::command::{CommandStatus, CommandType};
+    use crate::cache::command::command_executor::CommandExecutor;
+    use crate::cache::command::command_executor::Store;
+    use crate::cache::config::PutMode;
+    use crate::cache::expiration::config::TTLConfig;
+    use crate::cache::expiration::TTLTicker;
+    use crate::cache::key_description::KeyDescription;
+    use crate::cache::policy::admission_policy::AdmissionPolicy;
+    use crate::cache::policy::config::CacheWeightConfig;
+    use crate::cache::stats::ConcurrentStatsCounter;
+    use crate::cache::store::shard_hash::ShardHashBuilder;
+
+    fn no_action_ttl_ticker() -> Arc<TTLTicker> {
+        TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), SystemClock::boxed()), |_key_id| {})
+    }
+
+    fn test_store(clock: ClockType, stats_counter: Arc<ConcurrentStatsCounter>) -> Arc<Store<&'static str, &'static str>> {
+        Store::new(clock, stats_counter, 16, 4, ShardHashBuilder::default())
+    }
+
+    fn test_cache_weight_config() -> CacheWeightConfig {
+        CacheWeightConfig::new(100, 4, 100)
+    }
+
+    #[test]
+    fn puts_a_key_value() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::inline(
+            store.clone(),
+            admission_policy.clone(),
+            stats_counter,
+            no_action_ttl_ticker(),
+            PutMode::InsertOnly,
+            None,
+        );
+
+        let _ = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+        )).unwrap();
+
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert!(admission_policy.contains(&1));
+    }
+
+    #[test]
+    fn deletes_a_key() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::inline(
+            store.clone(),
+            admission_policy.clone(),
+            stats_counter,
+            no_action_ttl_ticker(),
+            PutMode::InsertOnly,
+            None,
+        );
+
+        let _ = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+        )).unwrap();
+        let _ = command_executor.send(CommandType::Delete("topic")).unwrap();
+
+        assert_eq!(None, store.get(&"topic"));
+        assert!(!admission_policy.contains(&1));
+    }
+
+    #[tokio::test]
+    async fn puts_a_key_value_after_shutdown() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = test_store(SystemClock::boxed(), stats_counter.clone());
+        let admission_policy = Arc::new(AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone()));
+
+        let command_executor = CommandExecutor::inline(
+            store.clone(),
+            admission_policy,
+            stats_counter,
+            no_action_ttl_ticker(),
+            PutMode::InsertOnly,
+            None,
+        );
+        let status = command_executor.shutdown().unwrap().handle().await;
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let status = command_executor.send(CommandType::Put(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+        )).unwrap().handle().await;
+
+        assert_eq!(CommandStatus::ShuttingDown, status);
+        assert_eq!(None, store.get(&"topic"));
+    }
+}
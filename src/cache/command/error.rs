@@ -1,34 +1,149 @@
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 
+use crate::cache::config::error::WeightError;
+
 const SHUTDOWN_MESSAGE: &str = "could not accept the command for execution, probably the cache is being shutdown.";
+const TTL_DISABLED_MESSAGE: &str = "could not accept the command with time to live, the cache is built with no_ttl.";
+const EXECUTOR_THREAD_MESSAGE: &str = "could not wait for the command on a thread of the command executor, the command executor would wait for itself.";
 
 /// The execution of every write operation is returned a [`crate::cache::command::command_executor::CommandSendResult`].
 ///
 /// `CommandSendResult` wraps `CommandSendError` that is encountered when there is an error in sending a command to `crate::cache::command::command_executor::CommandExecutor`.
 ///
 /// `CommandSendError` is also returned to the clients if an attempt is made to perform any operation say `put`, `delete`, while the cache is being shutdown.
+///
+/// `CommandSendError` wraps a [`crate::cache::config::error::WeightError`] if the put is rejected because
+/// the [`crate::cache::config::FallibleWeightCalculationFn`] could not calculate the weight of the key/value pair.
+///
+/// `CommandSendError` is returned for an operation with time to live, if the cache is built with [`crate::cache::config::ConfigBuilder::no_ttl`].
+///
+/// `CommandSendError` is returned by [`crate::cache::cached::CacheD::clear_barrier`] if it is invoked from a listener that runs on a thread of
+/// `crate::cache::command::command_executor::CommandExecutor`, because waiting for the clear would block the thread that executes the clear.
+#[derive(Clone)]
 pub struct CommandSendError {
     command_description: String,
+    weight_error: Option<WeightError>,
+    ttl_disabled: bool,
+    on_executor_thread: bool,
 }
 
 impl CommandSendError {
     pub(crate) fn new(command_description: String) -> Self {
         CommandSendError {
-            command_description
+            command_description,
+            weight_error: None,
+            ttl_disabled: false,
+            on_executor_thread: false,
         }
     }
 
     pub(crate) fn shutdown() -> Self {
         CommandSendError {
-            command_description: SHUTDOWN_MESSAGE.to_string()
+            command_description: SHUTDOWN_MESSAGE.to_string(),
+            weight_error: None,
+            ttl_disabled: false,
+            on_executor_thread: false,
+        }
+    }
+
+    pub(crate) fn weight(weight_error: WeightError) -> Self {
+        CommandSendError {
+            command_description: "Put".to_string(),
+            weight_error: Some(weight_error),
+            ttl_disabled: false,
+            on_executor_thread: false,
+        }
+    }
+
+    pub(crate) fn ttl_disabled(command_description: &str) -> Self {
+        CommandSendError {
+            command_description: command_description.to_string(),
+            weight_error: None,
+            ttl_disabled: true,
+            on_executor_thread: false,
+        }
+    }
+
+    pub(crate) fn on_executor_thread(command_description: &str) -> Self {
+        CommandSendError {
+            command_description: command_description.to_string(),
+            weight_error: None,
+            ttl_disabled: false,
+            on_executor_thread: true,
+        }
+    }
+
+    /// Returns the `WeightError` if the command was not sent because the weight of the key/value pair could not be calculated.
+    pub fn weight_error(&self) -> Option<&WeightError> {
+        self.weight_error.as_ref()
+    }
+
+    /// Returns true if the command was not sent because it has a time to live, and the cache is built with [`crate::cache::config::ConfigBuilder::no_ttl`].
+    pub fn is_ttl_disabled(&self) -> bool {
+        self.ttl_disabled
+    }
+
+    /// Returns true if the command was not awaited because the caller runs on a thread of `crate::cache::command::command_executor::CommandExecutor`.
+    pub fn is_on_executor_thread(&self) -> bool {
+        self.on_executor_thread
+    }
+}
+
+impl From<WeightError> for CommandSendError {
+    fn from(weight_error: WeightError) -> Self {
+        CommandSendError::weight(weight_error)
+    }
+}
+
+/// `TryPutError` is returned by [`crate::cache::cached::CacheD::try_put`], which never blocks on a full command channel.
+///
+/// `ChannelFull`: the command channel of `crate::cache::command::command_executor::CommandExecutor` is full, the put is not sent.
+///
+/// `Send`: the put could not be sent for any other reason, for example, the cache is being shutdown, read [`CommandSendError`].
+#[derive(Debug)]
+pub enum TryPutError {
+    ChannelFull,
+    Send(CommandSendError),
+}
+
+impl From<CommandSendError> for TryPutError {
+    fn from(command_send_error: CommandSendError) -> Self {
+        TryPutError::Send(command_send_error)
+    }
+}
+
+impl From<WeightError> for TryPutError {
+    fn from(weight_error: WeightError) -> Self {
+        TryPutError::Send(CommandSendError::weight(weight_error))
+    }
+}
+
+/// Display implementation for `TryPutError`.
+impl Display for TryPutError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TryPutError::ChannelFull => write!(formatter, "could not send the command for execution, the command channel is full."),
+            TryPutError::Send(command_send_error) => Display::fmt(command_send_error, formatter),
         }
     }
 }
 
+/// Error implementation for `TryPutError`.
+impl Error for TryPutError {}
+
 /// Display implementation for `CommandSendError`. Currently, both `Display` and `Debug` return the same message.
 impl Display for CommandSendError {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(weight_error) = &self.weight_error {
+            return write!(formatter, "{} Command description: {}", weight_error, self.command_description);
+        }
+        if self.ttl_disabled {
+            return write!(formatter, "{} Command description: {}", TTL_DISABLED_MESSAGE, self.command_description);
+        }
+        if self.on_executor_thread {
+            return write!(formatter, "{} Command description: {}", EXECUTOR_THREAD_MESSAGE, self.command_description);
+        }
         write!(
             formatter,
             "{} Command description: {}",
@@ -41,12 +156,7 @@ impl Display for CommandSendError {
 /// Debug implementation for `CommandSendError`. Currently, both `Display` and `Debug` return the same message.
 impl Debug for CommandSendError {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            formatter,
-            "{} Command description: {}",
-            SHUTDOWN_MESSAGE,
-            self.command_description
-        )
+        Display::fmt(self, formatter)
     }
 }
 
@@ -55,7 +165,8 @@ impl Error for CommandSendError {}
 
 #[cfg(test)]
 mod tests {
-    use crate::cache::command::error::CommandSendError;
+    use crate::cache::command::error::{CommandSendError, TryPutError};
+    use crate::cache::config::error::WeightError;
 
     #[test]
     fn command_send_error_display() {
@@ -74,4 +185,54 @@ mod tests {
             "could not accept the command for execution, probably the cache is being shutdown. Command description: put",
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn command_send_error_with_weight_error() {
+        let error = CommandSendError::from(WeightError::new("value is too large"));
+        assert_eq!(Some(&WeightError::new("value is too large")), error.weight_error());
+        assert_eq!(
+            format!("{}", error),
+            "could not calculate the weight of the key/value pair, value is too large Command description: Put",
+        );
+    }
+
+    #[test]
+    fn command_send_error_without_weight_error() {
+        let error = CommandSendError::shutdown();
+        assert_eq!(None, error.weight_error());
+        assert!(!error.is_ttl_disabled());
+    }
+
+    #[test]
+    fn try_put_error_channel_full() {
+        let error = TryPutError::ChannelFull;
+        assert_eq!("could not send the command for execution, the command channel is full.", format!("{}", error));
+    }
+
+    #[test]
+    fn try_put_error_from_command_send_error() {
+        let error = TryPutError::from(CommandSendError::shutdown());
+        assert!(matches!(error, TryPutError::Send(_)));
+        assert_eq!(format!("{}", CommandSendError::shutdown()), format!("{}", error));
+    }
+
+    #[test]
+    fn command_send_error_on_executor_thread() {
+        let error = CommandSendError::on_executor_thread("ClearBarrier");
+        assert!(error.is_on_executor_thread());
+        assert_eq!(
+            format!("{}", error),
+            "could not wait for the command on a thread of the command executor, the command executor would wait for itself. Command description: ClearBarrier",
+        );
+    }
+
+    #[test]
+    fn command_send_error_with_ttl_disabled() {
+        let error = CommandSendError::ttl_disabled("PutWithTTL");
+        assert!(error.is_ttl_disabled());
+        assert_eq!(
+            format!("{}", error),
+            "could not accept the command with time to live, the cache is built with no_ttl. Command description: PutWithTTL",
+        );
+    }
+}
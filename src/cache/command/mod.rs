@@ -2,24 +2,46 @@ use std::hash::Hash;
 use std::time::Duration;
 
 use crate::cache::key_description::KeyDescription;
-use crate::cache::types::{KeyId, Weight};
+use crate::cache::store::KeyIdExpiry;
+use crate::cache::types::{ExpireAfter, KeyId, Weight};
 
 pub mod acknowledgement;
 pub mod error;
 pub mod command_executor;
+pub(crate) mod adaptive_capacity;
 
 /// CommandType defines various write commands including:
 /// Put             : attempts to put the new key/value pair in the cache
 /// PutWithTTL      : attempts to put the new key/value pair with time_to_live in the cache
+/// PutReserved     : puts the new key/value pair using the weight reserved by `crate::cache::cached::Reservation`
+/// PutReservedWithTTL : puts the new key/value pair with time_to_live using the weight reserved by `crate::cache::cached::Reservation`
+/// PutVersioned    : attempts to put the key/value pair with a version (and an optional time_to_live), replacing the existing key only if its version is lower
+/// PutOrUpdate     : attempts to put the key/value pair (with an optional time_to_live) that `put_or_update` could not update, `PutMode::UpdateOnly` does not reject it
 /// Delete          : attempts to delete the key
+/// DeleteTaken     : deletes the weight and the expiry of the keys that `take_all` has already removed from the store
+/// DeleteBatch     : attempts to delete all the keys, sent as a part of `delete_all` operation
+/// Admit           : attempts to admit the key that `increment_with_ttl` has already put in the store, replacing the weight of the expired key (if any)
 /// UpdateWeight    : updates the weight of the key. This command is sent as a part of `put_or_update` operation
+/// UpdateWeightBatch : updates the weights of the keys swapped by `compare_and_swap_batch` operation
+/// ResumeEviction  : evicts the keys that were admitted over the cache weight while the eviction was suspended
 /// Shutdown        : informs the `crate::cache::command::command_executor::CommandExecutor` that the cache is being shutdown
 pub(crate) enum CommandType<Key, Value>
     where Key: Hash + Eq + Clone {
     Put(KeyDescription<Key>, Value),
     PutWithTTL(KeyDescription<Key>, Value, Duration),
+    PutReserved(KeyDescription<Key>, Value),
+    PutReservedWithTTL(KeyDescription<Key>, Value, Duration),
+    PutVersioned(KeyDescription<Key>, Value, u64, Option<Duration>),
+    PutOrUpdate(KeyDescription<Key>, Value, Option<Duration>),
     Delete(Key),
-    UpdateWeight(KeyId, Weight),
+    Rename(Key, KeyDescription<Key>, fn(&Value) -> Value),
+    DeleteTaken(Vec<(Key, KeyIdExpiry)>),
+    DeleteBatch(Vec<Key>),
+    Admit(KeyDescription<Key>, ExpireAfter, Option<KeyIdExpiry>),
+    UpdateWeight(Key, KeyId, Weight),
+    UpdateWeightBatch(Vec<(Key, KeyId, Weight)>),
+    ResumeEviction,
+    Clear,
     Shutdown,
 }
 
@@ -31,8 +53,19 @@ impl<Key, Value> CommandType<Key, Value>
         match self {
             CommandType::Put(_, _) => "Put".to_string(),
             CommandType::PutWithTTL(_, _, _) => "PutWithTTL".to_string(),
+            CommandType::PutReserved(_, _) => "PutReserved".to_string(),
+            CommandType::PutReservedWithTTL(_, _, _) => "PutReservedWithTTL".to_string(),
+            CommandType::PutVersioned(_, _, _, _) => "PutVersioned".to_string(),
+            CommandType::PutOrUpdate(_, _, _) => "PutOrUpdate".to_string(),
             CommandType::Delete(_) => "Delete".to_string(),
-            CommandType::UpdateWeight(_, _) => "UpdateWeight".to_string(),
+            CommandType::Rename(_, _, _) => "Rename".to_string(),
+            CommandType::DeleteTaken(_) => "DeleteTaken".to_string(),
+            CommandType::DeleteBatch(_) => "DeleteBatch".to_string(),
+            CommandType::Admit(_, _, _) => "Admit".to_string(),
+            CommandType::UpdateWeight(_, _, _) => "UpdateWeight".to_string(),
+            CommandType::UpdateWeightBatch(_) => "UpdateWeightBatch".to_string(),
+            CommandType::ResumeEviction => "ResumeEviction".to_string(),
+            CommandType::Clear => "Clear".to_string(),
             CommandType::Shutdown => "Shutdown".to_string(),
         }
     }
@@ -66,6 +99,18 @@ pub enum CommandStatus {
 /// `KeyDoesNotExist`: Key does not exist during delete operation.
 ///
 /// `KeyAlreadyExists`: Key already exists during put operation.
+///
+/// `KeysInDifferentShards`: Keys of a `compare_and_swap_batch` operation do not belong to the same shard.
+///
+/// `DuplicateKeys`: A key appears more than once in a `compare_and_swap_batch` operation.
+///
+/// `PreconditionFailed`: The current value of a key in a `compare_and_swap_batch` operation is not the expected value, or the key does not exist.
+///
+/// `KeyRecentlyDeleted`: Key was deleted recently and its delete tombstone has not expired, during put operation.
+///
+/// `SoftCapacityExceeded`: The weight used by the cache exceeds the soft capacity, during `put_optional` operation.
+///
+/// `StaleVersion`: The version of the key/value pair is not greater than the version of the existing key, during `put_versioned` operation.
 #[non_exhaustive]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum RejectionReason {
@@ -73,14 +118,21 @@ pub enum RejectionReason {
     KeyWeightIsGreaterThanCacheWeight,
     KeyDoesNotExist,
     KeyAlreadyExists,
+    KeysInDifferentShards,
+    DuplicateKeys,
+    PreconditionFailed,
+    KeyRecentlyDeleted,
+    SoftCapacityExceeded,
+    StaleVersion,
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::time::{Duration, SystemTime};
 
     use crate::cache::command::CommandType;
     use crate::cache::key_description::KeyDescription;
+    use crate::cache::store::KeyIdExpiry;
 
     #[test]
     fn command_description_put() {
@@ -106,6 +158,55 @@ mod tests {
         assert_eq!("PutWithTTL", put.description());
     }
 
+    #[test]
+    fn command_description_put_reserved() {
+        let put = CommandType::PutReserved(
+            KeyDescription::new(
+                "topic", 1, 2090, 10,
+            ),
+            "microservices");
+
+        assert_eq!("PutReserved", put.description());
+    }
+
+    #[test]
+    fn command_description_put_reserved_with_ttl() {
+        let put = CommandType::PutReservedWithTTL(
+            KeyDescription::new(
+                "topic", 1, 2090, 10,
+            ),
+            "microservices",
+            Duration::from_millis(10),
+        );
+
+        assert_eq!("PutReservedWithTTL", put.description());
+    }
+
+    #[test]
+    fn command_description_put_versioned() {
+        let put = CommandType::PutVersioned(
+            KeyDescription::new(
+                "topic", 1, 2090, 10,
+            ),
+            "microservices",
+            2,
+            None,
+        );
+
+        assert_eq!("PutVersioned", put.description());
+    }
+
+    #[test]
+    fn command_description_put_or_update() {
+        let put_or_update = CommandType::PutOrUpdate(
+            KeyDescription::new("topic", 1, 1029, 10),
+            "microservices",
+            None,
+        );
+
+        assert_eq!("PutOrUpdate", put_or_update.description());
+    }
+
     #[test]
     fn command_description_delete() {
         let delete: CommandType<&str, &str> = CommandType::Delete("topic");
@@ -113,13 +214,54 @@ mod tests {
         assert_eq!("Delete", delete.description());
     }
 
+    #[test]
+    fn command_description_delete_taken() {
+        let delete_taken: CommandType<&str, &str> = CommandType::DeleteTaken(vec![("topic", KeyIdExpiry(1, None))]);
+
+        assert_eq!("DeleteTaken", delete_taken.description());
+    }
+
+    #[test]
+    fn command_description_delete_batch() {
+        let delete_batch: CommandType<&str, &str> = CommandType::DeleteBatch(vec!["topic", "disk"]);
+
+        assert_eq!("DeleteBatch", delete_batch.description());
+    }
+
+    #[test]
+    fn command_description_admit() {
+        let admit: CommandType<&str, i64> = CommandType::Admit(
+            KeyDescription::new(
+                "requests", 1, 2090, 10,
+            ),
+            SystemTime::UNIX_EPOCH,
+            None,
+        );
+
+        assert_eq!("Admit", admit.description());
+    }
+
     #[test]
     fn command_description_update_weight() {
-        let update_weight: CommandType<&str, &str> = CommandType::UpdateWeight(10, 200);
+        let update_weight: CommandType<&str, &str> = CommandType::UpdateWeight("topic", 10, 200);
 
         assert_eq!("UpdateWeight", update_weight.description());
     }
 
+    #[test]
+    fn command_description_update_weight_batch() {
+        let update_weight_batch: CommandType<&str, &str> = CommandType::UpdateWeightBatch(vec![("topic", 10, 200), ("disk", 20, 300)]);
+
+        assert_eq!("UpdateWeightBatch", update_weight_batch.description());
+    }
+
+    #[test]
+    fn command_description_resume_eviction() {
+        let resume_eviction: CommandType<&str, &str> = CommandType::ResumeEviction;
+
+        assert_eq!("ResumeEviction", resume_eviction.description());
+    }
+
     #[test]
     fn command_description_shutdown() {
         let shutdown: CommandType<&str, &str> = CommandType::Shutdown;
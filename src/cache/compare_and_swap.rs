@@ -0,0 +1,39 @@
+use std::hash::Hash;
+
+/// `CompareAndSwap` encapsulates a single compare-and-swap operation: replace the value of `key` with `new_value`, if the
+/// current value of `key` is equal to `expected`.
+///
+/// It is a parameter to `compare_and_swap_batch` method of [`crate::cache::cached::CacheD`].
+pub struct CompareAndSwap<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone,
+          Value: Send + Sync + PartialEq {
+    pub(crate) key: Key,
+    pub(crate) expected: Value,
+    pub(crate) new_value: Value,
+}
+
+impl<Key, Value> CompareAndSwap<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone,
+          Value: Send + Sync + PartialEq {
+    pub fn new(key: Key, expected: Value, new_value: Value) -> Self {
+        CompareAndSwap {
+            key,
+            expected,
+            new_value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::compare_and_swap::CompareAndSwap;
+
+    #[test]
+    fn compare_and_swap() {
+        let compare_and_swap = CompareAndSwap::new("topic", "microservices", "cached");
+
+        assert_eq!("topic", compare_and_swap.key);
+        assert_eq!("microservices", compare_and_swap.expected);
+        assert_eq!("cached", compare_and_swap.new_value);
+    }
+}
@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::cache::errors::Errors;
+
+/// `WeightError` is returned by a [`crate::cache::config::FallibleWeightCalculationFn`] if the weight of the key/value pair can not be calculated,
+/// for example, if the value is too large to measure.
+///
+/// The `WeightError` is returned to the clients as a part of [`crate::cache::command::error::CommandSendError`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WeightError {
+    reason: String,
+}
+
+impl WeightError {
+    pub fn new<Reason: Into<String>>(reason: Reason) -> Self {
+        WeightError {
+            reason: reason.into()
+        }
+    }
+
+    /// Returns the reason why the weight could not be calculated.
+    pub fn reason(&self) -> &str { &self.reason }
+}
+
+/// Display implementation for `WeightError`.
+impl Display for WeightError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "could not calculate the weight of the key/value pair, {}", self.reason)
+    }
+}
+
+/// Error implementation for `WeightError`.
+impl Error for WeightError {}
+
+/// `ConfigError` is returned by [`crate::cache::config::ConfigBuilder::try_build`] if a parameter of the config is invalid,
+/// [`crate::cache::config::ConfigBuilder::build`] panics with the same message instead.
+///
+/// `TotalCountersGtZero`:         `counters` is zero.
+///
+/// `TotalCapacityGtZero`:         `capacity` is zero.
+///
+/// `TotalCacheWeightGtZero`:      `cache_weight` is not greater than zero.
+///
+/// `TotalShardsGtOne`:            `shards` is not greater than one.
+///
+/// `TotalShardsPowerOf2`:         `shards` is not a power of 2.
+///
+/// `PoolSizeGtZero`:              `access_pool_size` is zero.
+///
+/// `BufferSizeGtZero`:            `access_buffer_size` is zero.
+///
+/// `CommandBufferSizeGtZero`:     `command_buffer_size` is zero.
+///
+/// `InvalidMaxCommandBufferSize`: the maximum size of `adaptive_command_buffer` is less than the `command_buffer_size`.
+///
+/// `NoTTLWithTimeToLive`:         a cache built with `no_ttl` has a default time to live, a sliding time to live or a delete tombstone time to live.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    TotalCountersGtZero,
+    TotalCapacityGtZero,
+    TotalCacheWeightGtZero,
+    TotalShardsGtOne,
+    TotalShardsPowerOf2,
+    PoolSizeGtZero,
+    BufferSizeGtZero,
+    CommandBufferSizeGtZero,
+    InvalidMaxCommandBufferSize,
+    NoTTLWithTimeToLive,
+}
+
+impl ConfigError {
+    fn error(&self) -> Errors {
+        match self {
+            ConfigError::TotalCountersGtZero => Errors::TotalCountersGtZero,
+            ConfigError::TotalCapacityGtZero => Errors::TotalCapacityGtZero,
+            ConfigError::TotalCacheWeightGtZero => Errors::TotalCacheWeightGtZero,
+            ConfigError::TotalShardsGtOne => Errors::TotalShardsGtOne,
+            ConfigError::TotalShardsPowerOf2 => Errors::TotalShardsPowerOf2,
+            ConfigError::PoolSizeGtZero => Errors::PoolSizeGtZero,
+            ConfigError::BufferSizeGtZero => Errors::BufferSizeGtZero,
+            ConfigError::CommandBufferSizeGtZero => Errors::CommandBufferSizeGtZero,
+            ConfigError::InvalidMaxCommandBufferSize => Errors::InvalidMaxCommandBufferSize,
+            ConfigError::NoTTLWithTimeToLive => Errors::NoTTLWithTimeToLive,
+        }
+    }
+}
+
+/// Display implementation for `ConfigError`.
+impl Display for ConfigError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{}", self.error())
+    }
+}
+
+/// Error implementation for `ConfigError`.
+impl Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::config::error::{ConfigError, WeightError};
+
+    #[test]
+    fn weight_error_reason() {
+        let error = WeightError::new("value is too large");
+        assert_eq!("value is too large", error.reason());
+    }
+
+    #[test]
+    fn weight_error_display() {
+        let error = WeightError::new("value is too large");
+        assert_eq!("could not calculate the weight of the key/value pair, value is too large", format!("{}", error));
+    }
+
+    #[test]
+    fn config_error_display() {
+        let error = ConfigError::TotalShardsPowerOf2;
+        assert_eq!("[Config]: Total number of shards must be a power of 2", format!("{}", error));
+    }
+}
@@ -1,24 +1,217 @@
 use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
+#[cfg(feature = "trace")]
+use std::io::Write;
+use std::sync::Arc;
 use std::time::Duration;
 
+use rand::Rng;
+
+use crate::cache::buffer_event::AdditionalBufferConsumer;
 use crate::cache::clock::{ClockType, SystemClock};
+use crate::cache::command::CommandStatus;
+use crate::cache::config::error::{ConfigError, WeightError};
 use crate::cache::config::weight_calculation::Calculation;
 use crate::cache::errors::Errors;
 use crate::cache::expiration::config::TTLConfig;
 use crate::cache::policy::config::CacheWeightConfig;
+use crate::cache::policy::eviction_audit::AuditKeyFn;
 use crate::cache::pool::{BufferSize, PoolSize};
-use crate::cache::types::{IsTimeToLiveSpecified, KeyHash, TotalCapacity, TotalCounters, TotalShards, Weight};
+use crate::cache::sketch::SKETCH_ROWS;
+use crate::cache::store::shard_hash::ShardHashBuilder;
+#[cfg(feature = "trace")]
+use crate::cache::trace::TraceRecorder;
+use crate::cache::types::{FrequencyEstimate, IsTimeToLiveSpecified, KeyHash, KeyId, TotalCapacity, TotalCounters, TotalShards, Weight};
 pub(crate) mod weight_calculation;
+pub mod error;
 
 /// Defines the function for calculating the hash of the incoming key. This hash is used to put the key in `crate::cache::policy::cache_weight::CacheWeight`.
 /// By default, DefaultHasher is used that uses SipHasher13 as the hash function.
 pub type HashFn<Key> = dyn Fn(&Key) -> KeyHash + Send + Sync;
 
+/// Defines the function that creates a new hasher for placing a key in a shard of the [`dashmap::DashMap`] inside `crate::cache::store::Store`.
+/// By default, [`std::collections::hash_map::RandomState`] is used that creates a randomly seeded SipHasher13, read [`crate::cache::config::ConfigBuilder::dual_hash`].
+pub type ShardHasherFn = dyn Fn() -> Box<dyn Hasher> + Send + Sync;
+
 /// Defines the function for calculating the weight of the incoming key/value pair.
 /// Default is the `perform` function defined in `crate::cache::config::weight_calculation::Calculation`.
 pub type WeightCalculationFn<Key, Value> = dyn Fn(&Key, &Value, IsTimeToLiveSpecified) -> Weight + Send + Sync;
 
+/// Defines the fallible function for calculating the weight of the incoming key/value pair.
+/// If the function returns a [`crate::cache::config::error::WeightError`], the `put` (or `put_or_update`) is rejected with
+/// a [`crate::cache::command::error::CommandSendError`] that wraps the `WeightError`.
+///
+/// If set, the `FallibleWeightCalculationFn` is used instead of the [`crate::cache::config::WeightCalculationFn`].
+pub type FallibleWeightCalculationFn<Key, Value> = dyn Fn(&Key, &Value, IsTimeToLiveSpecified) -> Result<Weight, WeightError> + Send + Sync;
+
+/// The target of the audit log records, read [`crate::cache::config::ConfigBuilder::audit_evictions`].
+/// Clients can route the audit trail separately from the other logs using this target.
+pub const AUDIT_LOG_TARGET: &str = "tinylfu_cached::audit";
+
+/// Defines the function that transforms a value on its way in to the cache, or on its way out of the cache, read [`crate::cache::config::ConfigBuilder::value_transform`].
+pub type ValueTransformFn<Value> = dyn Fn(Value) -> Value + Send + Sync;
+
+/// Defines the predicate that identifies a value which marks the deletion of its key, read [`crate::cache::config::ConfigBuilder::delete_on_value`].
+pub type DeleteOnValueFn<Value> = dyn Fn(&Value) -> bool + Send + Sync;
+
+/// ValueTransform holds the transformation applied to a value before it is stored (`on_put`), and the one applied to a value after it is retrieved (`on_get`).
+pub(crate) struct ValueTransform<Value> {
+    pub(crate) on_put: Box<ValueTransformFn<Value>>,
+    pub(crate) on_get: Box<ValueTransformFn<Value>>,
+}
+
+/// EvictionReason defines why a key was removed from the cache, it is passed to the eviction listener, to the batch eviction listener and to the audit log.
+///
+/// `Evicted`: the key was evicted by the admission policy to create space for the other keys.
+/// `Expired`: the `time_to_live` of the key elapsed.
+/// `Explicit`: the key was deleted, for example, using [`crate::cache::cached::CacheD::delete`] or [`crate::cache::cached::CacheD::clear`].
+/// `Replaced`: the key was replaced by a put, with [`PutMode::Overwrite`] or [`PutMode::UpdateOnly`], or by a versioned put.
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EvictionReason {
+    Evicted,
+    Expired,
+    Explicit,
+    Replaced,
+}
+
+impl Display for EvictionReason {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvictionReason::Evicted => write!(formatter, "evicted"),
+            EvictionReason::Expired => write!(formatter, "expired"),
+            EvictionReason::Explicit => write!(formatter, "deleted"),
+            EvictionReason::Replaced => write!(formatter, "replaced"),
+        }
+    }
+}
+
+/// Defines the function that receives the keys removed from the cache in a batch, read [`crate::cache::config::ConfigBuilder::batch_eviction_listener`].
+pub type BatchEvictionListenerFn<Key> = dyn Fn(Vec<(Key, EvictionReason)>) + Send + Sync;
+
+/// Defines the function that receives every key removed from the cache, along with its value, read [`crate::cache::config::ConfigBuilder::eviction_listener`].
+pub type EvictionListenerFn<Key, Value> = dyn Fn(&Key, &Value, EvictionReason) + Send + Sync;
+
+/// AdmissionDecision describes a single admission decision of the admission policy, it is passed to the admission observer,
+/// read [`crate::cache::config::ConfigBuilder::admission_observer`].
+///
+/// `victim` is the sampled key with the least access frequency that the incoming key was last compared against, along with its frequency.
+/// The victim was evicted if the incoming key was accepted, and it stays in the cache if the incoming key was rejected.
+/// There is no victim if the cache had the space for the incoming key, or if the incoming key was rejected without sampling the existing keys.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AdmissionDecision {
+    key_id: KeyId,
+    frequency: FrequencyEstimate,
+    weight: Weight,
+    victim: Option<(KeyId, FrequencyEstimate)>,
+    status: CommandStatus,
+}
+
+impl AdmissionDecision {
+    pub(crate) fn new(key_id: KeyId, frequency: FrequencyEstimate, weight: Weight, victim: Option<(KeyId, FrequencyEstimate)>, status: CommandStatus) -> Self {
+        AdmissionDecision { key_id, frequency, weight, victim, status }
+    }
+
+    /// Returns the key_id of the incoming key.
+    pub fn key_id(&self) -> KeyId {
+        self.key_id
+    }
+
+    /// Returns the estimated access frequency of the incoming key.
+    pub fn frequency(&self) -> FrequencyEstimate {
+        self.frequency
+    }
+
+    /// Returns the weight of the incoming key.
+    pub fn weight(&self) -> Weight {
+        self.weight
+    }
+
+    /// Returns the key_id and the estimated access frequency of the victim, if any.
+    pub fn victim(&self) -> Option<(KeyId, FrequencyEstimate)> {
+        self.victim
+    }
+
+    /// Returns the status of the incoming key: `CommandStatus::Accepted` or `CommandStatus::Rejected`.
+    pub fn status(&self) -> CommandStatus {
+        self.status
+    }
+}
+
+/// Defines the function that observes every admission decision, read [`crate::cache::config::ConfigBuilder::admission_observer`].
+pub type AdmissionObserverFn = dyn Fn(AdmissionDecision) + Send + Sync;
+
+/// Defines the function that is invoked with a key that is admitted again after it was evicted, read [`crate::cache::config::ConfigBuilder::on_readmission`].
+pub type ReadmissionListenerFn<Key> = dyn Fn(&Key) + Send + Sync;
+
+/// Defines the function that is invoked when the cache becomes full, or when it regains headroom.
+pub type CapacityListenerFn = dyn Fn() + Send + Sync;
+
+/// Defines the behavior of `put` (and its variants) when the key is (or is not) present in the cache.
+///
+/// `put_or_update` remains the general-purpose path to change an existing key and is not affected by `PutMode`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PutMode {
+    /// Puts the key/value pair, replacing the existing key/value pair if the key is present.
+    Overwrite,
+    /// Puts the key/value pair only if the key is not present, else rejects the put with `RejectionReason::KeyAlreadyExists`. This is the default.
+    InsertOnly,
+    /// Puts the key/value pair only if the key is present, else rejects the put with `RejectionReason::KeyDoesNotExist`.
+    /// `put_or_update` still puts an absent key.
+    UpdateOnly,
+}
+
+impl PutMode {
+    /// Returns `PutMode::Overwrite` for `PutMode::UpdateOnly`, for the puts that are allowed to put an absent key, like the put of `put_or_update`.
+    pub(crate) fn ignoring_update_only(self) -> PutMode {
+        match self {
+            PutMode::UpdateOnly => PutMode::Overwrite,
+            put_mode => put_mode,
+        }
+    }
+}
+
+/// Defines when the accesses (`get` and its variants) buffered in the access pool are applied to the access frequency of the keys,
+/// which trades the latency of `get` for the accuracy of the frequency estimates used by the admission policy.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AccessDrain {
+    /// Drains every buffer in the access pool once every period, in addition to draining a buffer when it is full.
+    /// The access frequency lags the accesses by at most the period (plus the time taken by the drain).
+    Periodic(Duration),
+    /// Drains a buffer in the access pool when it is full. The accesses in a buffer that is not full are not reflected in the access frequency.
+    /// This is the default.
+    OnFull,
+    /// Applies every access to the access frequency synchronously, on the thread performing the `get`.
+    /// The access frequency is accurate immediately after `get`, at the cost of acquiring the write lock on the access frequency on every access.
+    Sync,
+}
+
+/// Defines the behavior of `get` (and its variants) for a key that has expired, but is not yet removed by `crate::cache::expiration::TTLTicker`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ExpiredReadBehavior {
+    /// Treats the expired key as a miss, the key is removed by `TTLTicker` on its next run. This is the default.
+    Miss,
+    /// Returns the stale value of the expired key exactly once, and marks the key deleted, so that the subsequent reads miss.
+    /// The key (and its weight) is removed by `TTLTicker` on its next run.
+    ServeStaleOnce,
+    /// Treats the expired key as a miss, and [`crate::cache::cached::CacheD::get_result`] returns [`crate::cache::cached::ExpiredError`] for it,
+    /// so that the clients can distinguish an expired key from a missing key.
+    Error,
+}
+
+/// Defines how `crate::cache::policy::admission_policy::AdmissionPolicy` decides to admit a key when the cache does not have the weight to accommodate it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AdmissionMode {
+    /// Admits the incoming key only if its estimated access frequency is not less than the access frequency of the keys it evicts,
+    /// the keys are evicted from a sample, in the order of the least access frequency. This is the default.
+    TinyLfu,
+    /// Admits every key that fits in the cache weight, evicting the keys with the least access frequency (from a sample) to create the space for it.
+    /// The incoming key is never rejected for being accessed less often than the existing keys, which suits the small caches
+    /// where every put is expected to be retrievable.
+    Always,
+}
+
 /// Each put, put_or_update, delete results in a command to `crate::cache::command::command_executor::CommandExecutor`.
 /// CommandExecutor reads from an mpsc channel and COMMAND_BUFFER_SIZE defines the size (/buffer) of the command channel that
 /// is used by CommandExecutor.
@@ -36,6 +229,13 @@ const ACCESS_BUFFER_SIZE: BufferSize = BufferSize(64);
 /// Default is 256
 const SHARDS: usize = 256;
 
+/// The fraction of the ideal cache-hit ratio that TinyLFU achieves on a Zipf distribution, used by `ConfigBuilder::for_hit_ratio`.
+/// The cache_hits benchmark (Zipf exponent 1.001, 100_000 keys out of 1_600_000) measures ~73.41%, against ~80.6% of an ideal cache holding the most frequent keys.
+const HIT_RATIO_EFFICIENCY: f64 = 0.9;
+
+/// The number of keys per shard that `ConfigBuilder::for_hit_ratio` aims for.
+const KEYS_PER_SHARD: usize = 1024;
+
 /// Determines the frequency at which the `crate::cache::expiration::TTLTicker` runs.
 /// Default is every 5 seconds.
 const TTL_TICK_DURATION: Duration = Duration::from_secs(5);
@@ -46,30 +246,80 @@ pub struct Config<Key, Value>
           Value: 'static {
     pub key_hash_fn: Box<HashFn<Key>>,
     pub weight_calculation_fn: Box<WeightCalculationFn<Key, Value>>,
+    pub(crate) fallible_weight_calculation_fn: Option<Box<FallibleWeightCalculationFn<Key, Value>>>,
     pub clock: ClockType,
     pub counters: TotalCounters,
     pub command_buffer_size: usize,
+    pub(crate) max_command_buffer_size: Option<usize>,
+    pub(crate) command_executor_threads: usize,
+    pub(crate) sketch_depth: usize,
     pub total_cache_weight: Weight,
 
     pub(crate) access_pool_size: PoolSize,
     pub(crate) access_buffer_size: BufferSize,
     pub(crate) capacity: TotalCapacity,
     pub(crate) shards: TotalShards,
-
-    ttl_tick_duration: Duration,
+    pub(crate) inline_execution: bool,
+    pub(crate) put_mode: PutMode,
+    pub(crate) admission_mode: AdmissionMode,
+    pub(crate) frequency_reset_after: Option<u64>,
+    pub(crate) expired_read_behavior: ExpiredReadBehavior,
+    pub(crate) access_drain: AccessDrain,
+    pub(crate) prefer_evicting_ttl_entries: bool,
+    pub(crate) on_full: Option<Arc<CapacityListenerFn>>,
+    pub(crate) on_has_headroom: Option<Arc<CapacityListenerFn>>,
+    pub(crate) additional_access_consumer: Option<Arc<AdditionalBufferConsumer>>,
+    pub(crate) batch_eviction_listener: Option<Arc<BatchEvictionListenerFn<Key>>>,
+    pub(crate) eviction_listener: Option<Arc<EvictionListenerFn<Key, Value>>>,
+    pub(crate) audit_key_fn: Option<Arc<AuditKeyFn<Key>>>,
+    pub(crate) admission_observer: Option<Arc<AdmissionObserverFn>>,
+    pub(crate) on_readmission: Option<Arc<ReadmissionListenerFn<Key>>>,
+    pub(crate) delete_tombstone_ttl: Option<Duration>,
+    pub(crate) value_transform: Option<ValueTransform<Value>>,
+    pub(crate) delete_on_value: Option<Box<DeleteOnValueFn<Value>>>,
+    pub(crate) soft_capacity: Option<Weight>,
+    pub(crate) min_residency: Option<Duration>,
+    pub(crate) default_time_to_live: Option<Duration>,
+    pub(crate) sliding_ttl: Option<Duration>,
+    pub(crate) ttl_enabled: bool,
+    #[cfg(feature = "trace")]
+    pub(crate) trace_recorder: Option<TraceRecorder>,
+    pub(crate) ttl_tick_duration: Duration,
+
+    max_reaps_per_tick: usize,
+    shard_hasher_fn: Option<Arc<ShardHasherFn>>,
 }
 
 impl<Key, Value> Config<Key, Value>
     where Key: Hash + 'static,
           Value: 'static {
+    /// Calculates the weight of the key/value pair using the fallible weight calculation function if it is set,
+    /// else using the weight calculation function.
+    pub(crate) fn weight_of(&self, key: &Key, value: &Value, is_time_to_live_specified: IsTimeToLiveSpecified) -> Result<Weight, WeightError> {
+        match &self.fallible_weight_calculation_fn {
+            Some(fallible_weight_calculation_fn) => fallible_weight_calculation_fn(key, value, is_time_to_live_specified),
+            None => Ok((self.weight_calculation_fn)(key, value, is_time_to_live_specified)),
+        }
+    }
+
     /// Creates a new instance of TTLConfig.
     pub(crate) fn ttl_config(&self) -> TTLConfig {
         TTLConfig::new(self.shards, self.ttl_tick_duration, self.clock.clone_box())
+            .with_max_reaps_per_tick(self.max_reaps_per_tick)
     }
 
     /// Creates a new instance of CacheWeightConfig.
     pub(crate) fn cache_weight_config(&self) -> CacheWeightConfig {
         CacheWeightConfig::new(self.capacity, self.shards, self.total_cache_weight)
+            .with_capacity_listeners(self.on_full.clone(), self.on_has_headroom.clone())
+    }
+
+    /// Creates a new instance of ShardHashBuilder, that builds the hasher for placing a key in a shard of `crate::cache::store::Store`.
+    pub(crate) fn shard_hash_builder(&self) -> ShardHashBuilder {
+        match &self.shard_hasher_fn {
+            Some(shard_hasher_fn) => ShardHashBuilder::Custom(shard_hasher_fn.clone()),
+            None => ShardHashBuilder::default(),
+        }
     }
 }
 
@@ -78,16 +328,47 @@ pub struct ConfigBuilder<Key, Value>
     where Key: Hash + 'static,
           Value: 'static {
     key_hash_fn: Box<HashFn<Key>>,
+    shard_hasher_fn: Option<Arc<ShardHasherFn>>,
     weight_calculation_fn: Box<WeightCalculationFn<Key, Value>>,
+    fallible_weight_calculation_fn: Option<Box<FallibleWeightCalculationFn<Key, Value>>>,
     clock: ClockType,
     counters: TotalCounters,
     capacity: TotalCapacity,
     command_buffer_size: usize,
+    max_command_buffer_size: Option<usize>,
+    command_executor_threads: usize,
+    sketch_depth: usize,
     access_pool_size: PoolSize,
     access_buffer_size: BufferSize,
     total_cache_weight: Weight,
     shards: TotalShards,
     ttl_tick_duration: Duration,
+    max_reaps_per_tick: usize,
+    inline_execution: bool,
+    put_mode: PutMode,
+    admission_mode: AdmissionMode,
+    frequency_reset_after: Option<u64>,
+    expired_read_behavior: ExpiredReadBehavior,
+    access_drain: AccessDrain,
+    prefer_evicting_ttl_entries: bool,
+    on_full: Option<Arc<CapacityListenerFn>>,
+    on_has_headroom: Option<Arc<CapacityListenerFn>>,
+    additional_access_consumer: Option<Arc<AdditionalBufferConsumer>>,
+    batch_eviction_listener: Option<Arc<BatchEvictionListenerFn<Key>>>,
+    eviction_listener: Option<Arc<EvictionListenerFn<Key, Value>>>,
+    audit_key_fn: Option<Arc<AuditKeyFn<Key>>>,
+    admission_observer: Option<Arc<AdmissionObserverFn>>,
+    on_readmission: Option<Arc<ReadmissionListenerFn<Key>>>,
+    delete_tombstone_ttl: Option<Duration>,
+    value_transform: Option<ValueTransform<Value>>,
+    delete_on_value: Option<Box<DeleteOnValueFn<Value>>>,
+    soft_capacity: Option<Weight>,
+    min_residency: Option<Duration>,
+    default_time_to_live: Option<Duration>,
+    sliding_ttl: Option<Duration>,
+    ttl_enabled: bool,
+    #[cfg(feature = "trace")]
+    trace_recorder: Option<TraceRecorder>,
 }
 
 impl<Key, Value> ConfigBuilder<Key, Value>
@@ -110,11 +391,9 @@ impl<Key, Value> ConfigBuilder<Key, Value>
         /// - rejection of the incoming key
         ///
         /// - admission of the incoming key by causing eviction of some existing keys
+    ///
+    /// `counters`, `capacity` and `cache_weight` must be greater than zero, they are validated by [`ConfigBuilder::build`] and [`ConfigBuilder::try_build`].
     pub fn new(counters: TotalCounters, capacity: TotalCapacity, cache_weight: Weight) -> Self {
-        assert!(counters > 0, "{}", Errors::TotalCountersGtZero);
-        assert!(capacity > 0, "{}", Errors::TotalCapacityGtZero);
-        assert!(cache_weight > 0, "{}", Errors::TotalCacheWeightGtZero);
-
         let key_hash_fn = |key: &Key| -> KeyHash {
             let mut hasher = DefaultHasher::new();
             key.hash(&mut hasher);
@@ -123,19 +402,87 @@ impl<Key, Value> ConfigBuilder<Key, Value>
 
         ConfigBuilder {
             key_hash_fn: Box::new(key_hash_fn),
+            shard_hasher_fn: None,
             weight_calculation_fn: Box::new(Calculation::perform),
+            fallible_weight_calculation_fn: None,
             clock: SystemClock::boxed(),
             access_pool_size: ACCESS_POOL_SIZE,
             access_buffer_size: ACCESS_BUFFER_SIZE,
             command_buffer_size: COMMAND_BUFFER_SIZE,
+            max_command_buffer_size: None,
+            command_executor_threads: 1,
+            sketch_depth: SKETCH_ROWS,
             counters,
             capacity,
             total_cache_weight: cache_weight,
             shards: SHARDS,
             ttl_tick_duration: TTL_TICK_DURATION,
+            max_reaps_per_tick: usize::MAX,
+            inline_execution: false,
+            put_mode: PutMode::InsertOnly,
+            admission_mode: AdmissionMode::TinyLfu,
+            frequency_reset_after: None,
+            expired_read_behavior: ExpiredReadBehavior::Miss,
+            access_drain: AccessDrain::OnFull,
+            prefer_evicting_ttl_entries: false,
+            on_full: None,
+            on_has_headroom: None,
+            additional_access_consumer: None,
+            batch_eviction_listener: None,
+            eviction_listener: None,
+            audit_key_fn: None,
+            admission_observer: None,
+            on_readmission: None,
+            delete_tombstone_ttl: None,
+            value_transform: None,
+            delete_on_value: None,
+            soft_capacity: None,
+            min_residency: None,
+            default_time_to_live: None,
+            sliding_ttl: None,
+            ttl_enabled: true,
+            #[cfg(feature = "trace")]
+            trace_recorder: None,
         }
     }
 
+    /// Create a new instance of ConfigBuilder tuned to achieve roughly the `target` cache-hit ratio for a Zipf-like workload over `expected_keys` distinct keys,
+    /// where each key/value pair weighs around `value_weight`.
+    ///
+    /// For a Zipf distribution with an exponent close to `1`, an ideal cache that holds the `C` most frequent of the `N` keys achieves a cache-hit ratio
+    /// of about `ln(C) / ln(N)`. TinyLFU achieves around 90% of the ideal cache-hit ratio in the cache_hits benchmark, so the `capacity` is derived as
+    /// `N ^ (target / 0.9)`, capped at `expected_keys`. The remaining parameters are derived from the `capacity`:
+    ///
+    /// - `counters` are 10 times the `expected_keys`,
+    ///
+    /// - `cache_weight` is `capacity * value_weight`,
+    ///
+    /// - `shards` is a power of `2`, that places around `1024` keys in a shard, between `2` and the default `256`.
+    ///
+    /// The derived config is approximate: the actual cache-hit ratio depends on the skew of the workload. It is a starting point that can be refined
+    /// using the other methods of `ConfigBuilder`.
+    ///
+    /// `target` must be greater than `0` and less than `1`, `expected_keys` and `value_weight` must be greater than `0`.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    ///
+    /// let config = ConfigBuilder::for_hit_ratio(0.7, 1_600_000, 40).build();
+    /// let cached: CacheD<u64, u64> = CacheD::new(config);
+    /// cached.shutdown();
+    /// ```
+    pub fn for_hit_ratio(target: f64, expected_keys: usize, value_weight: Weight) -> Self {
+        assert!(target > 0.0 && target < 1.0, "{}", Errors::InvalidTargetHitRatio);
+        assert!(expected_keys > 0, "{}", Errors::TotalCapacityGtZero);
+        assert!(value_weight > 0, "{}", Errors::TotalCacheWeightGtZero);
+
+        let ideal_hit_ratio = (target / HIT_RATIO_EFFICIENCY).min(1.0);
+        let capacity = ((expected_keys as f64).powf(ideal_hit_ratio).ceil() as usize).clamp(1, expected_keys);
+        let shards = (capacity / KEYS_PER_SHARD).next_power_of_two().clamp(2, SHARDS);
+
+        ConfigBuilder::new((expected_keys * 10) as TotalCounters, capacity, capacity as Weight * value_weight).shards(shards)
+    }
+
     /// Sets the key hash function.
     ///
     /// By default, DefaultHasher is used that uses SipHasher13 as the hash function.
@@ -144,13 +491,72 @@ impl<Key, Value> ConfigBuilder<Key, Value>
         self
     }
 
+    /// Sets independent hash functions for placing a key in a shard of the `crate::cache::store::Store` and for estimating its access frequency.
+    ///
+    /// `shard_hasher_fn` creates a new hasher for every key that is placed in (or looked up from) the [`dashmap::DashMap`] inside `Store`.
+    /// `sketch_hash_fn` is the key hash function (same as [`ConfigBuilder::key_hash_fn`]) used by the count-min sketch and by the eviction.
+    ///
+    /// By default, the two hashes are derived from the same hash function (SipHasher13) with different seeds: the key hash function uses
+    /// fixed keys and the `Store` uses a randomly seeded [`std::collections::hash_map::RandomState`], so they are already independent.
+    ///
+    /// `dual_hash` matters when the keys are adversarial or highly-structured (for example, sequential ids) and the clients replace the default hash functions
+    /// with faster ones. If the same weak hash function is used for both, the keys that collide in the count-min sketch also collide in the same shard,
+    /// making a shard hotspot also a sketch hotspot. Using independent hash functions spreads such keys across the shards.
+    ///
+    /// Every lookup in the `Store` creates a new boxed hasher through `shard_hasher_fn`, so it is slightly slower than the default.
+    pub fn dual_hash(mut self, shard_hasher_fn: Box<ShardHasherFn>, sketch_hash_fn: Box<HashFn<Key>>) -> ConfigBuilder<Key, Value> {
+        self.shard_hasher_fn = Some(Arc::from(shard_hasher_fn));
+        self.key_hash_fn = sketch_hash_fn;
+        self
+    }
+
+    /// Sets a key hash function seeded with a randomly generated seed, for resistance against the hash-flooding of the shards and the count-min sketch.
+    ///
+    /// The default key hash function uses fixed keys, so the clients that control the keys can craft the keys that collide in the count-min sketch.
+    /// Read [`ConfigBuilder::seeded_hasher_with`] to pass an explicit seed, for example, to reproduce the hashes across the runs.
+    pub fn seeded_hasher(self) -> ConfigBuilder<Key, Value> {
+        self.seeded_hasher_with(rand::thread_rng().gen())
+    }
+
+    /// Sets a key hash function seeded with the `seed`.
+    ///
+    /// The key hash function is SipHasher13 ([`std::collections::hash_map::DefaultHasher`]) that hashes the `seed` before the key.
+    /// The same seeded hash function is used for placing a key in a shard of `crate::cache::store::Store` and by the admission policy,
+    /// so every lookup in the `Store` creates a new boxed hasher, like [`ConfigBuilder::dual_hash`].
+    pub fn seeded_hasher_with(mut self, seed: u64) -> ConfigBuilder<Key, Value> {
+        let seeded_hasher = move || {
+            let mut hasher = DefaultHasher::new();
+            hasher.write_u64(seed);
+            hasher
+        };
+        self.shard_hasher_fn = Some(Arc::new(move || Box::new(seeded_hasher()) as Box<dyn Hasher>));
+        self.key_hash_fn = Box::new(move |key: &Key| {
+            let mut hasher = seeded_hasher();
+            key.hash(&mut hasher);
+            hasher.finish()
+        });
+        self
+    }
+
     /// Sets the weight calculation function.
     ///
     /// Weight calculation function calculates the weight of the incoming key/value pair.
     ///
     /// Default is the `perform` function defined in `crate::cache::config::weight_calculation::Calculation`.
+    /// Setting the weight calculation function replaces the fallible weight calculation function, if any.
     pub fn weight_calculation_fn(mut self, weight_calculation: Box<WeightCalculationFn<Key, Value>>) -> ConfigBuilder<Key, Value> {
         self.weight_calculation_fn = weight_calculation;
+        self.fallible_weight_calculation_fn = None;
+        self
+    }
+
+    /// Sets the fallible weight calculation function.
+    ///
+    /// Fallible weight calculation function calculates the weight of the incoming key/value pair, or returns a [`crate::cache::config::error::WeightError`]
+    /// if the weight can not be calculated. The error is returned to the clients as a part of [`crate::cache::command::error::CommandSendError`].
+    /// If set, it is used instead of the weight calculation function.
+    pub fn fallible_weight_calculation_fn(mut self, weight_calculation: Box<FallibleWeightCalculationFn<Key, Value>>) -> ConfigBuilder<Key, Value> {
+        self.fallible_weight_calculation_fn = Some(weight_calculation);
         self
     }
 
@@ -164,37 +570,84 @@ impl<Key, Value> ConfigBuilder<Key, Value>
     ///
     /// Pool represents a ring-buffer that is used to buffer the gets for various keys.
     ///
-    /// Default pool size is `32`.
+    /// Default pool size is `32`. The pool size must be greater than zero, it is validated by [`ConfigBuilder::build`] and [`ConfigBuilder::try_build`].
     pub fn access_pool_size(mut self, pool_size: usize) -> ConfigBuilder<Key, Value> {
-        assert!(pool_size > 0, "{}", Errors::PoolSizeGtZero);
         self.access_pool_size = PoolSize(pool_size);
         self
     }
 
     /// Sets the size of each buffer inside Pool.
     ///
-    /// Default capacity of the buffer is `64`.
+    /// Default capacity of the buffer is `64`. The buffer size must be greater than zero, it is validated by [`ConfigBuilder::build`] and [`ConfigBuilder::try_build`].
     pub fn access_buffer_size(mut self, buffer_size: usize) -> ConfigBuilder<Key, Value> {
-        assert!(buffer_size > 0, "{}", Errors::BufferSizeGtZero);
         self.access_buffer_size = BufferSize(buffer_size);
         self
     }
 
+    /// Sets when the accesses buffered in the access pool are applied to the access frequency of the keys, read [`crate::cache::config::AccessDrain`].
+    ///
+    /// Default is `AccessDrain::OnFull`. The period of `AccessDrain::Periodic` must be greater than zero.
+    pub fn access_drain(mut self, access_drain: AccessDrain) -> ConfigBuilder<Key, Value> {
+        if let AccessDrain::Periodic(period) = access_drain {
+            assert!(!period.is_zero(), "{}", Errors::AccessDrainPeriodGtZero);
+        }
+        self.access_drain = access_drain;
+        self
+    }
+
     /// Each put, put_or_update, delete results in a command to `crate::cache::command::command_executor::CommandExecutor`.
     ///
     /// CommandExecutor reads from a channel and the default channel size is `32 * 1024`.
+    /// The channel size must be greater than zero, it is validated by [`ConfigBuilder::build`] and [`ConfigBuilder::try_build`].
     pub fn command_buffer_size(mut self, command_buffer_size: usize) -> ConfigBuilder<Key, Value> {
-        assert!(command_buffer_size > 0, "{}", Errors::CommandBufferSizeGtZero);
         self.command_buffer_size = command_buffer_size;
         self
     }
 
+    /// Enables the adaptive command buffer that starts with `command_buffer_size` and grows up to `max_command_buffer_size`.
+    ///
+    /// A `put`, `put_or_update` or `delete` blocks if the command buffer is full. With the adaptive command buffer, the capacity of the buffer doubles
+    /// (up to `max_command_buffer_size`) every time a command finds the buffer full, so a transient burst of writes does not block the writers.
+    /// The capacity halves (down to `command_buffer_size`) once the buffered commands fall to a quarter of the capacity.
+    /// The current capacity is available from `command_buffer_capacity` of [`crate::cache::cached::CacheD`].
+    ///
+    /// `max_command_buffer_size` must be greater than or equal to the `command_buffer_size`, it is validated by [`ConfigBuilder::build`] and [`ConfigBuilder::try_build`].
+    pub fn adaptive_command_buffer(mut self, max_command_buffer_size: usize) -> ConfigBuilder<Key, Value> {
+        self.max_command_buffer_size = Some(max_command_buffer_size);
+        self
+    }
+
+    /// Sets the number of threads of `crate::cache::command::command_executor::CommandExecutor` that execute the commands, default is 1.
+    ///
+    /// The commands are partitioned between the threads by the shards of their keys in the `crate::cache::store::Store`, and each thread has its own
+    /// command buffer of `command_buffer_size`, so the writes are not capped by the throughput of a single thread.
+    /// The commands of the same key are executed one after the other, in the order they are sent.
+    /// A command with the keys of more than one thread (like `clear`) waits till all those threads execute the commands sent before it.
+    ///
+    /// `command_executor_threads` is ignored with [`ConfigBuilder::inline_execution`].
+    pub fn command_executor_threads(mut self, command_executor_threads: usize) -> ConfigBuilder<Key, Value> {
+        assert!(command_executor_threads > 0, "{}", Errors::CommandExecutorThreadsGtZero);
+        self.command_executor_threads = command_executor_threads;
+        self
+    }
+
+    /// Sets the depth (the number of rows) of the count-min sketch that maintains the access frequency of the keys.
+    ///
+    /// The `counters` determine the width of each row. The estimate of a key is the minimum of its counters across the rows,
+    /// so a deeper sketch over-counts less for the high-cardinality workloads, at the cost of `counters / 2` bytes (and a hash) for each additional row.
+    /// The depth is a part of the exported sketch, read [`crate::cache::cached::CacheD::export_sketch`].
+    ///
+    /// `sketch_depth` must be at least 4. Default is 4.
+    pub fn sketch_depth(mut self, sketch_depth: usize) -> ConfigBuilder<Key, Value> {
+        assert!(sketch_depth >= SKETCH_ROWS, "{}", Errors::SketchDepthGtThree);
+        self.sketch_depth = sketch_depth;
+        self
+    }
+
     /// Sets the number of shards to use in the DashMap inside `crate::cache::store::Store`.
     ///
-    /// `shards` must be a power of `2` and greater than `1`.
+    /// `shards` must be a power of `2` and greater than `1`, it is validated by [`ConfigBuilder::build`] and [`ConfigBuilder::try_build`].
     pub fn shards(mut self, shards: TotalShards) -> ConfigBuilder<Key, Value> {
-        assert!(shards > 1, "{}", Errors::TotalShardsGtOne);
-        assert!(shards.is_power_of_two(), "{}", Errors::TotalShardsPowerOf2);
         self.shards = shards;
         self
     }
@@ -207,114 +660,630 @@ impl<Key, Value> ConfigBuilder<Key, Value>
         self
     }
 
-    // Builds an instance of Config with the supplied values.
-    pub fn build(self) -> Config<Key, Value> {
-        Config {
-            key_hash_fn: self.key_hash_fn,
-            weight_calculation_fn: self.weight_calculation_fn,
-            clock: self.clock,
-            access_pool_size: self.access_pool_size,
-            access_buffer_size: self.access_buffer_size,
-            command_buffer_size: self.command_buffer_size,
-            counters: self.counters,
-            capacity: self.capacity,
-            total_cache_weight: self.total_cache_weight,
-            shards: self.shards,
-            ttl_tick_duration: self.ttl_tick_duration,
-        }
+    /// Builds a cache without the support for time to live, for the applications that never use TTLs.
+    ///
+    /// The thread of `crate::cache::expiration::TTLTicker` is never spawned, so the cache does not wake up every `ttl_tick_duration`.
+    /// The operations that take a time to live ([`crate::cache::cached::CacheD::put_with_ttl`], [`crate::cache::cached::CacheD::put_with_weight_and_ttl`]
+    /// and [`crate::cache::cached::CacheD::put_or_update`] with a `time_to_live`) return a [`crate::cache::command::error::CommandSendError`]
+    /// for which `is_ttl_disabled` is true. `increment_with_ttl` and `get_and_extend` panic.
+    ///
+    /// `no_ttl` can not be combined with [`ConfigBuilder::default_time_to_live`], [`ConfigBuilder::sliding_ttl`] or [`ConfigBuilder::delete_tombstone_ttl`],
+    /// `build` panics if any of them is set.
+    pub fn no_ttl(mut self) -> ConfigBuilder<Key, Value> {
+        self.ttl_enabled = false;
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::time::{Duration, SystemTime};
+    /// Sets the maximum number of expired keys that `crate::cache::expiration::TTLTicker` removes in a single tick.
+    ///
+    /// If a large number of keys expire together, removing all of them in a single tick holds the locks for a long time and stalls the reads and the writes.
+    /// With `max_reaps_per_tick`, the remaining expired keys are deferred to the subsequent ticks.
+    /// This smooths the latency at the cost of delayed reclamation of the weight, the deferred keys are never returned by `get` (and its variants) because
+    /// the expiry of a key is checked on every read.
+    ///
+    /// `max_reaps_per_tick` must be greater than `0`. Default is unbounded.
+    pub fn max_reaps_per_tick(mut self, max_reaps_per_tick: usize) -> ConfigBuilder<Key, Value> {
+        assert!(max_reaps_per_tick > 0, "{}", Errors::MaxReapsPerTickGtZero);
+        self.max_reaps_per_tick = max_reaps_per_tick;
+        self
+    }
 
-    use crate::cache::clock::ClockType;
-    use crate::cache::config::{Config, ConfigBuilder};
-    use crate::cache::config::tests::setup::UnixEpochClock;
-    use crate::cache::pool::{BufferSize, PoolSize};
-    use crate::cache::types::IsTimeToLiveSpecified;
+    /// Sets the time to live of the tombstone that a `delete` leaves behind for the deleted key.
+    ///
+    /// Until the tombstone expires, the `put` operations (and its variants) for the key are rejected with
+    /// [`crate::cache::command::RejectionReason::KeyRecentlyDeleted`]. This prevents a slow read from the backing store, that started before the key was invalidated,
+    /// from re-populating the cache with stale data.
+    ///
+    /// A tombstone is not counted in the cache weight, it is removed by `crate::cache::expiration::TTLTicker` after it expires.
+    ///
+    /// `delete_tombstone_ttl` must be greater than zero. By default, `delete` does not leave a tombstone.
+    pub fn delete_tombstone_ttl(mut self, delete_tombstone_ttl: Duration) -> ConfigBuilder<Key, Value> {
+        assert!(!delete_tombstone_ttl.is_zero(), "{}", Errors::DeleteTombstoneTTLGtZero);
+        self.delete_tombstone_ttl = Some(delete_tombstone_ttl);
+        self
+    }
 
-    mod setup {
-        use std::time::SystemTime;
+    /// Sets the soft capacity of the cache, a watermark below the total cache weight (the hard capacity).
+    ///
+    /// The cache has two tiers of watermarks:
+    /// - Once the weight used by the cache exceeds the soft capacity, `put_optional` of [`crate::cache::cached::CacheD`] is rejected immediately
+    ///   with [`crate::cache::command::RejectionReason::SoftCapacityExceeded`], without attempting the admission of the key/value pair.
+    /// - `put` (and its variants) continue to attempt the admission of the key/value pair up to the total cache weight,
+    ///   beyond which the admission may evict other keys or reject the key/value pair.
+    ///
+    /// This preserves the room between the soft capacity and the total cache weight for the essential puts, while the best-effort puts
+    /// (labelled by `put_optional`) are dropped.
+    ///
+    /// `soft_capacity` must be greater than zero and less than the total cache weight. By default, there is no soft capacity.
+    pub fn soft_capacity(mut self, soft_capacity: Weight) -> ConfigBuilder<Key, Value> {
+        assert!(soft_capacity > 0 && soft_capacity < self.total_cache_weight, "{}", Errors::InvalidSoftCapacity);
+        self.soft_capacity = Some(soft_capacity);
+        self
+    }
 
-        use crate::cache::clock::Clock;
+    /// Sets the minimum residency of the keys: a key admitted less than `min_residency` ago is not evicted to create space for the incoming keys,
+    /// an older key is evicted instead. This protects the new keys long enough to accumulate the accesses, so that the work of putting them is not wasted.
+    ///
+    /// The age of a key is measured from its admission, using the [`ConfigBuilder::clock`]. A key replaced by a put is admitted again, so its age restarts.
+    /// The minimum residency does not protect a key from expiring, or from being deleted.
+    ///
+    /// If all the keys in the cache are younger than `min_residency`, there is no key to evict, and the incoming key is rejected with
+    /// [`crate::cache::command::RejectionReason::EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers`], even if it is accessed more frequently than the existing keys.
+    /// Hence, a long `min_residency` under a high rate of puts may reject most of the incoming keys. Finding an old enough key may also require
+    /// walking past the young keys, which makes the admission slower when most of the keys are young.
+    ///
+    /// `min_residency` must be greater than zero. By default, there is no minimum residency.
+    pub fn min_residency(mut self, min_residency: Duration) -> ConfigBuilder<Key, Value> {
+        assert!(!min_residency.is_zero(), "{}", Errors::MinResidencyGtZero);
+        self.min_residency = Some(min_residency);
+        self
+    }
 
-        #[derive(Clone)]
-        pub(crate) struct UnixEpochClock;
+    /// Sets the default time to live that is applied to the keys put by [`crate::cache::cached::CacheD::put`] and [`crate::cache::cached::CacheD::put_with_weight`],
+    /// so that every key expires after `default_time_to_live`, like the keys put by [`crate::cache::cached::CacheD::put_with_ttl`].
+    ///
+    /// An explicit `time_to_live` (`put_with_ttl` and `put_with_weight_and_ttl`) overrides the default,
+    /// and [`crate::cache::cached::CacheD::put_without_ttl`] puts a key that never expires.
+    ///
+    /// `default_time_to_live` must be greater than zero. By default, there is no default time to live.
+    pub fn default_time_to_live(mut self, default_time_to_live: Duration) -> ConfigBuilder<Key, Value> {
+        assert!(!default_time_to_live.is_zero(), "{}", Errors::DefaultTimeToLiveGtZero);
+        self.default_time_to_live = Some(default_time_to_live);
+        self
+    }
 
-        impl Clock for UnixEpochClock {
-            fn now(&self) -> SystemTime {
-                SystemTime::UNIX_EPOCH
-            }
-        }
+    /// Sets the sliding window of time to live, so that every hit extends the life of the key, for example, for the session-style caches.
+    ///
+    /// On a hit by [`crate::cache::cached::CacheD::get`] or [`crate::cache::cached::CacheD::get_ref`] (and the variants built on them), the expiry of a key
+    /// that has a time to live is moved to `sliding_ttl` from now, and the key is rescheduled in `crate::cache::expiration::TTLTicker`.
+    /// A key without a time to live is not affected, so `sliding_ttl` is usually combined with [`ConfigBuilder::default_time_to_live`].
+    ///
+    /// The expiry is refreshed only when the remaining time to live of the key drops below half of `sliding_ttl`,
+    /// so that a frequently read key does not reschedule on every hit.
+    ///
+    /// `sliding_ttl` must be greater than zero. By default, the hits do not change the expiry of the keys.
+    pub fn sliding_ttl(mut self, sliding_ttl: Duration) -> ConfigBuilder<Key, Value> {
+        assert!(!sliding_ttl.is_zero(), "{}", Errors::SlidingTimeToLiveGtZero);
+        self.sliding_ttl = Some(sliding_ttl);
+        self
     }
 
-    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str>{
-        ConfigBuilder::new(100, 10, 100)
+    /// Sets the transformations that are applied to a value on its way in to the cache and on its way out of the cache, for example, for encrypting the values at rest.
+    ///
+    /// `on_put` is applied before the value is stored by `put` (and its variants), `put_or_update` and `compare_and_swap_batch`,
+    /// and the weight of the key/value pair is calculated on the transformed (stored) value.
+    /// `on_get` is applied after the value is retrieved by `get` (and its variants). `on_get` must reverse `on_put`.
+    ///
+    /// `get_ref` and `map_get_ref` return a reference to the stored value, so they return the transformed value.
+    /// `compare_and_swap_batch` applies `on_put` to the expected value, so `on_put` must be deterministic for it to match the stored value.
+    ///
+    /// Every `put` and every `get` invokes the corresponding transformation, so its cost is added to every operation.
+    pub fn value_transform(mut self, on_put: Box<ValueTransformFn<Value>>, on_get: Box<ValueTransformFn<Value>>) -> ConfigBuilder<Key, Value> {
+        self.value_transform = Some(ValueTransform { on_put, on_get });
+        self
     }
 
-    #[test]
-    fn key_hash_function() {
-        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+    /// Sets the predicate that identifies a value which marks the deletion of its key, for example, a tombstone value in a change-data-capture stream.
+    ///
+    /// A `put` (and its variants) whose value matches the predicate is treated as a `delete` of the key, and returns the result of the `delete`.
+    /// The predicate is evaluated on the value supplied to `put`, before the `on_put` transformation of `value_transform` (if configured).
+    /// Such a `put` does not calculate the weight of the value, and the weight supplied to `put_with_weight` (and its variants) is ignored.
+    ///
+    /// `put_or_update` and `compare_and_swap_batch` store the value even if it matches the predicate.
+    pub fn delete_on_value(mut self, delete_on_value: Box<DeleteOnValueFn<Value>>) -> ConfigBuilder<Key, Value> {
+        self.delete_on_value = Some(delete_on_value);
+        self
+    }
 
-        let key_hash_fn = Box::new(|_key: &&str| 1);
-        let config = builder.key_hash_fn(key_hash_fn).build();
+    /// Enables the inline execution of the write operations, intended for tests.
+    ///
+    /// By default, every write operation (`put`, `put_or_update`, `delete`) results in a command that is executed
+    /// by `crate::cache::command::command_executor::CommandExecutor` on a separate thread, and the clients `await` on the acknowledgement.
+    ///
+    /// With inline execution, the command is executed on the caller's thread before the write operation returns,
+    /// so a `get` right after a `put` sees the result without any `await` or `sleep`.
+    /// Inline execution serializes all the writes on the callers and is not meant for production use.
+    pub fn inline_execution(mut self) -> ConfigBuilder<Key, Value> {
+        self.inline_execution = true;
+        self
+    }
 
-        let key = "topic";
-        let hash = (config.key_hash_fn)(&key);
+    /// Sets the behavior of `put` (and its variants) for a key that is present or absent in the cache.
+    ///
+    /// The presence of the key is checked by `crate::cache::command::command_executor::CommandExecutor`, which executes the writes one at a time,
+    /// so the clients do not need to check for the presence of the key before putting it.
+    /// With `PutMode::Overwrite` and `PutMode::UpdateOnly`, the existing key is removed only after the put is admitted,
+    /// so a rejected put leaves the existing key in the cache.
+    ///
+    /// Default is [`PutMode::InsertOnly`].
+    pub fn put_mode(mut self, put_mode: PutMode) -> ConfigBuilder<Key, Value> {
+        self.put_mode = put_mode;
+        self
+    }
 
-        assert_eq!(1, hash);
+    /// Sets how the admission policy decides to admit a key when the cache does not have the weight to accommodate it, read [`crate::cache::config::AdmissionMode`].
+    ///
+    /// `AdmissionMode::Always` is meant for the small caches that want to retain every put (that fits in the cache weight),
+    /// the access frequency of the keys is still maintained to pick the keys to evict.
+    ///
+    /// Default is [`AdmissionMode::TinyLfu`].
+    pub fn admission(mut self, admission_mode: AdmissionMode) -> ConfigBuilder<Key, Value> {
+        self.admission_mode = admission_mode;
+        self
     }
 
-    #[test]
-    fn weight_calculation_function() {
-        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+    /// Sets the number of accesses after which the admission policy halves the access frequencies of all the keys (and clears the doorkeeper),
+    /// so that the cache adapts to the shifting access patterns. A smaller number ages the frequencies faster.
+    ///
+    /// The accesses are counted when they are applied to the access frequencies, not when `get` is invoked.
+    /// The accesses are buffered in `crate::cache::pool::Pool` and applied when a buffer is drained (read [`crate::cache::config::AccessDrain`]),
+    /// so the buffered accesses count towards the reset only after they are drained, and the dropped buffers do not count at all.
+    /// Use [`crate::cache::cached::CacheD::reset_frequencies`] to halve the access frequencies on demand.
+    ///
+    /// `frequency_reset_after` must be greater than zero. Default is the `counters`.
+    pub fn frequency_reset_after(mut self, accesses: u64) -> ConfigBuilder<Key, Value> {
+        assert!(accesses > 0, "{}", Errors::FrequencyResetAfterGtZero);
+        self.frequency_reset_after = Some(accesses);
+        self
+    }
 
-        let weight_calculation_fn = Box::new(|_key: &&str, _value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| 10);
-        let config = builder.weight_calculation_fn(weight_calculation_fn).build();
+    /// Sets the behavior of `get` (and its variants) for a key that has expired, but is not yet removed by `crate::cache::expiration::TTLTicker`,
+    /// read [`crate::cache::config::ExpiredReadBehavior`].
+    ///
+    /// Default is `ExpiredReadBehavior::Miss`.
+    pub fn expired_read_behavior(mut self, expired_read_behavior: ExpiredReadBehavior) -> ConfigBuilder<Key, Value> {
+        self.expired_read_behavior = expired_read_behavior;
+        self
+    }
 
-        let key = "topic";
-        let value = "microservices";
-        let weight = (config.weight_calculation_fn)(&key, &value, false);
+    /// Biases the eviction towards the keys with `time_to_live` when the cache is full.
+    ///
+    /// The cache evicts the key with the smallest access frequency from a sample of keys.
+    /// If enabled, amongst the sampled keys with the same access frequency, the key that expires the soonest is evicted first,
+    /// and the keys without `time_to_live` are evicted last. Such keys would be removed soon anyway, so this keeps the long-lived hot keys resident longer.
+    /// The access frequency remains the primary criterion, so a frequently accessed key with `time_to_live` is not evicted before a rarely accessed key without it.
+    ///
+    /// Determining the expiry of the sampled keys requires a lookup in `crate::cache::store::Store`, which makes the eviction slightly costlier.
+    ///
+    /// Default is `false`.
+    pub fn prefer_evicting_ttl_entries(mut self, prefer: bool) -> ConfigBuilder<Key, Value> {
+        self.prefer_evicting_ttl_entries = prefer;
+        self
+    }
 
-        assert_eq!(10, weight);
+    /// Sets the function that is invoked when the cache becomes full.
+    ///
+    /// The cache becomes full when the weight used reaches the total cache weight, or when an incoming key does not fit in the weight left.
+    /// `on_full` is invoked once when the cache becomes full, and is not invoked again until the cache regains headroom. Read [`ConfigBuilder::on_has_headroom`].
+    ///
+    /// The function is invoked on the thread that performs the write (or the expiry) and should return quickly.
+    /// When it runs on the thread of `crate::cache::command::command_executor::CommandExecutor`, it can not wait for
+    /// [`crate::cache::cached::CacheD::clear_barrier`] (which returns an error), and its puts are not blocked by a `clear_barrier` in progress.
+    pub fn on_full(mut self, on_full: Box<CapacityListenerFn>) -> ConfigBuilder<Key, Value> {
+        self.on_full = Some(Arc::from(on_full));
+        self
     }
 
-    #[test]
-    fn clock() {
-        let builder: ConfigBuilder<&str, &str> = test_config_builder();
-        let clock: ClockType = Box::new(UnixEpochClock {});
+    /// Sets the function that is invoked when a full cache regains headroom.
+    ///
+    /// A full cache regains headroom when the weight used drops to 90% of the total cache weight (or below).
+    /// This keeps `on_full` and `on_has_headroom` from firing repeatedly while the weight used hovers around the total cache weight.
+    ///
+    /// The function is invoked on the thread that performs the write (or the expiry) and should return quickly.
+    /// Like `on_full`, it can not wait for [`crate::cache::cached::CacheD::clear_barrier`] on the thread of `crate::cache::command::command_executor::CommandExecutor`.
+    pub fn on_has_headroom(mut self, on_has_headroom: Box<CapacityListenerFn>) -> ConfigBuilder<Key, Value> {
+        self.on_has_headroom = Some(Arc::from(on_has_headroom));
+        self
+    }
 
-        let config = builder.clock(clock).build();
-        assert_eq!(SystemTime::UNIX_EPOCH, config.clock.now());
+    /// Sets the function that receives all the keys that expire in a single run of `crate::cache::expiration::TTLTicker` together, as one batch.
+    ///
+    /// Receiving the expired keys together allows flushing them efficiently, for example, with a single database write for the whole batch.
+    /// The batch is at most as large as [`ConfigBuilder::max_reaps_per_tick`], and the listener is not invoked for a run that removes no keys.
+    ///
+    /// The keys in a batch are in no particular order, and there is no ordering guarantee across the batches.
+    /// The listener is invoked on the thread of `TTLTicker`, so a slow listener delays the removal of the expired keys.
+    pub fn batch_eviction_listener(mut self, listener: Box<BatchEvictionListenerFn<Key>>) -> ConfigBuilder<Key, Value> {
+        self.batch_eviction_listener = Some(Arc::from(listener));
+        self
     }
 
-    #[test]
-    fn access_pool_size() {
-        let builder: ConfigBuilder<&str, &str> = test_config_builder();
-        let config = builder.access_pool_size(32).build();
+    /// Sets the function that receives every key removed from the cache, along with its value and the [`EvictionReason`].
+    ///
+    /// This allows flushing the removed entries to a secondary store. The listener is invoked when the admission policy evicts a key
+    /// to create space (`EvictionReason::Evicted`), when a key expires (`EvictionReason::Expired`), when a key is deleted (`EvictionReason::Explicit`),
+    /// and when a put replaces an existing key (`EvictionReason::Replaced`). A deleted or a replaced key that had already expired is reported as expired.
+    ///
+    /// The keys taken out of the cache using `take` (and its variants) are returned to the caller, and are not reported.
+    /// The values updated in place by `put_or_update` are not reported either.
+    ///
+    /// The listener is invoked on the thread of `crate::cache::command::command_executor::CommandExecutor`, or on the thread of
+    /// `crate::cache::expiration::TTLTicker` for the expired keys, never on the thread performing the operation (unless
+    /// [`ConfigBuilder::inline_execution`] is enabled). A slow listener delays the execution of the subsequent commands, so it should return quickly.
+    ///
+    /// On the thread of `CommandExecutor`, the listener can not wait for [`crate::cache::cached::CacheD::clear_barrier`] (which returns an error),
+    /// and the keys it puts are not blocked by a `clear_barrier` in progress, so they may be present after the clear.
+    pub fn eviction_listener(mut self, listener: Box<EvictionListenerFn<Key, Value>>) -> ConfigBuilder<Key, Value> {
+        self.eviction_listener = Some(Arc::from(listener));
+        self
+    }
 
-        assert_eq!(PoolSize(32), config.access_pool_size);
+    /// Sets the function that observes every admission decision of the admission policy, without affecting the decision.
+    ///
+    /// The observer receives an [`AdmissionDecision`] for every key that is put (and its variants) and reaches the admission policy,
+    /// describing the incoming key, its estimated access frequency, the victim (if any) and the decision.
+    /// This allows evaluating a change in the admission policy in production, by feeding the same stream of decisions to a shadow policy
+    /// and comparing what it would have decided.
+    ///
+    /// The observer is invoked on the thread of `crate::cache::command::command_executor::CommandExecutor` (or on the thread performing `put`
+    /// with [`ConfigBuilder::inline_execution`]), so a slow observer delays the execution of the subsequent commands.
+    /// The observer can not wait for [`crate::cache::cached::CacheD::clear_barrier`] (which returns an error), and its puts are not blocked by a `clear_barrier`.
+    pub fn admission_observer(mut self, observer: Box<AdmissionObserverFn>) -> ConfigBuilder<Key, Value> {
+        self.admission_observer = Some(Arc::from(observer));
+        self
     }
 
-    #[test]
-    fn access_buffer_size() {
-        let builder: ConfigBuilder<&str, &str> = test_config_builder();
-        let config = builder.access_buffer_size(64).build();
+    /// Sets the function that is invoked with a key that is admitted again after it was evicted to create space for the other keys.
+    ///
+    /// A key that oscillates in and out of the cache is a sign that the cache is undersized for the working set.
+    /// The cache remembers the hashes of the last 1024 keys that were evicted to create space in a ring buffer, so the memory used to detect
+    /// the readmissions is bounded; a key evicted before the last 1024 evictions is not reported when it is admitted again.
+    /// The keys that expire, or are deleted, are not considered evicted.
+    ///
+    /// Setting the function also enables [`crate::cache::cached::CacheD::thrash_rate`].
+    /// The function is invoked on the thread of `crate::cache::command::command_executor::CommandExecutor`, so it should return quickly.
+    /// For the same reason, it can not wait for [`crate::cache::cached::CacheD::clear_barrier`] (which returns an error), and its puts are not blocked by a `clear_barrier`.
+    pub fn on_readmission(mut self, on_readmission: Box<ReadmissionListenerFn<Key>>) -> ConfigBuilder<Key, Value> {
+        self.on_readmission = Some(Arc::from(on_readmission));
+        self
+    }
 
-        assert_eq!(BufferSize(64), config.access_buffer_size);
+    /// Registers a consumer that receives the same drained access batches (the key hashes of `get` and its variants) as the admission policy.
+    ///
+    /// The consumer is invoked after the admission policy accepts the batch, on the thread that drains the access buffer
+    /// (the thread performing `get`, or the periodic drain of [`crate::cache::config::AccessDrain::Periodic`]), while holding the lock on the buffer.
+    /// Hence, the consumer can not stall the update of the access frequency, but it should hand off the batch quickly (or drop it, if it lags) to keep `get` fast.
+    /// With [`crate::cache::config::AccessDrain::Sync`], every access is passed to the consumer as a batch of one.
+    ///
+    /// The consumer receives [`crate::cache::buffer_event::BufferEvent::Shutdown`] when the cache is shutdown.
+    pub fn additional_access_consumer(mut self, consumer: Box<AdditionalBufferConsumer>) -> ConfigBuilder<Key, Value> {
+        self.additional_access_consumer = Some(Arc::from(consumer));
+        self
     }
 
-    #[test]
-    fn command_buffer_size() {
-        let builder: ConfigBuilder<&str, &str> = test_config_builder();
-        let config = builder.command_buffer_size(1024).build();
+    /// Records each `get`, `put` and `delete` with its key hash and timestamp to the `writer`. Available with the `trace` feature.
+    ///
+    /// The recorded trace can be replayed against a fresh cache using [`crate::cache::trace::replay::replay`].
+    ///
+    /// The writer is buffered and the buffered events are flushed when the cache is shutdown.
+    /// Recording takes a lock on the writer for every operation, so it is meant for capturing the access patterns, not for every deployment.
+    #[cfg(feature = "trace")]
+    pub fn record_trace(mut self, writer: Box<dyn Write + Send>) -> ConfigBuilder<Key, Value> {
+        self.trace_recorder = Some(TraceRecorder::new(writer));
+        self
+    }
 
-        assert_eq!(1024, config.command_buffer_size);
+    // Builds an instance of Config with the supplied values, panics if a value is invalid, read [`ConfigBuilder::try_build`].
+    pub fn build(self) -> Config<Key, Value> {
+        match self.try_build() {
+            Ok(config) => config,
+            Err(error) => panic!("{}", error),
+        }
     }
 
-    #[test]
+    /// Builds an instance of Config with the supplied values, or returns a [`crate::cache::config::error::ConfigError`] if a value is invalid,
+    /// which is useful if the values come from an external input (for example, a configuration file).
+    /// ```
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use tinylfu_cached::cache::config::error::ConfigError;
+    ///
+    /// let result = ConfigBuilder::<&str, &str>::new(100, 10, 100).shards(3).try_build();
+    /// assert_eq!(Some(ConfigError::TotalShardsPowerOf2), result.err());
+    /// ```
+    pub fn try_build(self) -> Result<Config<Key, Value>, ConfigError> {
+        self.validate()?;
+        Ok(Config {
+            key_hash_fn: self.key_hash_fn,
+            weight_calculation_fn: self.weight_calculation_fn,
+            fallible_weight_calculation_fn: self.fallible_weight_calculation_fn,
+            clock: self.clock,
+            access_pool_size: self.access_pool_size,
+            access_buffer_size: self.access_buffer_size,
+            command_buffer_size: self.command_buffer_size,
+            max_command_buffer_size: self.max_command_buffer_size,
+            command_executor_threads: self.command_executor_threads,
+            sketch_depth: self.sketch_depth,
+            counters: self.counters,
+            capacity: self.capacity,
+            total_cache_weight: self.total_cache_weight,
+            shards: self.shards,
+            ttl_tick_duration: self.ttl_tick_duration,
+            max_reaps_per_tick: self.max_reaps_per_tick,
+            shard_hasher_fn: self.shard_hasher_fn,
+            inline_execution: self.inline_execution,
+            put_mode: self.put_mode,
+            admission_mode: self.admission_mode,
+            frequency_reset_after: self.frequency_reset_after,
+            expired_read_behavior: self.expired_read_behavior,
+            access_drain: self.access_drain,
+            prefer_evicting_ttl_entries: self.prefer_evicting_ttl_entries,
+            on_full: self.on_full,
+            on_has_headroom: self.on_has_headroom,
+            additional_access_consumer: self.additional_access_consumer,
+            batch_eviction_listener: self.batch_eviction_listener,
+            eviction_listener: self.eviction_listener,
+            audit_key_fn: self.audit_key_fn,
+            admission_observer: self.admission_observer,
+            on_readmission: self.on_readmission,
+            delete_tombstone_ttl: self.delete_tombstone_ttl,
+            value_transform: self.value_transform,
+            delete_on_value: self.delete_on_value,
+            soft_capacity: self.soft_capacity,
+            min_residency: self.min_residency,
+            default_time_to_live: self.default_time_to_live,
+            sliding_ttl: self.sliding_ttl,
+            ttl_enabled: self.ttl_enabled,
+            #[cfg(feature = "trace")]
+            trace_recorder: self.trace_recorder,
+        })
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.counters == 0 {
+            return Err(ConfigError::TotalCountersGtZero);
+        }
+        if self.capacity == 0 {
+            return Err(ConfigError::TotalCapacityGtZero);
+        }
+        if self.total_cache_weight <= 0 {
+            return Err(ConfigError::TotalCacheWeightGtZero);
+        }
+        if self.shards <= 1 {
+            return Err(ConfigError::TotalShardsGtOne);
+        }
+        if !self.shards.is_power_of_two() {
+            return Err(ConfigError::TotalShardsPowerOf2);
+        }
+        if self.access_pool_size.0 == 0 {
+            return Err(ConfigError::PoolSizeGtZero);
+        }
+        if self.access_buffer_size.0 == 0 {
+            return Err(ConfigError::BufferSizeGtZero);
+        }
+        if self.command_buffer_size == 0 {
+            return Err(ConfigError::CommandBufferSizeGtZero);
+        }
+        if self.max_command_buffer_size.is_some_and(|max_command_buffer_size| max_command_buffer_size < self.command_buffer_size) {
+            return Err(ConfigError::InvalidMaxCommandBufferSize);
+        }
+        if !self.ttl_enabled && (self.default_time_to_live.is_some() || self.sliding_ttl.is_some() || self.delete_tombstone_ttl.is_some()) {
+            return Err(ConfigError::NoTTLWithTimeToLive);
+        }
+        Ok(())
+    }
+}
+
+impl<Key, Value> ConfigBuilder<Key, Value>
+    where Key: Hash + Display + 'static,
+          Value: 'static {
+    /// Enables (or disables) the audit trail of the keys that leave the cache.
+    ///
+    /// If enabled, every eviction, expiry and delete is logged at info level with the target [`crate::cache::config::AUDIT_LOG_TARGET`],
+    /// along with the key (formatted using its `Display` implementation), the reason and the weight reclaimed.
+    /// The target allows routing the audit trail separately from the other logs of the cache.
+    ///
+    /// Every removal of a key produces a log record, so the audit trail is meant for the deployments that need a record of what left the cache and why.
+    /// When disabled, removing a key costs a single additional branch.
+    ///
+    /// Default is `false`.
+    pub fn audit_evictions(mut self, audit: bool) -> ConfigBuilder<Key, Value> {
+        self.audit_key_fn = if audit {
+            Some(Arc::new(|key: &Key| key.to_string()))
+        } else {
+            None
+        };
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{BuildHasher, Hash, Hasher};
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::clock::{ClockType, ManualClock};
+    use crate::cache::config::{AccessDrain, AdmissionMode, Config, ConfigBuilder, EvictionReason, ExpiredReadBehavior, PutMode};
+    use crate::cache::config::error::{ConfigError, WeightError};
+    use crate::cache::pool::{BufferSize, PoolSize};
+    use crate::cache::types::{IsTimeToLiveSpecified, Weight};
+
+    fn test_config_builder() -> ConfigBuilder<&'static str, &'static str>{
+        ConfigBuilder::new(100, 10, 100)
+    }
+
+    #[test]
+    fn key_hash_function() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+
+        let key_hash_fn = Box::new(|_key: &&str| 1);
+        let config = builder.key_hash_fn(key_hash_fn).build();
+
+        let key = "topic";
+        let hash = (config.key_hash_fn)(&key);
+
+        assert_eq!(1, hash);
+    }
+
+    #[test]
+    fn seeded_hashers_with_different_seeds() {
+        let config: Config<&str, &str> = test_config_builder().seeded_hasher_with(10).build();
+        let other_config: Config<&str, &str> = test_config_builder().seeded_hasher_with(20).build();
+
+        assert_ne!((config.key_hash_fn)(&"topic"), (other_config.key_hash_fn)(&"topic"));
+    }
+
+    #[test]
+    fn seeded_hashers_with_the_same_seed() {
+        let config: Config<&str, &str> = test_config_builder().seeded_hasher_with(10).build();
+        let other_config: Config<&str, &str> = test_config_builder().seeded_hasher_with(10).build();
+
+        assert_eq!((config.key_hash_fn)(&"topic"), (other_config.key_hash_fn)(&"topic"));
+    }
+
+    #[test]
+    fn seeded_hasher_is_used_for_the_shards_and_the_key_hash() {
+        let config: Config<&str, &str> = test_config_builder().seeded_hasher().build();
+
+        assert_eq!((config.key_hash_fn)(&"topic"), config.shard_hash_builder().hash_one("topic"));
+    }
+
+    #[test]
+    fn dual_hash() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+
+        let shard_hasher_fn = Box::new(|| Box::new(DefaultHasher::new()) as Box<dyn Hasher>);
+        let config = builder.dual_hash(shard_hasher_fn, Box::new(|_key: &&str| 1)).build();
+
+        assert_eq!(1, (config.key_hash_fn)(&"topic"));
+
+        let mut hasher = DefaultHasher::new();
+        "topic".hash(&mut hasher);
+        assert_eq!(hasher.finish(), config.shard_hash_builder().hash_one("topic"));
+    }
+
+    #[test]
+    fn weight_calculation_function() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+
+        let weight_calculation_fn = Box::new(|_key: &&str, _value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| 10);
+        let config = builder.weight_calculation_fn(weight_calculation_fn).build();
+
+        let key = "topic";
+        let value = "microservices";
+        let weight = (config.weight_calculation_fn)(&key, &value, false);
+
+        assert_eq!(10, weight);
+        assert_eq!(Ok(10), config.weight_of(&key, &value, false));
+    }
+
+    #[test]
+    fn fallible_weight_calculation_function() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+
+        let weight_calculation_fn = Box::new(|_key: &&str, value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| {
+            if value.is_empty() { Err(WeightError::new("value is empty")) } else { Ok(10) }
+        });
+        let config = builder.fallible_weight_calculation_fn(weight_calculation_fn).build();
+
+        assert_eq!(Ok(10), config.weight_of(&"topic", &"microservices", false));
+        assert_eq!(Err(WeightError::new("value is empty")), config.weight_of(&"topic", &"", false));
+    }
+
+    #[test]
+    fn weight_calculation_function_replaces_the_fallible_weight_calculation_function() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+
+        let config = builder
+            .fallible_weight_calculation_fn(Box::new(|_key, _value, _is_time_to_live_specified| Err(WeightError::new("value is empty"))))
+            .weight_calculation_fn(Box::new(|_key, _value, _is_time_to_live_specified| 10))
+            .build();
+
+        assert!(config.fallible_weight_calculation_fn.is_none());
+        assert_eq!(Ok(10), config.weight_of(&"topic", &"", false));
+    }
+
+    #[test]
+    fn clock() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+
+        let config = builder.clock(clock).build();
+        assert_eq!(SystemTime::UNIX_EPOCH, config.clock.now());
+    }
+
+    #[test]
+    fn access_pool_size() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.access_pool_size(32).build();
+
+        assert_eq!(PoolSize(32), config.access_pool_size);
+    }
+
+    #[test]
+    fn access_buffer_size() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.access_buffer_size(64).build();
+
+        assert_eq!(BufferSize(64), config.access_buffer_size);
+    }
+
+    #[test]
+    fn command_buffer_size() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.command_buffer_size(1024).build();
+
+        assert_eq!(1024, config.command_buffer_size);
+    }
+
+    #[test]
+    fn command_executor_threads() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.command_executor_threads(4).build();
+
+        assert_eq!(4, config.command_executor_threads);
+    }
+
+    #[test]
+    #[should_panic]
+    fn command_executor_threads_must_be_greater_than_zero() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.command_executor_threads(0);
+    }
+
+    #[test]
+    fn sketch_depth() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.sketch_depth(8).build();
+
+        assert_eq!(8, config.sketch_depth);
+    }
+
+    #[test]
+    fn sketch_depth_is_4_by_default() {
+        let config: Config<&str, &str> = test_config_builder().build();
+
+        assert_eq!(4, config.sketch_depth);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sketch_depth_must_be_at_least_4() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.sketch_depth(3);
+    }
+
+    #[test]
     fn counters() {
         let config: Config<&str, &str> = ConfigBuilder::new(4096, 400, 100).build();
 
@@ -345,6 +1314,177 @@ mod tests {
         assert_eq!(Duration::from_secs(5), config.ttl_tick_duration);
     }
 
+    #[test]
+    fn inline_execution() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.inline_execution().build();
+
+        assert!(config.inline_execution);
+    }
+
+    #[test]
+    fn inline_execution_is_disabled_by_default() {
+        let config: Config<&str, &str> = test_config_builder().build();
+
+        assert!(!config.inline_execution);
+    }
+
+    #[test]
+    fn put_mode() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.put_mode(PutMode::Overwrite).build();
+
+        assert_eq!(PutMode::Overwrite, config.put_mode);
+    }
+
+    #[test]
+    fn access_drain() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.access_drain(AccessDrain::Sync).build();
+
+        assert_eq!(AccessDrain::Sync, config.access_drain);
+    }
+
+    #[test]
+    fn access_drain_is_on_full_by_default() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.build();
+
+        assert_eq!(AccessDrain::OnFull, config.access_drain);
+    }
+
+    #[test]
+    #[should_panic]
+    fn access_drain_period_must_be_greater_than_zero() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.access_drain(AccessDrain::Periodic(Duration::ZERO));
+    }
+
+    #[test]
+    fn expired_read_behavior() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.expired_read_behavior(ExpiredReadBehavior::ServeStaleOnce).build();
+
+        assert_eq!(ExpiredReadBehavior::ServeStaleOnce, config.expired_read_behavior);
+    }
+
+    #[test]
+    fn expired_read_behavior_is_miss_by_default() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.build();
+
+        assert_eq!(ExpiredReadBehavior::Miss, config.expired_read_behavior);
+    }
+
+    #[test]
+    fn put_mode_is_insert_only_by_default() {
+        let config: Config<&str, &str> = test_config_builder().build();
+
+        assert_eq!(PutMode::InsertOnly, config.put_mode);
+    }
+
+    #[test]
+    fn admission_mode() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.admission(AdmissionMode::Always).build();
+
+        assert_eq!(AdmissionMode::Always, config.admission_mode);
+    }
+
+    #[test]
+    fn frequency_reset_after() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.frequency_reset_after(500).build();
+
+        assert_eq!(Some(500), config.frequency_reset_after);
+    }
+
+    #[test]
+    #[should_panic]
+    fn frequency_reset_after_must_be_greater_than_zero() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.frequency_reset_after(0);
+    }
+
+    #[test]
+    fn admission_mode_is_tiny_lfu_by_default() {
+        let config: Config<&str, &str> = test_config_builder().build();
+
+        assert_eq!(AdmissionMode::TinyLfu, config.admission_mode);
+    }
+
+    #[test]
+    fn prefer_evicting_ttl_entries() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.prefer_evicting_ttl_entries(true).build();
+
+        assert!(config.prefer_evicting_ttl_entries);
+    }
+
+    #[test]
+    fn prefer_evicting_ttl_entries_is_disabled_by_default() {
+        let config: Config<&str, &str> = test_config_builder().build();
+
+        assert!(!config.prefer_evicting_ttl_entries);
+    }
+
+    #[test]
+    fn audit_evictions() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.audit_evictions(true).build();
+
+        let audit_key_fn = config.audit_key_fn.unwrap();
+        assert_eq!("topic", audit_key_fn(&"topic"));
+    }
+
+    #[test]
+    fn disable_audit_evictions() {
+        let config: Config<&str, &str> = test_config_builder().audit_evictions(true).audit_evictions(false).build();
+
+        assert!(config.audit_key_fn.is_none());
+    }
+
+    #[test]
+    fn audit_evictions_is_disabled_by_default() {
+        let config: Config<&str, &str> = test_config_builder().build();
+
+        assert!(config.audit_key_fn.is_none());
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn record_trace() {
+        let config: Config<&str, &str> = test_config_builder().record_trace(Box::new(std::io::sink())).build();
+
+        assert!(config.trace_recorder.is_some());
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn trace_is_not_recorded_by_default() {
+        let config: Config<&str, &str> = test_config_builder().build();
+
+        assert!(config.trace_recorder.is_none());
+    }
+
+    #[test]
+    fn on_full() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.on_full(Box::new(|| {})).build();
+
+        assert!(config.on_full.is_some());
+        assert!(config.on_has_headroom.is_none());
+    }
+
+    #[test]
+    fn on_has_headroom() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.on_has_headroom(Box::new(|| {})).build();
+
+        assert!(config.on_has_headroom.is_some());
+        assert!(config.on_full.is_none());
+    }
+
     #[test]
     fn ttl_config() {
         let builder: ConfigBuilder<&str, &str> = test_config_builder();
@@ -353,6 +1493,148 @@ mod tests {
         let ttl_config = config.ttl_config();
         assert_eq!(16, ttl_config.shards());
         assert_eq!(Duration::from_secs(5), ttl_config.tick_duration());
+        assert_eq!(usize::MAX, ttl_config.max_reaps_per_tick());
+    }
+
+    #[test]
+    fn ttl_config_with_max_reaps_per_tick() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.max_reaps_per_tick(100).build();
+
+        assert_eq!(100, config.ttl_config().max_reaps_per_tick());
+    }
+
+    #[test]
+    #[should_panic]
+    fn max_reaps_per_tick_must_be_greater_than_zero() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.max_reaps_per_tick(0);
+    }
+
+    #[test]
+    fn delete_tombstone_ttl() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.delete_tombstone_ttl(Duration::from_secs(5)).build();
+
+        assert_eq!(Some(Duration::from_secs(5)), config.delete_tombstone_ttl);
+    }
+
+    #[test]
+    #[should_panic]
+    fn delete_tombstone_ttl_must_be_greater_than_zero() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.delete_tombstone_ttl(Duration::ZERO);
+    }
+
+    #[test]
+    fn soft_capacity() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.soft_capacity(50).build();
+
+        assert_eq!(Some(50), config.soft_capacity);
+    }
+
+    #[test]
+    #[should_panic]
+    fn soft_capacity_must_be_greater_than_zero() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.soft_capacity(0);
+    }
+
+    #[test]
+    fn min_residency() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.min_residency(Duration::from_secs(5)).build();
+
+        assert_eq!(Some(Duration::from_secs(5)), config.min_residency);
+    }
+
+    #[test]
+    #[should_panic]
+    fn min_residency_must_be_greater_than_zero() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.min_residency(Duration::ZERO);
+    }
+
+    #[test]
+    fn default_time_to_live() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.default_time_to_live(Duration::from_secs(300)).build();
+
+        assert_eq!(Some(Duration::from_secs(300)), config.default_time_to_live);
+    }
+
+    #[test]
+    #[should_panic]
+    fn default_time_to_live_must_be_greater_than_zero() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.default_time_to_live(Duration::ZERO);
+    }
+
+    #[test]
+    fn sliding_ttl() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.sliding_ttl(Duration::from_secs(60)).build();
+
+        assert_eq!(Some(Duration::from_secs(60)), config.sliding_ttl);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sliding_ttl_must_be_greater_than_zero() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.sliding_ttl(Duration::ZERO);
+    }
+
+    #[test]
+    fn no_ttl() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        assert!(builder.ttl_enabled);
+
+        let config = builder.no_ttl().build();
+        assert!(!config.ttl_enabled);
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_ttl_can_not_have_a_default_time_to_live() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.no_ttl().default_time_to_live(Duration::from_secs(300)).build();
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_ttl_can_not_have_a_delete_tombstone_ttl() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let _ = builder.delete_tombstone_ttl(Duration::from_secs(5)).no_ttl().build();
+    }
+
+    #[test]
+    #[should_panic]
+    fn soft_capacity_must_be_less_than_the_total_cache_weight() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let total_cache_weight = builder.total_cache_weight;
+        let _ = builder.soft_capacity(total_cache_weight);
+    }
+
+    #[test]
+    fn value_transform() {
+        let builder: ConfigBuilder<&str, String> = ConfigBuilder::new(100, 10, 100);
+        let config = builder.value_transform(Box::new(|value| value.to_uppercase()), Box::new(|value| value.to_lowercase())).build();
+
+        let value_transform = config.value_transform.unwrap();
+        assert_eq!("CACHED", (value_transform.on_put)("cached".to_string()));
+        assert_eq!("cached", (value_transform.on_get)("CACHED".to_string()));
+    }
+
+    #[test]
+    fn delete_on_value() {
+        let builder: ConfigBuilder<&str, &str> = ConfigBuilder::new(100, 10, 100);
+        let config = builder.delete_on_value(Box::new(|value| value.is_empty())).build();
+
+        let delete_on_value = config.delete_on_value.unwrap();
+        assert!(delete_on_value(&""));
+        assert!(!delete_on_value(&"cached"));
     }
 
     #[test]
@@ -378,6 +1660,21 @@ mod tests {
         let _: Config<&str, &str> = test_config_builder().access_buffer_size(0).build();
     }
 
+    #[test]
+    fn adaptive_command_buffer() {
+        let builder: ConfigBuilder<&str, &str> = test_config_builder();
+        let config = builder.command_buffer_size(16).adaptive_command_buffer(1024).build();
+
+        assert_eq!(16, config.command_buffer_size);
+        assert_eq!(Some(1024), config.max_command_buffer_size);
+    }
+
+    #[test]
+    #[should_panic]
+    fn max_command_buffer_size_must_not_be_less_than_command_buffer_size() {
+        let _: Config<&str, &str> = test_config_builder().command_buffer_size(16).adaptive_command_buffer(8).build();
+    }
+
     #[test]
     #[should_panic]
     fn command_buffer_size_must_be_greater_than_zero() {
@@ -402,6 +1699,47 @@ mod tests {
         let _: Config<&str, &str> = ConfigBuilder::new(100, 100, 0).build();
     }
 
+    #[test]
+    fn for_hit_ratio() {
+        let config: Config<&str, &str> = ConfigBuilder::for_hit_ratio(0.73, 1_600_000, 40).build();
+
+        assert_eq!(16_000_000, config.counters);
+        assert!(config.shards.is_power_of_two());
+        assert!(config.capacity > 90_000 && config.capacity < 120_000);
+        assert_eq!(config.capacity as Weight * 40, config.total_cache_weight);
+    }
+
+    #[test]
+    fn for_hit_ratio_with_a_higher_target_derives_a_higher_capacity() {
+        let lower: Config<&str, &str> = ConfigBuilder::for_hit_ratio(0.5, 10_000, 40).build();
+        let higher: Config<&str, &str> = ConfigBuilder::for_hit_ratio(0.8, 10_000, 40).build();
+
+        assert_eq!(lower.counters, higher.counters);
+        assert!(higher.capacity > lower.capacity);
+        assert!(lower.shards.is_power_of_two() && lower.shards > 1);
+    }
+
+    #[test]
+    fn for_hit_ratio_caps_the_capacity_at_the_expected_keys() {
+        let config: Config<&str, &str> = ConfigBuilder::for_hit_ratio(0.99, 100, 40).build();
+
+        assert_eq!(100, config.capacity);
+        assert_eq!(1000, config.counters);
+        assert_eq!(2, config.shards);
+    }
+
+    #[test]
+    #[should_panic]
+    fn for_hit_ratio_target_must_be_less_than_one() {
+        let _: Config<&str, &str> = ConfigBuilder::for_hit_ratio(1.0, 100, 40).build();
+    }
+
+    #[test]
+    #[should_panic]
+    fn for_hit_ratio_target_must_be_greater_than_zero() {
+        let _: Config<&str, &str> = ConfigBuilder::for_hit_ratio(0.0, 100, 40).build();
+    }
+
     #[test]
     #[should_panic]
     fn shards_must_be_greater_than_one() {
@@ -413,4 +1751,84 @@ mod tests {
     fn shards_must_be_power_of_2() {
         let _: Config<&str, &str> = test_config_builder().shards(3).build();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn try_build_a_valid_config() {
+        let config: Config<&str, &str> = test_config_builder().shards(16).try_build().unwrap();
+        assert_eq!(16, config.shards);
+    }
+
+    #[test]
+    fn try_build_with_zero_counters() {
+        let result = ConfigBuilder::<&str, &str>::new(0, 10, 10).try_build();
+        assert_eq!(Some(ConfigError::TotalCountersGtZero), result.err());
+    }
+
+    #[test]
+    fn try_build_with_zero_capacity() {
+        let result = ConfigBuilder::<&str, &str>::new(10, 0, 10).try_build();
+        assert_eq!(Some(ConfigError::TotalCapacityGtZero), result.err());
+    }
+
+    #[test]
+    fn try_build_with_zero_cache_weight() {
+        let result = ConfigBuilder::<&str, &str>::new(10, 10, 0).try_build();
+        assert_eq!(Some(ConfigError::TotalCacheWeightGtZero), result.err());
+    }
+
+    #[test]
+    fn try_build_with_one_shard() {
+        let result = test_config_builder().shards(1).try_build();
+        assert_eq!(Some(ConfigError::TotalShardsGtOne), result.err());
+    }
+
+    #[test]
+    fn try_build_with_shards_that_are_not_a_power_of_2() {
+        let result = test_config_builder().shards(6).try_build();
+        assert_eq!(Some(ConfigError::TotalShardsPowerOf2), result.err());
+    }
+
+    #[test]
+    fn try_build_with_zero_access_pool_size() {
+        let result = test_config_builder().access_pool_size(0).try_build();
+        assert_eq!(Some(ConfigError::PoolSizeGtZero), result.err());
+    }
+
+    #[test]
+    fn try_build_with_zero_access_buffer_size() {
+        let result = test_config_builder().access_buffer_size(0).try_build();
+        assert_eq!(Some(ConfigError::BufferSizeGtZero), result.err());
+    }
+
+    #[test]
+    fn try_build_with_zero_command_buffer_size() {
+        let result = test_config_builder().command_buffer_size(0).try_build();
+        assert_eq!(Some(ConfigError::CommandBufferSizeGtZero), result.err());
+    }
+
+    #[test]
+    fn try_build_with_max_command_buffer_size_less_than_command_buffer_size() {
+        let result = test_config_builder().command_buffer_size(16).adaptive_command_buffer(8).try_build();
+        assert_eq!(Some(ConfigError::InvalidMaxCommandBufferSize), result.err());
+    }
+
+    #[test]
+    fn try_build_adaptive_command_buffer_before_command_buffer_size() {
+        let config = test_config_builder().adaptive_command_buffer(64).command_buffer_size(16).try_build().unwrap();
+        assert_eq!(Some(64), config.max_command_buffer_size);
+    }
+
+    #[test]
+    fn try_build_no_ttl_with_a_default_time_to_live() {
+        let result = test_config_builder().no_ttl().default_time_to_live(Duration::from_secs(300)).try_build();
+        assert_eq!(Some(ConfigError::NoTTLWithTimeToLive), result.err());
+    }
+
+    #[test]
+    fn eviction_reason_display() {
+        assert_eq!("evicted", EvictionReason::Evicted.to_string());
+        assert_eq!("expired", EvictionReason::Expired.to_string());
+        assert_eq!("deleted", EvictionReason::Explicit.to_string());
+        assert_eq!("replaced", EvictionReason::Replaced.to_string());
+    }
+}
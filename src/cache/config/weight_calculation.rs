@@ -29,6 +29,12 @@ impl Calculation {
 
     pub(crate) fn ttl_ticker_entry_size() -> usize { KEY_ID_SIZE + SYSTEM_TIME_SIZE }
 
+    /// Returns the weight of the entry in `crate::cache::expiration::TTLTicker`, if time_to_live is specified.
+    /// This weight is added to the client provided weight of a key/value pair with time_to_live.
+    pub(crate) fn time_to_live_weight(time_to_live_specified: IsTimeToLiveSpecified) -> Weight {
+        if time_to_live_specified { Self::ttl_ticker_entry_size() as Weight } else { 0 }
+    }
+
     fn stored_value_size<Key, Value>(key: &Key, value: &Value) -> (usize, usize) {
         (std::mem::size_of_val(key), std::mem::size_of_val(value))
     }
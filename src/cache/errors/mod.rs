@@ -7,8 +7,24 @@ const ERROR_MESSAGE_TOTAL_SHARDS_GT_ONE: &str = "Total number of shards must be
 const ERROR_MESSAGE_TOTAL_SHARDS_POWER_OF_2: &str = "Total number of shards must be a power of 2";
 const ERROR_MESSAGE_POOL_SIZE_GT_ZERO: &str = "Pool size must be greater than zero";
 const ERROR_MESSAGE_BUFFER_SIZE_GT_ZERO: &str = "Buffer size must be greater than zero";
+const ERROR_MESSAGE_ACCESS_DRAIN_PERIOD_GT_ZERO: &str = "Period of the access drain must be greater than zero";
 const ERROR_MESSAGE_COMMAND_BUFFER_SIZE_GT_ZERO: &str = "Command buffer size must be greater than zero";
+const ERROR_MESSAGE_COMMAND_EXECUTOR_THREADS_GT_ZERO: &str = "Command executor threads must be greater than zero";
+const ERROR_MESSAGE_FREQUENCY_RESET_AFTER_GT_ZERO: &str = "Number of accesses after which the frequencies are reset must be greater than zero";
+const ERROR_MESSAGE_INVALID_MAX_COMMAND_BUFFER_SIZE: &str = "Maximum command buffer size must be greater than or equal to the command buffer size";
+const ERROR_MESSAGE_MAX_REAPS_PER_TICK_GT_ZERO: &str = "Maximum number of reaps per tick must be greater than zero";
+const ERROR_MESSAGE_DELETE_TOMBSTONE_TTL_GT_ZERO: &str = "Time to live of the delete tombstone must be greater than zero";
+const ERROR_MESSAGE_INVALID_SOFT_CAPACITY: &str = "Soft capacity must be greater than zero and less than the total cache weight";
+const ERROR_MESSAGE_INVALID_TARGET_HIT_RATIO: &str = "Target hit ratio must be greater than zero and less than one";
+const ERROR_MESSAGE_SKETCH_DEPTH_GT_THREE: &str = "Depth of the sketch must be at least 4";
+const ERROR_MESSAGE_SKETCH_DIMENSIONS_MISMATCH: &str = "Total counters of the sketch must match the total counters derived from the counters in the config";
+const ERROR_MESSAGE_MIN_RESIDENCY_GT_ZERO: &str = "Minimum residency must be greater than zero";
+const ERROR_MESSAGE_DEFAULT_TIME_TO_LIVE_GT_ZERO: &str = "Default time to live must be greater than zero";
+const ERROR_MESSAGE_SLIDING_TIME_TO_LIVE_GT_ZERO: &str = "Sliding time to live must be greater than zero";
+const ERROR_MESSAGE_NO_TTL_WITH_TIME_TO_LIVE: &str = "A cache without TTL can not have a default time to live, a sliding time to live or a delete tombstone time to live";
+const ERROR_MESSAGE_TIME_TO_LIVE_DISABLED: &str = "Time to live is not supported by a cache built with no_ttl";
 const ERROR_MESSAGE_KEY_WEIGHT_GT_ZERO: &str = "Weight of the input key/value must be greater than zero";
+const ERROR_MESSAGE_RESERVED_WEIGHT_GT_ZERO: &str = "Weight to reserve must be greater than zero";
 const ERROR_MESSAGE_WEIGHT_CALCULATION_GT_ZERO: &str = "Weight of the input key/value calculated by the weight calculation function must be greater than zero";
 const ERROR_MESSAGE_PUT_OR_UPDATE_VALUE_MISSING: &str = "PutOrUpdate has resulted in a put request, value must be specified";
 const ERROR_MESSAGE_INVALID_PUT_OR_UPDATE: &str = "PutOrUpdate request is invalid, either 'value', 'weight', 'time_to_live' or 'remove_time_to_live' must be specified";
@@ -25,7 +41,23 @@ pub(crate) enum Errors {
     PoolSizeGtZero,
     BufferSizeGtZero,
     CommandBufferSizeGtZero,
+    CommandExecutorThreadsGtZero,
+    FrequencyResetAfterGtZero,
+    InvalidMaxCommandBufferSize,
+    MaxReapsPerTickGtZero,
+    DeleteTombstoneTTLGtZero,
+    AccessDrainPeriodGtZero,
+    InvalidSoftCapacity,
+    InvalidTargetHitRatio,
+    SketchDepthGtThree,
+    SketchDimensionsMismatch,
+    MinResidencyGtZero,
+    DefaultTimeToLiveGtZero,
+    SlidingTimeToLiveGtZero,
+    NoTTLWithTimeToLive,
+    TimeToLiveDisabled(&'static str),
     KeyWeightGtZero(&'static str),
+    ReservedWeightGtZero,
     WeightCalculationGtZero,
     PutOrUpdateValueMissing,
     InvalidPutOrUpdate,
@@ -74,10 +106,42 @@ impl Display for Errors {
                 write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_BUFFER_SIZE_GT_ZERO),
             Errors::CommandBufferSizeGtZero =>
                 write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_COMMAND_BUFFER_SIZE_GT_ZERO),
+            Errors::CommandExecutorThreadsGtZero =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_COMMAND_EXECUTOR_THREADS_GT_ZERO),
+            Errors::FrequencyResetAfterGtZero =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_FREQUENCY_RESET_AFTER_GT_ZERO),
+            Errors::InvalidMaxCommandBufferSize =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_INVALID_MAX_COMMAND_BUFFER_SIZE),
+            Errors::MaxReapsPerTickGtZero =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_MAX_REAPS_PER_TICK_GT_ZERO),
+            Errors::DeleteTombstoneTTLGtZero =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_DELETE_TOMBSTONE_TTL_GT_ZERO),
+            Errors::AccessDrainPeriodGtZero =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_ACCESS_DRAIN_PERIOD_GT_ZERO),
+            Errors::InvalidSoftCapacity =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_INVALID_SOFT_CAPACITY),
+            Errors::InvalidTargetHitRatio =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_INVALID_TARGET_HIT_RATIO),
+            Errors::SketchDepthGtThree =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_SKETCH_DEPTH_GT_THREE),
+            Errors::SketchDimensionsMismatch =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_SKETCH_DIMENSIONS_MISMATCH),
+            Errors::MinResidencyGtZero =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_MIN_RESIDENCY_GT_ZERO),
+            Errors::DefaultTimeToLiveGtZero =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_DEFAULT_TIME_TO_LIVE_GT_ZERO),
+            Errors::SlidingTimeToLiveGtZero =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_SLIDING_TIME_TO_LIVE_GT_ZERO),
+            Errors::NoTTLWithTimeToLive =>
+                write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_NO_TTL_WITH_TIME_TO_LIVE),
+            Errors::TimeToLiveDisabled(operation) =>
+                write!(formatter, "[{}]: {}", ErrorType::Operation(operation), ERROR_MESSAGE_TIME_TO_LIVE_DISABLED),
             Errors::WeightCalculationGtZero =>
                 write!(formatter, "[{}]: {}", ErrorType::Config, ERROR_MESSAGE_WEIGHT_CALCULATION_GT_ZERO),
             Errors::KeyWeightGtZero(operation) =>
                 write!(formatter, "[{}]: {}", ErrorType::Operation(operation), ERROR_MESSAGE_KEY_WEIGHT_GT_ZERO),
+            Errors::ReservedWeightGtZero =>
+                write!(formatter, "[{}]: {}", ErrorType::Operation("reserve"), ERROR_MESSAGE_RESERVED_WEIGHT_GT_ZERO),
             Errors::PutOrUpdateValueMissing =>
                 write!(formatter, "[{}]: {}", ErrorType::Operation("PutOrUpdate"), ERROR_MESSAGE_PUT_OR_UPDATE_VALUE_MISSING),
             Errors::InvalidPutOrUpdate =>
@@ -91,10 +155,26 @@ impl Display for Errors {
 #[cfg(test)]
 mod tests {
     use crate::cache::errors::{ERROR_MESSAGE_BUFFER_SIZE_GT_ZERO, ERROR_MESSAGE_TOTAL_CAPACITY_GT_ZERO, ERROR_MESSAGE_TOTAL_SHARDS_POWER_OF_2};
+    use crate::cache::errors::ERROR_MESSAGE_ACCESS_DRAIN_PERIOD_GT_ZERO;
     use crate::cache::errors::ERROR_MESSAGE_COMMAND_BUFFER_SIZE_GT_ZERO;
+    use crate::cache::errors::ERROR_MESSAGE_COMMAND_EXECUTOR_THREADS_GT_ZERO;
+    use crate::cache::errors::ERROR_MESSAGE_FREQUENCY_RESET_AFTER_GT_ZERO;
+    use crate::cache::errors::ERROR_MESSAGE_INVALID_MAX_COMMAND_BUFFER_SIZE;
+    use crate::cache::errors::ERROR_MESSAGE_DELETE_TOMBSTONE_TTL_GT_ZERO;
     use crate::cache::errors::ERROR_MESSAGE_INVALID_PUT_OR_UPDATE;
+    use crate::cache::errors::ERROR_MESSAGE_INVALID_SOFT_CAPACITY;
+    use crate::cache::errors::ERROR_MESSAGE_INVALID_TARGET_HIT_RATIO;
+    use crate::cache::errors::ERROR_MESSAGE_SKETCH_DEPTH_GT_THREE;
+    use crate::cache::errors::ERROR_MESSAGE_SKETCH_DIMENSIONS_MISMATCH;
+    use crate::cache::errors::ERROR_MESSAGE_MIN_RESIDENCY_GT_ZERO;
+    use crate::cache::errors::ERROR_MESSAGE_DEFAULT_TIME_TO_LIVE_GT_ZERO;
+    use crate::cache::errors::ERROR_MESSAGE_SLIDING_TIME_TO_LIVE_GT_ZERO;
+    use crate::cache::errors::ERROR_MESSAGE_NO_TTL_WITH_TIME_TO_LIVE;
+    use crate::cache::errors::ERROR_MESSAGE_TIME_TO_LIVE_DISABLED;
     use crate::cache::errors::ERROR_MESSAGE_INVALID_PUT_OR_UPDATE_EITHER_TIME_TO_LIVE_OR_REMOVE_TIME_TO_LIVE;
     use crate::cache::errors::ERROR_MESSAGE_KEY_WEIGHT_GT_ZERO;
+    use crate::cache::errors::ERROR_MESSAGE_MAX_REAPS_PER_TICK_GT_ZERO;
+    use crate::cache::errors::ERROR_MESSAGE_RESERVED_WEIGHT_GT_ZERO;
     use crate::cache::errors::ERROR_MESSAGE_POOL_SIZE_GT_ZERO;
     use crate::cache::errors::ERROR_MESSAGE_TOTAL_CACHE_WEIGHT_GT_ZERO;
     use crate::cache::errors::ERROR_MESSAGE_TOTAL_COUNTERS_GT_ZERO;
@@ -152,6 +232,102 @@ mod tests {
         assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_COMMAND_BUFFER_SIZE_GT_ZERO), error.to_string());
     }
 
+    #[test]
+    fn error_command_executor_threads() {
+        let error = Errors::CommandExecutorThreadsGtZero;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_COMMAND_EXECUTOR_THREADS_GT_ZERO), error.to_string());
+    }
+
+    #[test]
+    fn error_frequency_reset_after() {
+        let error = Errors::FrequencyResetAfterGtZero;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_FREQUENCY_RESET_AFTER_GT_ZERO), error.to_string());
+    }
+
+    #[test]
+    fn error_invalid_max_command_buffer_size() {
+        let error = Errors::InvalidMaxCommandBufferSize;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_INVALID_MAX_COMMAND_BUFFER_SIZE), error.to_string());
+    }
+
+    #[test]
+    fn error_reserved_weight() {
+        let error = Errors::ReservedWeightGtZero;
+        assert_eq!(format!("[{}]: {}", ErrorType::Operation("reserve"), ERROR_MESSAGE_RESERVED_WEIGHT_GT_ZERO), error.to_string());
+    }
+
+    #[test]
+    fn error_max_reaps_per_tick() {
+        let error = Errors::MaxReapsPerTickGtZero;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_MAX_REAPS_PER_TICK_GT_ZERO), error.to_string());
+    }
+
+    #[test]
+    fn error_delete_tombstone_ttl() {
+        let error = Errors::DeleteTombstoneTTLGtZero;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_DELETE_TOMBSTONE_TTL_GT_ZERO), error.to_string());
+    }
+
+    #[test]
+    fn error_access_drain_period_gt_zero() {
+        let error = Errors::AccessDrainPeriodGtZero;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_ACCESS_DRAIN_PERIOD_GT_ZERO), error.to_string());
+    }
+
+    #[test]
+    fn error_invalid_soft_capacity() {
+        let error = Errors::InvalidSoftCapacity;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_INVALID_SOFT_CAPACITY), error.to_string());
+    }
+
+    #[test]
+    fn error_invalid_target_hit_ratio() {
+        let error = Errors::InvalidTargetHitRatio;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_INVALID_TARGET_HIT_RATIO), error.to_string());
+    }
+
+    #[test]
+    fn error_sketch_depth() {
+        let error = Errors::SketchDepthGtThree;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_SKETCH_DEPTH_GT_THREE), error.to_string());
+    }
+
+    #[test]
+    fn error_sketch_dimensions_mismatch() {
+        let error = Errors::SketchDimensionsMismatch;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_SKETCH_DIMENSIONS_MISMATCH), error.to_string());
+    }
+
+    #[test]
+    fn error_min_residency_gt_zero() {
+        let error = Errors::MinResidencyGtZero;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_MIN_RESIDENCY_GT_ZERO), error.to_string());
+    }
+
+    #[test]
+    fn error_default_time_to_live_gt_zero() {
+        let error = Errors::DefaultTimeToLiveGtZero;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_DEFAULT_TIME_TO_LIVE_GT_ZERO), error.to_string());
+    }
+
+    #[test]
+    fn error_sliding_time_to_live_gt_zero() {
+        let error = Errors::SlidingTimeToLiveGtZero;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_SLIDING_TIME_TO_LIVE_GT_ZERO), error.to_string());
+    }
+
+    #[test]
+    fn error_no_ttl_with_time_to_live() {
+        let error = Errors::NoTTLWithTimeToLive;
+        assert_eq!(format!("[{}]: {}", ErrorType::Config, ERROR_MESSAGE_NO_TTL_WITH_TIME_TO_LIVE), error.to_string());
+    }
+
+    #[test]
+    fn error_time_to_live_disabled() {
+        let error = Errors::TimeToLiveDisabled("increment_with_ttl");
+        assert_eq!(format!("[{}]: {}", ErrorType::Operation("increment_with_ttl"), ERROR_MESSAGE_TIME_TO_LIVE_DISABLED), error.to_string());
+    }
+
     #[test]
     fn error_key_weight_calculation() {
         let error = Errors::WeightCalculationGtZero;
@@ -7,10 +7,12 @@ use crate::cache::types::TotalShards;
 /// `shards` define the total number of shards to be used inside `TTLTicker`
 /// `tick_duration` defines the interval at which `TTLTicker` should run
 /// `clock` defines an implementation of [`crate::cache::clock::Clock`] to be used to get the current time
+/// `max_reaps_per_tick` defines the maximum number of expired keys that `TTLTicker` removes in a single run, unbounded by default
 pub(crate) struct TTLConfig {
     shards: TotalShards,
     tick_duration: Duration,
     clock: ClockType,
+    max_reaps_per_tick: usize,
 }
 
 impl TTLConfig {
@@ -19,14 +21,22 @@ impl TTLConfig {
             shards,
             tick_duration,
             clock,
+            max_reaps_per_tick: usize::MAX,
         }
     }
 
+    pub(crate) fn with_max_reaps_per_tick(mut self, max_reaps_per_tick: usize) -> Self {
+        self.max_reaps_per_tick = max_reaps_per_tick;
+        self
+    }
+
     pub(crate) fn shards(&self) -> TotalShards { self.shards }
 
     pub(crate) fn tick_duration(&self) -> Duration { self.tick_duration }
 
     pub(crate) fn clock(&self) -> ClockType { self.clock.clone_box() }
+
+    pub(crate) fn max_reaps_per_tick(&self) -> usize { self.max_reaps_per_tick }
 }
 
 #[cfg(test)]
@@ -46,4 +56,16 @@ mod tests {
         let ttl_config = TTLConfig::new(4, Duration::from_millis(20), SystemClock::boxed());
         assert_eq!(Duration::from_millis(20), ttl_config.tick_duration());
     }
+
+    #[test]
+    fn ttl_max_reaps_per_tick_is_unbounded_by_default() {
+        let ttl_config = TTLConfig::new(4, Duration::from_millis(20), SystemClock::boxed());
+        assert_eq!(usize::MAX, ttl_config.max_reaps_per_tick());
+    }
+
+    #[test]
+    fn ttl_max_reaps_per_tick() {
+        let ttl_config = TTLConfig::new(4, Duration::from_millis(20), SystemClock::boxed()).with_max_reaps_per_tick(100);
+        assert_eq!(100, ttl_config.max_reaps_per_tick());
+    }
 }
\ No newline at end of file
@@ -1,12 +1,13 @@
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
+use std::thread::JoinHandle;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crossbeam_channel::tick;
 use hashbrown::HashMap;
-use log::{debug, info};
-use parking_lot::RwLock;
+use log::{debug, error, info};
+use parking_lot::{Mutex, RwLock};
 
 use crate::cache::clock::ClockType;
 use crate::cache::expiration::config::TTLConfig;
@@ -17,25 +18,54 @@ pub(crate) mod config;
 /// `TTLTicker` removes the expired keys.
 /// `TTLTicker` is a shared lock based HashMap. Each shard holds a [`parking_lot::RwLock`] protected [`hashbrown::HashMap`]
 /// During the event of cache shutdown, keep_running is set to false.
+/// The thread of `TTLTicker` stops on its next tick after the shutdown, and sets `has_stopped` to true.
+/// `handle` holds the `JoinHandle` of the thread till it is joined, read `join`.
 pub(crate) struct TTLTicker {
     shards: Arc<[RwLock<HashMap<KeyId, ExpireAfter>>]>,
     keep_running: Arc<AtomicBool>,
+    has_stopped: Arc<AtomicBool>,
+    handle: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl TTLTicker {
+    #[cfg(test)]
     pub(crate) fn new<EvictHook>(config: TTLConfig, evict_hook: EvictHook) -> Arc<TTLTicker>
         where EvictHook: Fn(&KeyId) + Send + Sync + 'static {
+        Self::with_reaped_hook(config, evict_hook, || {})
+    }
+
+    /// Creates a `TTLTicker` that invokes the `reaped_hook` after every run, once the expired keys of the run are passed to the `evict_hook`.
+    pub(crate) fn with_reaped_hook<EvictHook, ReapedHook>(config: TTLConfig, evict_hook: EvictHook, reaped_hook: ReapedHook) -> Arc<TTLTicker>
+        where EvictHook: Fn(&KeyId) + Send + Sync + 'static,
+              ReapedHook: Fn() + Send + Sync + 'static {
         let ticker = Arc::new(
             TTLTicker {
                 shards: (0..config.shards())
                     .map(|_| RwLock::new(HashMap::new())).collect(),
                 keep_running: Arc::new(AtomicBool::new(true)),
+                has_stopped: Arc::new(AtomicBool::new(false)),
+                handle: Mutex::new(None),
             }
         );
-        ticker.clone().spin(config.tick_duration(), config.clock(), evict_hook);
+        let handle = ticker.clone().spin(config.tick_duration(), config.clock(), config.max_reaps_per_tick(), evict_hook, reaped_hook);
+        *ticker.handle.lock() = Some(handle);
         ticker
     }
 
+    /// Creates a `TTLTicker` that never spawns its thread, for a cache built without the support for time to live.
+    /// The idle `TTLTicker` has stopped from the beginning, so the shutdown does not wait for it.
+    pub(crate) fn idle(config: TTLConfig) -> Arc<TTLTicker> {
+        Arc::new(
+            TTLTicker {
+                shards: (0..config.shards())
+                    .map(|_| RwLock::new(HashMap::new())).collect(),
+                keep_running: Arc::new(AtomicBool::new(false)),
+                has_stopped: Arc::new(AtomicBool::new(true)),
+                handle: Mutex::new(None),
+            }
+        )
+    }
+
     pub(crate) fn put(self: &Arc<TTLTicker>, key_id: KeyId, expire_after: ExpireAfter) {
         let shard_index = self.shard_index(&expire_after);
         self.shards[shard_index].write().insert(key_id, expire_after);
@@ -57,6 +87,7 @@ impl TTLTicker {
         self.shards[shard_index].write().remove(key_id);
     }
 
+    #[cfg(test)]
     pub(crate) fn get(self: &Arc<TTLTicker>, key_id: &KeyId, expire_after: &ExpireAfter) -> Option<ExpireAfter> {
         let shard_index = self.shard_index(expire_after);
         self.shards[shard_index].read().get(key_id).copied()
@@ -72,6 +103,22 @@ impl TTLTicker {
         self.keep_running.store(false, Ordering::Release);
     }
 
+    /// Returns true if the thread of `TTLTicker` has stopped after the shutdown.
+    pub(crate) fn has_stopped(&self) -> bool {
+        self.has_stopped.load(Ordering::Acquire)
+    }
+
+    /// Waits till the thread of `TTLTicker` exits, which happens on its next tick after the shutdown.
+    /// `join` is expected to be invoked after `shutdown`, and returns immediately if the thread is already joined (or was never spawned).
+    pub(crate) fn join(&self) {
+        let handle = self.handle.lock().take();
+        if let Some(handle) = handle {
+            if handle.join().is_err() {
+                error!("the thread of TTLTicker panicked");
+            }
+        }
+    }
+
     /// Determines the shard to pick for put, update, delete and get operations based on the time.
     /// Detailed explanation is available in the `spin` method.
     fn shard_index(self: &Arc<TTLTicker>, time: &SystemTime) -> usize {
@@ -87,32 +134,91 @@ impl TTLTicker {
     /// In order to minimize contention, a single shard is picked based on the current_time.
     /// The current_time `(clock.now())` is converted to a duration `time.duration_since(UNIX_EPOCH)` and
     /// its modulo operation with total number of shards gives the shard_index.
-    fn spin<EvictHook>(self: Arc<TTLTicker>, tick_duration: Duration, clock: ClockType, evict_hook: EvictHook)
-        where EvictHook: Fn(&KeyId) + Send + Sync + 'static {
+    ///
+    /// At most `max_reaps_per_tick` expired keys are removed in a single run. The shards which still hold expired keys
+    /// are remembered and are picked again (before the shard for the current time) in the subsequent runs.
+    /// The `reaped_hook` is invoked after every run. Returns the `JoinHandle` of the thread.
+    fn spin<EvictHook, ReapedHook>(self: Arc<TTLTicker>,
+                                   tick_duration: Duration,
+                                   clock: ClockType,
+                                   max_reaps_per_tick: usize,
+                                   evict_hook: EvictHook,
+                                   reaped_hook: ReapedHook) -> JoinHandle<()>
+        where EvictHook: Fn(&KeyId) + Send + Sync + 'static,
+              ReapedHook: Fn() + Send + Sync + 'static {
         let keep_running = self.keep_running.clone();
+        let has_stopped = self.has_stopped.clone();
         let receiver = tick(tick_duration);
 
         thread::spawn(move || {
+            let mut pending_shard_indices = Vec::new();
             while let Ok(_instant) = receiver.recv() {
                 let now = clock.now();
-                let shard_index = self.shard_index(&now);
-
-                self.shards[shard_index].write().retain(|key, expire_after| {
-                    let has_not_expired = now.le(expire_after);
-                    if !has_not_expired {
-                        debug!("Key with id {} has expired", key);
-                        (evict_hook)(key);
-                    }
-                    has_not_expired
-                });
+                pending_shard_indices = self.reap(&now, max_reaps_per_tick, pending_shard_indices, &evict_hook);
+                (reaped_hook)();
 
                 if !keep_running.load(Ordering::Acquire) {
                     info!("Shutting down TTLTicker");
                     drop(receiver);
+                    has_stopped.store(true, Ordering::Release);
                     break;
                 }
             }
-        });
+        })
+    }
+
+    /// Removes at most `max_reaps` expired keys from the `pending_shard_indices` followed by the shard for the time `now`.
+    /// Returns the indices of the shards which still hold expired keys.
+    fn reap<EvictHook>(self: &Arc<TTLTicker>, now: &SystemTime, max_reaps: usize, pending_shard_indices: Vec<usize>, evict_hook: &EvictHook) -> Vec<usize>
+        where EvictHook: Fn(&KeyId) {
+        let mut shard_indices = pending_shard_indices;
+        let shard_index = self.shard_index(now);
+        if !shard_indices.contains(&shard_index) {
+            shard_indices.push(shard_index);
+        }
+
+        let mut reaps_left = max_reaps;
+        shard_indices.into_iter()
+            .filter(|shard_index| self.reap_shard(*shard_index, now, &mut reaps_left, evict_hook))
+            .collect()
+    }
+
+    /// Removes the expired keys from the shard at `shard_index` till `reaps_left` becomes zero.
+    /// Returns true if the shard still holds expired keys.
+    ///
+    /// At most `reaps_left` (+1, to know if the shard still holds expired keys) expired keys are collected under the read lock,
+    /// and only the collected keys are removed under the write lock, so a shard with a lot of keys is not locked for writes while it is scanned.
+    /// A collected key whose expiry is changed before the write lock is acquired is not removed.
+    /// `evict_hook` is invoked for the removed keys after the write lock is released, so that the eviction listener may use the cache.
+    fn reap_shard<EvictHook>(&self, shard_index: usize, now: &SystemTime, reaps_left: &mut usize, evict_hook: &EvictHook) -> bool
+        where EvictHook: Fn(&KeyId) {
+        let expired_keys: Vec<KeyId> = self.shards[shard_index].read().iter()
+            .filter(|(_key, expire_after)| now.gt(expire_after))
+            .map(|(key, _expire_after)| *key)
+            .take(reaps_left.saturating_add(1))
+            .collect();
+
+        let has_expired_keys = expired_keys.len() > *reaps_left;
+        if *reaps_left == 0 || expired_keys.is_empty() {
+            return has_expired_keys;
+        }
+
+        let mut removed_keys = Vec::new();
+        {
+            let mut shard = self.shards[shard_index].write();
+            for key in expired_keys.into_iter().take(*reaps_left) {
+                if shard.get(&key).is_some_and(|expire_after| now.gt(expire_after)) {
+                    shard.remove(&key);
+                    removed_keys.push(key);
+                }
+            }
+        }
+        for key in removed_keys.iter() {
+            debug!("Key with id {} has expired", key);
+            (evict_hook)(key);
+            *reaps_left -= 1;
+        }
+        has_expired_keys
     }
 }
 
@@ -120,34 +226,23 @@ impl TTLTicker {
 mod tests {
     use std::ops::Add;
     use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration, SystemTime};
 
     use parking_lot::lock_api::Mutex;
 
-    use crate::cache::clock::{Clock, SystemClock};
+    use crate::cache::clock::{Clock, ManualClock, SystemClock};
     use crate::cache::expiration::config::TTLConfig;
-    use crate::cache::expiration::tests::setup::{EvictedKeys, UnixEpochClock};
+    use crate::cache::expiration::tests::setup::EvictedKeys;
     use crate::cache::expiration::TTLTicker;
     use crate::cache::types::KeyId;
 
     mod setup {
-        use std::time::SystemTime;
-
         use parking_lot::Mutex;
 
-        use crate::cache::clock::Clock;
         use crate::cache::types::KeyId;
 
-        #[derive(Clone)]
-        pub(crate) struct UnixEpochClock;
-
-        impl Clock for UnixEpochClock {
-            fn now(&self) -> SystemTime {
-                SystemTime::UNIX_EPOCH
-            }
-        }
-
         pub(crate) struct EvictedKeys {
             pub(crate) keys: Mutex<Vec<KeyId>>,
         }
@@ -155,7 +250,7 @@ mod tests {
 
     #[test]
     fn shard_index_0() {
-        let clock = Box::new(UnixEpochClock {});
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let no_operation_evict_hook = |_key: &KeyId| {};
         let ticker = TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), clock.clone()), no_operation_evict_hook);
 
@@ -165,7 +260,7 @@ mod tests {
 
     #[test]
     fn shard_index_1() {
-        let clock = Box::new(UnixEpochClock {});
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let no_operation_evict_hook = |_key: &KeyId| {};
         let ticker = TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), clock.clone()), no_operation_evict_hook);
 
@@ -176,7 +271,7 @@ mod tests {
 
     #[test]
     fn put() {
-        let clock = Box::new(UnixEpochClock {});
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let no_operation_evict_hook = |_key: &KeyId| {};
         let ticker = TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), clock.clone()), no_operation_evict_hook);
 
@@ -190,7 +285,7 @@ mod tests {
 
     #[test]
     fn update() {
-        let clock = Box::new(UnixEpochClock {});
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let no_operation_evict_hook = |_key: &KeyId| {};
         let ticker = TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), clock.clone()), no_operation_evict_hook);
 
@@ -207,7 +302,7 @@ mod tests {
 
     #[test]
     fn delete() {
-        let clock = Box::new(UnixEpochClock {});
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let no_operation_evict_hook = |_key: &KeyId| {};
         let ticker = TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), clock.clone()), no_operation_evict_hook);
 
@@ -240,6 +335,32 @@ mod tests {
         assert_eq!(vec![10], *readonly_evicted_keys.keys.lock());
     }
 
+    #[test]
+    fn invoke_the_reaped_hook_after_the_expired_keys_are_evicted() {
+        let evicted_keys = Arc::new(EvictedKeys { keys: Mutex::new(Vec::new()) });
+        let evicted_keys_on_first_reap = Arc::new(AtomicUsize::new(0));
+        let readonly_evicted_keys_on_first_reap = evicted_keys_on_first_reap.clone();
+
+        let clock = SystemClock::boxed();
+        let evict_hook = {
+            let evicted_keys = evicted_keys.clone();
+            move |key_id: &KeyId| { evicted_keys.keys.lock().push(*key_id) }
+        };
+        let reaped_hook = move || {
+            let keys = evicted_keys.keys.lock().len();
+            let _ = evicted_keys_on_first_reap.compare_exchange(0, keys, Ordering::AcqRel, Ordering::Acquire);
+        };
+        let ticker = TTLTicker::with_reaped_hook(TTLConfig::new(1, Duration::from_millis(5), clock.clone_box()), evict_hook, reaped_hook);
+
+        let expire_after = clock.now();
+        ticker.put(40, expire_after);
+        ticker.put(50, expire_after);
+
+        thread::sleep(Duration::from_secs(1));
+
+        assert_eq!(2, readonly_evicted_keys_on_first_reap.load(Ordering::Acquire));
+    }
+
     #[test]
     fn delete_an_expired_key_amongst_multiple_keys() {
         let evicted_keys = Arc::new(EvictedKeys { keys: Mutex::new(Vec::new()) });
@@ -264,6 +385,138 @@ mod tests {
         assert_eq!(vec![40], *readonly_evicted_keys.keys.lock());
     }
 
+    #[test]
+    fn reap_at_most_max_reaps_per_tick() {
+        let evicted_keys = Arc::new(EvictedKeys { keys: Mutex::new(Vec::new()) });
+        let readonly_evicted_keys = evicted_keys.clone();
+
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let evict_hook = move |key_id: &KeyId| { evicted_keys.keys.lock().push(*key_id) };
+        let ticker = TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), clock.clone()), |_key: &KeyId| {});
+
+        let expire_after = clock.now();
+        (0..1000).for_each(|key_id| ticker.put(key_id, expire_after));
+
+        let now = clock.now().add(Duration::from_secs(4));
+        let mut pending_shard_indices = Vec::new();
+        for run in 1..=10 {
+            pending_shard_indices = ticker.reap(&now, 100, pending_shard_indices, &evict_hook);
+            assert_eq!(run * 100, readonly_evicted_keys.keys.lock().len());
+        }
+        assert!(pending_shard_indices.is_empty());
+        assert!(ticker.get(&0, &expire_after).is_none());
+    }
+
+    #[test]
+    fn reap_shard_removes_only_the_expired_keys_within_reaps_left() {
+        let evicted_keys = Arc::new(EvictedKeys { keys: Mutex::new(Vec::new()) });
+        let readonly_evicted_keys = evicted_keys.clone();
+
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let evict_hook = move |key_id: &KeyId| { evicted_keys.keys.lock().push(*key_id) };
+        let ticker = TTLTicker::new(TTLConfig::new(1, Duration::from_secs(300), clock.clone()), |_key: &KeyId| {});
+
+        let expire_after = clock.now();
+        let later_expire_after = clock.now().add(Duration::from_secs(10));
+        (0..3).for_each(|key_id| ticker.put(key_id, expire_after));
+        ticker.put(10, later_expire_after);
+
+        let now = clock.now().add(Duration::from_secs(4));
+        let mut reaps_left = 0;
+        assert!(ticker.reap_shard(0, &now, &mut reaps_left, &evict_hook));
+        assert!(readonly_evicted_keys.keys.lock().is_empty());
+
+        let mut reaps_left = 2;
+        assert!(ticker.reap_shard(0, &now, &mut reaps_left, &evict_hook));
+        assert_eq!(0, reaps_left);
+        assert_eq!(2, readonly_evicted_keys.keys.lock().len());
+
+        let mut reaps_left = 2;
+        assert!(!ticker.reap_shard(0, &now, &mut reaps_left, &evict_hook));
+        assert_eq!(1, reaps_left);
+        assert_eq!(3, readonly_evicted_keys.keys.lock().len());
+        assert!(ticker.get(&10, &later_expire_after).is_some());
+    }
+
+    #[test]
+    fn reap_pending_shard_before_the_current_shard() {
+        let evicted_keys = Arc::new(EvictedKeys { keys: Mutex::new(Vec::new()) });
+        let readonly_evicted_keys = evicted_keys.clone();
+
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let evict_hook = move |key_id: &KeyId| { evicted_keys.keys.lock().push(*key_id) };
+        let ticker = TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), clock.clone()), |_key: &KeyId| {});
+
+        let expire_after = clock.now();
+        ticker.put(10, expire_after);
+        ticker.put(20, expire_after);
+
+        let now = clock.now().add(Duration::from_secs(4));
+        let pending_shard_indices = ticker.reap(&now, 1, Vec::new(), &evict_hook);
+        assert_eq!(vec![0], pending_shard_indices);
+
+        let later = clock.now().add(Duration::from_secs(5));
+        let pending_shard_indices = ticker.reap(&later, 1, pending_shard_indices, &evict_hook);
+        assert!(pending_shard_indices.is_empty());
+        assert_eq!(2, readonly_evicted_keys.keys.lock().len());
+    }
+
+    #[test]
+    fn delete_mass_expired_keys_over_multiple_ticks() {
+        let evicted_keys = Arc::new(EvictedKeys { keys: Mutex::new(Vec::new()) });
+        let readonly_evicted_keys = evicted_keys.clone();
+
+        let clock = SystemClock::boxed();
+        let evict_hook = move |key_id: &KeyId| { evicted_keys.keys.lock().push(*key_id) };
+        let ticker = TTLTicker::new(
+            TTLConfig::new(1, Duration::from_millis(5), clock.clone_box()).with_max_reaps_per_tick(100),
+            evict_hook,
+        );
+
+        let expire_after = clock.now();
+        (0..1000).for_each(|key_id| ticker.put(key_id, expire_after));
+
+        thread::sleep(Duration::from_secs(1));
+
+        assert_eq!(1000, readonly_evicted_keys.keys.lock().len());
+        assert!(ticker.get(&999, &expire_after).is_none());
+    }
+
+    #[test]
+    fn delete_a_key_once_a_manual_clock_advances_past_its_expiry() {
+        let evicted_keys = Arc::new(EvictedKeys { keys: Mutex::new(Vec::new()) });
+        let readonly_evicted_keys = evicted_keys.clone();
+
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let evict_hook = move |key_id: &KeyId| { evicted_keys.keys.lock().push(*key_id) };
+        let ticker = TTLTicker::new(TTLConfig::new(1, Duration::from_millis(5), clock.boxed()), evict_hook);
+
+        let expire_after = clock.now().add(Duration::from_secs(5));
+        ticker.put(10, expire_after);
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(ticker.get(&10, &expire_after).is_some());
+
+        clock.advance(Duration::from_secs(6));
+        thread::sleep(Duration::from_millis(100));
+
+        assert!(ticker.get(&10, &expire_after).is_none());
+        assert_eq!(vec![10], *readonly_evicted_keys.keys.lock());
+    }
+
+    #[test]
+    fn join_after_shutdown() {
+        let clock = SystemClock::boxed();
+        let no_operation_evict_hook = |_key: &KeyId| {};
+        let ticker = TTLTicker::new(TTLConfig::new(1, Duration::from_millis(5), clock.clone_box()), no_operation_evict_hook);
+
+        ticker.shutdown();
+        ticker.join();
+
+        assert!(ticker.has_stopped());
+        assert_eq!(1, Arc::strong_count(&ticker));
+    }
+
     #[test]
     fn shutdown() {
         let clock = SystemClock::boxed();
@@ -272,9 +525,11 @@ mod tests {
 
         let expire_after = clock.now();
         ticker.put(10, expire_after);
+        assert!(!ticker.has_stopped());
         ticker.shutdown();
 
         thread::sleep(Duration::from_secs(1));
+        assert!(ticker.has_stopped());
 
         let stored_value = ticker.get(&10, &expire_after);
         assert!(stored_value.is_none());
@@ -287,9 +542,24 @@ mod tests {
         assert!(stored_value.is_some());
     }
 
+    #[test]
+    fn idle_ticker_does_not_remove_expired_keys() {
+        let clock = SystemClock::boxed();
+        let ticker = TTLTicker::idle(TTLConfig::new(1, Duration::from_millis(5), clock.clone_box()));
+        assert!(ticker.has_stopped());
+
+        let expire_after = clock.now();
+        ticker.put(10, expire_after);
+        thread::sleep(Duration::from_millis(50));
+
+        assert!(ticker.get(&10, &expire_after).is_some());
+        ticker.shutdown();
+        assert!(ticker.has_stopped());
+    }
+
     #[test]
     fn clear() {
-        let clock = Box::new(UnixEpochClock {});
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let no_operation_evict_hook = |_key: &KeyId| {};
         let ticker = TTLTicker::new(TTLConfig::new(4, Duration::from_secs(300), clock.clone()), no_operation_evict_hook);
 
@@ -0,0 +1,248 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{ErrorKind, Read, Write};
+use std::time::Duration;
+#[cfg(feature = "persistence")]
+use std::time::SystemTime;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+#[cfg(feature = "persistence")]
+use crate::cache::types::Weight;
+
+const LENGTH_BYTES: usize = 8;
+
+#[cfg(feature = "persistence")]
+const PERSISTENCE_MAGIC: [u8; 4] = *b"TLFC";
+#[cfg(feature = "persistence")]
+pub(crate) const PERSISTENCE_VERSION: u32 = 1;
+
+/// ExportedEntry is a key/value pair of `crate::cache::cached::CacheD` along with its remaining `time_to_live`, if any.
+#[derive(Debug)]
+pub(crate) struct ExportedEntry<Key, Value> {
+    pub(crate) key: Key,
+    pub(crate) value: Value,
+    pub(crate) time_to_live: Option<Duration>,
+}
+
+/// PersistedEntry is a key/value pair of `crate::cache::cached::CacheD` along with its remaining `time_to_live` (at the time of save), if any, and its weight.
+#[cfg(feature = "persistence")]
+#[derive(Debug)]
+pub(crate) struct PersistedEntry<Key, Value> {
+    pub(crate) key: Key,
+    pub(crate) value: Value,
+    pub(crate) time_to_live: Option<Duration>,
+    pub(crate) weight: Weight,
+}
+
+/// Writes the header of a saved cache: the magic bytes, followed by the `PERSISTENCE_VERSION` (as a little-endian u32),
+/// followed by the milliseconds elapsed from UNIX_EPOCH till `saved_at` (as a little-endian u64).
+#[cfg(feature = "persistence")]
+pub(crate) fn write_header<W: Write>(writer: &mut W, saved_at: SystemTime) -> Result<(), ExportError> {
+    let saved_at_millis = saved_at.duration_since(SystemTime::UNIX_EPOCH).unwrap_or(Duration::ZERO).as_millis() as u64;
+    writer.write_all(&PERSISTENCE_MAGIC).map_err(ExportError::Io)?;
+    writer.write_all(&PERSISTENCE_VERSION.to_le_bytes()).map_err(ExportError::Io)?;
+    writer.write_all(&saved_at_millis.to_le_bytes()).map_err(ExportError::Io)
+}
+
+/// Reads the header written by `write_header` and returns the time the cache was saved at.
+/// Returns `ExportError::InvalidHeader` if the magic bytes do not match, and `ExportError::UnsupportedVersion` if the version is not `PERSISTENCE_VERSION`.
+#[cfg(feature = "persistence")]
+pub(crate) fn read_header<R: Read>(reader: &mut R) -> Result<SystemTime, ExportError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic).map_err(ExportError::Io)?;
+    if magic != PERSISTENCE_MAGIC {
+        return Err(ExportError::InvalidHeader);
+    }
+    let mut version = [0; 4];
+    reader.read_exact(&mut version).map_err(ExportError::Io)?;
+    let version = u32::from_le_bytes(version);
+    if version != PERSISTENCE_VERSION {
+        return Err(ExportError::UnsupportedVersion(version));
+    }
+    let mut saved_at_millis = [0; LENGTH_BYTES];
+    reader.read_exact(&mut saved_at_millis).map_err(ExportError::Io)?;
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_millis(u64::from_le_bytes(saved_at_millis)))
+}
+
+/// Writes an entry as a frame: the length of the serialized entry (as a little-endian u64), followed by the serialized entry.
+/// The entry is serialized using `bincode`, as a tuple of the key, the value and the remaining `time_to_live`.
+pub(crate) fn write_entry<Key, Value, W>(writer: &mut W, key: &Key, value: &Value, time_to_live: Option<Duration>) -> Result<(), ExportError>
+    where Key: Serialize,
+          Value: Serialize,
+          W: Write {
+    write_frame(writer, &(key, value, time_to_live))
+}
+
+/// Writes a persisted entry as a frame, like `write_entry`, serializing a tuple of the key, the value, the remaining `time_to_live` and the weight.
+#[cfg(feature = "persistence")]
+pub(crate) fn write_persisted_entry<Key, Value, W>(writer: &mut W, key: &Key, value: &Value, time_to_live: Option<Duration>, weight: Weight) -> Result<(), ExportError>
+    where Key: Serialize,
+          Value: Serialize,
+          W: Write {
+    write_frame(writer, &(key, value, time_to_live, weight))
+}
+
+fn write_frame<T: Serialize, W: Write>(writer: &mut W, frame: &T) -> Result<(), ExportError> {
+    let bytes = bincode::serialize(frame).map_err(ExportError::Encoding)?;
+    writer.write_all(&(bytes.len() as u64).to_le_bytes()).map_err(ExportError::Io)?;
+    writer.write_all(&bytes).map_err(ExportError::Io)
+}
+
+/// Reads the next frame written by `write_entry`, returns `None` if the reader ends at the boundary of a frame.
+pub(crate) fn read_entry<Key, Value, R>(reader: &mut R) -> Result<Option<ExportedEntry<Key, Value>>, ExportError>
+    where Key: DeserializeOwned,
+          Value: DeserializeOwned,
+          R: Read {
+    Ok(read_frame(reader)?.map(|(key, value, time_to_live)| ExportedEntry { key, value, time_to_live }))
+}
+
+/// Reads the next frame written by `write_persisted_entry`, returns `None` if the reader ends at the boundary of a frame.
+#[cfg(feature = "persistence")]
+pub(crate) fn read_persisted_entry<Key, Value, R>(reader: &mut R) -> Result<Option<PersistedEntry<Key, Value>>, ExportError>
+    where Key: DeserializeOwned,
+          Value: DeserializeOwned,
+          R: Read {
+    Ok(read_frame(reader)?.map(|(key, value, time_to_live, weight)| PersistedEntry { key, value, time_to_live, weight }))
+}
+
+fn read_frame<T: DeserializeOwned, R: Read>(reader: &mut R) -> Result<Option<T>, ExportError> {
+    let mut length = [0; LENGTH_BYTES];
+    if !read_length(reader, &mut length)? {
+        return Ok(None);
+    }
+    let mut bytes = vec![0; u64::from_le_bytes(length) as usize];
+    reader.read_exact(&mut bytes).map_err(ExportError::Io)?;
+    bincode::deserialize(&bytes).map(Some).map_err(ExportError::Encoding)
+}
+
+/// Fills `length` from the reader, returns false if the reader has ended before the first byte of `length`.
+fn read_length<R: Read>(reader: &mut R, length: &mut [u8; LENGTH_BYTES]) -> Result<bool, ExportError> {
+    let mut filled = 0;
+    while filled < LENGTH_BYTES {
+        match reader.read(&mut length[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(ExportError::Io(ErrorKind::UnexpectedEof.into())),
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(ExportError::Io(error)),
+        }
+    }
+    Ok(true)
+}
+
+/// `ExportError` is returned by [`crate::cache::cached::CacheD::export_to`] and [`crate::cache::cached::CacheD::import_from`],
+/// and by `save_to_writer` and `load_from_reader` of `crate::cache::cached::CacheD` (behind the `persistence` feature).
+///
+/// `Io`:                 the entries could not be written to the writer or read from the reader, including a reader that ends in the middle of an entry.
+///
+/// `Encoding`:           an entry could not be serialized, or the bytes read do not hold a valid entry.
+///
+/// `InvalidHeader`:      the reader does not begin with the header of a saved cache.
+///
+/// `UnsupportedVersion`: the saved cache has a version of the format that can not be loaded.
+#[derive(Debug)]
+pub enum ExportError {
+    Io(std::io::Error),
+    Encoding(bincode::Error),
+    InvalidHeader,
+    UnsupportedVersion(u32),
+}
+
+/// Display implementation for `ExportError`.
+impl Display for ExportError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Io(error) => write!(formatter, "could not transfer the entries, {}", error),
+            ExportError::Encoding(error) => write!(formatter, "could not encode the entries, {}", error),
+            ExportError::InvalidHeader => write!(formatter, "could not find the header of a saved cache"),
+            ExportError::UnsupportedVersion(version) => write!(formatter, "could not load the saved cache of unsupported version {}", version),
+        }
+    }
+}
+
+/// Error implementation for `ExportError`.
+impl Error for ExportError {}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    use crate::cache::export::{ExportedEntry, ExportError, read_entry, write_entry};
+
+    #[test]
+    fn write_and_read_entries() {
+        let mut bytes = Vec::new();
+        write_entry(&mut bytes, &"topic", &"microservices", Some(Duration::from_secs(5))).unwrap();
+        write_entry(&mut bytes, &"disk", &"SSD", None).unwrap();
+
+        let mut reader = Cursor::new(bytes);
+        let entry: ExportedEntry<String, String> = read_entry(&mut reader).unwrap().unwrap();
+        assert_eq!(("topic", "microservices", Some(Duration::from_secs(5))), (entry.key.as_str(), entry.value.as_str(), entry.time_to_live));
+
+        let entry: ExportedEntry<String, String> = read_entry(&mut reader).unwrap().unwrap();
+        assert_eq!(("disk", "SSD", None), (entry.key.as_str(), entry.value.as_str(), entry.time_to_live));
+
+        assert!(read_entry::<String, String, _>(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn does_not_read_a_truncated_entry() {
+        let mut bytes = Vec::new();
+        write_entry(&mut bytes, &"topic", &"microservices", None).unwrap();
+        bytes.pop();
+
+        let error = read_entry::<String, String, _>(&mut Cursor::new(bytes)).unwrap_err();
+        assert!(matches!(error, ExportError::Io(_)));
+    }
+
+    #[test]
+    fn does_not_read_a_truncated_length() {
+        let error = read_entry::<String, String, _>(&mut Cursor::new(vec![1, 0, 0])).unwrap_err();
+        assert!(matches!(error, ExportError::Io(_)));
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn write_and_read_a_header_and_persisted_entries() {
+        use std::time::SystemTime;
+
+        use crate::cache::export::{PersistedEntry, read_header, read_persisted_entry, write_header, write_persisted_entry};
+
+        let saved_at = SystemTime::UNIX_EPOCH + Duration::from_millis(1_500);
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, saved_at).unwrap();
+        write_persisted_entry(&mut bytes, &"topic", &"microservices", Some(Duration::from_secs(5)), 40).unwrap();
+
+        let mut reader = Cursor::new(bytes);
+        assert_eq!(saved_at, read_header(&mut reader).unwrap());
+
+        let entry: PersistedEntry<String, String> = read_persisted_entry(&mut reader).unwrap().unwrap();
+        assert_eq!(("topic", "microservices", Some(Duration::from_secs(5)), 40), (entry.key.as_str(), entry.value.as_str(), entry.time_to_live, entry.weight));
+        assert!(read_persisted_entry::<String, String, _>(&mut reader).unwrap().is_none());
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn does_not_read_a_header_of_an_unsupported_version() {
+        use std::time::SystemTime;
+
+        use crate::cache::export::{PERSISTENCE_VERSION, read_header, write_header};
+
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, SystemTime::UNIX_EPOCH).unwrap();
+        bytes[4..8].copy_from_slice(&(PERSISTENCE_VERSION + 1).to_le_bytes());
+
+        let error = read_header(&mut Cursor::new(bytes)).unwrap_err();
+        assert!(matches!(error, ExportError::UnsupportedVersion(version) if version == PERSISTENCE_VERSION + 1));
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn does_not_read_an_invalid_header() {
+        let error = crate::cache::export::read_header(&mut Cursor::new(vec![0; 16])).unwrap_err();
+        assert!(matches!(error, ExportError::InvalidHeader));
+    }
+}
@@ -1,11 +1,16 @@
 use log::{debug, info};
 use rand::Rng;
+
+#[cfg(test)]
+use crate::cache::sketch::SKETCH_ROWS;
+use crate::cache::sketch::SketchState;
 use crate::cache::types::{FrequencyEstimate, KeyHash, TotalCounters};
 
 const BINARY_ONE: u64 = 0x01;
 const MAX_VALUE_LOWER_FOUR_BITS: u8 = 0x0f;
 const HALF_COUNTERS_BITS: u8 = 0x77;
 const SHIFT_OFFSET: u64 = 4;
+const INDEX_MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
 
 #[repr(transparent)]
 #[derive(Debug, PartialEq)]
@@ -53,44 +58,72 @@ impl Row {
     }
 }
 
-const ROWS: usize = 4;
-
 /// FrequencyCounter is an implementation of count-min sketch based on 4 bit counter taken from
 /// https://github.com/dgryski/go-tinylfu/blob/master/cm4.go
 /// More on 4 bit counter is available [here](https://tech-lessons.in/blog/count_min_sketch/#4-bit-counter)
 /// Count-min sketch (CM sketch) is a probabilistic data structure1 used to estimate the frequency of events in a data stream.
 /// It relies on hash functions to map events to frequencies, but unlike a hash table, it uses only sublinear space at the expense of over-counting some events due to hash collisions.
+///
+/// The sketch has `depth` rows (`SKETCH_ROWS` by default), each with its own seed, and the estimate is the minimum of the counters of a key across the rows.
+/// The position of a key in a row is derived by mixing the key_hash with the seed of the row, so that two keys colliding in one row
+/// are unlikely to collide in the others. A deeper sketch over-counts less, at the cost of `counters / 2` bytes for each additional row.
 pub(crate) struct FrequencyCounter {
-    matrix: [Row; ROWS],
-    seeds: [u64; ROWS],
+    matrix: Vec<Row>,
+    seeds: Vec<u64>,
     total_counters: TotalCounters,
 }
 
 impl FrequencyCounter {
+    #[cfg(test)]
     pub(crate) fn new(counters: TotalCounters) -> FrequencyCounter {
+        Self::with_depth(counters, SKETCH_ROWS)
+    }
+
+    pub(crate) fn with_depth(counters: TotalCounters, depth: usize) -> FrequencyCounter {
         let total_counters = Self::next_power_2(counters);
-        info!("Initializing FrequencyCounter with total counters {}", counters);
+        info!("Initializing FrequencyCounter with total counters {} and depth {}", counters, depth);
+        FrequencyCounter {
+            matrix: Self::matrix(total_counters, depth),
+            seeds: Self::seeds(depth),
+            total_counters,
+        }
+    }
+
+    /// Creates a FrequencyCounter with the counters and the seeds of an exported sketch.
+    pub(crate) fn from_sketch(sketch: SketchState) -> FrequencyCounter {
+        let total_counters = sketch.total_counters();
+        let (seeds, rows) = sketch.into_parts();
+        info!("Initializing FrequencyCounter from a sketch with total counters {}", total_counters);
         FrequencyCounter {
-            matrix: Self::matrix(total_counters),
-            seeds: Self::seeds(),
+            matrix: rows.into_iter().map(Row).collect(),
+            seeds,
             total_counters,
         }
     }
 
+    /// Returns the total counters in each row of a FrequencyCounter created with `counters`.
+    pub(crate) fn total_counters_for(counters: TotalCounters) -> TotalCounters {
+        Self::next_power_2(counters)
+    }
+
+    /// Exports the counters along with the seeds of the rows.
+    pub(crate) fn export(&self) -> SketchState {
+        SketchState::new(self.total_counters, self.seeds.clone(), self.matrix.iter().map(|row| row.0.clone()).collect())
+    }
+
     pub(crate) fn increment(&mut self, key_hash: KeyHash) {
-        (0..ROWS).for_each(|index| {
-            let hash = key_hash ^ self.seeds[index];
+        (0..self.matrix.len()).for_each(|index| {
+            let position = self.position_of(key_hash, index);
             let current_row = &mut self.matrix[index];
-            current_row.increment_at(hash % self.total_counters)
+            current_row.increment_at(position)
         });
     }
 
     pub(crate) fn estimate(&self, key_hash: KeyHash) -> FrequencyEstimate {
         let mut min = u8::MAX;
-        (0..ROWS).for_each(|index| {
-            let hash = key_hash ^ self.seeds[index];
+        (0..self.matrix.len()).for_each(|index| {
             let current_row = &self.matrix[index];
-            let current_min = current_row.get_at(hash % self.total_counters);
+            let current_min = current_row.get_at(self.position_of(key_hash, index));
 
             if current_min < min {
                 min = current_min;
@@ -101,17 +134,19 @@ impl FrequencyCounter {
 
     pub(crate) fn reset(&mut self) {
         debug!("Resetting the counters");
-        (0..ROWS).for_each(|index| {
-            let row = &mut self.matrix[index];
-            row.half_counters();
-        });
+        self.matrix.iter_mut().for_each(|row| row.half_counters());
     }
 
     pub(crate) fn clear(&mut self) {
-        (0..ROWS).for_each(|index| {
-            let row = &mut self.matrix[index];
-            row.clear();
-        });
+        self.matrix.iter_mut().for_each(|row| row.clear());
+    }
+
+    /// Returns the position of the key_hash in the row at `index`.
+    /// The key_hash is mixed with the seed of the row, and the higher bits of the mix are folded into the lower bits,
+    /// since the total counters is a power of 2 and only the lower bits determine the position.
+    fn position_of(&self, key_hash: KeyHash, index: usize) -> u64 {
+        let hash = (key_hash ^ self.seeds[index]).wrapping_mul(INDEX_MULTIPLIER);
+        (hash ^ (hash >> 32)) % self.total_counters
     }
 
     fn next_power_2(counters: TotalCounters) -> u64 {
@@ -129,24 +164,18 @@ impl FrequencyCounter {
         updated_counters
     }
 
-    fn seeds() -> [u64; ROWS] {
+    fn seeds(depth: usize) -> Vec<u64> {
         let mut random_number_generator = rand::thread_rng();
-        let seeds =
-            (0..ROWS)
-                .map(|_index| random_number_generator.gen::<u64>())
-                .collect::<Vec<u64>>();
-
-        seeds.try_into().unwrap()
+        (0..depth)
+            .map(|_index| random_number_generator.gen::<u64>())
+            .collect::<Vec<u64>>()
     }
 
-    fn matrix(total_counters: TotalCounters) -> [Row; ROWS] {
+    fn matrix(total_counters: TotalCounters, depth: usize) -> Vec<Row> {
         let total_counters = (total_counters / 2) as usize;
-        let rows =
-            (0..ROWS)
-                .map(|_index| Row(vec![0; total_counters]))
-                .collect::<Vec<Row>>();
-
-        rows.try_into().unwrap()
+        (0..depth)
+            .map(|_index| Row(vec![0; total_counters]))
+            .collect::<Vec<Row>>()
     }
 }
 
@@ -192,6 +221,58 @@ mod tests {
         assert_eq!(2, frequency_counter.estimate(15));
     }
 
+    #[test]
+    fn export_and_import_a_sketch() {
+        let mut frequency_counter = FrequencyCounter::new(10);
+        frequency_counter.increment(10);
+        frequency_counter.increment(10);
+        frequency_counter.increment(15);
+
+        let imported = FrequencyCounter::from_sketch(frequency_counter.export());
+
+        assert_eq!(16, imported.total_counters);
+        assert_eq!(2, imported.estimate(10));
+        assert_eq!(1, imported.estimate(15));
+    }
+
+    #[test]
+    fn depth() {
+        let frequency_counter = FrequencyCounter::with_depth(10, 8);
+        assert_eq!(8, frequency_counter.matrix.len());
+        assert_eq!(8, frequency_counter.seeds.len());
+    }
+
+    #[test]
+    fn deeper_frequency_counter_over_counts_less() {
+        let key_hashes = (0..256).collect::<Vec<u64>>();
+        let over_count_of = |mut frequency_counter: FrequencyCounter| {
+            key_hashes.iter().for_each(|key_hash| frequency_counter.increment(*key_hash));
+            key_hashes.iter().map(|key_hash| frequency_counter.estimate(*key_hash) as u64 - 1).sum::<u64>()
+        };
+
+        let shallow_over_count = over_count_of(FrequencyCounter::with_depth(64, 4));
+        let deep_over_count = over_count_of(FrequencyCounter::with_depth(64, 16));
+
+        assert!(deep_over_count < shallow_over_count, "deep over count {} is not less than shallow over count {}", deep_over_count, shallow_over_count);
+    }
+
+    #[test]
+    fn export_and_import_a_deeper_sketch() {
+        let mut frequency_counter = FrequencyCounter::with_depth(10, 6);
+        frequency_counter.increment(10);
+        frequency_counter.increment(10);
+
+        let imported = FrequencyCounter::from_sketch(frequency_counter.export());
+
+        assert_eq!(6, imported.matrix.len());
+        assert_eq!(2, imported.estimate(10));
+    }
+
+    #[test]
+    fn total_counters_for() {
+        assert_eq!(32, FrequencyCounter::total_counters_for(18));
+    }
+
     #[test]
     fn reset_count_for_a_row() {
         let mut row = Row(vec![15, 10, 240, 255]);
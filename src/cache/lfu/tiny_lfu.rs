@@ -2,6 +2,9 @@ use log::{debug, info};
 
 use crate::cache::lfu::doorkeeper::DoorKeeper;
 use crate::cache::lfu::frequency_counter::FrequencyCounter;
+#[cfg(test)]
+use crate::cache::sketch::SKETCH_ROWS;
+use crate::cache::sketch::SketchState;
 use crate::cache::types::{DoorKeeperCapacity, FrequencyEstimate, KeyHash, TotalCounters};
 
 /// TinyLFU maintains determines the key access frequencies.
@@ -11,6 +14,10 @@ use crate::cache::types::{DoorKeeperCapacity, FrequencyEstimate, KeyHash, TotalC
     /// https://tech-lessons.in/blog/count_min_sketch/
     /// https://tech-lessons.in/blog/bloom_filter/
 /// Both these data structures work on the hash of the key. All the methods in these abstraction accept `KeyHash` or a `Vec<KeyHash>`.
+///
+/// TinyLFU ages the access frequencies: once `reset_counters_at` accesses are incremented, the counters are halved and the doorkeeper is cleared,
+/// so that the keys which were frequently accessed in the past, but not anymore, give way to the recently accessed keys.
+/// `reset_counters_at` defaults to the total counters.
 pub(crate) struct TinyLFU {
     key_access_frequency: FrequencyCounter,
     door_keeper: DoorKeeper,
@@ -19,9 +26,15 @@ pub(crate) struct TinyLFU {
 }
 
 impl TinyLFU {
+    #[cfg(test)]
     pub(crate) fn new(counters: TotalCounters) -> TinyLFU {
+        Self::with_depth(counters, SKETCH_ROWS)
+    }
+
+    /// Creates a TinyLFU whose `FrequencyCounter` has `depth` rows.
+    pub(crate) fn with_depth(counters: TotalCounters, depth: usize) -> TinyLFU {
         let tiny_lfu = TinyLFU {
-            key_access_frequency: FrequencyCounter::new(counters),
+            key_access_frequency: FrequencyCounter::with_depth(counters, depth),
             door_keeper: DoorKeeper::new(counters as DoorKeeperCapacity, 0.01),
             total_increments: 0,
             reset_counters_at: counters,
@@ -33,6 +46,11 @@ impl TinyLFU {
         tiny_lfu
     }
 
+    /// Resets (halves) the counters after every `accesses` increments, instead of after every total counters increments.
+    pub(crate) fn reset_counters_after(&mut self, accesses: u64) {
+        self.reset_counters_at = accesses;
+    }
+
     pub(crate) fn increment_access(&mut self, key_hashes: Vec<KeyHash>) {
         key_hashes.iter().for_each(|key_hash| self.increment_access_for(*key_hash));
     }
@@ -50,6 +68,18 @@ impl TinyLFU {
         estimate
     }
 
+    /// Replaces the access frequencies with the exported sketch, and clears the doorkeeper.
+    pub(crate) fn import_sketch(&mut self, sketch: SketchState) {
+        debug!("Importing a sketch in tinyLFU");
+        self.total_increments = 0;
+        self.key_access_frequency = FrequencyCounter::from_sketch(sketch);
+        self.door_keeper.clear();
+    }
+
+    pub(crate) fn export_sketch(&self) -> SketchState {
+        self.key_access_frequency.export()
+    }
+
     pub(crate) fn clear(&mut self) {
         debug!("Clearing tinyLFU");
         self.total_increments = 0;
@@ -71,7 +101,8 @@ impl TinyLFU {
         }
     }
 
-    fn reset(&mut self) {
+    /// Halves the counters of the `FrequencyCounter`, clears the doorkeeper and starts counting the increments towards the next reset afresh.
+    pub(crate) fn reset(&mut self) {
         debug!("Resetting tinyLFU");
         self.total_increments = 0;
         self.key_access_frequency.reset();
@@ -111,6 +142,18 @@ mod tests {
         assert_eq!(4, tiny_lfu.total_increments);
     }
 
+    #[test]
+    fn export_and_import_a_sketch() {
+        let mut tiny_lfu = TinyLFU::new(10);
+        tiny_lfu.increment_access(vec![10, 10, 10, 20]);
+
+        let mut imported = TinyLFU::new(10);
+        imported.import_sketch(tiny_lfu.export_sketch());
+
+        assert_eq!(2, imported.estimate(10));
+        assert_eq!(0, imported.estimate(20));
+    }
+
     #[test]
     fn reset() {
         let mut tiny_lfu = TinyLFU::new(2);
@@ -118,4 +161,26 @@ mod tests {
 
         assert_eq!(0, tiny_lfu.total_increments);
     }
+
+    #[test]
+    fn reset_counters_after_the_given_accesses() {
+        let mut tiny_lfu = TinyLFU::new(16);
+        tiny_lfu.reset_counters_after(5);
+        tiny_lfu.increment_access(vec![10, 10, 10, 10]);
+        assert_eq!(4, tiny_lfu.estimate(10));
+
+        tiny_lfu.increment_access(vec![10]);
+        assert_eq!(0, tiny_lfu.total_increments);
+        assert_eq!(2, tiny_lfu.estimate(10));
+    }
+
+    #[test]
+    fn reset_halves_the_counters() {
+        let mut tiny_lfu = TinyLFU::new(1024);
+        tiny_lfu.increment_access(vec![10; 8]);
+        assert_eq!(8, tiny_lfu.estimate(10));
+
+        tiny_lfu.reset();
+        assert_eq!(3, tiny_lfu.estimate(10));
+    }
 }
\ No newline at end of file
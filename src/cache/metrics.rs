@@ -0,0 +1,76 @@
+use std::fmt::Write;
+
+use crate::cache::stats::{StatsSummary, StatsType};
+use crate::cache::types::Weight;
+
+/// Returns the stable name (without the prefix) and the help text of the counter of the `stats_type`.
+fn counter_of(stats_type: StatsType) -> (&'static str, &'static str) {
+    match stats_type {
+        StatsType::CacheHits => ("hits_total", "The number of hits for the keys."),
+        StatsType::CacheMisses => ("misses_total", "The number of misses for the keys."),
+        StatsType::KeysAdded => ("keys_added_total", "The number of keys added."),
+        StatsType::KeysDeleted => ("keys_deleted_total", "The number of keys deleted."),
+        StatsType::KeysUpdated => ("keys_updated_total", "The number of keys updated."),
+        StatsType::KeysRejected => ("keys_rejected_total", "The number of keys rejected."),
+        StatsType::WeightAdded => ("weight_added_total", "The total weight added."),
+        StatsType::WeightRemoved => ("weight_removed_total", "The total weight removed."),
+        StatsType::AccessAdded => ("access_added_total", "The number of gets registered in the frequency counter."),
+        StatsType::AccessDropped => ("access_dropped_total", "The number of gets dropped."),
+        StatsType::WeakValuesReclaimed => ("weak_values_reclaimed_total", "The number of keys reclaimed because their weak values were dropped."),
+        StatsType::KeysExpired => ("keys_expired_total", "The number of keys expired."),
+        StatsType::KeysEvicted => ("keys_evicted_total", "The number of keys evicted."),
+    }
+}
+
+/// Renders the counters of the `summary` along with the `weight_used` and the number of `keys` in the Prometheus text exposition format.
+///
+/// Each [`StatsType`] is rendered as a counter named `<prefix>_<name>`, where the names are stable across the releases,
+/// the `weight_used` is rendered as the gauge `<prefix>_weight_used` and `keys` as the gauge `<prefix>_keys`.
+pub(crate) fn render(prefix: &str, summary: &StatsSummary, weight_used: Weight, keys: usize) -> String {
+    let mut output = String::new();
+    for stats_type in StatsType::VALUES.iter() {
+        let (name, help) = counter_of(*stats_type);
+        write_metric(&mut output, prefix, name, "counter", help, summary.get(stats_type).unwrap_or(0));
+    }
+    write_metric(&mut output, prefix, "weight_used", "gauge", "The total weight used in the cache.", weight_used);
+    write_metric(&mut output, prefix, "keys", "gauge", "The number of keys in the cache, including the expired keys that are not yet removed.", keys);
+    output
+}
+
+fn write_metric<V: std::fmt::Display>(output: &mut String, prefix: &str, name: &str, metric_type: &str, help: &str, value: V) {
+    let _ = writeln!(output, "# HELP {}_{} {}", prefix, name, help);
+    let _ = writeln!(output, "# TYPE {}_{} {}", prefix, name, metric_type);
+    let _ = writeln!(output, "{}_{} {}", prefix, name, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::cache::metrics::render;
+    use crate::cache::stats::{StatsSummary, StatsType};
+
+    #[test]
+    fn render_the_metrics() {
+        let mut stats_by_type = HashMap::new();
+        stats_by_type.insert(StatsType::CacheHits, 3);
+        stats_by_type.insert(StatsType::CacheMisses, 1);
+        let summary = StatsSummary::new(stats_by_type, 0.75);
+
+        let output = render("cached", &summary, 40, 2);
+
+        assert!(output.contains("# HELP cached_hits_total The number of hits for the keys.\n# TYPE cached_hits_total counter\ncached_hits_total 3\n"));
+        assert!(output.contains("\ncached_misses_total 1\n"));
+        assert!(output.contains("\ncached_keys_added_total 0\n"));
+        assert!(output.contains("# TYPE cached_weight_used gauge\ncached_weight_used 40\n"));
+        assert!(output.contains("# TYPE cached_keys gauge\ncached_keys 2\n"));
+    }
+
+    #[test]
+    fn render_a_counter_for_each_stats_type() {
+        let output = render("cached", &StatsSummary::new(HashMap::new(), 0.0), 0, 0);
+
+        assert_eq!(13, output.lines().filter(|line| line.starts_with("# TYPE") && line.ends_with("counter")).count());
+        assert_eq!(2, output.lines().filter(|line| line.starts_with("# TYPE") && line.ends_with("gauge")).count());
+    }
+}
@@ -3,14 +3,24 @@ pub mod config;
 pub mod command;
 pub mod types;
 pub mod put_or_update;
+pub mod compare_and_swap;
 pub mod stats;
 pub mod clock;
 pub mod store;
+pub mod buffer_event;
+pub mod sketch;
+
+#[cfg(feature = "trace")]
+pub mod trace;
+#[cfg(feature = "serde")]
+pub mod export;
+#[cfg(feature = "metrics")]
+pub(crate) mod metrics;
+#[cfg(feature = "bench_util")]
+pub mod bench_util;
 
 #[cfg(feature = "bench_testable")]
 pub mod proxy;
-#[cfg(feature = "bench_testable")]
-pub mod buffer_event;
 
 pub(crate) mod lfu;
 pub(crate) mod pool;
@@ -19,6 +29,5 @@ pub(crate) mod key_description;
 pub(crate) mod unique_id;
 pub(crate) mod expiration;
 pub(crate) mod errors;
+pub(crate) mod single_flight;
 
-#[cfg(not(feature = "bench_testable"))]
-pub(crate) mod buffer_event;
@@ -1,19 +1,26 @@
 use std::hash::Hash;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
 
 use crossbeam_channel::{Receiver, select};
 use log::{debug, info, warn};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
 use crate::cache::buffer_event::{BufferConsumer, BufferEvent};
 use crate::cache::command::{CommandStatus, RejectionReason};
 use crate::cache::command::RejectionReason::EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers;
+use crate::cache::config::{AdmissionDecision, AdmissionMode, AdmissionObserverFn, EvictionReason, ReadmissionListenerFn};
 use crate::cache::key_description::KeyDescription;
 use crate::cache::lfu::tiny_lfu::TinyLFU;
-use crate::cache::policy::cache_weight::CacheWeight;
+use crate::cache::policy::cache_weight::{CacheWeight, ExpiryLookupFn, MinResidency};
+use crate::cache::policy::capacity_listener::CapacityListener;
 use crate::cache::policy::config::CacheWeightConfig;
+use crate::cache::policy::eviction_audit::AuditKeyFn;
+use crate::cache::policy::readmission::ReadmissionTracker;
+#[cfg(test)]
+use crate::cache::sketch::SKETCH_ROWS;
+use crate::cache::sketch::SketchState;
 use crate::cache::stats::ConcurrentStatsCounter;
 use crate::cache::types::{FrequencyEstimate, KeyHash, KeyId, TotalCounters, Weight};
 
@@ -24,26 +31,40 @@ const CHANNEL_CAPACITY: usize = 10;
 /// `AdmissionPolicy` is responsible for a few things:
 /// 1) It contains [`crate::cache::lfu::tiny_lfu::TinyLFU`] that provides methods to increase and estimate the access frequency of keys
 /// 2) It is responsible for deciding if a key should be admitted in the cache.
-    /// If the cache has weight available to accommodate the incoming key, it will be admitted
-    /// otherwise, `AdmissionPolicy` has 2 options: either reject the incoming key or create space to accommodate the incoming key. Read `create_space`.
+///    If the cache has weight available to accommodate the incoming key, it will be admitted
+///    otherwise, `AdmissionPolicy` has 2 options: either reject the incoming key or create space to accommodate the incoming key. Read `create_space`.
 /// 3) It is responsible for updating the weight of a key
 /// 4) It is responsible for deleting a key which in turn reduces the cache weight
-/// 5) It also acts as a buffer consumer.
-    /// All the access (`get`) to keys are buffered [BP-Wrapper](https://dgraph.io/blog/refs/bp_wrapper.pdf). Read [`crate::cache::pool::Pool`] for more details
-    /// When a buffer is full, it is drained.
-    /// As a part of draining the buffer, an instance of buffer consumer is invoked
-    /// The buffer consumer which is `AdmissionPolicy` in this case, will `accept` the buffer and take a single lock on the `access_frequency` and increase
-    /// the access frequency of the keys
+/// 5) It notifies [`crate::cache::policy::capacity_listener::CapacityListener`] when the cache becomes full and when it regains headroom
+/// 6) It allows suspending the eviction, read `suspend_eviction`
+/// 7) It allows reserving the weight for the keys that are put under a `crate::cache::cached::Reservation`, read `reserve`
+/// 8) It passes every admission decision to the admission observer (if any), read `observe_admissions`
+/// 9) It allows admitting every key that fits in the cache weight, irrespective of its access frequency, read `admit_always`
+/// 10) It also acts as a buffer consumer.
+///     All the access (`get`) to keys are buffered [BP-Wrapper](https://dgraph.io/blog/refs/bp_wrapper.pdf). Read [`crate::cache::pool::Pool`] for more details
+///     When a buffer is full, it is drained.
+///     As a part of draining the buffer, an instance of buffer consumer is invoked
+///     The buffer consumer which is `AdmissionPolicy` in this case, will `accept` the buffer and take a single lock on the `access_frequency` and increase
+///     the access frequency of the keys
+///
 /// *) Buffer consumer is implemented as a single thread that receives [`crate::cache::buffer_event::BufferEvent`] and acts on them.
-    /// However, there can be contention if too many accesses happen in the system which results in the access buffer(s) filling in too fast.
-    /// This contention is around acquiring the write lock on `TinyLFU` as [`crate::cache::lfu::tiny_lfu::TinyLFU`] is wrapped inside a `RwLock`.
-    /// In order to reduce this contention, the system decides to drop the buffers by maintaining a limited `CHANNEL_CAPACITY`.
-/// `AdmissionPolicy` is invoked by a single thread through `crate::cache::command::command_executor::CommandExecutor`
-///  and its delete is invoked by `crate::cache::expiration::TTLTicker`
+///    However, there can be contention if too many accesses happen in the system which results in the access buffer(s) filling in too fast.
+///    This contention is around acquiring the write lock on `TinyLFU` as [`crate::cache::lfu::tiny_lfu::TinyLFU`] is wrapped inside a `RwLock`.
+///    In order to reduce this contention, the system decides to drop the buffers by maintaining a limited `CHANNEL_CAPACITY`.
+///
+/// `AdmissionPolicy` is invoked through `crate::cache::command::command_executor::CommandExecutor`
+///  and its delete is invoked by `crate::cache::expiration::TTLTicker`.
+/// `CommandExecutor` may run more than one thread, so `maybe_add` holds `admission_lock` while it checks and creates the space for a key,
+/// which ensures that the concurrent admissions do not add the keys beyond the max weight.
 pub(crate) struct AdmissionPolicy<Key>
     where Key: Hash + Eq + Send + Sync + Clone + 'static, {
     access_frequency: Arc<RwLock<TinyLFU>>,
     cache_weight: CacheWeight<Key>,
+    capacity_listener: CapacityListener,
+    eviction_suspensions: AtomicUsize,
+    admission_observer: Option<Arc<AdmissionObserverFn>>,
+    admission_mode: AdmissionMode,
+    admission_lock: Mutex<()>,
     sender: crossbeam_channel::Sender<BufferEvent>,
     keep_running: Arc<AtomicBool>,
     stats_counter: Arc<ConcurrentStatsCounter>,
@@ -51,18 +72,30 @@ pub(crate) struct AdmissionPolicy<Key>
 
 impl<Key> AdmissionPolicy<Key>
     where Key: Hash + Eq + Send + Sync + Clone + 'static, {
+    #[cfg(test)]
     pub(crate) fn new(counters: TotalCounters, cache_weight_config: CacheWeightConfig, stats_counter: Arc<ConcurrentStatsCounter>) -> Self {
-        Self::with_channel_capacity(counters, cache_weight_config, CHANNEL_CAPACITY, stats_counter)
+        Self::with_sketch_depth(counters, SKETCH_ROWS, cache_weight_config, stats_counter)
+    }
+
+    /// Creates an `AdmissionPolicy` whose count-min sketch (that maintains the access frequency) has `sketch_depth` rows.
+    pub(crate) fn with_sketch_depth(counters: TotalCounters, sketch_depth: usize, cache_weight_config: CacheWeightConfig, stats_counter: Arc<ConcurrentStatsCounter>) -> Self {
+        Self::with_channel_capacity(counters, sketch_depth, cache_weight_config, CHANNEL_CAPACITY, stats_counter)
     }
 
     fn with_channel_capacity(
         counters: TotalCounters,
+        sketch_depth: usize,
         cache_weight_config: CacheWeightConfig,
         channel_capacity: usize,
         stats_counter: Arc<ConcurrentStatsCounter>) -> Self {
         let (sender, receiver) = crossbeam_channel::bounded(channel_capacity);
         let policy = AdmissionPolicy {
-            access_frequency: Arc::new(RwLock::new(TinyLFU::new(counters))),
+            access_frequency: Arc::new(RwLock::new(TinyLFU::with_depth(counters, sketch_depth))),
+            capacity_listener: cache_weight_config.capacity_listener(),
+            eviction_suspensions: AtomicUsize::new(0),
+            admission_observer: None,
+            admission_mode: AdmissionMode::TinyLfu,
+            admission_lock: Mutex::new(()),
             cache_weight: CacheWeight::new(cache_weight_config, stats_counter.clone()),
             sender,
             keep_running: Arc::new(AtomicBool::new(true)),
@@ -72,6 +105,54 @@ impl<Key> AdmissionPolicy<Key>
         policy
     }
 
+    /// Prefers evicting the keys that expire the soonest, amongst the sampled keys with the same estimated frequency.
+    /// `expiry_lookup` returns the expiry of a key, if any.
+    pub(crate) fn prefer_evicting_expiring_keys(mut self, expiry_lookup: Box<ExpiryLookupFn<Key>>) -> Self {
+        self.cache_weight.prefer_evicting_expiring_keys(expiry_lookup);
+        self
+    }
+
+    /// Protects the keys admitted less than `min_residency` ago from being evicted to create space, the older keys are evicted instead.
+    pub(crate) fn protect_young_keys(mut self, min_residency: MinResidency) -> Self {
+        self.cache_weight.protect_young_keys(min_residency);
+        self
+    }
+
+    /// Logs every removal of a key (eviction, expiry or delete) with the key formatted by `audit_key_fn`, the reason and the weight reclaimed.
+    pub(crate) fn audit_removals(mut self, audit_key_fn: Arc<AuditKeyFn<Key>>) -> Self {
+        self.cache_weight.audit_removals(audit_key_fn);
+        self
+    }
+
+    /// Invokes `on_readmission` with every key that is admitted again after it was evicted to create space.
+    pub(crate) fn track_readmissions(mut self, on_readmission: Arc<ReadmissionListenerFn<Key>>) -> Self {
+        self.cache_weight.track_readmissions(ReadmissionTracker::new(on_readmission));
+        self
+    }
+
+    pub(crate) fn thrash_rate(&self) -> f64 {
+        self.cache_weight.thrash_rate()
+    }
+
+    /// Passes every decision of `maybe_add` to the `admission_observer`, after the decision is made.
+    pub(crate) fn observe_admissions(mut self, admission_observer: Arc<AdmissionObserverFn>) -> Self {
+        self.admission_observer = Some(admission_observer);
+        self
+    }
+
+    /// Halves the access frequencies after every `accesses` accesses are applied to them, read `crate::cache::lfu::tiny_lfu::TinyLFU`.
+    pub(crate) fn reset_frequencies_after(self, accesses: u64) -> Self {
+        self.access_frequency.write().reset_counters_after(accesses);
+        self
+    }
+
+    /// Admits every key that fits in the cache weight: `create_space` evicts the sampled keys with the least access frequency
+    /// without comparing their access frequency with that of the incoming key.
+    pub(crate) fn admit_always(mut self) -> Self {
+        self.admission_mode = AdmissionMode::Always;
+        self
+    }
+
     fn start(&self, receiver: Receiver<BufferEvent>) {
         let keep_running = self.keep_running.clone();
         let access_frequency = self.access_frequency.clone();
@@ -97,45 +178,185 @@ impl<Key> AdmissionPolicy<Key>
         });
     }
 
+    /// Increments the access frequency of the key_hash synchronously, on the caller's thread, bypassing the access buffers.
+    pub(crate) fn record_access(&self, key_hash: KeyHash) {
+        { self.access_frequency.write().increment_access(vec![key_hash]); }
+        self.stats_counter.add_access(1);
+    }
+
+    /// Increments the access frequency of the key_hash by `count` synthetic accesses, without recording them as accesses in the stats.
+    pub(crate) fn prime(&self, key_hash: KeyHash, count: usize) {
+        if count > 0 {
+            self.access_frequency.write().increment_access(vec![key_hash; count]);
+        }
+    }
+
+    /// Halves the access frequencies of all the keys, and clears the doorkeeper.
+    pub(crate) fn reset_frequencies(&self) {
+        self.access_frequency.write().reset();
+    }
+
+    /// Replaces the access frequency of the keys with the exported sketch.
+    pub(crate) fn import_sketch(&self, sketch: SketchState) {
+        self.access_frequency.write().import_sketch(sketch);
+    }
+
+    pub(crate) fn export_sketch(&self) -> SketchState {
+        self.access_frequency.read().export_sketch()
+    }
+
     pub(crate) fn estimate(&self, key_hash: KeyHash) -> FrequencyEstimate {
         return self.access_frequency.read().estimate(key_hash);
     }
 
+    /// Admits the key if there is space for it, or if it can create the space by evicting the keys with a smaller access frequency.
+    /// `delete_hook` (that removes an evicted key from the `Store` and notifies the eviction listener) is invoked after `admission_lock` is released,
+    /// so that the eviction listener may use the cache.
     pub(crate) fn maybe_add<DeleteHook>(&self,
                                         key_description: &KeyDescription<Key>,
                                         delete_hook: &DeleteHook) -> CommandStatus
         where DeleteHook: Fn(Key) {
+        self.maybe_replace(key_description, &[], delete_hook)
+    }
+
+    /// Admits the key like `maybe_add`, where the incoming key replaces the `replaced` keys, so the weight of the `replaced` keys is available to it.
+    /// The `replaced` keys are deleted (with their reasons) only if the incoming key is admitted, and they are never evicted to create the space.
+    /// Removing the `replaced` keys from the `Store` is left to the caller.
+    pub(crate) fn maybe_replace<DeleteHook>(&self,
+                                            key_description: &KeyDescription<Key>,
+                                            replaced: &[(KeyId, EvictionReason)],
+                                            delete_hook: &DeleteHook) -> CommandStatus
+        where DeleteHook: Fn(Key) {
+        let mut evicted_keys = Vec::new();
+        let (status, victim) = {
+            let _admitting = self.admission_lock.lock();
+            self.admit(key_description, replaced, &mut evicted_keys)
+        };
+        evicted_keys.into_iter().for_each(delete_hook);
+        if let Some(admission_observer) = &self.admission_observer {
+            admission_observer(AdmissionDecision::new(
+                key_description.id, self.estimate(key_description.hash), key_description.weight, victim, status,
+            ));
+        }
+        status
+    }
+
+    /// Returns the status of the incoming key along with the victim (the key_id and the estimated frequency) it was last compared against, if any.
+    /// The keys evicted to create the space are collected in `evicted_keys`, so that the delete hook runs after `admission_lock` is released.
+    fn admit(&self,
+             key_description: &KeyDescription<Key>,
+             replaced: &[(KeyId, EvictionReason)],
+             evicted_keys: &mut Vec<Key>) -> (CommandStatus, Option<(KeyId, FrequencyEstimate)>) {
         if key_description.weight > self.cache_weight.get_max_weight() {
             debug!(
                 "Rejecting key with id {} and weight {}, given its weight is greater than the max cache weight {}",
                 key_description.id, key_description.weight, self.cache_weight.get_max_weight()
             );
-            return CommandStatus::Rejected(RejectionReason::KeyWeightIsGreaterThanCacheWeight);
+            return (CommandStatus::Rejected(RejectionReason::KeyWeightIsGreaterThanCacheWeight), None);
         }
-        let (space_left, is_enough_space_available) = self.cache_weight.is_space_available_for(key_description.weight);
+        let (space_left, is_enough_space_available) = self.cache_weight.is_space_available_for(key_description.weight - self.weight_of_all(replaced));
         if is_enough_space_available {
-            self.cache_weight.add(key_description);
-            return CommandStatus::Accepted;
+            self.replace(key_description, replaced);
+            self.capacity_listener.weight_added(self.cache_weight.get_weight_used(), self.cache_weight.get_max_weight());
+            return (CommandStatus::Accepted, None);
         }
-        let status = self.create_space(space_left, key_description, delete_hook);
+        self.capacity_listener.full();
+        if self.is_eviction_suspended() {
+            debug!(
+                "Admitting key with id {} and weight {} over the max cache weight {}, given the eviction is suspended",
+                key_description.id, key_description.weight, self.cache_weight.get_max_weight()
+            );
+            self.replace(key_description, replaced);
+            return (CommandStatus::Accepted, None);
+        }
+        let (status, victim) = self.create_space(space_left, key_description, replaced, evicted_keys);
         if let CommandStatus::Accepted = status {
-            self.cache_weight.add(key_description);
+            self.replace(key_description, replaced);
         }
-        status
+        (status, victim)
+    }
+
+    fn weight_of_all(&self, replaced: &[(KeyId, EvictionReason)]) -> Weight {
+        replaced.iter().filter_map(|(key_id, _)| self.cache_weight.weight_of(key_id)).sum()
+    }
+
+    /// Deletes the `replaced` keys and adds the incoming key.
+    fn replace(&self, key_description: &KeyDescription<Key>, replaced: &[(KeyId, EvictionReason)]) {
+        for (key_id, reason) in replaced {
+            self.cache_weight.delete(key_id, *reason);
+        }
+        self.cache_weight.add(key_description);
+    }
+
+    /// Reserves the `weight` if it is available (after accounting for the weight used and the weight already reserved).
+    /// The reserved weight is not available to `maybe_add`, it is only available to `add_reserved`.
+    pub(crate) fn reserve(&self, weight: Weight) -> bool {
+        self.cache_weight.reserve(weight)
+    }
+
+    pub(crate) fn release_reservation(&self, weight: Weight) {
+        self.cache_weight.release_reservation(weight);
+    }
+
+    /// Admits the key using the weight that was reserved for it, without creating space (evicting other keys) for it.
+    pub(crate) fn add_reserved(&self, key_description: &KeyDescription<Key>) -> CommandStatus {
+        self.cache_weight.add_reserved(key_description);
+        self.capacity_listener.weight_added(self.cache_weight.get_weight_used(), self.cache_weight.get_max_weight());
+        CommandStatus::Accepted
+    }
+
+    /// Suspends the eviction until `resume_eviction` is invoked (as many times as `suspend_eviction`).
+    /// While the eviction is suspended, `maybe_add` admits the incoming key even if the cache does not have the weight to accommodate it,
+    /// so the weight used may exceed the max cache weight.
+    pub(crate) fn suspend_eviction(&self) {
+        self.eviction_suspensions.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Resumes the eviction and returns true if no other suspension is in place.
+    /// The keys admitted over the max cache weight are evicted by `evict_excess`.
+    pub(crate) fn resume_eviction(&self) -> bool {
+        self.eviction_suspensions.fetch_sub(1, Ordering::AcqRel) == 1
+    }
+
+    /// Evicts the keys with the smallest access frequency (from a sample) until the weight used is within the max cache weight.
+    /// Does nothing if the eviction is suspended.
+    pub(crate) fn evict_excess<DeleteHook>(&self, delete_hook: &DeleteHook)
+        where DeleteHook: Fn(Key) {
+        if self.is_eviction_suspended() {
+            return;
+        }
+        let frequency_counter = |key_hash| self.estimate(key_hash);
+        let mut sample = self.cache_weight.sample(EVICTION_SAMPLE_SIZE, frequency_counter);
+
+        while self.cache_weight.get_weight_used() > self.cache_weight.get_max_weight() {
+            if let Some(sampled_key) = sample.min_frequency_key() {
+                if let Some(key) = self.cache_weight.delete(&sampled_key.id, EvictionReason::Evicted) {
+                    delete_hook(key);
+                }
+                let _ = sample.maybe_fill_in();
+            } else {
+                break;
+            }
+        }
+        self.notify_weight_changed();
     }
 
     pub(crate) fn update(&self, key_id: &KeyId, weight: Weight) {
         self.cache_weight.update(key_id, weight);
+        self.notify_weight_changed();
     }
 
-    pub(crate) fn delete(&self, key_id: &KeyId) {
+    pub(crate) fn delete(&self, key_id: &KeyId, reason: EvictionReason) {
         let no_operation_delete_hook = |_key| {};
-        self.delete_with_hook(key_id, &no_operation_delete_hook);
+        self.delete_with_hook(key_id, reason, &no_operation_delete_hook);
     }
 
-    pub(crate) fn delete_with_hook<DeleteHook>(&self, key_id: &KeyId, delete_hook: &DeleteHook)
+    pub(crate) fn delete_with_hook<DeleteHook>(&self, key_id: &KeyId, reason: EvictionReason, delete_hook: &DeleteHook)
         where DeleteHook: Fn(Key) {
-        self.cache_weight.delete(key_id, delete_hook);
+        if let Some(key) = self.cache_weight.delete(key_id, reason) {
+            delete_hook(key);
+        }
+        self.notify_weight_changed();
     }
 
     pub(crate) fn contains(&self, key_id: &KeyId) -> bool {
@@ -146,6 +367,10 @@ impl<Key> AdmissionPolicy<Key>
         self.cache_weight.weight_of(key_id)
     }
 
+    pub(crate) fn key_of(&self, key_id: &KeyId) -> Option<Key> {
+        self.cache_weight.key_of(key_id)
+    }
+
     pub(crate) fn weight_used(&self) -> Weight {
         self.cache_weight.get_weight_used()
     }
@@ -156,9 +381,25 @@ impl<Key> AdmissionPolicy<Key>
     }
 
     pub(crate) fn clear(&self) {
-        self.cache_weight.clear();
+        self.cache_weight.remove_all();
         self.access_frequency.write().clear();
-        self.stats_counter.clear();
+        self.stats_counter.reset();
+        self.notify_weight_changed();
+    }
+
+    /// Removes all the keys and resets the access frequency of the keys, without clearing the stats, so that the cache can be used after.
+    pub(crate) fn remove_all(&self) {
+        self.cache_weight.remove_all();
+        self.access_frequency.write().clear();
+        self.notify_weight_changed();
+    }
+
+    fn is_eviction_suspended(&self) -> bool {
+        self.eviction_suspensions.load(Ordering::Acquire) > 0
+    }
+
+    fn notify_weight_changed(&self) {
+        self.capacity_listener.weight_changed(self.cache_weight.get_weight_used(), self.cache_weight.get_max_weight());
     }
 
     /// Attempts to create the space for the incoming key.
@@ -177,41 +418,50 @@ impl<Key> AdmissionPolicy<Key>
     ///    its access frequency is less than the smallest access frequency
     /// 6) Else, delete the key K1 and create the space in the cache. The space created will be equal to the weight of K1
     /// 7) Repeat the process until either the incoming key is rejected or enough space to accommodate the incoming key is created in the cache
-    fn create_space<DeleteHook>(&self,
-                                space_left: Weight,
-                                key_description: &KeyDescription<Key>,
-                                delete_hook: &DeleteHook) -> CommandStatus
-        where DeleteHook: Fn(Key) {
+    ///
+    /// Returns the status along with the last key K1 (the victim), if any. The evicted keys are collected in `evicted_keys`.
+    /// The weight of the `replaced` keys is counted as available, and the `replaced` keys are skipped if they are sampled.
+    fn create_space(&self,
+                    space_left: Weight,
+                    key_description: &KeyDescription<Key>,
+                    replaced: &[(KeyId, EvictionReason)],
+                    evicted_keys: &mut Vec<Key>) -> (CommandStatus, Option<(KeyId, FrequencyEstimate)>) {
         let frequency_counter = |key_hash| self.estimate(key_hash);
+        let weight_needed = key_description.weight - self.weight_of_all(replaced);
 
         let incoming_key_access_frequency = self.estimate(key_description.hash);
         let mut space_available = space_left;
+        let mut victim = None;
 
         let mut sample = self.cache_weight.sample(EVICTION_SAMPLE_SIZE, frequency_counter);
-        while space_available < key_description.weight {
+        while space_available < weight_needed {
             if let Some(sampled_key) = sample.min_frequency_key() {
-                if incoming_key_access_frequency < sampled_key.estimated_frequency {
+                if replaced.iter().any(|(key_id, _)| *key_id == sampled_key.id) {
+                    continue;
+                }
+                victim = Some((sampled_key.id, sampled_key.estimated_frequency));
+                if self.admission_mode == AdmissionMode::TinyLfu && incoming_key_access_frequency < sampled_key.estimated_frequency {
                     debug!(
                         "Rejecting key with id {} and estimated frequency {}, given its frequency is less than the sampled key with frequency {}",
                         key_description.id, incoming_key_access_frequency, sampled_key.estimated_frequency
                     );
-                    return CommandStatus::Rejected(EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers);
+                    return (CommandStatus::Rejected(EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers), victim);
                 }
 
-                self.cache_weight.delete(&sampled_key.id, delete_hook);
-                let (fresh_space_available, _) = self.cache_weight.is_space_available_for(key_description.weight);
+                evicted_keys.extend(self.cache_weight.delete(&sampled_key.id, EvictionReason::Evicted));
+                let (fresh_space_available, _) = self.cache_weight.is_space_available_for(weight_needed);
 
                 space_available = fresh_space_available;
                 let _ = sample.maybe_fill_in();
             } else {
-                let (_, is_enough_space_available) = self.cache_weight.is_space_available_for(key_description.weight);
+                let (_, is_enough_space_available) = self.cache_weight.is_space_available_for(weight_needed);
                 if is_enough_space_available {
-                    return CommandStatus::Accepted;
+                    return (CommandStatus::Accepted, victim);
                 }
-                return CommandStatus::Rejected(EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers);
+                return (CommandStatus::Rejected(EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers), victim);
             }
         }
-        CommandStatus::Accepted
+        (CommandStatus::Accepted, victim)
     }
 }
 
@@ -255,6 +505,7 @@ mod tests {
     use crate::cache::buffer_event::{BufferConsumer, BufferEvent};
     use crate::cache::command::CommandStatus;
     use crate::cache::command::RejectionReason::{EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers, KeyWeightIsGreaterThanCacheWeight};
+    use crate::cache::config::{AdmissionDecision, EvictionReason};
     use crate::cache::key_description::KeyDescription;
     use crate::cache::policy::admission_policy::AdmissionPolicy;
     use crate::cache::policy::config::CacheWeightConfig;
@@ -268,6 +519,53 @@ mod tests {
         CacheWeightConfig::new(100, 4, 10)
     }
 
+    #[test]
+    fn record_access() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let policy: AdmissionPolicy<&str> = AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone());
+
+        policy.record_access(10);
+        policy.record_access(10);
+        policy.record_access(14);
+
+        assert_eq!(2, policy.estimate(10));
+        assert_eq!(1, policy.estimate(14));
+        assert_eq!(3, stats_counter.access_added());
+    }
+
+    #[test]
+    fn reset_frequencies() {
+        let policy: AdmissionPolicy<&str> = AdmissionPolicy::new(1024, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        policy.access_frequency.write().increment_access(vec![10; 8]);
+
+        policy.reset_frequencies();
+        assert_eq!(3, policy.estimate(10));
+    }
+
+    #[test]
+    fn reset_frequencies_after_the_given_accesses() {
+        let policy: AdmissionPolicy<&str> = AdmissionPolicy::new(1024, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()))
+            .reset_frequencies_after(8);
+        policy.access_frequency.write().increment_access(vec![10; 7]);
+        assert_eq!(7, policy.estimate(10));
+
+        policy.access_frequency.write().increment_access(vec![10]);
+        assert_eq!(3, policy.estimate(10));
+    }
+
+    #[test]
+    fn prime() {
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let policy: AdmissionPolicy<&str> = AdmissionPolicy::new(10, test_cache_weight_config(), stats_counter.clone());
+
+        policy.prime(10, 3);
+        policy.prime(14, 0);
+
+        assert_eq!(3, policy.estimate(10));
+        assert_eq!(0, policy.estimate(14));
+        assert_eq!(0, stats_counter.access_added());
+    }
+
     #[test]
     fn increase_access_and_shutdown() {
         let policy: AdmissionPolicy<&str> = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
@@ -395,7 +693,7 @@ mod tests {
         let status = policy.maybe_add(&KeyDescription::new("topic", 1, 10, 5), &delete_hook);
         assert_eq!(CommandStatus::Accepted, status);
 
-        policy.cache_weight.clear();
+        policy.cache_weight.remove_all();
 
         let status = policy.maybe_add(&KeyDescription::new("SSD", 2, 14, 6), &delete_hook);
         assert_eq!(CommandStatus::Accepted, status);
@@ -433,6 +731,81 @@ mod tests {
         assert_eq!(vec!["topic"], *deleted_keys.keys.read());
     }
 
+    #[test]
+    fn replaces_a_key_using_the_weight_of_the_replaced_key() {
+        let policy = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        let deleted_keys = DeletedKeys { keys: RwLock::new(Vec::new()) };
+        let delete_hook = |key| { deleted_keys.keys.write().push(key) };
+
+        let status = policy.maybe_add(&KeyDescription::new("topic", 1, 20, 5), &delete_hook);
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let status = policy.maybe_add(&KeyDescription::new("HDD", 2, 14, 5), &delete_hook);
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let status = policy.maybe_replace(&KeyDescription::new("topic", 3, 20, 5), &[(1, EvictionReason::Replaced)], &delete_hook);
+        assert_eq!(CommandStatus::Accepted, status);
+
+        assert!(!policy.contains(&1));
+        assert!(policy.contains(&2));
+        assert!(policy.contains(&3));
+        assert_eq!(10, policy.cache_weight.get_weight_used());
+        assert!(deleted_keys.keys.read().is_empty());
+    }
+
+    #[test]
+    fn admits_the_incoming_key_with_a_lower_access_frequency_if_admitting_always() {
+        let policy = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new())).admit_always();
+        policy.access_frequency.write().increment_access(vec![14, 14, 20, 20]);
+
+        let deleted_keys = DeletedKeys { keys: RwLock::new(Vec::new()) };
+        let delete_hook = |key| { deleted_keys.keys.write().push(key) };
+
+        let status = policy.maybe_add(&KeyDescription::new("topic", 1, 20, 5), &delete_hook);
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let status = policy.maybe_add(&KeyDescription::new("HDD", 2, 14, 3), &delete_hook);
+        assert_eq!(CommandStatus::Accepted, status);
+
+        let status = policy.maybe_add(&KeyDescription::new("SSD", 3, 90, 9), &delete_hook);
+        assert_eq!(CommandStatus::Accepted, status);
+
+        assert!(policy.contains(&3));
+        assert_eq!(9, policy.cache_weight.get_weight_used());
+        assert_eq!(2, deleted_keys.keys.read().len());
+    }
+
+    #[test]
+    fn rejects_the_incoming_key_heavier_than_the_cache_weight_if_admitting_always() {
+        let policy: AdmissionPolicy<&str> = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new())).admit_always();
+
+        let status = policy.maybe_add(&KeyDescription::new("topic", 1, 20, 11), &|_key| {});
+        assert_eq!(CommandStatus::Rejected(KeyWeightIsGreaterThanCacheWeight), status);
+    }
+
+    #[test]
+    fn observes_the_admission_decisions() {
+        let decisions = Arc::new(RwLock::new(Vec::new()));
+        let observed_decisions = decisions.clone();
+        let policy = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()))
+            .observe_admissions(Arc::new(move |decision| observed_decisions.write().push(decision)));
+        policy.access_frequency.write().increment_access(vec![14]);
+
+        let delete_hook = |_key| {};
+        policy.maybe_add(&KeyDescription::new("topic", 1, 20, 5), &delete_hook);
+        policy.maybe_add(&KeyDescription::new("HDD", 2, 14, 3), &delete_hook);
+        policy.maybe_add(&KeyDescription::new("SSD", 3, 90, 9), &delete_hook);
+
+        let decisions = decisions.read();
+        assert_eq!(3, decisions.len());
+        assert_eq!(AdmissionDecision::new(1, 0, 5, None, CommandStatus::Accepted), decisions[0]);
+        assert_eq!(AdmissionDecision::new(2, 1, 3, None, CommandStatus::Accepted), decisions[1]);
+        assert_eq!(
+            AdmissionDecision::new(3, 0, 9, Some((2, 1)), CommandStatus::Rejected(EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers)),
+            decisions[2]
+        );
+    }
+
     #[test]
     fn updates_the_weight_of_a_key() {
         let policy = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
@@ -454,7 +827,7 @@ mod tests {
         let addition_status = policy.maybe_add(&KeyDescription::new("topic", 1, 3018, 5), &no_operation_delete_hook);
         assert_eq!(CommandStatus::Accepted, addition_status);
 
-        policy.delete(&1);
+        policy.delete(&1, EvictionReason::Explicit);
         assert!(!policy.contains(&1));
     }
 
@@ -467,7 +840,7 @@ mod tests {
         let addition_status = policy.maybe_add(&KeyDescription::new("topic", 1, 3018, 5), &delete_hook);
         assert_eq!(CommandStatus::Accepted, addition_status);
 
-        policy.delete_with_hook(&1, &delete_hook);
+        policy.delete_with_hook(&1, EvictionReason::Expired, &delete_hook);
         assert!(!policy.contains(&1));
         assert_eq!("topic", deleted_keys.keys.read()[0]);
     }
@@ -501,6 +874,18 @@ mod tests {
         assert_eq!(Some(5), policy.weight_of(&1));
     }
 
+    #[test]
+    fn key_of_an_existing_key_id() {
+        let policy = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        let no_operation_delete_hook = |_key| {};
+
+        let addition_status = policy.maybe_add(&KeyDescription::new("topic", 1, 3018, 5), &no_operation_delete_hook);
+        assert_eq!(CommandStatus::Accepted, addition_status);
+
+        assert_eq!(Some("topic"), policy.key_of(&1));
+        assert_eq!(None, policy.key_of(&2));
+    }
+
     #[test]
     fn weight_of_a_non_existing_key() {
         let policy: AdmissionPolicy<&str> = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
@@ -566,4 +951,84 @@ mod tests {
         assert!(!policy.contains(&1));
         assert!(!policy.contains(&2));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn reserved_weight_is_not_available_to_the_incoming_key() {
+        let policy = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        let deleted_keys = DeletedKeys { keys: RwLock::new(Vec::new()) };
+        let delete_hook = |key| { deleted_keys.keys.write().push(key) };
+
+        assert!(policy.reserve(8));
+        assert_eq!(CommandStatus::Rejected(EnoughSpaceIsNotAvailableAndKeyFailedToEvictOthers), policy.maybe_add(&KeyDescription::new("topic", 1, 10, 5), &delete_hook));
+        assert_eq!(CommandStatus::Accepted, policy.maybe_add(&KeyDescription::new("disk", 2, 20, 2), &delete_hook));
+    }
+
+    #[test]
+    fn adds_keys_with_the_reserved_weight() {
+        let policy = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+
+        assert!(policy.reserve(10));
+        assert_eq!(CommandStatus::Accepted, policy.add_reserved(&KeyDescription::new("topic", 1, 10, 5)));
+        assert_eq!(CommandStatus::Accepted, policy.add_reserved(&KeyDescription::new("disk", 2, 20, 5)));
+
+        assert!(policy.contains(&1));
+        assert!(policy.contains(&2));
+        assert_eq!(10, policy.weight_used());
+        assert!(!policy.reserve(1));
+    }
+
+    #[test]
+    fn adds_a_key_over_the_max_weight_if_the_eviction_is_suspended() {
+        let policy = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        let key_hashes = vec![10, 14, 116];
+        policy.access_frequency.write().increment_access(key_hashes);
+
+        let deleted_keys = DeletedKeys { keys: RwLock::new(Vec::new()) };
+        let delete_hook = |key| { deleted_keys.keys.write().push(key) };
+
+        policy.suspend_eviction();
+        assert_eq!(CommandStatus::Accepted, policy.maybe_add(&KeyDescription::new("topic", 1, 10, 5), &delete_hook));
+        assert_eq!(CommandStatus::Accepted, policy.maybe_add(&KeyDescription::new("SSD", 2, 90, 6), &delete_hook));
+
+        assert!(policy.contains(&1));
+        assert!(policy.contains(&2));
+        assert_eq!(11, policy.weight_used());
+        assert!(deleted_keys.keys.read().is_empty());
+    }
+
+    #[test]
+    fn evicts_the_excess_weight_after_the_eviction_is_resumed() {
+        let policy = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        let key_hashes = vec![10, 10, 14, 14];
+        policy.access_frequency.write().increment_access(key_hashes);
+
+        let deleted_keys = DeletedKeys { keys: RwLock::new(Vec::new()) };
+        let delete_hook = |key| { deleted_keys.keys.write().push(key) };
+
+        policy.suspend_eviction();
+        let _ = policy.maybe_add(&KeyDescription::new("topic", 1, 10, 5), &delete_hook);
+        let _ = policy.maybe_add(&KeyDescription::new("SSD", 2, 14, 4), &delete_hook);
+        let _ = policy.maybe_add(&KeyDescription::new("HDD", 3, 90, 3), &delete_hook);
+
+        policy.evict_excess(&delete_hook);
+        assert_eq!(12, policy.weight_used());
+
+        assert!(policy.resume_eviction());
+        policy.evict_excess(&delete_hook);
+
+        assert_eq!(9, policy.weight_used());
+        assert!(!policy.contains(&3));
+        assert_eq!(vec!["HDD"], *deleted_keys.keys.read());
+    }
+
+    #[test]
+    fn resumes_the_eviction_after_all_the_suspensions_are_resumed() {
+        let policy: AdmissionPolicy<&str> = AdmissionPolicy::new(10, test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+
+        policy.suspend_eviction();
+        policy.suspend_eviction();
+
+        assert!(!policy.resume_eviction());
+        assert!(policy.resume_eviction());
+    }
+}
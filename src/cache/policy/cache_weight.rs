@@ -2,16 +2,53 @@ use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashSet};
 use std::hash::Hash;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use dashmap::DashMap;
 use dashmap::mapref::multiple::RefMulti;
 use log::info;
 use parking_lot::RwLock;
 
+use crate::cache::clock::ClockType;
+use crate::cache::config::EvictionReason;
 use crate::cache::key_description::KeyDescription;
 use crate::cache::policy::config::CacheWeightConfig;
+use crate::cache::policy::eviction_audit::{audit_removal, AuditKeyFn};
+use crate::cache::policy::readmission::ReadmissionTracker;
 use crate::cache::stats::ConcurrentStatsCounter;
-use crate::cache::types::{FrequencyEstimate, KeyHash, KeyId, Weight};
+use crate::cache::types::{ExpireAfter, FrequencyEstimate, KeyHash, KeyId, Weight};
+
+/// Defines the function that returns the expiry of a key, it is used to prefer evicting the keys that expire the soonest.
+pub(crate) type ExpiryLookupFn<Key> = dyn Fn(&Key) -> Option<ExpireAfter> + Send + Sync;
+
+/// MinResidency protects the keys admitted less than `duration` ago from being sampled for eviction, as per the `clock`.
+/// It maintains the time of admission of every key by its key_id, outside `WeightedKey`, so the weight of the keys does not change.
+pub(crate) struct MinResidency {
+    duration: Duration,
+    clock: ClockType,
+    admitted_at: DashMap<KeyId, SystemTime>,
+}
+
+impl MinResidency {
+    pub(crate) fn new(duration: Duration, clock: ClockType) -> Self {
+        MinResidency { duration, clock, admitted_at: DashMap::new() }
+    }
+
+    fn admitted(&self, key_id: KeyId) {
+        self.admitted_at.insert(key_id, self.clock.now());
+    }
+
+    fn removed(&self, key_id: &KeyId) {
+        self.admitted_at.remove(key_id);
+    }
+
+    fn is_young(&self, key_id: &KeyId) -> bool {
+        self.admitted_at
+            .get(key_id)
+            .map(|admitted_at| !self.clock.has_passed(&(*admitted_at + self.duration)))
+            .unwrap_or(false)
+    }
+}
 
 /// WeightedKey maintains the key, its hash and its weight. It is used as a value type in the DashMap used inside `CacheWeight`
 pub(crate) struct WeightedKey<Key> {
@@ -30,18 +67,25 @@ impl<Key> WeightedKey<Key> {
     }
 }
 
-/// SampledKey represents a key with its id, weight and estimated frequency.
+/// SampledKey represents a key with its id, weight, estimated frequency and the optional expiry.
 /// A collection of `SampledKey` is returned by `FrequencyCounterBasedMinHeapSamples` when a sample is requested during the admission of a key
+///
+/// The key with the smallest estimated frequency is evicted first. Amongst the keys with the same estimated frequency,
+/// the key that expires the soonest is evicted first (keys without expiry are evicted last), followed by the key with the largest weight.
+/// The expiry is only known if the cache prefers evicting the keys with `time_to_live`, else all the keys are treated as never expiring.
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct SampledKey {
     pub(crate) id: KeyId,
     pub(crate) weight: Weight,
     pub(crate) estimated_frequency: FrequencyEstimate,
+    pub(crate) expire_after: Option<ExpireAfter>,
 }
 
 impl Ord for SampledKey {
     fn cmp(&self, other: &Self) -> Ordering {
-        (other.estimated_frequency, self.weight).cmp(&(self.estimated_frequency, other.weight))
+        other.estimated_frequency.cmp(&self.estimated_frequency)
+            .then_with(|| other.expiry_order().cmp(&self.expiry_order()))
+            .then_with(|| self.weight.cmp(&other.weight))
     }
 }
 
@@ -60,22 +104,30 @@ impl PartialEq for SampledKey {
 impl Eq for SampledKey {}
 
 impl SampledKey {
-    pub(crate) fn new<Key>(frequency: FrequencyEstimate, pair: RefMulti<KeyId, WeightedKey<Key>>) -> Self <> {
-        Self::using(*pair.key(), pair.weight, frequency)
+    pub(crate) fn new<Key>(frequency: FrequencyEstimate, expire_after: Option<ExpireAfter>, pair: RefMulti<KeyId, WeightedKey<Key>>) -> Self <> {
+        Self::using(*pair.key(), pair.weight, frequency, expire_after)
     }
 
-    fn using(id: KeyId, key_weight: Weight, frequency: FrequencyEstimate) -> Self <> {
+    fn using(id: KeyId, key_weight: Weight, frequency: FrequencyEstimate, expire_after: Option<ExpireAfter>) -> Self <> {
         SampledKey {
             id,
             weight: key_weight,
             estimated_frequency: frequency,
+            expire_after,
         }
     }
+
+    /// Orders the keys without expiry after the keys with expiry.
+    fn expiry_order(&self) -> (bool, Option<ExpireAfter>) {
+        (self.expire_after.is_none(), self.expire_after)
+    }
 }
 
 /// FrequencyCounterBasedMinHeapSamples returns a sample to the `create_space` method of `crate::cache::policy::admission_policy::AdmissionPolicy`
 /// The idea is to return a sample and allow getting the key with the smallest access frequency.
 /// Internally, `FrequencyCounterBasedMinHeapSamples` uses [`std::collections::BinaryHeap`] and returns `SampledKey` that contains the key_id, its weight and its access frequency.
+///
+/// The keys younger than the `min_residency` (if any) are never sampled, so the sample may be smaller than `sample_size` (or empty).
 pub(crate) struct FrequencyCounterBasedMinHeapSamples<'a, Key, Freq>
     where Freq: Fn(KeyHash) -> FrequencyEstimate {
     source: &'a DashMap<KeyId, WeightedKey<Key>>,
@@ -83,21 +135,35 @@ pub(crate) struct FrequencyCounterBasedMinHeapSamples<'a, Key, Freq>
     current_sample_key_ids: HashSet<KeyId>,
     sample_size: usize,
     frequency_counter: Freq,
+    expiry_lookup: Option<&'a ExpiryLookupFn<Key>>,
+    min_residency: Option<&'a MinResidency>,
 }
 
 impl<'a, Key, Freq> FrequencyCounterBasedMinHeapSamples<'a, Key, Freq>
     where Freq: Fn(KeyHash) -> FrequencyEstimate {
+    #[cfg(test)]
     fn new(
         source: &'a DashMap<KeyId, WeightedKey<Key>>,
         sample_size: usize,
         frequency_counter: Freq) -> Self <> {
-        let (sample, current_sample_key_ids) = Self::initial_sample(source, sample_size, &frequency_counter);
+        Self::with_preferences(source, sample_size, frequency_counter, None, None)
+    }
+
+    fn with_preferences(
+        source: &'a DashMap<KeyId, WeightedKey<Key>>,
+        sample_size: usize,
+        frequency_counter: Freq,
+        expiry_lookup: Option<&'a ExpiryLookupFn<Key>>,
+        min_residency: Option<&'a MinResidency>) -> Self <> {
+        let (sample, current_sample_key_ids) = Self::initial_sample(source, sample_size, &frequency_counter, expiry_lookup, min_residency);
         FrequencyCounterBasedMinHeapSamples {
             source,
             sample,
             current_sample_key_ids,
             sample_size,
             frequency_counter,
+            expiry_lookup,
+            min_residency,
         }
     }
 
@@ -128,10 +194,11 @@ impl<'a, Key, Freq> FrequencyCounterBasedMinHeapSamples<'a, Key, Freq>
         while self.sample.len() < self.sample_size {
             match iterator.next() {
                 Some(pair) => {
-                    if !self.current_sample_key_ids.contains(pair.key()) {
+                    if !self.current_sample_key_ids.contains(pair.key()) && !Self::is_young(self.min_residency, pair.key()) {
                         let frequency = (self.frequency_counter)(pair.key_hash);
+                        let expire_after = Self::expire_after(self.expiry_lookup, pair.value());
                         self.current_sample_key_ids.insert(*pair.key());
-                        self.sample.push(SampledKey::new(frequency, pair));
+                        self.sample.push(SampledKey::new(frequency, expire_after, pair));
                         filled_in = true;
                     }
                 }
@@ -143,6 +210,7 @@ impl<'a, Key, Freq> FrequencyCounterBasedMinHeapSamples<'a, Key, Freq>
         filled_in
     }
 
+    #[cfg(test)]
     pub(crate) fn size(&self) -> usize {
         self.sample.len()
     }
@@ -151,14 +219,20 @@ impl<'a, Key, Freq> FrequencyCounterBasedMinHeapSamples<'a, Key, Freq>
     fn initial_sample(
         source: &DashMap<KeyId, WeightedKey<Key>>,
         sample_size: usize,
-        frequency_counter: &Freq) -> (BinaryHeap<SampledKey>, HashSet<KeyId>) {
+        frequency_counter: &Freq,
+        expiry_lookup: Option<&ExpiryLookupFn<Key>>,
+        min_residency: Option<&MinResidency>) -> (BinaryHeap<SampledKey>, HashSet<KeyId>) {
         let mut counter = 0;
         let mut sample = BinaryHeap::new();
         let mut current_sample_key_ids = HashSet::new();
 
         for pair in source.iter().by_ref() {
+            if Self::is_young(min_residency, pair.key()) {
+                continue;
+            }
             current_sample_key_ids.insert(*pair.key());
-            sample.push(SampledKey::new(frequency_counter(pair.value().key_hash), pair));
+            let expire_after = Self::expire_after(expiry_lookup, pair.value());
+            sample.push(SampledKey::new(frequency_counter(pair.value().key_hash), expire_after, pair));
             counter += 1;
 
             if counter >= sample_size {
@@ -167,6 +241,14 @@ impl<'a, Key, Freq> FrequencyCounterBasedMinHeapSamples<'a, Key, Freq>
         }
         (sample, current_sample_key_ids)
     }
+
+    fn expire_after(expiry_lookup: Option<&ExpiryLookupFn<Key>>, weighted_key: &WeightedKey<Key>) -> Option<ExpireAfter> {
+        expiry_lookup.and_then(|expiry_lookup| expiry_lookup(&weighted_key.key))
+    }
+
+    fn is_young(min_residency: Option<&MinResidency>, key_id: &KeyId) -> bool {
+        min_residency.map(|min_residency| min_residency.is_young(key_id)).unwrap_or(false)
+    }
 }
 
 /// CacheWeight maintains the weight of each key in the Cache and also manages the weight that is used in the cache.
@@ -174,12 +256,18 @@ impl<'a, Key, Freq> FrequencyCounterBasedMinHeapSamples<'a, Key, Freq>
 /// Every time a key is added in the cache, it is also added in `CacheWeight`, there by increasing the total weight of the cache.
 /// Every time a key is updated, an attempt is made to update its weight, there by changing the total weight of the cache.
 /// Every time a key is deleted, it is also deleted from `CacheWeight`, there by decreasing the total weight of the cache.
+/// `CacheWeight` also maintains the weight reserved by `crate::cache::cached::Reservation`, the reserved weight is not available to the other keys.
 pub(crate) struct CacheWeight<Key>
     where Key: Hash + Eq + Send + Sync + Clone + 'static, {
     max_weight: Weight,
     weight_used: RwLock<Weight>,
+    reserved_weight: RwLock<Weight>,
     key_weights: DashMap<KeyId, WeightedKey<Key>>,
     stats_counter: Arc<ConcurrentStatsCounter>,
+    expiry_lookup: Option<Box<ExpiryLookupFn<Key>>>,
+    audit_key_fn: Option<Arc<AuditKeyFn<Key>>>,
+    readmission_tracker: Option<ReadmissionTracker<Key>>,
+    min_residency: Option<MinResidency>,
 }
 
 impl<Key> CacheWeight<Key>
@@ -189,11 +277,42 @@ impl<Key> CacheWeight<Key>
         CacheWeight {
             max_weight: cache_weight_config.total_cache_weight(),
             weight_used: RwLock::new(0),
+            reserved_weight: RwLock::new(0),
             key_weights: DashMap::with_capacity_and_shard_amount(cache_weight_config.capacity(), cache_weight_config.shards()),
             stats_counter,
+            expiry_lookup: None,
+            audit_key_fn: None,
+            readmission_tracker: None,
+            min_residency: None,
         }
     }
 
+    /// Sets the function that returns the expiry of a key, so that the samples prefer evicting the keys that expire the soonest.
+    pub(crate) fn prefer_evicting_expiring_keys(&mut self, expiry_lookup: Box<ExpiryLookupFn<Key>>) {
+        self.expiry_lookup = Some(expiry_lookup);
+    }
+
+    /// Sets the function that formats a key for the audit log, so that every removal of a key is logged with the key, the reason and the weight reclaimed.
+    pub(crate) fn audit_removals(&mut self, audit_key_fn: Arc<AuditKeyFn<Key>>) {
+        self.audit_key_fn = Some(audit_key_fn);
+    }
+
+    /// Sets the tracker that detects the keys admitted again after they were evicted to create space.
+    pub(crate) fn track_readmissions(&mut self, readmission_tracker: ReadmissionTracker<Key>) {
+        self.readmission_tracker = Some(readmission_tracker);
+    }
+
+    /// Sets the `MinResidency`, so that the keys admitted less than `MinResidency` ago are not sampled for eviction.
+    pub(crate) fn protect_young_keys(&mut self, min_residency: MinResidency) {
+        self.min_residency = Some(min_residency);
+    }
+
+    /// Returns the ratio of the readmissions to the admissions, read `crate::cache::policy::readmission::ReadmissionTracker`.
+    /// Returns 0 if the readmissions are not tracked.
+    pub(crate) fn thrash_rate(&self) -> f64 {
+        self.readmission_tracker.as_ref().map(|tracker| tracker.thrash_rate()).unwrap_or(0.0)
+    }
+
     pub(crate) fn get_max_weight(&self) -> Weight {
         self.max_weight
     }
@@ -202,17 +321,50 @@ impl<Key> CacheWeight<Key>
         *self.weight_used.read()
     }
 
+    pub(crate) fn get_reserved_weight(&self) -> Weight {
+        *self.reserved_weight.read()
+    }
+
     pub(crate) fn is_space_available_for(&self, weight: Weight) -> (Weight, bool) {
-        let available = self.max_weight - (*self.weight_used.read());
+        let available = self.max_weight - self.get_weight_used() - self.get_reserved_weight();
         (available, available >= weight)
     }
 
+    /// Reserves the `weight` if it is available, the reserved weight is consumed by `add_reserved` or returned by `release_reservation`.
+    pub(crate) fn reserve(&self, weight: Weight) -> bool {
+        let mut guard = self.reserved_weight.write();
+        let available = self.max_weight - self.get_weight_used() - *guard;
+        if available < weight {
+            return false;
+        }
+        *guard += weight;
+        true
+    }
+
+    /// Releases the reserved `weight`.
+    pub(crate) fn release_reservation(&self, weight: Weight) {
+        let mut guard = self.reserved_weight.write();
+        *guard -= weight;
+    }
+
+    /// Adds the key using the weight that was reserved for it.
+    pub(crate) fn add_reserved(&self, key_description: &KeyDescription<Key>) {
+        self.add(key_description);
+        self.release_reservation(key_description.weight);
+    }
+
     pub(crate) fn add(&self, key_description: &KeyDescription<Key>) {
         self.key_weights.insert(key_description.id, WeightedKey::new(key_description.clone_key(), key_description.hash, key_description.weight));
+        if let Some(min_residency) = &self.min_residency {
+            min_residency.admitted(key_description.id);
+        }
         let mut guard = self.weight_used.write();
         *guard += key_description.weight;
 
         self.stats_counter.add_weight(key_description.weight as u64);
+        if let Some(readmission_tracker) = &self.readmission_tracker {
+            readmission_tracker.admitted(key_description.key(), key_description.hash);
+        }
     }
 
     pub(crate) fn update(&self, key_id: &KeyId, weight: Weight) -> bool {
@@ -233,15 +385,24 @@ impl<Key> CacheWeight<Key>
         false
     }
 
-    pub(crate) fn delete<DeleteHook>(&self, key_id: &KeyId, delete_hook: &DeleteHook)
-        where DeleteHook: Fn(Key) {
+    /// Deletes the key and returns it, so that the caller can remove the key from the `crate::cache::store::Store` (and notify the eviction listener)
+    /// after it releases its own locks. The lock on the weight used is released before `delete` returns.
+    pub(crate) fn delete(&self, key_id: &KeyId, reason: EvictionReason) -> Option<Key> {
         if let Some(weight_by_key_hash) = self.key_weights.remove(key_id) {
-            let mut guard = self.weight_used.write();
-            *guard -= weight_by_key_hash.1.weight;
-            delete_hook(weight_by_key_hash.1.key);
-
+            *self.weight_used.write() -= weight_by_key_hash.1.weight;
+            if let Some(audit_key_fn) = &self.audit_key_fn {
+                audit_removal(&audit_key_fn(&weight_by_key_hash.1.key), *key_id, reason, weight_by_key_hash.1.weight);
+            }
+            if let (Some(readmission_tracker), EvictionReason::Evicted) = (&self.readmission_tracker, reason) {
+                readmission_tracker.evicted(weight_by_key_hash.1.key_hash);
+            }
+            if let Some(min_residency) = &self.min_residency {
+                min_residency.removed(key_id);
+            }
             self.stats_counter.remove_weight(weight_by_key_hash.1.weight as u64);
+            return Some(weight_by_key_hash.1.key);
         }
+        None
     }
 
     pub(crate) fn contains(&self, key_id: &KeyId) -> bool {
@@ -252,15 +413,31 @@ impl<Key> CacheWeight<Key>
         self.key_weights.get(key_id).map(|pair| pair.weight)
     }
 
+    pub(crate) fn key_of(&self, key_id: &KeyId) -> Option<Key> {
+        self.key_weights.get(key_id).map(|pair| pair.key.clone())
+    }
+
     pub(crate) fn sample<Freq>(&self, size: usize, frequency_counter: Freq)
                                -> FrequencyCounterBasedMinHeapSamples<'_, Key, Freq>
         where Freq: Fn(KeyHash) -> FrequencyEstimate {
-        FrequencyCounterBasedMinHeapSamples::new(&self.key_weights, size, frequency_counter)
+        FrequencyCounterBasedMinHeapSamples::with_preferences(
+            &self.key_weights,
+            size,
+            frequency_counter,
+            self.expiry_lookup.as_deref(),
+            self.min_residency.as_ref(),
+        )
     }
 
-    pub(crate) fn clear(&self) {
+    /// Removes all the keys, retaining the reserved weight, and records the weight of the removed keys as the removed weight.
+    /// The reserved weight is retained because the outstanding `crate::cache::cached::Reservation`s continue to use it, and release it when they are dropped.
+    pub(crate) fn remove_all(&self) {
         self.key_weights.clear();
+        if let Some(min_residency) = &self.min_residency {
+            min_residency.admitted_at.clear();
+        }
         let mut guard = self.weight_used.write();
+        self.stats_counter.remove_weight(*guard as u64);
         *guard = 0;
     }
 
@@ -279,17 +456,12 @@ impl<Key> CacheWeight<Key>
 mod tests {
     use std::sync::Arc;
 
-    use parking_lot::RwLock;
-
+    use crate::cache::config::EvictionReason;
     use crate::cache::key_description::KeyDescription;
     use crate::cache::policy::cache_weight::CacheWeight;
     use crate::cache::policy::config::CacheWeightConfig;
     use crate::cache::stats::ConcurrentStatsCounter;
 
-    struct DeletedKeys<Key> {
-        keys: RwLock<Vec<Key>>,
-    }
-
     fn test_cache_weight_config() -> CacheWeightConfig {
         CacheWeightConfig::new(100, 4, 10)
     }
@@ -316,6 +488,47 @@ mod tests {
         assert!(!cache_weight.is_space_available_for(8).1);
     }
 
+    #[test]
+    fn reserve_the_available_weight() {
+        let cache_weight: CacheWeight<&str> = CacheWeight::new(test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        cache_weight.add(&KeyDescription::new("disk", 1, 3040, 3));
+
+        assert!(cache_weight.reserve(5));
+        assert_eq!(5, cache_weight.get_reserved_weight());
+        assert!(!cache_weight.is_space_available_for(3).1);
+        assert!(cache_weight.is_space_available_for(2).1);
+    }
+
+    #[test]
+    fn reserve_more_than_the_available_weight() {
+        let cache_weight: CacheWeight<&str> = CacheWeight::new(test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        cache_weight.add(&KeyDescription::new("disk", 1, 3040, 3));
+
+        assert!(cache_weight.reserve(5));
+        assert!(!cache_weight.reserve(3));
+        assert_eq!(5, cache_weight.get_reserved_weight());
+    }
+
+    #[test]
+    fn add_a_key_with_the_reserved_weight() {
+        let cache_weight: CacheWeight<&str> = CacheWeight::new(test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        assert!(cache_weight.reserve(5));
+
+        cache_weight.add_reserved(&KeyDescription::new("disk", 1, 3040, 3));
+        assert_eq!(3, cache_weight.get_weight_used());
+        assert_eq!(2, cache_weight.get_reserved_weight());
+    }
+
+    #[test]
+    fn release_the_reserved_weight() {
+        let cache_weight: CacheWeight<&str> = CacheWeight::new(test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        assert!(cache_weight.reserve(5));
+
+        cache_weight.release_reservation(5);
+        assert_eq!(0, cache_weight.get_reserved_weight());
+        assert!(cache_weight.is_space_available_for(10).1);
+    }
+
     #[test]
     fn add_key_weight() {
         let cache_weight = CacheWeight::new(test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
@@ -424,11 +637,9 @@ mod tests {
         cache_weight.add(&KeyDescription::new("disk", 1, 3040, 3));
         assert_eq!(3, cache_weight.get_weight_used());
 
-        let deleted_keys = DeletedKeys { keys: RwLock::new(Vec::new()) };
-        let delete_hook = |key| { deleted_keys.keys.write().push(key) };
-        cache_weight.delete(&1, &delete_hook);
+        let deleted_key = cache_weight.delete(&1, EvictionReason::Explicit);
 
-        assert_eq!(vec!["disk"], *deleted_keys.keys.read());
+        assert_eq!(Some("disk"), deleted_key);
         assert_eq!(0, cache_weight.get_weight_used());
         assert!(!cache_weight.contains(&1));
     }
@@ -440,32 +651,50 @@ mod tests {
         cache_weight.add(&KeyDescription::new("disk", 1, 3040, 3));
         assert_eq!(3, cache_weight.get_weight_used());
 
-        let delete_hook = |_| {};
-        cache_weight.delete(&1, &delete_hook);
+        cache_weight.delete(&1, EvictionReason::Explicit);
 
         assert_eq!(3, cache_weight.stats_counter.weight_removed())
     }
 
     #[test]
-    fn clear() {
+    fn remove_all() {
         let cache_weight = CacheWeight::new(test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
         cache_weight.add(&KeyDescription::new("disk", 1, 3040, 3));
 
         assert_eq!(3, cache_weight.get_weight_used());
         assert!(cache_weight.contains(&1));
 
-        cache_weight.clear();
+        cache_weight.remove_all();
 
         assert_eq!(0, cache_weight.get_weight_used());
         assert!(!cache_weight.contains(&1));
     }
+
+    #[test]
+    fn remove_all_retains_the_reserved_weight() {
+        let cache_weight = CacheWeight::new(test_cache_weight_config(), Arc::new(ConcurrentStatsCounter::new()));
+        assert!(cache_weight.reserve(4));
+        cache_weight.add_reserved(&KeyDescription::new("disk", 1, 3040, 3));
+
+        cache_weight.remove_all();
+
+        assert_eq!(0, cache_weight.get_weight_used());
+        assert_eq!(1, cache_weight.get_reserved_weight());
+
+        cache_weight.release_reservation(1);
+        assert_eq!(0, cache_weight.get_reserved_weight());
+    }
 }
 
 #[cfg(test)]
 mod frequency_counter_based_min_heap_samples_tests {
+    use std::ops::{Add, Sub};
+    use std::time::{Duration, SystemTime};
+
     use dashmap::DashMap;
 
-    use crate::cache::policy::cache_weight::{FrequencyCounterBasedMinHeapSamples, SampledKey, WeightedKey};
+    use crate::cache::clock::SystemClock;
+    use crate::cache::policy::cache_weight::{ExpiryLookupFn, FrequencyCounterBasedMinHeapSamples, MinResidency, SampledKey, WeightedKey};
     use crate::cache::types::KeyId;
 
     #[test]
@@ -475,7 +704,7 @@ mod frequency_counter_based_min_heap_samples_tests {
 
         let mut sampled_keys = Vec::new();
         for pair in cache.iter().by_ref() {
-            sampled_keys.push(SampledKey::new(10, pair));
+            sampled_keys.push(SampledKey::new(10, None, pair));
         }
 
         assert_eq!(sampled_keys[0], sampled_keys[0]);
@@ -655,4 +884,61 @@ mod frequency_counter_based_min_heap_samples_tests {
         assert_eq!(2, sampled_key.weight);
         assert_eq!(20, sampled_key.id);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn sample_keys_with_same_frequencies_and_different_expiry() {
+        let cache: DashMap<KeyId, WeightedKey<&str>> = DashMap::new();
+        cache.insert(10, WeightedKey::new("disk", 3040, 5));
+        cache.insert(20, WeightedKey::new("topic", 1090, 2));
+        cache.insert(30, WeightedKey::new("SSD", 1290, 3));
+        cache.insert(40, WeightedKey::new("HDD", 1390, 1));
+
+        let now = SystemTime::now();
+        let expiry_lookup: Box<ExpiryLookupFn<&str>> = Box::new(move |key| {
+            match *key {
+                "topic" => Some(now.add(Duration::from_secs(300))),
+                "SSD" => Some(now.add(Duration::from_secs(10))),
+                "HDD" => Some(now.add(Duration::from_secs(5))),
+                _ => None
+            }
+        });
+        let mut sample = FrequencyCounterBasedMinHeapSamples::with_preferences(
+            &cache,
+            4,
+            |hash| {
+                match hash {
+                    1390 => 2,
+                    _ => 1
+                }
+            },
+            Some(expiry_lookup.as_ref()),
+            None,
+        );
+
+        assert_eq!(30, sample.min_frequency_key().unwrap().id);
+        assert_eq!(20, sample.min_frequency_key().unwrap().id);
+        assert_eq!(10, sample.min_frequency_key().unwrap().id);
+        assert_eq!(40, sample.min_frequency_key().unwrap().id);
+    }
+
+    #[test]
+    fn sample_skips_the_keys_younger_than_the_min_residency() {
+        let cache: DashMap<KeyId, WeightedKey<&str>> = DashMap::new();
+        cache.insert(10, WeightedKey::new("disk", 3040, 5));
+        cache.insert(20, WeightedKey::new("topic", 1090, 2));
+        cache.insert(30, WeightedKey::new("SSD", 1290, 3));
+
+        let min_residency = MinResidency::new(Duration::from_secs(300), SystemClock::boxed());
+        min_residency.admitted(10);
+        min_residency.admitted_at.insert(20, SystemTime::now().sub(Duration::from_secs(600)));
+        min_residency.admitted(30);
+        let mut sample = FrequencyCounterBasedMinHeapSamples::with_preferences(&cache, 3, |_hash| 1, None, Some(&min_residency));
+
+        assert_eq!(1, sample.size());
+        assert_eq!(20, sample.min_frequency_key().unwrap().id);
+
+        cache.remove(&20);
+        assert!(!sample.maybe_fill_in());
+        assert!(sample.min_frequency_key().is_none());
+    }
+}
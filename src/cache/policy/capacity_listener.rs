@@ -0,0 +1,164 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::cache::config::CapacityListenerFn;
+use crate::cache::types::Weight;
+
+/// Once the cache is full, it is considered to have headroom again only when the weight used drops to (or below) `HEADROOM_PERCENTAGE` of the
+/// maximum cache weight.
+/// This keeps the listeners from firing repeatedly while the weight used hovers around the maximum cache weight.
+const HEADROOM_PERCENTAGE: Weight = 90;
+
+/// CapacityListener invokes `on_full` when the cache becomes full and `on_has_headroom` when the cache regains headroom.
+/// The cache is full either when the weight used reaches the maximum cache weight or when an incoming key does not fit in the space left.
+/// The listeners are invoked only on a transition between the states, so `on_full` is not invoked again until `on_has_headroom` is invoked.
+pub(crate) struct CapacityListener {
+    on_full: Option<Arc<CapacityListenerFn>>,
+    on_has_headroom: Option<Arc<CapacityListenerFn>>,
+    is_full: AtomicBool,
+}
+
+impl CapacityListener {
+    pub(crate) fn new(on_full: Option<Arc<CapacityListenerFn>>, on_has_headroom: Option<Arc<CapacityListenerFn>>) -> Self {
+        CapacityListener {
+            on_full,
+            on_has_headroom,
+            is_full: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn full(&self) {
+        if !self.is_full.swap(true, Ordering::AcqRel) {
+            if let Some(on_full) = &self.on_full {
+                on_full();
+            }
+        }
+    }
+
+    /// Does not check for headroom, since an addition that evicts other keys to make space leaves the cache full.
+    pub(crate) fn weight_added(&self, weight_used: Weight, max_weight: Weight) {
+        if weight_used >= max_weight {
+            self.full();
+        }
+    }
+
+    pub(crate) fn weight_changed(&self, weight_used: Weight, max_weight: Weight) {
+        if weight_used >= max_weight {
+            self.full();
+        } else if weight_used * 100 <= max_weight * HEADROOM_PERCENTAGE {
+            self.has_headroom();
+        }
+    }
+
+    fn has_headroom(&self) {
+        if self.is_full.swap(false, Ordering::AcqRel) {
+            if let Some(on_has_headroom) = &self.on_has_headroom {
+                on_has_headroom();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::cache::policy::capacity_listener::CapacityListener;
+
+    fn counting_listener() -> (CapacityListener, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let full_count = Arc::new(AtomicUsize::new(0));
+        let headroom_count = Arc::new(AtomicUsize::new(0));
+
+        let on_full_count = full_count.clone();
+        let on_headroom_count = headroom_count.clone();
+        let listener = CapacityListener::new(
+            Some(Arc::new(move || { on_full_count.fetch_add(1, Ordering::SeqCst); })),
+            Some(Arc::new(move || { on_headroom_count.fetch_add(1, Ordering::SeqCst); })),
+        );
+        (listener, full_count, headroom_count)
+    }
+
+    #[test]
+    fn invokes_on_full_once_the_weight_used_reaches_the_max_weight() {
+        let (listener, full_count, headroom_count) = counting_listener();
+
+        listener.weight_changed(10, 10);
+        assert_eq!(1, full_count.load(Ordering::SeqCst));
+        assert_eq!(0, headroom_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn invokes_on_full_only_once_until_headroom_returns() {
+        let (listener, full_count, _) = counting_listener();
+
+        listener.full();
+        listener.weight_changed(10, 10);
+        listener.full();
+
+        assert_eq!(1, full_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn does_not_invoke_on_has_headroom_on_weight_added() {
+        let (listener, full_count, headroom_count) = counting_listener();
+
+        listener.full();
+        listener.weight_added(20, 100);
+
+        assert_eq!(1, full_count.load(Ordering::SeqCst));
+        assert_eq!(0, headroom_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn does_not_invoke_on_has_headroom_if_the_cache_was_not_full() {
+        let (listener, _, headroom_count) = counting_listener();
+
+        listener.weight_changed(2, 10);
+        assert_eq!(0, headroom_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn does_not_invoke_on_has_headroom_while_the_weight_used_hovers_near_the_max_weight() {
+        let (listener, full_count, headroom_count) = counting_listener();
+
+        listener.weight_changed(100, 100);
+        listener.weight_changed(95, 100);
+        listener.weight_changed(100, 100);
+
+        assert_eq!(1, full_count.load(Ordering::SeqCst));
+        assert_eq!(0, headroom_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn invokes_on_has_headroom_once_the_weight_used_drops_enough() {
+        let (listener, full_count, headroom_count) = counting_listener();
+
+        listener.weight_changed(100, 100);
+        listener.weight_changed(90, 100);
+        listener.weight_changed(80, 100);
+
+        assert_eq!(1, full_count.load(Ordering::SeqCst));
+        assert_eq!(1, headroom_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn invokes_on_full_again_after_headroom_returns() {
+        let (listener, full_count, headroom_count) = counting_listener();
+
+        listener.full();
+        listener.weight_changed(0, 100);
+        listener.full();
+
+        assert_eq!(2, full_count.load(Ordering::SeqCst));
+        assert_eq!(1, headroom_count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn tracks_the_state_without_listeners() {
+        let listener = CapacityListener::new(None, None);
+
+        listener.full();
+        listener.weight_changed(0, 100);
+    }
+}
@@ -1,3 +1,7 @@
+use std::sync::Arc;
+
+use crate::cache::config::CapacityListenerFn;
+use crate::cache::policy::capacity_listener::CapacityListener;
 use crate::cache::types::{TotalCapacity, TotalShards, Weight};
 
 /// CacheWeightConfig defines the following:
@@ -5,10 +9,13 @@ use crate::cache::types::{TotalCapacity, TotalShards, Weight};
 ///             it defines the total number of keys and their weight which may be a part of the DashMap
 /// `shards`:   is used as a `shard` parameter for the DashMap used inside [`crate::cache::policy::cache_weight::CacheWeight`]
 /// `total_cache_weight`: defines the maximum weight of the cache and is used inside [`crate::cache::policy::cache_weight::CacheWeight`]
+/// `on_full` and `on_has_headroom`: are the listeners used inside [`crate::cache::policy::capacity_listener::CapacityListener`]
 pub(crate) struct CacheWeightConfig {
     capacity: TotalCapacity,
     shards: TotalShards,
-    total_cache_weight: Weight
+    total_cache_weight: Weight,
+    on_full: Option<Arc<CapacityListenerFn>>,
+    on_has_headroom: Option<Arc<CapacityListenerFn>>,
 }
 
 impl CacheWeightConfig {
@@ -16,15 +23,30 @@ impl CacheWeightConfig {
         CacheWeightConfig {
             capacity,
             shards,
-            total_cache_weight
+            total_cache_weight,
+            on_full: None,
+            on_has_headroom: None,
         }
     }
 
+    pub(crate) fn with_capacity_listeners(
+        mut self,
+        on_full: Option<Arc<CapacityListenerFn>>,
+        on_has_headroom: Option<Arc<CapacityListenerFn>>) -> Self {
+        self.on_full = on_full;
+        self.on_has_headroom = on_has_headroom;
+        self
+    }
+
     pub(crate) fn capacity(&self) -> TotalCapacity { self.capacity }
 
     pub(crate) fn shards(&self) -> TotalShards { self.shards }
 
     pub(crate) fn total_cache_weight(&self) -> Weight { self.total_cache_weight }
+
+    pub(crate) fn capacity_listener(&self) -> CapacityListener {
+        CapacityListener::new(self.on_full.clone(), self.on_has_headroom.clone())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,13 @@
+use log::info;
+
+use crate::cache::config::{AUDIT_LOG_TARGET, EvictionReason};
+use crate::cache::types::{KeyId, Weight};
+
+/// Defines the function that formats a key for the audit log.
+pub(crate) type AuditKeyFn<Key> = dyn Fn(&Key) -> String + Send + Sync;
+
+/// Logs the removal of a key from the cache, at info level with the target `AUDIT_LOG_TARGET`.
+pub(crate) fn audit_removal(formatted_key: &str, key_id: KeyId, reason: EvictionReason, weight: Weight) {
+    info!(target: AUDIT_LOG_TARGET, "key {} with id {} {}, weight reclaimed {}", formatted_key, key_id, reason, weight);
+}
+
@@ -1,3 +1,6 @@
 pub(crate) mod admission_policy;
 pub(crate) mod cache_weight;
-pub(crate) mod config;
\ No newline at end of file
+pub(crate) mod capacity_listener;
+pub(crate) mod config;
+pub(crate) mod eviction_audit;
+pub(crate) mod readmission;
\ No newline at end of file
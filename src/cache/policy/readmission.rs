@@ -0,0 +1,158 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::Mutex;
+
+use crate::cache::config::ReadmissionListenerFn;
+use crate::cache::types::KeyHash;
+
+/// The number of the recently evicted key hashes that `ReadmissionTracker` remembers.
+pub(crate) const RECENTLY_EVICTED_CAPACITY: usize = 1024;
+
+/// ReadmissionTracker detects the keys that are admitted again after they were evicted to create space, a sign of thrashing.
+///
+/// It remembers the hashes of the last `RECENTLY_EVICTED_CAPACITY` keys evicted for capacity in a ring buffer, so its memory is bounded
+/// irrespective of the number of evictions. An eviction older than the last `RECENTLY_EVICTED_CAPACITY` evictions is forgotten.
+/// An admitted key whose hash is in the ring buffer is a readmission: `on_readmission` is invoked with the key, and the hash is forgotten.
+///
+/// The `thrash_rate` is the ratio of the readmissions to the admissions.
+pub(crate) struct ReadmissionTracker<Key> {
+    recently_evicted: Mutex<RecentlyEvicted>,
+    on_readmission: Arc<ReadmissionListenerFn<Key>>,
+    admissions: AtomicU64,
+    readmissions: AtomicU64,
+}
+
+/// RecentlyEvicted is a ring buffer of the key hashes, along with the count of each key hash in the ring buffer for a constant time lookup.
+struct RecentlyEvicted {
+    key_hashes: VecDeque<KeyHash>,
+    counts: HashMap<KeyHash, usize>,
+    capacity: usize,
+}
+
+impl RecentlyEvicted {
+    fn new(capacity: usize) -> Self {
+        RecentlyEvicted {
+            key_hashes: VecDeque::with_capacity(capacity),
+            counts: HashMap::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn add(&mut self, key_hash: KeyHash) {
+        if self.key_hashes.len() == self.capacity {
+            if let Some(oldest) = self.key_hashes.pop_front() {
+                self.forget(oldest);
+            }
+        }
+        self.key_hashes.push_back(key_hash);
+        *self.counts.entry(key_hash).or_insert(0) += 1;
+    }
+
+    /// Removes the most recent occurrence of the key_hash and returns true if the key_hash was present.
+    fn remove(&mut self, key_hash: KeyHash) -> bool {
+        if !self.counts.contains_key(&key_hash) {
+            return false;
+        }
+        if let Some(position) = self.key_hashes.iter().rposition(|existing| *existing == key_hash) {
+            self.key_hashes.remove(position);
+        }
+        self.forget(key_hash);
+        true
+    }
+
+    fn forget(&mut self, key_hash: KeyHash) {
+        if let Some(count) = self.counts.get_mut(&key_hash) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&key_hash);
+            }
+        }
+    }
+}
+
+impl<Key> ReadmissionTracker<Key> {
+    pub(crate) fn new(on_readmission: Arc<ReadmissionListenerFn<Key>>) -> Self {
+        Self::with_capacity(on_readmission, RECENTLY_EVICTED_CAPACITY)
+    }
+
+    fn with_capacity(on_readmission: Arc<ReadmissionListenerFn<Key>>, capacity: usize) -> Self {
+        ReadmissionTracker {
+            recently_evicted: Mutex::new(RecentlyEvicted::new(capacity)),
+            on_readmission,
+            admissions: AtomicU64::new(0),
+            readmissions: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn evicted(&self, key_hash: KeyHash) {
+        self.recently_evicted.lock().add(key_hash);
+    }
+
+    pub(crate) fn admitted(&self, key: &Key, key_hash: KeyHash) {
+        self.admissions.fetch_add(1, Ordering::AcqRel);
+        let is_readmission = self.recently_evicted.lock().remove(key_hash);
+        if is_readmission {
+            self.readmissions.fetch_add(1, Ordering::AcqRel);
+            (self.on_readmission)(key);
+        }
+    }
+
+    pub(crate) fn thrash_rate(&self) -> f64 {
+        let admissions = self.admissions.load(Ordering::Acquire);
+        if admissions == 0 {
+            return 0.0;
+        }
+        self.readmissions.load(Ordering::Acquire) as f64 / admissions as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+
+    use crate::cache::policy::readmission::ReadmissionTracker;
+
+    #[test]
+    fn readmission_of_an_evicted_key() {
+        let readmitted = Arc::new(Mutex::new(Vec::new()));
+        let tracker = ReadmissionTracker::new(Arc::new({
+            let readmitted = readmitted.clone();
+            move |key: &&str| readmitted.lock().push(*key)
+        }));
+
+        tracker.admitted(&"topic", 10);
+        tracker.evicted(10);
+        tracker.admitted(&"topic", 10);
+        tracker.admitted(&"disk", 20);
+
+        assert_eq!(vec!["topic"], *readmitted.lock());
+        assert_eq!(1.0 / 3.0, tracker.thrash_rate());
+    }
+
+    #[test]
+    fn forgets_the_oldest_eviction_beyond_the_capacity() {
+        let readmitted = Arc::new(Mutex::new(Vec::new()));
+        let tracker = ReadmissionTracker::with_capacity(Arc::new({
+            let readmitted = readmitted.clone();
+            move |key: &&str| readmitted.lock().push(*key)
+        }), 2);
+
+        tracker.evicted(10);
+        tracker.evicted(20);
+        tracker.evicted(30);
+        tracker.admitted(&"topic", 10);
+        tracker.admitted(&"disk", 30);
+
+        assert_eq!(vec!["disk"], *readmitted.lock());
+    }
+
+    #[test]
+    fn thrash_rate_without_admissions() {
+        let tracker: ReadmissionTracker<&str> = ReadmissionTracker::new(Arc::new(|_key: &&str| {}));
+        assert_eq!(0.0, tracker.thrash_rate());
+    }
+}
@@ -1,6 +1,10 @@
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
 
-use log::debug;
+use crossbeam_channel::tick;
+use log::{debug, info};
 use parking_lot::RwLock;
 use rand::{Rng, thread_rng};
 
@@ -17,9 +21,13 @@ pub(crate) struct BufferSize(pub(crate) usize);
 
 /// Pool represents a ring-buffer that is used to buffer the gets for various keys.
 /// PoolSize is a configurable parameter defined in [`crate::cache::config::Config`].
+///
+/// A buffer is drained when it is full. Pool can also drain all its buffers periodically, read [`crate::cache::config::AccessDrain`].
+/// During the event of cache shutdown, keep_running is set to false, which stops the periodic drain.
 pub(crate) struct Pool<Consumer: BufferConsumer> {
     buffers: Vec<RwLock<Buffer<Consumer>>>,
     pool_size: PoolSize,
+    keep_running: Arc<AtomicBool>,
 }
 
 /// Each buffer inside the Pool is a Vec<KeyHash>. The capacity of buffer is a configurable parameter.
@@ -51,6 +59,14 @@ impl<Consumer> Buffer<Consumer>
         }
         self.key_hashes.push(key_hash);
     }
+
+    /// Sends the key_hashes to the consumer as `BufferEvent::Full`, if the buffer is not empty.
+    pub(crate) fn drain(&mut self) {
+        if !self.key_hashes.is_empty() {
+            self.consumer.accept(BufferEvent::Full(self.key_hashes.clone()));
+            self.key_hashes.clear();
+        }
+    }
 }
 
 impl<Consumer> Pool<Consumer>
@@ -60,7 +76,7 @@ impl<Consumer> Pool<Consumer>
             .map(|_| RwLock::new(Buffer::new(buffer_size, buffer_consumer.clone())))
             .collect::<_>();
 
-        Pool { buffers, pool_size }
+        Pool { buffers, pool_size, keep_running: Arc::new(AtomicBool::new(true)) }
     }
 
     /// Adds the key_hash to a random buffer. There are a total of pool_size buffers and the
@@ -71,6 +87,32 @@ impl<Consumer> Pool<Consumer>
         let index = thread_rng().gen_range(0..pool_size);
         self.buffers[index].write().add(key_hash);
     }
+
+    /// Drains all the buffers of the Pool, one at a time.
+    pub(crate) fn drain(&self) {
+        self.buffers.iter().for_each(|buffer| buffer.write().drain());
+    }
+
+    /// Spins a single thread that drains all the buffers every `period`, till the Pool is shutdown.
+    pub(crate) fn drain_periodically(self: Arc<Pool<Consumer>>, period: Duration)
+        where Consumer: Send + Sync + 'static {
+        let keep_running = self.keep_running.clone();
+        let receiver = tick(period);
+
+        thread::spawn(move || {
+            for _ in receiver {
+                if !keep_running.load(Ordering::Acquire) {
+                    info!("Shutting down the periodic drain of the Pool");
+                    break;
+                }
+                self.drain();
+            }
+        });
+    }
+
+    pub(crate) fn shutdown(&self) {
+        self.keep_running.store(false, Ordering::Release);
+    }
 }
 
 #[cfg(test)]
@@ -78,6 +120,7 @@ mod tests {
     use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::thread;
+    use std::time::Duration;
 
     use crate::cache::pool::{BufferSize, Pool, PoolSize};
     use crate::cache::pool::tests::setup::TestBufferConsumer;
@@ -132,6 +175,41 @@ mod tests {
         assert_eq!(3, total_keys);
     }
 
+    #[test]
+    fn drain_all_the_buffers() {
+        let consumer = Arc::new(TestBufferConsumer { total_keys: AtomicUsize::new(0) });
+        let pool = Pool::new(
+            PoolSize(2),
+            BufferSize(8),
+            consumer.clone(),
+        );
+        pool.add(10);
+        pool.add(12);
+        pool.add(16);
+        pool.drain();
+
+        assert_eq!(3, consumer.total_keys.load(Ordering::SeqCst));
+        assert!(pool.buffers.iter().all(|buffer| buffer.read().key_hashes.is_empty()));
+    }
+
+    #[test]
+    fn drain_the_buffers_periodically() {
+        let consumer = Arc::new(TestBufferConsumer { total_keys: AtomicUsize::new(0) });
+        let pool = Arc::new(Pool::new(
+            PoolSize(2),
+            BufferSize(8),
+            consumer.clone(),
+        ));
+        pool.clone().drain_periodically(Duration::from_millis(5));
+        pool.add(10);
+        pool.add(12);
+
+        thread::sleep(Duration::from_millis(50));
+        pool.shutdown();
+
+        assert_eq!(2, consumer.total_keys.load(Ordering::SeqCst));
+    }
+
     #[test]
     fn drain_the_buffer_with_contention_1() {
         let consumer = Arc::new(TestBufferConsumer { total_keys: AtomicUsize::new(0) });
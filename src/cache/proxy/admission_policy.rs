@@ -4,6 +4,7 @@ use std::sync::Arc;
 use crate::cache::buffer_event::{BufferConsumer, BufferEvent};
 use crate::cache::policy::admission_policy::AdmissionPolicy;
 use crate::cache::policy::config::CacheWeightConfig;
+use crate::cache::sketch::SKETCH_ROWS;
 use crate::cache::stats::ConcurrentStatsCounter;
 use crate::cache::types::{TotalCapacity, TotalCounters, TotalShards, Weight};
 
@@ -22,8 +23,9 @@ impl<Key> ProxyAdmissionPolicy<Key>
     pub fn new(counters: TotalCounters, capacity: TotalCapacity, shards: TotalShards, total_cache_weight: Weight) -> Self {
         ProxyAdmissionPolicy {
             admission_policy: Arc::new(
-                AdmissionPolicy::new(
+                AdmissionPolicy::with_sketch_depth(
                     counters,
+                    SKETCH_ROWS,
                     CacheWeightConfig::new(
                         capacity,
                         shards,
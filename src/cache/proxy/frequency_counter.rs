@@ -1,4 +1,5 @@
 use crate::cache::lfu::frequency_counter::FrequencyCounter;
+use crate::cache::sketch::SKETCH_ROWS;
 use crate::cache::types::{FrequencyEstimate, KeyHash, TotalCounters};
 
 /// Proxy representation of the `crate::cache::lfu::frequency_counter::FrequencyCounter`.
@@ -16,7 +17,7 @@ impl ProxyFrequencyCounter {
     #[cfg(not(tarpaulin_include))]
     pub fn new(counters: TotalCounters) -> Self {
         ProxyFrequencyCounter {
-            frequency_counter: FrequencyCounter::new(counters)
+            frequency_counter: FrequencyCounter::with_depth(counters, SKETCH_ROWS)
         }
     }
 
@@ -1,9 +1,10 @@
 use std::hash::Hash;
 use std::time::Duration;
 
-use crate::cache::config::WeightCalculationFn;
+use crate::cache::config::error::WeightError;
+use crate::cache::config::weight_calculation::Calculation;
 use crate::cache::errors::Errors;
-use crate::cache::types::Weight;
+use crate::cache::types::{IsTimeToLiveSpecified, Weight};
 
 /// `PutOrUpdateRequest` encapsulates the fields that are required for a `put` or an `update` operation.
 ///
@@ -28,17 +29,20 @@ impl<Key, Value> PutOrUpdateRequest<Key, Value>
     where Key: Hash + Eq + Send + Sync + Clone,
           Value: Send + Sync {
 
-    /// Returns the weight in a `PutOrUpdateRequest`.
+    /// Returns the weight in a `PutOrUpdateRequest`, if the request results in a `put` operation.
     ///
-    /// Weight is either the client provided weight or calculated from the value and presence/absence of `time_to_live`
-    pub(crate) fn updated_weight(&self, weight_calculation_fn: &WeightCalculationFn<Key, Value>) -> Option<Weight> {
-        self.weight.or_else(|| self.value.as_ref().map(|value| {
-            if self.time_to_live.is_some() {
-                (weight_calculation_fn)(&self.key, value, true)
-            } else {
-                (weight_calculation_fn)(&self.key, value, false)
-            }
-        }))
+    /// Weight is either the client provided weight or calculated from the value and presence/absence of `time_to_live`.
+    /// If `time_to_live` is specified, the client provided weight is increased by the weight of the entry in `crate::cache::expiration::TTLTicker`.
+    /// Returns the `WeightError` if the weight calculation function could not calculate the weight.
+    pub(crate) fn updated_weight<WeightFn>(&self, weight_calculation_fn: WeightFn) -> Result<Option<Weight>, WeightError>
+        where WeightFn: Fn(&Key, &Value, IsTimeToLiveSpecified) -> Result<Weight, WeightError> {
+        let time_to_live_weight = Calculation::time_to_live_weight(self.time_to_live.is_some());
+        if let Some(weight) = self.weight {
+            return Ok(Some(weight + time_to_live_weight));
+        }
+        self.value.as_ref().map(|value| {
+            (weight_calculation_fn)(&self.key, value, self.time_to_live.is_some())
+        }).transpose()
     }
 }
 
@@ -116,6 +120,7 @@ impl<Key, Value> PutOrUpdateRequestBuilder<Key, Value>
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
+    use crate::cache::config::error::WeightError;
     use crate::cache::config::weight_calculation::Calculation;
 
     use crate::cache::types::IsTimeToLiveSpecified;
@@ -171,41 +176,57 @@ mod tests {
     #[test]
     fn updated_weight_if_weight_is_provided() {
         let put_or_update_request = PutOrUpdateRequestBuilder::new("topic").weight(10).build();
-        let weight_calculation_fn = Box::new(|_key: &&str, _value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| 100);
+        let weight_calculation_fn = Box::new(|_key: &&str, _value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| Ok(100));
+
+        assert_eq!(Ok(Some(10)), put_or_update_request.updated_weight(&weight_calculation_fn));
+    }
+
+    #[test]
+    fn updated_weight_if_weight_and_time_to_live_is_provided() {
+        let put_or_update_request = PutOrUpdateRequestBuilder::new("topic").weight(10).time_to_live(Duration::from_secs(10)).build();
+        let weight_calculation_fn = Box::new(|_key: &&str, _value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| Ok(100));
 
-        assert_eq!(Some(10), put_or_update_request.updated_weight(&weight_calculation_fn));
+        assert_eq!(Ok(Some(10 + Calculation::ttl_ticker_entry_size() as i64)), put_or_update_request.updated_weight(&weight_calculation_fn));
     }
 
     #[test]
     fn updated_weight_if_value_is_provided() {
         let put_or_update_request = PutOrUpdateRequestBuilder::new("topic").value("cached").build();
-        let weight_calculation_fn = Box::new(|_key: &&str, value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| value.len() as i64);
+        let weight_calculation_fn = Box::new(|_key: &&str, value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| Ok(value.len() as i64));
 
-        assert_eq!(Some(6), put_or_update_request.updated_weight(&weight_calculation_fn));
+        assert_eq!(Ok(Some(6)), put_or_update_request.updated_weight(&weight_calculation_fn));
     }
 
     #[test]
     fn updated_weight_if_weight_and_value_is_provided() {
         let put_or_update_request = PutOrUpdateRequestBuilder::new("topic").value("cached").weight(22).build();
-        let weight_calculation_fn = Box::new(|_key: &&str, value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| value.len() as i64);
+        let weight_calculation_fn = Box::new(|_key: &&str, value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| Ok(value.len() as i64));
 
-        assert_eq!(Some(22), put_or_update_request.updated_weight(&weight_calculation_fn));
+        assert_eq!(Ok(Some(22)), put_or_update_request.updated_weight(&weight_calculation_fn));
     }
 
     #[test]
     fn updated_weight_if_neither_weight_nor_value_is_provided() {
         let put_or_update_request = PutOrUpdateRequestBuilder::new("topic").remove_time_to_live().build();
-        let weight_calculation_fn = Box::new(|_key: &&str, value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| value.len() as i64);
+        let weight_calculation_fn = Box::new(|_key: &&str, value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| Ok(value.len() as i64));
+
+        assert_eq!(Ok(None), put_or_update_request.updated_weight(&weight_calculation_fn));
+    }
+
+    #[test]
+    fn updated_weight_if_the_weight_can_not_be_calculated() {
+        let put_or_update_request = PutOrUpdateRequestBuilder::new("topic").value("cached").build();
+        let weight_calculation_fn = Box::new(|_key: &&str, _value: &&str, _is_time_to_live_specified: IsTimeToLiveSpecified| Err(WeightError::new("value is too large")));
 
-        assert_eq!(None, put_or_update_request.updated_weight(&weight_calculation_fn));
+        assert_eq!(Err(WeightError::new("value is too large")), put_or_update_request.updated_weight(&weight_calculation_fn));
     }
 
     #[test]
     fn updated_weight_if_only_time_to_live_is_provided() {
         let put_or_update_request: PutOrUpdateRequest<&str, &str> = PutOrUpdateRequestBuilder::new("topic").time_to_live(Duration::from_secs(500)).build();
-        let weight_calculation_fn = Box::new(Calculation::perform);
+        let weight_calculation_fn = Box::new(|key: &&str, value: &&str, is_time_to_live_specified: IsTimeToLiveSpecified| Ok(Calculation::perform(key, value, is_time_to_live_specified)));
 
-        assert_eq!(None, put_or_update_request.updated_weight(&weight_calculation_fn));
+        assert_eq!(Ok(None), put_or_update_request.updated_weight(&weight_calculation_fn));
     }
 
     #[test]
@@ -214,9 +235,9 @@ mod tests {
         let value: u64 = 1000;
 
         let put_or_update_request = PutOrUpdateRequestBuilder::new(key).value(value).build();
-        let weight_calculation_fn = Box::new(Calculation::perform);
+        let weight_calculation_fn = Box::new(|key: &u64, value: &u64, is_time_to_live_specified: IsTimeToLiveSpecified| Ok(Calculation::perform(key, value, is_time_to_live_specified)));
 
-        assert_eq!(Some(40), put_or_update_request.updated_weight(&weight_calculation_fn));
+        assert_eq!(Ok(Some(40)), put_or_update_request.updated_weight(&weight_calculation_fn));
     }
 
     #[test]
@@ -225,8 +246,8 @@ mod tests {
         let value: u64 = 1000;
 
         let put_or_update_request = PutOrUpdateRequestBuilder::new(key).value(value).time_to_live(Duration::from_secs(100)).build();
-        let weight_calculation_fn = Box::new(Calculation::perform);
+        let weight_calculation_fn = Box::new(|key: &u64, value: &u64, is_time_to_live_specified: IsTimeToLiveSpecified| Ok(Calculation::perform(key, value, is_time_to_live_specified)));
 
-        assert_eq!(Some(64), put_or_update_request.updated_weight(&weight_calculation_fn));
+        assert_eq!(Ok(Some(64)), put_or_update_request.updated_weight(&weight_calculation_fn));
     }
 }
\ No newline at end of file
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+/// SingleFlight coordinates the concurrent computations of the value of the same key, so that only one of them runs.
+///
+/// Every key being computed has a flight, which is a slot (behind a lock) for the computed value.
+/// The first caller to acquire the lock on the slot computes the value and fills the slot, the concurrent callers wait on the lock and
+/// return the value from the slot. The flight is removed once the value is computed, so the map only holds the keys that are being computed.
+pub(crate) struct SingleFlight<Key, Value>
+    where Key: Hash + Eq + Clone, {
+    flights: Mutex<HashMap<Key, Arc<Mutex<Option<Value>>>>>,
+}
+
+impl<Key, Value> SingleFlight<Key, Value>
+    where Key: Hash + Eq + Clone, {
+    pub(crate) fn new() -> Self {
+        SingleFlight {
+            flights: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<Key, Value> SingleFlight<Key, Value>
+    where Key: Hash + Eq + Clone,
+          Value: Clone, {
+    /// Returns the value computed by `compute`, or the value computed by a concurrent caller for the same key.
+    pub(crate) fn get_or_compute<ComputeFn>(&self, key: &Key, compute: ComputeFn) -> Value
+        where ComputeFn: FnOnce() -> Value {
+        let flight = self.flights.lock().entry(key.clone()).or_default().clone();
+        let mut slot = flight.lock();
+        if let Some(value) = slot.as_ref() {
+            return value.clone();
+        }
+
+        let value = compute();
+        *slot = Some(value.clone());
+        self.flights.lock().retain(|_, existing| !Arc::ptr_eq(existing, &flight));
+        value
+    }
+
+    #[cfg(test)]
+    fn in_flight(&self) -> usize {
+        self.flights.lock().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Barrier};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::cache::single_flight::SingleFlight;
+
+    #[test]
+    fn compute_a_value() {
+        let single_flight: SingleFlight<&str, &str> = SingleFlight::new();
+
+        assert_eq!("microservices", single_flight.get_or_compute(&"topic", || "microservices"));
+        assert_eq!(0, single_flight.in_flight());
+    }
+
+    #[test]
+    fn compute_a_value_once_for_concurrent_callers() {
+        let single_flight: Arc<SingleFlight<&str, usize>> = Arc::new(SingleFlight::new());
+        let computations = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(4));
+
+        let handles = (0..4).map(|_| {
+            let single_flight = single_flight.clone();
+            let computations = computations.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                single_flight.get_or_compute(&"topic", || {
+                    thread::sleep(Duration::from_millis(50));
+                    computations.fetch_add(1, Ordering::SeqCst) + 10
+                })
+            })
+        }).collect::<Vec<_>>();
+
+        let values = handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>();
+        assert_eq!(vec![10, 10, 10, 10], values);
+        assert_eq!(1, computations.load(Ordering::SeqCst));
+        assert_eq!(0, single_flight.in_flight());
+    }
+}
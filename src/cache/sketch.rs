@@ -0,0 +1,158 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::cache::types::TotalCounters;
+
+/// The default (and the minimum) depth of the sketch, read [`crate::cache::config::ConfigBuilder::sketch_depth`].
+pub(crate) const SKETCH_ROWS: usize = 4;
+const U64_BYTES: usize = 8;
+const HEADER_BYTES: usize = U64_BYTES * (1 + SKETCH_ROWS);
+
+/// SketchState is the exported state of the count-min sketch that maintains the access frequency of the keys.
+///
+/// A `SketchState` exported from one instance of `crate::cache::cached::CacheD` (read [`crate::cache::cached::CacheD::export_sketch`])
+/// can be used to construct another instance with a warmed access frequency (read [`crate::cache::cached::CacheD::new_with_sketch`]),
+/// so that the hot keys are admitted from their first put, instead of after a warm-up period.
+///
+/// `to_bytes` and `from_bytes` define the export/import format, which is:
+/// the total counters, followed by the seed of each row (as little-endian u64s), followed by the 4-bit counters of each row.
+/// The depth (the number of rows) is not written, it is derived from the total counters and the number of bytes.
+///
+/// The sketch is only meaningful to an instance that hashes the keys the same way, so both the instances must use the same `key_hash_fn`.
+/// The doorkeeper is not a part of the sketch, it starts empty.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SketchState {
+    total_counters: TotalCounters,
+    seeds: Vec<u64>,
+    rows: Vec<Vec<u8>>,
+}
+
+impl SketchState {
+    pub(crate) fn new(total_counters: TotalCounters, seeds: Vec<u64>, rows: Vec<Vec<u8>>) -> Self {
+        SketchState { total_counters, seeds, rows }
+    }
+
+    /// Returns the total counters in each row of the sketch, which is the `counters` of [`crate::cache::config::ConfigBuilder`]
+    /// rounded up to the next power of 2.
+    pub fn total_counters(&self) -> TotalCounters {
+        self.total_counters
+    }
+
+    /// Returns the depth (the number of rows) of the sketch, which is the `sketch_depth` of [`crate::cache::config::ConfigBuilder`].
+    pub fn depth(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Serializes the sketch in the export format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(U64_BYTES * (1 + self.seeds.len()) + self.rows.iter().map(|row| row.len()).sum::<usize>());
+        bytes.extend_from_slice(&self.total_counters.to_le_bytes());
+        self.seeds.iter().for_each(|seed| bytes.extend_from_slice(&seed.to_le_bytes()));
+        self.rows.iter().for_each(|row| bytes.extend_from_slice(row));
+        bytes
+    }
+
+    /// Deserializes the sketch from the export format, returns a `SketchError` if the bytes do not hold a valid sketch.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SketchState, SketchError> {
+        if bytes.len() < HEADER_BYTES {
+            return Err(SketchError::new(format!("expected at least {} bytes, found {}", HEADER_BYTES, bytes.len())));
+        }
+        let read_u64 = |index: usize| u64::from_le_bytes(bytes[index * U64_BYTES..(index + 1) * U64_BYTES].try_into().unwrap());
+
+        let total_counters = read_u64(0);
+        if total_counters < 2 || !total_counters.is_power_of_two() {
+            return Err(SketchError::new(format!("total counters {} must be a power of 2, greater than 1", total_counters)));
+        }
+        let row_bytes = (total_counters / 2) as usize;
+        let depth = ((bytes.len() - U64_BYTES) / (U64_BYTES + row_bytes)).max(SKETCH_ROWS);
+        let expected_bytes = U64_BYTES + depth * (U64_BYTES + row_bytes);
+        if bytes.len() != expected_bytes {
+            return Err(SketchError::new(format!("expected {} bytes for {} total counters, found {}", expected_bytes, total_counters, bytes.len())));
+        }
+
+        let seeds = (1..=depth).map(read_u64).collect();
+        let rows_start = U64_BYTES * (1 + depth);
+        let rows = bytes[rows_start..].chunks(row_bytes).map(|row| row.to_vec()).collect();
+        Ok(SketchState::new(total_counters, seeds, rows))
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<u64>, Vec<Vec<u8>>) {
+        (self.seeds, self.rows)
+    }
+}
+
+/// `SketchError` is returned by [`SketchState::from_bytes`] if the bytes do not hold a valid sketch.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SketchError {
+    reason: String,
+}
+
+impl SketchError {
+    fn new(reason: String) -> Self {
+        SketchError { reason }
+    }
+
+    /// Returns the reason why the sketch could not be deserialized.
+    pub fn reason(&self) -> &str { &self.reason }
+}
+
+/// Display implementation for `SketchError`.
+impl Display for SketchError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "could not deserialize the sketch, {}", self.reason)
+    }
+}
+
+/// Error implementation for `SketchError`.
+impl Error for SketchError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::sketch::SketchState;
+
+    fn sketch() -> SketchState {
+        SketchState::new(4, vec![1, 2, 3, 4], vec![vec![0x12, 0x00], vec![0x01, 0x10], vec![0x00, 0x00], vec![0xff, 0x0f]])
+    }
+
+    #[test]
+    fn serialize_and_deserialize_a_sketch() {
+        let sketch = sketch();
+        let bytes = sketch.to_bytes();
+
+        assert_eq!(Ok(sketch), SketchState::from_bytes(&bytes));
+    }
+
+    #[test]
+    fn serialize_and_deserialize_a_deeper_sketch() {
+        let sketch = SketchState::new(4, vec![1, 2, 3, 4, 5], vec![vec![0x12, 0x00], vec![0x01, 0x10], vec![0x00, 0x00], vec![0xff, 0x0f], vec![0x21, 0x03]]);
+        let bytes = sketch.to_bytes();
+
+        let deserialized = SketchState::from_bytes(&bytes).unwrap();
+        assert_eq!(5, deserialized.depth());
+        assert_eq!(sketch, deserialized);
+    }
+
+    #[test]
+    fn does_not_deserialize_a_truncated_header() {
+        let error = SketchState::from_bytes(&[0, 1, 2]).unwrap_err();
+        assert_eq!("expected at least 40 bytes, found 3", error.reason());
+    }
+
+    #[test]
+    fn does_not_deserialize_total_counters_that_are_not_a_power_of_2() {
+        let mut bytes = sketch().to_bytes();
+        bytes[0] = 3;
+
+        let error = SketchState::from_bytes(&bytes).unwrap_err();
+        assert_eq!("total counters 3 must be a power of 2, greater than 1", error.reason());
+    }
+
+    #[test]
+    fn does_not_deserialize_truncated_rows() {
+        let bytes = sketch().to_bytes();
+
+        let error = SketchState::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!("expected 48 bytes for 4 total counters, found 47", error.reason());
+        assert_eq!("could not deserialize the sketch, expected 48 bytes for 4 total counters, found 47", error.to_string());
+    }
+}
@@ -0,0 +1,56 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// `StatsSummaryDecodeError` is returned by [`crate::cache::stats::StatsSummary::from_bytes`] if the bytes can not be decoded into a `StatsSummary`.
+///
+/// `UnsupportedVersion`: the encoding version is not supported by this version of the cache.
+///
+/// `UnknownStats`: the bytes carry more counters than the [`crate::cache::stats::StatsType`]s known to this version of the cache.
+///
+/// `InvalidLength`: the length of the bytes does not match the number of counters in the encoding.
+#[derive(Debug, Eq, PartialEq)]
+pub enum StatsSummaryDecodeError {
+    UnsupportedVersion(u8),
+    UnknownStats(usize),
+    InvalidLength { expected: usize, actual: usize },
+}
+
+/// Display implementation for `StatsSummaryDecodeError`.
+impl Display for StatsSummaryDecodeError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatsSummaryDecodeError::UnsupportedVersion(version) =>
+                write!(formatter, "could not decode stats summary, unsupported encoding version {}", version),
+            StatsSummaryDecodeError::UnknownStats(total_stats) =>
+                write!(formatter, "could not decode stats summary, unknown stats in {} counters", total_stats),
+            StatsSummaryDecodeError::InvalidLength { expected, actual } =>
+                write!(formatter, "could not decode stats summary, expected {} bytes but found {} bytes", expected, actual),
+        }
+    }
+}
+
+/// Error implementation for `StatsSummaryDecodeError`.
+impl Error for StatsSummaryDecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::stats::error::StatsSummaryDecodeError;
+
+    #[test]
+    fn unsupported_version_display() {
+        let error = StatsSummaryDecodeError::UnsupportedVersion(2);
+        assert_eq!("could not decode stats summary, unsupported encoding version 2", format!("{}", error));
+    }
+
+    #[test]
+    fn unknown_stats_display() {
+        let error = StatsSummaryDecodeError::UnknownStats(12);
+        assert_eq!("could not decode stats summary, unknown stats in 12 counters", format!("{}", error));
+    }
+
+    #[test]
+    fn invalid_length_display() {
+        let error = StatsSummaryDecodeError::InvalidLength { expected: 82, actual: 10 };
+        assert_eq!("could not decode stats summary, expected 82 bytes but found 10 bytes", format!("{}", error));
+    }
+}
@@ -2,13 +2,30 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use crossbeam_utils::CachePadded;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-const TOTAL_STATS: usize = 10;
+use crate::cache::stats::error::StatsSummaryDecodeError;
+
+pub mod error;
+
+const TOTAL_STATS: usize = 13;
+
+/// Version of the binary encoding of [`StatsSummary`].
+const ENCODING_VERSION: u8 = 1;
+
+/// The binary encoding of [`StatsSummary`] starts with a header of 2 bytes: the encoding version and the total number of counters.
+const ENCODING_HEADER_SIZE: usize = 2;
+
+const COUNTER_SIZE: usize = std::mem::size_of::<u64>();
 
 /// Defines various stats that are measured in the cache.
+///
+/// With the `serde` feature, `StatsType` is serialized as its name in snake case, for example, `cache_hits`.
 #[repr(usize)]
 #[non_exhaustive]
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
 pub enum StatsType {
     /// Defines the number of `hits` for the keys
     CacheHits = 0,
@@ -16,7 +33,7 @@ pub enum StatsType {
     CacheMisses = 1,
     /// Defines the number of `keys added`
     KeysAdded = 2,
-    /// Defines the number of `keys deleted`
+    /// Defines the number of `keys deleted`, other than the keys that expired or were evicted
     KeysDeleted = 3,
     /// Defines the number of `keys updated`
     KeysUpdated = 4,
@@ -30,10 +47,16 @@ pub enum StatsType {
     AccessAdded = 8,
     /// Defines the total number of `gets dropped`
     AccessDropped = 9,
+    /// Defines the number of `keys reclaimed` because their weak values were dropped
+    WeakValuesReclaimed = 10,
+    /// Defines the number of `keys expired` and removed by the TTL ticker
+    KeysExpired = 11,
+    /// Defines the number of `keys evicted` to create space for the incoming keys
+    KeysEvicted = 12,
 }
 
 impl StatsType {
-    const VALUES: [Self; TOTAL_STATS] = [
+    pub(crate) const VALUES: [Self; TOTAL_STATS] = [
         Self::CacheHits,
         Self::CacheMisses,
         Self::KeysAdded,
@@ -43,12 +66,19 @@ impl StatsType {
         Self::WeightAdded,
         Self::WeightRemoved,
         Self::AccessAdded,
-        Self::AccessDropped
+        Self::AccessDropped,
+        Self::WeakValuesReclaimed,
+        Self::KeysExpired,
+        Self::KeysEvicted,
     ];
 }
 
 /// StatsSummary is view representation of various stats represented by [`StatsType`].
+///
+/// With the `serde` feature, `StatsSummary` can be serialized (for example, to JSON), where `stats_by_type` is a map keyed by the
+/// snake case names of [`StatsType`]: `{"stats_by_type":{"cache_hits":3,"cache_misses":1,..},"hit_ratio":0.75}`.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StatsSummary {
     pub stats_by_type: HashMap<StatsType, u64>,
     pub hit_ratio: f64,
@@ -67,10 +97,157 @@ impl StatsSummary {
         self.stats_by_type.get(stats_type).copied()
     }
 
+    /// Returns the ratio of the [`StatsType::CacheHits`] to the total accesses (hits and misses), or 0.0 if there are no accesses.
+    ///
+    /// Unlike the `hit_ratio` field, which is 0.0 if there are no misses, `hit_ratio()` is 1.0 if all the accesses are hits.
+    pub fn hit_ratio(&self) -> f64 {
+        let total_accesses = self.total_accesses();
+        if total_accesses == 0 {
+            return 0.0;
+        }
+        self.get(&StatsType::CacheHits).unwrap_or(0) as f64 / total_accesses as f64
+    }
+
+    /// Returns the total accesses, which is the sum of [`StatsType::CacheHits`] and [`StatsType::CacheMisses`].
+    pub fn total_accesses(&self) -> u64 {
+        self.get(&StatsType::CacheHits).unwrap_or(0).wrapping_add(self.get(&StatsType::CacheMisses).unwrap_or(0))
+    }
+
+    /// Returns a copy of the counters by [`StatsType`], for example, to export all the counters at once to a metrics system.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use tinylfu_cached::cache::stats::StatsType;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let _ = cached.get(&"topic");
+    ///
+    ///     let summary = cached.stats_summary();
+    ///     assert_eq!(Some(&1), summary.as_map().get(&StatsType::KeysAdded));
+    ///     assert_eq!(1, summary.total_accesses());
+    ///     assert_eq!(1.0, summary.hit_ratio());
+    /// }
+    /// ```
+    pub fn as_map(&self) -> HashMap<StatsType, u64> {
+        self.stats_by_type.clone()
+    }
+
     /// Returns an hit ratio as %. Performs `round()`. since v0.0.4.
     pub fn hit_ratio_as_percentage(&self) -> f64 {
         (self.hit_ratio * 100.0).round()
     }
+
+    /// Encodes the `StatsSummary` into a compact binary representation that can be shipped to another process.
+    ///
+    /// The encoding has a fixed layout:
+    /// 1) 1 byte for the encoding version,
+    /// 2) 1 byte for the total number of counters,
+    /// 3) 8 bytes (little-endian u64) for each counter, in the order of the [`StatsType`] discriminants.
+    ///
+    /// `hit_ratio` is not encoded, it is derived from the counters in [`StatsSummary::from_bytes`].
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use tinylfu_cached::cache::stats::StatsSummary;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let summary = cached.stats_summary();
+    ///     let decoded = StatsSummary::from_bytes(&summary.to_bytes()).unwrap();
+    ///     assert_eq!(summary, decoded);
+    /// }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(ENCODING_HEADER_SIZE + TOTAL_STATS * COUNTER_SIZE);
+        bytes.push(ENCODING_VERSION);
+        bytes.push(TOTAL_STATS as u8);
+        for stats_type in StatsType::VALUES.iter() {
+            bytes.extend_from_slice(&self.get(stats_type).unwrap_or(0).to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes the `StatsSummary` from the binary representation returned by [`StatsSummary::to_bytes`].
+    ///
+    /// Bytes with fewer counters than the [`StatsType`]s known to this version of the cache are decoded with the missing counters as zero.
+    /// Returns [`crate::cache::stats::error::StatsSummaryDecodeError`] if the bytes can not be decoded.
+    pub fn from_bytes(bytes: &[u8]) -> Result<StatsSummary, StatsSummaryDecodeError> {
+        if bytes.len() < ENCODING_HEADER_SIZE {
+            return Err(StatsSummaryDecodeError::InvalidLength { expected: ENCODING_HEADER_SIZE, actual: bytes.len() });
+        }
+        if bytes[0] != ENCODING_VERSION {
+            return Err(StatsSummaryDecodeError::UnsupportedVersion(bytes[0]));
+        }
+        let total_stats = bytes[1] as usize;
+        if total_stats > TOTAL_STATS {
+            return Err(StatsSummaryDecodeError::UnknownStats(total_stats));
+        }
+        let expected_length = ENCODING_HEADER_SIZE + total_stats * COUNTER_SIZE;
+        if bytes.len() != expected_length {
+            return Err(StatsSummaryDecodeError::InvalidLength { expected: expected_length, actual: bytes.len() });
+        }
+
+        let mut stats_by_type = HashMap::new();
+        for (index, stats_type) in StatsType::VALUES.iter().copied().enumerate() {
+            let count = if index < total_stats {
+                let start = ENCODING_HEADER_SIZE + index * COUNTER_SIZE;
+                u64::from_le_bytes(bytes[start..start + COUNTER_SIZE].try_into().unwrap())
+            } else {
+                0
+            };
+            stats_by_type.insert(stats_type, count);
+        }
+        Ok(StatsSummary::from_stats(stats_by_type))
+    }
+
+    /// Merges the `other` summary into this summary by adding the counters of each [`StatsType`], and recomputes the `hit_ratio`.
+    /// The addition saturates at `u64::MAX`, so merging never wraps a counter around to a small value.
+    ///
+    /// `merge` allows aggregating the summaries of many cache instances into one.
+    /// ```
+    /// use tinylfu_cached::cache::cached::CacheD;
+    /// use tinylfu_cached::cache::config::ConfigBuilder;
+    /// use tinylfu_cached::cache::stats::StatsType;
+    /// #[tokio::main]
+    ///  async fn main() {
+    ///     let cached = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let other = CacheD::new(ConfigBuilder::new(100, 10, 100).build());
+    ///     let _ = cached.put("topic", "microservices").unwrap().handle().await;
+    ///     let _ = other.put("disk", "SSD").unwrap().handle().await;
+    ///
+    ///     let mut summary = cached.stats_summary();
+    ///     summary.merge(&other.stats_summary());
+    ///     assert_eq!(Some(2), summary.get(&StatsType::KeysAdded));
+    /// }
+    /// ```
+    pub fn merge(&mut self, other: &StatsSummary) {
+        for stats_type in StatsType::VALUES.iter() {
+            let count = self.get(stats_type).unwrap_or(0).saturating_add(other.get(stats_type).unwrap_or(0));
+            self.stats_by_type.insert(*stats_type, count);
+        }
+        self.hit_ratio = hit_ratio(
+            self.get(&StatsType::CacheHits).unwrap_or(0),
+            self.get(&StatsType::CacheMisses).unwrap_or(0),
+        );
+    }
+
+    fn from_stats(stats_by_type: HashMap<StatsType, u64>) -> Self {
+        let hit_ratio = hit_ratio(
+            stats_by_type.get(&StatsType::CacheHits).copied().unwrap_or(0),
+            stats_by_type.get(&StatsType::CacheMisses).copied().unwrap_or(0),
+        );
+        StatsSummary::new(stats_by_type, hit_ratio)
+    }
+}
+
+fn hit_ratio(hits: u64, misses: u64) -> f64 {
+    if hits == 0 || misses == 0 {
+        return 0.0;
+    }
+    (hits as f64) / (hits + misses) as f64
 }
 
 #[repr(transparent)]
@@ -115,8 +292,16 @@ impl ConcurrentStatsCounter {
 
     pub(crate) fn delete_key(&self) { self.add(StatsType::KeysDeleted, 1); }
 
+    pub(crate) fn delete_keys(&self, count: u64) { self.add(StatsType::KeysDeleted, count); }
+
     pub(crate) fn update_key(&self) { self.add(StatsType::KeysUpdated, 1); }
 
+    pub(crate) fn reclaim_weak_value(&self) { self.add(StatsType::WeakValuesReclaimed, 1); }
+
+    pub(crate) fn expire_key(&self) { self.add(StatsType::KeysExpired, 1); }
+
+    pub(crate) fn evict_key(&self) { self.add(StatsType::KeysEvicted, 1); }
+
     pub(crate) fn hits(&self) -> u64 {
         self.get(&StatsType::CacheHits)
     }
@@ -125,43 +310,67 @@ impl ConcurrentStatsCounter {
         self.get(&StatsType::CacheMisses)
     }
 
+    #[cfg(test)]
     pub(crate) fn keys_added(&self) -> u64 {
         self.get(&StatsType::KeysAdded)
     }
 
+    #[cfg(test)]
     pub(crate) fn keys_deleted(&self) -> u64 {
         self.get(&StatsType::KeysDeleted)
     }
 
+    #[cfg(test)]
     pub(crate) fn keys_rejected(&self) -> u64 { self.get(&StatsType::KeysRejected) }
 
+    #[cfg(test)]
     pub(crate) fn keys_updated(&self) -> u64 { self.get(&StatsType::KeysUpdated) }
 
+    #[cfg(test)]
     pub(crate) fn weight_added(&self) -> u64 {
         self.get(&StatsType::WeightAdded)
     }
 
+    #[cfg(test)]
     pub(crate) fn weight_removed(&self) -> u64 { self.get(&StatsType::WeightRemoved) }
 
+    #[cfg(test)]
     pub(crate) fn access_added(&self) -> u64 { self.get(&StatsType::AccessAdded) }
 
+    #[cfg(test)]
     pub(crate) fn access_dropped(&self) -> u64 { self.get(&StatsType::AccessDropped) }
 
+    #[cfg(test)]
+    pub(crate) fn weak_values_reclaimed(&self) -> u64 { self.get(&StatsType::WeakValuesReclaimed) }
+
+    #[cfg(test)]
+    pub(crate) fn keys_expired(&self) -> u64 { self.get(&StatsType::KeysExpired) }
+
+    #[cfg(test)]
+    pub(crate) fn keys_evicted(&self) -> u64 { self.get(&StatsType::KeysEvicted) }
+
     pub(crate) fn hit_ratio(&self) -> f64 {
-        let hits = self.hits();
-        let misses = self.misses();
-        if hits == 0 || misses == 0 {
-            return 0.0;
-        }
-        (hits as f64) / (hits + misses) as f64
+        hit_ratio(self.hits(), self.misses())
     }
 
-    pub(crate) fn clear(&self) {
+    /// Zeroes all the counters.
+    pub(crate) fn reset(&self) {
         for entry in &self.entries {
             entry.0.store(0, Ordering::Release);
         }
     }
 
+    /// Returns the summary of the counters and zeroes them, swapping each counter with zero,
+    /// so that an increment is either a part of the returned summary or is retained in the counter.
+    /// The counters are swapped one at a time, so the summary is not a point-in-time snapshot across all the counters.
+    pub(crate) fn summary_and_reset(&self) -> StatsSummary {
+        let mut stats_by_type = HashMap::new();
+        for stats_type in StatsType::VALUES.iter().copied() {
+            stats_by_type.insert(stats_type, self.entries[stats_type as usize].0.swap(0, Ordering::AcqRel));
+        }
+        StatsSummary::from_stats(stats_by_type)
+    }
+
     pub(crate) fn summary(&self) -> StatsSummary {
         let mut stats_by_type = HashMap::new();
         for stats_type in StatsType::VALUES.iter().copied() {
@@ -296,14 +505,57 @@ mod tests {
     }
 
     #[test]
-    fn clear() {
+    fn increase_weak_values_reclaimed() {
+        let stats_counter = ConcurrentStatsCounter::new();
+        stats_counter.reclaim_weak_value();
+
+        assert_eq!(1, stats_counter.weak_values_reclaimed());
+    }
+
+    #[test]
+    fn increase_keys_expired() {
+        let stats_counter = ConcurrentStatsCounter::new();
+        stats_counter.expire_key();
+        stats_counter.expire_key();
+
+        assert_eq!(2, stats_counter.keys_expired());
+        assert_eq!(0, stats_counter.keys_deleted());
+    }
+
+    #[test]
+    fn increase_keys_evicted() {
+        let stats_counter = ConcurrentStatsCounter::new();
+        stats_counter.evict_key();
+
+        assert_eq!(1, stats_counter.keys_evicted());
+        assert_eq!(0, stats_counter.keys_deleted());
+    }
+
+    #[test]
+    fn reset() {
         let stats_counter = ConcurrentStatsCounter::new();
         stats_counter.add_key();
         stats_counter.add_key();
         assert_eq!(2, stats_counter.keys_added());
 
-        stats_counter.clear();
+        stats_counter.reset();
+        assert_eq!(0, stats_counter.keys_added());
+    }
+
+    #[test]
+    fn summary_and_reset() {
+        let stats_counter = ConcurrentStatsCounter::new();
+        stats_counter.found_a_hit();
+        stats_counter.found_a_miss();
+        stats_counter.add_key();
+
+        let summary = stats_counter.summary_and_reset();
+        assert_eq!(Some(1), summary.get(&StatsType::KeysAdded));
+        assert_eq!(0.5, summary.hit_ratio);
+
         assert_eq!(0, stats_counter.keys_added());
+        assert_eq!(0, stats_counter.hits());
+        assert_eq!(0.0, stats_counter.hit_ratio());
     }
 
     #[test]
@@ -319,6 +571,9 @@ mod tests {
         stats_counter.remove_weight(1);
         stats_counter.add_access(1);
         stats_counter.drop_access(1);
+        stats_counter.reclaim_weak_value();
+        stats_counter.expire_key();
+        stats_counter.evict_key();
 
         let summary = stats_counter.summary();
         let mut stats_by_type = HashMap::new();
@@ -353,6 +608,9 @@ mod tests {
         stats_by_type.insert(StatsType::WeightRemoved, 1);
         stats_by_type.insert(StatsType::AccessAdded, 1);
         stats_by_type.insert(StatsType::AccessDropped, 2);
+        stats_by_type.insert(StatsType::WeakValuesReclaimed, 0);
+        stats_by_type.insert(StatsType::KeysExpired, 0);
+        stats_by_type.insert(StatsType::KeysEvicted, 0);
 
         assert_eq!(0.5, summary.hit_ratio);
         assert_eq!(stats_by_type, summary.stats_by_type);
@@ -373,7 +631,8 @@ mod tests {
 #[cfg(test)]
 mod stats_summary_tests {
     use std::collections::HashMap;
-    use crate::cache::stats::{StatsSummary, StatsType};
+    use crate::cache::stats::{ConcurrentStatsCounter, StatsSummary, StatsType, TOTAL_STATS};
+    use crate::cache::stats::error::StatsSummaryDecodeError;
 
     #[test]
     fn missing_stats() {
@@ -391,4 +650,175 @@ mod stats_summary_tests {
         assert_eq!(1, summary.get(&StatsType::CacheHits).unwrap());
         assert_eq!(5, summary.get(&StatsType::KeysAdded).unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn encode_and_decode_stats_summary() {
+        let stats_counter = ConcurrentStatsCounter::new();
+        stats_counter.found_a_hit();
+        stats_counter.found_a_miss();
+        stats_counter.found_a_miss();
+        stats_counter.add_key();
+        stats_counter.add_weight(u64::MAX - 5);
+        stats_counter.drop_access(7);
+
+        let summary = stats_counter.summary();
+        let bytes = summary.to_bytes();
+        assert_eq!(106, bytes.len());
+
+        let decoded = StatsSummary::from_bytes(&bytes).unwrap();
+        assert_eq!(summary, decoded);
+    }
+
+    #[test]
+    fn decode_stats_summary_with_fewer_counters() {
+        let mut bytes = vec![1, 1];
+        bytes.extend_from_slice(&3u64.to_le_bytes());
+
+        let decoded = StatsSummary::from_bytes(&bytes).unwrap();
+        assert_eq!(Some(3), decoded.get(&StatsType::CacheHits));
+        assert_eq!(Some(0), decoded.get(&StatsType::AccessDropped));
+        assert_eq!(0.0, decoded.hit_ratio);
+    }
+
+    #[test]
+    fn decode_stats_summary_with_unsupported_version() {
+        let mut bytes = StatsSummary::new(HashMap::new(), 0.0).to_bytes();
+        bytes[0] = 2;
+
+        assert_eq!(Err(StatsSummaryDecodeError::UnsupportedVersion(2)), StatsSummary::from_bytes(&bytes));
+    }
+
+    #[test]
+    fn decode_stats_summary_with_unknown_stats() {
+        let mut bytes = StatsSummary::new(HashMap::new(), 0.0).to_bytes();
+        bytes[1] = 14;
+
+        assert_eq!(Err(StatsSummaryDecodeError::UnknownStats(14)), StatsSummary::from_bytes(&bytes));
+    }
+
+    #[test]
+    fn decode_stats_summary_with_invalid_length() {
+        let bytes = StatsSummary::new(HashMap::new(), 0.0).to_bytes();
+
+        assert_eq!(
+            Err(StatsSummaryDecodeError::InvalidLength { expected: 106, actual: 105 }),
+            StatsSummary::from_bytes(&bytes[0..105])
+        );
+        assert_eq!(
+            Err(StatsSummaryDecodeError::InvalidLength { expected: 2, actual: 1 }),
+            StatsSummary::from_bytes(&bytes[0..1])
+        );
+    }
+
+    #[test]
+    fn merge_stats_summaries() {
+        let stats_counter = ConcurrentStatsCounter::new();
+        stats_counter.found_a_hit();
+        stats_counter.add_key();
+        stats_counter.add_weight(10);
+
+        let other_stats_counter = ConcurrentStatsCounter::new();
+        other_stats_counter.found_a_miss();
+        other_stats_counter.found_a_miss();
+        other_stats_counter.found_a_miss();
+        other_stats_counter.add_key();
+        other_stats_counter.add_weight(5);
+        other_stats_counter.reject_key();
+
+        let mut summary = stats_counter.summary();
+        summary.merge(&StatsSummary::from_bytes(&other_stats_counter.summary().to_bytes()).unwrap());
+
+        assert_eq!(Some(1), summary.get(&StatsType::CacheHits));
+        assert_eq!(Some(3), summary.get(&StatsType::CacheMisses));
+        assert_eq!(Some(2), summary.get(&StatsType::KeysAdded));
+        assert_eq!(Some(15), summary.get(&StatsType::WeightAdded));
+        assert_eq!(Some(1), summary.get(&StatsType::KeysRejected));
+        assert_eq!(Some(0), summary.get(&StatsType::KeysDeleted));
+        assert_eq!(0.25, summary.hit_ratio);
+    }
+
+    #[test]
+    fn merge_stats_summaries_saturating_the_counters() {
+        let mut summary = StatsSummary::from_stats(HashMap::from([(StatsType::KeysAdded, u64::MAX - 1)]));
+        let other = StatsSummary::from_stats(HashMap::from([(StatsType::KeysAdded, 5)]));
+
+        summary.merge(&other);
+        assert_eq!(Some(u64::MAX), summary.get(&StatsType::KeysAdded));
+    }
+
+    #[test]
+    fn hit_ratio_and_total_accesses_of_stats_summary() {
+        let stats_counter = ConcurrentStatsCounter::new();
+        stats_counter.found_a_hit();
+        stats_counter.found_a_hit();
+        stats_counter.found_a_hit();
+        stats_counter.found_a_miss();
+
+        let summary = stats_counter.summary();
+        assert_eq!(0.75, summary.hit_ratio());
+        assert_eq!(4, summary.total_accesses());
+    }
+
+    #[test]
+    fn hit_ratio_of_stats_summary_without_accesses() {
+        let summary = ConcurrentStatsCounter::new().summary();
+
+        assert_eq!(0.0, summary.hit_ratio());
+        assert_eq!(0, summary.total_accesses());
+    }
+
+    #[test]
+    fn hit_ratio_of_stats_summary_with_only_hits() {
+        let stats_counter = ConcurrentStatsCounter::new();
+        stats_counter.found_a_hit();
+
+        let summary = stats_counter.summary();
+        assert_eq!(1.0, summary.hit_ratio());
+    }
+
+    #[test]
+    fn stats_summary_as_map() {
+        let stats_counter = ConcurrentStatsCounter::new();
+        stats_counter.add_key();
+        stats_counter.add_weight(10);
+
+        let stats_by_type = stats_counter.summary().as_map();
+        assert_eq!(TOTAL_STATS, stats_by_type.len());
+        assert_eq!(Some(&1), stats_by_type.get(&StatsType::KeysAdded));
+        assert_eq!(Some(&10), stats_by_type.get(&StatsType::WeightAdded));
+        assert_eq!(Some(&0), stats_by_type.get(&StatsType::KeysDeleted));
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use std::collections::HashMap;
+
+    use crate::cache::stats::{StatsSummary, StatsType};
+
+    #[test]
+    fn serialize_a_stats_summary_to_json() {
+        let mut stats_by_type = HashMap::new();
+        stats_by_type.insert(StatsType::CacheHits, 3);
+        stats_by_type.insert(StatsType::CacheMisses, 1);
+        stats_by_type.insert(StatsType::WeightAdded, 40);
+        let summary = StatsSummary::new(stats_by_type, 0.75);
+
+        let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&summary).unwrap()).unwrap();
+        assert_eq!(3, json["stats_by_type"]["cache_hits"]);
+        assert_eq!(1, json["stats_by_type"]["cache_misses"]);
+        assert_eq!(40, json["stats_by_type"]["weight_added"]);
+        assert_eq!(0.75, json["hit_ratio"]);
+    }
+
+    #[test]
+    fn serialize_and_deserialize_a_stats_summary() {
+        let mut stats_by_type = HashMap::new();
+        stats_by_type.insert(StatsType::KeysAdded, 2);
+        stats_by_type.insert(StatsType::WeakValuesReclaimed, 1);
+        let summary = StatsSummary::new(stats_by_type, 0.0);
+
+        let json = serde_json::to_string(&summary).unwrap();
+        assert_eq!(summary, serde_json::from_str::<StatsSummary>(&json).unwrap());
+    }
+}
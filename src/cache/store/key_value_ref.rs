@@ -2,6 +2,8 @@ use std::hash::Hash;
 
 use dashmap::mapref::one::Ref;
 
+use crate::cache::store::shard_hash::ShardHashBuilder;
+
 /// KeyValueRef contains DashMap's Ref [`dashmap::mapref::one::Ref`] which internally holds
 /// a `RwLockReadGuard` for the shard. It is returned as a response to `get_ref` method of [`crate::cache::cached::CacheD`].
 ///
@@ -12,12 +14,12 @@ use dashmap::mapref::one::Ref;
 /// Hence, the invocation of `get_ref` will hold a lock against the shard that contains the key (within the scope of its usage).
 pub struct KeyValueRef<'a, Key, Value>
     where Key: Eq + Hash {
-    key_value_ref: Ref<'a, Key, Value>,
+    key_value_ref: Ref<'a, Key, Value, ShardHashBuilder>,
 }
 
 impl<'a, Key, Value> KeyValueRef<'a, Key, Value>
     where Key: Eq + Hash {
-    pub(crate) fn new(key_value_ref: Ref<'a, Key, Value>) -> Self <> {
+    pub(crate) fn new(key_value_ref: Ref<'a, Key, Value, ShardHashBuilder>) -> Self <> {
         KeyValueRef {
             key_value_ref
         }
@@ -37,11 +39,13 @@ impl<'a, Key, Value> KeyValueRef<'a, Key, Value>
 #[cfg(test)]
 mod tests {
     use dashmap::DashMap;
+
     use crate::cache::store::key_value_ref::KeyValueRef;
+    use crate::cache::store::shard_hash::ShardHashBuilder;
 
     #[test]
     fn get_key() {
-        let key_values = DashMap::new();
+        let key_values = DashMap::with_hasher(ShardHashBuilder::default());
         key_values.insert("topic", "microservices");
         let value_ref = key_values.get(&"topic").unwrap();
 
@@ -51,7 +55,7 @@ mod tests {
 
     #[test]
     fn get_value() {
-        let key_values = DashMap::new();
+        let key_values = DashMap::with_hasher(ShardHashBuilder::default());
         key_values.insert("topic", "microservices");
         let value_ref = key_values.get(&"topic").unwrap();
 
@@ -1,17 +1,25 @@
+use std::collections::HashSet;
 use std::hash::Hash;
 use std::sync::Arc;
 use std::time::Duration;
 
 use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
 
 use crate::cache::clock::ClockType;
+use crate::cache::command::RejectionReason;
+use crate::cache::compare_and_swap::CompareAndSwap;
+use crate::cache::config::error::WeightError;
 use crate::cache::stats::ConcurrentStatsCounter;
 use crate::cache::store::key_value_ref::KeyValueRef;
+use crate::cache::store::shard_hash::ShardHashBuilder;
 use crate::cache::store::stored_value::StoredValue;
-use crate::cache::types::{ExpireAfter, KeyId, TotalCapacity, TotalShards};
+use crate::cache::types::{ExpireAfter, IsTimeToLiveSpecified, KeyId, TotalCapacity, TotalShards, Weight};
 
 pub mod stored_value;
 pub mod key_value_ref;
+pub(crate) mod shard_hash;
+pub(crate) mod tombstones;
 
 /// KeyIdExpiry holds the key id and the optional expiry of the key
 #[derive(Eq, PartialEq, Debug)]
@@ -21,6 +29,30 @@ pub(crate) struct KeyIdExpiry(pub(crate) KeyId, pub(crate) Option<ExpireAfter>);
 #[derive(Eq, PartialEq, Debug)]
 pub(crate) struct UpdateResponse<Value>(Option<KeyIdExpiry>, Option<ExpireAfter>, Option<Value>);
 
+/// IncrementResponse is returned by the `increment_with_ttl` operation of the `Store`.
+/// `Incremented` holds the new count of a key that was alive in the `Store`.
+/// `Started` holds the count, the expiry and the key id of a key that was put in the `Store`, and the `KeyIdExpiry` of the key it replaced (if any).
+#[derive(Eq, PartialEq, Debug)]
+pub(crate) enum IncrementResponse {
+    Incremented(i64),
+    Started(i64, ExpireAfter, Option<KeyIdExpiry>),
+}
+
+/// ShardEntry is a copy of an alive key/value pair in a shard of the `Store`, along with its key_id and expiry, if any.
+pub(crate) struct ShardEntry<Key, Value> {
+    pub(crate) key: Key,
+    pub(crate) value: Value,
+    pub(crate) key_id: KeyId,
+    pub(crate) expire_after: Option<ExpireAfter>,
+}
+
+/// BatchSwapResponse is returned by `compare_and_swap_batch` of the `Store`.
+/// `Swapped` carries the key, the key_id and the new weight of each swapped key, `Rejected` carries the reason for rejecting the whole batch.
+pub(crate) enum BatchSwapResponse<Key> {
+    Swapped(Vec<(Key, KeyId, Weight)>),
+    Rejected(RejectionReason),
+}
+
 #[derive(Eq, PartialEq, Debug)]
 pub(crate) enum TypeOfExpiryUpdate {
     Added(KeyId, ExpireAfter),
@@ -67,16 +99,14 @@ impl<Value> UpdateResponse<Value> {
         let new_expiry = self.1;
         let key_id = self.key_id_or_panic();
 
-        if existing_expiry.is_none() && new_expiry.is_none() {
-            TypeOfExpiryUpdate::Nothing
-        } else if existing_expiry.is_none() && new_expiry.is_some() {
-            TypeOfExpiryUpdate::Added(key_id, new_expiry.unwrap())
-        } else if existing_expiry.is_some() && new_expiry.is_none() {
-            TypeOfExpiryUpdate::Deleted(key_id, existing_expiry.unwrap())
-        } else if existing_expiry.is_some() && new_expiry.is_some() && existing_expiry.ne(&new_expiry) {
-            TypeOfExpiryUpdate::Updated(key_id, existing_expiry.unwrap(), new_expiry.unwrap())
-        } else {
-            TypeOfExpiryUpdate::Nothing
+        match (existing_expiry, new_expiry) {
+            (None, Some(new_expiry)) =>
+                TypeOfExpiryUpdate::Added(key_id, new_expiry),
+            (Some(existing_expiry), None) =>
+                TypeOfExpiryUpdate::Deleted(key_id, existing_expiry),
+            (Some(existing_expiry), Some(new_expiry)) if existing_expiry.ne(&new_expiry) =>
+                TypeOfExpiryUpdate::Updated(key_id, existing_expiry, new_expiry),
+            _ => TypeOfExpiryUpdate::Nothing,
         }
     }
 }
@@ -85,7 +115,7 @@ impl<Value> UpdateResponse<Value> {
 /// Value is wrapped in another abstraction `crate::cache::store::stored_value::StoredValue` that contains key_id and expiry, if any, of the key.
 pub(crate) struct Store<Key, Value>
     where Key: Hash + Eq, {
-    store: DashMap<Key, StoredValue<Value>>,
+    store: DashMap<Key, StoredValue<Value>, ShardHashBuilder>,
     clock: ClockType,
     stats_counter: Arc<ConcurrentStatsCounter>,
 }
@@ -97,16 +127,27 @@ impl<Key, Value> Store<Key, Value>
     /// `stats_counter`: Is an instance of `crate::cache::stats::ConcurrentStatsCounter`
     /// `capacity`: Is used as a capacity parameter in DashMap, it defines the number of items that the cache may store
     /// `shards`: Is used as a shards parameter in DashMap
-    pub(crate) fn new(clock: ClockType, stats_counter: Arc<ConcurrentStatsCounter>, capacity: TotalCapacity, shards: TotalShards) -> Arc<Store<Key, Value>> {
+    /// `shard_hash_builder`: Builds the hasher that DashMap uses to place a key in a shard
+    pub(crate) fn new(
+        clock: ClockType,
+        stats_counter: Arc<ConcurrentStatsCounter>,
+        capacity: TotalCapacity,
+        shards: TotalShards,
+        shard_hash_builder: ShardHashBuilder) -> Arc<Store<Key, Value>> {
         Arc::new(Store {
-            store: DashMap::with_capacity_and_shard_amount(capacity, shards),
+            store: DashMap::with_capacity_and_hasher_and_shard_amount(capacity, shard_hash_builder, shards),
             clock,
             stats_counter,
         })
     }
 
     pub(crate) fn put(&self, key: Key, value: Value, key_id: KeyId) {
-        self.store.insert(key, StoredValue::never_expiring(value, key_id));
+        self.store.insert(key, StoredValue::never_expiring(value, key_id, &self.clock));
+        self.stats_counter.add_key();
+    }
+
+    pub(crate) fn put_versioned(&self, key: Key, value: Value, key_id: KeyId, version: u64) {
+        self.store.insert(key, StoredValue::never_expiring(value, key_id, &self.clock).versioned(version));
         self.stats_counter.add_key();
     }
 
@@ -120,19 +161,114 @@ impl<Key, Value> Store<Key, Value>
         expire_after.unwrap()
     }
 
-    pub(crate) fn delete(&self, key: &Key) -> Option<KeyIdExpiry> {
-        if let Some(pair) = self.store.remove(key) {
+    pub(crate) fn put_versioned_with_ttl(&self, key: Key, value: Value, key_id: KeyId, version: u64, time_to_live: Duration) -> ExpireAfter {
+        let stored_value = StoredValue::expiring(value, key_id, time_to_live, &self.clock).versioned(version);
+        let expire_after = stored_value.expire_after();
+
+        self.store.insert(key, stored_value);
+        self.stats_counter.add_key();
+
+        expire_after.unwrap()
+    }
+
+    /// Removes the key irrespective of its liveness, and returns its value along with its `KeyIdExpiry`.
+    /// The value of an expired or a soft deleted key is also returned, use `has_expired` to identify an expired value.
+    pub(crate) fn remove(&self, key: &Key) -> Option<(Value, KeyIdExpiry)> {
+        if let Some((_, stored_value)) = self.store.remove(key) {
+            self.stats_counter.delete_key();
+            let key_id_expiry = KeyIdExpiry(stored_value.key_id(), stored_value.expire_after());
+            return Some((stored_value.into_value(), key_id_expiry));
+        }
+        None
+    }
+
+    /// Removes the key expired by the `crate::cache::expiration::TTLTicker`, and returns its value along with its `KeyIdExpiry`.
+    /// The key is counted in `StatsType::KeysExpired` instead of `StatsType::KeysDeleted`.
+    pub(crate) fn remove_expired(&self, key: &Key) -> Option<(Value, KeyIdExpiry)> {
+        if let Some((_, stored_value)) = self.store.remove(key) {
+            self.stats_counter.expire_key();
+            let key_id_expiry = KeyIdExpiry(stored_value.key_id(), stored_value.expire_after());
+            return Some((stored_value.into_value(), key_id_expiry));
+        }
+        None
+    }
+
+    /// Removes the key evicted by the `crate::cache::policy::admission_policy::AdmissionPolicy`, unless the key was put again with a key_id that
+    /// `is_admitted`, and returns its value along with its `KeyIdExpiry`.
+    /// The check matters when the commands are executed by more than one thread, one of which may put the key while another one evicts it.
+    /// The key is counted in `StatsType::KeysEvicted` instead of `StatsType::KeysDeleted`.
+    pub(crate) fn remove_evicted<IsAdmitted>(&self, key: &Key, is_admitted: IsAdmitted) -> Option<(Value, KeyIdExpiry)>
+        where IsAdmitted: Fn(&KeyId) -> bool {
+        if let Some((_, stored_value)) = self.store.remove_if(key, |_, stored_value| !is_admitted(&stored_value.key_id())) {
+            self.stats_counter.evict_key();
+            let key_id_expiry = KeyIdExpiry(stored_value.key_id(), stored_value.expire_after());
+            return Some((stored_value.into_value(), key_id_expiry));
+        }
+        None
+    }
+
+    /// Returns the number of shards in the Store.
+    pub(crate) fn total_shards(&self) -> usize {
+        self.store.shards().len()
+    }
+
+    /// Returns the index of the shard that holds the key.
+    pub(crate) fn shard_index(&self, key: &Key) -> usize {
+        self.store.determine_map(key)
+    }
+
+    /// Returns true if `expire_after` has passed as per the clock of the Store.
+    pub(crate) fn has_expired(&self, expire_after: Option<ExpireAfter>) -> bool {
+        expire_after.map(|expire_after| self.clock.has_passed(&expire_after)).unwrap_or(false)
+    }
+
+    /// Deletes the key only if it is stored with the given `key_id`, so that a key put again after `key_id` was assigned is retained.
+    pub(crate) fn delete_if_key_id(&self, key: &Key, key_id: KeyId) -> Option<KeyIdExpiry> {
+        if let Some(pair) = self.store.remove_if(key, |_, stored_value| stored_value.key_id() == key_id) {
             self.stats_counter.delete_key();
             return Some(KeyIdExpiry(pair.1.key_id(), pair.1.expire_after()));
         }
         None
     }
 
-    pub(crate) fn mark_deleted(&self, key: &Key) {
+    /// Puts a copy of the alive `old_key` (made using `clone_value`) under `new_key` with the `key_id`, keeping its expiry and version,
+    /// and then deletes the `old_key`. Returns the `KeyIdExpiry` of the `old_key`, or `None` if the `old_key` is not alive.
+    ///
+    /// The `new_key` is put before the `old_key` is deleted, so that there is no window in which neither of the keys is present.
+    pub(crate) fn rename(&self, old_key: &Key, new_key: Key, key_id: KeyId, clone_value: fn(&Value) -> Value) -> Option<KeyIdExpiry> {
+        let (key_id_expiry, renamed) = self.store.get(old_key)
+            .filter(|stored_value| stored_value.is_alive(&self.clock))
+            .map(|stored_value| (
+                KeyIdExpiry(stored_value.key_id(), stored_value.expire_after()),
+                stored_value.renamed(key_id, clone_value)
+            ))?;
+
+        self.store.insert(new_key, renamed);
+        self.stats_counter.add_key();
+        self.delete_if_key_id(old_key, key_id_expiry.0);
+        Some(key_id_expiry)
+    }
+
+    /// Removes the key only if it is alive, and returns its value along with its `KeyIdExpiry`.
+    /// An expired or a soft deleted key is left for the `TTLTicker` or the pending delete to remove.
+    pub(crate) fn take(&self, key: &Key) -> Option<(Value, KeyIdExpiry)> {
+        if let Some((_, stored_value)) = self.store.remove_if(key, |_, stored_value| stored_value.is_alive(&self.clock)) {
+            self.stats_counter.delete_key();
+            let key_id_expiry = KeyIdExpiry(stored_value.key_id(), stored_value.expire_after());
+            return Some((stored_value.into_value(), key_id_expiry));
+        }
+        None
+    }
+
+    /// Soft deletes the key and returns true if the key was alive (present, not expired and not already soft deleted).
+    pub(crate) fn mark_deleted(&self, key: &Key) -> bool {
         if let Some(mut pair) = self.store.get_mut(key) {
             let stored_value = pair.value_mut();
+            let was_alive = stored_value.is_alive(&self.clock);
             stored_value.is_soft_deleted = true;
+            return was_alive;
         }
+        false
     }
 
     pub(crate) fn get_ref(&self, key: &Key) -> Option<KeyValueRef<'_, Key, StoredValue<Value>>> {
@@ -141,6 +277,11 @@ impl<Key, Value> Store<Key, Value>
         mapped_value
     }
 
+    /// Returns the reference to the alive key/value pair, without recording a hit or a miss.
+    pub(crate) fn peek_ref(&self, key: &Key) -> Option<KeyValueRef<'_, Key, StoredValue<Value>>> {
+        self.contains(key)
+    }
+
     pub(crate) fn update(&self, key: &Key, value: Option<Value>, time_to_live: Option<Duration>, remove_time_to_live: bool) -> UpdateResponse<Value> {
         if let Some(mut existing_value) = self.store.get_mut(key) {
             let existing_expiry = existing_value.expire_after();
@@ -156,20 +297,130 @@ impl<Key, Value> Store<Key, Value>
         UpdateResponse(None, None, value)
     }
 
-    pub(crate) fn clear(&self) {
-        self.store.clear();
+    /// Applies the `map_fn` on the stored value of the key, without recording a hit or a miss.
+    pub(crate) fn map_stored_value<MapFn, MappedValue>(&self, key: &Key, map_fn: MapFn) -> Option<MappedValue>
+        where MapFn: FnOnce(&StoredValue<Value>) -> MappedValue {
+        self.store.get(key).map(|key_value_ref| map_fn(key_value_ref.value()))
+    }
+
+    /// Returns the live key with the smallest key_id, without recording a hit or a miss.
+    pub(crate) fn oldest_key(&self) -> Option<Key> where Key: Clone {
+        self.live_key_ids().min_by_key(|(key_id, _)| *key_id).map(|(_, key)| key)
+    }
+
+    /// Returns the live key with the largest key_id, without recording a hit or a miss.
+    pub(crate) fn newest_key(&self) -> Option<Key> where Key: Clone {
+        self.live_key_ids().max_by_key(|(key_id, _)| *key_id).map(|(_, key)| key)
+    }
+
+    /// Returns the number of key/value pairs, including the expired pairs that are not removed yet.
+    /// The length of each shard is read under its read lock, without walking the pairs.
+    pub(crate) fn len(&self) -> usize {
+        self.store.shards().iter().map(|shard| shard.read().len()).sum()
+    }
+
+    /// Returns the number of the alive key/value pairs, walking all the pairs one shard at a time.
+    pub(crate) fn live_len(&self) -> usize {
+        self.store.shards().iter().map(|shard| {
+            shard.read().values().filter(|shared_value| shared_value.get().is_alive(&self.clock)).count()
+        }).sum()
+    }
+
+    /// Removes all the key/value pairs, one shard at a time, and returns the number of key/value pairs removed.
+    pub(crate) fn clear(&self) -> usize {
+        self.store.shards().iter().map(|shard| {
+            let mut shard = shard.write();
+            let cleared = shard.len();
+            shard.clear();
+            cleared
+        }).sum()
+    }
+
+    /// Removes all the key/value pairs, one shard at a time, and returns the number of key/value pairs removed.
+    /// Each removed key/value pair is passed to `removal_hook` after the lock on its shard is released, and is counted as a deleted key.
+    pub(crate) fn drain<RemovalHook>(&self, removal_hook: RemovalHook) -> usize
+        where RemovalHook: Fn(&Key, &StoredValue<Value>) {
+        self.store.shards().iter().map(|shard| {
+            let drained = shard.write().drain().collect::<Vec<_>>();
+            drained.iter().for_each(|(key, shared_value)| removal_hook(key, shared_value.get()));
+            self.stats_counter.delete_keys(drained.len() as u64);
+            drained.len()
+        }).sum()
     }
 
     pub(crate) fn stats_counter(&self) -> &Arc<ConcurrentStatsCounter> {
         &self.stats_counter
     }
 
+    /// Returns the version of the alive key, if the key was put with a version.
+    pub(crate) fn version_of(&self, key: &Key) -> Option<u64> {
+        self.store
+            .get(key)
+            .filter(|stored_value| stored_value.is_alive(&self.clock))
+            .and_then(|stored_value| stored_value.version())
+    }
+
+    /// Returns the key_id of the alive key, without recording a hit or a miss.
+    pub(crate) fn key_id_of(&self, key: &Key) -> Option<KeyId> {
+        self.store
+            .get(key)
+            .filter(|stored_value| stored_value.is_alive(&self.clock))
+            .map(|stored_value| stored_value.key_id())
+    }
+
     pub(crate) fn is_present(&self, key: &Key) -> bool {
         let maybe_value = self.store.get(key);
         maybe_value.is_some()
     }
 
-    fn contains(&self, key: &Key) -> Option<KeyValueRef<Key, StoredValue<Value>>> {
+    /// Returns true if the key is present and alive, without recording a hit or a miss.
+    pub(crate) fn is_alive(&self, key: &Key) -> bool {
+        self.store
+            .get(key)
+            .map(|stored_value| stored_value.is_alive(&self.clock))
+            .unwrap_or(false)
+    }
+
+    /// Invokes `f` with every alive key and its `StoredValue`, without cloning and without recording a hit or a miss.
+    /// The shards are walked one at a time, and the read lock on a shard is held while `f` is invoked for its entries.
+    pub(crate) fn for_each<F>(&self, mut f: F)
+        where F: FnMut(&Key, &StoredValue<Value>) {
+        for shard in self.store.shards() {
+            let shard = shard.read();
+            shard.iter()
+                .map(|(key, shared_value)| (key, shared_value.get()))
+                .filter(|(_, stored_value)| stored_value.is_alive(&self.clock))
+                .for_each(|(key, stored_value)| f(key, stored_value));
+        }
+    }
+
+    fn live_key_ids(&self) -> impl Iterator<Item=(KeyId, Key)> + '_ where Key: Clone {
+        self.store
+            .iter()
+            .filter(|key_value_ref| key_value_ref.value().is_alive(&self.clock))
+            .map(|key_value_ref| (key_value_ref.value().key_id(), key_value_ref.key().clone()))
+    }
+
+    /// Returns true if the key has expired, but is neither removed nor marked deleted, without recording a hit or a miss.
+    pub(crate) fn is_expired(&self, key: &Key) -> bool {
+        self.store
+            .get(key)
+            .is_some_and(|stored_value| !stored_value.is_soft_deleted() && !stored_value.is_alive(&self.clock))
+    }
+
+    /// Marks the expired key deleted and returns a reference to its stale value, without recording a hit or a miss.
+    /// The key is marked deleted under the write lock of its shard, so the stale value of a key is returned at most once.
+    pub(crate) fn take_expired_ref(&self, key: &Key) -> Option<KeyValueRef<'_, Key, StoredValue<Value>>> {
+        let mut pair = self.store.get_mut(key)?;
+        let stored_value = pair.value_mut();
+        if stored_value.is_soft_deleted() || stored_value.is_alive(&self.clock) {
+            return None;
+        }
+        stored_value.is_soft_deleted = true;
+        Some(KeyValueRef::new(pair.downgrade()))
+    }
+
+    fn contains(&self, key: &Key) -> Option<KeyValueRef<'_, Key, StoredValue<Value>>> {
         let maybe_value = self.store.get(key);
         maybe_value
             .filter(|stored_value| stored_value.is_alive(&self.clock))
@@ -189,29 +440,201 @@ impl<Key, Value> Store<Key, Value>
         if mapped_value.is_some() { self.stats_counter.found_a_hit(); } else { self.stats_counter.found_a_miss(); }
         mapped_value
     }
+
+    /// Returns the value of the alive key if its age is at most `max_age`, an older value is treated (and recorded) as a miss.
+    pub(crate) fn get_if_fresher_than(&self, key: &Key, max_age: Duration) -> Option<Value> {
+        let maybe_value = self.store.get(key);
+        let mapped_value = maybe_value
+            .filter(|stored_value| stored_value.is_alive(&self.clock) && stored_value.age(&self.clock) <= max_age)
+            .map(|key_value_ref| { key_value_ref.value().value() });
+
+        if mapped_value.is_some() { self.stats_counter.found_a_hit(); } else { self.stats_counter.found_a_miss(); }
+        mapped_value
+    }
+
+    /// Marks the expired key deleted and returns its stale value, read `take_expired_ref`.
+    pub(crate) fn take_expired(&self, key: &Key) -> Option<Value> {
+        self.take_expired_ref(key).map(|key_value_ref| key_value_ref.value().value())
+    }
+
+    /// Returns the value of the alive key, without recording a hit or a miss.
+    pub(crate) fn peek(&self, key: &Key) -> Option<Value> {
+        self.store
+            .get(key)
+            .filter(|stored_value| stored_value.is_alive(&self.clock))
+            .map(|key_value_ref| key_value_ref.value().value())
+    }
+
+    /// Returns a copy of the alive key/value pairs in the shard at `shard_index` whose keys match the `predicate`, without recording a hit or a miss.
+    /// The read lock on the shard is held only while the pairs are copied.
+    pub(crate) fn shard_entries<Predicate>(&self, shard_index: usize, predicate: Predicate) -> Vec<ShardEntry<Key, Value>>
+        where Key: Clone,
+              Predicate: Fn(&Key) -> bool {
+        let shard = self.store.shards()[shard_index].read();
+        shard.iter()
+            .map(|(key, shared_value)| (key, shared_value.get()))
+            .filter(|(key, stored_value)| stored_value.is_alive(&self.clock) && predicate(key))
+            .map(|(key, stored_value)| ShardEntry {
+                key: key.clone(),
+                value: stored_value.value(),
+                key_id: stored_value.key_id(),
+                expire_after: stored_value.expire_after(),
+            })
+            .collect()
+    }
+}
+
+impl<Key, Value> Store<Key, Value>
+    where Key: Hash + Eq + Send + Sync + Clone,
+          Value: Send + Sync + PartialEq, {
+    /// Applies all the `swaps` if the current value of each key is equal to its expected value, else applies none of them.
+    /// The preconditions are checked and the swaps are applied while holding the write lock on the shard of the keys, which makes the batch atomic.
+    ///
+    /// The batch is rejected with `RejectionReason::KeysInDifferentShards` if all the keys do not belong to the same shard,
+    /// with `RejectionReason::DuplicateKeys` if a key appears more than once,
+    /// and with `RejectionReason::PreconditionFailed` if any key is not alive or its value is not the expected value.
+    ///
+    /// The new weights are calculated before the write lock is acquired, so the (client provided) weight calculation function does not block the shard.
+    /// The weight depends on the presence of time_to_live, which is read under the read lock, and is checked again under the write lock.
+    /// A `WeightError` leaves all the keys unchanged.
+    pub(crate) fn compare_and_swap_batch<WeightFn>(
+        &self,
+        swaps: Vec<CompareAndSwap<Key, Value>>,
+        weight_calculation_fn: WeightFn) -> Result<BatchSwapResponse<Key>, WeightError>
+        where WeightFn: Fn(&Key, &Value, IsTimeToLiveSpecified) -> Result<Weight, WeightError> {
+        let shard_index = match swaps.first() {
+            Some(swap) => self.store.determine_map(&swap.key),
+            None => return Ok(BatchSwapResponse::Swapped(Vec::new())),
+        };
+        if swaps.iter().any(|swap| self.store.determine_map(&swap.key) != shard_index) {
+            return Ok(BatchSwapResponse::Rejected(RejectionReason::KeysInDifferentShards));
+        }
+        let mut keys = HashSet::with_capacity(swaps.len());
+        if !swaps.iter().all(|swap| keys.insert(&swap.key)) {
+            return Ok(BatchSwapResponse::Rejected(RejectionReason::DuplicateKeys));
+        }
+
+        let is_time_to_live_specified = match self.time_to_live_presence(shard_index, &swaps) {
+            Some(is_time_to_live_specified) => is_time_to_live_specified,
+            None => return Ok(BatchSwapResponse::Rejected(RejectionReason::PreconditionFailed)),
+        };
+        let weights = swaps.iter().zip(is_time_to_live_specified.iter())
+            .map(|(swap, is_time_to_live_specified)| (weight_calculation_fn)(&swap.key, &swap.new_value, *is_time_to_live_specified))
+            .collect::<Result<Vec<Weight>, WeightError>>()?;
+
+        let mut shard = self.store.shards()[shard_index].write();
+        let mut key_id_weights = Vec::with_capacity(swaps.len());
+        for ((swap, is_time_to_live_specified), weight) in swaps.iter().zip(is_time_to_live_specified).zip(weights) {
+            match shard.get(&swap.key).map(|shared_value| shared_value.get()) {
+                Some(stored_value) if self.matches(stored_value, &swap.expected) && stored_value.expire_after().is_some() == is_time_to_live_specified =>
+                    key_id_weights.push((swap.key.clone(), stored_value.key_id(), weight)),
+                _ => return Ok(BatchSwapResponse::Rejected(RejectionReason::PreconditionFailed)),
+            }
+        }
+        for swap in swaps {
+            if let Some(shared_value) = shard.get_mut(&swap.key) {
+                shared_value.get_mut().update(Some(swap.new_value), None, false, &self.clock);
+            }
+        }
+        Ok(BatchSwapResponse::Swapped(key_id_weights))
+    }
+
+    /// Returns the presence of time_to_live of each key of the `swaps` under the read lock on the shard at `shard_index`,
+    /// or None if any key is not alive or its value is not the expected value.
+    fn time_to_live_presence(&self, shard_index: usize, swaps: &[CompareAndSwap<Key, Value>]) -> Option<Vec<IsTimeToLiveSpecified>> {
+        let shard = self.store.shards()[shard_index].read();
+        swaps.iter().map(|swap| {
+            match shard.get(&swap.key).map(|shared_value| shared_value.get()) {
+                Some(stored_value) if self.matches(stored_value, &swap.expected) => Some(stored_value.expire_after().is_some()),
+                _ => None,
+            }
+        }).collect()
+    }
+
+    fn matches(&self, stored_value: &StoredValue<Value>, expected: &Value) -> bool {
+        stored_value.is_alive(&self.clock) && stored_value.value_ref() == expected
+    }
+}
+
+impl<Key> Store<Key, i64>
+    where Key: Hash + Eq, {
+    /// Increments the count of the key by `delta`, if the key is alive.
+    /// Else, puts `delta` as the count with `time_to_live` and `key_id`, replacing the key if it has expired or is soft deleted.
+    /// Both the operations happen while holding the lock on the shard of the key, which makes `increment_with_ttl` atomic for a key.
+    pub(crate) fn increment_with_ttl(&self, key: Key, delta: i64, key_id: KeyId, time_to_live: Duration) -> IncrementResponse {
+        let stored_value = StoredValue::expiring(delta, key_id, time_to_live, &self.clock);
+        let expire_after = stored_value.expire_after().unwrap();
+
+        match self.store.entry(key) {
+            Entry::Occupied(mut entry) if entry.get().is_alive(&self.clock) => {
+                let existing_value = entry.get_mut();
+                let count = existing_value.value_ref() + delta;
+                existing_value.update(Some(count), None, false, &self.clock);
+                IncrementResponse::Incremented(count)
+            }
+            Entry::Occupied(mut entry) => {
+                let existing_value = entry.insert(stored_value);
+                IncrementResponse::Started(delta, expire_after, Some(KeyIdExpiry(existing_value.key_id(), existing_value.expire_after())))
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(stored_value);
+                self.stats_counter.add_key();
+                IncrementResponse::Started(delta, expire_after, None)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::ops::Add;
     use std::sync::Arc;
-    use std::time::Duration;
+    use std::time::{Duration, SystemTime};
 
+    use parking_lot::Mutex;
     use setup::FutureClock;
 
-    use crate::cache::clock::{Clock, SystemClock};
+    use crate::cache::clock::{Clock, ManualClock, SystemClock};
     use crate::cache::stats::ConcurrentStatsCounter;
-    use crate::cache::store::Store;
+    use crate::cache::store::{IncrementResponse, KeyIdExpiry, Store};
+    use crate::cache::store::shard_hash::ShardHashBuilder;
     use crate::cache::store::stored_value::StoredValue;
-    use crate::cache::store::tests::setup::{Name, UnixEpochClock};
+    use crate::cache::store::tests::setup::{modulo_hash, ModuloHasher, Name};
     use crate::cache::types::{TotalCapacity, TotalShards};
 
     mod setup {
+        use std::hash::Hasher;
         use std::ops::Add;
         use std::time::{Duration, SystemTime};
 
         use crate::cache::clock::Clock;
 
+        pub(crate) const MODULO: u64 = 16;
+
+        /// A weak hash for highly-structured keys: the hash of an u64 key is the key modulo `MODULO`.
+        pub(crate) fn modulo_hash(key: &u64) -> u64 {
+            key % MODULO
+        }
+
+        #[derive(Default)]
+        pub(crate) struct ModuloHasher {
+            hash: u64,
+        }
+
+        impl Hasher for ModuloHasher {
+            fn finish(&self) -> u64 {
+                self.hash
+            }
+
+            fn write(&mut self, bytes: &[u8]) {
+                bytes.iter().for_each(|byte| self.hash = (self.hash * 31 + *byte as u64) % MODULO);
+            }
+
+            fn write_u64(&mut self, i: u64) {
+                self.hash = modulo_hash(&i);
+            }
+        }
+
         #[derive(Eq, PartialEq, Debug)]
         pub(crate) struct Name {
             pub(crate) first: String,
@@ -227,23 +650,49 @@ mod tests {
             }
         }
 
-        #[derive(Clone)]
-        pub(crate) struct UnixEpochClock;
+    }
 
-        impl Clock for UnixEpochClock {
-            fn now(&self) -> SystemTime {
-                SystemTime::UNIX_EPOCH
+    const DEFAULT_CAPACITY: TotalCapacity = 16;
+    const DEFAULT_SHARDS: TotalShards = 4;
+
+    /// Counts the pairs of keys that collide in the sketch hash and in the shard.
+    fn combined_collisions(store: &Store<u64, u64>, keys: &[u64]) -> usize {
+        let mut collisions = 0;
+        for (index, key) in keys.iter().enumerate() {
+            for other in &keys[index + 1..] {
+                if modulo_hash(key) == modulo_hash(other) && store.store.determine_map(key) == store.store.determine_map(other) {
+                    collisions += 1;
+                }
             }
         }
+        collisions
     }
 
-    const DEFAULT_CAPACITY: TotalCapacity = 16;
-    const DEFAULT_SHARDS: TotalShards = 4;
+    #[test]
+    fn dual_hash_reduces_combined_collisions() {
+        let keys = (0..64).map(|index| index * 16).collect::<Vec<u64>>();
+
+        let single_hash_builder = ShardHashBuilder::Custom(Arc::new(|| Box::<ModuloHasher>::default()));
+        let single_hash_store = Store::new(SystemClock::boxed(), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, single_hash_builder);
+        let dual_hash_store = Store::new(SystemClock::boxed(), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        keys.iter().for_each(|key| {
+            single_hash_store.put(*key, *key, *key);
+            dual_hash_store.put(*key, *key, *key);
+        });
+
+        let single_hash_collisions = combined_collisions(&single_hash_store, &keys);
+        let dual_hash_collisions = combined_collisions(&dual_hash_store, &keys);
+
+        assert_eq!(64 * 63 / 2, single_hash_collisions);
+        assert!(dual_hash_collisions < single_hash_collisions);
+        assert_eq!(Some(32), dual_hash_store.get(&32));
+    }
 
     #[test]
     fn get_value_for_an_existing_key() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("topic", "microservices", 1);
 
@@ -254,7 +703,7 @@ mod tests {
     #[test]
     fn get_value_for_an_existing_key_and_increase_stats() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("topic", "microservices", 1);
 
@@ -262,10 +711,61 @@ mod tests {
         assert_eq!(1, store.stats_counter.hits());
     }
 
+    #[test]
+    fn peek_the_value_for_an_existing_key_without_increasing_stats() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 1);
+
+        assert_eq!(Some("microservices"), store.peek(&"topic"));
+        assert_eq!(None, store.peek(&"non-existing"));
+        assert_eq!("microservices", store.peek_ref(&"topic").unwrap().value().value());
+        assert_eq!(0, store.stats_counter.hits());
+        assert_eq!(0, store.stats_counter.misses());
+    }
+
+    #[test]
+    fn oldest_and_newest_key() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 1);
+        store.put("disk", "SSD", 2);
+        store.put("cache", "cached", 3);
+
+        assert_eq!(Some("topic"), store.oldest_key());
+        assert_eq!(Some("cache"), store.newest_key());
+    }
+
+    #[test]
+    fn oldest_and_newest_key_ignore_the_keys_that_are_not_alive() {
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 1);
+        store.put("disk", "SSD", 2);
+        store.put("cache", "cached", 3);
+        store.mark_deleted(&"topic");
+        store.mark_deleted(&"cache");
+
+        assert_eq!(Some("disk"), store.oldest_key());
+        assert_eq!(Some("disk"), store.newest_key());
+    }
+
+    #[test]
+    fn oldest_and_newest_key_in_an_empty_store() {
+        let clock = SystemClock::boxed();
+        let store: Arc<Store<&str, &str>> = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        assert_eq!(None, store.oldest_key());
+        assert_eq!(None, store.newest_key());
+    }
+
     #[test]
     fn put_a_key_value_and_increase_stats() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("topic", "microservices", 1);
         assert_eq!(1, store.stats_counter.keys_added());
@@ -274,7 +774,7 @@ mod tests {
     #[test]
     fn put_with_ttl() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put_with_ttl("topic", "microservices", 1, Duration::from_millis(5));
 
@@ -284,17 +784,27 @@ mod tests {
 
     #[test]
     fn put_with_ttl_and_get_expire_after() {
-        let clock = Box::new(UnixEpochClock {});
-        let store = Store::new(clock.clone(), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let store = Store::new(clock.clone(), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         let expire_after = store.put_with_ttl("topic", "microservices", 1, Duration::from_secs(5));
         assert_eq!(clock.now().add(Duration::from_secs(5)), expire_after);
     }
 
+    #[test]
+    fn put_versioned_with_ttl_and_get_expire_after() {
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let store = Store::new(clock.clone(), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        let expire_after = store.put_versioned_with_ttl("topic", "microservices", 1, 2, Duration::from_secs(5));
+        assert_eq!(clock.now().add(Duration::from_secs(5)), expire_after);
+        assert_eq!(Some(2), store.get_ref(&"topic").unwrap().value().version());
+    }
+
     #[test]
     fn put_with_ttl_and_increase_stats() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put_with_ttl("topic", "microservices", 1, Duration::from_millis(5));
         assert_eq!(1, store.stats_counter.keys_added());
@@ -303,7 +813,7 @@ mod tests {
     #[test]
     fn put_with_ttl_and_get_the_value_of_an_expired_key() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put_with_ttl("topic", "microservices", 1, Duration::from_nanos(1));
 
@@ -314,7 +824,7 @@ mod tests {
     #[test]
     fn get_value_ref_for_an_existing_key_if_value_is_not_cloneable() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("name", Name { first: "John".to_string(), last: "Mcnamara".to_string() }, 1);
 
@@ -325,7 +835,7 @@ mod tests {
     #[test]
     fn get_value_ref_for_an_existing_key_and_increase_stats() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("name", Name { first: "John".to_string(), last: "Mcnamara".to_string() }, 1);
 
@@ -333,10 +843,40 @@ mod tests {
         assert_eq!(1, store.stats_counter.hits());
     }
 
+    #[test]
+    fn for_each_alive_entry_if_value_is_not_cloneable() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("name", Name { first: "John".to_string(), last: "Mcnamara".to_string() }, 1);
+        store.put("another", Name { first: "Ada".to_string(), last: "Lovelace".to_string() }, 2);
+
+        let mut total_length = 0;
+        store.for_each(|_key, stored_value| total_length += stored_value.value_ref().first.len());
+
+        assert_eq!(7, total_length);
+        assert_eq!(0, store.stats_counter.hits());
+    }
+
+    #[test]
+    fn for_each_skips_an_expired_entry() {
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+        {
+            let clock = SystemClock::boxed();
+            store.store.insert("topic", StoredValue::expiring("microservices", 1, Duration::from_secs(5), &clock));
+        }
+        store.put("cache", "cached", 2);
+
+        let mut keys = Vec::new();
+        store.for_each(|key, _| keys.push(*key));
+
+        assert_eq!(vec!["cache"], keys);
+    }
+
     #[test]
     fn get_value_for_a_non_existing_key() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         let value: Option<&str> = store.get(&"non-existing");
         assert_eq!(None, value);
@@ -345,7 +885,7 @@ mod tests {
     #[test]
     fn get_value_for_a_non_existing_key_and_increase_stats() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         let _value: Option<&str> = store.get(&"non-existing");
         assert_eq!(1, store.stats_counter.misses());
@@ -353,7 +893,7 @@ mod tests {
 
     #[test]
     fn get_value_for_an_expired_key() {
-        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
         {
             let clock = SystemClock::boxed();
             store.store.insert("topic", StoredValue::expiring("microservices", 1, Duration::from_secs(5), &clock));
@@ -365,7 +905,7 @@ mod tests {
 
     #[test]
     fn get_value_for_an_expired_key_and_increase_stats() {
-        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
         {
             let clock = SystemClock::boxed();
             store.store.insert("topic", StoredValue::expiring("microservices", 1, Duration::from_secs(5), &clock));
@@ -377,7 +917,7 @@ mod tests {
 
     #[test]
     fn get_value_for_an_unexpired_key() {
-        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
         {
             let clock = SystemClock::boxed();
             store.store.insert("topic", StoredValue::expiring("microservices", 1, Duration::from_secs(15), &clock));
@@ -389,7 +929,7 @@ mod tests {
 
     #[test]
     fn get_value_for_an_unexpired_key_and_increase_stats() {
-        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
         {
             let clock = SystemClock::boxed();
             store.store.insert("topic", StoredValue::expiring("microservices", 1, Duration::from_secs(15), &clock));
@@ -399,13 +939,52 @@ mod tests {
         assert_eq!(1, store.stats_counter.hits());
     }
 
+    #[test]
+    fn get_value_if_fresher_than_for_a_stale_key() {
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+        store.store.insert("topic", StoredValue::never_expiring("microservices", 1, &SystemClock::boxed()));
+
+        assert_eq!(None, store.get_if_fresher_than(&"topic", Duration::from_secs(5)));
+        assert_eq!(1, store.stats_counter.misses());
+    }
+
+    #[test]
+    fn get_value_if_fresher_than_for_a_fresh_key() {
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+        store.store.insert("topic", StoredValue::never_expiring("microservices", 1, &SystemClock::boxed()));
+
+        assert_eq!(Some("microservices"), store.get_if_fresher_than(&"topic", Duration::from_secs(15)));
+        assert_eq!(1, store.stats_counter.hits());
+    }
+
+    #[test]
+    fn take_an_expired_key_once() {
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+        store.store.insert("topic", StoredValue::expiring("microservices", 1, Duration::from_secs(5), &SystemClock::boxed()));
+
+        assert!(store.is_expired(&"topic"));
+        assert_eq!(Some("microservices"), store.take_expired(&"topic"));
+        assert_eq!(None, store.take_expired(&"topic"));
+        assert!(!store.is_expired(&"topic"));
+    }
+
+    #[test]
+    fn does_not_take_an_unexpired_key() {
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+        store.store.insert("topic", StoredValue::expiring("microservices", 1, Duration::from_secs(15), &SystemClock::boxed()));
+
+        assert!(!store.is_expired(&"topic"));
+        assert_eq!(None, store.take_expired(&"topic"));
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+    }
+
     #[test]
     fn delete_a_key() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("topic", "microservices", 10);
-        let key_id_expiry = store.delete(&"topic");
+        let key_id_expiry = store.remove(&"topic").map(|(_, key_id_expiry)| key_id_expiry);
 
         let value = store.get(&"topic");
         assert_eq!(None, value);
@@ -415,39 +994,79 @@ mod tests {
     #[test]
     fn delete_a_key_and_increase_stats() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("topic", "microservices", 10);
 
-        let _ = store.delete(&"topic");
+        let _ = store.remove(&"topic");
         assert_eq!(1, store.stats_counter.keys_deleted());
     }
 
     #[test]
     fn delete_a_non_existing_key() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
-        let key_id_expiry = store.delete(&"non-existing");
+        let removed = store.remove(&"non-existing");
 
         let value: Option<&str> = store.get(&"non-existing");
         assert_eq!(None, value);
-        assert_eq!(None, key_id_expiry);
+        assert_eq!(None, removed);
+    }
+
+    #[test]
+    fn remove_a_soft_deleted_key_and_return_its_value() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 10);
+        store.mark_deleted(&"topic");
+
+        let (value, key_id_expiry) = store.remove(&"topic").unwrap();
+        assert_eq!("microservices", value);
+        assert_eq!(10, key_id_expiry.0);
+        assert!(!store.has_expired(key_id_expiry.1));
+        assert!(!store.is_present(&"topic"));
+    }
+
+    #[test]
+    fn mark_deleted_returns_whether_the_key_was_alive() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 10);
+
+        assert!(store.mark_deleted(&"topic"));
+        assert!(!store.mark_deleted(&"topic"));
+        assert!(!store.mark_deleted(&"disk"));
+    }
+
+    #[test]
+    fn remove_an_expired_key() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put_with_ttl("topic", "microservices", 10, Duration::from_nanos(1));
+
+        let (value, key_id_expiry) = store.remove(&"topic").unwrap();
+        assert_eq!("microservices", value);
+        assert_eq!(10, key_id_expiry.0);
+        assert!(store.has_expired(key_id_expiry.1));
     }
 
     #[test]
     fn delete_a_non_existing_key_and_do_not_increase_stats() {
         let clock = SystemClock::boxed();
-        let store = Store::<&str, &str>::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::<&str, &str>::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
-        let _ = store.delete(&"non-existing");
+        let _ = store.remove(&"non-existing");
         assert_eq!(0, store.stats_counter.keys_deleted());
     }
 
     #[test]
     fn marks_a_key_deleted() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("topic", "microservices", 10);
         store.mark_deleted(&"topic");
@@ -456,10 +1075,108 @@ mod tests {
         assert_eq!(None, value);
     }
 
+    #[test]
+    fn delete_a_key_with_the_matching_key_id() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 10);
+        let key_id_expiry = store.delete_if_key_id(&"topic", 10);
+
+        assert_eq!(None, store.get(&"topic"));
+        assert_eq!(10, key_id_expiry.unwrap().0);
+    }
+
+    #[test]
+    fn take_a_key() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 10);
+        let (value, key_id_expiry) = store.take(&"topic").unwrap();
+
+        assert_eq!("microservices", value);
+        assert_eq!(10, key_id_expiry.0);
+        assert_eq!(None, store.get(&"topic"));
+        assert_eq!(1, store.stats_counter.keys_deleted());
+    }
+
+    #[test]
+    fn does_not_take_a_soft_deleted_key() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 10);
+        store.mark_deleted(&"topic");
+
+        assert!(store.take(&"topic").is_none());
+        assert!(store.store.contains_key(&"topic"));
+    }
+
+    #[test]
+    fn does_not_delete_a_key_with_a_different_key_id() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 10);
+        let key_id_expiry = store.delete_if_key_id(&"topic", 11);
+
+        assert_eq!(Some("microservices"), store.get(&"topic"));
+        assert_eq!(None, key_id_expiry);
+    }
+
+    #[test]
+    fn increment_with_ttl_starts_the_count_for_a_non_existing_key() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        let response = store.increment_with_ttl("requests", 2, 10, Duration::from_secs(5));
+        let expire_after = store.get_ref(&"requests").unwrap().value().expire_after().unwrap();
+
+        assert_eq!(IncrementResponse::Started(2, expire_after, None), response);
+        assert_eq!(Some(2), store.get(&"requests"));
+        assert_eq!(1, store.stats_counter.keys_added());
+    }
+
+    #[test]
+    fn increment_with_ttl_increments_the_count_of_an_existing_key() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.increment_with_ttl("requests", 2, 10, Duration::from_secs(5));
+        let expire_after = store.get_ref(&"requests").unwrap().value().expire_after();
+
+        let response = store.increment_with_ttl("requests", 3, 11, Duration::from_secs(5));
+
+        assert_eq!(IncrementResponse::Incremented(5), response);
+        assert_eq!(Some(5), store.get(&"requests"));
+        assert_eq!(expire_after, store.get_ref(&"requests").unwrap().value().expire_after());
+        assert_eq!(10, store.get_ref(&"requests").unwrap().value().key_id());
+    }
+
+    #[test]
+    fn increment_with_ttl_restarts_the_count_of_an_expired_key() {
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+        let expired_at = {
+            let clock = SystemClock::boxed();
+            let stored_value = StoredValue::expiring(8, 10, Duration::from_secs(5), &clock);
+            let expired_at = stored_value.expire_after().unwrap();
+            store.store.insert("requests", stored_value);
+            expired_at
+        };
+
+        let response = store.increment_with_ttl("requests", 1, 11, Duration::from_secs(5));
+        let expire_after = store.get_ref(&"requests").unwrap().value().expire_after().unwrap();
+
+        assert_eq!(IncrementResponse::Started(1, expire_after, Some(KeyIdExpiry(10, Some(expired_at)))), response);
+        assert_eq!(Some(1), store.get(&"requests"));
+        assert_eq!(11, store.get_ref(&"requests").unwrap().value().key_id());
+    }
+
     #[test]
     fn update_time_to_live_for_non_existing_key() {
         let clock = SystemClock::boxed();
-        let store: Arc<Store<&str, &str>> = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store: Arc<Store<&str, &str>> = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
         let response = store.update(&"topic", None, Some(Duration::from_secs(5)), false);
 
         assert!(!response.did_update_happen());
@@ -467,8 +1184,8 @@ mod tests {
 
     #[test]
     fn update_time_to_live_for_an_existing_key() {
-        let clock = Box::new(UnixEpochClock {});
-        let store = Store::new(clock.clone(), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let store = Store::new(clock.clone(), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("topic", "microservices", 10);
         let update_response = store.update(&"topic", None, Some(Duration::from_secs(5)), false);
@@ -481,8 +1198,8 @@ mod tests {
 
     #[test]
     fn update_time_to_live_for_an_existing_key_that_has_an_expiry() {
-        let clock = Box::new(UnixEpochClock {});
-        let store = Store::new(clock.clone(), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let store = Store::new(clock.clone(), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put_with_ttl("topic", "microservices", 10, Duration::from_secs(300));
         store.update(&"topic", None, Some(Duration::from_secs(15)), false);
@@ -494,8 +1211,8 @@ mod tests {
 
     #[test]
     fn remove_time_to_live_for_an_existing_key_that_has_an_expiry() {
-        let clock = Box::new(UnixEpochClock {});
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put_with_ttl("topic", "microservices", 10, Duration::from_secs(300));
         store.update(&"topic", None, None, true);
@@ -507,8 +1224,8 @@ mod tests {
 
     #[test]
     fn update_value_for_an_existing() {
-        let clock = Box::new(UnixEpochClock {});
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let clock = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("topic", "microservices", 10);
         store.update(&"topic", Some("cache"), None, false);
@@ -521,20 +1238,53 @@ mod tests {
     #[test]
     fn clear() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("topic", "microservices", 1);
+        store.put("disk", "SSD", 2);
 
-        store.clear();
+        assert_eq!(2, store.clear());
 
         let value = store.get(&"topic");
         assert_eq!(None, value);
     }
 
+    #[test]
+    fn len_and_live_len() {
+        let store = Store::new(Box::new(FutureClock {}), Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+        {
+            let clock = SystemClock::boxed();
+            store.store.insert("topic", StoredValue::expiring("microservices", 1, Duration::from_secs(5), &clock));
+        }
+        store.put("cache", "cached", 2);
+
+        assert_eq!(2, store.len());
+        assert_eq!(1, store.live_len());
+    }
+
+    #[test]
+    fn drain() {
+        let clock = SystemClock::boxed();
+        let stats_counter = Arc::new(ConcurrentStatsCounter::new());
+        let store = Store::new(clock, stats_counter.clone(), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 1);
+        store.put("disk", "SSD", 2);
+
+        let drained = Mutex::new(Vec::new());
+        assert_eq!(2, store.drain(|key, stored_value| drained.lock().push((*key, stored_value.value()))));
+
+        let mut drained = drained.into_inner();
+        drained.sort();
+        assert_eq!(vec![("disk", "SSD"), ("topic", "microservices")], drained);
+        assert_eq!(None, store.get(&"topic"));
+        assert_eq!(2, stats_counter.keys_deleted());
+    }
+
     #[test]
     fn is_not_present() {
         let clock = SystemClock::boxed();
-        let store: Arc<Store<&str, &str>> = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store: Arc<Store<&str, &str>> = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         let is_present = store.is_present(&"non-existing");
         assert!(!is_present)
@@ -543,13 +1293,36 @@ mod tests {
     #[test]
     fn is_present() {
         let clock = SystemClock::boxed();
-        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS);
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
 
         store.put("topic", "microservices", 1);
 
         let is_present = store.is_present(&"topic");
         assert!(is_present)
     }
+
+    #[test]
+    fn is_alive() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put("topic", "microservices", 1);
+
+        assert!(store.is_alive(&"topic"));
+        assert!(!store.is_alive(&"non-existing"));
+    }
+
+    #[test]
+    fn is_not_alive_after_expiry() {
+        let clock = SystemClock::boxed();
+        let store = Store::new(clock, Arc::new(ConcurrentStatsCounter::new()), DEFAULT_CAPACITY, DEFAULT_SHARDS, ShardHashBuilder::default());
+
+        store.put_with_ttl("topic", "microservices", 1, Duration::from_nanos(1));
+
+        assert!(store.is_present(&"topic"));
+        assert!(!store.is_alive(&"topic"));
+        assert_eq!(0, store.stats_counter.misses());
+    }
 }
 
 #[cfg(test)]
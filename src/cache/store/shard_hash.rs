@@ -0,0 +1,97 @@
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+
+use crate::cache::config::ShardHasherFn;
+
+/// ShardHashBuilder builds the hasher that [`dashmap::DashMap`] inside `crate::cache::store::Store` uses to place a key in a shard.
+///
+/// `Random` uses [`std::collections::hash_map::RandomState`], which is seeded randomly and is hence independent of the hash used by the
+/// count-min sketch (`crate::cache::config::Config::key_hash_fn`). This is the default.
+///
+/// `Custom` uses the [`crate::cache::config::ShardHasherFn`] provided by the clients to create a new hasher for every key.
+#[derive(Clone)]
+pub(crate) enum ShardHashBuilder {
+    Random(RandomState),
+    Custom(Arc<ShardHasherFn>),
+}
+
+impl Default for ShardHashBuilder {
+    fn default() -> Self {
+        ShardHashBuilder::Random(RandomState::new())
+    }
+}
+
+impl BuildHasher for ShardHashBuilder {
+    type Hasher = ShardHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        match self {
+            ShardHashBuilder::Random(random_state) => ShardHasher::Random(random_state.build_hasher()),
+            ShardHashBuilder::Custom(shard_hasher_fn) => ShardHasher::Custom(shard_hasher_fn()),
+        }
+    }
+}
+
+/// ShardHasher delegates to either the randomly seeded [`std::collections::hash_map::DefaultHasher`] or the hasher created by the clients.
+/// All the `write_*` methods are delegated so that the hasher created by the clients sees the same calls that it would see otherwise.
+pub(crate) enum ShardHasher {
+    Random(DefaultHasher),
+    Custom(Box<dyn Hasher>),
+}
+
+impl ShardHasher {
+    fn inner(&mut self) -> &mut dyn Hasher {
+        match self {
+            ShardHasher::Random(hasher) => hasher,
+            ShardHasher::Custom(hasher) => hasher.as_mut(),
+        }
+    }
+}
+
+impl Hasher for ShardHasher {
+    fn finish(&self) -> u64 {
+        match self {
+            ShardHasher::Random(hasher) => hasher.finish(),
+            ShardHasher::Custom(hasher) => hasher.finish(),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) { self.inner().write(bytes) }
+
+    fn write_u8(&mut self, i: u8) { self.inner().write_u8(i) }
+
+    fn write_u16(&mut self, i: u16) { self.inner().write_u16(i) }
+
+    fn write_u32(&mut self, i: u32) { self.inner().write_u32(i) }
+
+    fn write_u64(&mut self, i: u64) { self.inner().write_u64(i) }
+
+    fn write_u128(&mut self, i: u128) { self.inner().write_u128(i) }
+
+    fn write_usize(&mut self, i: usize) { self.inner().write_usize(i) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{BuildHasher, Hash, Hasher};
+    use std::sync::Arc;
+
+    use crate::cache::store::shard_hash::ShardHashBuilder;
+
+    #[test]
+    fn random_shard_hash_is_stable_for_a_builder() {
+        let builder = ShardHashBuilder::default();
+        assert_eq!(builder.hash_one("topic"), builder.hash_one("topic"));
+    }
+
+    #[test]
+    fn custom_shard_hash() {
+        let builder = ShardHashBuilder::Custom(Arc::new(|| Box::new(DefaultHasher::new())));
+
+        let mut hasher = DefaultHasher::new();
+        "topic".hash(&mut hasher);
+        assert_eq!(hasher.finish(), builder.hash_one("topic"));
+    }
+}
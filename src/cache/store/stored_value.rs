@@ -6,8 +6,11 @@ use crate::cache::types::{ExpireAfter, KeyId};
 
 /// `StoredValue` wraps the client provided Value and it is stored as a value in the `crate::cache::store::Store`.
 ///
-/// It encapsulates the `value`, `key_id`, the optional expiry of the key.
-/// and a flag to identify whether a key is soft deleted
+/// It encapsulates the `value`, `key_id`, the optional expiry of the key along with the `time_to_live` that the expiry was calculated from,
+/// the optional version of the value (if it was put using `put_versioned`), the time at which the value was inserted,
+/// and a flag to identify whether a key is soft deleted.
+///
+/// `key_id` is generated on every put, and it increases with every put, so it also identifies the insertion order of the keys.
 ///
 /// It is relevant to the clients on the invocation of `get_ref` and `map_get_ref` methods on [`crate::cache::cached::CacheD`].
 /// ```
@@ -42,15 +45,21 @@ pub struct StoredValue<Value> {
     value: Value,
     key_id: KeyId,
     expire_after: Option<ExpireAfter>,
+    time_to_live: Option<Duration>,
+    version: Option<u64>,
+    inserted_at: SystemTime,
     pub(crate) is_soft_deleted: bool,
 }
 
 impl<Value> StoredValue<Value> {
-    pub(crate) fn never_expiring(value: Value, key_id: KeyId) -> Self {
+    pub(crate) fn never_expiring(value: Value, key_id: KeyId, clock: &ClockType) -> Self {
         StoredValue {
             value,
             key_id,
             expire_after: None,
+            time_to_live: None,
+            version: None,
+            inserted_at: clock.now(),
             is_soft_deleted: false,
         }
     }
@@ -60,11 +69,28 @@ impl<Value> StoredValue<Value> {
             value,
             key_id,
             expire_after: Some(Self::calculate_expiry(time_to_live, clock)),
+            time_to_live: Some(time_to_live),
+            version: None,
+            inserted_at: clock.now(),
             is_soft_deleted: false,
         }
     }
 
-    pub(crate) fn is_alive(&self, clock: &ClockType) -> bool {
+    /// Returns a copy of the `StoredValue` (made using `clone_value`) with the `key_id`, keeping the expiry, the version and the insertion time.
+    pub(crate) fn renamed(&self, key_id: KeyId, clone_value: fn(&Value) -> Value) -> Self {
+        StoredValue {
+            value: clone_value(&self.value),
+            key_id,
+            expire_after: self.expire_after,
+            time_to_live: self.time_to_live,
+            version: self.version,
+            inserted_at: self.inserted_at,
+            is_soft_deleted: false,
+        }
+    }
+
+    /// Returns true if the key is neither soft deleted nor expired as per the supplied `clock`.
+    pub fn is_alive(&self, clock: &ClockType) -> bool {
         if self.is_soft_deleted {
             return false;
         }
@@ -85,6 +111,30 @@ impl<Value> StoredValue<Value> {
         /// Some: if the expiry is set
     pub fn expire_after(&self) -> Option<ExpireAfter> { self.expire_after }
 
+    /// Returns the `time_to_live` that the expiry of the key was calculated from, when the key was put or when its `time_to_live` was last updated.
+    pub fn time_to_live(&self) -> Option<Duration> { self.time_to_live }
+
+    /// Returns the time left before the key expires as per the supplied `clock`. It returns:
+        /// None: if the expiry is not set
+        /// Some(Duration::ZERO): if the key has expired
+    pub fn remaining_time_to_live(&self, clock: &ClockType) -> Option<Duration> {
+        self.expire_after.map(|expire_after| expire_after.duration_since(clock.now()).unwrap_or(Duration::ZERO))
+    }
+
+    /// Returns the version of the value, if the value was put using `put_versioned` of [`crate::cache::cached::CacheD`].
+    pub fn version(&self) -> Option<u64> { self.version }
+
+    /// Returns the time at which the value was put, or was last replaced by an update of the value.
+    pub fn inserted_at(&self) -> SystemTime { self.inserted_at }
+
+    /// Returns the time elapsed since the value was put (or was last replaced by an update of the value) as per the supplied `clock`.
+    pub fn age(&self, clock: &ClockType) -> Duration {
+        clock.now().duration_since(self.inserted_at).unwrap_or(Duration::ZERO)
+    }
+
+    /// Returns true if the key is deleted, but is not yet removed from the Store.
+    pub fn is_soft_deleted(&self) -> bool { self.is_soft_deleted }
+
     pub(crate) fn update(&mut self,
                          value: Option<Value>,
                          time_to_live: Option<Duration>,
@@ -93,16 +143,27 @@ impl<Value> StoredValue<Value> {
 
         if remove_time_to_live {
             self.expire_after = None;
+            self.time_to_live = None;
         } else if let Some(time_to_live) = time_to_live {
             self.expire_after = Some(Self::calculate_expiry(time_to_live, clock));
+            self.time_to_live = Some(time_to_live);
         }
 
         if let Some(value) = value {
-            self.value = value
+            self.value = value;
+            self.inserted_at = clock.now();
         }
         self.expire_after
     }
 
+    pub(crate) fn versioned(mut self, version: u64) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Consumes the `StoredValue` and returns the value stored inside Store
+    pub(crate) fn into_value(self) -> Value { self.value }
+
     pub(crate) fn calculate_expiry(time_to_live: Duration, clock: &ClockType) -> SystemTime {
         clock.now().add(time_to_live)
     }
@@ -119,9 +180,9 @@ mod tests {
     use std::ops::Add;
     use std::time::{Duration, SystemTime};
 
-    use crate::cache::clock::{ClockType, SystemClock};
+    use crate::cache::clock::{ClockType, ManualClock, SystemClock};
     use crate::cache::store::stored_value::StoredValue;
-    use crate::cache::store::stored_value::tests::setup::{FutureClock, UnixEpochClock};
+    use crate::cache::store::stored_value::tests::setup::FutureClock;
 
     mod setup {
         use std::ops::Add;
@@ -132,61 +193,107 @@ mod tests {
         #[derive(Clone)]
         pub(crate) struct FutureClock;
 
-        #[derive(Clone)]
-        pub(crate) struct UnixEpochClock;
-
         impl Clock for FutureClock {
             fn now(&self) -> SystemTime {
                 SystemTime::now().add(Duration::from_secs(10))
             }
         }
 
-        impl Clock for UnixEpochClock {
-            fn now(&self) -> SystemTime {
-                SystemTime::UNIX_EPOCH
-            }
-        }
     }
 
     #[test]
     fn value_ref() {
-        let stored_value = StoredValue::never_expiring("microservices", 1);
+        let stored_value = StoredValue::never_expiring("microservices", 1, &SystemClock::boxed());
         let value = stored_value.value_ref();
         assert_eq!(&"microservices", value);
     }
 
     #[test]
     fn value() {
-        let stored_value = StoredValue::never_expiring("microservices", 1);
+        let stored_value = StoredValue::never_expiring("microservices", 1, &SystemClock::boxed());
         let value = stored_value.value();
         assert_eq!("microservices", value);
     }
 
     #[test]
     fn expiration_time() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let stored_value = StoredValue::expiring("SSD", 1, Duration::from_secs(10), &clock);
 
         assert!(stored_value.expire_after.unwrap().eq(&SystemTime::UNIX_EPOCH.add(Duration::from_secs(10))));
     }
 
+    #[test]
+    fn time_to_live() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let stored_value = StoredValue::expiring("SSD", 1, Duration::from_secs(10), &clock);
+
+        assert_eq!(Some(Duration::from_secs(10)), stored_value.time_to_live());
+    }
+
+    #[test]
+    fn time_to_live_of_a_never_expiring_value() {
+        let stored_value = StoredValue::never_expiring("SSD", 1, &SystemClock::boxed());
+
+        assert_eq!(None, stored_value.time_to_live());
+        assert_eq!(None, stored_value.remaining_time_to_live(&SystemClock::boxed()));
+    }
+
+    #[test]
+    fn remaining_time_to_live() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let stored_value = StoredValue::expiring("SSD", 1, Duration::from_secs(10), &clock);
+
+        assert_eq!(Some(Duration::from_secs(10)), stored_value.remaining_time_to_live(&clock));
+    }
+
+    #[test]
+    fn remaining_time_to_live_of_an_expired_value() {
+        let system_clock = SystemClock::boxed();
+        let stored_value = StoredValue::expiring("SSD", 1, Duration::from_secs(5), &system_clock);
+
+        let future_clock: ClockType = Box::new(FutureClock {});
+        assert_eq!(Some(Duration::ZERO), stored_value.remaining_time_to_live(&future_clock));
+    }
+
+    #[test]
+    fn version() {
+        let stored_value = StoredValue::never_expiring("SSD", 1, &SystemClock::boxed()).versioned(3);
+        assert_eq!(Some(3), stored_value.version());
+    }
+
+    #[test]
+    fn version_of_an_unversioned_value() {
+        let stored_value = StoredValue::never_expiring("SSD", 1, &SystemClock::boxed());
+        assert_eq!(None, stored_value.version());
+    }
+
+    #[test]
+    fn is_soft_deleted() {
+        let mut stored_value = StoredValue::never_expiring("storage-engine", 1, &SystemClock::boxed());
+        assert!(!stored_value.is_soft_deleted());
+
+        stored_value.is_soft_deleted = true;
+        assert!(stored_value.is_soft_deleted());
+    }
+
     #[test]
     fn is_alive() {
-        let stored_value = StoredValue::never_expiring("storage-engine", 1);
+        let stored_value = StoredValue::never_expiring("storage-engine", 1, &SystemClock::boxed());
 
         assert!(stored_value.is_alive(&SystemClock::boxed()));
     }
 
     #[test]
     fn is_alive_if_not_soft_deleted() {
-        let stored_value = StoredValue::never_expiring("storage-engine", 1);
+        let stored_value = StoredValue::never_expiring("storage-engine", 1, &SystemClock::boxed());
 
         assert!(stored_value.is_alive(&SystemClock::boxed()));
     }
 
     #[test]
     fn is_not_alive_if_soft_deleted() {
-        let mut stored_value = StoredValue::never_expiring("storage-engine", 1);
+        let mut stored_value = StoredValue::never_expiring("storage-engine", 1, &SystemClock::boxed());
         stored_value.is_soft_deleted = true;
 
         assert!(!stored_value.is_alive(&SystemClock::boxed()));
@@ -201,6 +308,19 @@ mod tests {
         assert!(!stored_value.is_alive(&future_clock));
     }
 
+    #[test]
+    fn is_not_alive_after_a_manual_clock_advances_past_the_expiry() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH);
+        let boxed_clock = clock.boxed();
+        let stored_value = StoredValue::expiring("storage-engine", 1, Duration::from_secs(5), &boxed_clock);
+
+        clock.advance(Duration::from_secs(5));
+        assert!(stored_value.is_alive(&boxed_clock));
+
+        clock.advance(Duration::from_secs(1));
+        assert!(!stored_value.is_alive(&boxed_clock));
+    }
+
     #[test]
     fn is_not_alive_if_clock_has_not_passed_but_is_soft_deleted() {
         let system_clock = SystemClock::boxed();
@@ -217,6 +337,30 @@ mod tests {
 
         stored_value.update(None, None, true, &system_clock);
         assert!(stored_value.expire_after.is_none());
+        assert!(stored_value.time_to_live().is_none());
+    }
+
+    #[test]
+    fn age_of_a_value() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let stored_value = StoredValue::never_expiring("storage-engine", 1, &clock);
+
+        assert_eq!(SystemTime::UNIX_EPOCH, stored_value.inserted_at());
+        assert_eq!(Duration::ZERO, stored_value.age(&clock));
+        assert!(stored_value.age(&SystemClock::boxed()) > Duration::ZERO);
+    }
+
+    #[test]
+    fn update_the_value_resets_the_insertion_time() {
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+        let mut stored_value = StoredValue::never_expiring("storage-engine", 1, &clock);
+
+        stored_value.update(Some("bitcask"), None, false, &SystemClock::boxed());
+        assert!(stored_value.inserted_at() > SystemTime::UNIX_EPOCH);
+
+        let inserted_at = stored_value.inserted_at();
+        stored_value.update(None, Some(Duration::from_secs(300)), false, &clock);
+        assert_eq!(inserted_at, stored_value.inserted_at());
     }
 
     #[test]
@@ -232,18 +376,19 @@ mod tests {
 
     #[test]
     fn update_the_expiry() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let mut stored_value = StoredValue::expiring("storage-engine", 1, Duration::from_secs(5), &clock);
 
         stored_value.update(None, Some(Duration::from_secs(300)), false, &clock);
 
         let expiry_after = stored_value.expire_after.unwrap();
         assert_eq!(clock.now().add(Duration::from_secs(300)), expiry_after);
+        assert_eq!(Some(Duration::from_secs(300)), stored_value.time_to_live());
     }
 
     #[test]
     fn update_value_and_expiry() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let mut stored_value = StoredValue::expiring("storage-engine", 1, Duration::from_secs(5), &clock);
 
         stored_value.update(Some("bitcask"), Some(Duration::from_secs(300)), false, &clock);
@@ -257,7 +402,7 @@ mod tests {
 
     #[test]
     fn update_value_and_remove_expiry() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let mut stored_value = StoredValue::expiring("storage-engine", 1, Duration::from_secs(5), &clock);
 
         stored_value.update(Some("bitcask"), None, true, &clock);
@@ -271,7 +416,7 @@ mod tests {
 
     #[test]
     fn update_value_and_remove_expiry_given_updated_time_to_live_is_also_provided() {
-        let clock: ClockType = Box::new(UnixEpochClock {});
+        let clock: ClockType = Box::new(ManualClock::new(SystemTime::UNIX_EPOCH));
         let mut stored_value = StoredValue::expiring("storage-engine", 1, Duration::from_secs(5), &clock);
 
         stored_value.update(Some("bitcask"), Some(Duration::from_secs(100)), true, &clock);
@@ -0,0 +1,123 @@
+use std::hash::Hash;
+
+use dashmap::DashMap;
+
+use crate::cache::clock::ClockType;
+use crate::cache::types::{ExpireAfter, KeyId};
+
+/// Tombstones maintains the recently deleted keys, along with the key_id and the expiry of their tombstones.
+///
+/// A tombstone is identified by its key_id in `crate::cache::expiration::TTLTicker`, so `Tombstones` also maintains the key against its key_id.
+/// `TTLTicker` removes a tombstone after it expires, the expiry is also checked on every lookup so an expired tombstone never rejects a put.
+pub(crate) struct Tombstones<Key>
+    where Key: Hash + Eq + Clone, {
+    expiry_by_key: DashMap<Key, (KeyId, ExpireAfter)>,
+    key_by_id: DashMap<KeyId, Key>,
+}
+
+impl<Key> Tombstones<Key>
+    where Key: Hash + Eq + Clone, {
+    pub(crate) fn new() -> Self {
+        Tombstones {
+            expiry_by_key: DashMap::new(),
+            key_by_id: DashMap::new(),
+        }
+    }
+
+    /// Adds a tombstone for the key and returns the key_id and the expiry of the tombstone it replaced, if any.
+    pub(crate) fn add(&self, key: Key, key_id: KeyId, expire_after: ExpireAfter) -> Option<(KeyId, ExpireAfter)> {
+        self.key_by_id.insert(key_id, key.clone());
+        let replaced = self.expiry_by_key.insert(key, (key_id, expire_after));
+        if let Some((replaced_key_id, _)) = replaced {
+            self.key_by_id.remove(&replaced_key_id);
+        }
+        replaced
+    }
+
+    /// Returns true if the key has a tombstone that has not expired.
+    pub(crate) fn is_present(&self, key: &Key, clock: &ClockType) -> bool {
+        self.expiry_by_key
+            .get(key)
+            .map(|key_id_expiry| !clock.has_passed(&key_id_expiry.1))
+            .unwrap_or(false)
+    }
+
+    /// Removes the tombstone with the key_id and returns true if the key_id belonged to a tombstone.
+    pub(crate) fn remove(&self, key_id: &KeyId) -> bool {
+        if let Some((_, key)) = self.key_by_id.remove(key_id) {
+            self.expiry_by_key.remove_if(&key, |_, (tombstone_key_id, _)| tombstone_key_id == key_id);
+            return true;
+        }
+        false
+    }
+
+    pub(crate) fn clear(&self) {
+        self.expiry_by_key.clear();
+        self.key_by_id.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Add;
+    use std::time::{Duration, SystemTime};
+
+    use crate::cache::clock::{ClockType, SystemClock};
+    use crate::cache::store::tombstones::Tombstones;
+
+    fn clock() -> ClockType {
+        SystemClock::boxed()
+    }
+
+    #[test]
+    fn tombstone_is_present() {
+        let tombstones = Tombstones::new();
+        tombstones.add("topic", 1, SystemTime::now().add(Duration::from_secs(300)));
+
+        assert!(tombstones.is_present(&"topic", &clock()));
+    }
+
+    #[test]
+    fn tombstone_is_not_present_for_a_key() {
+        let tombstones: Tombstones<&str> = Tombstones::new();
+
+        assert!(!tombstones.is_present(&"topic", &clock()));
+    }
+
+    #[test]
+    fn expired_tombstone_is_not_present() {
+        let tombstones = Tombstones::new();
+        tombstones.add("topic", 1, SystemTime::now().checked_sub(Duration::from_secs(5)).unwrap());
+
+        assert!(!tombstones.is_present(&"topic", &clock()));
+    }
+
+    #[test]
+    fn remove_a_tombstone() {
+        let tombstones = Tombstones::new();
+        tombstones.add("topic", 1, SystemTime::now().add(Duration::from_secs(300)));
+
+        assert!(tombstones.remove(&1));
+        assert!(!tombstones.is_present(&"topic", &clock()));
+    }
+
+    #[test]
+    fn remove_a_non_existing_tombstone() {
+        let tombstones: Tombstones<&str> = Tombstones::new();
+
+        assert!(!tombstones.remove(&1));
+    }
+
+    #[test]
+    fn replace_a_tombstone() {
+        let tombstones = Tombstones::new();
+        let expire_after = SystemTime::now().add(Duration::from_secs(300));
+        tombstones.add("topic", 1, expire_after);
+
+        let replaced = tombstones.add("topic", 2, SystemTime::now().add(Duration::from_secs(600)));
+        assert_eq!(Some((1, expire_after)), replaced);
+
+        assert!(!tombstones.remove(&1));
+        assert!(tombstones.is_present(&"topic", &clock()));
+    }
+}
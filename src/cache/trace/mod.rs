@@ -0,0 +1,149 @@
+use std::fmt::{Display, Formatter};
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use log::warn;
+use parking_lot::Mutex;
+
+use crate::cache::types::KeyHash;
+
+pub mod replay;
+
+/// Defines the operations that are recorded in an access trace.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TraceOperation {
+    Get,
+    Put,
+    Delete,
+}
+
+impl Display for TraceOperation {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceOperation::Get => write!(formatter, "get"),
+            TraceOperation::Put => write!(formatter, "put"),
+            TraceOperation::Delete => write!(formatter, "delete"),
+        }
+    }
+}
+
+impl FromStr for TraceOperation {
+    type Err = ();
+
+    fn from_str(operation: &str) -> Result<Self, Self::Err> {
+        match operation {
+            "get" => Ok(TraceOperation::Get),
+            "put" => Ok(TraceOperation::Put),
+            "delete" => Ok(TraceOperation::Delete),
+            _ => Err(())
+        }
+    }
+}
+
+/// TraceEvent represents a single line of an access trace.
+///
+/// Each line has the format: `<timestamp in micros since UNIX_EPOCH> <operation> <key hash>`. Example: `1680000000000000 get 3018`.
+#[derive(Debug, Eq, PartialEq)]
+pub struct TraceEvent {
+    pub timestamp_micros: u128,
+    pub operation: TraceOperation,
+    pub key_hash: KeyHash,
+}
+
+impl TraceEvent {
+    pub(crate) fn parse(line: &str) -> Option<TraceEvent> {
+        let mut parts = line.split_whitespace();
+        let timestamp_micros = parts.next()?.parse().ok()?;
+        let operation = parts.next()?.parse().ok()?;
+        let key_hash = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(TraceEvent { timestamp_micros, operation, key_hash })
+    }
+}
+
+/// TraceRecorder records each `get`/`put`/`delete` with its key hash and timestamp to the writer provided in [`crate::cache::config::ConfigBuilder::record_trace`].
+///
+/// The writer is wrapped in a [`std::io::BufWriter`], so recording an event does not result in a write to the underlying writer every time.
+/// The buffered events are flushed when the cache is shutdown.
+pub(crate) struct TraceRecorder {
+    writer: Mutex<BufWriter<Box<dyn Write + Send>>>,
+}
+
+impl TraceRecorder {
+    pub(crate) fn new(writer: Box<dyn Write + Send>) -> Self {
+        TraceRecorder {
+            writer: Mutex::new(BufWriter::new(writer)),
+        }
+    }
+
+    pub(crate) fn record(&self, operation: TraceOperation, key_hash: KeyHash, time: SystemTime) {
+        let timestamp_micros = time.duration_since(SystemTime::UNIX_EPOCH).map(|duration| duration.as_micros()).unwrap_or(0);
+        if let Err(err) = writeln!(self.writer.lock(), "{} {} {}", timestamp_micros, operation, key_hash) {
+            warn!("Could not record the trace event {} for key hash {}, error {}", operation, key_hash, err);
+        }
+    }
+
+    pub(crate) fn flush(&self) {
+        if let Err(err) = self.writer.lock().flush() {
+            warn!("Could not flush the trace, error {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+
+    use parking_lot::Mutex;
+
+    use crate::cache::trace::{TraceEvent, TraceOperation, TraceRecorder};
+
+    #[derive(Clone)]
+    pub(crate) struct SharedBuffer {
+        pub(crate) bytes: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.bytes.lock().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn record_events_and_flush() {
+        let buffer = SharedBuffer { bytes: Arc::new(Mutex::new(Vec::new())) };
+        let recorder = TraceRecorder::new(Box::new(buffer.clone()));
+
+        let time = SystemTime::UNIX_EPOCH + Duration::from_micros(10);
+        recorder.record(TraceOperation::Put, 3018, time);
+        recorder.record(TraceOperation::Get, 3018, time);
+        recorder.record(TraceOperation::Delete, 3018, time);
+        assert!(buffer.bytes.lock().is_empty());
+
+        recorder.flush();
+        assert_eq!("10 put 3018\n10 get 3018\n10 delete 3018\n", String::from_utf8(buffer.bytes.lock().clone()).unwrap());
+    }
+
+    #[test]
+    fn parse_trace_event() {
+        let event = TraceEvent::parse("10 get 3018").unwrap();
+        assert_eq!(TraceEvent { timestamp_micros: 10, operation: TraceOperation::Get, key_hash: 3018 }, event);
+    }
+
+    #[test]
+    fn parse_malformed_trace_events() {
+        assert_eq!(None, TraceEvent::parse("10 get"));
+        assert_eq!(None, TraceEvent::parse("10 fetch 3018"));
+        assert_eq!(None, TraceEvent::parse("ten get 3018"));
+        assert_eq!(None, TraceEvent::parse("10 get 3018 3019"));
+    }
+}
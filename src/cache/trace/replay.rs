@@ -0,0 +1,133 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+
+use crate::cache::cached::CacheD;
+use crate::cache::config::Config;
+use crate::cache::stats::StatsSummary;
+use crate::cache::trace::{TraceEvent, TraceOperation};
+use crate::cache::types::KeyHash;
+
+/// `TraceReplayError` is returned by [`replay`] if the trace can not be read or contains a malformed line.
+#[derive(Debug)]
+pub enum TraceReplayError {
+    Io(std::io::Error),
+    MalformedEvent { line_number: usize, line: String },
+}
+
+/// Display implementation for `TraceReplayError`.
+impl Display for TraceReplayError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceReplayError::Io(err) =>
+                write!(formatter, "could not read the trace, error {}", err),
+            TraceReplayError::MalformedEvent { line_number, line } =>
+                write!(formatter, "could not parse the trace event '{}' at line {}", line, line_number),
+        }
+    }
+}
+
+/// Error implementation for `TraceReplayError`.
+impl Error for TraceReplayError {}
+
+/// Replays an access trace recorded using [`crate::cache::config::ConfigBuilder::record_trace`] against a fresh instance of `CacheD` created with the `config`,
+/// and returns the [`crate::cache::stats::StatsSummary`] of the fresh instance (including its hit ratio).
+///
+/// The trace carries key hashes, so the fresh instance uses the key hash as both the key and the value.
+/// The timestamps only define the order of the events, the events are replayed one after the other.
+/// The writes are executed inline, so a `get` that follows a `put` in the trace sees the result of the `put`.
+///
+/// Replaying allows capturing the production access patterns and experimenting with `counters`, `capacity` and `cache_weight` offline.
+/// ```
+/// use tinylfu_cached::cache::config::ConfigBuilder;
+/// use tinylfu_cached::cache::trace::replay::replay;
+///
+/// let trace = "1 put 3018\n2 get 3018\n3 get 3019\n";
+/// let summary = replay(trace.as_bytes(), ConfigBuilder::new(100, 10, 1000).build()).unwrap();
+/// assert_eq!(0.5, summary.hit_ratio);
+/// ```
+pub fn replay<Trace>(trace: Trace, mut config: Config<KeyHash, KeyHash>) -> Result<StatsSummary, TraceReplayError>
+    where Trace: BufRead {
+    config.inline_execution = true;
+    let cached = CacheD::new(config);
+
+    for (index, line) in trace.lines().enumerate() {
+        let line = line.map_err(TraceReplayError::Io)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = TraceEvent::parse(&line)
+            .ok_or(TraceReplayError::MalformedEvent { line_number: index + 1, line: line.clone() })?;
+
+        match event.operation {
+            TraceOperation::Get => { let _ = cached.get(&event.key_hash); }
+            TraceOperation::Put => { let _ = cached.put(event.key_hash, event.key_hash); }
+            TraceOperation::Delete => { let _ = cached.delete(event.key_hash); }
+        }
+    }
+
+    let summary = cached.stats_summary();
+    cached.shutdown();
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+
+    use crate::cache::cached::CacheD;
+    use crate::cache::config::ConfigBuilder;
+    use crate::cache::trace::replay::{replay, TraceReplayError};
+    use crate::cache::trace::tests::SharedBuffer;
+
+    #[test]
+    fn replay_a_recorded_trace_and_reproduce_the_hit_ratio() {
+        let buffer = SharedBuffer { bytes: Arc::new(Mutex::new(Vec::new())) };
+        let cached = CacheD::new(
+            ConfigBuilder::new(100, 10, 1000)
+                .inline_execution()
+                .record_trace(Box::new(buffer.clone()))
+                .build()
+        );
+
+        let _ = cached.put("topic", "microservices");
+        let _ = cached.put("disk", "SSD");
+        let _ = cached.get(&"topic");
+        let _ = cached.get(&"topic");
+        let _ = cached.get(&"non-existing");
+        let _ = cached.delete("disk");
+        let _ = cached.get(&"disk");
+        let _ = cached.get(&"topic");
+
+        let hit_ratio = cached.stats_summary().hit_ratio;
+        cached.shutdown();
+
+        let trace = buffer.bytes.lock().clone();
+        assert_eq!(8, String::from_utf8(trace.clone()).unwrap().lines().count());
+
+        let summary = replay(trace.as_slice(), ConfigBuilder::new(100, 10, 1000).build()).unwrap();
+        assert_eq!(0.6, hit_ratio);
+        assert_eq!(hit_ratio, summary.hit_ratio);
+    }
+
+    #[test]
+    fn replay_a_malformed_trace() {
+        let result = replay("1 put 3018\n2 fetch 3018\n".as_bytes(), ConfigBuilder::new(100, 10, 1000).build());
+
+        match result {
+            Err(TraceReplayError::MalformedEvent { line_number, line }) => {
+                assert_eq!(2, line_number);
+                assert_eq!("2 fetch 3018", line);
+            }
+            _ => panic!("expected a malformed event")
+        }
+    }
+
+    #[test]
+    fn malformed_event_display() {
+        let error = TraceReplayError::MalformedEvent { line_number: 2, line: "2 fetch 3018".to_string() };
+        assert_eq!("could not parse the trace event '2 fetch 3018' at line 2", format!("{}", error));
+    }
+}
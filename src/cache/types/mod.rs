@@ -23,7 +23,7 @@ pub type Weight = i64;
 pub type FrequencyEstimate = u8;
 
 /// Defines the type for the id of each key.
-pub(crate) type KeyId = u64;
+pub type KeyId = u64;
 
 /// Defines the type expiry of a key.
 pub(crate) type ExpireAfter = SystemTime;